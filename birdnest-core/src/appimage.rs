@@ -0,0 +1,605 @@
+//! AppImages: applications shipped as a single executable file, kept in
+//! `~/Applications` and any directories the config adds.
+//!
+//! An AppImage is an ELF runtime with a squashfs image appended. Nothing
+//! installs it, so BirdNest "integrates" one by copying its embedded desktop
+//! entry and icon into `~/.local/share`, pointed at the file. Updates follow
+//! the update information the runtime carries in its `.upd_info` section:
+//! `appimageupdatetool` does delta updates when it is installed, otherwise
+//! the zsync metadata it names is compared and the whole file downloaded.
+
+use anyhow::{Context, Result};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::config::Config;
+use crate::utils::{find_in_path, run_command, CommandBuilder};
+
+/// Desktop entries and icons BirdNest writes start with this
+pub const ENTRY_PREFIX: &str = "birdnest-appimage-";
+
+/// Where a newer build is published, from the runtime's `.upd_info` section
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UpdateInfo {
+    /// `zsync|<url>`: a fixed URL for the newest build's .zsync file
+    Zsync { url: String },
+    /// `gh-releases-zsync|<owner>|<repo>|<tag>|<file pattern>`; the tag is
+    /// usually "latest"
+    GithubReleases { owner: String, repo: String, tag: String, pattern: String },
+    /// Another transport, e.g. `pling-v1-zsync`; only AppImageUpdate
+    /// understands it
+    Other(String),
+}
+
+impl UpdateInfo {
+    pub fn parse(text: &str) -> Option<Self> {
+        let fields: Vec<&str> = text.trim().split('|').collect();
+        match fields.as_slice() {
+            [""] => None,
+            ["zsync", url] => Some(UpdateInfo::Zsync { url: url.to_string() }),
+            ["gh-releases-zsync", owner, repo, tag, pattern] => Some(UpdateInfo::GithubReleases {
+                owner: owner.to_string(),
+                repo: repo.to_string(),
+                tag: tag.to_string(),
+                pattern: pattern.to_string(),
+            }),
+            _ => Some(UpdateInfo::Other(text.trim().to_string())),
+        }
+    }
+
+    /// zsync or GitHub releases, for the list's update column
+    pub fn transport(&self) -> &str {
+        match self {
+            UpdateInfo::Zsync { .. } => "zsync",
+            UpdateInfo::GithubReleases { .. } => "GitHub releases",
+            UpdateInfo::Other(text) => text.split('|').next().unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppImage {
+    pub path: PathBuf,
+    /// The integrated entry's Name, or one read from the file name
+    pub name: String,
+    pub version: Option<String>,
+    pub size: u64,
+    pub update_info: Option<UpdateInfo>,
+    /// The desktop entry BirdNest wrote for it, when it is integrated
+    pub desktop_entry: Option<PathBuf>,
+}
+
+impl AppImage {
+    pub fn is_integrated(&self) -> bool {
+        self.desktop_entry.is_some()
+    }
+}
+
+/// What checking an AppImage for updates found
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UpdateStatus {
+    UpToDate,
+    /// A newer build, with its version when the file name gives one
+    Available(Option<String>),
+}
+
+/// Whether a file starts like an AppImage: an ELF header with the "AI"
+/// magic and type 1 or 2 in the padding after it
+pub fn is_appimage(header: &[u8]) -> bool {
+    header.len() >= 11 && header.starts_with(b"\x7fELF") && &header[8..10] == b"AI" && matches!(header[10], 1 | 2)
+}
+
+const ARCHES: &[&str] = &["x86_64", "x86-64", "amd64", "x64", "aarch64", "arm64", "armhf", "i386", "i686"];
+
+/// Name and version from a file name like `MuseScore-Studio-4.3.0-x86_64`
+pub fn parse_file_name(stem: &str) -> (String, Option<String>) {
+    // Arches go first since x86_64 has a separator of its own
+    let mut stem = stem;
+    while let Some(rest) = ARCHES.iter().find_map(|arch| {
+        let rest = stem.get(..stem.len().checked_sub(arch.len() + 1)?)?;
+        let suffix = &stem[rest.len()..];
+        (suffix[1..].eq_ignore_ascii_case(arch) && suffix.starts_with(['-', '_']) && !rest.is_empty()).then_some(rest)
+    }) {
+        stem = rest;
+    }
+    let words: Vec<&str> = stem.split(['-', '_']).filter(|w| !w.is_empty()).collect();
+    let starts_version = |word: &&str| word.trim_start_matches(['v', 'V']).starts_with(|c: char| c.is_ascii_digit());
+    match words.iter().skip(1).position(starts_version) {
+        Some(index) => (words[..=index].join(" "), Some(words[index + 1..].join("-").trim_start_matches(['v', 'V']).to_string())),
+        None => (words.join(" "), None),
+    }
+}
+
+// The ELF fields read here are little-endian; there are no big-endian AppImages
+fn field(bytes: &[u8], offset: usize, width: usize) -> Option<u64> {
+    let bytes = bytes.get(offset..offset + width)?;
+    Some(bytes.iter().rev().fold(0, |value, byte| (value << 8) | u64::from(*byte)))
+}
+
+fn read_at<R: Read + Seek>(file: &mut R, offset: u64, len: u64) -> std::io::Result<Vec<u8>> {
+    file.seek(SeekFrom::Start(offset))?;
+    let mut bytes = Vec::new();
+    file.take(len).read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// The update information in an AppImage runtime's `.upd_info` section,
+/// read without running it; None when the section is missing or empty
+pub fn read_update_info<R: Read + Seek>(file: &mut R) -> Result<Option<String>> {
+    let header = read_at(file, 0, 64)?;
+    if !header.starts_with(b"\x7fELF") || header.get(5) != Some(&1) {
+        return Ok(None);
+    }
+    let wide = header.get(4) == Some(&2);
+    // Section table offset, entry size, count and the names section's index
+    let layout = if wide { (0x28, 8, 0x3A, 0x3C, 0x3E) } else { (0x20, 4, 0x2E, 0x30, 0x32) };
+    let (Some(table), Some(entry_size), Some(count), Some(names_index)) =
+        (field(&header, layout.0, layout.1), field(&header, layout.2, 2), field(&header, layout.3, 2), field(&header, layout.4, 2))
+    else {
+        return Ok(None);
+    };
+    // A runtime has a few dozen sections; anything wildly bigger isn't one
+    if count == 0 || count > 512 || names_index >= count {
+        return Ok(None);
+    }
+    let sections = read_at(file, table, entry_size * count)?;
+    // Each section's name offset, and where its contents are
+    let section = |index: u64| -> Option<(u64, u64, u64)> {
+        let base = (index * entry_size) as usize;
+        let (offset, size) = if wide { (field(&sections, base + 0x18, 8)?, field(&sections, base + 0x20, 8)?) } else { (field(&sections, base + 0x10, 4)?, field(&sections, base + 0x14, 4)?) };
+        Some((field(&sections, base, 4)?, offset, size))
+    };
+    let Some((_, names_offset, names_size)) = section(names_index) else {
+        return Ok(None);
+    };
+    let names = read_at(file, names_offset, names_size.min(64 * 1024))?;
+    for index in 0..count {
+        let Some((name, offset, size)) = section(index) else {
+            continue;
+        };
+        let name = names.get(name as usize..).unwrap_or_default();
+        if name.split(|b| *b == 0).next() != Some(b".upd_info") {
+            continue;
+        }
+        let contents = read_at(file, offset, size.min(4096))?;
+        let text = String::from_utf8_lossy(contents.split(|b| *b == 0).next().unwrap_or_default()).trim().to_string();
+        return Ok(Some(text).filter(|text| !text.is_empty()));
+    }
+    Ok(None)
+}
+
+/// `~/Applications` followed by the config's `appimage_dirs`, `~/`
+/// expanded
+pub fn directories(config: &Config) -> Vec<PathBuf> {
+    let home = std::env::var_os("HOME").map(PathBuf::from).unwrap_or_default();
+    let mut dirs = vec![home.join("Applications")];
+    for dir in &config.appimage_dirs {
+        let dir = match dir.strip_prefix("~") {
+            Ok(rest) => home.join(rest),
+            Err(_) => dir.clone(),
+        };
+        if !dirs.contains(&dir) {
+            dirs.push(dir);
+        }
+    }
+    dirs
+}
+
+fn applications_dir() -> Option<PathBuf> {
+    Some(PathBuf::from(std::env::var_os("HOME")?).join(".local/share/applications"))
+}
+
+fn icons_dir() -> Option<PathBuf> {
+    Some(PathBuf::from(std::env::var_os("HOME")?).join(".local/share/icons"))
+}
+
+/// The name integration files get: the prefix and the AppImage's file name,
+/// lowercased, with anything but letters, digits, dots and dashes replaced
+pub fn entry_id(path: &Path) -> String {
+    let stem = path.file_stem().map(|stem| stem.to_string_lossy().to_lowercase()).unwrap_or_default();
+    let stem: String = stem.chars().map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '-' }).collect();
+    format!("{}{}", ENTRY_PREFIX, stem)
+}
+
+fn desktop_value<'a>(text: &'a str, key: &str) -> Option<&'a str> {
+    let mut in_entry = false;
+    for line in text.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_entry = line == "[Desktop Entry]";
+        } else if in_entry {
+            if let Some(value) = line.strip_prefix(key).and_then(|rest| rest.strip_prefix('=')) {
+                return Some(value.trim());
+            }
+        }
+    }
+    None
+}
+
+/// The AppImages directly inside `dirs`, by name, with integration looked
+/// up in `applications`
+pub fn scan_in(dirs: &[PathBuf], applications: &Path) -> Vec<AppImage> {
+    let mut found = Vec::new();
+    for dir in dirs {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            continue;
+        };
+        for path in entries.flatten().map(|entry| entry.path()) {
+            match read_appimage(&path, applications) {
+                Ok(Some(appimage)) => found.push(appimage),
+                Ok(None) => {}
+                Err(e) => tracing::debug!("appimage: skipping {}: {}", path.display(), e),
+            }
+        }
+    }
+    found.sort_by(|a: &AppImage, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()).then_with(|| a.path.cmp(&b.path)));
+    found
+}
+
+fn read_appimage(path: &Path, applications: &Path) -> Result<Option<AppImage>> {
+    let meta = std::fs::metadata(path)?;
+    if !meta.is_file() {
+        return Ok(None);
+    }
+    let mut file = std::fs::File::open(path)?;
+    let mut header = [0u8; 16];
+    if file.read_exact(&mut header).is_err() || !is_appimage(&header) {
+        return Ok(None);
+    }
+    let update_info = read_update_info(&mut file)?.and_then(|text| UpdateInfo::parse(&text));
+    let stem = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+    let (mut name, mut version) = parse_file_name(&stem);
+    let entry_path = applications.join(format!("{}.desktop", entry_id(path)));
+    let desktop_entry = match std::fs::read_to_string(&entry_path) {
+        Ok(text) => {
+            if let Some(entry_name) = desktop_value(&text, "Name") {
+                name = entry_name.to_string();
+            }
+            if let Some(entry_version) = desktop_value(&text, "X-AppImage-Version") {
+                version = Some(entry_version.to_string());
+            }
+            Some(entry_path)
+        }
+        Err(_) => None,
+    };
+    Ok(Some(AppImage { path: path.to_path_buf(), name, version, size: meta.len(), update_info, desktop_entry }))
+}
+
+/// The AppImages in `dirs`, usually [`directories`]
+pub fn scan(dirs: &[PathBuf]) -> Vec<AppImage> {
+    match applications_dir() {
+        Some(applications) => scan_in(dirs, &applications),
+        None => Vec::new(),
+    }
+}
+
+fn quote(path: &Path) -> String {
+    let text = path.display().to_string();
+    if text.contains(|c: char| c.is_whitespace() || c == '"') {
+        format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        text
+    }
+}
+
+/// The desktop entry an AppImage embeds, rewritten to run `appimage` and
+/// show `icon`: every Exec keeps its arguments but runs the file, and
+/// TryExec hides the entry once the file is gone
+pub fn integrated_entry(embedded: &str, appimage: &Path, icon: Option<&Path>) -> String {
+    let program = quote(appimage);
+    let mut text = format!("# Added by BirdNest for {}\n", appimage.display());
+    let mut in_entry = false;
+    for line in embedded.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            in_entry = trimmed == "[Desktop Entry]";
+            text.push_str(trimmed);
+            text.push('\n');
+            if in_entry {
+                text.push_str(&format!("TryExec={}\n", program));
+            }
+            continue;
+        }
+        if let Some(exec) = trimmed.strip_prefix("Exec=") {
+            let args = exec.trim().split_once(char::is_whitespace).map(|(_, args)| args.trim());
+            match args.filter(|args| !args.is_empty()) {
+                Some(args) => text.push_str(&format!("Exec={} {}\n", program, args)),
+                None => text.push_str(&format!("Exec={}\n", program)),
+            }
+        } else if trimmed.starts_with("TryExec=") {
+            continue;
+        } else if let (true, Some(icon), true) = (in_entry, icon, trimmed.starts_with("Icon=")) {
+            text.push_str(&format!("Icon={}\n", icon.display()));
+        } else {
+            text.push_str(line);
+            text.push('\n');
+        }
+    }
+    text
+}
+
+// Pull files matching `pattern` out of the image into `dir`/squashfs-root
+fn extract(appimage: &Path, dir: &Path, pattern: &str) -> Result<()> {
+    let output = Command::new(appimage)
+        .args(["--appimage-extract", pattern])
+        .current_dir(dir)
+        .env("APPIMAGE_EXTRACT_AND_RUN", "0")
+        .output()
+        .with_context(|| format!("Couldn't run {}", appimage.display()))?;
+    if !output.status.success() {
+        anyhow::bail!("Extracting {} failed: {}", pattern, String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(())
+}
+
+// .DirIcon is usually a link into usr/share/icons, which has to come out too
+fn extract_icon(appimage: &Path, dir: &Path) -> Option<PathBuf> {
+    let root = dir.join("squashfs-root");
+    let mut icon = PathBuf::from(".DirIcon");
+    for _ in 0..3 {
+        extract(appimage, dir, &icon.display().to_string()).ok()?;
+        let path = root.join(&icon);
+        match std::fs::read_link(&path) {
+            Ok(target) => icon = path.parent()?.join(target).strip_prefix(&root).ok()?.to_path_buf(),
+            Err(_) => return path.is_file().then_some(path),
+        }
+    }
+    None
+}
+
+fn refresh_menu(applications: &Path) {
+    if let Err(e) = run_command("update-desktop-database", &[&applications.display().to_string()], false) {
+        tracing::debug!("appimage: update-desktop-database failed: {}", e);
+    }
+}
+
+/// Add an AppImage to the application menu with its own desktop entry and
+/// icon; returns the entry written
+pub fn integrate(appimage: &AppImage) -> Result<PathBuf> {
+    let (Some(applications), Some(icons)) = (applications_dir(), icons_dir()) else {
+        anyhow::bail!("HOME is not set");
+    };
+    let id = entry_id(&appimage.path);
+    let work = std::env::temp_dir().join(format!("{}{}", id, std::process::id()));
+    std::fs::create_dir_all(&work)?;
+    let result = (|| -> Result<PathBuf> {
+        extract(&appimage.path, &work, "*.desktop")?;
+        let root = work.join("squashfs-root");
+        let embedded = std::fs::read_dir(&root)?
+            .flatten()
+            .map(|entry| entry.path())
+            .find(|path| path.extension().is_some_and(|ext| ext == "desktop"))
+            .with_context(|| format!("{} has no desktop entry", appimage.name))?;
+        let embedded = std::fs::read_to_string(embedded)?;
+
+        let icon = extract_icon(&appimage.path, &work).and_then(|source| {
+            let bytes = std::fs::read(&source).ok()?;
+            let extension = if bytes.starts_with(b"\x89PNG") { "png" } else { "svg" };
+            let target = icons.join(format!("{}.{}", id, extension));
+            std::fs::create_dir_all(&icons).ok()?;
+            std::fs::write(&target, bytes).ok()?;
+            Some(target)
+        });
+        std::fs::create_dir_all(&applications)?;
+        let entry = applications.join(format!("{}.desktop", id));
+        std::fs::write(&entry, integrated_entry(&embedded, &appimage.path, icon.as_deref()))?;
+        Ok(entry)
+    })();
+    let _ = std::fs::remove_dir_all(&work);
+    let entry = result?;
+    refresh_menu(&applications);
+    Ok(entry)
+}
+
+// Remove the desktop entry and icons integration wrote
+fn remove_integration(path: &Path) -> Result<()> {
+    let id = entry_id(path);
+    if let Some(applications) = applications_dir() {
+        let entry = applications.join(format!("{}.desktop", id));
+        if entry.exists() {
+            std::fs::remove_file(&entry)?;
+            refresh_menu(&applications);
+        }
+    }
+    if let Some(icons) = icons_dir() {
+        for extension in ["png", "svg"] {
+            let _ = std::fs::remove_file(icons.join(format!("{}.{}", id, extension)));
+        }
+    }
+    Ok(())
+}
+
+/// Take an AppImage out of the application menu, keeping the file
+pub fn unintegrate(appimage: &AppImage) -> Result<()> {
+    remove_integration(&appimage.path)
+}
+
+/// Delete an AppImage along with its menu entry and icon
+pub fn remove(appimage: &AppImage) -> Result<()> {
+    remove_integration(&appimage.path)?;
+    std::fs::remove_file(&appimage.path).with_context(|| format!("Couldn't delete {}", appimage.path.display()))
+}
+
+/// The header fields of a .zsync file that matter for an update
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ZsyncHeader {
+    /// The new build's file name
+    pub filename: Option<String>,
+    /// Where to download it, relative to the .zsync file
+    pub url: Option<String>,
+    pub sha1: Option<String>,
+    pub length: Option<u64>,
+}
+
+/// Parse the text header of a .zsync file, which ends at the first blank
+/// line before the binary checksums
+pub fn parse_zsync_header(bytes: &[u8]) -> ZsyncHeader {
+    let mut header = ZsyncHeader::default();
+    let end = bytes.windows(2).position(|pair| pair == b"\n\n").unwrap_or(bytes.len());
+    for line in String::from_utf8_lossy(&bytes[..end]).lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim().to_string();
+        match key.trim() {
+            "Filename" => header.filename = Some(value),
+            "URL" => header.url = Some(value),
+            "SHA-1" => header.sha1 = Some(value.to_lowercase()),
+            "Length" => header.length = value.parse().ok(),
+            _ => {}
+        }
+    }
+    header
+}
+
+/// Whether the build `header` describes differs from a local file with
+/// checksum `local_sha1`
+pub fn compare(header: &ZsyncHeader, local_sha1: &str) -> UpdateStatus {
+    match &header.sha1 {
+        Some(sha1) if !sha1.eq_ignore_ascii_case(local_sha1.trim()) => {
+            let version = header.filename.as_deref().and_then(|name| parse_file_name(name.trim_end_matches(".AppImage")).1);
+            UpdateStatus::Available(version)
+        }
+        _ => UpdateStatus::UpToDate,
+    }
+}
+
+/// Whether `name` matches a gh-releases-zsync file pattern, where `*`
+/// stands for any run of characters
+pub fn matches_pattern(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    for (index, part) in parts.iter().enumerate() {
+        if index == parts.len() - 1 {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    rest.is_empty()
+}
+
+/// The download URL of the asset matching `pattern` in a GitHub release
+/// from the REST API
+pub fn github_asset(release_json: &str, pattern: &str) -> Option<String> {
+    let release: serde_json::Value = serde_json::from_str(release_json).ok()?;
+    release["assets"].as_array()?.iter().find_map(|asset| {
+        let name = asset["name"].as_str()?;
+        matches_pattern(pattern, name).then(|| asset["browser_download_url"].as_str().map(str::to_string)).flatten()
+    })
+}
+
+fn curl(url: &str, target: Option<&Path>) -> Result<Vec<u8>> {
+    // Whole AppImages take longer than metadata
+    let timeout = if target.is_some() { "3600" } else { "20" };
+    let (mut command, _) = CommandBuilder::new("curl").args(&["-fsSL", "--max-time", timeout, url]).build()?;
+    if let Some(target) = target {
+        command.arg("-o").arg(target);
+    }
+    let output = command.output().context("curl is not installed")?;
+    if !output.status.success() {
+        anyhow::bail!("Downloading {} failed: {}", url, String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(output.stdout)
+}
+
+/// The URL of the .zsync file for the newest build
+pub fn zsync_url(info: &UpdateInfo) -> Result<String> {
+    match info {
+        UpdateInfo::Zsync { url } => Ok(url.clone()),
+        UpdateInfo::GithubReleases { owner, repo, tag, pattern } => {
+            let release = match tag.as_str() {
+                "latest" => format!("https://api.github.com/repos/{}/{}/releases/latest", owner, repo),
+                tag => format!("https://api.github.com/repos/{}/{}/releases/tags/{}", owner, repo, tag),
+            };
+            let json = String::from_utf8_lossy(&curl(&release, None)?).into_owned();
+            github_asset(&json, pattern).with_context(|| format!("{}/{} has no release file matching {}", owner, repo, pattern))
+        }
+        UpdateInfo::Other(_) => anyhow::bail!("Install AppImageUpdate to update AppImages published through {}", info.transport()),
+    }
+}
+
+// A relative URL in a zsync header is relative to the .zsync file
+fn resolve(base: &str, url: &str) -> String {
+    if url.contains("://") {
+        return url.to_string();
+    }
+    match base.rsplit_once('/') {
+        Some((dir, _)) => format!("{}/{}", dir, url),
+        None => url.to_string(),
+    }
+}
+
+fn sha1(path: &Path) -> Result<String> {
+    let output = run_command("sha1sum", &[&path.display().to_string()], false)?;
+    Ok(output.split_whitespace().next().unwrap_or_default().to_string())
+}
+
+fn update_tool() -> Option<PathBuf> {
+    find_in_path("appimageupdatetool").or_else(|| find_in_path("AppImageUpdate"))
+}
+
+/// Ask AppImageUpdate, or failing that the zsync metadata, whether a newer
+/// build is out
+pub fn check_update(appimage: &AppImage) -> Result<UpdateStatus> {
+    let Some(info) = &appimage.update_info else {
+        anyhow::bail!("{} doesn't say where its updates come from", appimage.name);
+    };
+    if let Some(tool) = update_tool() {
+        // --check-for-update exits 1 when there is one and 0 when there isn't
+        let path = appimage.path.display().to_string();
+        let (mut command, _) = CommandBuilder::new(&tool.display().to_string()).args(&["--check-for-update", &path]).build()?;
+        let status = command.output()?.status;
+        return match status.code() {
+            Some(0) => Ok(UpdateStatus::UpToDate),
+            Some(1) => Ok(UpdateStatus::Available(None)),
+            code => anyhow::bail!("{} exited with code {}", tool.display(), code.unwrap_or(-1)),
+        };
+    }
+    let header = parse_zsync_header(&curl(&zsync_url(info)?, None)?);
+    Ok(compare(&header, &sha1(&appimage.path)?))
+}
+
+/// Replace an AppImage with the newest build, in place so its menu entry
+/// keeps working
+pub fn update(appimage: &AppImage) -> Result<()> {
+    let Some(info) = &appimage.update_info else {
+        anyhow::bail!("{} doesn't say where its updates come from", appimage.name);
+    };
+    if let Some(tool) = update_tool() {
+        run_command(&tool.display().to_string(), &["--overwrite", &appimage.path.display().to_string()], false)?;
+        return Ok(());
+    }
+    let zsync = zsync_url(info)?;
+    let header = parse_zsync_header(&curl(&zsync, None)?);
+    let url = header
+        .url
+        .as_deref()
+        .or(header.filename.as_deref())
+        .map(|url| resolve(&zsync, url))
+        .context("The update metadata doesn't say where the new build is")?;
+    let part = appimage.path.with_extension("AppImage.part");
+    let result = (|| -> Result<()> {
+        curl(&url, Some(&part))?;
+        if let Some(expected) = &header.sha1 {
+            if !sha1(&part)?.eq_ignore_ascii_case(expected) {
+                anyhow::bail!("The download from {} doesn't match its checksum", url);
+            }
+        }
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&part, std::fs::Permissions::from_mode(0o755))?;
+        std::fs::rename(&part, &appimage.path)?;
+        Ok(())
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&part);
+    }
+    result
+}
@@ -0,0 +1,434 @@
+//! AppStream metadata: the display names, icons, screenshots, categories and
+//! release notes distributions and Flatpak remotes publish for their
+//! applications.
+//!
+//! apt reads DEP-11 YAML catalogs from `/var/lib/swcatalog/yaml` (or the
+//! older `/var/lib/app-info/yaml`); Fedora, Arch and every Flatpak remote
+//! ship AppStream XML instead. Both are read into one [`Catalog`], looked up
+//! by package name for the Search tab and by application ID for Flatpaks.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::utils::utc_date;
+
+// Cached icons come in several sizes; this one fits a result row
+const ICON_SIZE: &str = "64x64";
+// Screenshot thumbnails up to this wide fit the details pane
+const SCREENSHOT_WIDTH: u32 = 752;
+
+// Where distributions keep their catalogs, newest layout first
+const SYSTEM_CATALOGS: &[&str] = &["/var/lib/swcatalog", "/usr/share/swcatalog", "/var/lib/app-info", "/usr/share/app-info"];
+
+/// One application from a catalog
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Component {
+    /// The AppStream ID, which for Flatpaks is the application ID
+    pub id: String,
+    /// The package providing it; None for Flatpak remotes
+    pub package: Option<String>,
+    pub name: String,
+    pub summary: String,
+    /// A cached icon on disk, if the catalog shipped one
+    pub icon: Option<PathBuf>,
+    /// Screenshot URLs, the default one first
+    pub screenshots: Vec<String>,
+    pub categories: Vec<String>,
+    /// Release notes from the XML catalogs, newest first
+    pub releases: Vec<Release>,
+}
+
+/// One `<release>` of a component
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Release {
+    pub version: String,
+    /// YYYY-MM-DD
+    pub date: Option<String>,
+    /// low, medium, high or critical, when the catalog says
+    pub urgency: Option<String>,
+    /// The description's paragraphs and list items, in order
+    pub notes: Vec<String>,
+}
+
+/// Components from every catalog found, indexed for lookup
+#[derive(Debug, Default)]
+pub struct Catalog {
+    components: Vec<Component>,
+    by_package: HashMap<String, usize>,
+    by_id: HashMap<String, usize>,
+}
+
+impl Catalog {
+    pub fn new(components: Vec<Component>) -> Self {
+        let mut catalog = Self::default();
+        catalog.extend(components);
+        catalog
+    }
+
+    fn extend(&mut self, components: Vec<Component>) {
+        for component in components {
+            let index = self.components.len();
+            // The first catalog to describe a package wins, as they are read
+            // in order of preference
+            if let Some(package) = &component.package {
+                self.by_package.entry(package.clone()).or_insert(index);
+            }
+            self.by_id.entry(bare_id(&component.id).to_string()).or_insert(index);
+            self.components.push(component);
+        }
+    }
+
+    /// The distribution's catalogs, for packages from apt, dnf or pacman
+    pub fn load_system() -> Self {
+        let mut catalog = Self::default();
+        for root in SYSTEM_CATALOGS.iter().map(Path::new) {
+            for file in catalog_files(&root.join("yaml")) {
+                match read_catalog(&file).and_then(|yaml| parse_dep11(&yaml, &root.join("icons"))) {
+                    Ok(components) => catalog.extend(components),
+                    Err(e) => tracing::debug!("appstream: Skipping {}: {}", file.display(), e),
+                }
+            }
+            for dir in ["xml", "xmls"] {
+                for file in catalog_files(&root.join(dir)) {
+                    let icons = root.join("icons");
+                    match read_catalog(&file).and_then(|xml| parse_collection(&xml, |origin| icons.join(origin))) {
+                        Ok(components) => catalog.extend(components),
+                        Err(e) => tracing::debug!("appstream: Skipping {}: {}", file.display(), e),
+                    }
+                }
+            }
+        }
+        tracing::debug!("appstream: {} system components", catalog.len());
+        catalog
+    }
+
+    /// Every configured Flatpak remote's catalog, system-wide and per-user
+    pub fn load_flatpak() -> Self {
+        let mut installations = vec![PathBuf::from("/var/lib/flatpak")];
+        if let Ok(home) = std::env::var("HOME") {
+            installations.push(PathBuf::from(home).join(".local/share/flatpak"));
+        }
+
+        let mut catalog = Self::default();
+        // appstream/<remote>/<arch>/active holds the remote's current catalog
+        for arch_dir in installations.iter().flat_map(|i| subdirs(&i.join("appstream"))).flat_map(|r| subdirs(&r)) {
+            let active = arch_dir.join("active");
+            let Some(file) = ["appstream.xml.gz", "appstream.xml"].iter().map(|f| active.join(f)).find(|f| f.exists()) else {
+                continue;
+            };
+            let icons = active.join("icons");
+            match read_catalog(&file).and_then(|xml| parse_collection(&xml, |_| icons.clone())) {
+                Ok(components) => catalog.extend(components),
+                Err(e) => tracing::debug!("appstream: Skipping {}: {}", file.display(), e),
+            }
+        }
+        tracing::debug!("appstream: {} Flatpak components", catalog.len());
+        catalog
+    }
+
+    pub fn for_package(&self, package: &str) -> Option<&Component> {
+        self.by_package.get(package).map(|&i| &self.components[i])
+    }
+
+    /// Looks up an application ID, with or without the `.desktop` some
+    /// catalogs append
+    pub fn for_app(&self, id: &str) -> Option<&Component> {
+        self.by_id.get(bare_id(id)).map(|&i| &self.components[i])
+    }
+
+    /// Components filed under any of `categories`, by name, each ID once
+    pub fn in_categories(&self, categories: &[&str]) -> Vec<&Component> {
+        let mut found: Vec<&Component> = self
+            .components
+            .iter()
+            .enumerate()
+            // Later catalogs' copies of an ID lose to the first, as in lookups
+            .filter(|(index, component)| self.by_id.get(bare_id(&component.id)) == Some(index))
+            .map(|(_, component)| component)
+            .filter(|component| component.categories.iter().any(|c| categories.contains(&c.as_str())))
+            .collect();
+        found.sort_by_cached_key(|component| component.name.to_lowercase());
+        found
+    }
+
+    /// Packages whose name or app name starts with `prefix`, ignoring
+    /// case, each once: typing "Text" offers gedit
+    pub fn packages_starting_with(&self, prefix: &str) -> Vec<&str> {
+        let prefix = prefix.trim().to_lowercase();
+        if prefix.is_empty() {
+            return Vec::new();
+        }
+        let mut packages: Vec<&str> = self
+            .components
+            .iter()
+            .filter_map(|component| Some((component.package.as_deref()?, component)))
+            .filter(|(package, component)| {
+                package.to_lowercase().starts_with(&prefix) || component.name.to_lowercase().starts_with(&prefix)
+            })
+            .map(|(package, _)| package)
+            .collect();
+        packages.sort_unstable();
+        packages.dedup();
+        packages
+    }
+
+    pub fn len(&self) -> usize {
+        self.components.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.components.is_empty()
+    }
+}
+
+fn bare_id(id: &str) -> &str {
+    id.strip_suffix(".desktop").unwrap_or(id)
+}
+
+fn subdirs(dir: &Path) -> Vec<PathBuf> {
+    fs::read_dir(dir)
+        .map(|entries| entries.flatten().map(|e| e.path()).filter(|p| p.is_dir()).collect())
+        .unwrap_or_default()
+}
+
+fn catalog_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|entries| entries.flatten().map(|e| e.path()).filter(|p| p.is_file()).collect())
+        .unwrap_or_default();
+    files.sort();
+    files
+}
+
+// Catalogs are usually gzipped; gzip is on every system that has one
+fn read_catalog(path: &Path) -> Result<String> {
+    if path.extension().is_some_and(|ext| ext == "gz") {
+        let output = Command::new("gzip").arg("-dc").arg(path).output().context("Failed to run gzip")?;
+        if !output.status.success() {
+            anyhow::bail!("gzip failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))
+    }
+}
+
+/// Parse an AppStream XML collection. Cached icons live under
+/// `icons(origin)/64x64`, `origin` being the collection's origin attribute.
+pub fn parse_collection(xml: &str, icons: impl Fn(&str) -> PathBuf) -> Result<Vec<Component>> {
+    let document = roxmltree::Document::parse(xml).context("Invalid AppStream XML")?;
+    let root = document.root_element();
+    let icon_dir = icons(root.attribute("origin").unwrap_or_default()).join(ICON_SIZE);
+
+    let components = root
+        .children()
+        .filter(|node| node.has_tag_name("component"))
+        .filter_map(|node| {
+            let id = child_text(node, "id")?;
+            let mut component = Component {
+                name: untranslated(node, "name").unwrap_or_else(|| id.clone()),
+                summary: untranslated(node, "summary").unwrap_or_default(),
+                package: child_text(node, "pkgname"),
+                id,
+                ..Default::default()
+            };
+            for child in node.children().filter(|n| n.is_element()) {
+                match child.tag_name().name() {
+                    "icon" if child.attribute("type") == Some("cached") && component.icon.is_none() => {
+                        let size = format!("{}x{}", child.attribute("width").unwrap_or("64"), child.attribute("height").unwrap_or("64"));
+                        if size == ICON_SIZE {
+                            component.icon = child.text().map(|file| icon_dir.join(file.trim()));
+                        }
+                    }
+                    "categories" => {
+                        component.categories = child
+                            .children()
+                            .filter(|n| n.has_tag_name("category"))
+                            .filter_map(|n| n.text())
+                            .map(|c| c.trim().to_string())
+                            .collect();
+                    }
+                    "screenshots" => {
+                        let mut shots: Vec<_> = child.children().filter(|n| n.has_tag_name("screenshot")).collect();
+                        // The default screenshot leads
+                        shots.sort_by_key(|shot| shot.attribute("type") != Some("default"));
+                        component.screenshots = shots
+                            .into_iter()
+                            .filter_map(|shot| {
+                                let images = shot.children().filter(|n| n.has_tag_name("image"));
+                                let thumbnails = images
+                                    .clone()
+                                    .filter(|image| image.attribute("type") == Some("thumbnail"))
+                                    .filter_map(|image| Some((image.attribute("width")?.parse().ok()?, image.text()?.trim().to_string())))
+                                    .collect();
+                                let source = images
+                                    .filter(|image| image.attribute("type") != Some("thumbnail"))
+                                    .find_map(|image| image.text())
+                                    .map(|url| url.trim().to_string());
+                                pick_screenshot(thumbnails, source)
+                            })
+                            .collect();
+                    }
+                    "releases" => {
+                        component.releases = child.children().filter(|n| n.has_tag_name("release")).filter_map(parse_release).collect();
+                    }
+                    _ => {}
+                }
+            }
+            Some(component)
+        })
+        .collect();
+    Ok(components)
+}
+
+fn child_text(node: roxmltree::Node, tag: &str) -> Option<String> {
+    node.children()
+        .find(|n| n.has_tag_name(tag))
+        .and_then(|n| n.text())
+        .map(|t| t.trim().to_string())
+}
+
+// The untranslated <name> or <summary>; translations carry xml:lang
+fn untranslated(node: roxmltree::Node, tag: &str) -> Option<String> {
+    node.children()
+        .filter(|n| n.has_tag_name(tag))
+        .find(|n| n.attribute(("http://www.w3.org/XML/1998/namespace", "lang")).is_none())
+        .and_then(|n| n.text())
+        .map(|t| t.trim().to_string())
+}
+
+fn parse_release(node: roxmltree::Node) -> Option<Release> {
+    let date = node
+        .attribute("date")
+        .map(|date| date.get(..10).unwrap_or(date).to_string())
+        .or_else(|| node.attribute("timestamp")?.parse().ok().map(utc_date));
+    let notes = node
+        .children()
+        .find(|n| n.has_tag_name("description"))
+        .map(|description| {
+            description
+                .descendants()
+                .filter(|n| (n.has_tag_name("p") || n.has_tag_name("li")) && n.attribute(("http://www.w3.org/XML/1998/namespace", "lang")).is_none())
+                .map(|n| n.descendants().filter_map(|t| t.is_text().then(|| t.text()).flatten()).collect::<String>())
+                .map(|text| text.split_whitespace().collect::<Vec<_>>().join(" "))
+                .filter(|text| !text.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+    Some(Release {
+        version: node.attribute("version")?.to_string(),
+        date,
+        urgency: node.attribute("urgency").map(str::to_string),
+        notes,
+    })
+}
+
+// The widest thumbnail that fits the details pane, else the full image
+fn pick_screenshot(thumbnails: Vec<(u32, String)>, source: Option<String>) -> Option<String> {
+    thumbnails
+        .into_iter()
+        .filter(|(width, _)| *width <= SCREENSHOT_WIDTH)
+        .max_by_key(|(width, _)| *width)
+        .map(|(_, url)| url)
+        .or(source)
+}
+
+// One YAML document of a DEP-11 catalog: the header, which names the origin
+// and the base of relative media URLs, or a component
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+struct Dep11Document {
+    origin: Option<String>,
+    media_base_url: Option<String>,
+    #[serde(rename = "ID")]
+    id: Option<String>,
+    package: Option<String>,
+    name: HashMap<String, String>,
+    summary: HashMap<String, String>,
+    categories: Vec<String>,
+    icon: Dep11Icon,
+    screenshots: Vec<Dep11Screenshot>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Dep11Icon {
+    cached: Vec<Dep11Image>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+struct Dep11Screenshot {
+    default: bool,
+    thumbnails: Vec<Dep11Image>,
+    source_image: Option<Dep11Image>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Dep11Image {
+    name: String,
+    url: String,
+    width: u32,
+    height: u32,
+}
+
+/// Parse a DEP-11 YAML catalog as apt fetches it. Cached icons live under
+/// `icons/<origin>/64x64`; relative screenshot URLs are resolved against the
+/// header's MediaBaseUrl.
+pub fn parse_dep11(yaml: &str, icons: &Path) -> Result<Vec<Component>> {
+    let mut origin = String::new();
+    let mut media_base = String::new();
+    let mut components = Vec::new();
+
+    for document in serde_yaml::Deserializer::from_str(yaml) {
+        // One malformed component shouldn't cost the whole catalog
+        let Ok(document) = Dep11Document::deserialize(document) else {
+            continue;
+        };
+        let Some(id) = document.id else {
+            origin = document.origin.unwrap_or(origin);
+            media_base = document.media_base_url.unwrap_or(media_base);
+            continue;
+        };
+
+        let media_url = |url: &str| {
+            if url.contains("://") || media_base.is_empty() {
+                url.to_string()
+            } else {
+                format!("{}/{}", media_base.trim_end_matches('/'), url)
+            }
+        };
+        let icon = document
+            .icon
+            .cached
+            .iter()
+            .find(|image| format!("{}x{}", image.width, image.height) == ICON_SIZE)
+            .map(|image| icons.join(&origin).join(ICON_SIZE).join(&image.name));
+        let mut screenshots = document.screenshots;
+        screenshots.sort_by_key(|shot| !shot.default);
+
+        components.push(Component {
+            name: document.name.get("C").cloned().unwrap_or_else(|| id.clone()),
+            summary: document.summary.get("C").cloned().unwrap_or_default(),
+            package: document.package,
+            icon,
+            screenshots: screenshots
+                .into_iter()
+                .filter_map(|shot| {
+                    pick_screenshot(
+                        shot.thumbnails.into_iter().map(|t| (t.width, media_url(&t.url))).collect(),
+                        shot.source_image.map(|s| media_url(&s.url)),
+                    )
+                })
+                .collect(),
+            categories: document.categories,
+            releases: Vec::new(),
+            id,
+        });
+    }
+    Ok(components)
+}
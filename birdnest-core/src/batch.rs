@@ -0,0 +1,139 @@
+//! Installs and removals picked across the system's packages, Flatpaks and
+//! pikman's containers, applied together as one batch.
+//!
+//! The items are grouped by what happens to them and where they come from,
+//! and each group runs as one step: removals before installs, so swapping a
+//! package for its Flatpak doesn't leave both, then system packages before
+//! Flatpaks before each pikman distro. A group that fails doesn't stop the
+//! groups after it; every item reports how its group went, and
+//! [`summary`] rolls them up once the last one is done.
+
+use crate::manifest::PIKMAN_DISTROS;
+use crate::package_manager::{BackendCommand, PackageBackend};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Action {
+    Remove,
+    Install,
+}
+
+/// Where an item is installed from or removed from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Origin {
+    /// apt, or whichever backend the system uses
+    System,
+    Flatpak,
+    /// A pikman container, by its distro's flag, one of [`PIKMAN_DISTROS`]
+    Pikman(&'static str),
+}
+
+impl Origin {
+    /// The pikman origin for `distro`, e.g. "aur"; None for an unknown one
+    pub fn pikman(distro: &str) -> Option<Origin> {
+        PIKMAN_DISTROS.iter().find(|known| known.eq_ignore_ascii_case(distro)).map(|known| Origin::Pikman(known))
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Origin::System => "System",
+            Origin::Flatpak => "Flatpak",
+            Origin::Pikman("aur") => "AUR",
+            Origin::Pikman("fedora") => "Fedora",
+            Origin::Pikman("alpine") => "Alpine",
+            Origin::Pikman(distro) => distro,
+        }
+    }
+}
+
+/// One package or app to install or remove
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Item {
+    pub name: String,
+    pub origin: Origin,
+    pub action: Action,
+}
+
+impl Item {
+    pub fn new(name: &str, origin: Origin, action: Action) -> Self {
+        Self { name: name.to_string(), origin, action }
+    }
+}
+
+/// The items one step handles
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Group {
+    pub action: Action,
+    pub origin: Origin,
+    /// Sorted, without repeats
+    pub names: Vec<String>,
+}
+
+impl Group {
+    /// "Install 2 Flatpak apps", "Remove 1 system package"
+    pub fn label(&self) -> String {
+        let verb = match self.action {
+            Action::Install => "Install",
+            Action::Remove => "Remove",
+        };
+        let noun = match (self.origin, self.names.len()) {
+            (Origin::Flatpak, 1) => "app",
+            (Origin::Flatpak, _) => "apps",
+            (_, 1) => "package",
+            _ => "packages",
+        };
+        let origin = match self.origin {
+            Origin::System => "system",
+            other => other.label(),
+        };
+        format!("{} {} {} {}", verb, self.names.len(), origin, noun)
+    }
+
+    /// What the step runs. System packages go through the backend, which
+    /// may have no command line (the mock); the GUI then calls it directly.
+    /// Flatpak and pikman elevate themselves, so they run as the user.
+    pub fn commands(&self, backend: &dyn PackageBackend) -> Vec<BackendCommand> {
+        match (self.origin, self.action) {
+            (Origin::System, Action::Install) => backend.install_commands(&self.names),
+            (Origin::System, Action::Remove) => backend.remove_commands(&self.names),
+            (Origin::Flatpak, Action::Install) => vec![BackendCommand::as_user("flatpak", &["install", "-y", "--noninteractive"], &self.names)],
+            (Origin::Flatpak, Action::Remove) => vec![BackendCommand::as_user("flatpak", &["uninstall", "-y", "--noninteractive"], &self.names)],
+            (Origin::Pikman(distro), action) => {
+                let verb = if action == Action::Install { "install" } else { "remove" };
+                vec![BackendCommand::as_user("pikman", &[verb, &format!("--{}", distro), "-y"], &self.names)]
+            }
+        }
+    }
+}
+
+/// `items` grouped into the steps that apply them, in the order they run
+pub fn groups(items: &[Item]) -> Vec<Group> {
+    let mut groups: Vec<Group> = Vec::new();
+    for item in items {
+        match groups.iter_mut().find(|group| group.action == item.action && group.origin == item.origin) {
+            Some(group) => group.names.push(item.name.clone()),
+            None => groups.push(Group { action: item.action, origin: item.origin, names: vec![item.name.clone()] }),
+        }
+    }
+    for group in &mut groups {
+        group.names.sort();
+        group.names.dedup();
+    }
+    groups.sort_by_key(|group| (group.action, group.origin));
+    groups
+}
+
+/// "5 of 6 changes applied; Install 1 AUR package failed: pikman exited
+/// with code 1", from each group and how it went
+pub fn summary(results: &[(Group, Result<(), String>)]) -> String {
+    let total: usize = results.iter().map(|(group, _)| group.names.len()).sum();
+    let done: usize = results.iter().filter(|(_, result)| result.is_ok()).map(|(group, _)| group.names.len()).sum();
+    let failures: Vec<String> = results
+        .iter()
+        .filter_map(|(group, result)| result.as_ref().err().map(|e| format!("{} failed: {}", group.label(), e)))
+        .collect();
+    match (failures.is_empty(), total) {
+        (true, 1) => "1 change applied".to_string(),
+        (true, _) => format!("All {} changes applied", total),
+        (false, _) => format!("{} of {} changes applied; {}", done, total, failures.join("; ")),
+    }
+}
@@ -0,0 +1,237 @@
+//! On-disk cache of the installed package list, with each package's size,
+//! architecture, description and provenance.
+//!
+//! The main window, the dialog processes and the CLI all read and write the
+//! same file, so every access holds an flock on a lock file beside it and
+//! writes land through a per-process temp file renamed into place.
+
+use std::fs;
+use std::io::Write;
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use bincode::Options;
+
+use crate::dpkg::{self, InstalledPackage};
+use crate::events::{self, Event};
+
+// Installed-package cache, stored as:
+//   magic (4) | version u32 | payload length u64 | checksum u64 | payload
+// where payload is the package list serialized with bincode. The version
+// changes whenever InstalledPackage does, since bincode isn't
+// self-describing. Anything that fails validation is deleted so the caller
+// regenerates it from the dpkg status file.
+const CACHE_MAGIC: &[u8; 4] = b"BNPC";
+pub const CACHE_VERSION: u32 = 5;
+const HEADER_LEN: usize = 4 + 4 + 8 + 8;
+
+// Set from the config's cache_ttl_hours at startup; None keeps the cache
+// until dpkg changes
+static TTL: Mutex<Option<Duration>> = Mutex::new(None);
+
+/// Treat the cache as stale once it is older than `ttl`
+pub fn set_ttl(ttl: Option<Duration>) {
+    *TTL.lock().unwrap() = ttl;
+}
+
+/// `~/.cache/birdnest/installed_packages.cache`, which the GUI, its dialogs
+/// and the CLI all share
+pub fn cache_path() -> Option<PathBuf> {
+    let home = std::env::var("HOME").ok()?;
+    Some(PathBuf::from(home).join(".cache").join("birdnest").join("installed_packages.cache"))
+}
+
+// Where versions before 5 kept it, among the settings
+fn old_cache_path() -> Option<PathBuf> {
+    let home = std::env::var("HOME").ok()?;
+    Some(PathBuf::from(home).join(".config").join("birdnest").join("installed_packages.cache"))
+}
+
+// An exclusive flock on `installed_packages.cache.lock`, released when
+// dropped. Reads take it too, since a reader deletes a cache it finds stale
+// or corrupt and must not delete one another process has just written.
+struct CacheLock(#[allow(dead_code)] fs::File);
+
+impl CacheLock {
+    // Waits for the other process; accesses take milliseconds. Without a
+    // lock (a read-only home, say) the cache is used unguarded.
+    fn acquire(cache: &Path) -> Option<Self> {
+        let path = cache.with_extension("cache.lock");
+        let file = match fs::OpenOptions::new().create(true).truncate(false).write(true).open(&path) {
+            Ok(file) => file,
+            Err(e) => {
+                tracing::debug!("cache: can't open {}: {}", path.display(), e);
+                return None;
+            }
+        };
+        loop {
+            if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX) } == 0 {
+                return Some(CacheLock(file));
+            }
+            let error = std::io::Error::last_os_error();
+            if error.kind() != std::io::ErrorKind::Interrupted {
+                tracing::warn!("cache: can't lock {}: {}", path.display(), error);
+                return None;
+            }
+        }
+    }
+}
+
+// The last change to dpkg's status file, or to apt's auto-installed marks,
+// which `apt-mark` changes without touching dpkg
+fn records_mtime() -> Option<SystemTime> {
+    let status = fs::metadata(dpkg::STATUS_PATH).ok()?.modified().ok()?;
+    let marks = fs::metadata(dpkg::EXTENDED_STATES_PATH).and_then(|metadata| metadata.modified());
+    Some(marks.map_or(status, |marks| marks.max(status)))
+}
+
+// FNV-1a, enough to catch truncation and bit rot without pulling in a dependency
+fn checksum(data: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in data {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+fn read_u32(data: &[u8], pos: usize) -> u32 {
+    let mut bytes = [0u8; 4];
+    bytes.copy_from_slice(&data[pos..pos + 4]);
+    u32::from_le_bytes(bytes)
+}
+
+fn read_u64(data: &[u8], pos: usize) -> u64 {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&data[pos..pos + 8]);
+    u64::from_le_bytes(bytes)
+}
+
+// bincode with variable-length integers, which keeps sizes and lengths to a
+// byte or two; trailing bytes after the list are an error
+fn payload_format() -> impl Options {
+    bincode::DefaultOptions::new().reject_trailing_bytes()
+}
+
+/// The cache file's contents for `packages`
+pub fn encode(packages: &[InstalledPackage]) -> Vec<u8> {
+    // Serializing owned strings and integers can't fail
+    let payload = payload_format().serialize(packages).expect("package list serializes");
+    let mut data = Vec::with_capacity(HEADER_LEN + payload.len());
+    data.extend_from_slice(CACHE_MAGIC);
+    data.extend_from_slice(&CACHE_VERSION.to_le_bytes());
+    data.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+    data.extend_from_slice(&checksum(&payload).to_le_bytes());
+    data.extend_from_slice(&payload);
+    data
+}
+
+/// The package list in a cache file, or why it can't be trusted
+pub fn decode(data: &[u8]) -> Result<Vec<InstalledPackage>, String> {
+    if data.len() < HEADER_LEN {
+        return Err(format!("file too short ({} bytes)", data.len()));
+    }
+    if &data[0..4] != CACHE_MAGIC {
+        return Err("bad magic number".to_string());
+    }
+    let version = read_u32(data, 4);
+    if version != CACHE_VERSION {
+        return Err(format!("unsupported version {} (expected {})", version, CACHE_VERSION));
+    }
+    let payload_len = read_u64(data, 8) as usize;
+    let expected_checksum = read_u64(data, 16);
+
+    let payload = &data[HEADER_LEN..];
+    if payload.len() != payload_len {
+        return Err(format!("payload is {} bytes, header says {}", payload.len(), payload_len));
+    }
+    if checksum(payload) != expected_checksum {
+        return Err("checksum mismatch".to_string());
+    }
+    // The limit stops a corrupt length from allocating more than the file holds
+    payload_format()
+        .with_limit(payload_len as u64)
+        .deserialize(payload)
+        .map_err(|e| format!("unreadable entries: {}", e))
+}
+
+/// Load the cached package list if it is intact and newer than the dpkg
+/// status file and apt's auto-installed marks
+pub fn load_installed() -> Option<Vec<InstalledPackage>> {
+    let path = cache_path()?;
+    let status_mtime = records_mtime()?;
+    let _lock = CacheLock::acquire(&path);
+    let cache_mtime = fs::metadata(&path).ok()?.modified().ok()?;
+
+    if cache_mtime < status_mtime {
+        tracing::debug!("cache::load_installed: Cache is older than dpkg status or apt's marks, invalidating");
+        let _ = fs::remove_file(&path);
+        return None;
+    }
+    let ttl = *TTL.lock().unwrap();
+    if let Some(ttl) = ttl.filter(|ttl| cache_mtime.elapsed().is_ok_and(|age| age > *ttl)) {
+        tracing::debug!("cache::load_installed: Cache is older than {:?}, invalidating", ttl);
+        let _ = fs::remove_file(&path);
+        return None;
+    }
+
+    let data = fs::read(&path).ok()?;
+    match decode(&data) {
+        Ok(packages) => {
+            tracing::debug!("cache::load_installed: Loaded {} packages from cache", packages.len());
+            Some(packages)
+        }
+        Err(e) => {
+            tracing::warn!("cache::load_installed: Discarding invalid cache: {}", e);
+            let _ = fs::remove_file(&path);
+            None
+        }
+    }
+}
+
+/// Write the package list via a temp file + rename so readers never see a
+/// partial cache, holding the lock so concurrent writers take turns
+pub fn save_installed(packages: &[InstalledPackage]) {
+    let Some(path) = cache_path() else {
+        tracing::warn!("cache::save_installed: Could not get cache path");
+        return;
+    };
+
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    if let Some(old) = old_cache_path() {
+        let _ = fs::remove_file(old.with_extension("cache.lock"));
+        let _ = fs::remove_file(old);
+    }
+
+    let _lock = CacheLock::acquire(&path);
+    // Named per process, so a writer that couldn't lock still doesn't share it
+    let tmp_path = path.with_extension(format!("cache.tmp.{}", std::process::id()));
+    let result = fs::File::create(&tmp_path)
+        .and_then(|mut file| {
+            file.write_all(&encode(packages))?;
+            file.sync_all()
+        })
+        .and_then(|_| fs::rename(&tmp_path, &path));
+
+    match result {
+        Ok(()) => tracing::debug!("cache::save_installed: Saved {} packages to cache", packages.len()),
+        Err(e) => {
+            tracing::warn!("cache::save_installed: Failed to write cache: {}", e);
+            let _ = fs::remove_file(&tmp_path);
+        }
+    }
+}
+
+/// Drop the cache (call after install/remove operations)
+pub fn invalidate_installed() {
+    if let Some(path) = cache_path() {
+        let _lock = CacheLock::acquire(&path);
+        let _ = fs::remove_file(&path);
+        tracing::debug!("cache::invalidate_installed: Cache invalidated");
+    }
+    events::publish(Event::CacheInvalidated);
+}
@@ -0,0 +1,142 @@
+//! What changed in a package: the Debian changelog `apt-get changelog`
+//! downloads for system packages, or the release notes a Flatpak remote's
+//! AppStream catalog carries, read into the same [`ChangelogEntry`] list so
+//! frontends can show either before an upgrade.
+
+use anyhow::Result;
+
+use crate::appstream::{Catalog, Release};
+
+/// One version's changes, newest first in a list
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChangelogEntry {
+    pub version: String,
+    /// low, medium, high, emergency or critical
+    pub urgency: Option<String>,
+    pub date: Option<String>,
+    /// Who made the upload; AppStream releases don't say
+    pub author: Option<String>,
+    /// One line per change, wrapped lines joined
+    pub changes: Vec<String>,
+}
+
+/// Parse a debian/changelog: per version a `pkg (version) dists;
+/// urgency=...` line, the `*` or `-` items below it, and a ` -- author  date`
+/// trailer. Contributor headings like `[ Jane Doe ]` are dropped.
+pub fn parse_debian(text: &str) -> Vec<ChangelogEntry> {
+    let mut entries: Vec<ChangelogEntry> = Vec::new();
+    for line in text.lines() {
+        if !line.starts_with(char::is_whitespace) && !line.is_empty() {
+            if let Some(entry) = parse_heading(line) {
+                entries.push(entry);
+            }
+            continue;
+        }
+        let Some(entry) = entries.last_mut() else {
+            continue;
+        };
+        let trimmed = line.trim();
+        if let Some(trailer) = line.strip_prefix(" -- ") {
+            // Two spaces separate the maintainer from the date
+            let (author, date) = trailer.split_once("  ").unwrap_or((trailer, ""));
+            entry.author = Some(author.trim().to_string()).filter(|a| !a.is_empty());
+            entry.date = Some(date.trim().to_string()).filter(|d| !d.is_empty());
+        } else if let Some(item) = trimmed.strip_prefix("* ").or_else(|| trimmed.strip_prefix("- ")).or_else(|| trimmed.strip_prefix("+ ")) {
+            entry.changes.push(item.trim().to_string());
+        } else if trimmed.is_empty() || (trimmed.starts_with('[') && trimmed.ends_with(']')) {
+            continue;
+        } else if let Some(last) = entry.changes.last_mut() {
+            last.push(' ');
+            last.push_str(trimmed);
+        } else {
+            entry.changes.push(trimmed.to_string());
+        }
+    }
+    entries
+}
+
+// "htop (3.3.0-4) unstable; urgency=medium"
+fn parse_heading(line: &str) -> Option<ChangelogEntry> {
+    let (_, rest) = line.split_once(" (")?;
+    let (version, rest) = rest.split_once(')')?;
+    let urgency = rest
+        .split_once(';')
+        .map(|(_, fields)| fields)
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|field| field.trim().split_once('='))
+        .find(|(key, _)| key.eq_ignore_ascii_case("urgency"))
+        .map(|(_, value)| value.trim().to_lowercase());
+    Some(ChangelogEntry { version: version.trim().to_string(), urgency, ..Default::default() })
+}
+
+/// A Flatpak's AppStream releases as changelog entries
+pub fn from_releases(releases: &[Release]) -> Vec<ChangelogEntry> {
+    releases
+        .iter()
+        .map(|release| ChangelogEntry {
+            version: release.version.clone(),
+            urgency: release.urgency.clone(),
+            date: release.date.clone(),
+            author: None,
+            changes: release.notes.clone(),
+        })
+        .collect()
+}
+
+/// A Flatpak app's release notes from the remotes' catalogs
+pub fn for_app(catalog: &Catalog, app: &str) -> Result<Vec<ChangelogEntry>> {
+    match catalog.for_app(app) {
+        Some(component) if !component.releases.is_empty() => Ok(from_releases(&component.releases)),
+        _ => anyhow::bail!("The Flatpak remotes publish no release notes for {}", app),
+    }
+}
+
+/// The entries above `installed`, what upgrading from it brings; all of them
+/// when it isn't listed (or nothing is installed)
+pub fn newer_than(entries: Vec<ChangelogEntry>, installed: &str) -> Vec<ChangelogEntry> {
+    if !entries.iter().any(|entry| entry.version == installed) {
+        return entries;
+    }
+    entries.into_iter().take_while(|entry| entry.version != installed).collect()
+}
+
+// Debian's urgencies, least pressing first; AppStream's are a subset
+const URGENCIES: &[&str] = &["low", "medium", "high", "emergency", "critical"];
+
+/// The most pressing urgency among `entries`, so a batch of versions is as
+/// urgent as its most urgent upload
+pub fn urgency(entries: &[ChangelogEntry]) -> Option<&str> {
+    entries
+        .iter()
+        .filter_map(|entry| entry.urgency.as_deref())
+        .filter_map(|urgency| Some((URGENCIES.iter().position(|u| *u == urgency)?, urgency)))
+        .max_by_key(|(rank, _)| *rank)
+        .map(|(_, urgency)| urgency)
+}
+
+/// Whether `urgency` is worth pointing out: high or above
+pub fn is_urgent(urgency: &str) -> bool {
+    URGENCIES.iter().position(|u| *u == urgency).is_some_and(|rank| rank >= 2)
+}
+
+/// Entries as a terminal listing, the way the CLI prints them
+pub fn render(entries: &[ChangelogEntry]) -> String {
+    let mut text = String::new();
+    for entry in entries {
+        let mut heading = entry.version.clone();
+        if let Some(date) = &entry.date {
+            heading.push_str(&format!(" ({})", date));
+        }
+        if let Some(urgency) = entry.urgency.as_deref().filter(|u| is_urgent(u)) {
+            heading.push_str(&format!(" [urgency: {}]", urgency));
+        }
+        text.push_str(&heading);
+        text.push('\n');
+        for change in &entry.changes {
+            text.push_str(&format!("  * {}\n", change));
+        }
+        text.push('\n');
+    }
+    text
+}
@@ -0,0 +1,233 @@
+//! User configuration stored in `~/.config/birdnest/config.toml`.
+//!
+//! Earlier versions wrote `config.json`; it is read once and rewritten as
+//! TOML when no `config.toml` exists yet.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::elevation::ElevationMethod;
+use crate::hooks::Hook;
+use crate::install_options::InstallOptions;
+use crate::notify::NotificationConfig;
+use crate::proxy::ProxyConfig;
+use crate::schedule::RefreshSchedule;
+use crate::update_check::UpdateCheck;
+
+/// The GUI's color scheme
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemePreference {
+    #[default]
+    Dark,
+    Light,
+}
+
+/// What the GUI loads in the background once its window is up, so the
+/// first visit to those tabs needn't wait; installed system packages always
+/// load at startup
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preload {
+    /// The installed Flatpak apps for the Flatpak tab
+    pub flatpak: bool,
+    /// Each pikman container's installed packages, which also starts the
+    /// containers so the first pikman search doesn't have to
+    pub pikman: bool,
+    /// Seconds to wait after startup, leaving the first frames the CPU
+    pub delay_secs: u64,
+}
+
+impl Default for Preload {
+    fn default() -> Self {
+        Self { flatpak: true, pikman: true, delay_secs: 3 }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Config {
+    /// "auto", "pikman"/"apt", "dnf" or "pacman"
+    pub package_manager: String,
+    /// Answer yes to the CLI's confirmation prompts
+    pub auto_confirm: bool,
+    pub flatpak_enabled: bool,
+    /// Skip icons and shadows and cap result lists for low-RAM machines
+    #[serde(default)]
+    pub low_memory_mode: bool,
+    /// Draw the GUI with the software renderer, for drivers that show blank windows
+    #[serde(default)]
+    pub software_rendering: bool,
+    /// How to gain root; `BIRDNEST_ELEVATION` overrides it for a session
+    #[serde(default)]
+    pub elevation: ElevationMethod,
+    /// Remote to install from when a Flatpak is on several; flathub when unset
+    #[serde(default)]
+    pub flatpak_default_remote: Option<String>,
+    /// AUR helper for the pacman backend ("paru", "yay" or "none"); detected when unset
+    #[serde(default)]
+    pub aur_helper: Option<String>,
+    /// When background metadata refreshes run and on which connections
+    #[serde(default)]
+    pub refresh_schedule: RefreshSchedule,
+    /// Enlarges (1.25) or shrinks (0.9) the GUI on top of the desktop's own scaling
+    #[serde(default)]
+    pub ui_scale: Option<f32>,
+    #[serde(default)]
+    pub theme: ThemePreference,
+    /// Corner radius of the GUI's buttons and cards
+    #[serde(default = "default_border_radius")]
+    pub border_radius: f32,
+    /// Tab the GUI opens on ("search", "flatpak", ...); Search, or Flatpak on
+    /// immutable systems, when unset
+    #[serde(default)]
+    pub default_tab: Option<String>,
+    /// Rebuild the installed-package cache once it is this old, even if dpkg
+    /// hasn't changed; only dpkg changes invalidate it when unset
+    #[serde(default)]
+    pub cache_ttl_hours: Option<u32>,
+    /// Hide removed GUI apps for this many days before uninstalling them;
+    /// removals are immediate when unset
+    #[serde(default)]
+    pub deferred_removal_days: Option<u32>,
+    /// How often the GUI counts available updates, and whether it notifies
+    #[serde(default)]
+    pub update_check: UpdateCheck,
+    /// Where the GUI and the CLI announce finished transactions and updates
+    #[serde(default)]
+    pub notifications: NotificationConfig,
+    /// HTTP and HTTPS proxy for every backend and BirdNest's own downloads;
+    /// apt's configuration and the environment are used when unset
+    #[serde(default)]
+    pub proxy: ProxyConfig,
+    /// Commands to run after installs, removals and upgrades succeed
+    #[serde(default)]
+    pub hooks: Vec<Hook>,
+    /// Flags installs start with, which the install dialogs can change
+    #[serde(default)]
+    pub install_options: InstallOptions,
+    /// Directories scanned for AppImages besides `~/Applications`
+    #[serde(default)]
+    pub appimage_dirs: Vec<PathBuf>,
+    #[serde(default)]
+    pub preload: Preload,
+    /// Rows the GUI's Search and Installed tabs list a page at a time; 0
+    /// lists them all on one page
+    #[serde(default = "default_search_result_limit")]
+    pub search_result_limit: u32,
+    /// Terminal emulator the GUI opens containers in; found on PATH when unset
+    #[serde(default)]
+    pub terminal: Option<String>,
+}
+
+fn default_border_radius() -> f32 {
+    24.0
+}
+
+fn default_search_result_limit() -> u32 {
+    200
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            package_manager: "auto".to_string(),
+            auto_confirm: false,
+            flatpak_enabled: true,
+            low_memory_mode: false,
+            software_rendering: false,
+            elevation: ElevationMethod::Auto,
+            flatpak_default_remote: None,
+            aur_helper: None,
+            refresh_schedule: RefreshSchedule::default(),
+            ui_scale: None,
+            theme: ThemePreference::Dark,
+            border_radius: default_border_radius(),
+            default_tab: None,
+            cache_ttl_hours: None,
+            deferred_removal_days: None,
+            update_check: UpdateCheck::default(),
+            notifications: NotificationConfig::default(),
+            proxy: ProxyConfig::default(),
+            hooks: Vec::new(),
+            install_options: InstallOptions::default(),
+            appimage_dirs: Vec::new(),
+            preload: Preload::default(),
+            search_result_limit: default_search_result_limit(),
+            terminal: None,
+        }
+    }
+}
+
+impl Config {
+    pub fn load() -> Result<Self> {
+        let config_path = Self::config_path()?;
+        
+        if config_path.exists() {
+            let content = fs::read_to_string(&config_path)?;
+            Ok(Self::from_toml(&content)?)
+        } else {
+            let legacy = config_path.with_extension("json");
+            let config = match fs::read_to_string(&legacy) {
+                Ok(content) => {
+                    tracing::debug!("Config: moving {} to TOML", legacy.display());
+                    serde_json::from_str(&content)?
+                }
+                Err(_) => Config::default(),
+            };
+            config.save()?;
+            Ok(config)
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let config_path = Self::config_path()?;
+        if let Some(parent) = config_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&config_path, self.to_toml()?)?;
+        Ok(())
+    }
+
+    pub fn from_toml(content: &str) -> Result<Self> {
+        Ok(toml::from_str(content)?)
+    }
+
+    pub fn to_toml(&self) -> Result<String> {
+        Ok(toml::to_string_pretty(self)?)
+    }
+
+    /// The configured cache lifetime, for [`crate::cache::set_ttl`]
+    pub fn cache_ttl(&self) -> Option<std::time::Duration> {
+        self.cache_ttl_hours.map(|hours| std::time::Duration::from_secs(u64::from(hours) * 3600))
+    }
+
+    // Most result lists are capped to this many rows in low-memory mode
+    pub const LOW_MEMORY_RESULT_LIMIT: usize = 200;
+
+    pub fn result_limit(&self) -> Option<usize> {
+        if self.low_memory_mode {
+            Some(Self::LOW_MEMORY_RESULT_LIMIT)
+        } else {
+            None
+        }
+    }
+
+    /// How many rows a page of search results or installed packages has,
+    /// the lower of `search_result_limit` and the low-memory cap; None for
+    /// all of them
+    pub fn search_limit(&self) -> Option<usize> {
+        let limit = (self.search_result_limit > 0).then_some(self.search_result_limit as usize);
+        match (limit, self.result_limit()) {
+            (Some(limit), Some(cap)) => Some(limit.min(cap)),
+            (limit, cap) => limit.or(cap),
+        }
+    }
+
+    fn config_path() -> Result<PathBuf> {
+        let home = std::env::var("HOME")?;
+        Ok(PathBuf::from(home).join(".config").join("birdnest").join("config.toml"))
+    }
+}
+
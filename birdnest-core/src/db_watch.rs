@@ -0,0 +1,131 @@
+//! Notices when another tool changes what is installed: `apt` in a
+//! terminal, Discover, `flatpak install` and so on.
+//!
+//! The package databases (dpkg's status file, rpm's database or pacman's
+//! local directory) and the `.changed` file Flatpak touches in each
+//! installation after every change are watched with inotify. A transaction
+//! rewrites them many times, so a change is reported once they have been
+//! quiet for [`QUIET`].
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, SystemTime};
+
+use notify::event::{AccessKind, AccessMode};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+
+use crate::dpkg;
+use crate::flatpak::FlatpakInstallation;
+
+/// How long a database has to stay untouched before its change is reported
+pub const QUIET: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Database {
+    /// The system package manager's
+    Packages,
+    Flatpak,
+}
+
+/// A database that changed, and when it was last written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Change {
+    pub database: Database,
+    pub at: SystemTime,
+}
+
+/// A directory to watch, and the file in it that belongs to the database;
+/// every entry counts when `file` is None
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Target {
+    pub database: Database,
+    pub dir: PathBuf,
+    pub file: Option<&'static str>,
+}
+
+impl Target {
+    fn new(database: Database, dir: impl Into<PathBuf>, file: Option<&'static str>) -> Self {
+        Self { database, dir: dir.into(), file }
+    }
+
+    /// Whether a change to `path` changes the database
+    pub fn matches(&self, path: &Path) -> bool {
+        match self.file {
+            Some(file) => path.parent() == Some(self.dir.as_path()) && path.file_name().is_some_and(|name| name == file),
+            None => path.starts_with(&self.dir),
+        }
+    }
+}
+
+/// The databases on this system; those that don't exist are left out
+pub fn targets() -> Vec<Target> {
+    let status = Path::new(dpkg::STATUS_PATH);
+    let mut targets = vec![
+        Target::new(Database::Packages, status.parent().unwrap_or(Path::new("/")), Some("status")),
+        Target::new(Database::Packages, "/var/lib/rpm", Some("rpmdb.sqlite")),
+        // One directory per installed package
+        Target::new(Database::Packages, "/var/lib/pacman/local", None),
+    ];
+    targets.extend(FlatpakInstallation::ALL.map(|installation| Target::new(Database::Flatpak, installation.path(), Some(".changed"))));
+    targets.retain(|target| target.dir.is_dir());
+    targets
+}
+
+/// The watch in progress; dropping it stops watching
+pub struct DatabaseWatch {
+    _watcher: RecommendedWatcher,
+    /// Each database once it settles after a change
+    pub changes: UnboundedReceiver<Change>,
+}
+
+/// Watch `targets`, reporting a change once its database has been quiet for
+/// `quiet`
+pub fn watch(targets: Vec<Target>, quiet: Duration) -> notify::Result<DatabaseWatch> {
+    let (events_tx, events) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(events_tx)?;
+    for target in &targets {
+        watcher.watch(&target.dir, RecursiveMode::NonRecursive)?;
+    }
+    tracing::debug!("db_watch: watching {} directories", targets.len());
+
+    let (changes_tx, changes) = unbounded_channel();
+    std::thread::spawn(move || {
+        // Last write to each database that hasn't settled yet
+        let mut pending: HashMap<Database, SystemTime> = HashMap::new();
+        loop {
+            let event = if pending.is_empty() {
+                events.recv().map_err(|_| mpsc::RecvTimeoutError::Disconnected)
+            } else {
+                events.recv_timeout(quiet)
+            };
+            match event {
+                Ok(Ok(event)) => {
+                    // Reads and opens happen on every query
+                    if matches!(event.kind, EventKind::Access(kind) if kind != AccessKind::Close(AccessMode::Write)) {
+                        continue;
+                    }
+                    for path in &event.paths {
+                        if let Some(target) = targets.iter().find(|target| target.matches(path)) {
+                            pending.insert(target.database, SystemTime::now());
+                        }
+                    }
+                }
+                Ok(Err(e)) => tracing::warn!("db_watch: {}", e),
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    for (database, at) in pending.drain() {
+                        tracing::debug!("db_watch: {:?} changed", database);
+                        if changes_tx.send(Change { database, at }).is_err() {
+                            return;
+                        }
+                    }
+                }
+                // The watcher was dropped
+                Err(mpsc::RecvTimeoutError::Disconnected) => return,
+            }
+        }
+    });
+
+    Ok(DatabaseWatch { _watcher: watcher, changes })
+}
@@ -0,0 +1,186 @@
+//! Apps removed in "deferred removal" mode, hidden until they are uninstalled.
+//!
+//! Instead of uninstalling a GUI app straight away, deferring writes a
+//! `Hidden=true` override for each of its desktop files to
+//! `~/.local/share/applications` and, for Flatpaks, masks the app so it
+//! doesn't update. The app is recorded in
+//! `~/.config/birdnest/deferred-removals.json` with the time it is due;
+//! [`restore`] undoes both, and [`uninstall`] removes it for good once the
+//! frontend finds it due.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::hooks::{self, HookEvent};
+use crate::package_manager::PackageBackend;
+use crate::utils::{now, run_command};
+
+const DAY: u64 = 24 * 3600;
+
+// What an override needs to hide the system's entry of the same name
+const HIDDEN_ENTRY: &str = "[Desktop Entry]\nType=Application\nHidden=true\n";
+
+/// An app hidden until its removal is due
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeferredRemoval {
+    /// Package name or Flatpak application ID
+    pub name: String,
+    pub flatpak: bool,
+    /// Seconds since the Unix epoch after which it is uninstalled
+    pub remove_after: u64,
+    /// The overrides BirdNest wrote, and deletes again on restore
+    pub hidden: Vec<PathBuf>,
+}
+
+impl DeferredRemoval {
+    pub fn is_due(&self, now: u64) -> bool {
+        now >= self.remove_after
+    }
+
+    /// Whole days left before it is uninstalled, rounded up
+    pub fn days_left(&self, now: u64) -> u64 {
+        self.remove_after.saturating_sub(now).div_ceil(DAY)
+    }
+}
+
+fn list_path() -> Option<PathBuf> {
+    let home = std::env::var("HOME").ok()?;
+    Some(PathBuf::from(home).join(".config").join("birdnest").join("deferred-removals.json"))
+}
+
+// Where the user's desktop files go, ahead of the system's on the XDG data path
+fn applications_dir() -> Option<PathBuf> {
+    match std::env::var("XDG_DATA_HOME") {
+        Ok(data) if !data.is_empty() => Some(PathBuf::from(data).join("applications")),
+        _ => Some(PathBuf::from(std::env::var("HOME").ok()?).join(".local").join("share").join("applications")),
+    }
+}
+
+/// The hidden apps; empty when there are none or the list can't be read
+pub fn load() -> Vec<DeferredRemoval> {
+    list_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn save(deferred: &[DeferredRemoval]) -> Result<()> {
+    let path = list_path().ok_or_else(|| anyhow::anyhow!("HOME is not set"))?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(deferred)?)?;
+    Ok(())
+}
+
+/// Desktop file names among the paths a package owns, e.g. "gimp.desktop"
+pub fn desktop_files(paths: &[String]) -> Vec<String> {
+    paths
+        .iter()
+        .filter(|path| path.starts_with("/usr/share/applications/") && path.ends_with(".desktop"))
+        .filter_map(|path| Path::new(path).file_name()?.to_str().map(str::to_string))
+        .collect()
+}
+
+/// The desktop files removing `name` would take away; empty for packages
+/// without any, which aren't GUI apps and are removed straight away
+pub fn app_desktop_files(backend: &dyn PackageBackend, name: &str, flatpak: bool) -> Vec<String> {
+    if flatpak {
+        // Flatpak exports its apps' entries under their application ID
+        return vec![format!("{}.desktop", name)];
+    }
+    match backend.files(name) {
+        Ok(paths) => desktop_files(&paths),
+        Err(e) => {
+            tracing::warn!("deferred_removal: can't list files of {}: {}", name, e);
+            Vec::new()
+        }
+    }
+}
+
+/// Write a `Hidden=true` override for each desktop file into `dir`, leaving
+/// any the user already has alone; returns the overrides written
+pub fn hide_in(dir: &Path, desktop_files: &[String]) -> Result<Vec<PathBuf>> {
+    fs::create_dir_all(dir)?;
+    let mut hidden = Vec::new();
+    for file in desktop_files {
+        let path = dir.join(file);
+        if path.exists() {
+            continue;
+        }
+        fs::write(&path, HIDDEN_ENTRY)?;
+        hidden.push(path);
+    }
+    Ok(hidden)
+}
+
+/// Record `entry` in `deferred`, replacing an earlier one for the same app
+pub fn merge(deferred: &mut Vec<DeferredRemoval>, entry: DeferredRemoval) {
+    deferred.retain(|d| d.name != entry.name);
+    deferred.push(entry);
+}
+
+/// Hide `name` for `days` days instead of uninstalling it
+pub fn defer(name: &str, flatpak: bool, desktop_files: &[String], days: u32) -> Result<DeferredRemoval> {
+    let dir = applications_dir().ok_or_else(|| anyhow::anyhow!("HOME is not set"))?;
+    let hidden = hide_in(&dir, desktop_files)?;
+    if flatpak {
+        // Not fatal: a missed update is harmless next to a visible app
+        if let Err(e) = run_command("flatpak", &["mask", name], false) {
+            tracing::warn!("deferred_removal: couldn't mask {}: {}", name, e);
+        }
+    }
+    let entry = DeferredRemoval { name: name.to_string(), flatpak, remove_after: now() + u64::from(days) * DAY, hidden };
+    let mut deferred = load();
+    merge(&mut deferred, entry.clone());
+    save(&deferred)?;
+    tracing::debug!("deferred_removal: {} hidden for {} day(s)", name, days);
+    Ok(entry)
+}
+
+// Undo what defer did, without touching the saved list
+fn unhide(entry: &DeferredRemoval) {
+    for path in &entry.hidden {
+        if let Err(e) = fs::remove_file(path) {
+            tracing::warn!("deferred_removal: couldn't remove {}: {}", path.display(), e);
+        }
+    }
+    if entry.flatpak {
+        if let Err(e) = run_command("flatpak", &["mask", "--remove", &entry.name], false) {
+            tracing::warn!("deferred_removal: couldn't unmask {}: {}", entry.name, e);
+        }
+    }
+}
+
+fn forget(name: &str) -> Result<()> {
+    let mut deferred = load();
+    deferred.retain(|d| d.name != name);
+    save(&deferred)
+}
+
+/// Show a hidden app again and keep it installed
+pub fn restore(name: &str) -> Result<()> {
+    let entry = load()
+        .into_iter()
+        .find(|d| d.name == name)
+        .ok_or_else(|| anyhow::anyhow!("{} isn't waiting to be removed", name))?;
+    unhide(&entry);
+    forget(name)
+}
+
+/// Uninstall a hidden app now; it stays on the list if uninstalling fails
+pub fn uninstall(entry: &DeferredRemoval, backend: &dyn PackageBackend) -> Result<()> {
+    // A mask only stops installs and updates, so it can go afterwards
+    if entry.flatpak {
+        run_command("flatpak", &["uninstall", "--noninteractive", "-y", &entry.name], false)?;
+    } else {
+        backend.remove(std::slice::from_ref(&entry.name))?;
+    }
+    unhide(entry);
+    for outcome in hooks::run_configured(HookEvent::Remove, std::slice::from_ref(&entry.name), entry.flatpak) {
+        tracing::debug!("deferred_removal: {}", outcome.summary());
+    }
+    forget(&entry.name)
+}
@@ -0,0 +1,309 @@
+//! A digest of the last week's changes to the system, for auditing a shared
+//! machine: what was installed, upgraded and removed, by whom and with which
+//! command, and when the apt sources changed.
+//!
+//! apt's `history.log` has the changes apt made, with the command line and
+//! the user who asked for them. `dpkg.log` also has the ones made with dpkg
+//! directly, such as a downloaded .deb; what it has that apt's history
+//! already covers is left out. Sources changes are the backups
+//! [`sources_backup`](crate::sources_backup) takes before each edit. Both
+//! logs are read along with their last rotation (`.1`); apt and dpkg rotate
+//! them monthly, so that covers a week, and older compressed ones aren't read.
+
+use std::collections::HashSet;
+use std::fs;
+
+use crate::sources_backup::{self, Snapshot};
+use crate::utils::now;
+
+pub const APT_HISTORY: &str = "/var/log/apt/history.log";
+pub const DPKG_LOG: &str = "/var/log/dpkg.log";
+
+/// Days a digest covers unless asked otherwise
+pub const DEFAULT_DAYS: u32 = 7;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChangeKind {
+    Installed,
+    /// Downgrades too: the version changed in place
+    Upgraded,
+    /// Purges too
+    Removed,
+}
+
+impl ChangeKind {
+    pub const ALL: [ChangeKind; 3] = [ChangeKind::Installed, ChangeKind::Upgraded, ChangeKind::Removed];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ChangeKind::Installed => "Installed",
+            ChangeKind::Upgraded => "Upgraded",
+            ChangeKind::Removed => "Removed",
+        }
+    }
+}
+
+/// One package changed once
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    /// Local time, as the logs have it: "2024-05-06 12:45:00"
+    pub time: String,
+    pub kind: ChangeKind,
+    /// Without the architecture
+    pub package: String,
+    /// The version before, for upgrades and removals
+    pub old_version: Option<String>,
+    /// The version after, for installs and upgrades
+    pub new_version: Option<String>,
+    /// The command that made the change; None when dpkg made it alone
+    pub command: Option<String>,
+    /// Who ran the command, when it went through sudo or pkexec
+    pub requested_by: Option<String>,
+}
+
+impl Change {
+    /// "1.0-1 → 1.1-1", or the one version there is
+    pub fn versions(&self) -> String {
+        match (&self.old_version, &self.new_version) {
+            (Some(old), Some(new)) => format!("{} → {}", old, new),
+            (Some(version), None) | (None, Some(version)) => version.clone(),
+            (None, None) => String::new(),
+        }
+    }
+
+    /// "alice: apt install vlc", or whichever half is known
+    pub fn origin(&self) -> Option<String> {
+        match (&self.requested_by, &self.command) {
+            (Some(user), Some(command)) => Some(format!("{}: {}", user, command)),
+            (Some(user), None) => Some(user.clone()),
+            (None, Some(command)) => Some(command.clone()),
+            (None, None) => None,
+        }
+    }
+
+    // The same change seen in both logs has the same key
+    fn key(&self) -> (ChangeKind, String, Option<String>) {
+        let version = match self.kind {
+            ChangeKind::Removed => self.old_version.clone(),
+            _ => self.new_version.clone(),
+        };
+        (self.kind, self.package.clone(), version)
+    }
+}
+
+/// Everything that changed since a day
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Digest {
+    /// The first day covered, local: "2024-05-01"
+    pub since: String,
+    /// Newest first
+    pub changes: Vec<Change>,
+    /// The sources backups taken in the period, newest first; each was
+    /// taken just before the sources were changed
+    pub source_changes: Vec<Snapshot>,
+}
+
+impl Digest {
+    pub fn of(&self, kind: ChangeKind) -> impl Iterator<Item = &Change> {
+        self.changes.iter().filter(move |change| change.kind == kind)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty() && self.source_changes.is_empty()
+    }
+
+    /// "Since 2024-05-01: 3 installed, 12 upgraded, 1 removed, sources changed twice"
+    pub fn summary(&self) -> String {
+        if self.is_empty() {
+            return format!("No package or sources changes since {}", self.since);
+        }
+        let mut parts: Vec<String> = ChangeKind::ALL
+            .iter()
+            .map(|kind| format!("{} {}", self.of(*kind).count(), kind.label().to_lowercase()))
+            .collect();
+        match self.source_changes.len() {
+            0 => {}
+            1 => parts.push("sources changed once".to_string()),
+            2 => parts.push("sources changed twice".to_string()),
+            count => parts.push(format!("sources changed {} times", count)),
+        }
+        format!("Since {}: {}", self.since, parts.join(", "))
+    }
+
+    /// The whole digest as text, a section per kind of change
+    pub fn render(&self) -> String {
+        let mut out = format!("{}\n", self.summary());
+        for kind in ChangeKind::ALL {
+            let changes: Vec<&Change> = self.of(kind).collect();
+            if changes.is_empty() {
+                continue;
+            }
+            out.push_str(&format!("\n{} ({})\n", kind.label(), changes.len()));
+            for change in changes {
+                out.push_str(&format!("  {}  {} {}", change.time, change.package, change.versions()));
+                if let Some(origin) = change.origin() {
+                    out.push_str(&format!("  ({})", origin));
+                }
+                out.push('\n');
+            }
+        }
+        if !self.source_changes.is_empty() {
+            out.push_str(&format!("\nSources changed ({})\n", self.source_changes.len()));
+            for snapshot in &self.source_changes {
+                out.push_str(&format!("  {}  backup {}\n", snapshot.summary(), snapshot.id));
+            }
+        }
+        out
+    }
+}
+
+// "vlc:amd64" -> "vlc"
+fn without_arch(package: &str) -> String {
+    package.split(':').next().unwrap_or(package).to_string()
+}
+
+// "vlc:amd64 (3.0.20-3), libvlc5:amd64 (3.0.20-3, automatic)" -> each
+// package with the versions in its parentheses
+fn parse_package_list(list: &str) -> Vec<(String, Vec<String>)> {
+    let mut packages = Vec::new();
+    let mut rest = list;
+    while let Some(open) = rest.find('(') {
+        let Some(close) = rest[open..].find(')').map(|close| open + close) else {
+            break;
+        };
+        let name = rest[..open].trim().trim_start_matches(',').trim();
+        let versions = rest[open + 1..close]
+            .split(',')
+            .map(str::trim)
+            .filter(|version| !version.is_empty() && *version != "automatic")
+            .map(str::to_string)
+            .collect();
+        if !name.is_empty() {
+            packages.push((without_arch(name), versions));
+        }
+        rest = &rest[close + 1..];
+    }
+    packages
+}
+
+/// The changes in apt's `history.log` from the day `since` ("2024-05-01") on
+pub fn parse_history(content: &str, since: &str) -> Vec<Change> {
+    let mut changes = Vec::new();
+    for entry in content.split("\n\n") {
+        let field = |name: &str| {
+            entry
+                .lines()
+                .find_map(|line| line.strip_prefix(name)?.strip_prefix(':'))
+                .map(str::trim)
+        };
+        // "2024-05-06  12:45:00", with two spaces
+        let Some(time) = field("Start-Date").map(|date| date.split_whitespace().collect::<Vec<_>>().join(" ")) else {
+            continue;
+        };
+        if time.as_str() < since {
+            continue;
+        }
+        let command = field("Commandline").map(str::to_string);
+        // "alice (1000)"
+        let requested_by = field("Requested-By").map(|user| user.split(" (").next().unwrap_or(user).to_string());
+        for (name, kind) in [
+            ("Install", ChangeKind::Installed),
+            ("Upgrade", ChangeKind::Upgraded),
+            ("Downgrade", ChangeKind::Upgraded),
+            ("Remove", ChangeKind::Removed),
+            ("Purge", ChangeKind::Removed),
+        ] {
+            for (package, versions) in field(name).map(parse_package_list).unwrap_or_default() {
+                let (old_version, new_version) = match (kind, versions.as_slice()) {
+                    (ChangeKind::Installed, [new, ..]) => (None, Some(new.clone())),
+                    (ChangeKind::Upgraded, [old, new, ..]) => (Some(old.clone()), Some(new.clone())),
+                    (ChangeKind::Removed, [old, ..]) => (Some(old.clone()), None),
+                    _ => (None, None),
+                };
+                changes.push(Change {
+                    time: time.clone(),
+                    kind,
+                    package,
+                    old_version,
+                    new_version,
+                    command: command.clone(),
+                    requested_by: requested_by.clone(),
+                });
+            }
+        }
+    }
+    changes
+}
+
+/// The changes in `dpkg.log` from the day `since` on:
+///   2024-05-06 12:45:01 install vlc:amd64 <none> 3.0.20-3
+///   2024-05-06 12:45:02 upgrade libc6:amd64 2.36-9 2.36-9+deb12u4
+///   2024-05-06 12:45:03 remove vlc:amd64 3.0.20-3 <none>
+pub fn parse_dpkg_log(content: &str, since: &str) -> Vec<Change> {
+    let version = |field: &str| (field != "<none>").then(|| field.to_string());
+    content
+        .lines()
+        .filter_map(|line| {
+            let [date, time, action, package, old, new] = line.split_whitespace().collect::<Vec<_>>().try_into().ok()?;
+            let time = format!("{} {}", date, time);
+            if time.as_str() < since {
+                return None;
+            }
+            let kind = match action {
+                "install" => ChangeKind::Installed,
+                // The same version again is a reinstall, which changes nothing
+                "upgrade" if old != new => ChangeKind::Upgraded,
+                "remove" | "purge" => ChangeKind::Removed,
+                _ => return None,
+            };
+            Some(Change {
+                time,
+                kind,
+                package: without_arch(package),
+                old_version: version(old),
+                new_version: version(new),
+                command: None,
+                requested_by: None,
+            })
+        })
+        .collect()
+}
+
+/// apt's changes, the ones only dpkg saw, and the sources backups taken from
+/// `since_time` (the start of the day `since`) on
+pub fn build(history: &str, dpkg_log: &str, snapshots: Vec<Snapshot>, since: &str, since_time: i64) -> Digest {
+    let mut changes = parse_history(history, since);
+    let mut seen: HashSet<_> = changes.iter().map(Change::key).collect();
+    // A purge after a removal logs the same version twice
+    changes.extend(parse_dpkg_log(dpkg_log, since).into_iter().filter(|change| seen.insert(change.key())));
+    // Times are zero-padded, so they sort as text
+    changes.sort_by(|a, b| b.time.cmp(&a.time));
+    let source_changes = snapshots.into_iter().filter(|snapshot| snapshot.created >= since_time).collect();
+    Digest { since: since.to_string(), changes, source_changes }
+}
+
+// A log and its last rotation, oldest first; missing ones read as empty
+fn read_log(path: &str) -> String {
+    let rotated = fs::read_to_string(format!("{}.1", path)).unwrap_or_default();
+    let current = fs::read_to_string(path).unwrap_or_default();
+    format!("{}\n\n{}", rotated, current)
+}
+
+// The local date `days` days ago and the Unix time its day started
+fn local_day_start(days: u32) -> (String, i64) {
+    let then = now() as i64 - i64::from(days) * 86_400;
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    unsafe {
+        let time = then as libc::time_t;
+        libc::localtime_r(&time, &mut tm);
+    }
+    let date = format!("{:04}-{:02}-{:02}", tm.tm_year + 1900, tm.tm_mon + 1, tm.tm_mday);
+    let start = then - i64::from(tm.tm_hour * 3600 + tm.tm_min * 60 + tm.tm_sec);
+    (date, start)
+}
+
+/// The system's changes over the last `days` days, today included
+pub fn collect(days: u32) -> Digest {
+    let (since, since_time) = local_day_start(days);
+    build(&read_log(APT_HISTORY), &read_log(DPKG_LOG), sources_backup::list(), &since, since_time)
+}
@@ -0,0 +1,92 @@
+//! Disk space taken by installed packages and Flatpak apps.
+//!
+//! Package sizes come with the installed list
+//! ([`InstalledPackage::installed_bytes`]); Flatpak sizes from
+//! `flatpak list --app --columns=application,size`. [`strips`] lays the
+//! largest of them out as a treemap made of rows, so a frontend can draw it
+//! with nothing more than proportional widths and heights.
+
+use anyhow::Result;
+
+use crate::dpkg::InstalledPackage;
+use crate::flatpak::parse_flatpak_size;
+use crate::utils::run_command;
+
+/// One package or Flatpak app and the space it takes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Usage {
+    /// Package name or Flatpak application ID
+    pub name: String,
+    pub bytes: u64,
+    pub flatpak: bool,
+}
+
+/// The packages whose size is known
+pub fn from_installed(packages: &[InstalledPackage]) -> Vec<Usage> {
+    packages
+        .iter()
+        .filter_map(|pkg| {
+            Some(Usage { name: pkg.name.clone(), bytes: pkg.installed_bytes?, flatpak: false })
+        })
+        .collect()
+}
+
+/// Parse `flatpak list --app --columns=application,size`
+pub fn parse_flatpak_sizes(output: &str) -> Vec<Usage> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (application, size) = line.split_once('\t')?;
+            Some(Usage {
+                name: application.trim().to_string(),
+                bytes: parse_flatpak_size(size.trim())?,
+                flatpak: true,
+            })
+        })
+        .collect()
+}
+
+/// Installed Flatpak apps and their sizes; runtimes are shared between apps
+/// and removed with `flatpak uninstall --unused`, so they're left out
+pub fn flatpak_usage() -> Result<Vec<Usage>> {
+    let output = run_command("flatpak", &["list", "--app", "--columns=application,size"], false)?;
+    Ok(parse_flatpak_sizes(&output))
+}
+
+/// Largest first, then by name
+pub fn sort_largest(usage: &mut [Usage]) {
+    usage.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.name.cmp(&b.name)));
+}
+
+/// Combined size of the packages (`flatpak` false) or the Flatpak apps
+pub fn total(usage: &[Usage], flatpak: bool) -> u64 {
+    usage.iter().filter(|u| u.flatpak == flatpak).map(|u| u.bytes).sum()
+}
+
+/// Split `usage`, sorted largest first, into at most `rows` rows of roughly
+/// equal combined size. Drawing each row at a height proportional to its
+/// total and each entry at a width proportional to its size gives a treemap.
+pub fn strips(usage: &[Usage], rows: usize) -> Vec<Vec<Usage>> {
+    let total: u64 = usage.iter().map(|u| u.bytes).sum();
+    if usage.is_empty() || rows == 0 {
+        return Vec::new();
+    }
+    let target = total.div_ceil(rows as u64).max(1);
+
+    let mut strips = Vec::new();
+    let mut current = Vec::new();
+    let mut current_bytes = 0;
+    for entry in usage {
+        current_bytes += entry.bytes;
+        current.push(entry.clone());
+        // The last row takes whatever is left
+        if current_bytes >= target && strips.len() + 1 < rows {
+            strips.push(std::mem::take(&mut current));
+            current_bytes = 0;
+        }
+    }
+    if !current.is_empty() {
+        strips.push(current);
+    }
+    strips
+}
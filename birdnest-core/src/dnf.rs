@@ -0,0 +1,270 @@
+//! dnf backend for Fedora-based systems.
+//!
+//! Search and details come from `dnf`, the installed list from the rpm
+//! database. Both dnf4 and dnf5 output layouts are understood.
+
+use anyhow::Result;
+use std::collections::HashMap;
+
+use crate::dpkg::{self, InstalledPackage};
+use crate::package_manager::{parse_file_list, BackendCommand, PackageBackend, PackageDetails, PackageSummary, PackageUpdate};
+use crate::utils::run_command;
+
+/// Backend for hosts managed by dnf
+#[derive(Debug)]
+pub struct DnfBackend;
+
+impl PackageBackend for DnfBackend {
+    fn name(&self) -> &'static str {
+        "dnf"
+    }
+
+    fn search(&self, query: &str) -> Result<Vec<PackageSummary>> {
+        let output = run_command("dnf", &["search", "--quiet", query], false)?;
+        Ok(parse_dnf_search(&output))
+    }
+
+    fn info(&self, package: &str) -> Result<PackageDetails> {
+        let output = run_command("dnf", &["info", "--quiet", package], false)?;
+        parse_dnf_info(&output).ok_or_else(|| anyhow::anyhow!("No package information for {}", package))
+    }
+
+    fn list_installed(&self) -> Result<Vec<InstalledPackage>> {
+        // rpm reads its database directly, much faster than `dnf list --installed`
+        let output = run_command("rpm", &["-qa", "--queryformat", "%{NAME}\t%{VERSION}-%{RELEASE}\t%{SIZE}\n"], false)?;
+        Ok(parse_rpm_installed(&output))
+    }
+
+    fn list_upgradable(&self) -> Result<Vec<PackageUpdate>> {
+        // Not `dnf check-update`: it exits with 100 when there are updates
+        let output = run_command("dnf", &["list", "--upgrades", "--quiet"], false)?;
+        let installed = self.list_installed()?;
+        Ok(parse_dnf_upgrades(&output, &installed))
+    }
+
+    fn install(&self, packages: &[String]) -> Result<()> {
+        run_all(&self.install_commands(packages))
+    }
+
+    fn remove(&self, packages: &[String]) -> Result<()> {
+        run_all(&self.remove_commands(packages))
+    }
+
+    fn upgrade(&self, packages: &[String]) -> Result<()> {
+        run_all(&self.upgrade_commands(packages))
+    }
+
+    fn install_commands(&self, packages: &[String]) -> Vec<BackendCommand> {
+        vec![BackendCommand::new("dnf", &["install", "-y"], packages)]
+    }
+
+    fn remove_commands(&self, packages: &[String]) -> Vec<BackendCommand> {
+        vec![BackendCommand::new("dnf", &["remove", "-y"], packages)]
+    }
+
+    // With no packages dnf upgrades the whole system, same as `apt upgrade`
+    fn upgrade_commands(&self, packages: &[String]) -> Vec<BackendCommand> {
+        vec![BackendCommand::new("dnf", &["upgrade", "-y"], packages)]
+    }
+
+    fn download_commands(&self, packages: &[String]) -> Vec<BackendCommand> {
+        vec![BackendCommand::new("dnf", &["upgrade", "--downloadonly", "-y"], packages)]
+    }
+
+    // No hold: dnf needs its versionlock plugin for that, which isn't installed by default
+    fn reinstall_commands(&self, packages: &[String]) -> Vec<BackendCommand> {
+        vec![BackendCommand::new("dnf", &["reinstall", "-y"], packages)]
+    }
+
+    fn files(&self, package: &str) -> Result<Vec<String>> {
+        let output = run_command("rpm", &["-ql", package], false)?;
+        Ok(parse_file_list(&output))
+    }
+
+    fn owner(&self, path: &str) -> Result<Option<String>> {
+        // rpm fails for a path no package owns
+        let output = run_command("rpm", &["-qf", "--queryformat", "%{NAME}\n", path], false).unwrap_or_default();
+        Ok(output.lines().map(str::trim).find(|line| !line.is_empty()).map(str::to_string))
+    }
+
+    fn descriptions(&self, packages: &[String]) -> Result<HashMap<String, String>> {
+        let mut args = vec!["-q", "--queryformat", "%{NAME}\t%{SUMMARY}\n"];
+        args.extend(packages.iter().map(String::as_str));
+        let output = run_command("rpm", &args, false)?;
+        Ok(dpkg::parse_tab_descriptions(&output))
+    }
+}
+
+fn run_all(commands: &[BackendCommand]) -> Result<()> {
+    for command in commands {
+        tracing::debug!("DnfBackend: running {}", command.display());
+        run_command(&command.program, &command.args(), !command.as_user)?;
+    }
+    Ok(())
+}
+
+// "hello.x86_64" -> "hello"; names can contain dots themselves ("python3.12")
+fn strip_arch(name: &str) -> &str {
+    match name.rsplit_once('.') {
+        Some((base, arch)) if !base.is_empty() && is_arch(arch) => base,
+        _ => name,
+    }
+}
+
+fn is_arch(arch: &str) -> bool {
+    matches!(arch, "noarch" | "x86_64" | "i686" | "aarch64" | "armv7hl" | "ppc64le" | "s390x" | "src")
+}
+
+/// Parse `dnf search` output, dropping section headers and the extra
+/// architectures of multilib packages. dnf4 prints "name.arch : summary"
+/// under "=== ... ===" headers, dnf5 " name.arch<TAB>summary" under
+/// "Matched fields: ..." lines.
+pub fn parse_dnf_search(output: &str) -> Vec<PackageSummary> {
+    let mut packages = Vec::new();
+    let mut seen = std::collections::HashSet::new();
+
+    for line in output.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('=') || line.starts_with("Matched fields") || line.starts_with("Last metadata") {
+            continue;
+        }
+
+        let Some((name_part, description)) = line.split_once(" : ").or_else(|| line.split_once('\t')) else {
+            continue;
+        };
+        let name = strip_arch(name_part.trim());
+        if name.is_empty() || name.contains(' ') || !seen.insert(name.to_string()) {
+            continue;
+        }
+
+        packages.push(PackageSummary {
+            name: name.to_string(),
+            version: String::new(),
+            description: description.trim().to_string(),
+        });
+    }
+
+    packages
+}
+
+/// Parse the first record of `dnf info`. Version and release are joined
+/// the way rpm shows them; dnf4's single "Size" field is the download size.
+pub fn parse_dnf_info(output: &str) -> Option<PackageDetails> {
+    let mut details = PackageDetails::default();
+    let mut release = String::new();
+    let mut in_description = false;
+
+    for line in output.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            // "Installed packages" / "Available packages" section headers
+            continue;
+        };
+        let key = key.trim();
+        let value = value.trim();
+
+        if key.is_empty() {
+            // Description continuation lines start with "  : "
+            if in_description && !value.is_empty() {
+                details.description.push(' ');
+                details.description.push_str(value);
+            }
+            continue;
+        }
+        in_description = false;
+
+        match key {
+            "Name" => {
+                if !details.name.is_empty() {
+                    break;
+                }
+                details.name = value.to_string();
+            }
+            "Version" => details.version = value.to_string(),
+            "Release" => release = value.to_string(),
+            "Size" | "Download size" => details.download_size = parse_dnf_size(value),
+            "Installed size" => details.installed_size = parse_dnf_size(value),
+            "URL" => details.homepage = Some(value.to_string()),
+            "Description" => {
+                details.description = value.to_string();
+                in_description = true;
+            }
+            _ => {}
+        }
+    }
+
+    if details.name.is_empty() {
+        return None;
+    }
+    if !release.is_empty() {
+        details.version = format!("{}-{}", details.version, release);
+    }
+    Some(details)
+}
+
+/// "1.2 M", "345 k" (dnf4) or "1.2 MiB" (dnf5) in bytes
+pub fn parse_dnf_size(value: &str) -> Option<u64> {
+    let (number, unit) = value.split_once(' ').unwrap_or((value, ""));
+    let number: f64 = number.parse().ok()?;
+    let multiplier = match unit.trim().trim_end_matches("iB").trim_end_matches('B') {
+        "" => 1.0,
+        "k" | "K" => 1024.0,
+        "M" => 1024.0 * 1024.0,
+        "G" => 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some((number * multiplier) as u64)
+}
+
+/// Parse `rpm -qa` with a "name<TAB>version-release<TAB>size" query format,
+/// the size in bytes and optional. The gpg-pubkey entries are imported
+/// signing keys, not packages.
+pub fn parse_rpm_installed(output: &str) -> Vec<InstalledPackage> {
+    let mut packages: Vec<InstalledPackage> = output
+        .lines()
+        .filter_map(|line| {
+            let (name, rest) = line.split_once('\t')?;
+            let (version, size) = rest.split_once('\t').unwrap_or((rest, ""));
+            (name != "gpg-pubkey").then(|| InstalledPackage {
+                name: name.to_string(),
+                version: version.trim().to_string(),
+                installed_bytes: size.trim().parse().ok(),
+                ..InstalledPackage::default()
+            })
+        })
+        .collect();
+    packages.sort_by(|a, b| a.name.cmp(&b.name));
+    packages.dedup_by(|a, b| a.name == b.name);
+    packages
+}
+
+/// Parse `dnf list --upgrades` ("name.arch  version-release  repo"); the
+/// current versions come from the installed list since dnf doesn't print them
+pub fn parse_dnf_upgrades(output: &str, installed: &[InstalledPackage]) -> Vec<PackageUpdate> {
+    let mut seen = std::collections::HashSet::new();
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let name_part = fields.next()?;
+            let new_version = fields.next()?;
+            // The repo column rules out headers like "Available Upgrades"
+            fields.next()?;
+            let name = strip_arch(name_part);
+            if name == name_part || !seen.insert(name.to_string()) {
+                return None;
+            }
+            // dnf prints an epoch ("1:2.3-4") that rpm's VERSION-RELEASE leaves out
+            let new_version = new_version.split_once(':').map_or(new_version, |(_, version)| version);
+            let current_version = installed
+                .iter()
+                .find(|pkg| pkg.name == name)
+                .map(|pkg| pkg.version.clone())
+                .unwrap_or_default();
+            Some(PackageUpdate {
+                name: name.to_string(),
+                current_version,
+                new_version: new_version.to_string(),
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,402 @@
+//! Parsing of the dpkg status database, and of what apt records about the
+//! installed packages: which were installed automatically and where each
+//! installed version comes from.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::utils::run_command;
+
+/// Location of the dpkg status database
+pub const STATUS_PATH: &str = "/var/lib/dpkg/status";
+
+/// Where dpkg keeps each package's file list and maintainer scripts
+pub const INFO_DIR: &str = "/var/lib/dpkg/info";
+
+/// Where apt marks the packages it installed as dependencies
+pub const EXTENDED_STATES_PATH: &str = "/var/lib/apt/extended_states";
+
+/// The origin of an installed version no configured source offers: one
+/// installed from a .deb file, or left behind by its repository
+pub const LOCAL_ORIGIN: &str = "local";
+
+/// A package dpkg reports as installed
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstalledPackage {
+    pub name: String,
+    pub version: String,
+    /// Disk space the package takes once installed; None when the package
+    /// database doesn't say
+    pub installed_bytes: Option<u64>,
+    /// What it is built for; None when the package database doesn't say
+    pub arch: Option<Arch>,
+    /// The first line of its description; empty when the package database
+    /// doesn't say
+    pub description: String,
+    pub provenance: Provenance,
+}
+
+/// How a package came to be installed, and from where
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Provenance {
+    /// Marked `Essential: yes`; dpkg won't remove it without being forced
+    pub essential: bool,
+    /// apt installed it as a dependency, so autoremove takes it once
+    /// nothing needs it
+    pub auto_installed: bool,
+    /// The source of the installed version, e.g. "deb.debian.org/debian
+    /// bookworm/main", or [`LOCAL_ORIGIN`]; None when apt wasn't asked
+    pub origin: Option<String>,
+}
+
+impl Provenance {
+    /// "Essential", "Automatically installed", "From deb.debian.org/debian
+    /// bookworm/main", as listed beside the package
+    pub fn labels(&self) -> Vec<String> {
+        let mut labels = Vec::new();
+        if self.essential {
+            labels.push("Essential".to_string());
+        }
+        if self.auto_installed {
+            labels.push("Automatically installed".to_string());
+        }
+        match self.origin.as_deref() {
+            Some(LOCAL_ORIGIN) => labels.push("Local: no source offers this version".to_string()),
+            Some(origin) => labels.push(format!("From {}", origin)),
+            None => {}
+        }
+        labels
+    }
+}
+
+/// The architecture a package is built for, and whether builds of it for
+/// other architectures can be installed beside it
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Arch {
+    /// "amd64", "i386", "all" and so on
+    pub name: String,
+    /// The Multi-Arch field: "same", "foreign" or "allowed"; None for "no"
+    pub multi_arch: Option<String>,
+}
+
+impl Arch {
+    /// Built for an architecture other than `native`; "all" packages
+    /// run anywhere, so they aren't
+    pub fn is_foreign(&self, native: &str) -> bool {
+        !self.name.is_empty() && self.name != "all" && self.name != native
+    }
+
+    /// "amd64", or "i386, Multi-Arch: same"
+    pub fn label(&self) -> String {
+        match &self.multi_arch {
+            Some(multi_arch) => format!("{}, Multi-Arch: {}", self.name, multi_arch),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// A Multi-Arch field's value, None for "no", which dpkg also assumes when
+/// it is missing
+pub fn parse_multi_arch(value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty() && value != "no").then(|| value.to_string())
+}
+
+/// Parse the contents of the dpkg status file, keeping installed packages
+/// (including ones removed but with config files left behind)
+pub fn parse_status(content: &str) -> Vec<InstalledPackage> {
+    let mut packages = Vec::new();
+    let mut current = InstalledPackage::default();
+    let mut is_installed = false;
+
+    // Optimized parsing: single pass through the file
+    for line in content.lines() {
+        if let Some(name) = line.strip_prefix("Package: ") {
+            // Save previous package if it was installed
+            if is_installed && !current.name.is_empty() {
+                packages.push(std::mem::take(&mut current));
+            }
+            current = InstalledPackage { name: name.trim().to_string(), ..InstalledPackage::default() };
+            is_installed = false;
+        } else if let Some(version) = line.strip_prefix("Version: ") {
+            current.version = version.trim().to_string();
+        } else if let Some(size) = line.strip_prefix("Installed-Size: ") {
+            current.installed_bytes = kib_to_bytes(size);
+        } else if let Some(arch) = line.strip_prefix("Architecture: ") {
+            current.arch.get_or_insert_with(Arch::default).name = arch.trim().to_string();
+        } else if let Some(multi_arch) = line.strip_prefix("Multi-Arch: ") {
+            current.arch.get_or_insert_with(Arch::default).multi_arch = parse_multi_arch(multi_arch);
+        } else if let Some(description) = line.strip_prefix("Description: ") {
+            current.description = description.trim().to_string();
+        } else if let Some(essential) = line.strip_prefix("Essential: ") {
+            current.provenance.essential = essential.trim() == "yes";
+        } else if let Some(status) = line.strip_prefix("Status: ") {
+            is_installed = status.contains("install ok installed") || status.contains("install ok config-files");
+        } else if line.is_empty() {
+            // End of package entry - save if installed
+            if is_installed && !current.name.is_empty() {
+                packages.push(std::mem::take(&mut current));
+            }
+            is_installed = false;
+        }
+    }
+
+    // Don't forget the last package if file doesn't end with newline
+    if is_installed && !current.name.is_empty() {
+        packages.push(current);
+    }
+
+    packages
+}
+
+// dpkg records Installed-Size in KiB
+fn kib_to_bytes(value: &str) -> Option<u64> {
+    value.trim().parse::<u64>().ok().map(|kib| kib * 1024)
+}
+
+/// Read and parse the dpkg status file
+pub fn read_status() -> std::io::Result<Vec<InstalledPackage>> {
+    let content = std::fs::read_to_string(STATUS_PATH)?;
+    Ok(parse_status(&content))
+}
+
+/// The package a file in [`INFO_DIR`] lists the files of: "vlc.list" or
+/// "libc6:amd64.list" (Multi-Arch: same packages) give "vlc" and "libc6"
+pub fn package_of_list_file(file_name: &str) -> Option<&str> {
+    let package = file_name.strip_suffix(".list")?;
+    let package = package.split(':').next().unwrap_or(package);
+    (!package.is_empty()).then_some(package)
+}
+
+/// When each installed package was last installed or upgraded, as Unix
+/// time: dpkg rewrites a package's file list then. Empty without dpkg.
+pub fn read_change_times() -> HashMap<String, i64> {
+    let Ok(entries) = std::fs::read_dir(INFO_DIR) else {
+        return HashMap::new();
+    };
+    let mut times: HashMap<String, i64> = HashMap::new();
+    for entry in entries.flatten() {
+        let file_name = entry.file_name();
+        let Some(package) = package_of_list_file(&file_name.to_string_lossy()).map(str::to_string) else {
+            continue;
+        };
+        let Some(modified) = entry.metadata().and_then(|metadata| metadata.modified()).ok() else {
+            continue;
+        };
+        let time = modified.duration_since(std::time::UNIX_EPOCH).map_or(0, |age| age.as_secs() as i64);
+        // Of a package's architectures, the latest change counts
+        let latest = times.entry(package).or_insert(time);
+        *latest = (*latest).max(time);
+    }
+    times
+}
+
+/// The short descriptions (the first line of `Description:`) the status
+/// file contents give for the packages in `names`
+pub fn parse_descriptions(content: &str, names: &[String]) -> HashMap<String, String> {
+    let wanted: HashSet<&str> = names.iter().map(String::as_str).collect();
+    let mut descriptions = HashMap::new();
+    let mut current = "";
+    for line in content.lines() {
+        if let Some(name) = line.strip_prefix("Package: ") {
+            current = name.trim();
+        } else if let Some(description) = line.strip_prefix("Description: ") {
+            // A Multi-Arch package has a stanza per architecture; the first will do
+            if wanted.contains(current) && !descriptions.contains_key(current) {
+                descriptions.insert(current.to_string(), description.trim().to_string());
+            }
+        }
+    }
+    descriptions
+}
+
+/// Read the status file for the descriptions of the packages in `names`
+pub fn read_descriptions(names: &[String]) -> std::io::Result<HashMap<String, String>> {
+    let content = std::fs::read_to_string(STATUS_PATH)?;
+    Ok(parse_descriptions(&content, names))
+}
+
+/// Ask dpkg-query for the descriptions, for when the status file can't be read
+pub fn query_descriptions(names: &[String]) -> anyhow::Result<HashMap<String, String>> {
+    let mut args = vec!["-W", "-f=${Package}\t${binary:Summary}\n", "--"];
+    args.extend(names.iter().map(String::as_str));
+    let output = run_command("dpkg-query", &args, false)?;
+    Ok(parse_tab_descriptions(&output))
+}
+
+/// The package owning `path` in `dpkg-query -S` output
+/// ("libc6:amd64, libc6:i386: /path"), without its architecture; diversion
+/// lines are skipped
+pub fn parse_owner(output: &str, path: &str) -> Option<String> {
+    output.lines().filter(|line| !line.starts_with("diversion by ")).find_map(|line| {
+        let (packages, owned) = line.rsplit_once(": ")?;
+        let package = packages.split(", ").next()?;
+        (owned == path).then(|| package.split(':').next().unwrap_or(package).to_string())
+    })
+}
+
+/// Parse "name<TAB>description" lines, as dpkg-query and rpm print them
+/// with a query format
+pub fn parse_tab_descriptions(output: &str) -> HashMap<String, String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (name, description) = line.split_once('\t')?;
+            let (name, description) = (name.trim(), description.trim());
+            (!name.is_empty() && !description.is_empty()).then(|| (name.to_string(), description.to_string()))
+        })
+        .collect()
+}
+
+/// Parse `dpkg-query -W` output with the query format in [`query_installed`]:
+/// name, version, size, architecture, Multi-Arch, Essential and summary,
+/// tab-separated, of which all but the first two are optional
+pub fn parse_query_output(output: &str) -> Vec<InstalledPackage> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t').map(str::trim);
+            let name = fields.next().filter(|name| !name.is_empty())?;
+            let version = fields.next()?;
+            let [size, arch, multi_arch, essential, summary] = std::array::from_fn(|_| fields.next().unwrap_or(""));
+            Some(InstalledPackage {
+                name: name.to_string(),
+                version: version.to_string(),
+                installed_bytes: kib_to_bytes(size),
+                arch: (!arch.is_empty()).then(|| Arch { name: arch.to_string(), multi_arch: parse_multi_arch(multi_arch) }),
+                description: summary.to_string(),
+                provenance: Provenance { essential: essential == "yes", ..Provenance::default() },
+            })
+        })
+        .collect()
+}
+
+/// List installed packages via dpkg-query, for when the status file can't be read
+pub fn query_installed() -> anyhow::Result<Vec<InstalledPackage>> {
+    let format = "-f=${Package}\t${Version}\t${Installed-Size}\t${Architecture}\t${Multi-Arch}\t${Essential}\t${binary:Summary}\n";
+    let output = run_command("dpkg-query", &["-W", format], false)?;
+    Ok(parse_query_output(&output))
+}
+
+/// Installed packages in the metapackages section (or a component's, such
+/// as "universe/metapackages") in `dpkg-query -W` output of name, section
+/// and abbreviated status
+pub fn parse_meta_packages(output: &str) -> HashSet<String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let (name, section, status) = (fields.next()?.trim(), fields.next()?.trim(), fields.next()?);
+            let meta = section.rsplit('/').next() == Some("metapackages");
+            (meta && status.starts_with("ii") && !name.is_empty()).then(|| name.to_string())
+        })
+        .collect()
+}
+
+/// The installed meta-packages: those that exist to pull in a desktop or a
+/// set of defaults
+pub fn meta_packages() -> anyhow::Result<HashSet<String>> {
+    let output = run_command("dpkg-query", &["-W", "-f=${Package}\t${Section}\t${db:Status-Abbrev}\n"], false)?;
+    Ok(parse_meta_packages(&output))
+}
+
+/// The architectures of each package apt's extended_states marks
+/// `Auto-Installed: 1`, by name
+pub fn parse_extended_states(content: &str) -> HashMap<String, Vec<String>> {
+    let mut auto: HashMap<String, Vec<String>> = HashMap::new();
+    for stanza in content.split("\n\n") {
+        let field = |key: &str| stanza.lines().find_map(|line| line.strip_prefix(key)).map(str::trim);
+        if let (Some(name), Some("1")) = (field("Package:"), field("Auto-Installed:")) {
+            auto.entry(name.to_string()).or_default().push(field("Architecture:").unwrap_or("").to_string());
+        }
+    }
+    auto
+}
+
+/// The source of each package's installed version (the `***` one) in
+/// `apt-cache policy` output, by name: the first repository listing it, or
+/// [`LOCAL_ORIGIN`] when only dpkg's status file does
+pub fn parse_policy_origins(output: &str) -> HashMap<String, String> {
+    let mut origins: HashMap<String, String> = HashMap::new();
+    let mut package: Option<&str> = None;
+    let mut installed = false;
+    for line in output.lines() {
+        // "vim:", or "libc6:i386:" for another architecture
+        if !line.starts_with(' ') {
+            package = line.strip_suffix(':').and_then(|name| name.split(':').next());
+            installed = false;
+            continue;
+        }
+        let Some(package) = package else {
+            continue;
+        };
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match fields.as_slice() {
+            ["***", ..] => installed = true,
+            // "500 http://deb.debian.org/debian bookworm/main amd64 Packages";
+            // the first repository wins
+            [_, uri, rest @ ..]
+                if installed && uri.contains(":/") && origins.get(package).is_none_or(|origin| origin == LOCAL_ORIGIN) =>
+            {
+                let location = uri.split_once(':').map_or(*uri, |(_, location)| location).trim_matches('/');
+                let origin = match rest.first() {
+                    Some(suite) if *suite != "Packages" => format!("{} {}", location, suite),
+                    _ => location.to_string(),
+                };
+                origins.insert(package.to_string(), origin);
+            }
+            // "100 /var/lib/dpkg/status"
+            [_, path] if installed && path.starts_with('/') => {
+                origins.entry(package.to_string()).or_insert_with(|| LOCAL_ORIGIN.to_string());
+            }
+            // Another version: "2:9.1.0016-1 500"
+            [_, _] => installed = false,
+            _ => {}
+        }
+    }
+    origins
+}
+
+/// Fills in what apt knows about `packages`: which it installed
+/// automatically, and where each installed version comes from. Without apt
+/// they stay as they were.
+pub fn read_apt_records(packages: &mut [InstalledPackage]) {
+    let auto = std::fs::read_to_string(EXTENDED_STATES_PATH)
+        .map(|content| parse_extended_states(&content))
+        .unwrap_or_default();
+    let mut names: Vec<&str> = packages.iter().map(|package| package.name.as_str()).collect();
+    names.sort_unstable();
+    names.dedup();
+    if names.is_empty() {
+        return;
+    }
+    let mut args = vec!["policy"];
+    args.extend(names);
+    let origins = match run_command("apt-cache", &args, false) {
+        Ok(output) => parse_policy_origins(&output),
+        Err(e) => {
+            tracing::debug!("dpkg: no package origins from apt-cache: {}", e);
+            HashMap::new()
+        }
+    };
+    for package in packages {
+        let arch = package.arch.as_ref().map_or("", |arch| arch.name.as_str());
+        // extended_states files "all" packages under the native architecture
+        package.provenance.auto_installed = auto
+            .get(&package.name)
+            .is_some_and(|arches| matches!(arch, "" | "all") || arches.iter().any(|marked| marked == arch));
+        if let Some(origin) = origins.get(&package.name) {
+            package.provenance.origin = Some(origin.clone());
+        }
+    }
+}
+
+/// The architecture dpkg installs packages for by default, e.g. "amd64";
+/// None where there is no dpkg
+pub fn native_architecture() -> Option<String> {
+    run_command("dpkg", &["--print-architecture"], false)
+        .ok()
+        .map(|output| output.trim().to_string())
+        .filter(|arch| !arch.is_empty())
+}
@@ -0,0 +1,316 @@
+//! How BirdNest gets root for package operations: pkexec, `sudo`, `sudo -A`
+//! with an askpass program, or the privileged [`helper`](crate::helper).
+//!
+//! Minimal sessions (a bare compositor, a TTY) often have no polkit agent,
+//! where pkexec can only fail, so the method is a setting. The config file
+//! holds the persistent choice; `BIRDNEST_ELEVATION` (or `--elevation`)
+//! overrides it for one session, and is inherited by the dialog windows the
+//! GUI spawns.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::config::Config;
+use crate::helper;
+use crate::utils::{self, find_in_path, is_executable};
+
+/// Environment variable holding the session's elevation preference
+pub const SESSION_ENV: &str = "BIRDNEST_ELEVATION";
+
+/// Where the privileged helper is installed: what pkexec starts the daemon as,
+/// and what its polkit policy names
+pub const HELPER_PATH: &str = "/usr/libexec/birdnest/birdnest-helper";
+
+// Checked in order when SUDO_ASKPASS isn't set
+const ASKPASS_CANDIDATES: &[&str] = &[
+    "/usr/bin/ksshaskpass",
+    "/usr/libexec/openssh/gnome-ssh-askpass",
+    "/usr/lib/openssh/gnome-ssh-askpass",
+    "/usr/bin/lxqt-openssh-askpass",
+    "/usr/bin/ssh-askpass",
+    "/usr/lib/ssh/x11-ssh-askpass",
+];
+
+// Processes that register a polkit authentication agent (comm names, which
+// the kernel cuts to 15 characters)
+const POLKIT_AGENTS: &[&str] = &[
+    "gnome-shell",
+    "cosmic-osd",
+    "polkit-gnome-au",
+    "polkit-kde-auth",
+    "polkit-mate-aut",
+    "lxqt-policykit-",
+    "lxpolkit",
+    "xfce-polkit",
+    "hyprpolkitagent",
+    "mate-polkit",
+    "soteria",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ElevationMethod {
+    /// The helper if installed, else pkexec in a graphical session (`sudo -A`
+    /// without a polkit agent) and sudo in a terminal
+    #[default]
+    Auto,
+    Pkexec,
+    Sudo,
+    /// `sudo -A`, asking for the password through `SUDO_ASKPASS`
+    SudoAskpass,
+    /// The privileged helper daemon, authorized through pkexec once per session
+    Helper,
+}
+
+impl ElevationMethod {
+    pub const ALL: [ElevationMethod; 5] = [
+        ElevationMethod::Auto,
+        ElevationMethod::Pkexec,
+        ElevationMethod::Sudo,
+        ElevationMethod::SudoAskpass,
+        ElevationMethod::Helper,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ElevationMethod::Auto => "auto",
+            ElevationMethod::Pkexec => "pkexec",
+            ElevationMethod::Sudo => "sudo",
+            ElevationMethod::SudoAskpass => "sudo-askpass",
+            ElevationMethod::Helper => "helper",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|method| method.name() == value.trim())
+            .ok_or_else(|| {
+                let names: Vec<&str> = Self::ALL.iter().map(|m| m.name()).collect();
+                anyhow::anyhow!("Unknown elevation method '{}' (expected one of: {})", value, names.join(", "))
+            })
+    }
+
+    /// What a command line run this way starts with, for previews like "pkexec apt-get remove ..."
+    pub fn command_prefix(self) -> &'static str {
+        match self {
+            ElevationMethod::Auto | ElevationMethod::Pkexec => "pkexec",
+            ElevationMethod::Sudo => "sudo",
+            ElevationMethod::SudoAskpass => "sudo -A",
+            ElevationMethod::Helper => HELPER_PATH,
+        }
+    }
+}
+
+/// What the system offers, as reported by [`detect`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Availability {
+    pub pkexec: bool,
+    pub polkit_agent: bool,
+    pub sudo: bool,
+    pub askpass: Option<PathBuf>,
+    pub helper: bool,
+    /// Running inside an X11 or Wayland session
+    pub graphical: bool,
+}
+
+impl Availability {
+    /// Whether `method` can be used right now; pkexec needs an agent in a
+    /// graphical session (outside one it falls back to its text agent)
+    pub fn supports(&self, method: ElevationMethod) -> bool {
+        match method {
+            ElevationMethod::Auto => self.pkexec || self.sudo || self.helper,
+            ElevationMethod::Pkexec => self.pkexec && (self.polkit_agent || !self.graphical),
+            ElevationMethod::Sudo => self.sudo,
+            ElevationMethod::SudoAskpass => self.sudo && self.askpass.is_some(),
+            // Starting the daemon goes through pkexec
+            ElevationMethod::Helper => self.helper && self.supports(ElevationMethod::Pkexec),
+        }
+    }
+
+    /// The concrete method `preferred` turns into
+    pub fn resolve(&self, preferred: ElevationMethod) -> Result<ElevationMethod> {
+        if preferred != ElevationMethod::Auto {
+            if self.supports(preferred) {
+                return Ok(preferred);
+            }
+            anyhow::bail!("{}", unavailable_reason(preferred, self));
+        }
+
+        let order: &[ElevationMethod] = if self.graphical {
+            &[ElevationMethod::Helper, ElevationMethod::Pkexec, ElevationMethod::SudoAskpass]
+        } else {
+            &[ElevationMethod::Helper, ElevationMethod::Sudo, ElevationMethod::Pkexec]
+        };
+        order
+            .iter()
+            .copied()
+            .find(|method| self.supports(*method))
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "No way to gain administrator privileges: install polkit and an authentication agent, or sudo. Run as root otherwise."
+                )
+            })
+    }
+}
+
+fn unavailable_reason(method: ElevationMethod, availability: &Availability) -> String {
+    match method {
+        ElevationMethod::Pkexec if !availability.pkexec => "pkexec is not installed".to_string(),
+        ElevationMethod::Pkexec => {
+            "pkexec needs a polkit authentication agent, and none is running in this session".to_string()
+        }
+        ElevationMethod::Sudo => "sudo is not available. Please install sudo or run as root.".to_string(),
+        ElevationMethod::SudoAskpass if !availability.sudo => {
+            "sudo is not available. Please install sudo or run as root.".to_string()
+        }
+        ElevationMethod::SudoAskpass => {
+            "sudo -A needs an askpass program: set SUDO_ASKPASS or install ssh-askpass".to_string()
+        }
+        ElevationMethod::Helper if !availability.helper => {
+            format!("The privileged helper is not installed ({})", HELPER_PATH)
+        }
+        ElevationMethod::Helper => "The privileged helper is started through pkexec, which can't ask for a password here".to_string(),
+        ElevationMethod::Auto => "No elevation method is available".to_string(),
+    }
+}
+
+/// Probe the system for elevation methods
+pub fn detect() -> Availability {
+    Availability {
+        pkexec: find_in_path("pkexec").is_some(),
+        polkit_agent: polkit_agent_running(),
+        sudo: find_in_path("sudo").is_some(),
+        askpass: find_askpass(),
+        helper: is_executable(Path::new(HELPER_PATH)),
+        graphical: is_graphical_session(),
+    }
+}
+
+/// The preference for this session: `BIRDNEST_ELEVATION` if set, else the config file
+pub fn preference() -> ElevationMethod {
+    if let Ok(value) = std::env::var(SESSION_ENV) {
+        match ElevationMethod::parse(&value) {
+            Ok(method) => return method,
+            Err(e) => tracing::warn!("Ignoring {}: {}", SESSION_ENV, e),
+        }
+    }
+    Config::load().map(|config| config.elevation).unwrap_or_default()
+}
+
+/// Override the preference for this process and everything it spawns
+pub fn set_session_preference(method: ElevationMethod) {
+    std::env::set_var(SESSION_ENV, method.name());
+}
+
+/// Build `cmd args` to run as root with the preferred method
+pub fn command(cmd: &str, args: &[&str]) -> Result<(Command, ElevationMethod)> {
+    let method = detect().resolve(preference())?;
+    Ok((utils::CommandBuilder::new(cmd).args(args).build_with(method)?, method))
+}
+
+/// Whether root is available right now without a password prompt: already
+/// root, a sudo NOPASSWD rule (or still-valid cached credentials), or a
+/// polkit rule that authorizes pkexec outright
+pub fn passwordless() -> bool {
+    if utils::is_root() {
+        return true;
+    }
+    detect().resolve(preference()).map(is_passwordless).unwrap_or(false)
+}
+
+/// Whether `method` can elevate without asking for a password
+pub fn is_passwordless(method: ElevationMethod) -> bool {
+    match method {
+        // `-n` fails instead of prompting when a password would be needed
+        ElevationMethod::Sudo | ElevationMethod::SudoAskpass => Command::new("sudo")
+            .args(["-n", "true"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .map(|status| status.success())
+            .unwrap_or(false),
+        // Without --allow-user-interaction pkcheck only succeeds if no authentication is needed
+        ElevationMethod::Auto | ElevationMethod::Pkexec => Command::new("pkcheck")
+            .args(["--action-id", "org.freedesktop.policykit.exec", "--process"])
+            .arg(std::process::id().to_string())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .map(|status| status.success())
+            .unwrap_or(false),
+        // Once started, the daemon runs commands until it goes idle
+        ElevationMethod::Helper => helper::is_running(),
+    }
+}
+
+/// Prefix for previewing an elevated command line, e.g. "sudo -A"
+pub fn preferred_prefix() -> &'static str {
+    detect().resolve(preference()).unwrap_or(ElevationMethod::Pkexec).command_prefix()
+}
+
+/// Build `cmd args` to run as root with a specific, already resolved method,
+/// without the session environment [`utils::CommandBuilder`] adds
+pub fn command_with(method: ElevationMethod, cmd: &str, args: &[&str]) -> Result<Command> {
+    let mut command = match method {
+        ElevationMethod::Auto | ElevationMethod::Pkexec => {
+            let mut pkexec = Command::new("pkexec");
+            pkexec.arg(cmd);
+            pkexec
+        }
+        ElevationMethod::Sudo => {
+            let mut sudo = Command::new("sudo");
+            sudo.arg(cmd);
+            sudo
+        }
+        ElevationMethod::SudoAskpass => {
+            let askpass = find_askpass()
+                .ok_or_else(|| anyhow::anyhow!("{}", unavailable_reason(method, &detect())))?;
+            let mut sudo = Command::new("sudo");
+            sudo.env("SUDO_ASKPASS", askpass).arg("-A").arg(cmd);
+            sudo
+        }
+        // `birdnest helper run` hands the command to the daemon
+        ElevationMethod::Helper if helper::runs(cmd, args) => {
+            let mut client = Command::new(std::env::current_exe()?);
+            client.args(["helper", "run", cmd, "--"]);
+            client
+        }
+        // What the daemon doesn't run, like scripts or local packages, still
+        // asks through pkexec
+        ElevationMethod::Helper => {
+            let mut pkexec = Command::new("pkexec");
+            pkexec.arg(cmd);
+            pkexec
+        }
+    };
+    command.args(args);
+    Ok(command)
+}
+
+fn is_graphical_session() -> bool {
+    std::env::var_os("DISPLAY").is_some() || std::env::var_os("WAYLAND_DISPLAY").is_some()
+}
+
+fn find_askpass() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os("SUDO_ASKPASS").map(PathBuf::from) {
+        if is_executable(&path) {
+            return Some(path);
+        }
+    }
+    ASKPASS_CANDIDATES.iter().map(PathBuf::from).find(|path| is_executable(path))
+}
+
+fn polkit_agent_running() -> bool {
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return false;
+    };
+    entries.flatten().any(|entry| {
+        std::fs::read_to_string(entry.path().join("comm"))
+            .map(|comm| POLKIT_AGENTS.contains(&comm.trim()))
+            .unwrap_or(false)
+    })
+}
@@ -0,0 +1,210 @@
+//! Development environments in pikman containers: a TOML template names a
+//! container type, the packages to install inside it and the tools to export
+//! to the host, and creating one runs `pikman init`, `install` and `export`
+//! in order. BirdNest ships a few templates; more go in
+//! `~/.config/birdnest/environments/*.toml`, where a template with the same
+//! name as a built-in one replaces it.
+//!
+//! ```toml
+//! name = "Rust dev on Arch"
+//! description = "Stable Rust with cargo, a linker and a debugger"
+//! manager = "arch"
+//! container = "rust-dev"
+//! packages = ["rustup", "base-devel", "gdb"]
+//! export = ["gdb"]
+//! ```
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::package_manager::BackendCommand;
+use crate::utils::{self, confirm, run_command_interactive};
+
+/// Container types `pikman init --manager` takes
+pub const MANAGERS: &[&str] = &["arch", "fedora", "alpine"];
+
+const BUILTIN: &[&str] = &[
+    r#"
+name = "Rust dev on Arch"
+description = "Rust through rustup, with a C toolchain for build scripts and a debugger"
+manager = "arch"
+container = "rust-dev"
+packages = ["rustup", "base-devel", "gdb", "git"]
+export = ["gdb"]
+"#,
+    r#"
+name = "Python data science on Fedora"
+description = "Python with NumPy, pandas, Matplotlib and JupyterLab"
+manager = "fedora"
+container = "python-data"
+packages = ["python3", "python3-numpy", "python3-pandas", "python3-matplotlib", "python3-jupyterlab"]
+export = ["jupyter-lab"]
+"#,
+    r#"
+name = "C and C++ on Alpine"
+description = "A small musl toolchain with CMake and a debugger"
+manager = "alpine"
+container = "cxx-dev"
+packages = ["build-base", "cmake", "gdb", "git"]
+export = []
+"#,
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Template {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// The container type, one of [`MANAGERS`]
+    pub manager: String,
+    /// The container to create, unless another name is given
+    pub container: String,
+    /// Installed inside the container
+    pub packages: Vec<String>,
+    /// Programs from those packages exported to the host's application menu
+    #[serde(default)]
+    pub export: Vec<String>,
+}
+
+impl Template {
+    pub fn from_toml(text: &str) -> Result<Self> {
+        let template: Template = toml::from_str(text)?;
+        template.check()?;
+        Ok(template)
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// Every name goes on a pikman command line, so each has to be one word
+    /// that can't pass for a flag
+    pub fn check(&self) -> Result<()> {
+        if !MANAGERS.contains(&self.manager.as_str()) {
+            anyhow::bail!("'{}' has an unknown manager '{}' (expected one of: {})", self.name, self.manager, MANAGERS.join(", "));
+        }
+        if self.packages.is_empty() {
+            anyhow::bail!("'{}' doesn't list any packages", self.name);
+        }
+        check_container(&self.container)?;
+        for word in self.packages.iter().chain(&self.export) {
+            if !is_word(word) {
+                anyhow::bail!("'{}' lists '{}', which isn't a package or program name", self.name, word);
+            }
+        }
+        Ok(())
+    }
+
+    /// pikman's flag for installing into this kind of container
+    pub fn distro_flag(&self) -> &'static str {
+        match self.manager.as_str() {
+            "fedora" => "--fedora",
+            "alpine" => "--alpine",
+            _ => "--aur",
+        }
+    }
+
+    /// What creating the environment runs, in order: create `container`,
+    /// install the packages into it and export `exports`. All run as the
+    /// user, like the rest of pikman.
+    pub fn steps(&self, container: &str, exports: &[String]) -> Vec<BackendCommand> {
+        let mut steps = vec![
+            BackendCommand::as_user("pikman", &["init", container, "--manager", &self.manager], &[]),
+            BackendCommand::as_user("pikman", &["install", self.distro_flag(), "--name", container, "-y"], &self.packages),
+        ];
+        steps.extend(exports.iter().map(|tool| BackendCommand::as_user("pikman", &["export", tool, "--name", container], &[])));
+        steps
+    }
+}
+
+/// A container name has to be one word of letters, digits, '-', '_' and '.'
+pub fn check_container(name: &str) -> Result<()> {
+    let valid = is_word(name) && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        anyhow::bail!("Container name '{}' has to be one word like \"rust-dev\"", name);
+    }
+    Ok(())
+}
+
+fn is_word(word: &str) -> bool {
+    !word.is_empty() && !word.starts_with('-') && !word.contains(char::is_whitespace) && !word.contains('/')
+}
+
+/// The templates BirdNest ships
+pub fn builtin() -> Vec<Template> {
+    BUILTIN.iter().filter_map(|text| Template::from_toml(text).ok()).collect()
+}
+
+/// Where user templates go
+pub fn user_dir() -> Option<PathBuf> {
+    let home = std::env::var_os("HOME")?;
+    Some(PathBuf::from(home).join(".config").join("birdnest").join("environments"))
+}
+
+/// Built-in templates followed by the user's from `dir`, sorted by name; a
+/// user template replaces a built-in one of the same name, and one that
+/// doesn't parse is skipped with a warning
+pub fn load_from(dir: Option<&Path>) -> Vec<Template> {
+    let mut templates = builtin();
+    let mut files: Vec<PathBuf> = dir
+        .and_then(|dir| std::fs::read_dir(dir).ok())
+        .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+        .unwrap_or_default();
+    files.retain(|path| path.extension().is_some_and(|ext| ext == "toml"));
+    files.sort();
+    for path in files {
+        match std::fs::read_to_string(&path).map_err(anyhow::Error::from).and_then(|text| Template::from_toml(&text)) {
+            Ok(template) => {
+                templates.retain(|t| t.name != template.name);
+                templates.push(template);
+            }
+            Err(e) => tracing::warn!("environments: skipping {}: {}", path.display(), e),
+        }
+    }
+    templates.sort_by(|a, b| a.name.cmp(&b.name));
+    templates
+}
+
+pub fn load() -> Vec<Template> {
+    load_from(user_dir().as_deref())
+}
+
+/// A template by name, or from a TOML file when `name` is a path to one
+pub fn find(name: &str) -> Result<Template> {
+    let path = Path::new(name);
+    if path.extension().is_some_and(|ext| ext == "toml") && path.exists() {
+        let text = std::fs::read_to_string(path).with_context(|| format!("Couldn't read {}", path.display()))?;
+        return Template::from_toml(&text);
+    }
+    let templates = load();
+    templates
+        .iter()
+        .find(|t| t.name.eq_ignore_ascii_case(name) || t.container == name)
+        .cloned()
+        .ok_or_else(|| {
+            let names: Vec<&str> = templates.iter().map(|t| t.name.as_str()).collect();
+            anyhow::anyhow!("No environment template '{}' (available: {})", name, names.join(", "))
+        })
+}
+
+/// Create `template`'s environment in `container` (its default when None),
+/// showing the commands first and asking unless `yes`
+pub fn create(template: &Template, container: Option<&str>, yes: bool) -> Result<()> {
+    let container = container.unwrap_or(&template.container);
+    check_container(container)?;
+    let steps = template.steps(container, &template.export);
+    println!("{}:", template.name);
+    for step in &steps {
+        println!("  {}", step.display());
+    }
+    if !yes && !confirm(&format!("Create the {} environment?", container))? {
+        utils::print_info("Cancelled");
+        return Ok(());
+    }
+    for step in &steps {
+        run_command_interactive(&step.program, &step.args(), !step.as_user)?;
+    }
+    utils::print_success(&format!("Environment {} is ready: birdnest pikman enter {}", container, container));
+    Ok(())
+}
@@ -0,0 +1,38 @@
+//! Typed events backends publish for the frontends to react to.
+//!
+//! Package transactions report when they start, their output, when they wait
+//! for a password and how they end; the installed-package cache reports when
+//! it goes stale. Every GUI window subscribes to the same bus instead of
+//! having each dialog pass its results back by hand. Publishing with nobody
+//! subscribed, as in the CLI, costs nothing.
+
+use std::sync::LazyLock;
+use tokio::sync::broadcast;
+
+// Events a slow subscriber can fall behind by before it misses some
+const CAPACITY: usize = 256;
+
+static BUS: LazyLock<broadcast::Sender<Event>> = LazyLock::new(|| broadcast::channel(CAPACITY).0);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// Transaction `id` got its turn and is running
+    TransactionStarted { id: u64, label: String },
+    /// A line of the transaction's output
+    Progress { id: u64, line: String },
+    /// The transaction is waiting for the user, e.g. for a password
+    NeedsInput { id: u64, prompt: String },
+    Finished { id: u64, result: Result<(), String> },
+    /// The installed-package cache was dropped, so installed lists are stale
+    CacheInvalidated,
+}
+
+pub fn publish(event: Event) {
+    // Only fails when nobody is subscribed
+    let _ = BUS.send(event);
+}
+
+/// Every event published from now on
+pub fn subscribe() -> broadcast::Receiver<Event> {
+    BUS.subscribe()
+}
@@ -0,0 +1,916 @@
+//! Flatpak application management.
+
+use anyhow::Result;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::hooks::{self, HookEvent};
+use crate::output::{self, PackageRow};
+use crate::package_manager::PackageUpdate;
+use crate::plan;
+use crate::progress;
+use crate::utils::{self, confirm, run_command, run_command_interactive};
+
+pub struct FlatpakManager;
+
+impl FlatpakManager {
+    pub fn new() -> Result<Self> {
+        #[cfg(debug_assertions)]
+        tracing::debug!("FlatpakManager::new() called");
+        use std::process::Command;
+        #[cfg(debug_assertions)]
+        tracing::debug!("Checking if flatpak is installed...");
+        if Command::new("flatpak").arg("--version").output().is_err() {
+            #[cfg(debug_assertions)]
+            tracing::debug!("ERROR: flatpak is not installed");
+            anyhow::bail!("flatpak is not installed");
+        }
+        #[cfg(debug_assertions)]
+        tracing::debug!("flatpak is installed, FlatpakManager created successfully");
+        Ok(Self)
+    }
+
+    pub fn install(&self, packages: &[String], yes: bool) -> Result<()> {
+        #[cfg(debug_assertions)]
+        tracing::debug!("FlatpakManager::install() called with {} packages, yes={}", packages.len(), yes);
+        if packages.is_empty() {
+            #[cfg(debug_assertions)]
+            tracing::debug!("ERROR: No packages specified");
+            anyhow::bail!("No packages specified");
+        }
+
+        #[cfg(debug_assertions)]
+        tracing::debug!("Packages to install: {:?}", packages);
+
+        if !yes && !confirm(&format!("Install {} flatpak(s)?", packages.len()))? {
+            #[cfg(debug_assertions)]
+            tracing::debug!("Installation cancelled by user");
+            utils::print_info("Installation cancelled");
+            return Ok(());
+        }
+
+        let mut args = vec!["install", "-y"];
+        args.extend(packages.iter().map(|s| s.as_str()));
+        #[cfg(debug_assertions)]
+        tracing::debug!("Executing command: flatpak {}", args.join(" "));
+        progress::run_with_spinner("Installing", "flatpak", &args, false)?;
+
+        #[cfg(debug_assertions)]
+        tracing::debug!("Installation completed successfully");
+        utils::print_success(&format!("Successfully installed {} flatpak(s)", packages.len()));
+        hooks::run_and_print(HookEvent::Install, packages, true);
+        Ok(())
+    }
+
+    pub fn remove(&self, packages: &[String], yes: bool) -> Result<()> {
+        #[cfg(debug_assertions)]
+        tracing::debug!("FlatpakManager::remove() called with {} packages, yes={}", packages.len(), yes);
+        if packages.is_empty() {
+            #[cfg(debug_assertions)]
+            tracing::debug!("ERROR: No packages specified");
+            anyhow::bail!("No packages specified");
+        }
+
+        #[cfg(debug_assertions)]
+        tracing::debug!("Packages to remove: {:?}", packages);
+
+        if !yes && !confirm(&format!("Remove {} flatpak(s)?", packages.len()))? {
+            #[cfg(debug_assertions)]
+            tracing::debug!("Removal cancelled by user");
+            utils::print_info("Removal cancelled");
+            return Ok(());
+        }
+
+        let mut args = vec!["uninstall", "-y"];
+        args.extend(packages.iter().map(|s| s.as_str()));
+        #[cfg(debug_assertions)]
+        tracing::debug!("Executing command: flatpak {}", args.join(" "));
+        progress::run_with_spinner("Removing", "flatpak", &args, false)?;
+
+        #[cfg(debug_assertions)]
+        tracing::debug!("Removal completed successfully");
+        utils::print_success(&format!("Successfully removed {} flatpak(s)", packages.len()));
+        hooks::run_and_print(HookEvent::Remove, packages, true);
+        Ok(())
+    }
+
+    pub fn search(&self, query: &str) -> Result<()> {
+        #[cfg(debug_assertions)]
+        tracing::debug!("FlatpakManager::search() called with query: '{}'", query);
+        #[cfg(debug_assertions)]
+        tracing::debug!("Executing command: flatpak search {}", query);
+        let output = run_command("flatpak", &["search", "--columns=application,version,description", query], false)?;
+        #[cfg(debug_assertions)]
+        tracing::debug!("Search completed, output length: {} bytes", output.len());
+        output::print_table(&output::parse_flatpak_columns(&output, false));
+        Ok(())
+    }
+
+    pub fn update(&self) -> Result<()> {
+        #[cfg(debug_assertions)]
+        tracing::debug!("FlatpakManager::update() called");
+        utils::print_info("Updating flatpak repositories...");
+        #[cfg(debug_assertions)]
+        tracing::debug!("Executing command: flatpak update --noninteractive");
+        progress::run_with_spinner("Updating", "flatpak", &["update", "--noninteractive"], false)?;
+        #[cfg(debug_assertions)]
+        tracing::debug!("Repository update completed successfully");
+        utils::print_success("Flatpak repositories updated");
+        Ok(())
+    }
+
+    pub fn upgrade(&self, packages: &[String], yes: bool) -> Result<()> {
+        #[cfg(debug_assertions)]
+        tracing::debug!("FlatpakManager::upgrade() called with {} packages, yes={}", packages.len(), yes);
+        if !yes && !confirm("Upgrade flatpaks?")? {
+            #[cfg(debug_assertions)]
+            tracing::debug!("Upgrade cancelled by user");
+            utils::print_info("Upgrade cancelled");
+            return Ok(());
+        }
+
+        if packages.is_empty() {
+            #[cfg(debug_assertions)]
+            tracing::debug!("Upgrading all flatpaks, executing: flatpak update -y");
+            progress::run_with_spinner("Upgrading", "flatpak", &["update", "-y"], false)?;
+        } else {
+            let mut args = vec!["update", "-y"];
+            args.extend(packages.iter().map(|s| s.as_str()));
+            #[cfg(debug_assertions)]
+            tracing::debug!("Upgrading specific packages, executing: flatpak {}", args.join(" "));
+            progress::run_with_spinner("Upgrading", "flatpak", &args, false)?;
+        }
+
+        #[cfg(debug_assertions)]
+        tracing::debug!("Upgrade completed successfully");
+        utils::print_success("Flatpaks upgraded");
+        hooks::run_and_print(HookEvent::Upgrade, packages, true);
+        Ok(())
+    }
+
+    /// Apps on the remotes matching `query`, for frontends that list them
+    /// themselves; none are marked installed
+    pub fn search_rows(&self, query: &str) -> Result<Vec<PackageRow>> {
+        let output = run_command("flatpak", &["search", "--columns=application,version,description", query], false)?;
+        Ok(output::parse_flatpak_columns(&output, false))
+    }
+
+    /// Installed apps, without runtimes, as rows
+    pub fn app_rows(&self) -> Result<Vec<PackageRow>> {
+        let output = run_command("flatpak", &["list", "--app", "--columns=application,version,name"], false)?;
+        Ok(output::parse_flatpak_columns(&output, true))
+    }
+
+    pub fn list(&self, upgradable: bool) -> Result<()> {
+        #[cfg(debug_assertions)]
+        tracing::debug!("FlatpakManager::list() called, upgradable={}", upgradable);
+        if upgradable {
+            #[cfg(debug_assertions)]
+            tracing::debug!("Listing upgradable packages, executing: flatpak update --dry-run");
+            let output = run_command("flatpak", &["update", "--dry-run"], false)?;
+            #[cfg(debug_assertions)]
+            tracing::debug!("List completed, output length: {} bytes", output.len());
+            print!("{}", output);
+        } else {
+            #[cfg(debug_assertions)]
+            tracing::debug!("Listing all packages, executing: flatpak list");
+            let output = run_command("flatpak", &["list", "--columns=application,version,name"], false)?;
+            #[cfg(debug_assertions)]
+            tracing::debug!("List completed, output length: {} bytes", output.len());
+            output::print_table(&output::parse_flatpak_columns(&output, true));
+        }
+        Ok(())
+    }
+
+    /// Application IDs of the installed apps, without runtimes
+    pub fn installed_apps(&self) -> Result<Vec<String>> {
+        let output = run_command("flatpak", &["list", "--app", "--columns=application"], false)?;
+        Ok(output.lines().map(str::trim).filter(|line| !line.is_empty()).map(str::to_string).collect())
+    }
+
+    /// Installed apps that run on each of `refs`, for those that have any.
+    /// Flatpak refuses to uninstall a runtime while apps still use it, but
+    /// only says so once the uninstall is under way.
+    pub fn dependents(&self, refs: &[String]) -> Result<Vec<(String, Vec<String>)>> {
+        let output = run_command("flatpak", &["list", "--app", "--columns=application,runtime"], false)?;
+        let apps = parse_app_runtimes(&output);
+        Ok(refs
+            .iter()
+            .filter_map(|target| {
+                let dependents = runtime_dependents(target, &apps, refs);
+                (!dependents.is_empty()).then(|| (target.clone(), dependents))
+            })
+            .collect())
+    }
+
+    /// Installed apps and runtimes with an update on their remote
+    pub fn list_updates(&self) -> Result<Vec<PackageUpdate>> {
+        #[cfg(debug_assertions)]
+        tracing::debug!("FlatpakManager::list_updates() called");
+        let updates = run_command("flatpak", &["remote-ls", "--updates", "--columns=application,version"], false)?;
+        let installed = run_command("flatpak", &["list", "--columns=application,version"], false)?;
+        Ok(parse_flatpak_updates(&updates, &installed))
+    }
+
+    pub fn show(&self, package: &str) -> Result<()> {
+        #[cfg(debug_assertions)]
+        tracing::debug!("FlatpakManager::show() called for package: '{}'", package);
+        #[cfg(debug_assertions)]
+        tracing::debug!("Executing command: flatpak info {}", package);
+        let output = run_command("flatpak", &["info", package], false)?;
+        #[cfg(debug_assertions)]
+        tracing::debug!("Show completed, output length: {} bytes", output.len());
+        print!("{}", output);
+        Ok(())
+    }
+
+    pub fn clean(&self) -> Result<()> {
+        #[cfg(debug_assertions)]
+        tracing::debug!("FlatpakManager::clean() called");
+        utils::print_info("Cleaning flatpak cache...");
+        #[cfg(debug_assertions)]
+        tracing::debug!("Executing command: flatpak uninstall --unused -y");
+        run_command_interactive("flatpak", &["uninstall", "--unused", "-y"], false)?;
+        #[cfg(debug_assertions)]
+        tracing::debug!("Clean completed successfully");
+        utils::print_success("Flatpak cache cleaned");
+        Ok(())
+    }
+
+    /// What `app` may access, with the user's overrides; overrides need no root
+    pub fn permissions(&self, app: &str) -> Result<AppPermissions> {
+        let granted = run_command("flatpak", &["info", "--show-permissions", app], false)?;
+        let overrides = run_command("flatpak", &["override", "--user", "--show", app], false)?;
+        Ok(AppPermissions { granted: PermissionSet::parse(&granted), overrides: PermissionSet::parse(&overrides) })
+    }
+
+    /// Grant or revoke one permission with a per-user override
+    pub fn set_permission(&self, app: &str, permission: Permission, enabled: bool) -> Result<()> {
+        let arg = permission.override_arg(enabled);
+        tracing::debug!("FlatpakManager: flatpak override --user {} {}", arg, app);
+        run_command("flatpak", &["override", "--user", &arg, app], false)?;
+        Ok(())
+    }
+
+    /// Drop the user's overrides, back to what the app ships with
+    pub fn reset_permissions(&self, app: &str) -> Result<()> {
+        run_command("flatpak", &["override", "--user", "--reset", app], false)?;
+        Ok(())
+    }
+
+    /// Install a `.flatpakref` or bundle. flatpak itself adds a remote the
+    /// ref names that isn't configured yet.
+    pub fn install_file(&self, file: &FlatpakFile, yes: bool) -> Result<()> {
+        utils::print_info(&format!("Installing {} from {}", file.name(), file.path().display()));
+        if let FlatpakFile::Ref { app, .. } = file {
+            if let Some(remote) = self.new_remote(app)? {
+                utils::print_info(&format!("This adds the Flatpak remote '{}' ({})", remote, app.url));
+            }
+        }
+        if !yes && !confirm("Install it?")? {
+            utils::print_info("Installation cancelled");
+            return Ok(());
+        }
+
+        let [flag, path] = file.source_args();
+        progress::run_with_spinner("Installing", "flatpak", &["install", "-y", &flag, &path], false)?;
+        utils::print_success(&format!("Successfully installed {}", file.name()));
+        hooks::run_and_print(HookEvent::Install, &[file.name()], true);
+        Ok(())
+    }
+
+    /// The installed ref of `app`, looked for in the per-user installation
+    /// before the system one
+    pub fn installed_ref(&self, app: &str) -> Result<InstalledRef> {
+        for installation in FlatpakInstallation::ALL {
+            let Ok(output) = run_command("flatpak", &["info", "--show-ref", installation.flag(), app], false) else {
+                continue;
+            };
+            return InstalledRef::parse(installation, output.trim())
+                .ok_or_else(|| anyhow::anyhow!("flatpak gave an unexpected ref for {}: {}", app, output.trim()));
+        }
+        anyhow::bail!("{} is not an installed Flatpak app", app)
+    }
+
+    /// Write an installed app to a single-file `.flatpak` bundle, which
+    /// installs on another machine without a remote
+    pub fn export_bundle(&self, app: &str, file: &Path) -> Result<()> {
+        let installed = self.installed_ref(app)?;
+        utils::print_info(&format!(
+            "Exporting {} {} ({}) from the {} installation to {}",
+            installed.id,
+            installed.branch,
+            installed.arch,
+            installed.installation.label().to_lowercase(),
+            file.display()
+        ));
+        let args = installed.bundle_args(file);
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        progress::run_with_spinner("Exporting", "flatpak", &args, false)?;
+        let size = std::fs::metadata(file).map(|metadata| plan::format_size(metadata.len())).unwrap_or_default();
+        utils::print_success(&format!("Exported {} to {} ({})", app, file.display(), size));
+        utils::print_info("Installing it needs the app's runtime, from a configured remote or already installed");
+        Ok(())
+    }
+
+    /// The remote installing `app` would add, or None when a configured
+    /// remote already serves its URL
+    pub fn new_remote(&self, app: &RefFile) -> Result<Option<String>> {
+        let remotes = run_command("flatpak", &["remotes", "--columns=name,url"], false)?;
+        Ok(app.new_remote(&remotes))
+    }
+
+    /// What installing each app from its remote would pull: the app and any
+    /// runtime it needs that isn't installed yet
+    pub fn install_size(&self, apps: &[(String, String)]) -> Result<InstallSize> {
+        let mut refs: Vec<PulledRef> = Vec::new();
+        for (app, remote) in apps {
+            let info = parse_remote_info(&run_command("flatpak", &["remote-info", remote, app], false)?);
+            refs.push(PulledRef { name: app.clone(), runtime: false, download: info.download, installed: info.installed });
+            let Some(runtime) = info.runtime else { continue };
+            let runtime_ref = format!("runtime/{}", runtime);
+            if refs.iter().any(|r| r.name == runtime) || run_command("flatpak", &["info", &runtime_ref], false).is_ok() {
+                continue;
+            }
+            // Still listed when the remote won't say, so the pull isn't a surprise
+            let sizes = run_command("flatpak", &["remote-info", remote, &runtime_ref], false)
+                .map(|output| parse_remote_info(&output))
+                .unwrap_or_default();
+            refs.push(PulledRef { name: runtime, runtime: true, download: sizes.download, installed: sizes.installed });
+        }
+        Ok(InstallSize { refs })
+    }
+
+    /// Run `flatpak repair` on one installation, reporting disk usage before and after
+    pub fn repair(&self, installation: FlatpakInstallation, yes: bool) -> Result<()> {
+        #[cfg(debug_assertions)]
+        tracing::debug!("FlatpakManager::repair() called for {:?}", installation);
+        let before = installation.disk_usage();
+        utils::print_info(&format!(
+            "{} installation ({}) uses {}",
+            installation.label(),
+            installation.path().display(),
+            before.map(plan::format_size).unwrap_or_else(|| "unknown space".to_string())
+        ));
+
+        if !yes && !confirm(&format!("Repair the {} Flatpak installation?", installation.label().to_lowercase()))? {
+            utils::print_info("Repair cancelled");
+            return Ok(());
+        }
+
+        run_command_interactive("flatpak", &["repair", installation.flag()], installation.needs_root())?;
+
+        let after = installation.disk_usage();
+        utils::print_success(&format!("Repair finished: {}", usage_change(before, after)));
+        Ok(())
+    }
+}
+
+/// The remote to install from when an app is on several: the configured
+/// default, then flathub, then the first one listed
+pub fn preferred_remote<'a>(remotes: &'a [String], default: Option<&str>) -> Option<&'a str> {
+    [default, Some("flathub")]
+        .into_iter()
+        .flatten()
+        .find_map(|wanted| remotes.iter().find(|remote| remote.as_str() == wanted))
+        .or_else(|| remotes.first())
+        .map(String::as_str)
+}
+
+/// Pair `flatpak remote-ls --updates` with the installed versions from
+/// `flatpak list` (both `--columns=application,version`). Runtimes often
+/// carry no version, so either side may be empty.
+pub fn parse_flatpak_updates(updates: &str, installed: &str) -> Vec<PackageUpdate> {
+    let installed: std::collections::HashMap<&str, &str> = installed
+        .lines()
+        .filter_map(|line| {
+            let (application, version) = line.split_once('\t').unwrap_or((line, ""));
+            let application = application.trim();
+            (!application.is_empty()).then(|| (application, version.trim()))
+        })
+        .collect();
+
+    let mut seen = HashSet::new();
+    updates
+        .lines()
+        .filter_map(|line| {
+            let (application, version) = line.split_once('\t')?;
+            let application = application.trim();
+            // An app installed both per-user and system-wide is listed twice
+            if application.is_empty() || !seen.insert(application) {
+                return None;
+            }
+            Some(PackageUpdate {
+                name: application.to_string(),
+                current_version: installed.get(application).copied().unwrap_or_default().to_string(),
+                new_version: version.trim().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Parse `flatpak list --app --columns=application,runtime` into
+/// (application ID, runtime ref) pairs
+pub fn parse_app_runtimes(output: &str) -> Vec<(String, String)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (application, runtime) = line.split_once('\t')?;
+            let (application, runtime) = (application.trim(), runtime.trim());
+            (!application.is_empty() && !runtime.is_empty()).then(|| (application.to_string(), runtime.to_string()))
+        })
+        .collect()
+}
+
+/// The apps in `apps` (from [`parse_app_runtimes`]) that run on `target`,
+/// leaving out those in `removing`. `target` is an ID, which matches every
+/// branch, or a ref like `org.gnome.Platform/x86_64/46`.
+pub fn runtime_dependents(target: &str, apps: &[(String, String)], removing: &[String]) -> Vec<String> {
+    let target = target.strip_prefix("runtime/").or_else(|| target.strip_prefix("app/")).unwrap_or(target);
+    let wanted: Vec<&str> = target.split('/').collect();
+    let mut dependents: Vec<String> = apps
+        .iter()
+        .filter(|(application, _)| !removing.contains(application))
+        .filter(|(_, runtime)| {
+            // Parts the target leaves empty, like the arch in "id//46", match anything
+            let parts: Vec<&str> = runtime.split('/').collect();
+            wanted.iter().enumerate().all(|(i, part)| part.is_empty() || parts.get(i) == Some(part))
+        })
+        .map(|(application, _)| application.clone())
+        .collect();
+    dependents.sort();
+    dependents.dedup();
+    dependents
+}
+
+/// A Flatpak installation: per-user (`~/.local/share/flatpak`) or system-wide (`/var/lib/flatpak`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FlatpakInstallation {
+    User,
+    System,
+}
+
+impl FlatpakInstallation {
+    pub const ALL: [FlatpakInstallation; 2] = [FlatpakInstallation::User, FlatpakInstallation::System];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            FlatpakInstallation::User => "User",
+            FlatpakInstallation::System => "System",
+        }
+    }
+
+    /// `--user` or `--system`
+    pub fn flag(self) -> &'static str {
+        match self {
+            FlatpakInstallation::User => "--user",
+            FlatpakInstallation::System => "--system",
+        }
+    }
+
+    /// Repairing the system installation rewrites files owned by root
+    pub fn needs_root(self) -> bool {
+        self == FlatpakInstallation::System
+    }
+
+    pub fn path(self) -> PathBuf {
+        match self {
+            FlatpakInstallation::User => {
+                let home = std::env::var("HOME").unwrap_or_default();
+                PathBuf::from(home).join(".local/share/flatpak")
+            }
+            FlatpakInstallation::System => PathBuf::from("/var/lib/flatpak"),
+        }
+    }
+
+    /// Bytes used on disk, or None if the installation doesn't exist
+    pub fn disk_usage(self) -> Option<u64> {
+        let path = self.path();
+        if !path.exists() {
+            return None;
+        }
+        let mut seen = HashSet::new();
+        Some(dir_size(&path, &mut seen))
+    }
+}
+
+/// "1.2 GB -> 1.1 GB (freed 100 MB)" style summary of a repair
+pub fn usage_change(before: Option<u64>, after: Option<u64>) -> String {
+    match (before, after) {
+        (Some(before), Some(after)) if after <= before => format!(
+            "{} -> {} (freed {})",
+            plan::format_size(before),
+            plan::format_size(after),
+            plan::format_size(before - after)
+        ),
+        (Some(before), Some(after)) => format!(
+            "{} -> {} (grew by {})",
+            plan::format_size(before),
+            plan::format_size(after),
+            plan::format_size(after - before)
+        ),
+        (_, Some(after)) => format!("now using {}", plan::format_size(after)),
+        _ => "disk usage unknown".to_string(),
+    }
+}
+
+// Like `du`: deployments hardlink into the OSTree repo, so each inode is counted once
+fn dir_size(path: &Path, seen: &mut HashSet<(u64, u64)>) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return 0;
+    };
+    let mut total = 0;
+    for entry in entries.flatten() {
+        let Ok(meta) = entry.path().symlink_metadata() else {
+            continue;
+        };
+        if meta.is_dir() {
+            total += dir_size(&entry.path(), seen);
+        } else if seen.insert((meta.dev(), meta.ino())) {
+            total += meta.blocks() * 512;
+        }
+    }
+    total
+}
+
+
+/// The kinds of sandbox permission the override editor toggles, by their
+/// `[Context]` key in Flatpak metadata
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PermissionKind {
+    Shared,
+    Sockets,
+    Devices,
+    Filesystems,
+}
+
+impl PermissionKind {
+    pub const ALL: [PermissionKind; 4] =
+        [PermissionKind::Shared, PermissionKind::Sockets, PermissionKind::Devices, PermissionKind::Filesystems];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PermissionKind::Shared => "Network and IPC",
+            PermissionKind::Sockets => "Sockets",
+            PermissionKind::Devices => "Devices",
+            PermissionKind::Filesystems => "Filesystem",
+        }
+    }
+
+    fn key(self) -> &'static str {
+        match self {
+            PermissionKind::Shared => "shared",
+            PermissionKind::Sockets => "sockets",
+            PermissionKind::Devices => "devices",
+            PermissionKind::Filesystems => "filesystems",
+        }
+    }
+
+    /// What can be toggled: the values `flatpak override` understands for this kind
+    pub fn values(self) -> &'static [&'static str] {
+        match self {
+            PermissionKind::Shared => &["network", "ipc"],
+            PermissionKind::Sockets => &[
+                "x11", "wayland", "fallback-x11", "pulseaudio", "session-bus", "system-bus", "ssh-auth", "pcsc", "cups",
+                "gpg-agent",
+            ],
+            PermissionKind::Devices => &["dri", "input", "usb", "kvm", "shm", "all"],
+            PermissionKind::Filesystems => &[
+                "host", "host-os", "host-etc", "home", "xdg-desktop", "xdg-documents", "xdg-download", "xdg-music",
+                "xdg-pictures", "xdg-videos",
+            ],
+        }
+    }
+}
+
+/// One toggle in the permission editor, e.g. the `x11` socket
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Permission {
+    pub kind: PermissionKind,
+    pub value: &'static str,
+}
+
+impl Permission {
+    /// The `flatpak override` flag that grants or revokes it
+    pub fn override_arg(self, enabled: bool) -> String {
+        let flag = match (self.kind, enabled) {
+            (PermissionKind::Shared, true) => "share",
+            (PermissionKind::Shared, false) => "unshare",
+            (PermissionKind::Sockets, true) => "socket",
+            (PermissionKind::Sockets, false) => "nosocket",
+            (PermissionKind::Devices, true) => "device",
+            (PermissionKind::Devices, false) => "nodevice",
+            (PermissionKind::Filesystems, true) => "filesystem",
+            (PermissionKind::Filesystems, false) => "nofilesystem",
+        };
+        format!("--{}={}", flag, self.value)
+    }
+}
+
+/// The `[Context]` group of Flatpak metadata or an override file, by kind.
+/// Values keep their `!` (revoked) and `:ro` style suffixes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PermissionSet {
+    pub entries: Vec<(PermissionKind, String)>,
+}
+
+impl PermissionSet {
+    pub fn parse(keyfile: &str) -> Self {
+        let mut entries = Vec::new();
+        let mut in_context = false;
+        for line in keyfile.lines().map(str::trim) {
+            if line.starts_with('[') {
+                in_context = line == "[Context]";
+                continue;
+            }
+            let Some((key, values)) = line.split_once('=').filter(|_| in_context) else {
+                continue;
+            };
+            let Some(kind) = PermissionKind::ALL.into_iter().find(|kind| kind.key() == key.trim()) else {
+                continue;
+            };
+            entries.extend(values.split(';').map(str::trim).filter(|v| !v.is_empty()).map(|v| (kind, v.to_string())));
+        }
+        Self { entries }
+    }
+
+    /// Some(true) if listed, Some(false) if revoked with `!`, None if not mentioned
+    pub fn state(&self, permission: Permission) -> Option<bool> {
+        // The last mention wins, as when flatpak merges them
+        self.entries
+            .iter()
+            .rev()
+            .filter(|(kind, _)| *kind == permission.kind)
+            .find_map(|(_, value)| {
+                let (revoked, value) = match value.strip_prefix('!') {
+                    Some(value) => (true, value),
+                    None => (false, value.as_str()),
+                };
+                // "home:ro" still grants home, read-only
+                let name = value.split_once(':').map_or(value, |(name, _)| name);
+                (name == permission.value).then_some(!revoked)
+            })
+    }
+}
+
+/// An app's permissions as shipped and as overridden by the user
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AppPermissions {
+    pub granted: PermissionSet,
+    pub overrides: PermissionSet,
+}
+
+impl AppPermissions {
+    /// Whether the app has `permission` once the user's overrides apply
+    pub fn is_enabled(&self, permission: Permission) -> bool {
+        self.overrides.state(permission).or(self.granted.state(permission)).unwrap_or(false)
+    }
+
+    pub fn is_overridden(&self, permission: Permission) -> bool {
+        self.overrides.state(permission).is_some()
+    }
+}
+
+/// Sizes `flatpak remote-info` reports for one ref, and the runtime it runs on
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RemoteInfo {
+    pub download: Option<u64>,
+    pub installed: Option<u64>,
+    // e.g. "org.gnome.Platform/x86_64/46"; runtimes themselves have none
+    pub runtime: Option<String>,
+}
+
+pub fn parse_remote_info(output: &str) -> RemoteInfo {
+    let mut info = RemoteInfo::default();
+    for line in output.lines() {
+        let Some((key, value)) = line.trim().split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match key {
+            "Download" => info.download = parse_flatpak_size(value),
+            "Installed" => info.installed = parse_flatpak_size(value),
+            "Runtime" if !value.is_empty() => info.runtime = Some(value.to_string()),
+            _ => {}
+        }
+    }
+    info
+}
+
+/// "112.3 MB", "356.0 kB" or "512 bytes" in bytes. GLib formats these with
+/// powers of 1000 and a no-break space before the unit.
+pub fn parse_flatpak_size(value: &str) -> Option<u64> {
+    let (number, unit) = value.split_once(char::is_whitespace).unwrap_or((value, ""));
+    let number: f64 = number.parse().ok()?;
+    let multiplier = match unit.trim() {
+        "" | "B" | "byte" | "bytes" => 1.0,
+        "kB" => 1e3,
+        "MB" => 1e6,
+        "GB" => 1e9,
+        "TB" => 1e12,
+        _ => return None,
+    };
+    Some((number * multiplier) as u64)
+}
+
+/// An app or runtime an install fetches
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PulledRef {
+    pub name: String,
+    pub runtime: bool,
+    pub download: Option<u64>,
+    pub installed: Option<u64>,
+}
+
+impl PulledRef {
+    /// "org.gnome.Platform/x86_64/46: 312.4 MB download, 1.0 GB on disk"
+    pub fn sizes_line(&self) -> String {
+        match (self.download, self.installed) {
+            (Some(download), Some(installed)) => {
+                format!("{}: {} download, {} on disk", self.name, plan::format_size(download), plan::format_size(installed))
+            }
+            _ => format!("{}: size unknown", self.name),
+        }
+    }
+}
+
+/// Everything a Flatpak install would fetch. Runtimes are often several
+/// times the size of the app that needs them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InstallSize {
+    pub refs: Vec<PulledRef>,
+}
+
+impl InstallSize {
+    pub fn download(&self) -> u64 {
+        self.refs.iter().filter_map(|r| r.download).sum()
+    }
+
+    pub fn installed(&self) -> u64 {
+        self.refs.iter().filter_map(|r| r.installed).sum()
+    }
+
+    pub fn runtimes(&self) -> impl Iterator<Item = &PulledRef> {
+        self.refs.iter().filter(|r| r.runtime)
+    }
+
+    /// Totals over every ref, e.g. "Download 412.0 MB, 1.2 GB on disk"; "at
+    /// least" when a remote didn't report some sizes
+    pub fn totals_line(&self) -> String {
+        let complete = self.refs.iter().all(|r| r.download.is_some() && r.installed.is_some());
+        format!(
+            "Download {}{}, {} on disk",
+            if complete { "" } else { "at least " },
+            plan::format_size(self.download()),
+            plan::format_size(self.installed())
+        )
+    }
+}
+
+/// The `[Flatpak Ref]` group of a `.flatpakref`: one app and the remote it
+/// comes from
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RefFile {
+    pub name: String,
+    pub title: Option<String>,
+    pub branch: Option<String>,
+    pub url: String,
+    pub suggested_remote: Option<String>,
+}
+
+impl RefFile {
+    /// Given `flatpak remotes --columns=name,url`, the name flatpak will give
+    /// the ref's remote if none of them has its URL
+    pub fn new_remote(&self, remotes: &str) -> Option<String> {
+        let url = self.url.trim_end_matches('/');
+        let known = remotes
+            .lines()
+            .filter_map(|line| line.split_whitespace().nth(1))
+            .any(|remote_url| remote_url.trim_end_matches('/') == url);
+        if known {
+            return None;
+        }
+        Some(self.suggested_remote.clone().unwrap_or_else(|| format!("{}-origin", self.name)))
+    }
+}
+
+pub fn parse_flatpakref(keyfile: &str) -> Result<RefFile> {
+    let mut app = RefFile::default();
+    let mut in_group = false;
+    for line in keyfile.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_group = line == "[Flatpak Ref]";
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=').filter(|_| in_group) else {
+            continue;
+        };
+        let value = value.trim().to_string();
+        match key.trim() {
+            "Name" => app.name = value,
+            "Title" => app.title = Some(value),
+            "Branch" => app.branch = Some(value),
+            "Url" => app.url = value,
+            "SuggestRemoteName" => app.suggested_remote = Some(value),
+            _ => {}
+        }
+    }
+    if app.name.is_empty() || app.url.is_empty() {
+        anyhow::bail!("Not a flatpakref: it needs a Name and a Url");
+    }
+    Ok(app)
+}
+
+/// An installed app as `flatpak info --show-ref` names it,
+/// `app/org.gimp.GIMP/x86_64/stable`, and the installation holding it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstalledRef {
+    pub installation: FlatpakInstallation,
+    pub id: String,
+    pub arch: String,
+    pub branch: String,
+}
+
+impl InstalledRef {
+    pub fn parse(installation: FlatpakInstallation, flatpak_ref: &str) -> Option<Self> {
+        let mut parts = flatpak_ref.split('/');
+        let (Some("app"), Some(id), Some(arch), Some(branch), None) = (parts.next(), parts.next(), parts.next(), parts.next(), parts.next()) else {
+            return None;
+        };
+        if [id, arch, branch].iter().any(|part| part.is_empty()) {
+            return None;
+        }
+        Some(Self { installation, id: id.to_string(), arch: arch.to_string(), branch: branch.to_string() })
+    }
+
+    /// `flatpak build-bundle` arguments writing the app from its
+    /// installation's repository to `file`
+    pub fn bundle_args(&self, file: &Path) -> Vec<String> {
+        vec![
+            "build-bundle".to_string(),
+            format!("--arch={}", self.arch),
+            self.installation.path().join("repo").display().to_string(),
+            file.display().to_string(),
+            self.id.clone(),
+            self.branch.clone(),
+        ]
+    }
+}
+
+/// Where an app's bundle goes when no file is named: `<app ID>.flatpak` in
+/// `dir`
+pub fn bundle_file(dir: &Path, app: &str) -> PathBuf {
+    dir.join(format!("{}.flatpak", app))
+}
+
+/// A `.flatpakref` or `.flatpak` bundle to install, opened from the file
+/// manager or named on the command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FlatpakFile {
+    Ref { path: PathBuf, app: RefFile },
+    Bundle(PathBuf),
+}
+
+impl FlatpakFile {
+    /// Whether an install argument is one of these files rather than an app ID
+    pub fn is_file_arg(arg: &str) -> bool {
+        (arg.ends_with(".flatpakref") || arg.ends_with(".flatpak")) && Path::new(arg).is_file()
+    }
+
+    pub fn open(path: &Path) -> Result<Self> {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("flatpakref") => {
+                let keyfile = std::fs::read_to_string(path)?;
+                Ok(Self::Ref { path: path.to_path_buf(), app: parse_flatpakref(&keyfile)? })
+            }
+            Some("flatpak") if path.is_file() => Ok(Self::Bundle(path.to_path_buf())),
+            _ => anyhow::bail!("{} is not a .flatpakref or .flatpak file", path.display()),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::Ref { path, .. } | Self::Bundle(path) => path,
+        }
+    }
+
+    /// The app ID for a ref; the file name for a bundle, whose metadata only
+    /// flatpak reads
+    pub fn name(&self) -> String {
+        match self {
+            Self::Ref { app, .. } => app.name.clone(),
+            Self::Bundle(path) => path.file_name().unwrap_or_default().to_string_lossy().into_owned(),
+        }
+    }
+
+    /// The `flatpak install` source: `--from` a ref or `--bundle`
+    pub fn source_args(&self) -> [String; 2] {
+        let flag = match self {
+            Self::Ref { .. } => "--from",
+            Self::Bundle(_) => "--bundle",
+        };
+        [flag.to_string(), self.path().display().to_string()]
+    }
+}
@@ -0,0 +1,207 @@
+//! What an installed package's running programs cost right now: the CPU and
+//! memory of processes started from files the package installed, found by
+//! scanning `/proc`.
+//!
+//! A process belongs to the package when its executable, or the script its
+//! interpreter was started with (`python3 /usr/bin/meld`), is one of the
+//! package's files. Other users' processes can't be inspected without root,
+//! so only this user's count - which is what a desktop app's are.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::Result;
+
+use crate::package_manager::PackageBackend;
+use crate::plan::format_size;
+
+/// How long CPU time is sampled for
+pub const SAMPLE: Duration = Duration::from_secs(1);
+
+/// The package's running processes, added up
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Footprint {
+    pub processes: usize,
+    /// Resident memory, in bytes
+    pub memory: u64,
+    /// Share of one CPU core over the sample, so 250.0 is two and a half cores
+    pub cpu: f32,
+}
+
+impl Footprint {
+    pub fn is_running(&self) -> bool {
+        self.processes > 0
+    }
+
+    /// "2 processes using 350.2 MB of memory and 4% CPU", or "Not running"
+    pub fn summary(&self) -> String {
+        if !self.is_running() {
+            return "Not running".to_string();
+        }
+        format!(
+            "{} process{} using {} of memory and {:.0}% CPU",
+            self.processes,
+            if self.processes == 1 { "" } else { "es" },
+            format_size(self.memory),
+            self.cpu
+        )
+    }
+}
+
+/// The files a process may be started from, with symlinks resolved, since
+/// `/proc/<pid>/exe` names the real file (`/usr/bin/bash` where dpkg lists
+/// `/bin/bash`)
+#[derive(Debug, Clone, Default)]
+pub struct Programs(HashSet<PathBuf>);
+
+impl Programs {
+    /// The executable regular files among `files`
+    pub fn from_files(files: &[String]) -> Self {
+        use std::os::unix::fs::PermissionsExt;
+
+        Self(
+            files
+                .iter()
+                .filter_map(|file| std::fs::canonicalize(file).ok())
+                .filter(|path| {
+                    std::fs::metadata(path).is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+                })
+                .collect(),
+        )
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Whether the process in `dir` (`/proc/<pid>`) runs one of the programs
+    fn started(&self, dir: &Path) -> bool {
+        if std::fs::read_link(dir.join("exe")).is_ok_and(|exe| self.0.contains(&exe)) {
+            return true;
+        }
+        // Interpreted programs run as their interpreter, with the script as
+        // the first argument or two (after an option such as -s)
+        let Ok(cmdline) = std::fs::read(dir.join("cmdline")) else {
+            return false;
+        };
+        cmdline
+            .split(|b| *b == 0)
+            .take(3)
+            .filter(|arg| arg.starts_with(b"/"))
+            .filter_map(|arg| std::fs::canonicalize(String::from_utf8_lossy(arg).as_ref()).ok())
+            .any(|arg| self.0.contains(&arg))
+    }
+}
+
+/// Measure the running processes of the installed `package`; takes
+/// [`SAMPLE`], so it belongs in a background task
+pub fn measure(backend: &dyn PackageBackend, package: &str) -> Result<Footprint> {
+    let programs = Programs::from_files(&backend.files(package)?);
+    Ok(measure_each(&[programs])[0])
+}
+
+/// Like [`measure`] for several sets of programs, sampled over the same
+/// [`SAMPLE`]
+pub fn measure_each(programs: &[Programs]) -> Vec<Footprint> {
+    if programs.iter().all(Programs::is_empty) {
+        return vec![Footprint::default(); programs.len()];
+    }
+    let proc = Path::new("/proc");
+    let before: Vec<Sample> = programs.iter().map(|programs| Sample::take(proc, programs)).collect();
+    std::thread::sleep(SAMPLE);
+    let ticks = clock_ticks();
+    programs
+        .iter()
+        .zip(&before)
+        .map(|(programs, before)| Sample::take(proc, programs).since(before, SAMPLE, ticks))
+        .collect()
+}
+
+/// CPU time and memory of each matching process at one moment
+#[derive(Debug, Clone, Default)]
+pub struct Sample(Vec<(u32, Process)>);
+
+#[derive(Debug, Clone, Copy)]
+struct Process {
+    cpu_ticks: u64,
+    memory: u64,
+}
+
+impl Sample {
+    /// The processes in `proc` running `programs`
+    pub fn take(proc: &Path, programs: &Programs) -> Self {
+        if programs.is_empty() {
+            return Self::default();
+        }
+        let Ok(entries) = std::fs::read_dir(proc) else {
+            return Self::default();
+        };
+        Self(
+            entries
+                .flatten()
+                .filter_map(|entry| {
+                    let pid: u32 = entry.file_name().to_str()?.parse().ok()?;
+                    let dir = entry.path();
+                    if !programs.started(&dir) {
+                        return None;
+                    }
+                    let cpu_ticks = parse_stat_ticks(&std::fs::read_to_string(dir.join("stat")).ok()?)?;
+                    let memory = parse_status_memory(&std::fs::read_to_string(dir.join("status")).ok()?).unwrap_or(0);
+                    Some((pid, Process { cpu_ticks, memory }))
+                })
+                .collect(),
+        )
+    }
+
+    /// The footprint over the `interval` since `before`, with CPU time
+    /// counted in `ticks` per second; memory is what it is now
+    pub fn since(&self, before: &Sample, interval: Duration, ticks: u64) -> Footprint {
+        // Processes that started during the sample only count from when they did
+        let cpu_ticks: u64 = self
+            .0
+            .iter()
+            .map(|(pid, process)| {
+                let earlier = before.0.iter().find(|(old, _)| old == pid).map_or(0, |(_, old)| old.cpu_ticks);
+                process.cpu_ticks.saturating_sub(earlier)
+            })
+            .sum();
+        let seconds = interval.as_secs_f32().max(f32::EPSILON);
+        Footprint {
+            processes: self.0.len(),
+            memory: self.0.iter().map(|(_, process)| process.memory).sum(),
+            cpu: cpu_ticks as f32 / ticks.max(1) as f32 / seconds * 100.0,
+        }
+    }
+}
+
+/// User plus system CPU time from `/proc/<pid>/stat`, in clock ticks. The
+/// command name in parentheses may hold spaces and parentheses itself, so
+/// fields are counted from the last ')'.
+pub fn parse_stat_ticks(stat: &str) -> Option<u64> {
+    let (_, rest) = stat.rsplit_once(')')?;
+    let mut fields = rest.split_whitespace().skip(11);
+    let user: u64 = fields.next()?.parse().ok()?;
+    let system: u64 = fields.next()?.parse().ok()?;
+    Some(user + system)
+}
+
+/// Resident memory from `/proc/<pid>/status`'s `VmRSS:  1234 kB` line, in
+/// bytes; kernel threads have none
+pub fn parse_status_memory(status: &str) -> Option<u64> {
+    let line = status.lines().find_map(|line| line.strip_prefix("VmRSS:"))?;
+    let kilobytes: u64 = line.trim().strip_suffix("kB")?.trim().parse().ok()?;
+    Some(kilobytes * 1024)
+}
+
+// USER_HZ, which /proc reports CPU time in; 100 on every Linux architecture
+// in practice, but the kernel says what it is
+fn clock_ticks() -> u64 {
+    // SAFETY: sysconf only reads a configuration value
+    let ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+    if ticks > 0 {
+        ticks as u64
+    } else {
+        100
+    }
+}
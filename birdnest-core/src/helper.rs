@@ -0,0 +1,431 @@
+//! The privileged helper: a root daemon started through pkexec once per
+//! session, so a run of package operations asks for the password once rather
+//! than once per command.
+//!
+//! The `helper` elevation method hands each command to `birdnest helper run`,
+//! which starts the daemon when it isn't running and passes the command on
+//! over a unix socket only the user who started it can open. The daemon runs
+//! package tools one request at a time, in the order they arrive, and streams
+//! their output back line by line. It only runs the commands BirdNest itself
+//! builds: the verbs and options in [`shape`], with package names rather than
+//! paths, URLs or configuration overrides. Anything else keeps going through
+//! pkexec and asks each time. It exits after [`IDLE_TIMEOUT`] without
+//! requests, or on `birdnest helper stop`.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::io::AsRawFd;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::elevation::HELPER_PATH;
+
+/// Variables a request may set for its program, the ones
+/// [`CommandBuilder`](crate::utils::CommandBuilder) uses
+pub const ENV_VARS: &[&str] = &["LC_ALL", "DEBIAN_FRONTEND", "http_proxy", "https_proxy", "no_proxy"];
+
+/// How long the daemon waits for another request before exiting
+pub const IDLE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+// How long `run` waits for the daemon to come up, password prompt included
+const START_TIMEOUT: Duration = Duration::from_secs(120);
+
+// How long the daemon waits for a connected client's request line
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+// apt's `-o` options BirdNest passes: the progress bar's status pipe and
+// the configured proxy
+const APT_OPTIONS: &[&str] = &["APT::Status-Fd=1", "Dpkg::Use-Pty=0"];
+const APT_PROXY_OPTIONS: &[&str] = &["Acquire::http::Proxy=", "Acquire::https::Proxy="];
+
+// Package files, which carry their own maintainer scripts
+const PACKAGE_FILES: &[&str] = &[".deb", ".dsc", ".rpm", ".flatpak", ".flatpakref", ".flatpakrepo", ".pkg.tar"];
+
+const SOCKET_DIR: &str = "/run/birdnest";
+
+/// What a client sends, as one JSON line
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Request {
+    Run { program: String, args: Vec<String>, env: Vec<(String, String)> },
+    Stop,
+}
+
+/// What the daemon answers a [`Request::Run`] with, one JSON line each:
+/// output lines as they come, then the exit code
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Reply {
+    Stdout(String),
+    Stderr(String),
+    Exit(i32),
+    /// Not run at all, and why
+    Refused(String),
+}
+
+impl Request {
+    /// Why the daemon won't run this, if it won't
+    pub fn check(&self) -> Result<(), String> {
+        let Request::Run { program, args, env } = self else {
+            return Ok(());
+        };
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        check_args(program, &args)?;
+        if let Some((key, _)) = env.iter().find(|(key, _)| !ENV_VARS.contains(&key.as_str())) {
+            return Err(format!("The privileged helper doesn't set {}", key));
+        }
+        Ok(())
+    }
+}
+
+/// The commands the helper runs for a program, as BirdNest builds them
+#[derive(Debug, Clone, Copy)]
+pub struct Shape {
+    /// What the first operand must be; anything, when empty
+    pub verbs: &'static [&'static str],
+    /// Options on their own
+    pub flags: &'static [&'static str],
+    /// Options followed by a value
+    pub valued: &'static [&'static str],
+}
+
+/// The verbs and options BirdNest runs `program` with as root; None for
+/// programs the helper doesn't run, which take scripts or aren't package
+/// tools. A path never matches.
+pub fn shape(program: &str) -> Option<Shape> {
+    let shape = |verbs, flags, valued| Some(Shape { verbs, flags, valued });
+    match program {
+        "apt" | "apt-get" => shape(
+            &["install", "remove", "upgrade", "update", "clean", "autoclean"],
+            &["-y", "--no-install-recommends", "--only-upgrade", "--upgrade", "--download-only", "--reinstall", "--fix-broken", "--autoremove"],
+            &["-t", "-o"],
+        ),
+        "apt-mark" => shape(&["hold", "unhold", "manual", "auto"], &[], &[]),
+        "pikman" => shape(
+            &["install", "remove", "upgrade", "update", "search", "autoremove", "purge", "clean"],
+            &["-y", "--aur", "--fedora", "--alpine"],
+            &["--name"],
+        ),
+        "flatpak" => shape(&["install", "uninstall", "update", "repair"], &["-y", "--noninteractive", "--system", "--user", "--appstream", "--unused"], &[]),
+        "needrestart" => shape(&[], &["-b"], &["-r"]),
+        "dnf" => shape(&["install", "remove", "upgrade", "reinstall"], &["-y", "--downloadonly"], &[]),
+        "pacman" => shape(&[], &["-S", "-R", "-Syu", "-D", "--needed", "--noconfirm", "--asexplicit"], &[]),
+        "abroot" => shape(&["pkg", "upgrade"], &[], &[]),
+        "rpm-ostree" => shape(&["install", "uninstall", "upgrade"], &["-y"], &[]),
+        _ => None,
+    }
+}
+
+/// Whether the helper runs `program args`; anything else goes through pkexec
+pub fn runs(program: &str, args: &[&str]) -> bool {
+    check_args(program, args).is_ok()
+}
+
+/// Why the helper won't run `program args`, if it won't
+pub fn check_args(program: &str, args: &[&str]) -> Result<(), String> {
+    let Some(shape) = shape(program) else {
+        return Err(format!("The privileged helper doesn't run {}", program));
+    };
+    let refuse = |arg: &str| Err(format!("The privileged helper doesn't run {} with {}", program, arg));
+    let mut operands = 0;
+    let mut args = args.iter();
+    while let Some(&arg) = args.next() {
+        if shape.flags.contains(&arg) {
+            continue;
+        }
+        if shape.valued.contains(&arg) {
+            let Some(&value) = args.next() else {
+                return refuse(arg);
+            };
+            let allowed = match arg {
+                "-o" => is_apt_option(value),
+                _ => is_value(value),
+            };
+            if !allowed {
+                return refuse(value);
+            }
+            continue;
+        }
+        let allowed = match operands {
+            0 if !shape.verbs.is_empty() => shape.verbs.contains(&arg),
+            _ => is_operand(arg),
+        };
+        if !allowed {
+            return refuse(arg);
+        }
+        operands += 1;
+    }
+    Ok(())
+}
+
+// A package name or remote: no option, path, URL or package file. Anything
+// with a slash may resolve against the working directory, so refs and
+// repo/package pairs go through pkexec instead
+fn is_operand(arg: &str) -> bool {
+    is_value(arg) && !arg.contains('/') && !arg.contains("..") && !arg.ends_with(".changes") && !PACKAGE_FILES.iter().any(|suffix| arg.contains(suffix))
+}
+
+// An option's value, like a release or container name
+fn is_value(arg: &str) -> bool {
+    !arg.is_empty() && !arg.starts_with(['-', '/', '.', '~']) && !arg.contains(|c: char| c.is_whitespace() || c.is_control())
+}
+
+fn is_apt_option(option: &str) -> bool {
+    APT_OPTIONS.contains(&option)
+        || APT_PROXY_OPTIONS
+            .iter()
+            .any(|prefix| option.strip_prefix(prefix).is_some_and(|proxy| !proxy.is_empty() && !proxy.contains(char::is_whitespace)))
+}
+
+/// The socket the daemon serving `uid` listens on
+pub fn socket_path(uid: u32) -> PathBuf {
+    Path::new(SOCKET_DIR).join(format!("helper-{}.sock", uid))
+}
+
+/// Whether this user's daemon is up, so commands run without a password prompt
+pub fn is_running() -> bool {
+    UnixStream::connect(socket_path(current_uid())).is_ok()
+}
+
+/// Runs `program args` through the daemon, starting it first when needed,
+/// with its output on this process's stdout and stderr. Returns the exit code.
+pub fn run(program: &str, args: &[String]) -> Result<i32> {
+    let env = ENV_VARS
+        .iter()
+        .filter_map(|key| Some((key.to_string(), std::env::var(key).ok()?)))
+        .collect();
+    let request = Request::Run { program: program.to_string(), args: args.to_vec(), env };
+    let stream = connect_or_start()?;
+    send(&stream, &request)?;
+    read_replies(BufReader::new(&stream), &mut std::io::stdout(), &mut std::io::stderr())
+}
+
+/// Asks this user's daemon to exit; false when none was running
+pub fn stop() -> Result<bool> {
+    let Ok(stream) = UnixStream::connect(socket_path(current_uid())) else {
+        return Ok(false);
+    };
+    send(&stream, &Request::Stop)?;
+    Ok(true)
+}
+
+/// Copies the daemon's replies to `stdout` and `stderr` up to the exit code
+pub fn read_replies(reader: impl BufRead, stdout: &mut impl Write, stderr: &mut impl Write) -> Result<i32> {
+    for line in reader.lines() {
+        match serde_json::from_str::<Reply>(&line?)? {
+            Reply::Stdout(text) => writeln!(stdout, "{}", text)?,
+            Reply::Stderr(text) => writeln!(stderr, "{}", text)?,
+            Reply::Exit(code) => return Ok(code),
+            Reply::Refused(reason) => anyhow::bail!("{}", reason),
+        }
+    }
+    anyhow::bail!("The privileged helper stopped before the command finished")
+}
+
+fn send(mut stream: &UnixStream, request: &Request) -> Result<()> {
+    let mut line = serde_json::to_string(request)?;
+    line.push('\n');
+    stream.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+fn connect_or_start() -> Result<UnixStream> {
+    let path = socket_path(current_uid());
+    if let Ok(stream) = UnixStream::connect(&path) {
+        return Ok(stream);
+    }
+
+    // Without stdio of its own the daemon would keep this process's pipes
+    // open, and whoever reads our output would wait for it to exit
+    let mut pkexec = Command::new("pkexec")
+        .arg(HELPER_PATH)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .context("Couldn't start pkexec")?;
+    let started = Instant::now();
+    loop {
+        if let Ok(stream) = UnixStream::connect(&path) {
+            return Ok(stream);
+        }
+        // pkexec lasts as long as the daemon, so it exiting means no daemon
+        if let Some(status) = pkexec.try_wait()? {
+            match status.code() {
+                Some(126) | Some(127) => anyhow::bail!("Authentication failed or cancelled. Please try again."),
+                _ => anyhow::bail!("The privileged helper exited before it was ready ({})", status),
+            }
+        }
+        if started.elapsed() > START_TIMEOUT {
+            let _ = pkexec.kill();
+            anyhow::bail!("The privileged helper didn't start");
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+}
+
+/// The daemon, run as root by pkexec: serves the user who started it until
+/// idle for [`IDLE_TIMEOUT`] or asked to stop
+pub fn serve() -> Result<()> {
+    if !crate::utils::is_root() {
+        anyhow::bail!("The privileged helper has to run as root, through pkexec");
+    }
+    let uid: u32 = std::env::var("PKEXEC_UID")
+        .ok()
+        .and_then(|uid| uid.parse().ok())
+        .context("The privileged helper has to be started through pkexec")?;
+
+    std::fs::create_dir_all(SOCKET_DIR)?;
+    std::fs::set_permissions(SOCKET_DIR, std::fs::Permissions::from_mode(0o755))?;
+    let path = socket_path(uid);
+    if UnixStream::connect(&path).is_ok() {
+        anyhow::bail!("The privileged helper is already running");
+    }
+    let _ = std::fs::remove_file(&path);
+    let listener = UnixListener::bind(&path).with_context(|| format!("Couldn't listen on {}", path.display()))?;
+    // Only the user can open the socket; the peer check below is a second lock
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+    std::os::unix::fs::chown(&path, Some(uid), None)?;
+    tracing::info!("helper: serving uid {} on {}", uid, path.display());
+
+    // None while a command runs, so a long upgrade never counts as idle
+    let idle_since = Arc::new(Mutex::new(Some(Instant::now())));
+    exit_when_idle(path.clone(), idle_since.clone());
+
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else {
+            continue;
+        };
+        match peer_uid(&stream) {
+            Some(peer) if peer == uid || peer == 0 => {}
+            peer => {
+                tracing::warn!("helper: refusing a connection from uid {:?}", peer);
+                continue;
+            }
+        }
+        // A client that never sends its request would hold up everyone else's
+        if let Err(e) = stream.set_read_timeout(Some(REQUEST_TIMEOUT)) {
+            tracing::warn!("helper: couldn't set a read timeout: {}", e);
+            continue;
+        }
+        let mut line = String::new();
+        if BufReader::new(&stream).read_line(&mut line).unwrap_or(0) == 0 {
+            // A client checking whether the daemon is up, or one that timed out
+            continue;
+        }
+        let request: Request = match serde_json::from_str(&line) {
+            Ok(request) => request,
+            Err(e) => {
+                tracing::warn!("helper: unreadable request: {}", e);
+                continue;
+            }
+        };
+        if request == Request::Stop {
+            break;
+        }
+        *idle_since.lock().unwrap() = None;
+        if let Err(e) = respond(&request, &stream) {
+            tracing::debug!("helper: client went away: {}", e);
+        }
+        *idle_since.lock().unwrap() = Some(Instant::now());
+    }
+    let _ = std::fs::remove_file(&path);
+    Ok(())
+}
+
+/// Runs `request` and writes its output and exit code to `out`; a request
+/// [`Request::check`] turns down gets a [`Reply::Refused`] instead
+pub fn respond(request: &Request, mut out: impl Write) -> Result<()> {
+    let Request::Run { program, args, env } = request else {
+        return Ok(());
+    };
+    if let Err(reason) = request.check() {
+        return write_reply(&mut out, &Reply::Refused(reason));
+    }
+    tracing::info!("helper: running {} {}", program, args.join(" "));
+    let spawned = Command::new(program)
+        .args(args)
+        .envs(env.iter().map(|(key, value)| (key, value)))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn();
+    let mut child = match spawned {
+        Ok(child) => child,
+        Err(e) => return write_reply(&mut out, &Reply::Refused(format!("Couldn't run {}: {}", program, e))),
+    };
+
+    let (tx, rx) = mpsc::channel();
+    forward(child.stdout.take(), Reply::Stdout, tx.clone());
+    forward(child.stderr.take(), Reply::Stderr, tx);
+    for reply in rx {
+        if let Err(e) = write_reply(&mut out, &reply) {
+            // The client is gone, e.g. the dialog was cancelled
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(e);
+        }
+    }
+    let status = child.wait()?;
+    write_reply(&mut out, &Reply::Exit(status.code().unwrap_or(1)))
+}
+
+fn forward(pipe: Option<impl Read + Send + 'static>, wrap: fn(String) -> Reply, tx: mpsc::Sender<Reply>) {
+    let Some(pipe) = pipe else {
+        return;
+    };
+    std::thread::spawn(move || {
+        for line in BufReader::new(pipe).split(b'\n') {
+            let Ok(line) = line else {
+                break;
+            };
+            if tx.send(wrap(String::from_utf8_lossy(&line).into_owned())).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+fn write_reply(out: &mut impl Write, reply: &Reply) -> Result<()> {
+    let mut line = serde_json::to_string(reply)?;
+    line.push('\n');
+    out.write_all(line.as_bytes())?;
+    out.flush()?;
+    Ok(())
+}
+
+fn exit_when_idle(path: PathBuf, idle_since: Arc<Mutex<Option<Instant>>>) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(Duration::from_secs(30));
+        if idle_since.lock().unwrap().is_some_and(|since| since.elapsed() > IDLE_TIMEOUT) {
+            tracing::info!("helper: idle, exiting");
+            let _ = std::fs::remove_file(&path);
+            std::process::exit(0);
+        }
+    });
+}
+
+fn peer_uid(stream: &UnixStream) -> Option<u32> {
+    let mut credentials = libc::ucred { pid: 0, uid: 0, gid: 0 };
+    let mut len = std::mem::size_of::<libc::ucred>() as libc::socklen_t;
+    // SAFETY: credentials and len describe a writable ucred of the right size
+    let result = unsafe {
+        libc::getsockopt(
+            stream.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_PEERCRED,
+            &mut credentials as *mut libc::ucred as *mut libc::c_void,
+            &mut len,
+        )
+    };
+    (result == 0).then_some(credentials.uid)
+}
+
+fn current_uid() -> u32 {
+    unsafe { libc::getuid() }
+}
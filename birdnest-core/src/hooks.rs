@@ -0,0 +1,135 @@
+//! Commands from the config to run after a transaction succeeds.
+//!
+//! Each `[[hooks]]` entry in config.toml names a shell command and,
+//! optionally, the transactions it follows (`install`, `remove`,
+//! `upgrade`; all of them when unset). Hooks run through `sh -c` as the user
+//! who ran BirdNest, with the packages as arguments (`"$@"`) and in the
+//! environment:
+//!
+//! - `BIRDNEST_HOOK_EVENT`: install, remove or upgrade
+//! - `BIRDNEST_PACKAGES`: the packages, space-separated; empty for a full upgrade
+//! - `BIRDNEST_FLATPAK`: 1 when they are Flatpak apps, otherwise 0
+//!
+//! A failing hook is reported but doesn't fail the transaction, which has
+//! already happened.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::process::Command;
+
+use crate::config::Config;
+use crate::utils;
+
+/// The transactions a hook can follow
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HookEvent {
+    Install,
+    Remove,
+    Upgrade,
+}
+
+impl fmt::Display for HookEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            HookEvent::Install => "install",
+            HookEvent::Remove => "remove",
+            HookEvent::Upgrade => "upgrade",
+        })
+    }
+}
+
+/// One `[[hooks]]` entry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hook {
+    pub command: String,
+    /// Transactions it runs after; every kind when empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub on: Vec<HookEvent>,
+}
+
+impl Hook {
+    pub fn runs_on(&self, event: HookEvent) -> bool {
+        self.on.is_empty() || self.on.contains(&event)
+    }
+}
+
+/// What one hook did
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookOutcome {
+    pub command: String,
+    /// stdout and stderr, in that order
+    pub output: String,
+    pub result: Result<(), String>,
+}
+
+impl HookOutcome {
+    /// "Hook `stow -R dotfiles` failed: exited with code 1"
+    pub fn summary(&self) -> String {
+        match &self.result {
+            Ok(()) => format!("Hook `{}` finished", self.command),
+            Err(e) => format!("Hook `{}` failed: {}", self.command, e),
+        }
+    }
+}
+
+/// The `sh -c` invocation for `hook`, with the packages and environment set
+pub fn command(hook: &Hook, event: HookEvent, packages: &[String], flatpak: bool) -> Command {
+    let mut command = Command::new("sh");
+    // The word after the script becomes $0, the packages $1...
+    command
+        .arg("-c")
+        .arg(&hook.command)
+        .arg("birdnest-hook")
+        .args(packages)
+        .env("BIRDNEST_HOOK_EVENT", event.to_string())
+        .env("BIRDNEST_PACKAGES", packages.join(" "))
+        .env("BIRDNEST_FLATPAK", if flatpak { "1" } else { "0" });
+    command
+}
+
+/// Run the hooks that follow `event`, one after another
+pub fn run(hooks: &[Hook], event: HookEvent, packages: &[String], flatpak: bool) -> Vec<HookOutcome> {
+    hooks
+        .iter()
+        .filter(|hook| hook.runs_on(event))
+        .map(|hook| {
+            tracing::debug!("hooks: running `{}` after {}", hook.command, event);
+            let (output, result) = match command(hook, event, packages, flatpak).output() {
+                Ok(output) => {
+                    let text = format!("{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+                    let result = if output.status.success() {
+                        Ok(())
+                    } else {
+                        Err(format!("exited with code {}", output.status.code().unwrap_or(-1)))
+                    };
+                    (text, result)
+                }
+                Err(e) => (String::new(), Err(e.to_string())),
+            };
+            HookOutcome { command: hook.command.clone(), output, result }
+        })
+        .collect()
+}
+
+/// Run the configured hooks after a CLI transaction, printing what they did
+pub fn run_and_print(event: HookEvent, packages: &[String], flatpak: bool) {
+    for outcome in run_configured(event, packages, flatpak) {
+        print!("{}", outcome.output);
+        match outcome.result {
+            Ok(()) => utils::print_info(&outcome.summary()),
+            Err(_) => utils::print_warning(&outcome.summary()),
+        }
+    }
+}
+
+/// Run the hooks from config.toml; none when it can't be read
+pub fn run_configured(event: HookEvent, packages: &[String], flatpak: bool) -> Vec<HookOutcome> {
+    match Config::load() {
+        Ok(config) => run(&config.hooks, event, packages, flatpak),
+        Err(e) => {
+            tracing::warn!("hooks: config unavailable, skipping hooks: {}", e);
+            Vec::new()
+        }
+    }
+}
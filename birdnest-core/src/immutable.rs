@@ -0,0 +1,100 @@
+//! Immutable PikaOS variants, where the root filesystem is managed by ABRoot
+//! or OSTree instead of being changed in place by apt.
+//!
+//! On those systems apt-get would either fail on a read-only `/usr` or change
+//! a root that gets thrown away, so native package changes go through the
+//! layering tool (`abroot pkg`, `rpm-ostree`) and only apply after a reboot.
+//! Flatpak and pikman containers live outside the image and keep working as usual.
+
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::package_manager::BackendCommand;
+
+/// Shown wherever a native package change is about to be made on an immutable system
+pub const REBOOT_NOTICE: &str =
+    "This system is immutable: package changes are staged in a new image and apply after the next reboot.";
+
+/// How the root filesystem is managed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SystemKind {
+    /// A regular system that apt changes in place
+    Traditional,
+    /// A/B root partitions managed by ABRoot
+    Abroot,
+    /// An OSTree deployment, layered with rpm-ostree
+    Ostree,
+}
+
+impl SystemKind {
+    pub fn is_immutable(self) -> bool {
+        self != SystemKind::Traditional
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SystemKind::Traditional => "traditional",
+            SystemKind::Abroot => "ABRoot",
+            SystemKind::Ostree => "OSTree",
+        }
+    }
+
+    /// Commands that layer `packages` into the next image, in order; empty on
+    /// a traditional system, where apt installs directly
+    pub fn install_commands(self, packages: &[String]) -> Vec<BackendCommand> {
+        match self {
+            SystemKind::Traditional => Vec::new(),
+            // `pkg add` only queues the change; `pkg apply` builds the new root
+            SystemKind::Abroot => vec![
+                BackendCommand::new("abroot", &["pkg", "add"], packages),
+                BackendCommand::new("abroot", &["pkg", "apply"], &[]),
+            ],
+            SystemKind::Ostree => vec![BackendCommand::new("rpm-ostree", &["install", "-y"], packages)],
+        }
+    }
+
+    /// Like [`install_commands`](Self::install_commands), for removals
+    pub fn remove_commands(self, packages: &[String]) -> Vec<BackendCommand> {
+        match self {
+            SystemKind::Traditional => Vec::new(),
+            SystemKind::Abroot => vec![
+                BackendCommand::new("abroot", &["pkg", "remove"], packages),
+                BackendCommand::new("abroot", &["pkg", "apply"], &[]),
+            ],
+            SystemKind::Ostree => vec![BackendCommand::new("rpm-ostree", &["uninstall", "-y"], packages)],
+        }
+    }
+
+    /// Commands that stage an upgraded image with the layered packages carried over
+    pub fn upgrade_commands(self) -> Vec<BackendCommand> {
+        match self {
+            SystemKind::Traditional => Vec::new(),
+            SystemKind::Abroot => vec![BackendCommand::new("abroot", &["upgrade"], &[])],
+            SystemKind::Ostree => vec![BackendCommand::new("rpm-ostree", &["upgrade"], &[])],
+        }
+    }
+}
+
+/// The running system's kind, detected once per process
+pub fn detect() -> SystemKind {
+    static KIND: OnceLock<SystemKind> = OnceLock::new();
+    *KIND.get_or_init(|| {
+        let kind = detect_in(Path::new("/"));
+        tracing::debug!("System kind: {}", kind.name());
+        kind
+    })
+}
+
+/// Detect the kind of the system rooted at `root`
+pub fn detect_in(root: &Path) -> SystemKind {
+    // ABRoot ships its configuration with the image
+    let abroot_markers = ["usr/share/abroot/abroot.json", "etc/abroot/abroot.json"];
+    if abroot_markers.iter().any(|marker| root.join(marker).exists()) {
+        return SystemKind::Abroot;
+    }
+    // Written by the initramfs on every OSTree boot
+    if root.join("run/ostree-booted").exists() {
+        return SystemKind::Ostree;
+    }
+    SystemKind::Traditional
+}
@@ -0,0 +1,97 @@
+//! Source-specific flags for installs: what the GUI's install dialogs offer
+//! under "Advanced options", saved as the user's defaults in the config's
+//! `install_options` section. The Flatpak remote has its own setting,
+//! `flatpak_default_remote`.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::package_manager::BackendCommand;
+
+/// The `install_options` section of the config
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstallOptions {
+    /// apt: leave out recommended packages (`--no-install-recommends`)
+    #[serde(default)]
+    pub no_install_recommends: bool,
+    /// apt: the release to install from (`-t`), e.g. "noble-backports"
+    #[serde(default)]
+    pub target_release: Option<String>,
+    /// Flatpak: the branch to install, e.g. "beta"; the app's default when unset
+    #[serde(default)]
+    pub flatpak_branch: Option<String>,
+    /// pikman: the container to install into; pikman's default when unset
+    #[serde(default)]
+    pub pikman_container: Option<String>,
+}
+
+impl InstallOptions {
+    /// Every value has to be one word that doesn't start with "-", since
+    /// each goes on a command line where it could pass for another flag
+    pub fn check(&self) -> Result<()> {
+        let values = [
+            ("Target release", &self.target_release),
+            ("Flatpak branch", &self.flatpak_branch),
+            ("Container", &self.pikman_container),
+        ];
+        for (label, value) in values {
+            let Some(value) = value else {
+                continue;
+            };
+            if value.is_empty() || value.starts_with('-') || value.contains(char::is_whitespace) || value.contains('/') {
+                anyhow::bail!("{} '{}' has to be a single name like \"stable\"", label, value);
+            }
+        }
+        Ok(())
+    }
+
+    /// Flags that go after `apt install`
+    pub fn apt_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if self.no_install_recommends {
+            args.push("--no-install-recommends".to_string());
+        }
+        if let Some(release) = &self.target_release {
+            args.extend(["-t".to_string(), release.clone()]);
+        }
+        args
+    }
+
+    /// `commands` with [`apt_args`](Self::apt_args) added to its apt and
+    /// apt-get installs; other tools' commands, like an ABRoot layer, are
+    /// left as they are
+    pub fn apply_apt(&self, commands: Vec<BackendCommand>) -> Vec<BackendCommand> {
+        commands
+            .into_iter()
+            .map(|mut command| {
+                let is_apt = matches!(command.program.as_str(), "apt" | "apt-get");
+                if is_apt && command.args.first().is_some_and(|arg| arg == "install") {
+                    command.args.splice(1..1, self.apt_args());
+                }
+                command
+            })
+            .collect()
+    }
+
+    /// What `flatpak install` takes for `app`: "app//branch" with a branch set
+    pub fn flatpak_ref(&self, app: &str) -> String {
+        match &self.flatpak_branch {
+            Some(branch) => format!("{}//{}", app, branch),
+            None => app.to_string(),
+        }
+    }
+
+    /// Flags that go after `pikman install`
+    pub fn pikman_args(&self) -> Vec<String> {
+        match &self.pikman_container {
+            Some(container) => vec!["--name".to_string(), container.clone()],
+            None => Vec::new(),
+        }
+    }
+}
+
+/// A text field's value as an option: None when blank
+pub fn optional(value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
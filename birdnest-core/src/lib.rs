@@ -0,0 +1,103 @@
+//! Backend for BirdNest, the PikaOS package manager.
+//!
+//! This crate holds everything that talks to the system - pikman/apt,
+//! Flatpak, the dpkg status database and BirdNest's own config and cache -
+//! so the iced GUI and the clap CLI can stay thin frontends, and other
+//! PikaOS tools can reuse the same logic.
+//!
+//! - [`package_manager::PackageManager`]: install/remove/search/upgrade via pikman or apt
+//! - [`dnf::DnfBackend`]: the package backend for Fedora-based systems
+//! - [`pacman::PacmanBackend`]: the package backend for Arch-based systems, with AUR packages via paru or yay
+//! - [`flatpak::FlatpakManager`]: the same operations for Flatpak applications
+//! - [`appimage`]: AppImages in `~/Applications`, their menu entries and zsync updates
+//! - [`dpkg`]: parsing of `/var/lib/dpkg/status` and `dpkg-query` output
+//! - [`batch`]: installs and removals across system packages, Flatpaks and pikman, grouped into one batch
+//! - [`plan`]: `apt-get -s` transaction simulation shared by CLI prompts and GUI dialogs
+//! - [`output`]: aligned, colored search and list tables with source badges
+//! - [`progress`]: progress bars for apt, pikman and Flatpak operations in the CLI, and per-package progress for the GUI
+//! - [`cache`]: the on-disk installed-package cache
+//! - [`db_watch`]: inotify watches on the package and Flatpak databases, to notice changes made by other tools
+//! - [`disk_usage`]: installed sizes of packages and Flatpak apps, laid out as a treemap
+//! - [`footprint`]: CPU and memory of the running processes an installed package started
+//! - [`events`]: the bus backends publish transaction and cache events on for the frontends
+//! - [`appstream`]: display names, icons, screenshots, categories and release notes from AppStream catalogs
+//! - [`changelog`]: Debian changelogs and Flatpak release notes, to read before upgrading
+//! - [`search_index`]: in-memory fuzzy index of available packages for as-you-type search
+//! - [`environments`]: TOML templates for development environments in pikman containers
+//! - [`pikman_exports`]: applications and programs exported from pikman containers to the host, and exporting more
+//! - [`immutable`]: ABRoot/OSTree detection and package layering on immutable variants
+//! - [`repos`]: APT sources in one-line and deb822 format, and editing them
+//! - [`ppa`]: Launchpad PPAs, added with a signing key checked against Launchpad
+//! - [`sources_backup`]: snapshots of the apt sources taken before each edit, and restoring them
+//! - [`digest`]: the last week's installs, upgrades, removals and sources changes, from apt's history and dpkg's log
+//! - [`startup`]: autostart entries and user services that start at login, with their packages, and turning them off
+//! - [`restart`]: reboot-required, kernel and needrestart checks after upgrades
+//! - [`schedule`]: the hours and connection types background refreshes are limited to
+//! - [`notify`]: desktop notifications, the terminal bell and the dock badge, chosen per frontend
+//! - [`proxy`]: apt's and the environment's proxies, and the configured one applied to every backend
+//! - [`update_check`]: periodic counts of available updates for the GUI's badge and notifications
+//! - [`predownload`]: Flatpak runtimes queued to download overnight ahead of their apps
+//! - [`deferred_removal`]: removed GUI apps kept hidden for a few days before they are uninstalled
+//! - [`hooks`]: user commands from the config run after installs, removals and upgrades
+//! - [`install_options`]: apt, Flatpak and pikman flags for installs, with the user's defaults
+//! - [`shutdown_updates`]: updates staged to install from a systemd unit while the system shuts down
+//! - [`elevation`]: choosing and detecting pkexec, sudo, `sudo -A` or the privileged helper
+//! - [`helper`]: the privileged helper daemon, which runs package tools as root for a whole session
+//! - [`config::Config`]: user configuration in `~/.config/birdnest/config.toml`
+//! - [`script_export`]: shell script and Ansible generation for package selections
+//! - [`manifest`]: TOML manifests of installed apt packages, Flatpaks and pikman packages, to export and import
+//! - [`offline_bundle`]: apt packages with their dependencies and Flatpaks with their runtimes, downloaded to install without a network
+//! - [`sbom`]: SPDX and CycloneDX inventories of installed packages, Flatpaks and exported container programs
+//! - [`trim`]: a curated catalog of optional preinstalled apps, offered for removal unless a meta-package depends on them
+//! - [`terminal`]: finding the user's terminal emulator and opening interactive commands in it
+//! - [`utils`]: command execution and terminal output helpers
+//! - `mock` (feature `mock`): fixture-driven backend for tests and demos
+
+pub mod appimage;
+pub mod appstream;
+pub mod batch;
+pub mod cache;
+pub mod changelog;
+pub mod config;
+pub mod db_watch;
+pub mod deferred_removal;
+pub mod digest;
+pub mod disk_usage;
+pub mod dnf;
+pub mod dpkg;
+pub mod elevation;
+pub mod environments;
+pub mod events;
+pub mod flatpak;
+pub mod footprint;
+pub mod helper;
+pub mod hooks;
+pub mod immutable;
+pub mod install_options;
+pub mod manifest;
+#[cfg(feature = "mock")]
+pub mod mock;
+pub mod notify;
+pub mod offline_bundle;
+pub mod package_manager;
+pub mod output;
+pub mod pacman;
+pub mod pikman_exports;
+pub mod plan;
+pub mod ppa;
+pub mod predownload;
+pub mod progress;
+pub mod proxy;
+pub mod repos;
+pub mod restart;
+pub mod schedule;
+pub mod sbom;
+pub mod script_export;
+pub mod search_index;
+pub mod shutdown_updates;
+pub mod sources_backup;
+pub mod startup;
+pub mod terminal;
+pub mod trim;
+pub mod update_check;
+pub mod utils;
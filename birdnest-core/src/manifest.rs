@@ -0,0 +1,330 @@
+//! Manifests of what a user installed - manually installed apt packages,
+//! Flatpak apps with the remotes they came from, and packages in pikman's
+//! AUR, Fedora and Alpine containers - as TOML, so a system can be rebuilt
+//! after a reinstall with `birdnest import`.
+//!
+//! ```toml
+//! version = 1
+//! host = "pika-laptop"
+//! created = 1714995900
+//! apt = ["git", "htop"]
+//!
+//! [[flatpak]]
+//! app = "org.gimp.GIMP"
+//! remote = "flathub"
+//! url = "https://dl.flathub.org/repo/"
+//!
+//! [pikman]
+//! aur = ["yay-bin"]
+//! ```
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::output::{self, Source};
+use crate::package_manager::{host_backend, BackendCommand};
+use crate::utils::{self, confirm, run_command, run_command_interactive};
+
+/// The manifest format this build writes and reads
+pub const VERSION: u32 = 1;
+
+/// pikman's container distros, by the flag that selects them
+pub const PIKMAN_DISTROS: [&str; 3] = ["aur", "fedora", "alpine"];
+
+// A remote a manifest names without saying where it is
+const DEFAULT_FLATPAK_REMOTE: &str = "flathub";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FlatpakApp {
+    pub app: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote: Option<String>,
+    /// The remote's URL, so importing can add it when it is missing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+/// Packages in pikman's containers, per distro
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PikmanPackages {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aur: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fedora: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alpine: Vec<String>,
+}
+
+impl PikmanPackages {
+    /// Each distro's packages, in [`PIKMAN_DISTROS`] order
+    pub fn by_distro(&self) -> [(&'static str, &Vec<String>); 3] {
+        [("aur", &self.aur), ("fedora", &self.fedora), ("alpine", &self.alpine)]
+    }
+
+    fn by_distro_mut(&mut self, distro: &str) -> Option<&mut Vec<String>> {
+        match distro {
+            "aur" => Some(&mut self.aur),
+            "fedora" => Some(&mut self.fedora),
+            "alpine" => Some(&mut self.alpine),
+            _ => None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.aur.is_empty() && self.fedora.is_empty() && self.alpine.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub version: u32,
+    #[serde(default)]
+    pub host: String,
+    /// Unix time it was exported
+    #[serde(default)]
+    pub created: i64,
+    #[serde(default)]
+    pub apt: Vec<String>,
+    #[serde(default)]
+    pub flatpak: Vec<FlatpakApp>,
+    #[serde(default, skip_serializing_if = "PikmanPackages::is_empty")]
+    pub pikman: PikmanPackages,
+}
+
+impl Manifest {
+    /// What this machine has installed. apt-mark, Flatpak and pikman are
+    /// each optional: a tool that isn't there leaves its part empty.
+    pub fn collect() -> Result<Self> {
+        let mut manifest = Manifest { version: VERSION, created: utils::now() as i64, ..Manifest::default() };
+        manifest.host = std::fs::read_to_string("/proc/sys/kernel/hostname").map(|name| name.trim().to_string()).unwrap_or_default();
+
+        if host_backend().name() == "apt" {
+            manifest.apt = parse_lines(&run_command("apt-mark", &["showmanual"], false)?);
+        }
+        match run_command("flatpak", &["list", "--app", "--columns=application,origin"], false) {
+            Ok(apps) => {
+                let remotes = run_command("flatpak", &["remotes", "--columns=name,url"], false).unwrap_or_default();
+                manifest.flatpak = parse_flatpak_apps(&apps, &remotes);
+            }
+            Err(e) => tracing::debug!("manifest: no Flatpaks listed: {}", e),
+        }
+        if utils::find_in_path("pikman").is_some() {
+            for distro in PIKMAN_DISTROS {
+                let flag = format!("--{}", distro);
+                match run_command("pikman", &[&flag, "list", "--installed"], false) {
+                    Ok(output) => {
+                        if let Some(packages) = manifest.pikman.by_distro_mut(distro) {
+                            *packages = parse_pikman_installed(&output);
+                        }
+                    }
+                    Err(e) => tracing::debug!("manifest: nothing listed from pikman {}: {}", flag, e),
+                }
+            }
+        }
+        Ok(manifest)
+    }
+
+    /// Like [`collect`](Self::collect), but with every installed system
+    /// package rather than the manual ones: what importing compares against
+    pub fn installed() -> Result<Self> {
+        let mut manifest = Self::collect()?;
+        if let Ok(packages) = host_backend().list_installed() {
+            manifest.apt = packages.into_iter().map(|package| package.name).collect();
+        }
+        Ok(manifest)
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self> {
+        let manifest: Manifest = toml::from_str(text)?;
+        if manifest.version > VERSION {
+            anyhow::bail!("The manifest is version {}, newer than this BirdNest reads ({})", manifest.version, VERSION);
+        }
+        let pikman = manifest.pikman.by_distro().into_iter().flat_map(|(_, packages)| packages.iter());
+        for name in manifest.apt.iter().chain(manifest.flatpak.iter().map(|f| &f.app)).chain(pikman) {
+            check_name(name)?;
+        }
+        Ok(manifest)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path).with_context(|| format!("Couldn't read {}", path.display()))?;
+        Self::from_toml(&text).with_context(|| format!("{} isn't a BirdNest manifest", path.display()))
+    }
+
+    pub fn to_toml(&self) -> String {
+        let mut text = String::from("# Installed software, exported by BirdNest. Restore it with: birdnest import <file>\n");
+        text.push_str(&toml::to_string_pretty(self).unwrap_or_default());
+        text
+    }
+
+    /// How many packages and apps it lists
+    pub fn len(&self) -> usize {
+        self.apt.len() + self.flatpak.len() + self.pikman.by_distro().iter().map(|(_, packages)| packages.len()).sum::<usize>()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// What this manifest lists that `installed` doesn't have
+    pub fn missing(&self, installed: &Manifest) -> Manifest {
+        let without = |wanted: &[String], have: &[String]| -> Vec<String> {
+            let have: HashSet<&String> = have.iter().collect();
+            wanted.iter().filter(|name| !have.contains(name)).cloned().collect()
+        };
+        let have_apps: HashSet<&String> = installed.flatpak.iter().map(|f| &f.app).collect();
+        Manifest {
+            apt: without(&self.apt, &installed.apt),
+            flatpak: self.flatpak.iter().filter(|f| !have_apps.contains(&f.app)).cloned().collect(),
+            pikman: PikmanPackages {
+                aur: without(&self.pikman.aur, &installed.pikman.aur),
+                fedora: without(&self.pikman.fedora, &installed.pikman.fedora),
+                alpine: without(&self.pikman.alpine, &installed.pikman.alpine),
+            },
+            ..self.clone()
+        }
+    }
+
+    /// What installing it all runs, in order: apt packages as root, then
+    /// each Flatpak remote (added if missing) with its apps, then each
+    /// pikman distro's packages as the user
+    pub fn steps(&self) -> Vec<BackendCommand> {
+        let mut steps = Vec::new();
+        if !self.apt.is_empty() {
+            steps.push(BackendCommand::new("apt-get", &["install", "-y"], &self.apt));
+        }
+        steps.extend(flatpak_steps(&self.flatpak, &[]));
+        for (distro, packages) in self.pikman.by_distro() {
+            if !packages.is_empty() {
+                steps.push(BackendCommand::as_user("pikman", &["install", &format!("--{}", distro), "-y"], packages));
+            }
+        }
+        steps
+    }
+}
+
+/// Each remote `apps` come from, added if missing, then `flatpak install`
+/// with `options` for that remote's apps
+pub(crate) fn flatpak_steps(apps: &[FlatpakApp], options: &[&str]) -> Vec<BackendCommand> {
+    let mut remotes: Vec<(String, Option<String>, Vec<String>)> = Vec::new();
+    for app in apps {
+        let remote = app.remote.clone().unwrap_or_else(|| DEFAULT_FLATPAK_REMOTE.to_string());
+        match remotes.iter_mut().find(|(name, _, _)| *name == remote) {
+            Some((_, url, apps)) => {
+                if url.is_none() {
+                    *url = app.url.clone();
+                }
+                apps.push(app.app.clone());
+            }
+            None => remotes.push((remote, app.url.clone(), vec![app.app.clone()])),
+        }
+    }
+    let mut steps = Vec::new();
+    for (remote, url, apps) in remotes {
+        if let Some(url) = url {
+            steps.push(BackendCommand::as_user("flatpak", &["remote-add", "--if-not-exists", &remote, &url], &[]));
+        }
+        let mut args = vec!["install", "-y", "--noninteractive"];
+        args.extend(options);
+        args.push(&remote);
+        steps.push(BackendCommand::as_user("flatpak", &args, &apps));
+    }
+    steps
+}
+
+// Every name goes on a command line after the flags, so none may pass for one
+pub(crate) fn check_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.starts_with('-') || name.contains(char::is_whitespace) {
+        anyhow::bail!("'{}' isn't a package or app name", name);
+    }
+    Ok(())
+}
+
+fn parse_lines(text: &str) -> Vec<String> {
+    text.lines().map(str::trim).filter(|line| !line.is_empty()).map(str::to_string).collect()
+}
+
+/// `flatpak list --app --columns=application,origin` rows, each with its
+/// remote's URL from `flatpak remotes --columns=name,url`
+pub fn parse_flatpak_apps(apps: &str, remotes: &str) -> Vec<FlatpakApp> {
+    let urls: Vec<(&str, &str)> = remotes
+        .lines()
+        .filter_map(|line| {
+            let (name, url) = line.split_once('\t')?;
+            Some((name.trim(), url.trim()))
+        })
+        .collect();
+    apps.lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t').map(str::trim);
+            let app = fields.next().filter(|app| !app.is_empty())?.to_string();
+            let remote = fields.next().filter(|remote| !remote.is_empty()).map(str::to_string);
+            let url = remote
+                .as_deref()
+                .and_then(|remote| urls.iter().find(|(name, _)| *name == remote))
+                .map(|(_, url)| url.to_string())
+                .filter(|url| !url.is_empty());
+            Some(FlatpakApp { app, remote, url })
+        })
+        .collect()
+}
+
+/// Package names from `pikman --<distro> list --installed`, which prints an
+/// apt-style listing, or one "name version" line per package from the
+/// container's own tool (less the architecture dnf appends to names)
+pub fn parse_pikman_installed(output: &str) -> Vec<String> {
+    let rows = output::parse_apt_listing(output, Source::Pikman);
+    if !rows.is_empty() {
+        return rows.into_iter().map(|row| row.name).collect();
+    }
+    output
+        .lines()
+        .filter(|line| !line.starts_with(char::is_whitespace) && !line.trim_end().ends_with(':') && !line.trim_end().ends_with("..."))
+        .filter_map(|line| line.split_whitespace().next())
+        .map(|name| {
+            let name = name.rsplit('/').next().unwrap_or(name);
+            [".x86_64", ".aarch64", ".i686", ".noarch"]
+                .iter()
+                .find_map(|arch| name.strip_suffix(arch))
+                .unwrap_or(name)
+                .to_string()
+        })
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+/// Write this machine's manifest to `path`, or print it when None
+pub fn export(path: Option<&Path>) -> Result<Manifest> {
+    let manifest = Manifest::collect()?;
+    match path {
+        Some(path) => std::fs::write(path, manifest.to_toml()).with_context(|| format!("Couldn't write {}", path.display()))?,
+        None => print!("{}", manifest.to_toml()),
+    }
+    Ok(manifest)
+}
+
+/// Install what `manifest` lists and this machine lacks, showing the
+/// commands first and asking unless `yes`
+pub fn import(manifest: &Manifest, yes: bool) -> Result<()> {
+    let missing = manifest.missing(&Manifest::installed()?);
+    let steps = missing.steps();
+    if steps.is_empty() {
+        utils::print_success("Everything in the manifest is already installed");
+        return Ok(());
+    }
+    println!("Installing {} of the manifest's {} packages and apps:", missing.len(), manifest.len());
+    for step in &steps {
+        println!("  {}", step.display());
+    }
+    if !yes && !confirm("Install them?")? {
+        utils::print_info("Cancelled");
+        return Ok(());
+    }
+    for step in &steps {
+        run_command_interactive(&step.program, &step.args(), !step.as_user)?;
+    }
+    utils::print_success(&format!("Installed {} packages and apps from the manifest", missing.len()));
+    Ok(())
+}
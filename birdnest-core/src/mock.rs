@@ -0,0 +1,290 @@
+//! Fixture-driven [`PackageBackend`] for tests and for running the GUI
+//! without touching the real system (enabled by the `mock` feature).
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::changelog::{self, ChangelogEntry};
+use crate::dpkg::InstalledPackage;
+use crate::package_manager::{PackageBackend, PackageDetails, PackageSummary, PackageUpdate, ReverseDependency};
+
+/// Fixture shipped with the crate, used when no fixture file is given
+pub const DEFAULT_FIXTURE: &str = include_str!("../fixtures/system.json");
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Fixture {
+    #[serde(default)]
+    pub installed: Vec<FixturePackage>,
+    #[serde(default)]
+    pub available: Vec<FixturePackage>,
+    /// Installed packages reported as held
+    #[serde(default)]
+    pub held: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FixturePackage {
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub homepage: Option<String>,
+    /// What `files` reports once installed
+    #[serde(default)]
+    pub files: Vec<String>,
+    /// Names of packages it depends on, for `reverse_dependencies`
+    #[serde(default)]
+    pub depends: Vec<String>,
+    /// Disk space once installed, reported by `list_installed`
+    #[serde(default)]
+    pub installed_bytes: Option<u64>,
+    /// debian/changelog text for `changelog`
+    #[serde(default)]
+    pub changelog: Option<String>,
+}
+
+/// In-memory package system; install/remove update the installed list so
+/// follow-up listings reflect the change
+#[derive(Debug)]
+pub struct MockBackend {
+    state: Mutex<Fixture>,
+    transactions: Mutex<Vec<String>>,
+}
+
+impl MockBackend {
+    pub fn new(fixture: Fixture) -> Self {
+        Self {
+            state: Mutex::new(fixture),
+            transactions: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        let fixture: Fixture = serde_json::from_str(json).context("Invalid mock fixture")?;
+        Ok(Self::new(fixture))
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read mock fixture {}", path.display()))?;
+        Self::from_json(&json)
+    }
+
+    pub fn with_default_fixture() -> Self {
+        Self::from_json(DEFAULT_FIXTURE).expect("bundled fixture is valid")
+    }
+
+    /// Transactions performed so far, e.g. "install git-lfs"
+    pub fn transactions(&self) -> Vec<String> {
+        self.transactions.lock().unwrap().clone()
+    }
+
+    fn record(&self, action: &str, packages: &[String]) {
+        self.transactions
+            .lock()
+            .unwrap()
+            .push(format!("{} {}", action, packages.join(" ")));
+    }
+}
+
+impl PackageBackend for MockBackend {
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+
+    fn search(&self, query: &str) -> Result<Vec<PackageSummary>> {
+        let query = query.to_lowercase();
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .available
+            .iter()
+            .filter(|pkg| pkg.name.to_lowercase().contains(&query) || pkg.description.to_lowercase().contains(&query))
+            .map(|pkg| PackageSummary {
+                name: pkg.name.clone(),
+                version: pkg.version.clone(),
+                description: pkg.description.clone(),
+            })
+            .collect())
+    }
+
+    fn info(&self, package: &str) -> Result<PackageDetails> {
+        let state = self.state.lock().unwrap();
+        state
+            .installed
+            .iter()
+            .chain(state.available.iter())
+            .find(|pkg| pkg.name == package)
+            .map(|pkg| PackageDetails {
+                name: pkg.name.clone(),
+                version: pkg.version.clone(),
+                description: pkg.description.clone(),
+                installed_size: None,
+                download_size: None,
+                homepage: pkg.homepage.clone(),
+                ..Default::default()
+            })
+            .ok_or_else(|| anyhow::anyhow!("Unable to locate package {}", package))
+    }
+
+    fn list_installed(&self) -> Result<Vec<InstalledPackage>> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .installed
+            .iter()
+            .map(|pkg| InstalledPackage {
+                name: pkg.name.clone(),
+                version: pkg.version.clone(),
+                installed_bytes: pkg.installed_bytes,
+                ..InstalledPackage::default()
+            })
+            .collect())
+    }
+
+    // Installed packages whose fixture entry under "available" has another version
+    fn list_upgradable(&self) -> Result<Vec<PackageUpdate>> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .installed
+            .iter()
+            .filter_map(|installed| {
+                let available = state.available.iter().find(|pkg| pkg.name == installed.name)?;
+                (available.version != installed.version).then(|| PackageUpdate {
+                    name: installed.name.clone(),
+                    current_version: installed.version.clone(),
+                    new_version: available.version.clone(),
+                })
+            })
+            .collect())
+    }
+
+    fn install(&self, packages: &[String]) -> Result<()> {
+        if packages.is_empty() {
+            anyhow::bail!("No packages specified");
+        }
+        let mut state = self.state.lock().unwrap();
+        let mut to_install = Vec::new();
+        for name in packages {
+            match state.available.iter().find(|pkg| &pkg.name == name) {
+                Some(pkg) => to_install.push(pkg.clone()),
+                None => anyhow::bail!("Unable to locate package {}", name),
+            }
+        }
+        for pkg in to_install {
+            if !state.installed.iter().any(|p| p.name == pkg.name) {
+                state.installed.push(pkg);
+            }
+        }
+        state.installed.sort_by(|a, b| a.name.cmp(&b.name));
+        drop(state);
+        self.record("install", packages);
+        Ok(())
+    }
+
+    fn all_packages(&self) -> Result<Vec<PackageSummary>> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .available
+            .iter()
+            .map(|pkg| PackageSummary {
+                name: pkg.name.clone(),
+                version: pkg.version.clone(),
+                description: pkg.description.clone(),
+            })
+            .collect())
+    }
+
+    fn files(&self, package: &str) -> Result<Vec<String>> {
+        let state = self.state.lock().unwrap();
+        state
+            .installed
+            .iter()
+            .find(|pkg| pkg.name == package)
+            .map(|pkg| pkg.files.clone())
+            .ok_or_else(|| anyhow::anyhow!("Package '{}' is not installed", package))
+    }
+
+    fn owner(&self, path: &str) -> Result<Option<String>> {
+        let state = self.state.lock().unwrap();
+        Ok(state.installed.iter().find(|pkg| pkg.files.iter().any(|file| file == path)).map(|pkg| pkg.name.clone()))
+    }
+
+    fn descriptions(&self, packages: &[String]) -> Result<HashMap<String, String>> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .installed
+            .iter()
+            .filter(|pkg| packages.contains(&pkg.name) && !pkg.description.is_empty())
+            .map(|pkg| (pkg.name.clone(), pkg.description.clone()))
+            .collect())
+    }
+
+    fn held(&self) -> Result<Vec<String>> {
+        Ok(self.state.lock().unwrap().held.clone())
+    }
+
+    fn reverse_dependencies(&self, package: &str) -> Result<Vec<ReverseDependency>> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .installed
+            .iter()
+            .filter(|pkg| pkg.depends.iter().any(|dependency| dependency == package))
+            .map(|pkg| ReverseDependency { name: pkg.name.clone(), alternative: false })
+            .collect())
+    }
+
+    fn changelog(&self, package: &str) -> Result<Vec<ChangelogEntry>> {
+        let state = self.state.lock().unwrap();
+        state
+            .available
+            .iter()
+            .find(|pkg| pkg.name == package)
+            .and_then(|pkg| pkg.changelog.as_deref())
+            .map(changelog::parse_debian)
+            .ok_or_else(|| anyhow::anyhow!("No changelog for {}", package))
+    }
+
+    fn remove(&self, packages: &[String]) -> Result<()> {
+        if packages.is_empty() {
+            anyhow::bail!("No packages specified");
+        }
+        let mut state = self.state.lock().unwrap();
+        for name in packages {
+            if !state.installed.iter().any(|pkg| &pkg.name == name) {
+                anyhow::bail!("Package '{}' is not installed, so not removed", name);
+            }
+        }
+        state.installed.retain(|pkg| !packages.contains(&pkg.name));
+        drop(state);
+        self.record("remove", packages);
+        Ok(())
+    }
+
+    fn upgrade(&self, packages: &[String]) -> Result<()> {
+        if packages.is_empty() {
+            anyhow::bail!("No packages specified");
+        }
+        let mut state = self.state.lock().unwrap();
+        let mut to_upgrade = Vec::new();
+        for name in packages {
+            if !state.installed.iter().any(|pkg| &pkg.name == name) {
+                anyhow::bail!("Package '{}' is not installed, so not upgraded", name);
+            }
+            match state.available.iter().find(|pkg| &pkg.name == name) {
+                Some(pkg) => to_upgrade.push(pkg.clone()),
+                None => anyhow::bail!("Unable to locate package {}", name),
+            }
+        }
+        for pkg in to_upgrade {
+            if let Some(installed) = state.installed.iter_mut().find(|p| p.name == pkg.name) {
+                installed.version = pkg.version;
+            }
+        }
+        drop(state);
+        self.record("upgrade", packages);
+        Ok(())
+    }
+}
@@ -0,0 +1,289 @@
+//! Telling the user something finished, through whichever channels suit
+//! where BirdNest runs.
+//!
+//! Each channel is a [`Notifier`]: freedesktop notifications through
+//! notify-send, the terminal bell with a one-line summary on stderr, and the
+//! badge on BirdNest's icon in the dock or task manager. The badge goes out
+//! as the Unity LauncherEntry D-Bus signal, which KDE Plasma, Dash to Dock and
+//! most docks show. The `notifications` section of the config picks the
+//! channels for the GUI and for the CLI separately, and [`Notifiers::select`]
+//! leaves out those that can't reach the user, such as the bell when stderr
+//! isn't a terminal.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::io::{IsTerminal, Write};
+
+use crate::update_check::UpdateCount;
+use crate::utils::run_command;
+
+/// The desktop entry the badge belongs to, as build-and-install.sh names it
+pub const DESKTOP_ID: &str = "com.github.birdnest.desktop";
+
+/// A channel a notification can go out on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NotifierKind {
+    Desktop,
+    Terminal,
+    Badge,
+}
+
+/// Which frontend is notifying
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Context {
+    Gui,
+    Cli,
+}
+
+/// The `notifications` section of the config
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationConfig {
+    /// Channels the GUI uses
+    pub gui: Vec<NotifierKind>,
+    /// Channels the CLI uses
+    pub cli: Vec<NotifierKind>,
+    /// Announce finished transactions, in the GUI only while none of its
+    /// windows has focus
+    pub on_finish: bool,
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        Self {
+            gui: vec![NotifierKind::Desktop, NotifierKind::Badge],
+            cli: vec![NotifierKind::Terminal],
+            on_finish: true,
+        }
+    }
+}
+
+impl NotificationConfig {
+    pub fn kinds(&self, context: Context) -> &[NotifierKind] {
+        match context {
+            Context::Gui => &self.gui,
+            Context::Cli => &self.cli,
+        }
+    }
+}
+
+/// Something to tell the user
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notification {
+    /// One line: "Finished: apt install -y vlc"
+    pub summary: String,
+    /// More detail, such as why a transaction failed; may be empty
+    pub body: String,
+    /// Icon name from the freedesktop icon theme
+    pub icon: &'static str,
+    pub failed: bool,
+    /// A number for the badge, such as the updates available
+    pub count: Option<usize>,
+}
+
+impl Notification {
+    /// A transaction described by `label` ended with `result`
+    pub fn finished(label: &str, result: &Result<(), String>) -> Self {
+        match result {
+            Ok(()) => Self {
+                summary: format!("Finished: {}", label),
+                body: String::new(),
+                icon: "system-software-install",
+                failed: false,
+                count: None,
+            },
+            Err(e) => Self {
+                summary: format!("Failed: {}", label),
+                body: e.clone(),
+                icon: "dialog-error",
+                failed: true,
+                count: None,
+            },
+        }
+    }
+
+    /// An update check found `count`
+    pub fn updates(count: &UpdateCount) -> Self {
+        Self {
+            summary: count.summary(),
+            body: "Open BirdNest's Updates tab to install them.".to_string(),
+            icon: "system-software-update",
+            failed: false,
+            count: Some(count.total()),
+        }
+    }
+}
+
+/// A channel notifications go out on
+pub trait Notifier: Send + Sync + std::fmt::Debug {
+    fn kind(&self) -> NotifierKind;
+    /// Whether it can reach the user from here
+    fn available(&self) -> bool {
+        true
+    }
+    fn notify(&self, notification: &Notification) -> Result<()>;
+    /// The user is back, so whatever asks for their attention can stop
+    fn dismiss(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+// A graphical session to show notifications and badges in
+fn graphical_session() -> bool {
+    std::env::var_os("WAYLAND_DISPLAY").is_some() || std::env::var_os("DISPLAY").is_some()
+}
+
+/// Freedesktop notifications through notify-send
+#[derive(Debug)]
+pub struct Desktop;
+
+impl Notifier for Desktop {
+    fn kind(&self) -> NotifierKind {
+        NotifierKind::Desktop
+    }
+
+    fn available(&self) -> bool {
+        graphical_session()
+    }
+
+    fn notify(&self, notification: &Notification) -> Result<()> {
+        let icon = format!("--icon={}", notification.icon);
+        let urgency = if notification.failed { "--urgency=critical" } else { "--urgency=normal" };
+        run_command(
+            "notify-send",
+            &["--app-name=BirdNest", &icon, urgency, &notification.summary, &notification.body],
+            false,
+        )?;
+        Ok(())
+    }
+}
+
+/// The terminal bell and a one-line summary on stderr, for the end of a
+/// long CLI run; errors themselves are printed by the CLI as usual
+#[derive(Debug)]
+pub struct Terminal;
+
+impl Terminal {
+    /// "✓ Finished: apt install vlc" and the bell
+    pub fn line(notification: &Notification) -> String {
+        let mark = if notification.failed { "✗" } else { "✓" };
+        format!("\x07{} {}", mark, notification.summary)
+    }
+}
+
+impl Notifier for Terminal {
+    fn kind(&self) -> NotifierKind {
+        NotifierKind::Terminal
+    }
+
+    fn available(&self) -> bool {
+        std::io::stderr().is_terminal()
+    }
+
+    fn notify(&self, notification: &Notification) -> Result<()> {
+        writeln!(std::io::stderr(), "{}", Self::line(notification))?;
+        Ok(())
+    }
+}
+
+/// The badge on BirdNest's icon in the dock or task manager: the count a
+/// notification carries, and an urgent hint until [`dismiss`](Notifier::dismiss)
+#[derive(Debug)]
+pub struct Badge;
+
+impl Badge {
+    /// The LauncherEntry properties a notification sets
+    pub fn properties(notification: &Notification) -> String {
+        match notification.count {
+            Some(count) => format!("{{'count': <int64 {}>, 'count-visible': <{}>}}", count, count > 0),
+            None => "{'urgent': <true>}".to_string(),
+        }
+    }
+
+    fn emit(properties: &str) -> Result<()> {
+        // Object path elements can't have dots
+        let object_path = format!("/com/canonical/unity/launcherentry/{}", DESKTOP_ID.trim_end_matches(".desktop").replace('.', "_"));
+        let app_uri = format!("application://{}", DESKTOP_ID);
+        run_command(
+            "gdbus",
+            &[
+                "emit",
+                "--session",
+                "--object-path",
+                &object_path,
+                "--signal",
+                "com.canonical.Unity.LauncherEntry.Update",
+                &app_uri,
+                properties,
+            ],
+            false,
+        )?;
+        Ok(())
+    }
+}
+
+impl Notifier for Badge {
+    fn kind(&self) -> NotifierKind {
+        NotifierKind::Badge
+    }
+
+    fn available(&self) -> bool {
+        graphical_session()
+    }
+
+    fn notify(&self, notification: &Notification) -> Result<()> {
+        Self::emit(&Self::properties(notification))
+    }
+
+    // The count stays; it goes when the updates do
+    fn dismiss(&self) -> Result<()> {
+        Self::emit("{'urgent': <false>}")
+    }
+}
+
+pub fn notifier(kind: NotifierKind) -> Box<dyn Notifier> {
+    match kind {
+        NotifierKind::Desktop => Box::new(Desktop),
+        NotifierKind::Terminal => Box::new(Terminal),
+        NotifierKind::Badge => Box::new(Badge),
+    }
+}
+
+/// The channels a frontend notifies on
+#[derive(Debug, Default)]
+pub struct Notifiers {
+    notifiers: Vec<Box<dyn Notifier>>,
+}
+
+impl Notifiers {
+    /// The configured channels for `context` that can reach the user here
+    pub fn select(config: &NotificationConfig, context: Context) -> Self {
+        Self::new(config.kinds(context).iter().copied().map(notifier).filter(|notifier| notifier.available()).collect())
+    }
+
+    pub fn new(notifiers: Vec<Box<dyn Notifier>>) -> Self {
+        Self { notifiers }
+    }
+
+    pub fn kinds(&self) -> Vec<NotifierKind> {
+        self.notifiers.iter().map(|notifier| notifier.kind()).collect()
+    }
+
+    /// Sends `notification` on every channel; one failing doesn't stop the others
+    pub fn notify(&self, notification: &Notification) {
+        for notifier in &self.notifiers {
+            if let Err(e) = notifier.notify(notification) {
+                tracing::warn!("notify: {:?} failed: {}", notifier.kind(), e);
+            }
+        }
+    }
+
+    pub fn dismiss(&self) {
+        for notifier in &self.notifiers {
+            if let Err(e) = notifier.dismiss() {
+                tracing::debug!("notify: dismissing {:?} failed: {}", notifier.kind(), e);
+            }
+        }
+    }
+}
@@ -0,0 +1,277 @@
+//! Offline bundles: apt packages with everything they depend on and Flatpak
+//! apps with their runtimes, downloaded into a directory that can be copied
+//! to a USB stick and installed from on a machine without a network.
+//!
+//! ```text
+//! bundle/
+//!   bundle.toml     what was asked for, and each .deb in the bundle
+//!   debs/           the .debs, from `apt-get download`
+//!   .ostree/repo    the Flatpaks, from `flatpak create-usb`
+//! ```
+//!
+//! The .debs are the packages' whole dependency closure rather than what
+//! this machine lacks, since the other machine may have less installed.
+//! Importing installs the .debs for packages that machine doesn't have yet,
+//! marks the ones nobody asked for as automatically installed, and installs
+//! the Flatpaks with the bundle as a sideload repository. Dependencies it
+//! has in an older version aren't upgraded; apt says so when that matters.
+//! `flatpak create-usb` copies apps that are installed here, from remotes
+//! with a collection ID, which Flathub has.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::manifest::{self, FlatpakApp, Manifest};
+use crate::package_manager::BackendCommand;
+use crate::utils::{self, confirm, run_command, run_command_interactive, CommandBuilder};
+
+/// The bundle format this build writes and reads
+pub const VERSION: u32 = 1;
+
+/// What a bundle holds, in its top directory
+pub const BUNDLE_FILE: &str = "bundle.toml";
+/// Where the .debs go, under the bundle's directory
+pub const DEBS_DIR: &str = "debs";
+/// The repository `flatpak create-usb` writes, under the bundle's directory
+pub const FLATPAK_REPO: &str = ".ostree/repo";
+
+/// One downloaded .deb
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Deb {
+    pub package: String,
+    pub version: String,
+    pub architecture: String,
+    /// Relative to the bundle's directory: "debs/vlc_3.0.20-3_amd64.deb"
+    pub file: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bundle {
+    pub version: u32,
+    #[serde(default)]
+    pub host: String,
+    /// Unix time it was downloaded
+    #[serde(default)]
+    pub created: i64,
+    /// The dpkg architecture the .debs were downloaded for
+    #[serde(default)]
+    pub architecture: String,
+    /// The apt packages asked for; the rest of `debs` are their dependencies
+    #[serde(default)]
+    pub apt: Vec<String>,
+    #[serde(default)]
+    pub debs: Vec<Deb>,
+    #[serde(default)]
+    pub flatpak: Vec<FlatpakApp>,
+}
+
+impl Bundle {
+    pub fn from_toml(text: &str) -> Result<Self> {
+        let bundle: Bundle = toml::from_str(text)?;
+        if bundle.version > VERSION {
+            anyhow::bail!("The bundle is version {}, newer than this BirdNest reads ({})", bundle.version, VERSION);
+        }
+        for name in bundle.apt.iter().chain(bundle.flatpak.iter().map(|f| &f.app)) {
+            manifest::check_name(name)?;
+        }
+        // Every file is installed from under the bundle's directory
+        if let Some(deb) = bundle.debs.iter().find(|deb| {
+            let path = Path::new(&deb.file);
+            !path.starts_with(DEBS_DIR) || path.components().any(|c| matches!(c, std::path::Component::ParentDir))
+        }) {
+            anyhow::bail!("'{}' isn't in the bundle's {} directory", deb.file, DEBS_DIR);
+        }
+        Ok(bundle)
+    }
+
+    /// The bundle in `dir`
+    pub fn load(dir: &Path) -> Result<Self> {
+        let path = dir.join(BUNDLE_FILE);
+        let text = std::fs::read_to_string(&path).with_context(|| format!("Couldn't read {}", path.display()))?;
+        Self::from_toml(&text).with_context(|| format!("{} isn't a BirdNest offline bundle", dir.display()))
+    }
+
+    pub fn to_toml(&self) -> String {
+        let mut text = String::from("# An offline bundle, downloaded by BirdNest. Install it with: birdnest bundle import <directory>\n");
+        text.push_str(&toml::to_string_pretty(self).unwrap_or_default());
+        text
+    }
+
+    /// "12 apt packages (40 .debs with dependencies) and 2 Flatpak apps"
+    pub fn summary(&self) -> String {
+        format!(
+            "{} apt packages ({} .debs with dependencies) and {} Flatpak apps",
+            self.apt.len(),
+            self.debs.len(),
+            self.flatpak.len()
+        )
+    }
+
+    /// What installing the bundle in `dir` runs on a machine with
+    /// `installed`: the .debs it lacks, marking the dependencies among them
+    /// as automatic, then the Flatpaks it lacks from the bundle's repository
+    pub fn steps(&self, dir: &Path, installed: &Manifest) -> Vec<BackendCommand> {
+        let have: HashSet<&str> = installed.apt.iter().map(String::as_str).collect();
+        let wanted: HashSet<&str> = self.apt.iter().map(String::as_str).collect();
+        let needed: Vec<&Deb> = self.debs.iter().filter(|deb| !have.contains(deb.package.as_str())).collect();
+        let mut steps = Vec::new();
+        if !needed.is_empty() {
+            // A path rather than a name makes apt install the file
+            let files: Vec<String> = needed.iter().map(|deb| dir.join(&deb.file).display().to_string()).collect();
+            steps.push(BackendCommand::new("apt-get", &["install", "-y"], &files));
+            let automatic: Vec<String> = needed
+                .iter()
+                .filter(|deb| !wanted.contains(deb.package.as_str()))
+                .map(|deb| deb.package.clone())
+                .collect();
+            if !automatic.is_empty() {
+                steps.push(BackendCommand::new("apt-mark", &["auto"], &automatic));
+            }
+        }
+        let have_apps: HashSet<&String> = installed.flatpak.iter().map(|f| &f.app).collect();
+        let apps: Vec<FlatpakApp> = self.flatpak.iter().filter(|f| !have_apps.contains(&f.app)).cloned().collect();
+        let sideload = format!("--sideload-repo={}", dir.join(FLATPAK_REPO).display());
+        steps.extend(manifest::flatpak_steps(&apps, &[&sideload]));
+        steps
+    }
+}
+
+/// The packages `apt-cache depends --recurse` reaches: its unindented lines,
+/// less virtual packages ("<mail-transport-agent>"), which something else provides
+pub fn parse_depends_closure(output: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    output
+        .lines()
+        .filter(|line| !line.is_empty() && !line.starts_with(char::is_whitespace) && !line.starts_with('<'))
+        .map(str::trim)
+        .filter(|name| seen.insert(name.to_string()))
+        .map(str::to_string)
+        .collect()
+}
+
+/// A .deb's package, version and architecture from its file name, as
+/// `apt-get download` writes it: "libc6_2.36-9%3a1_amd64.deb", with the
+/// epoch's colon escaped
+pub fn parse_deb_filename(name: &str) -> Option<Deb> {
+    let stem = name.strip_suffix(".deb")?;
+    let mut fields = stem.split('_');
+    let (package, version, architecture) = (fields.next()?, fields.next()?, fields.next()?);
+    if package.is_empty() || version.is_empty() || architecture.is_empty() || fields.next().is_some() {
+        return None;
+    }
+    Some(Deb {
+        package: package.to_string(),
+        version: version.replace("%3a", ":"),
+        architecture: architecture.to_string(),
+        file: format!("{}/{}", DEBS_DIR, name),
+    })
+}
+
+// Downloads `packages` and everything they depend on into `debs`
+fn download_debs(packages: &[String], debs: &Path) -> Result<Vec<Deb>> {
+    let mut args = vec!["depends", "--recurse", "--no-recommends", "--no-suggests", "--no-conflicts", "--no-breaks", "--no-replaces", "--no-enhances"];
+    args.extend(packages.iter().map(String::as_str));
+    let closure = parse_depends_closure(&run_command("apt-cache", &args, false)?);
+
+    // apt-get download writes into the directory it runs in, as the user
+    let closure: Vec<&str> = closure.iter().map(String::as_str).collect();
+    let (mut command, _) = CommandBuilder::new("apt-get").args(&["download"]).args(&closure).c_locale().build()?;
+    let output = command.current_dir(debs).output().context("apt-get is not installed")?;
+    if !output.status.success() {
+        anyhow::bail!("Downloading the .debs failed:\n{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+
+    let mut files: Vec<Deb> = std::fs::read_dir(debs)?
+        .filter_map(|entry| parse_deb_filename(&entry.ok()?.file_name().to_string_lossy()))
+        .collect();
+    files.sort_by(|a, b| a.package.cmp(&b.package));
+    Ok(files)
+}
+
+// The installed Flatpak apps among `wanted`, with their remotes
+fn installed_flatpaks(wanted: &[String]) -> Result<Vec<FlatpakApp>> {
+    let apps = run_command("flatpak", &["list", "--app", "--columns=application,origin"], false)?;
+    let remotes = run_command("flatpak", &["remotes", "--columns=name,url"], false).unwrap_or_default();
+    let installed = manifest::parse_flatpak_apps(&apps, &remotes);
+    wanted
+        .iter()
+        .map(|app| {
+            installed
+                .iter()
+                .find(|installed| installed.app == *app)
+                .cloned()
+                .ok_or_else(|| anyhow::anyhow!("{} isn't installed here; install it first, so it can be copied into the bundle", app))
+        })
+        .collect()
+}
+
+/// Downloads `packages` with their dependencies and copies the installed
+/// Flatpak apps `flatpaks` with their runtimes into `dir`, and writes its
+/// `bundle.toml`
+pub fn export(dir: &Path, packages: &[String], flatpaks: &[String]) -> Result<Bundle> {
+    for name in packages.iter().chain(flatpaks) {
+        manifest::check_name(name)?;
+    }
+    if packages.is_empty() && flatpaks.is_empty() {
+        anyhow::bail!("Nothing to bundle; name apt packages, Flatpak apps or both");
+    }
+    let debs_dir = dir.join(DEBS_DIR);
+    std::fs::create_dir_all(&debs_dir).with_context(|| format!("Couldn't create {}", debs_dir.display()))?;
+
+    let mut bundle = Bundle {
+        version: VERSION,
+        host: std::fs::read_to_string("/proc/sys/kernel/hostname").map(|name| name.trim().to_string()).unwrap_or_default(),
+        created: utils::now() as i64,
+        architecture: crate::dpkg::native_architecture().unwrap_or_default(),
+        apt: packages.to_vec(),
+        ..Bundle::default()
+    };
+    if !packages.is_empty() {
+        utils::print_info("Downloading the packages and their dependencies");
+        bundle.debs = download_debs(packages, &debs_dir)?;
+    }
+    if !flatpaks.is_empty() {
+        bundle.flatpak = installed_flatpaks(flatpaks)?;
+        utils::print_info("Copying the Flatpak apps and their runtimes");
+        let dir_arg = dir.display().to_string();
+        let mut args = vec!["create-usb", dir_arg.as_str()];
+        args.extend(flatpaks.iter().map(String::as_str));
+        run_command_interactive("flatpak", &args, false)?;
+    }
+    let path: PathBuf = dir.join(BUNDLE_FILE);
+    std::fs::write(&path, bundle.to_toml()).with_context(|| format!("Couldn't write {}", path.display()))?;
+    Ok(bundle)
+}
+
+/// Installs what the bundle in `dir` has and this machine lacks, without
+/// the network, showing the commands first and asking unless `yes`
+pub fn import(dir: &Path, yes: bool) -> Result<()> {
+    let bundle = Bundle::load(dir)?;
+    if let Some(native) = crate::dpkg::native_architecture() {
+        if !bundle.debs.is_empty() && !bundle.architecture.is_empty() && bundle.architecture != native {
+            anyhow::bail!("The bundle's .debs are for {}, and this machine is {}", bundle.architecture, native);
+        }
+    }
+    // An absolute path, so apt takes each .deb for a file
+    let dir = std::fs::canonicalize(dir).with_context(|| format!("Couldn't open {}", dir.display()))?;
+    let steps = bundle.steps(&dir, &Manifest::installed()?);
+    if steps.is_empty() {
+        utils::print_success("Everything in the bundle is already installed");
+        return Ok(());
+    }
+    println!("Installing from the bundle's {}:", bundle.summary());
+    for step in &steps {
+        println!("  {}", step.display());
+    }
+    if !yes && !confirm("Install them?")? {
+        utils::print_info("Cancelled");
+        return Ok(());
+    }
+    for step in &steps {
+        run_command_interactive(&step.program, &step.args(), !step.as_user)?;
+    }
+    utils::print_success("Installed the bundle");
+    Ok(())
+}
@@ -0,0 +1,223 @@
+//! Structured terminal output: search and list results as aligned columns
+//! with a badge for where each package comes from.
+//!
+//! Colors go through `colored`'s global switch, which [`init`] turns off for
+//! `NO_COLOR` and when stdout isn't a terminal.
+
+use colored::*;
+use std::io::IsTerminal;
+
+// Descriptions are cut here when the terminal width is unknown but stdout is a tty
+const DEFAULT_WIDTH: usize = 100;
+// Very long names (mostly Flatpak IDs) shouldn't push every description off screen
+const MAX_NAME_WIDTH: usize = 40;
+const MAX_VERSION_WIDTH: usize = 24;
+
+/// Decide whether to color output: `NO_COLOR` always wins, `CLICOLOR_FORCE`
+/// forces colors on, otherwise only when stdout is a terminal
+pub fn init() {
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    let forced = std::env::var_os("CLICOLOR_FORCE").is_some_and(|v| !v.is_empty() && v != "0");
+    if no_color {
+        set_color(false);
+    } else if forced {
+        set_color(true);
+    } else if !std::io::stdout().is_terminal() {
+        set_color(false);
+    }
+}
+
+pub fn set_color(enabled: bool) {
+    colored::control::set_override(enabled);
+}
+
+/// Where a package comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    Apt,
+    Pikman,
+    Flatpak,
+    Aur,
+    Fedora,
+    Alpine,
+}
+
+impl Source {
+    pub fn label(self) -> &'static str {
+        match self {
+            Source::Apt => "apt",
+            Source::Pikman => "pikman",
+            Source::Flatpak => "flatpak",
+            Source::Aur => "aur",
+            Source::Fedora => "fedora",
+            Source::Alpine => "alpine",
+        }
+    }
+
+    /// Source for a pikman distro flag ("aur", "fedora", "alpine")
+    pub fn from_distro(distro: Option<&str>) -> Self {
+        match distro {
+            Some("aur") => Source::Aur,
+            Some("fedora") => Source::Fedora,
+            Some("alpine") => Source::Alpine,
+            _ => Source::Pikman,
+        }
+    }
+
+    fn badge(self) -> ColoredString {
+        // Padded before coloring so escape codes don't throw off the alignment
+        let text = format!("{:<9}", format!("[{}]", self.label()));
+        match self {
+            Source::Apt | Source::Pikman => text.blue(),
+            Source::Flatpak => text.magenta(),
+            Source::Aur => text.cyan(),
+            Source::Fedora => text.bright_blue(),
+            Source::Alpine => text.bright_cyan(),
+        }
+    }
+}
+
+/// One line of a search or list result
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageRow {
+    pub name: String,
+    pub version: String,
+    pub source: Source,
+    pub installed: bool,
+    /// Description, or a status such as "upgradable from 1.0-1"
+    pub detail: String,
+}
+
+/// Parse apt-style listings, as printed by `apt search`, `apt list` and pikman:
+///   hello/stable 2.10-3 amd64 [installed]
+///     example package based on GNU hello
+pub fn parse_apt_listing(output: &str, source: Source) -> Vec<PackageRow> {
+    let mut rows: Vec<PackageRow> = Vec::new();
+    for line in output.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if line.starts_with(char::is_whitespace) {
+            // Description belonging to the previous header
+            if let Some(row) = rows.last_mut() {
+                if row.detail.is_empty() {
+                    row.detail = line.trim().to_string();
+                }
+            }
+            continue;
+        }
+
+        let mut fields = line.split_whitespace();
+        let (Some(name_suite), Some(version)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        // "Sorting...", "Listing..." and other chatter have no suite
+        let Some((name, _suite)) = name_suite.split_once('/') else {
+            continue;
+        };
+
+        let flags = line
+            .find('[')
+            .and_then(|start| line[start + 1..].find(']').map(|end| &line[start + 1..start + 1 + end]))
+            .unwrap_or_default();
+        let detail = flags
+            .strip_prefix("upgradable from: ")
+            .map(|old| format!("upgradable from {}", old))
+            .unwrap_or_default();
+
+        rows.push(PackageRow {
+            name: name.to_string(),
+            version: version.to_string(),
+            source,
+            installed: flags.split(',').any(|flag| flag == "installed" || flag.starts_with("upgradable")),
+            detail,
+        });
+    }
+    rows
+}
+
+/// Parse tab-separated `flatpak ... --columns=application,version,<detail>` output
+pub fn parse_flatpak_columns(output: &str, installed: bool) -> Vec<PackageRow> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut columns = line.split('\t');
+            let application = columns.next()?.trim();
+            let version = columns.next()?.trim();
+            let detail = columns.next().unwrap_or_default().trim();
+            if application.is_empty() {
+                return None;
+            }
+            Some(PackageRow {
+                name: application.to_string(),
+                version: version.to_string(),
+                source: Source::Flatpak,
+                installed,
+                detail: detail.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Lay rows out in columns: installed mark, source badge, name, version, detail.
+/// The detail column is cut to `width` when one is given.
+pub fn render_table(rows: &[PackageRow], width: Option<usize>) -> String {
+    let name_width = rows.iter().map(|r| r.name.chars().count()).max().unwrap_or(0).min(MAX_NAME_WIDTH);
+    let version_width = rows.iter().map(|r| r.version.chars().count()).max().unwrap_or(0).min(MAX_VERSION_WIDTH);
+    // "✓ " + badge + " " + name + "  " + version + "  "
+    let fixed = 2 + 9 + 1 + name_width + 2 + version_width + 2;
+
+    let mut out = String::new();
+    for row in rows {
+        let mark = if row.installed { "✓".green() } else { " ".normal() };
+        let name = format!("{:<width$}", row.name, width = name_width);
+        let version = format!("{:<width$}", truncate(&row.version, MAX_VERSION_WIDTH), width = version_width);
+        let detail = match width {
+            Some(width) => truncate(&row.detail, width.saturating_sub(fixed)),
+            None => row.detail.clone(),
+        };
+        let line = format!("{} {} {}  {}  {}", mark, row.source.badge(), name.bold(), version.green(), detail.dimmed());
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+/// Print rows as a table sized to the terminal, or a note when there are none
+pub fn print_table(rows: &[PackageRow]) {
+    if rows.is_empty() {
+        crate::utils::print_info("No packages found");
+        return;
+    }
+    print!("{}", render_table(rows, terminal_width()));
+}
+
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    if max == 0 {
+        return String::new();
+    }
+    let mut cut: String = text.chars().take(max - 1).collect();
+    cut.push('…');
+    cut
+}
+
+// Columns available on stdout; None when it isn't a terminal, so piped output is never cut
+fn terminal_width() -> Option<usize> {
+    if !std::io::stdout().is_terminal() {
+        return None;
+    }
+    if let Some(columns) = std::env::var("COLUMNS").ok().and_then(|c| c.parse().ok()) {
+        return Some(columns);
+    }
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    // TIOCGWINSZ only fills in the winsize struct we pass
+    let ok = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) } == 0;
+    if ok && size.ws_col > 0 {
+        Some(size.ws_col as usize)
+    } else {
+        Some(DEFAULT_WIDTH)
+    }
+}
@@ -0,0 +1,1187 @@
+//! System package management through pikman, falling back to apt.
+
+use anyhow::Result;
+use crate::elevation;
+use crate::hooks::{self, HookEvent};
+use crate::immutable::{self, SystemKind};
+use crate::progress;
+use crate::restart;
+use crate::utils::{self, confirm, run_command, run_command_interactive};
+use crate::config::Config;
+use crate::cache;
+use crate::changelog::{self, ChangelogEntry};
+use crate::install_options::InstallOptions;
+use crate::output::{self, PackageRow, Source};
+use crate::plan::{self, TransactionPlan};
+use crate::dpkg::{self, Arch, InstalledPackage};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+
+pub struct PackageManager {
+    manager: PackageManagerType,
+    // Package lookups that don't depend on pikman vs apt
+    backend: Box<dyn PackageBackend>,
+    config: Config,
+}
+
+#[derive(Debug, Clone)]
+enum PackageManagerType {
+    Pikman,
+    Apt,
+}
+
+impl PackageManager {
+    pub fn new() -> Result<Self> {
+        let config = Config::load().unwrap_or_default();
+        let manager = Self::detect_manager()?;
+        
+        Ok(Self { manager, backend: Box::new(SystemBackend), config })
+    }
+
+    fn detect_manager() -> Result<PackageManagerType> {
+        use std::process::Command;
+        
+        // Check for pikman first (PikaOS specific)
+        if Command::new("pikman").arg("--version").output().is_ok() {
+            return Ok(PackageManagerType::Pikman);
+        }
+        
+        // Fallback to apt
+        if Command::new("apt").arg("--version").output().is_ok() {
+            return Ok(PackageManagerType::Apt);
+        }
+        
+        anyhow::bail!("No supported package manager found (pikman or apt)");
+    }
+
+    /// Install with the config's saved install options
+    pub fn install(&self, packages: &[String], yes: bool, distro: Option<&str>) -> Result<()> {
+        self.install_with(packages, yes, distro, &self.config.install_options)
+    }
+
+    /// Install with `options` in place of the saved ones, e.g. a one-off
+    /// `--no-recommends`
+    pub fn install_with(&self, packages: &[String], yes: bool, distro: Option<&str>, options: &InstallOptions) -> Result<()> {
+        if packages.is_empty() {
+            anyhow::bail!("No packages specified");
+        }
+
+        let system = immutable::detect();
+        if distro.is_none() && system.is_immutable() {
+            return apply_layered(system, &system.install_commands(packages), "Install", packages.len(), yes);
+        }
+
+        // Container distros aren't apt-managed, so there is nothing to simulate
+        let review = if distro.is_some() {
+            if yes || confirm(&format!("Install {} package(s)?", packages.len()))? {
+                Review::Proceed
+            } else {
+                Review::Cancelled
+            }
+        } else {
+            let flags = options.apt_args();
+            let recommends = plan::plan_recommends(packages, &flags)
+                .ok()
+                .and_then(|extras| plan::recommends_line(&extras, options.no_install_recommends));
+            review_plan(plan::plan_install_with(packages, &flags), recommends, yes)?
+        };
+        match review {
+            Review::Proceed => {}
+            Review::Cancelled => {
+                utils::print_info("Installation cancelled");
+                return Ok(());
+            }
+            Review::NothingToDo => {
+                utils::print_info("Nothing to do - all packages are already installed");
+                return Ok(());
+            }
+        }
+
+        // pikman has no way to hand apt flags on, so native installs that
+        // need them go to apt directly
+        let apt_only = distro.is_none() && !options.apt_args().is_empty();
+        match &self.manager {
+            PackageManagerType::Pikman if !apt_only => {
+                let mut args = vec!["install"];
+                
+                // Add distro-specific flags
+                match distro {
+                    Some("aur") => args.push("--aur"),
+                    Some("fedora") => args.push("--fedora"),
+                    Some("alpine") => args.push("--alpine"),
+                    _ => {}
+                }
+                
+                args.extend(packages.iter().map(|s| s.as_str()));
+                if yes {
+                    args.push("-y");
+                }
+                run_pikman("Installing", &args, yes)?;
+            }
+            PackageManagerType::Pikman | PackageManagerType::Apt => {
+                // Distro flags only work with pikman
+                if distro.is_some() {
+                    anyhow::bail!("Distro-specific flags (--aur, --fedora, --alpine) only work with pikman");
+                }
+                let flags = options.apt_args();
+                let mut args = vec!["install", "-y"];
+                args.extend(flags.iter().map(|s| s.as_str()));
+                args.extend(packages.iter().map(|s| s.as_str()));
+                progress::run_apt("Installing", &args, true)?;
+            }
+        }
+
+        utils::print_success(&format!("Successfully installed {} package(s)", packages.len()));
+        hooks::run_and_print(HookEvent::Install, packages, false);
+        Ok(())
+    }
+
+    pub fn remove(&self, packages: &[String], yes: bool, autoremove: bool) -> Result<()> {
+        if packages.is_empty() {
+            anyhow::bail!("No packages specified");
+        }
+
+        let system = immutable::detect();
+        if system.is_immutable() {
+            return apply_layered(system, &system.remove_commands(packages), "Remove", packages.len(), yes);
+        }
+
+        match review_plan(plan::plan_remove(packages, autoremove), None, yes)? {
+            Review::Proceed => {}
+            Review::Cancelled => {
+                utils::print_info("Removal cancelled");
+                return Ok(());
+            }
+            Review::NothingToDo => {
+                utils::print_info("Nothing to do - none of the packages are installed");
+                return Ok(());
+            }
+        }
+
+        match &self.manager {
+            PackageManagerType::Pikman => {
+                let mut args = vec!["remove"];
+                args.extend(packages.iter().map(|s| s.as_str()));
+                if yes {
+                    args.push("-y");
+                }
+                if autoremove {
+                    args.push("--autoremove");
+                }
+                run_pikman("Removing", &args, yes)?;
+            }
+            PackageManagerType::Apt => {
+                let mut args = vec!["remove", "-y"];
+                args.extend(packages.iter().map(|s| s.as_str()));
+                if autoremove {
+                    args.push("--autoremove");
+                }
+                progress::run_apt("Removing", &args, true)?;
+            }
+        }
+
+        utils::print_success(&format!("Successfully removed {} package(s)", packages.len()));
+        hooks::run_and_print(HookEvent::Remove, packages, false);
+        Ok(())
+    }
+
+    pub fn search(&self, query: &str) -> Result<()> {
+        // Always use apt for search
+        let output = run_command("apt", &["search", query], false)?;
+        output::print_table(&output::parse_apt_listing(&output, Source::Apt));
+        Ok(())
+    }
+
+    pub fn pikman_search(&self, query: &str, distro: Option<&str>) -> Result<()> {
+        // Directly use pikman for search, regardless of detected manager.
+        // Distro flags are global options and must come before the command
+        let mut args = Vec::new();
+        match distro {
+            Some("aur") => args.push("--aur"),
+            Some("fedora") => args.push("--fedora"),
+            Some("alpine") => args.push("--alpine"),
+            _ => {}
+        }
+        args.extend(["search", query]);
+        let output = run_command("pikman", &args, false)?;
+        print_listing(&output, Source::from_distro(distro));
+        Ok(())
+    }
+
+    pub fn update(&self) -> Result<()> {
+        utils::print_info("Updating package lists...");
+        
+        match &self.manager {
+            PackageManagerType::Pikman => {
+                progress::run_with_spinner("Updating", "pikman", &["update"], false)?;
+            }
+            PackageManagerType::Apt => {
+                progress::run_with_spinner("Updating", "apt", &["update"], true)?;
+            }
+        }
+
+        utils::print_success("Package lists updated");
+        Ok(())
+    }
+
+    pub fn upgrade(&self, packages: &[String], yes: bool) -> Result<()> {
+        let system = immutable::detect();
+        if system.is_immutable() {
+            // The image is upgraded as a whole, layered packages included
+            if !packages.is_empty() {
+                anyhow::bail!(
+                    "Single packages can't be upgraded on {} systems; run `birdnest upgrade` to upgrade the whole image",
+                    system.name()
+                );
+            }
+            utils::print_warning(immutable::REBOOT_NOTICE);
+            if !yes && !confirm(&format!("Upgrade the {} image?", system.name()))? {
+                utils::print_info("Upgrade cancelled");
+                return Ok(());
+            }
+            for command in system.upgrade_commands() {
+                run_command_interactive(&command.program, &command.args(), true)?;
+            }
+            utils::print_success("Upgrade staged - reboot to apply it");
+            return Ok(());
+        }
+
+        match review_plan(plan::plan_upgrade(packages), None, yes)? {
+            Review::Proceed => {}
+            Review::Cancelled => {
+                utils::print_info("Upgrade cancelled");
+                return Ok(());
+            }
+            Review::NothingToDo => {
+                utils::print_info("All packages are up to date");
+                return Ok(());
+            }
+        }
+
+        match &self.manager {
+            PackageManagerType::Pikman => {
+                if packages.is_empty() {
+                    let mut args = vec!["upgrade"];
+                    if yes {
+                        args.push("-y");
+                    }
+                    run_pikman("Upgrading", &args, yes)?;
+                } else {
+                    let mut args = vec!["upgrade"];
+                    args.extend(packages.iter().map(|s| s.as_str()));
+                    if yes {
+                        args.push("-y");
+                    }
+                    run_pikman("Upgrading", &args, yes)?;
+                }
+            }
+            PackageManagerType::Apt => {
+                if packages.is_empty() {
+                    let args = vec!["upgrade", "-y"];
+                    progress::run_apt("Upgrading", &args, true)?;
+                } else {
+                    let mut args = vec!["install", "--upgrade", "-y"];
+                    args.extend(packages.iter().map(|s| s.as_str()));
+                    progress::run_apt("Upgrading", &args, true)?;
+                }
+            }
+        }
+
+        utils::print_success("Packages upgraded");
+        hooks::run_and_print(HookEvent::Upgrade, packages, false);
+        report_restart_needs();
+        Ok(())
+    }
+
+    pub fn list(&self, upgradable: bool) -> Result<()> {
+        match &self.manager {
+            PackageManagerType::Pikman => {
+                let output = if upgradable {
+                    run_command("pikman", &["list", "--upgradable"], false)?
+                } else {
+                    run_command("pikman", &["list", "--installed"], false)?
+                };
+                print_listing(&output, Source::Pikman);
+            }
+            PackageManagerType::Apt => {
+                if upgradable {
+                    let output = run_command("apt", &["list", "--upgradable"], false)?;
+                    output::print_table(&output::parse_apt_listing(&output, Source::Apt));
+                } else {
+                    let output = run_command(
+                        "dpkg-query",
+                        &["-W", "-f=${db:Status-Abbrev}\t${Package}\t${Version}\t${binary:Summary}\n"],
+                        false,
+                    )?;
+                    output::print_table(&parse_installed_summaries(&output));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn show(&self, package: &str) -> Result<()> {
+        let details = self.backend.info(package)?;
+        print!("{}", render_details(&details));
+        Ok(())
+    }
+
+    pub fn clean(&self) -> Result<()> {
+        utils::print_info("Cleaning package cache...");
+        
+        match &self.manager {
+            PackageManagerType::Pikman => {
+                run_command_interactive("pikman", &["clean"], false)?;
+            }
+            PackageManagerType::Apt => {
+                run_command_interactive("apt", &["clean"], true)?;
+                run_command_interactive("apt", &["autoclean"], true)?;
+            }
+        }
+
+        utils::print_success("Cache cleaned");
+        Ok(())
+    }
+
+    pub fn status(&self) -> Result<()> {
+        let manager_name = match &self.manager {
+            PackageManagerType::Pikman => "pikman",
+            PackageManagerType::Apt => "apt",
+        };
+
+        println!("Package Manager: {}", manager_name);
+        println!("Backend: {}", self.backend.name());
+        let system = immutable::detect();
+        if system.is_immutable() {
+            println!("System: immutable ({}), package changes apply after reboot", system.name());
+        }
+        match elevation::detect().resolve(elevation::preference()) {
+            Ok(method) => println!("Elevation: {}", method.name()),
+            Err(e) => println!("Elevation: unavailable ({})", e),
+        }
+        
+        // Check for updates
+        self.update()?;
+        
+        // Show upgradable packages
+        println!("\nUpgradable packages:");
+        self.list(true)?;
+        
+        Ok(())
+    }
+
+    // Pikman-specific commands
+    pub fn pikman_autoremove(&self, yes: bool) -> Result<()> {
+        if !yes && !confirm("Remove all unused packages?")? {
+            utils::print_info("Autoremove cancelled");
+            return Ok(());
+        }
+
+        let mut args = vec!["autoremove"];
+        if yes {
+            args.push("-y");
+        }
+        run_command_interactive("pikman", &args, false)?;
+        utils::print_success("Unused packages removed");
+        Ok(())
+    }
+
+    pub fn pikman_enter(&self, name: &str) -> Result<()> {
+        run_command_interactive("pikman", &["enter", name], false)?;
+        Ok(())
+    }
+
+    pub fn pikman_export(&self, package: &str, name: Option<&str>) -> Result<()> {
+        let mut args = vec!["export", package];
+        if let Some(n) = name {
+            args.push("--name");
+            args.push(n);
+        }
+        run_command_interactive("pikman", &args, false)?;
+        utils::print_success(&format!("Desktop entry exported for {}", package));
+        Ok(())
+    }
+
+    pub fn pikman_init(&self, name: &str, manager: Option<&str>) -> Result<()> {
+        let mut args = vec!["init", name];
+        if let Some(mgr) = manager {
+            args.push("--manager");
+            args.push(mgr);
+        }
+        run_command_interactive("pikman", &args, false)?;
+        utils::print_success(&format!("Container {} initialized", name));
+        Ok(())
+    }
+
+    pub fn pikman_log(&self) -> Result<()> {
+        let output = run_command("pikman", &["log"], false)?;
+        print!("{}", output);
+        Ok(())
+    }
+
+    pub fn pikman_purge(&self, packages: &[String], yes: bool) -> Result<()> {
+        if packages.is_empty() {
+            anyhow::bail!("No packages specified");
+        }
+
+        if !yes && !confirm(&format!("Fully purge {} package(s)?", packages.len()))? {
+            utils::print_info("Purge cancelled");
+            return Ok(());
+        }
+
+        let mut args = vec!["purge"];
+        args.extend(packages.iter().map(|s| s.as_str()));
+        if yes {
+            args.push("-y");
+        }
+        run_command_interactive("pikman", &args, false)?;
+        utils::print_success(&format!("Successfully purged {} package(s)", packages.len()));
+        Ok(())
+    }
+
+    pub fn pikman_run(&self, name: &str, command: &[String]) -> Result<()> {
+        if command.is_empty() {
+            anyhow::bail!("No command specified");
+        }
+
+        let mut args = vec!["run", name];
+        args.extend(command.iter().map(|s| s.as_str()));
+        run_command_interactive("pikman", &args, false)?;
+        Ok(())
+    }
+
+    pub fn pikman_upgrades(&self) -> Result<()> {
+        let output = run_command("pikman", &["upgrades"], false)?;
+        print!("{}", output);
+        Ok(())
+    }
+
+    pub fn pikman_unexport(&self, package: &str, name: Option<&str>) -> Result<()> {
+        let mut args = vec!["unexport", package];
+        if let Some(n) = name {
+            args.push("--name");
+            args.push(n);
+        }
+        run_command_interactive("pikman", &args, false)?;
+        utils::print_success(&format!("Desktop entry removed for {}", package));
+        Ok(())
+    }
+}
+
+// Hand a native package change to the image's layering tool instead of apt
+fn apply_layered(system: SystemKind, commands: &[BackendCommand], action: &str, count: usize, yes: bool) -> Result<()> {
+    utils::print_warning(immutable::REBOOT_NOTICE);
+    if !yes && !confirm(&format!("{} {} package(s) through {}?", action, count, system.name()))? {
+        utils::print_info(&format!("{} cancelled", action));
+        return Ok(());
+    }
+    for command in commands {
+        run_command_interactive(&command.program, &command.args(), true)?;
+    }
+    utils::print_success(&format!("{} of {} package(s) staged - reboot to apply it", action, count));
+    Ok(())
+}
+
+// Warn about anything still running the old code after an upgrade
+fn report_restart_needs() {
+    let status = restart::check();
+    for line in status.summary() {
+        utils::print_warning(&line);
+    }
+    if !status.services.is_empty() {
+        utils::print_info(&format!("Restart them with: sudo {}", restart::restart_services_command(&status.services).display()));
+    }
+}
+
+enum Review {
+    Proceed,
+    Cancelled,
+    NothingToDo,
+}
+
+// Print the apt-style summary of a simulated transaction, and `note` under it,
+// then ask whether to go ahead (unless --yes). A failed simulation only costs
+// the summary, not the prompt.
+fn review_plan(plan: Result<TransactionPlan>, note: Option<String>, yes: bool) -> Result<Review> {
+    match plan {
+        Ok(plan) if plan.is_empty() => return Ok(Review::NothingToDo),
+        Ok(plan) => {
+            print!("{}", plan::render_summary(&plan));
+            if let Some(note) = note {
+                println!("{}", note);
+            }
+        }
+        Err(e) => utils::print_warning(&format!("Could not simulate transaction: {}", e)),
+    }
+    if yes || confirm("Do you want to continue?")? {
+        Ok(Review::Proceed)
+    } else {
+        Ok(Review::Cancelled)
+    }
+}
+
+// pikman's output is apt-style for PikaOS packages but may not be for other
+// distros; show it as-is rather than dropping lines that don't parse
+fn print_listing(text: &str, source: Source) {
+    let rows = output::parse_apt_listing(text, source);
+    if rows.is_empty() {
+        print!("{}", text);
+    } else {
+        output::print_table(&rows);
+    }
+}
+
+// `dpkg-query -W` rows of status, name, version and summary; only fully installed ones are kept
+fn parse_installed_summaries(text: &str) -> Vec<PackageRow> {
+    text.lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let status = fields.next()?;
+            let name = fields.next()?;
+            let version = fields.next()?;
+            let summary = fields.next().unwrap_or_default();
+            status.starts_with("ii").then(|| PackageRow {
+                name: name.to_string(),
+                version: version.to_string(),
+                source: Source::Apt,
+                installed: true,
+                detail: summary.trim().to_string(),
+            })
+        })
+        .collect()
+}
+
+// pikman only runs unattended with -y; otherwise its own prompt has to stay visible
+fn run_pikman(label: &str, args: &[&str], yes: bool) -> Result<()> {
+    if yes {
+        progress::run_with_spinner(label, "pikman", args, false)
+    } else {
+        run_command_interactive("pikman", args, false)
+    }
+}
+
+/// A package as reported by a backend search
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageSummary {
+    pub name: String,
+    pub version: String,
+    pub description: String,
+}
+
+/// An installed package and the version it would be upgraded to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageUpdate {
+    pub name: String,
+    pub current_version: String,
+    pub new_version: String,
+}
+
+/// An installed package that depends on one being removed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReverseDependency {
+    pub name: String,
+    /// Only one of several alternatives ("a | b") is the removed package, so
+    /// the dependent can stay if another one is installed
+    pub alternative: bool,
+}
+
+/// What a backend knows about a single package
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PackageDetails {
+    pub name: String,
+    pub version: String,
+    pub description: String,
+    /// Bytes on disk once installed
+    pub installed_size: Option<u64>,
+    /// Bytes to download
+    pub download_size: Option<u64>,
+    /// Upstream project page, when the package names one
+    pub homepage: Option<String>,
+    pub maintainer: Option<String>,
+    /// Packages it depends on, without version constraints; alternatives
+    /// stay together as "a | b"
+    pub dependencies: Vec<String>,
+    /// What it is built for, when the package source says
+    pub architecture: Option<Arch>,
+}
+
+/// Package operations the GUI and CLI need from a package source.
+///
+/// [`SystemBackend`] talks to apt hosts, [`DnfBackend`](crate::dnf::DnfBackend)
+/// and [`PacmanBackend`](crate::pacman::PacmanBackend) to Fedora- and
+/// Arch-based ones, picked by [`host_backend`]; the `mock` feature adds
+/// `mock::MockBackend`, which serves fixture data instead.
+pub trait PackageBackend: Send + Sync + std::fmt::Debug {
+    /// Short name shown in logs and status output, e.g. "apt"
+    fn name(&self) -> &'static str;
+    /// Search names and descriptions for `query`
+    fn search(&self, query: &str) -> Result<Vec<PackageSummary>>;
+    /// Version, description and sizes of one package, installed or not
+    fn info(&self, package: &str) -> Result<PackageDetails>;
+    /// All packages currently installed
+    fn list_installed(&self) -> Result<Vec<InstalledPackage>>;
+    /// Installed packages with a newer version available
+    fn list_upgradable(&self) -> Result<Vec<PackageUpdate>>;
+    /// Install packages without prompting
+    fn install(&self, packages: &[String]) -> Result<()>;
+    /// Remove packages without prompting
+    fn remove(&self, packages: &[String]) -> Result<()>;
+    /// Upgrade the given installed packages without prompting
+    fn upgrade(&self, packages: &[String]) -> Result<()>;
+
+    /// The privileged commands that install `packages`, run in order, for
+    /// frontends that run them themselves to show their output. Empty means
+    /// call [`install`](Self::install).
+    fn install_commands(&self, _packages: &[String]) -> Vec<BackendCommand> {
+        Vec::new()
+    }
+
+    /// Like [`install_commands`](Self::install_commands), for removals
+    fn remove_commands(&self, _packages: &[String]) -> Vec<BackendCommand> {
+        Vec::new()
+    }
+
+    /// Like [`install_commands`](Self::install_commands), for upgrades
+    fn upgrade_commands(&self, _packages: &[String]) -> Vec<BackendCommand> {
+        Vec::new()
+    }
+
+    /// Commands that download upgrades for `packages` without installing
+    /// them, so a later [`upgrade_commands`](Self::upgrade_commands) run needs
+    /// no network; empty when the backend can't
+    fn download_commands(&self, _packages: &[String]) -> Vec<BackendCommand> {
+        Vec::new()
+    }
+
+    /// Commands that reinstall installed packages; empty when the backend
+    /// can't, and frontends then don't offer it
+    fn reinstall_commands(&self, _packages: &[String]) -> Vec<BackendCommand> {
+        Vec::new()
+    }
+
+    /// Commands that hold packages at their installed version; empty when
+    /// the backend can't
+    fn hold_commands(&self, _packages: &[String]) -> Vec<BackendCommand> {
+        Vec::new()
+    }
+
+    /// Commands that let held packages upgrade again
+    fn unhold_commands(&self, _packages: &[String]) -> Vec<BackendCommand> {
+        Vec::new()
+    }
+
+    /// Commands that repair a half-finished transaction by installing or
+    /// removing what the installed packages are missing; empty when the
+    /// backend can't
+    fn fix_broken_commands(&self) -> Vec<BackendCommand> {
+        Vec::new()
+    }
+
+    /// Commands that mark packages as wanted for themselves, so nothing
+    /// removes them as leftovers later; empty when the backend can't
+    fn keep_commands(&self, _packages: &[String]) -> Vec<BackendCommand> {
+        Vec::new()
+    }
+
+    /// Installed packages held at their version, which upgrades skip
+    fn held(&self) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    /// Paths an installed package owns
+    fn files(&self, package: &str) -> Result<Vec<String>> {
+        anyhow::bail!("The {} backend can't list the files of {}", self.name(), package)
+    }
+
+    /// The installed package that owns `path`; None when no package does
+    fn owner(&self, path: &str) -> Result<Option<String>> {
+        anyhow::bail!("The {} backend can't tell which package owns {}", self.name(), path)
+    }
+
+    /// One-line descriptions of the installed `packages`, which
+    /// [`list_installed`](Self::list_installed) leaves out to stay fast;
+    /// packages without one are missing from the map
+    fn descriptions(&self, _packages: &[String]) -> Result<HashMap<String, String>> {
+        anyhow::bail!("The {} backend can't describe installed packages", self.name())
+    }
+
+    /// Every package available to install, for building a
+    /// [`SearchIndex`](crate::search_index::SearchIndex); frontends keep
+    /// calling [`search`](Self::search) for backends that can't list them
+    fn all_packages(&self) -> Result<Vec<PackageSummary>> {
+        anyhow::bail!("The {} backend can't list all packages", self.name())
+    }
+
+    /// Installed packages that depend on `package`, which removing it takes
+    /// along (apart from alternatives)
+    fn reverse_dependencies(&self, package: &str) -> Result<Vec<ReverseDependency>> {
+        anyhow::bail!("The {} backend can't list what depends on {}", self.name(), package)
+    }
+
+    /// The package's changelog, newest version first; usually downloaded,
+    /// so it belongs in a background task
+    fn changelog(&self, package: &str) -> Result<Vec<ChangelogEntry>> {
+        anyhow::bail!("The {} backend can't fetch the changelog of {}", self.name(), package)
+    }
+}
+
+/// A command line to run as root on a backend's behalf
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackendCommand {
+    pub program: String,
+    pub args: Vec<String>,
+    /// Run as the user rather than as root; set for tools that elevate
+    /// themselves and refuse to run as root, like AUR helpers
+    pub as_user: bool,
+}
+
+impl BackendCommand {
+    pub fn new(program: &str, args: &[&str], packages: &[String]) -> Self {
+        Self {
+            program: program.to_string(),
+            args: args.iter().map(|a| a.to_string()).chain(packages.iter().cloned()).collect(),
+            as_user: false,
+        }
+    }
+
+    /// Like [`new`](Self::new), for a command that must not be elevated
+    pub fn as_user(program: &str, args: &[&str], packages: &[String]) -> Self {
+        Self { as_user: true, ..Self::new(program, args, packages) }
+    }
+
+    pub fn args(&self) -> Vec<&str> {
+        self.args.iter().map(|a| a.as_str()).collect()
+    }
+
+    /// "apt-get remove -y hello", for logs and previews
+    pub fn display(&self) -> String {
+        std::iter::once(self.program.as_str())
+            .chain(self.args.iter().map(|a| a.as_str()))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Backend for the running system: apt-cache for search, the dpkg status
+/// database (through the installed-package cache) for listings
+#[derive(Debug)]
+pub struct SystemBackend;
+
+impl PackageBackend for SystemBackend {
+    fn name(&self) -> &'static str {
+        "apt"
+    }
+
+    fn search(&self, query: &str) -> Result<Vec<PackageSummary>> {
+        // apt-cache search covers both names and descriptions
+        let output = run_command("apt-cache", &["search", query], false)?;
+        Ok(parse_apt_cache_search(&output))
+    }
+
+    fn info(&self, package: &str) -> Result<PackageDetails> {
+        // apt-cache has a stable format (sizes in plain numbers) unlike `apt show`
+        let output = run_command("apt-cache", &["show", "--no-all-versions", package], false)?;
+        parse_apt_show(&output).ok_or_else(|| anyhow::anyhow!("No package information for {}", package))
+    }
+
+    fn list_installed(&self) -> Result<Vec<InstalledPackage>> {
+        if let Some(cached) = cache::load_installed() {
+            return Ok(cached);
+        }
+
+        // Read directly from dpkg status file - much faster than spawning dpkg-query
+        let mut installed = match dpkg::read_status() {
+            Ok(installed) => installed,
+            Err(e) => {
+                tracing::warn!("SystemBackend::list_installed: Failed to read status file: {}, using dpkg-query", e);
+                dpkg::query_installed()?
+            }
+        };
+        // One apt-cache call for every origin; the cache keeps them until dpkg changes
+        dpkg::read_apt_records(&mut installed);
+        cache::save_installed(&installed);
+        Ok(installed)
+    }
+
+    fn list_upgradable(&self) -> Result<Vec<PackageUpdate>> {
+        // Reads the package lists as of the last `apt update`; no root needed
+        let output = run_command("apt", &["list", "--upgradable"], false)?;
+        Ok(parse_apt_upgradable(&output))
+    }
+
+    fn install(&self, packages: &[String]) -> Result<()> {
+        PackageManager::new()?.install(packages, true, None)?;
+        cache::invalidate_installed();
+        Ok(())
+    }
+
+    fn remove(&self, packages: &[String]) -> Result<()> {
+        PackageManager::new()?.remove(packages, true, false)?;
+        cache::invalidate_installed();
+        Ok(())
+    }
+
+    fn upgrade(&self, packages: &[String]) -> Result<()> {
+        PackageManager::new()?.upgrade(packages, true)?;
+        cache::invalidate_installed();
+        Ok(())
+    }
+
+    fn install_commands(&self, packages: &[String]) -> Vec<BackendCommand> {
+        let system = immutable::detect();
+        if system.is_immutable() {
+            return system.install_commands(packages);
+        }
+        vec![BackendCommand::new("apt", &["install", "-y"], packages)]
+    }
+
+    // apt-get rather than apt: its output is stable when not on a terminal
+    fn remove_commands(&self, packages: &[String]) -> Vec<BackendCommand> {
+        let system = immutable::detect();
+        if system.is_immutable() {
+            return system.remove_commands(packages);
+        }
+        vec![BackendCommand::new("apt-get", &["remove", "-y"], packages)]
+    }
+
+    // --only-upgrade so nothing new gets installed and nothing is marked manual
+    fn upgrade_commands(&self, packages: &[String]) -> Vec<BackendCommand> {
+        let system = immutable::detect();
+        if system.is_immutable() {
+            return system.upgrade_commands();
+        }
+        vec![BackendCommand::new("apt-get", &["install", "--only-upgrade", "-y"], packages)]
+    }
+
+    // Into apt's archive cache, where the upgrade above finds them
+    fn download_commands(&self, packages: &[String]) -> Vec<BackendCommand> {
+        if immutable::detect().is_immutable() {
+            return Vec::new();
+        }
+        vec![BackendCommand::new("apt-get", &["install", "--only-upgrade", "--download-only", "-y"], packages)]
+    }
+
+    // Layered packages can only be added or removed on immutable systems
+    fn reinstall_commands(&self, packages: &[String]) -> Vec<BackendCommand> {
+        if immutable::detect().is_immutable() {
+            return Vec::new();
+        }
+        vec![BackendCommand::new("apt-get", &["install", "--reinstall", "-y"], packages)]
+    }
+
+    fn hold_commands(&self, packages: &[String]) -> Vec<BackendCommand> {
+        if immutable::detect().is_immutable() {
+            return Vec::new();
+        }
+        vec![BackendCommand::new("apt-mark", &["hold"], packages)]
+    }
+
+    fn unhold_commands(&self, packages: &[String]) -> Vec<BackendCommand> {
+        if immutable::detect().is_immutable() {
+            return Vec::new();
+        }
+        vec![BackendCommand::new("apt-mark", &["unhold"], packages)]
+    }
+
+    fn fix_broken_commands(&self) -> Vec<BackendCommand> {
+        if immutable::detect().is_immutable() {
+            return Vec::new();
+        }
+        vec![BackendCommand::new("apt-get", &["--fix-broken", "install", "-y"], &[])]
+    }
+
+    fn keep_commands(&self, packages: &[String]) -> Vec<BackendCommand> {
+        if immutable::detect().is_immutable() {
+            return Vec::new();
+        }
+        vec![BackendCommand::new("apt-mark", &["manual"], packages)]
+    }
+
+    fn held(&self) -> Result<Vec<String>> {
+        let output = run_command("apt-mark", &["showhold"], false)?;
+        Ok(output.lines().map(str::trim).filter(|line| !line.is_empty()).map(str::to_string).collect())
+    }
+
+    fn files(&self, package: &str) -> Result<Vec<String>> {
+        let output = run_command("dpkg", &["-L", package], false)?;
+        Ok(parse_file_list(&output))
+    }
+
+    fn owner(&self, path: &str) -> Result<Option<String>> {
+        // dpkg-query fails for a path no package owns
+        Ok(run_command("dpkg-query", &["-S", path], false).ok().and_then(|output| dpkg::parse_owner(&output, path)))
+    }
+
+    fn descriptions(&self, packages: &[String]) -> Result<HashMap<String, String>> {
+        match dpkg::read_descriptions(packages) {
+            Ok(descriptions) => Ok(descriptions),
+            Err(e) => {
+                tracing::warn!("SystemBackend::descriptions: Failed to read status file: {}, using dpkg-query", e);
+                dpkg::query_descriptions(packages)
+            }
+        }
+    }
+
+    // "." matches every package name; this is a few seconds' work on a big
+    // archive, so it belongs in a background task
+    fn all_packages(&self) -> Result<Vec<PackageSummary>> {
+        let output = run_command("apt-cache", &["search", "."], false)?;
+        Ok(parse_apt_cache_search(&output))
+    }
+
+    fn reverse_dependencies(&self, package: &str) -> Result<Vec<ReverseDependency>> {
+        let output = run_command("apt-cache", &["rdepends", "--installed", package], false)?;
+        Ok(parse_apt_rdepends(&output))
+    }
+
+    // Fetched from the archive's changelog server, installed or not
+    fn changelog(&self, package: &str) -> Result<Vec<ChangelogEntry>> {
+        let output = run_command("apt-get", &["changelog", "-qq", package], false)?;
+        Ok(changelog::parse_debian(&output))
+    }
+}
+
+/// Parse `apt-cache rdepends --installed`: the package name, "Reverse
+/// Depends:", then one indented dependent per line, with "|" marking those
+/// that depend on it as one alternative among several. A dependent appears
+/// once per relation; a hard one wins over an alternative.
+pub fn parse_apt_rdepends(output: &str) -> Vec<ReverseDependency> {
+    let mut dependents: Vec<ReverseDependency> = Vec::new();
+    for line in output.lines().skip_while(|line| line.trim() != "Reverse Depends:").skip(1) {
+        let line = line.trim();
+        let (name, alternative) = match line.strip_prefix('|') {
+            Some(name) => (name.trim(), true),
+            None => (line, false),
+        };
+        if name.is_empty() {
+            continue;
+        }
+        match dependents.iter_mut().find(|dependent| dependent.name == name) {
+            Some(dependent) => dependent.alternative &= alternative,
+            None => dependents.push(ReverseDependency { name: name.to_string(), alternative }),
+        }
+    }
+    dependents
+}
+
+/// `packages` and every installed package that depends on them, directly or
+/// through another dependent, sorted: what removing them has to take along
+/// for the removal to go through. Dependents that only use one of them as an
+/// alternative are left out.
+pub fn removal_closure(backend: &dyn PackageBackend, packages: &[String]) -> Result<Vec<String>> {
+    let mut closure: BTreeSet<String> = packages.iter().cloned().collect();
+    let mut queue = packages.to_vec();
+    while let Some(package) = queue.pop() {
+        for dependent in backend.reverse_dependencies(&package)? {
+            if !dependent.alternative && closure.insert(dependent.name.clone()) {
+                queue.push(dependent.name);
+            }
+        }
+    }
+    Ok(closure.into_iter().collect())
+}
+
+/// One path per line, as `dpkg -L`, `rpm -ql` and `pacman -Qlq` print them;
+/// dpkg lists "/." for the root, which isn't worth showing
+pub fn parse_file_list(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && *line != "/.")
+        .map(str::to_string)
+        .collect()
+}
+
+/// The package systems BirdNest has a backend for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackendKind {
+    /// pikman or apt, through [`SystemBackend`]
+    Apt,
+    /// dnf on Fedora-based systems, through [`DnfBackend`](crate::dnf::DnfBackend)
+    Dnf,
+    /// pacman on Arch-based systems, through [`PacmanBackend`](crate::pacman::PacmanBackend)
+    Pacman,
+}
+
+impl BackendKind {
+    /// The backend named by the config's `package_manager` ("apt", "pikman",
+    /// "dnf" or "pacman"), or with "auto" the first whose tool `available` finds.
+    /// apt comes first so PikaOS never picks up a stray dnf.
+    pub fn detect(configured: &str, available: impl Fn(&str) -> bool) -> Option<Self> {
+        match configured {
+            "apt" | "pikman" => return Some(BackendKind::Apt),
+            "dnf" => return Some(BackendKind::Dnf),
+            "pacman" => return Some(BackendKind::Pacman),
+            "auto" => {}
+            other => tracing::debug!("BackendKind::detect: unknown package_manager {:?}, detecting", other),
+        }
+        if available("pikman") || available("apt") {
+            Some(BackendKind::Apt)
+        } else if available("dnf") {
+            Some(BackendKind::Dnf)
+        } else if available("pacman") {
+            Some(BackendKind::Pacman)
+        } else {
+            None
+        }
+    }
+
+    pub fn backend(self) -> Arc<dyn PackageBackend> {
+        match self {
+            BackendKind::Apt => Arc::new(SystemBackend),
+            BackendKind::Dnf => Arc::new(crate::dnf::DnfBackend),
+            BackendKind::Pacman => Arc::new(crate::pacman::PacmanBackend::detect()),
+        }
+    }
+}
+
+/// The backend for the host's package manager, detected at startup. Falls
+/// back to [`SystemBackend`] so a missing tool surfaces as a failed search
+/// rather than no backend at all.
+pub fn host_backend() -> Arc<dyn PackageBackend> {
+    let configured = Config::load().unwrap_or_default().package_manager;
+    let kind = BackendKind::detect(&configured, |program| {
+        std::process::Command::new(program).arg("--version").output().is_ok()
+    });
+    tracing::debug!("host_backend: package_manager {:?} -> {:?}", configured, kind);
+    kind.unwrap_or(BackendKind::Apt).backend()
+}
+
+/// Parse `apt list --upgradable`:
+///   hello/stable 2.10-3 amd64 [upgradable from: 2.10-2]
+pub fn parse_apt_upgradable(output: &str) -> Vec<PackageUpdate> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let (name, _suite) = fields.next()?.split_once('/')?;
+            let new_version = fields.next()?;
+            let current_version = line.split_once("[upgradable from: ")?.1.trim_end_matches(']');
+            Some(PackageUpdate {
+                name: name.to_string(),
+                current_version: current_version.trim().to_string(),
+                new_version: new_version.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// `birdnest show` output: one "Field: value" line per known field
+pub fn render_details(details: &PackageDetails) -> String {
+    let mut out = format!("Package: {}\nVersion: {}\n", details.name, details.version);
+    if let Some(size) = details.download_size {
+        out.push_str(&format!("Download-Size: {}\n", plan::format_size(size)));
+    }
+    if let Some(size) = details.installed_size {
+        out.push_str(&format!("Installed-Size: {}\n", plan::format_size(size)));
+    }
+    if let Some(arch) = &details.architecture {
+        out.push_str(&format!("Architecture: {}\n", arch.label()));
+    }
+    out.push_str(&format!("Description: {}\n", details.description));
+    out
+}
+
+/// Parse the first record of `apt-cache show`; sizes there are bytes
+/// (Size) and KiB (Installed-Size)
+pub fn parse_apt_show(output: &str) -> Option<PackageDetails> {
+    let mut details = PackageDetails::default();
+    let mut in_description = false;
+
+    for line in output.lines() {
+        if line.trim().is_empty() {
+            if !details.name.is_empty() {
+                break;
+            }
+            continue;
+        }
+        if line.starts_with(' ') {
+            // Continuation lines; " ." is an empty paragraph line
+            if in_description {
+                let text = line.trim();
+                if text != "." {
+                    details.description.push(' ');
+                    details.description.push_str(text);
+                }
+            }
+            continue;
+        }
+        in_description = false;
+
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match key {
+            "Package" => details.name = value.to_string(),
+            "Version" => details.version = value.to_string(),
+            "Installed-Size" => details.installed_size = value.parse::<u64>().ok().map(|kib| kib * 1024),
+            "Size" => details.download_size = value.parse().ok(),
+            "Homepage" => details.homepage = Some(value.to_string()),
+            "Maintainer" => details.maintainer = Some(value.to_string()),
+            "Depends" => details.dependencies = parse_apt_depends(value),
+            "Architecture" => details.architecture.get_or_insert_with(Arch::default).name = value.to_string(),
+            "Multi-Arch" => details.architecture.get_or_insert_with(Arch::default).multi_arch = dpkg::parse_multi_arch(value),
+            // "Description-en" when the translation index is in use
+            key if key == "Description" || key.starts_with("Description-") => {
+                details.description = value.to_string();
+                in_description = true;
+            }
+            _ => {}
+        }
+    }
+
+    (!details.name.is_empty()).then_some(details)
+}
+
+/// Split a Depends field ("libc6 (>= 2.34), libx | liby") into package
+/// names, dropping version constraints and architecture qualifiers
+pub fn parse_apt_depends(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|dependency| {
+            dependency
+                .split('|')
+                .map(|alternative| {
+                    let name = alternative.split_whitespace().next().unwrap_or_default();
+                    name.split_once(':').map_or(name, |(name, _arch)| name)
+                })
+                .filter(|name| !name.is_empty())
+                .collect::<Vec<_>>()
+                .join(" | ")
+        })
+        .filter(|dependency| !dependency.is_empty())
+        .collect()
+}
+
+/// Parse `apt-cache search` output ("package - description"), dropping duplicates
+pub fn parse_apt_cache_search(output: &str) -> Vec<PackageSummary> {
+    let mut packages = Vec::new();
+    let mut seen = std::collections::HashSet::new();
+
+    for line in output.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        // Format: "package - description" or "package/version - description"
+        if let Some(dash_pos) = line.find(" - ") {
+            let name_part = line[..dash_pos].trim();
+            let description = line[dash_pos + 3..].trim().to_string();
+
+            let (name, version) = match name_part.split_once('/') {
+                Some((name, version)) => (name.to_string(), version.to_string()),
+                None => (name_part.to_string(), String::new()),
+            };
+
+            if !seen.insert(name.clone()) {
+                continue;
+            }
+
+            packages.push(PackageSummary { name, version, description });
+        }
+    }
+
+    packages
+}
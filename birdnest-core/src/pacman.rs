@@ -0,0 +1,318 @@
+//! pacman backend for Arch-based systems, with AUR packages through paru
+//! or yay when one is installed.
+//!
+//! AUR helpers build packages with makepkg, which refuses to run as root, so
+//! their commands run as the user and are told to elevate with the user's
+//! elevation method when they call pacman.
+
+use anyhow::Result;
+use std::collections::HashMap;
+use std::process::Command;
+
+use crate::config::Config;
+use crate::dpkg::InstalledPackage;
+use crate::elevation;
+use crate::package_manager::{parse_file_list, BackendCommand, PackageBackend, PackageDetails, PackageSummary, PackageUpdate};
+use crate::utils::run_command;
+
+/// AUR helpers BirdNest knows how to drive, in order of preference
+pub const AUR_HELPERS: &[&str] = &["paru", "yay"];
+
+/// Backend for hosts managed by pacman
+#[derive(Debug)]
+pub struct PacmanBackend {
+    /// paru or yay; None searches and installs from the sync repos only
+    pub aur_helper: Option<String>,
+}
+
+impl PacmanBackend {
+    /// Picks up the AUR helper from the config's `aur_helper`, or the first
+    /// of [`AUR_HELPERS`] installed; "none" turns AUR support off
+    pub fn detect() -> Self {
+        let configured = Config::load().unwrap_or_default().aur_helper;
+        let aur_helper = match configured.as_deref() {
+            Some("none") => None,
+            Some(helper) => Some(helper.to_string()),
+            None => AUR_HELPERS
+                .iter()
+                .find(|helper| Command::new(helper).arg("--version").output().is_ok())
+                .map(|helper| helper.to_string()),
+        };
+        tracing::debug!("PacmanBackend: AUR helper {:?}", aur_helper);
+        Self { aur_helper }
+    }
+
+    // paru and yay take the program that stands in for sudo and its flags separately
+    fn helper_command(&self, helper: &str, args: &[&str], packages: &[String]) -> BackendCommand {
+        let prefix = elevation::preferred_prefix();
+        let mut prefix = prefix.split_whitespace();
+        // The privileged helper only runs BirdNest's own commands
+        let sudo = match prefix.next() {
+            Some(program) if program != elevation::HELPER_PATH => program,
+            _ => "pkexec",
+        };
+        let mut helper_args = args.to_vec();
+        helper_args.extend(["--sudo", sudo]);
+        let flags: Vec<&str> = prefix.collect();
+        let flags = flags.join(" ");
+        if !flags.is_empty() {
+            helper_args.extend(["--sudoflags", &flags]);
+        }
+        BackendCommand::as_user(helper, &helper_args, packages)
+    }
+}
+
+impl PackageBackend for PacmanBackend {
+    fn name(&self) -> &'static str {
+        "pacman"
+    }
+
+    fn search(&self, query: &str) -> Result<Vec<PackageSummary>> {
+        let output = run_command("pacman", &["-Ss", query], false).unwrap_or_default();
+        let mut packages = parse_pacman_search(&output);
+        if let Some(helper) = &self.aur_helper {
+            // The AUR is a web service; without it the repo results still stand
+            match run_command(helper, &["-Ss", "--aur", query], false) {
+                Ok(output) => {
+                    for package in parse_pacman_search(&output) {
+                        if !packages.iter().any(|p| p.name == package.name) {
+                            packages.push(package);
+                        }
+                    }
+                }
+                Err(e) => tracing::warn!("PacmanBackend::search: AUR search with {} failed: {}", helper, e),
+            }
+        }
+        Ok(packages)
+    }
+
+    fn info(&self, package: &str) -> Result<PackageDetails> {
+        // Sync repos, then locally installed (covers AUR packages already
+        // built), then the AUR itself
+        let mut sources = vec![("pacman", "-Si"), ("pacman", "-Qi")];
+        if let Some(helper) = &self.aur_helper {
+            sources.push((helper.as_str(), "-Si"));
+        }
+        sources
+            .into_iter()
+            .find_map(|(program, flag)| {
+                let output = run_command(program, &[flag, package], false).ok()?;
+                parse_pacman_info(&output)
+            })
+            .ok_or_else(|| anyhow::anyhow!("No package information for {}", package))
+    }
+
+    fn list_installed(&self) -> Result<Vec<InstalledPackage>> {
+        let output = run_command("pacman", &["-Q"], false)?;
+        Ok(parse_pacman_installed(&output))
+    }
+
+    fn list_upgradable(&self) -> Result<Vec<PackageUpdate>> {
+        // `-Qu` exits with 1 when there's nothing to upgrade, so the status
+        // isn't an error; it reads the sync databases as of the last -Sy
+        let output = Command::new("pacman").arg("-Qu").output()?;
+        Ok(parse_pacman_upgrades(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    fn install(&self, packages: &[String]) -> Result<()> {
+        run_all(&self.install_commands(packages))
+    }
+
+    fn remove(&self, packages: &[String]) -> Result<()> {
+        run_all(&self.remove_commands(packages))
+    }
+
+    fn upgrade(&self, packages: &[String]) -> Result<()> {
+        run_all(&self.upgrade_commands(packages))
+    }
+
+    // The helper resolves repo and AUR packages alike, so it takes the whole list
+    fn install_commands(&self, packages: &[String]) -> Vec<BackendCommand> {
+        match &self.aur_helper {
+            Some(helper) => vec![self.helper_command(helper, &["-S", "--needed", "--noconfirm"], packages)],
+            None => vec![BackendCommand::new("pacman", &["-S", "--needed", "--noconfirm"], packages)],
+        }
+    }
+
+    fn remove_commands(&self, packages: &[String]) -> Vec<BackendCommand> {
+        vec![BackendCommand::new("pacman", &["-R", "--noconfirm"], packages)]
+    }
+
+    // Arch doesn't support partial upgrades, so with no packages this is a
+    // full -Syu (AUR packages included through the helper)
+    fn upgrade_commands(&self, packages: &[String]) -> Vec<BackendCommand> {
+        if !packages.is_empty() {
+            return self.install_commands(packages);
+        }
+        match &self.aur_helper {
+            Some(helper) => vec![self.helper_command(helper, &["-Syu", "--noconfirm"], packages)],
+            None => vec![BackendCommand::new("pacman", &["-Syu", "--noconfirm"], packages)],
+        }
+    }
+
+    // No hold: that's an IgnorePkg entry in pacman.conf rather than a command.
+    // No downloads ahead either: Arch doesn't support partial upgrades, and
+    // AUR helpers refuse to run as root, as the shutdown unit does
+    fn reinstall_commands(&self, packages: &[String]) -> Vec<BackendCommand> {
+        vec![BackendCommand::new("pacman", &["-S", "--noconfirm"], packages)]
+    }
+
+    // Explicitly installed packages are never orphans for `pacman -Qdt`
+    fn keep_commands(&self, packages: &[String]) -> Vec<BackendCommand> {
+        vec![BackendCommand::new("pacman", &["-D", "--asexplicit"], packages)]
+    }
+
+    fn files(&self, package: &str) -> Result<Vec<String>> {
+        let output = run_command("pacman", &["-Qlq", package], false)?;
+        Ok(parse_file_list(&output))
+    }
+
+    fn owner(&self, path: &str) -> Result<Option<String>> {
+        // pacman fails for a path no package owns
+        let output = run_command("pacman", &["-Qoq", path], false).unwrap_or_default();
+        Ok(output.lines().map(str::trim).find(|line| !line.is_empty()).map(str::to_string))
+    }
+
+    fn descriptions(&self, packages: &[String]) -> Result<HashMap<String, String>> {
+        let mut args = vec!["-Qi"];
+        args.extend(packages.iter().map(String::as_str));
+        let output = run_command("pacman", &args, false)?;
+        Ok(parse_pacman_descriptions(&output))
+    }
+}
+
+fn run_all(commands: &[BackendCommand]) -> Result<()> {
+    for command in commands {
+        tracing::debug!("PacmanBackend: running {}", command.display());
+        run_command(&command.program, &command.args(), !command.as_user)?;
+    }
+    Ok(())
+}
+
+/// Parse `pacman -Ss` (and `paru`/`yay -Ss --aur`) output: a
+/// "repo/name version [extras]" line followed by an indented description
+pub fn parse_pacman_search(output: &str) -> Vec<PackageSummary> {
+    let mut packages: Vec<PackageSummary> = Vec::new();
+
+    for line in output.lines() {
+        if line.starts_with(char::is_whitespace) {
+            if let Some(package) = packages.last_mut() {
+                if package.description.is_empty() {
+                    package.description = line.trim().to_string();
+                }
+            }
+            continue;
+        }
+
+        let mut fields = line.split_whitespace();
+        let Some((_repo, name)) = fields.next().and_then(|field| field.split_once('/')) else {
+            continue;
+        };
+        packages.push(PackageSummary {
+            name: name.to_string(),
+            version: fields.next().unwrap_or_default().to_string(),
+            description: String::new(),
+        });
+    }
+
+    packages
+}
+
+/// Parse `pacman -Si`/`-Qi` output; only the first record when a package
+/// is in several repos
+pub fn parse_pacman_info(output: &str) -> Option<PackageDetails> {
+    let mut details = PackageDetails::default();
+
+    for line in output.lines() {
+        // Wrapped dependency lists continue without a key
+        let Some((key, value)) = line.split_once(" : ") else {
+            if line.trim().is_empty() && !details.name.is_empty() {
+                break;
+            }
+            continue;
+        };
+        let value = value.trim();
+        match key.trim() {
+            "Name" => details.name = value.to_string(),
+            "Version" => details.version = value.to_string(),
+            "Description" => details.description = value.to_string(),
+            "Download Size" => details.download_size = parse_pacman_size(value),
+            "Installed Size" => details.installed_size = parse_pacman_size(value),
+            "URL" => details.homepage = Some(value.to_string()),
+            "Packager" if value != "Unknown Packager" => details.maintainer = Some(value.to_string()),
+            // AUR helpers print "Maintainer" instead of "Packager"
+            "Maintainer" => details.maintainer = Some(value.to_string()),
+            "Depends On" if value != "None" => {
+                // "glibc  ncurses>=6"
+                details.dependencies = value
+                    .split_whitespace()
+                    .map(|dependency| dependency.split(['<', '>', '=']).next().unwrap_or(dependency).to_string())
+                    .collect();
+            }
+            _ => {}
+        }
+    }
+
+    (!details.name.is_empty()).then_some(details)
+}
+
+/// The descriptions from `pacman -Qi` output for several packages
+pub fn parse_pacman_descriptions(output: &str) -> HashMap<String, String> {
+    output
+        .split("\n\n")
+        .filter_map(parse_pacman_info)
+        .filter(|details| !details.description.is_empty())
+        .map(|details| (details.name, details.description))
+        .collect()
+}
+
+/// "53.20 KiB" in bytes
+pub fn parse_pacman_size(value: &str) -> Option<u64> {
+    let (number, unit) = value.split_once(' ')?;
+    let number: f64 = number.parse().ok()?;
+    let multiplier = match unit.trim() {
+        "B" => 1.0,
+        "KiB" => 1024.0,
+        "MiB" => 1024.0 * 1024.0,
+        "GiB" => 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some((number * multiplier) as u64)
+}
+
+/// Parse `pacman -Q` ("name version"). Sizes are only in `pacman -Qi`,
+/// too slow to run for every package, so they stay unknown.
+pub fn parse_pacman_installed(output: &str) -> Vec<InstalledPackage> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (name, version) = line.split_once(' ')?;
+            Some(InstalledPackage {
+                name: name.to_string(),
+                version: version.trim().to_string(),
+                ..InstalledPackage::default()
+            })
+        })
+        .collect()
+}
+
+/// Parse `pacman -Qu` ("name old -> new", with "[ignored]" for held packages,
+/// which are left out)
+pub fn parse_pacman_upgrades(output: &str) -> Vec<PackageUpdate> {
+    output
+        .lines()
+        .filter(|line| !line.ends_with("[ignored]"))
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let name = fields.next()?;
+            let current_version = fields.next()?;
+            fields.next().filter(|arrow| *arrow == "->")?;
+            let new_version = fields.next()?;
+            Some(PackageUpdate {
+                name: name.to_string(),
+                current_version: current_version.to_string(),
+                new_version: new_version.to_string(),
+            })
+        })
+        .collect()
+}
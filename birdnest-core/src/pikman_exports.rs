@@ -0,0 +1,159 @@
+//! Applications and programs exported from pikman containers to the host.
+//!
+//! `pikman export` goes through distrobox, which leaves a desktop entry in
+//! `~/.local/share/applications` for an application and a wrapper script in
+//! `~/.local/bin` for a program. Both run the container's copy through
+//! `distrobox-enter -n <container> -- <command>`, which is how they are told
+//! apart from the host's own.
+
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+use crate::utils::run_command;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportKind {
+    /// A desktop entry in the application menu
+    Application,
+    /// A wrapper script on the PATH
+    Binary,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportedApp {
+    /// The entry's Name, or the wrapper's file name
+    pub name: String,
+    /// The container it runs in
+    pub container: String,
+    /// What runs inside the container
+    pub command: String,
+    pub kind: ExportKind,
+    /// The desktop entry or wrapper on the host
+    pub path: PathBuf,
+}
+
+/// The container and command of a `distrobox-enter` line, e.g.
+/// `exec "/usr/bin/distrobox-enter"  -n rust-dev  --  '/usr/bin/gdb'  "$@"`
+pub fn parse_enter_line(line: &str) -> Option<(String, String)> {
+    if !line.contains("distrobox-enter") {
+        return None;
+    }
+    let words: Vec<&str> = line.split_whitespace().map(|word| word.trim_matches(|c| c == '"' || c == '\'')).collect();
+    let container = words
+        .windows(2)
+        .find(|pair| pair[0] == "-n" || pair[0] == "--name")
+        .map(|pair| pair[1].to_string())?;
+    let command = words
+        .iter()
+        .position(|word| *word == "--")
+        .map(|start| {
+            words[start + 1..]
+                .iter()
+                .filter(|word| !word.starts_with('$') && !word.starts_with('%'))
+                .copied()
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .unwrap_or_default();
+    Some((container, command))
+}
+
+/// A desktop entry that runs a container's application
+pub fn parse_desktop_entry(text: &str, path: &Path) -> Option<ExportedApp> {
+    let mut name = None;
+    let mut exec = None;
+    let mut in_entry = false;
+    for line in text.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_entry = line == "[Desktop Entry]";
+        } else if in_entry {
+            if let Some(value) = line.strip_prefix("Name=") {
+                name.get_or_insert_with(|| value.to_string());
+            } else if let Some(value) = line.strip_prefix("Exec=") {
+                exec.get_or_insert_with(|| value.to_string());
+            }
+        }
+    }
+    let (container, command) = parse_enter_line(&exec?)?;
+    Some(ExportedApp {
+        name: name.unwrap_or_else(|| file_stem(path)),
+        container,
+        command,
+        kind: ExportKind::Application,
+        path: path.to_path_buf(),
+    })
+}
+
+/// A wrapper script distrobox exported a program with
+pub fn parse_binary(text: &str, path: &Path) -> Option<ExportedApp> {
+    if !text.lines().any(|line| line.trim() == "# distrobox_binary") {
+        return None;
+    }
+    let (container, command) = text.lines().find_map(parse_enter_line)?;
+    Some(ExportedApp { name: file_stem(path), container, command, kind: ExportKind::Binary, path: path.to_path_buf() })
+}
+
+fn file_stem(path: &Path) -> String {
+    path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default()
+}
+
+/// Everything exported into `applications` and `bin`, applications first,
+/// each by name
+pub fn list_in(applications: &Path, bin: &Path) -> Vec<ExportedApp> {
+    let read = |dir: &Path, parse: fn(&str, &Path) -> Option<ExportedApp>| {
+        let mut found: Vec<ExportedApp> = std::fs::read_dir(dir)
+            .map(|entries| entries.flatten().map(|entry| entry.path()).collect::<Vec<_>>())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|path| path.is_file())
+            // Wrappers are small scripts; this skips reading real binaries
+            .filter(|path| path.metadata().is_ok_and(|meta| meta.len() < 64 * 1024))
+            .filter_map(|path| parse(&std::fs::read_to_string(&path).ok()?, &path))
+            .collect();
+        found.sort_by(|a, b| a.name.cmp(&b.name));
+        found
+    };
+    let mut exports = read(applications, parse_desktop_entry);
+    exports.extend(read(bin, parse_binary));
+    exports
+}
+
+/// What the user has exported from pikman containers
+pub fn list() -> Vec<ExportedApp> {
+    let Some(home) = std::env::var_os("HOME").map(PathBuf::from) else {
+        return Vec::new();
+    };
+    list_in(&home.join(".local/share/applications"), &home.join(".local/bin"))
+}
+
+/// `pikman` arguments exporting `package` from the container for `distro`
+/// ("aur", "fedora" or "alpine")
+pub fn export_args(package: &str, distro: &str) -> Vec<String> {
+    vec![format!("--{}", distro), "export".to_string(), package.to_string()]
+}
+
+/// `pikman` arguments unexporting `app`, named as its command is
+pub fn unexport_args(app: &ExportedApp) -> Vec<String> {
+    let program = app.command.split_whitespace().next().unwrap_or(&app.name);
+    let program = Path::new(program).file_name().map_or(program.to_string(), |name| name.to_string_lossy().into_owned());
+    vec!["unexport".to_string(), program, "--name".to_string(), app.container.clone()]
+}
+
+/// Export `package`'s applications and programs from the `distro` container
+pub fn export(package: &str, distro: &str) -> Result<()> {
+    let args = export_args(package, distro);
+    run_command("pikman", &args.iter().map(String::as_str).collect::<Vec<_>>(), false)?;
+    Ok(())
+}
+
+/// Remove `app` from the host; fails if pikman left its desktop entry or
+/// wrapper behind, as it does when the command's name isn't what it exported
+pub fn unexport(app: &ExportedApp) -> Result<()> {
+    let args = unexport_args(app);
+    run_command("pikman", &args.iter().map(String::as_str).collect::<Vec<_>>(), false)?;
+    if app.path.exists() {
+        anyhow::bail!("pikman didn't remove {}", app.path.display());
+    }
+    Ok(())
+}
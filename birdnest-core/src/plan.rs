@@ -0,0 +1,385 @@
+//! Transaction planning: what an install or removal will change, worked out
+//! with `apt-get -s` before anything touches the system.
+
+use anyhow::Result;
+use colored::*;
+use std::collections::HashMap;
+
+use crate::utils::run_command;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedPackage {
+    pub name: String,
+    /// Version currently installed (upgrades and removals)
+    pub old_version: Option<String>,
+    /// Version that will be installed (installs and upgrades)
+    pub new_version: Option<String>,
+    /// Archive size of the new version, when apt-cache reported it
+    pub download_bytes: Option<u64>,
+    /// Installed size of the new version, when apt-cache reported it; for
+    /// removals, the size of the installed one from dpkg
+    pub installed_bytes: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TransactionPlan {
+    pub install: Vec<PlannedPackage>,
+    pub upgrade: Vec<PlannedPackage>,
+    pub remove: Vec<PlannedPackage>,
+    /// Bytes to download, when apt-cache could report it
+    pub download_bytes: Option<u64>,
+    /// Change in installed size in bytes (negative frees space)
+    pub disk_delta_bytes: Option<i64>,
+}
+
+impl TransactionPlan {
+    pub fn is_empty(&self) -> bool {
+        self.install.is_empty() && self.upgrade.is_empty() && self.remove.is_empty()
+    }
+
+    /// One-line apt-style count, e.g. "0 upgraded, 2 newly installed, 0 to remove"
+    pub fn counts_line(&self) -> String {
+        format!(
+            "{} upgraded, {} newly installed, {} to remove",
+            self.upgrade.len(),
+            self.install.len(),
+            self.remove.len()
+        )
+    }
+
+    /// Download and disk usage, if known
+    pub fn sizes_line(&self) -> Option<String> {
+        let mut parts = Vec::new();
+        if let Some(download) = self.download_bytes {
+            if download > 0 {
+                parts.push(format!("Need to get {} of archives.", format_size(download)));
+            }
+        }
+        if let Some(delta) = self.disk_delta_bytes {
+            if delta >= 0 {
+                parts.push(format!("After this operation, {} of additional disk space will be used.", format_size(delta as u64)));
+            } else {
+                parts.push(format!("After this operation, {} disk space will be freed.", format_size(delta.unsigned_abs())));
+            }
+        }
+        if parts.is_empty() {
+            None
+        } else {
+            Some(parts.join(" "))
+        }
+    }
+
+    /// Packages installed only because something in `requested` depends on them
+    pub fn dependencies(&self, requested: &[String]) -> Vec<&PlannedPackage> {
+        self.install.iter().filter(|p| !requested.contains(&p.name)).collect()
+    }
+
+    /// "40 additional dependencies (85.3 MB download, 310.2 MB installed)";
+    /// None when the install pulls nothing else in
+    pub fn dependencies_line(&self, requested: &[String]) -> Option<String> {
+        let dependencies = self.dependencies(requested);
+        if dependencies.is_empty() {
+            return None;
+        }
+        let noun = if dependencies.len() == 1 { "dependency" } else { "dependencies" };
+        let download: Option<u64> = dependencies.iter().map(|p| p.download_bytes).sum();
+        let installed: Option<u64> = dependencies.iter().map(|p| p.installed_bytes).sum();
+        let sizes = match (download, installed) {
+            (Some(download), Some(installed)) => {
+                format!(" ({} download, {} installed)", format_size(download), format_size(installed))
+            }
+            _ => String::new(),
+        };
+        Some(format!("{} additional {}{}", dependencies.len(), noun, sizes))
+    }
+}
+
+/// Simulate installing `packages` and collect everything it would pull in
+pub fn plan_install(packages: &[String]) -> Result<TransactionPlan> {
+    plan_install_with(packages, &[])
+}
+
+/// [`plan_install`] with extra `apt-get install` flags, such as
+/// [`InstallOptions::apt_args`](crate::install_options::InstallOptions::apt_args)
+pub fn plan_install_with(packages: &[String], flags: &[String]) -> Result<TransactionPlan> {
+    let mut args = vec!["-s", "install"];
+    args.extend(flags.iter().map(|s| s.as_str()));
+    args.extend(packages.iter().map(|s| s.as_str()));
+    let output = run_command("apt-get", &args, false)?;
+    let mut plan = parse_simulation(&output);
+    fill_sizes(&mut plan);
+    Ok(plan)
+}
+
+/// The packages apt would install for `packages` only because something
+/// recommends them: what a simulation with recommends has that one without
+/// lacks. `flags` are the install's other flags; a `--no-install-recommends`
+/// among them is ignored so both sides get simulated.
+pub fn plan_recommends(packages: &[String], flags: &[String]) -> Result<Vec<PlannedPackage>> {
+    let flags: Vec<String> = flags.iter().filter(|flag| *flag != NO_RECOMMENDS).cloned().collect();
+    let with = plan_install_with(packages, &flags)?;
+    let mut without_flags = flags;
+    without_flags.push(NO_RECOMMENDS.to_string());
+    let without = plan_install_with(packages, &without_flags)?;
+    Ok(recommended_extras(&with, &without))
+}
+
+const NO_RECOMMENDS: &str = "--no-install-recommends";
+
+/// Installs in `with` that `without` doesn't have
+pub fn recommended_extras(with: &TransactionPlan, without: &TransactionPlan) -> Vec<PlannedPackage> {
+    with.install
+        .iter()
+        .filter(|p| !without.install.iter().any(|other| other.name == p.name))
+        .cloned()
+        .collect()
+}
+
+/// "Recommends add 12 packages (35.0 MB installed)", or with `skipped`
+/// "Leaving out 12 recommended packages (35.0 MB installed)"; None when
+/// recommends make no difference
+pub fn recommends_line(extras: &[PlannedPackage], skipped: bool) -> Option<String> {
+    if extras.is_empty() {
+        return None;
+    }
+    let installed: Option<u64> = extras.iter().map(|p| p.installed_bytes).sum();
+    let size = installed.map(|bytes| format!(" ({} installed)", format_size(bytes))).unwrap_or_default();
+    let count = extras.len();
+    Some(if skipped {
+        format!("Leaving out {} recommended {}{}", count, if count == 1 { "package" } else { "packages" }, size)
+    } else {
+        format!("Recommends add {} {}{}", count, if count == 1 { "package" } else { "packages" }, size)
+    })
+}
+
+/// Simulate upgrading `packages`, or everything upgradable when empty
+pub fn plan_upgrade(packages: &[String]) -> Result<TransactionPlan> {
+    let mut args = vec!["-s"];
+    if packages.is_empty() {
+        args.push("upgrade");
+    } else {
+        args.extend(["install", "--only-upgrade"]);
+        args.extend(packages.iter().map(|s| s.as_str()));
+    }
+    let output = run_command("apt-get", &args, false)?;
+    let mut plan = parse_simulation(&output);
+    fill_sizes(&mut plan);
+    Ok(plan)
+}
+
+/// Simulate removing `packages` (and their unused dependencies with `autoremove`)
+pub fn plan_remove(packages: &[String], autoremove: bool) -> Result<TransactionPlan> {
+    let mut args = vec!["-s", "remove"];
+    if autoremove {
+        args.push("--autoremove");
+    }
+    args.extend(packages.iter().map(|s| s.as_str()));
+    let output = run_command("apt-get", &args, false)?;
+    let mut plan = parse_simulation(&output);
+    fill_sizes(&mut plan);
+    Ok(plan)
+}
+
+/// Simulate `apt-get autoremove`: the packages nothing needs any more
+pub fn plan_autoremove() -> Result<TransactionPlan> {
+    let output = run_command("apt-get", &["-s", "autoremove"], false)?;
+    let mut plan = parse_simulation(&output);
+    fill_sizes(&mut plan);
+    Ok(plan)
+}
+
+/// What an autoremove after removing `packages` would take that it wouldn't
+/// take now: the packages left installed only for them
+pub fn plan_follow_up(packages: &[String]) -> Result<Vec<PlannedPackage>> {
+    let with = plan_remove(packages, true)?;
+    let without = plan_remove(packages, false)?;
+    let unused = plan_autoremove()?;
+    Ok(follow_up_removals(&with, &without, &unused))
+}
+
+/// Removals in `with` (the removal plus an autoremove) that neither
+/// `without` (the removal alone) nor `unused` (an autoremove today) has
+pub fn follow_up_removals(with: &TransactionPlan, without: &TransactionPlan, unused: &TransactionPlan) -> Vec<PlannedPackage> {
+    with.remove
+        .iter()
+        .filter(|p| !without.remove.iter().chain(&unused.remove).any(|other| other.name == p.name))
+        .cloned()
+        .collect()
+}
+
+/// "Removing vlc will later allow removing libvlc5, vlc-data (12.0 MB
+/// freed)"; None when nothing is left behind
+pub fn follow_up_line(requested: &[String], follow_up: &[PlannedPackage]) -> Option<String> {
+    if follow_up.is_empty() {
+        return None;
+    }
+    let freed: Option<u64> = follow_up.iter().map(|p| p.installed_bytes).sum();
+    let size = freed.map(|bytes| format!(" ({} freed)", format_size(bytes))).unwrap_or_default();
+    let names: Vec<&str> = follow_up.iter().map(|p| p.name.as_str()).collect();
+    Some(format!("Removing {} will later allow removing {}{}", requested.join(", "), names.join(", "), size))
+}
+
+/// Parse `apt-get -s` output:
+///   Inst foo (1.2-1 Debian:12/stable [amd64])
+///   Inst bar [1.0-1] (1.1-1 Debian:12/stable [amd64])
+///   Remv baz [2.0-1]
+pub fn parse_simulation(output: &str) -> TransactionPlan {
+    let mut plan = TransactionPlan::default();
+    for line in output.lines() {
+        if let Some(rest) = line.strip_prefix("Inst ") {
+            let name = rest.split_whitespace().next().unwrap_or_default().to_string();
+            // "[old]" precedes the "(new ... [arch])" group; don't mistake the arch for it
+            let before_new = rest.split('(').next().unwrap_or(rest);
+            let old_version = bracketed(before_new, '[', ']');
+            let new_version = bracketed(rest, '(', ')')
+                .and_then(|inner| inner.split_whitespace().next().map(str::to_string));
+            let pkg = PlannedPackage { name, old_version, new_version, download_bytes: None, installed_bytes: None };
+            if pkg.old_version.is_some() {
+                plan.upgrade.push(pkg);
+            } else {
+                plan.install.push(pkg);
+            }
+        } else if let Some(rest) = line.strip_prefix("Remv ") {
+            let name = rest.split_whitespace().next().unwrap_or_default().to_string();
+            plan.remove.push(PlannedPackage {
+                name,
+                old_version: bracketed(rest, '[', ']'),
+                new_version: None,
+                download_bytes: None,
+                installed_bytes: None,
+            });
+        }
+    }
+    plan
+}
+
+// First "open ... close" group in `text`, without the delimiters
+fn bracketed(text: &str, open: char, close: char) -> Option<String> {
+    let start = text.find(open)? + 1;
+    let end = start + text[start..].find(close)?;
+    Some(text[start..end].to_string())
+}
+
+// Sizes are best-effort: a failed lookup leaves them unknown rather than failing the plan
+fn fill_sizes(plan: &mut TransactionPlan) {
+    let incoming: Vec<&str> = plan.install.iter().chain(&plan.upgrade).map(|p| p.name.as_str()).collect();
+    let existing: Vec<&str> = plan.upgrade.iter().chain(&plan.remove).map(|p| p.name.as_str()).collect();
+
+    let available = if incoming.is_empty() { Some(HashMap::new()) } else { apt_cache_sizes(&incoming) };
+    let installed = if existing.is_empty() { Some(HashMap::new()) } else { dpkg_installed_sizes(&existing) };
+    let (Some(available), Some(installed)) = (available, installed) else {
+        return;
+    };
+
+    for pkg in plan.install.iter_mut().chain(plan.upgrade.iter_mut()) {
+        if let Some(&(size, installed_kb)) = available.get(&pkg.name) {
+            pkg.download_bytes = Some(size);
+            pkg.installed_bytes = Some(installed_kb * 1024);
+        }
+    }
+
+    let mut download: u64 = 0;
+    let mut delta_kb: i64 = 0;
+    for pkg in &plan.install {
+        let (size, installed_kb) = available.get(&pkg.name).copied().unwrap_or_default();
+        download += size;
+        delta_kb += installed_kb as i64;
+    }
+    for pkg in &plan.upgrade {
+        let (size, installed_kb) = available.get(&pkg.name).copied().unwrap_or_default();
+        download += size;
+        delta_kb += installed_kb as i64 - installed.get(&pkg.name).copied().unwrap_or_default() as i64;
+    }
+    for pkg in plan.remove.iter_mut() {
+        let installed_kb = installed.get(&pkg.name).copied();
+        pkg.installed_bytes = installed_kb.map(|kb| kb * 1024);
+        delta_kb -= installed_kb.unwrap_or_default() as i64;
+    }
+
+    plan.download_bytes = Some(download);
+    plan.disk_delta_bytes = Some(delta_kb * 1024);
+}
+
+// Package -> (download bytes, installed KiB) for the candidate versions
+fn apt_cache_sizes(packages: &[&str]) -> Option<HashMap<String, (u64, u64)>> {
+    let mut args = vec!["show", "--no-all-versions"];
+    args.extend_from_slice(packages);
+    let output = run_command("apt-cache", &args, false).ok()?;
+
+    let mut sizes = HashMap::new();
+    let mut name = String::new();
+    let mut size = 0;
+    let mut installed_kb = 0;
+    for line in output.lines().chain(std::iter::once("")) {
+        if let Some(value) = line.strip_prefix("Package: ") {
+            name = value.trim().to_string();
+        } else if let Some(value) = line.strip_prefix("Size: ") {
+            size = value.trim().parse().unwrap_or(0);
+        } else if let Some(value) = line.strip_prefix("Installed-Size: ") {
+            installed_kb = value.trim().parse().unwrap_or(0);
+        } else if line.is_empty() && !name.is_empty() {
+            sizes.insert(std::mem::take(&mut name), (size, installed_kb));
+            size = 0;
+            installed_kb = 0;
+        }
+    }
+    Some(sizes)
+}
+
+// Package -> installed KiB for what is on disk now
+fn dpkg_installed_sizes(packages: &[&str]) -> Option<HashMap<String, u64>> {
+    let mut args = vec!["-W", "-f=${Package}\t${Installed-Size}\n"];
+    args.extend_from_slice(packages);
+    let output = run_command("dpkg-query", &args, false).ok()?;
+    Some(
+        output
+            .lines()
+            .filter_map(|line| {
+                let (name, kb) = line.split_once('\t')?;
+                Some((name.to_string(), kb.trim().parse().unwrap_or(0)))
+            })
+            .collect(),
+    )
+}
+
+/// Human-readable size using apt's units (kB, MB, GB; powers of 1000)
+pub fn format_size(bytes: u64) -> String {
+    let bytes = bytes as f64;
+    if bytes >= 1e9 {
+        format!("{:.1} GB", bytes / 1e9)
+    } else if bytes >= 1e6 {
+        format!("{:.1} MB", bytes / 1e6)
+    } else if bytes >= 1e3 {
+        format!("{:.0} kB", bytes / 1e3)
+    } else {
+        format!("{} B", bytes)
+    }
+}
+
+/// Diff-style summary for the terminal: `+` installs, `~` upgrades, `-` removals
+pub fn render_summary(plan: &TransactionPlan) -> String {
+    let mut out = String::new();
+    for pkg in &plan.install {
+        let line = format!("+ {} {}", pkg.name, pkg.new_version.as_deref().unwrap_or(""));
+        out.push_str(&format!("  {}\n", line.trim_end().green()));
+    }
+    for pkg in &plan.upgrade {
+        let line = format!(
+            "~ {} {} -> {}",
+            pkg.name,
+            pkg.old_version.as_deref().unwrap_or("?"),
+            pkg.new_version.as_deref().unwrap_or("?")
+        );
+        out.push_str(&format!("  {}\n", line.yellow()));
+    }
+    for pkg in &plan.remove {
+        let line = format!("- {} {}", pkg.name, pkg.old_version.as_deref().unwrap_or(""));
+        out.push_str(&format!("  {}\n", line.trim_end().red()));
+    }
+    out.push_str(&format!("{}.\n", plan.counts_line()));
+    if let Some(sizes) = plan.sizes_line() {
+        out.push_str(&sizes);
+        out.push('\n');
+    }
+    out
+}
@@ -0,0 +1,230 @@
+//! Launchpad PPAs on Ubuntu-based systems.
+//!
+//! Adding one does what `add-apt-repository ppa:owner/name` does, without
+//! needing software-properties: the PPA's signing key fingerprint comes from
+//! the Launchpad API, the key itself from the Ubuntu keyserver, and it is only
+//! kept when gpg shows that fingerprint. The key goes to `/etc/apt/keyrings`
+//! and the source is written through [`repos`] as a deb822 file signed by it.
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::package_manager::BackendCommand;
+use crate::repos::{self, Edit, NewSource, Source};
+use crate::utils::CommandBuilder;
+
+// Both hosts serve PPAs; launchpadcontent.net is the current one
+const PPA_HOSTS: &[&str] = &["ppa.launchpadcontent.net", "ppa.launchpad.net"];
+const KEYRING_DIR: &str = "/etc/apt/keyrings";
+
+/// A PPA, as in `ppa:owner/name`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ppa {
+    pub owner: String,
+    pub name: String,
+}
+
+fn valid_part(part: &str) -> bool {
+    !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '+' | '_'))
+}
+
+impl Ppa {
+    /// `ppa:owner/name`, or `ppa:owner` for the owner's PPA called "ppa"
+    pub fn parse(spec: &str) -> Result<Self> {
+        let rest = spec.trim().strip_prefix("ppa:").unwrap_or(spec.trim());
+        let (owner, name) = rest.split_once('/').unwrap_or((rest, "ppa"));
+        if !valid_part(owner) || !valid_part(name) {
+            anyhow::bail!("'{}' is not a PPA (expected ppa:owner/name)", spec);
+        }
+        Ok(Ppa { owner: owner.to_string(), name: name.to_string() })
+    }
+
+    /// The PPA a source's URI points to, if any
+    pub fn from_uri(uri: &str) -> Option<Self> {
+        let rest = uri.split_once("://")?.1;
+        let mut parts = rest.split('/');
+        let host = parts.next()?;
+        if !PPA_HOSTS.contains(&host) {
+            return None;
+        }
+        let (owner, name) = (parts.next()?, parts.next()?);
+        (valid_part(owner) && valid_part(name)).then(|| Ppa { owner: owner.to_string(), name: name.to_string() })
+    }
+
+    pub fn spec(&self) -> String {
+        format!("ppa:{}/{}", self.owner, self.name)
+    }
+
+    pub fn uri(&self) -> String {
+        format!("https://{}/{}/{}/ubuntu", PPA_HOSTS[0], self.owner, self.name)
+    }
+
+    fn api_url(&self) -> String {
+        format!("https://api.launchpad.net/1.0/~{}/+archive/ubuntu/{}", self.owner, self.name)
+    }
+
+    /// Where the signing key is kept, named like add-apt-repository's files
+    pub fn keyring_path(&self) -> PathBuf {
+        Path::new(KEYRING_DIR).join(format!("{}-ubuntu-{}.asc", self.owner, self.name))
+    }
+
+    /// The source for `suite`, signed by the PPA's keyring
+    pub fn source(&self, suite: &str) -> NewSource {
+        NewSource {
+            uri: self.uri(),
+            suite: suite.to_string(),
+            components: vec!["main".to_string()],
+            signed_by: Some(self.keyring_path().display().to_string()),
+        }
+    }
+}
+
+/// The signing key fingerprint in a Launchpad archive's API response
+pub fn parse_fingerprint(api_json: &str) -> Result<String> {
+    let archive: serde_json::Value = serde_json::from_str(api_json).context("Unexpected reply from Launchpad")?;
+    archive
+        .get("signing_key_fingerprint")
+        .and_then(|fingerprint| fingerprint.as_str())
+        .filter(|fingerprint| !fingerprint.is_empty())
+        .map(str::to_uppercase)
+        .ok_or_else(|| anyhow::anyhow!("The PPA has no signing key yet; Launchpad creates one after its first upload"))
+}
+
+/// The primary key fingerprints in `gpg --show-keys --with-colons` output
+pub fn primary_fingerprints(gpg_colons: &str) -> Vec<String> {
+    let mut fingerprints = Vec::new();
+    let mut after_pub = false;
+    for line in gpg_colons.lines() {
+        if line.starts_with("pub:") {
+            after_pub = true;
+        } else if line.starts_with("fpr:") && after_pub {
+            if let Some(fingerprint) = line.split(':').nth(9) {
+                fingerprints.push(fingerprint.to_uppercase());
+            }
+            after_pub = false;
+        }
+    }
+    fingerprints
+}
+
+/// The codename PPAs publish for: Ubuntu's, which derivatives name in
+/// `UBUNTU_CODENAME`
+pub fn ubuntu_codename() -> Option<String> {
+    let os_release = fs::read_to_string("/etc/os-release").ok()?;
+    let value = |key: &str| {
+        os_release.lines().find_map(|line| {
+            let value = line.strip_prefix(key)?.strip_prefix('=')?.trim_matches('"');
+            (!value.is_empty()).then(|| value.to_string())
+        })
+    };
+    value("UBUNTU_CODENAME").or_else(|| value("VERSION_CODENAME").filter(|_| is_ubuntu_based()))
+}
+
+/// Ubuntu or a distribution built on it
+pub fn is_ubuntu_based() -> bool {
+    let Ok(os_release) = fs::read_to_string("/etc/os-release") else {
+        return false;
+    };
+    os_release.lines().any(|line| {
+        let (key, value) = line.split_once('=').unwrap_or((line, ""));
+        matches!(key, "ID" | "ID_LIKE" | "UBUNTU_CODENAME")
+            && (key == "UBUNTU_CODENAME" || value.trim_matches('"').split_whitespace().any(|id| id == "ubuntu"))
+    })
+}
+
+fn curl(url: &str) -> Result<String> {
+    let (mut command, _) = CommandBuilder::new("curl").args(&["-fsSL", "--max-time", "20", url]).build()?;
+    let output = command.output().context("curl is not installed")?;
+    if !output.status.success() {
+        anyhow::bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Download the PPA's signing key and check it is the one Launchpad names;
+/// returns it ASCII-armored
+pub fn fetch_key(ppa: &Ppa) -> Result<String> {
+    let api = curl(&ppa.api_url()).with_context(|| format!("{} was not found on Launchpad", ppa.spec()))?;
+    let fingerprint = parse_fingerprint(&api)?;
+    let key = curl(&format!("https://keyserver.ubuntu.com/pks/lookup?op=get&options=mr&search=0x{}", fingerprint))
+        .context("Failed to download the PPA's signing key")?;
+    if !key.contains("-----BEGIN PGP PUBLIC KEY BLOCK-----") {
+        anyhow::bail!("The keyserver didn't return a key for {}", fingerprint);
+    }
+
+    // A throwaway home, so checking the key doesn't touch anyone's keyring.
+    // This runs as root, so it's a fresh 0700 directory with an unguessable
+    // name rather than one another user could create first.
+    let dir = tempfile::Builder::new().prefix("birdnest-ppa-").permissions(fs::Permissions::from_mode(0o700)).tempdir()?;
+    let key_file = dir.path().join("key.asc");
+    fs::write(&key_file, &key)?;
+    let shown = Command::new("gpg")
+        .arg("--homedir")
+        .arg(dir.path())
+        .args(["--batch", "--quiet", "--show-keys", "--with-colons"])
+        .arg(&key_file)
+        .output();
+    drop(dir);
+    let shown = shown.context("gpg is needed to check the PPA's signing key")?;
+    let found = primary_fingerprints(&String::from_utf8_lossy(&shown.stdout));
+    if found != [fingerprint.clone()] {
+        anyhow::bail!("The downloaded key doesn't match {}'s fingerprint {}", ppa.spec(), fingerprint);
+    }
+    Ok(key)
+}
+
+/// The PPAs among `sources`, with the entry for each
+pub fn list(sources: &[Source]) -> Vec<(Ppa, &Source)> {
+    sources
+        .iter()
+        .filter_map(|source| Some((source.uris.iter().find_map(|uri| Ppa::from_uri(uri))?, source)))
+        .collect()
+}
+
+/// Add `ppa` for `suite` with its checked key; runs as root
+pub fn add(ppa: &Ppa, suite: &str) -> Result<()> {
+    if list(&repos::load()?).iter().any(|(listed, _)| listed == ppa) {
+        anyhow::bail!("{} is already added", ppa.spec());
+    }
+    let key = fetch_key(ppa)?;
+    fs::create_dir_all(KEYRING_DIR)?;
+    fs::write(ppa.keyring_path(), key)?;
+    if let Err(e) = repos::apply(&Edit::Add(ppa.source(suite))) {
+        let _ = fs::remove_file(ppa.keyring_path());
+        return Err(e);
+    }
+    tracing::debug!("ppa: added {} for {}", ppa.spec(), suite);
+    Ok(())
+}
+
+/// Remove every entry for `ppa` and its keyring; runs as root. Returns how
+/// many entries went.
+pub fn remove(ppa: &Ppa) -> Result<usize> {
+    let sources = repos::load()?;
+    let mut entries: Vec<&Source> = list(&sources).into_iter().filter(|(listed, _)| listed == ppa).map(|(_, source)| source).collect();
+    if entries.is_empty() {
+        anyhow::bail!("{} is not added", ppa.spec());
+    }
+    // Later entries first, so the earlier ones keep their numbers
+    entries.sort_by(|a, b| (&b.file, b.entry).cmp(&(&a.file, a.entry)));
+    for source in &entries {
+        repos::apply(&Edit::Remove { file: source.file.clone(), entry: source.entry })?;
+    }
+    if ppa.keyring_path().exists() {
+        fs::remove_file(ppa.keyring_path())?;
+    }
+    Ok(entries.len())
+}
+
+/// The root command that adds `ppa` through `exe ppa add`
+pub fn add_command(exe: &Path, ppa: &Ppa) -> BackendCommand {
+    BackendCommand { program: exe.display().to_string(), args: vec!["ppa".to_string(), "add".to_string(), ppa.spec()], as_user: false }
+}
+
+/// The root command that removes `ppa` through `exe ppa remove`
+pub fn remove_command(exe: &Path, ppa: &Ppa) -> BackendCommand {
+    BackendCommand { program: exe.display().to_string(), args: vec!["ppa".to_string(), "remove".to_string(), ppa.spec()], as_user: false }
+}
@@ -0,0 +1,119 @@
+//! Flatpak runtimes queued to download ahead of the apps that need them.
+//!
+//! Queuing asks each app's remote which runtime it runs on and keeps the
+//! ones that aren't installed in `~/.config/birdnest/predownloads.json`.
+//! They download while the [`RefreshSchedule`] allows - overnight unless its
+//! `hours` say otherwise - from the GUI's timer or `birdnest update
+//! --scheduled`, so installing the apps later only fetches the apps.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::flatpak::{FlatpakManager, InstallSize};
+use crate::schedule::{Decision, HourWindow, RefreshSchedule};
+use crate::utils::run_command;
+
+/// Hours pre-downloads run in when the refresh schedule sets none
+pub const OVERNIGHT: HourWindow = HourWindow { start: 1, end: 6 };
+
+/// A runtime waiting to download, and the apps it was queued for
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingRuntime {
+    // e.g. "org.gnome.Platform/x86_64/46"
+    pub runtime: String,
+    pub remote: String,
+    pub apps: Vec<String>,
+}
+
+fn queue_path() -> Option<PathBuf> {
+    let home = std::env::var("HOME").ok()?;
+    Some(PathBuf::from(home).join(".config").join("birdnest").join("predownloads.json"))
+}
+
+/// The saved queue; empty when there is none or it can't be read
+pub fn load() -> Vec<PendingRuntime> {
+    queue_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn save(pending: &[PendingRuntime]) -> Result<()> {
+    let path = queue_path().ok_or_else(|| anyhow::anyhow!("HOME is not set"))?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(pending)?)?;
+    Ok(())
+}
+
+/// Add the runtimes installing `app` from `remote` would pull, once each
+/// however many apps need them
+pub fn merge(pending: &mut Vec<PendingRuntime>, app: &str, remote: &str, size: &InstallSize) {
+    for runtime in size.runtimes() {
+        match pending.iter_mut().find(|p| p.runtime == runtime.name) {
+            Some(entry) if entry.apps.iter().any(|a| a == app) => {}
+            Some(entry) => entry.apps.push(app.to_string()),
+            None => pending.push(PendingRuntime {
+                runtime: runtime.name.clone(),
+                remote: remote.to_string(),
+                apps: vec![app.to_string()],
+            }),
+        }
+    }
+}
+
+/// Queue the runtimes the `(app, remote)` pairs need; returns how many
+/// runtimes are waiting now
+pub fn queue(apps: &[(String, String)]) -> Result<usize> {
+    let flatpak = FlatpakManager::new()?;
+    let mut pending = load();
+    for (app, remote) in apps {
+        let size = flatpak.install_size(&[(app.clone(), remote.clone())])?;
+        merge(&mut pending, app, remote, &size);
+    }
+    save(&pending)?;
+    Ok(pending.len())
+}
+
+/// The refresh schedule with its hours defaulting to [`OVERNIGHT`]
+pub fn schedule(refresh: &RefreshSchedule) -> RefreshSchedule {
+    RefreshSchedule { hours: Some(refresh.hours.unwrap_or(OVERNIGHT)), ..refresh.clone() }
+}
+
+pub fn check_now(refresh: &RefreshSchedule) -> Decision {
+    schedule(refresh).check_now()
+}
+
+/// Install one queued runtime; the apps then only fetch themselves
+pub fn download(runtime: &PendingRuntime) -> Result<()> {
+    tracing::debug!("predownload: fetching {} from {}", runtime.runtime, runtime.remote);
+    let runtime_ref = format!("runtime/{}", runtime.runtime);
+    run_command("flatpak", &["install", "-y", "--noninteractive", &runtime.remote, &runtime_ref], false)?;
+    Ok(())
+}
+
+/// Drop a downloaded runtime from the saved queue
+pub fn finish(runtime: &str) -> Result<()> {
+    let mut pending = load();
+    pending.retain(|p| p.runtime != runtime);
+    save(&pending)
+}
+
+/// Download everything queued, for `birdnest update --scheduled`; returns
+/// how many runtimes were fetched. Failures stay queued for the next run.
+pub fn run_pending() -> Result<usize> {
+    let mut downloaded = 0;
+    for runtime in load() {
+        match download(&runtime) {
+            Ok(()) => {
+                finish(&runtime.runtime)?;
+                downloaded += 1;
+            }
+            Err(e) => tracing::warn!("predownload: {} failed: {}", runtime.runtime, e),
+        }
+    }
+    Ok(downloaded)
+}
@@ -0,0 +1,356 @@
+//! Progress bars for long-running CLI operations.
+//!
+//! apt is run with `APT::Status-Fd` so download and dpkg progress can be
+//! shown as a bar; pikman and Flatpak get a spinner with their latest output
+//! line. With `--plain` (or when stdout isn't a terminal) the tools' raw output
+//! is passed through unchanged, which is what logs want.
+//!
+//! [`TransactionProgress`] reads the same status lines, and Flatpak's output,
+//! back out of a transaction's log so the GUI can draw a bar per package.
+
+use anyhow::Result;
+use indicatif::{ProgressBar, ProgressStyle};
+use std::io::{BufRead, BufReader, IsTerminal, Read};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use crate::package_manager::BackendCommand;
+use crate::utils::{elevated_command, run_command_interactive};
+
+static PLAIN: AtomicBool = AtomicBool::new(false);
+
+// Lines of tool output kept to explain a failure
+const ERROR_CONTEXT_LINES: usize = 20;
+
+/// Disable progress bars and pass tool output through as-is
+pub fn set_plain(plain: bool) {
+    PLAIN.store(plain, Ordering::Relaxed);
+}
+
+fn show_progress() -> bool {
+    !PLAIN.load(Ordering::Relaxed) && std::io::stdout().is_terminal()
+}
+
+/// A line written by apt to its status file descriptor
+#[derive(Debug, Clone, PartialEq)]
+pub enum AptStatus {
+    /// `dlstatus:<item>:<percent>:<description>`
+    Download { percent: f32, message: String },
+    /// `pmstatus:<package>:<percent>:<description>`
+    Package { package: String, percent: f32, message: String },
+    /// `pmerror:<deb>:<percent>:<message>`
+    Error { package: String, message: String },
+}
+
+pub fn parse_apt_status(line: &str) -> Option<AptStatus> {
+    let mut fields = line.splitn(4, ':');
+    let kind = fields.next()?;
+    let subject = fields.next()?.to_string();
+    let percent: f32 = fields.next()?.parse().ok()?;
+    let message = fields.next().unwrap_or_default().trim().to_string();
+    match kind {
+        "dlstatus" => Some(AptStatus::Download { percent, message }),
+        "pmstatus" => Some(AptStatus::Package { package: subject, percent, message }),
+        "pmerror" => Some(AptStatus::Error { package: subject, message }),
+        _ => None,
+    }
+}
+
+/// `command` with apt's status lines turned on, when it runs apt at all
+pub fn with_status_fd(mut command: BackendCommand) -> BackendCommand {
+    if command.program == "apt-get" || command.program == "apt" {
+        // Options go before the operation, where apt expects them
+        let options = ["-o", "APT::Status-Fd=1", "-o", "Dpkg::Use-Pty=0"].map(str::to_string);
+        command.args.splice(0..0, options);
+    }
+    command
+}
+
+/// How far one package of a transaction has got
+#[derive(Debug, Clone, PartialEq)]
+pub struct PackageProgress {
+    pub name: String,
+    /// 0.0 to 1.0
+    pub fraction: f32,
+}
+
+/// Per-package progress of a transaction, worked out from its output one
+/// line at a time
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TransactionProgress {
+    packages: Vec<PackageProgress>,
+    // The Flatpak operation running now, which percentages refer to
+    current_flatpak: Option<String>,
+    // apt's description of what it is doing now
+    status: Option<String>,
+}
+
+// Where each dpkg phase apt reports leaves a package; downloading is the first
+// quarter. Removals download nothing, so preparing one counts for as much.
+fn dpkg_phase(message: &str) -> Option<f32> {
+    [
+        ("Preparing to configure", 0.65),
+        ("Preparing for removal", 0.25),
+        ("Preparing to completely remove", 0.25),
+        ("Preparing", 0.3),
+        ("Unpacking", 0.5),
+        ("Configuring", 0.8),
+        ("Installed", 1.0),
+        ("Removing", 0.6),
+        ("Completely removing", 0.6),
+        ("Removed", 1.0),
+        ("Completely removed", 1.0),
+    ]
+    .into_iter()
+        .find(|(phase, _)| message.starts_with(phase))
+        .map(|(_, fraction)| fraction)
+}
+
+// "app/org.gnome.Calculator/x86_64/stable" or "org.gnome.Calculator/x86_64/stable"
+fn flatpak_ref_name(reference: &str) -> &str {
+    let mut parts = reference.split('/');
+    match parts.next() {
+        Some("app") | Some("runtime") => parts.next().unwrap_or(reference),
+        Some(name) => name,
+        None => reference,
+    }
+}
+
+// The last "42%" in a line of Flatpak output
+fn percent_in(line: &str) -> Option<f32> {
+    line.split_whitespace().rev().find_map(|word| word.strip_suffix('%')?.parse().ok())
+}
+
+impl TransactionProgress {
+    /// Start every package in `names` at 0%
+    pub fn new(names: &[String]) -> Self {
+        Self {
+            packages: names.iter().map(|name| PackageProgress { name: name.clone(), fraction: 0.0 }).collect(),
+            current_flatpak: None,
+            status: None,
+        }
+    }
+
+    pub fn packages(&self) -> &[PackageProgress] {
+        &self.packages
+    }
+
+    /// What apt last said it was doing, e.g. "Unpacking vlc (amd64)" or
+    /// "Retrieving file 2 of 5"; None before its first status line
+    pub fn status(&self) -> Option<&str> {
+        self.status.as_deref()
+    }
+
+    /// 0.0 to 1.0 across every package, dependencies included
+    pub fn overall(&self) -> f32 {
+        if self.packages.is_empty() {
+            return 0.0;
+        }
+        self.packages.iter().map(|p| p.fraction).sum::<f32>() / self.packages.len() as f32
+    }
+
+    /// Everything is done, whatever the output said
+    pub fn finish(&mut self) {
+        for package in &mut self.packages {
+            package.fraction = 1.0;
+        }
+        self.current_flatpak = None;
+    }
+
+    // Packages only move forward, so late or repeated lines can't pull a bar back
+    fn advance(&mut self, name: &str, fraction: f32) {
+        match self.packages.iter_mut().find(|p| p.name == name) {
+            Some(package) => package.fraction = package.fraction.max(fraction),
+            None => self.packages.push(PackageProgress { name: name.to_string(), fraction }),
+        }
+    }
+
+    /// Take in a line of output; true when it was only progress, which the
+    /// log doesn't need to show
+    pub fn feed(&mut self, line: &str) -> bool {
+        // Flatpak redraws its progress with carriage returns; the last part is current
+        let line = line.rsplit('\r').find(|part| !part.trim().is_empty()).unwrap_or(line).trim();
+        match parse_apt_status(line) {
+            Some(AptStatus::Download { percent, message }) => {
+                for package in &mut self.packages {
+                    package.fraction = package.fraction.max(percent / 400.0);
+                }
+                self.status = Some(message);
+                return true;
+            }
+            Some(AptStatus::Package { package, message, .. }) => {
+                if let Some(fraction) = dpkg_phase(&message) {
+                    self.advance(&package, fraction);
+                }
+                self.status = Some(message);
+                return true;
+            }
+            // Errors stay in the log
+            Some(AptStatus::Error { .. }) => return false,
+            None => {}
+        }
+
+        if let Some(reference) = line.strip_prefix("Updating ").or_else(|| line.strip_prefix("Installing ")) {
+            let Some(reference) = reference.split_whitespace().next().filter(|r| r.contains('/') && !r.starts_with(|c: char| c.is_ascii_digit())) else {
+                return false;
+            };
+            if let Some(previous) = self.current_flatpak.take() {
+                self.advance(&previous, 1.0);
+            }
+            let name = flatpak_ref_name(reference).to_string();
+            self.advance(&name, 0.0);
+            self.current_flatpak = Some(name);
+            if let Some(percent) = percent_in(line) {
+                self.feed_flatpak_percent(percent);
+            }
+            return false;
+        }
+        if let Some(percent) = percent_in(line) {
+            if self.current_flatpak.is_some() {
+                self.feed_flatpak_percent(percent);
+                return true;
+            }
+        }
+        if line.ends_with("complete.") {
+            // "Updates complete." / "Changes complete."
+            if let Some(previous) = self.current_flatpak.take() {
+                self.advance(&previous, 1.0);
+            }
+        }
+        false
+    }
+
+    fn feed_flatpak_percent(&mut self, percent: f32) {
+        if let Some(name) = self.current_flatpak.clone() {
+            // 100% is downloaded, not yet deployed
+            self.advance(&name, (percent / 100.0).min(0.95));
+        }
+    }
+}
+
+fn bar_style() -> ProgressStyle {
+    ProgressStyle::with_template("{prefix:>12.cyan.bold} [{bar:30}] {pos:>3}% {wide_msg}")
+        .unwrap_or_else(|_| ProgressStyle::default_bar())
+        .progress_chars("=> ")
+}
+
+fn spinner_style() -> ProgressStyle {
+    ProgressStyle::with_template("{prefix:>12.cyan.bold} {spinner} {wide_msg}")
+        .unwrap_or_else(|_| ProgressStyle::default_spinner())
+}
+
+// Drain stderr on a separate thread so a chatty tool can't block on a full pipe
+fn collect_stderr(stderr: impl Read + Send + 'static) -> std::thread::JoinHandle<String> {
+    std::thread::spawn(move || {
+        let mut output = String::new();
+        let _ = BufReader::new(stderr).read_to_string(&mut output);
+        output
+    })
+}
+
+fn push_context(context: &mut Vec<String>, line: &str) {
+    if context.len() == ERROR_CONTEXT_LINES {
+        context.remove(0);
+    }
+    context.push(line.to_string());
+}
+
+fn failure(tool: &str, code: Option<i32>, stderr: &str, context: &[String]) -> anyhow::Error {
+    let mut details = stderr.trim().to_string();
+    if details.is_empty() {
+        details = context.join("\n");
+    }
+    anyhow::anyhow!("{} failed with exit code {:?}:\n{}", tool, code, details)
+}
+
+/// Run apt-get with a download/install progress bar; `label` names the dpkg
+/// phase ("Installing", "Removing", "Upgrading")
+pub fn run_apt(label: &str, args: &[&str], sudo: bool) -> Result<()> {
+    if !show_progress() {
+        return run_command_interactive("apt", args, sudo);
+    }
+
+    let mut full_args = args.to_vec();
+    full_args.extend(["-o", "APT::Status-Fd=1", "-o", "Dpkg::Use-Pty=0"]);
+    let mut child = elevated_command("apt-get", &full_args, sudo)?
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let stderr = collect_stderr(child.stderr.take().expect("stderr is piped"));
+
+    let bar = ProgressBar::new(100).with_style(bar_style()).with_prefix("Preparing");
+    let mut context = Vec::new();
+    let mut errors = Vec::new();
+    if let Some(stdout) = child.stdout.take() {
+        for line in BufReader::new(stdout).lines().map_while(|l| l.ok()) {
+            match parse_apt_status(&line) {
+                Some(AptStatus::Download { percent, message }) => {
+                    bar.set_prefix("Downloading");
+                    bar.set_position(percent as u64);
+                    bar.set_message(message);
+                }
+                Some(AptStatus::Package { percent, message, .. }) => {
+                    bar.set_prefix(label.to_string());
+                    bar.set_position(percent as u64);
+                    bar.set_message(message);
+                }
+                Some(AptStatus::Error { package, message }) => {
+                    errors.push(format!("{}: {}", package, message));
+                }
+                None => push_context(&mut context, &line),
+            }
+        }
+    }
+
+    let status = child.wait()?;
+    let stderr = stderr.join().unwrap_or_default();
+    if status.success() {
+        bar.finish_and_clear();
+        Ok(())
+    } else {
+        bar.abandon();
+        context.extend(errors);
+        Err(failure("apt-get", status.code(), &stderr, &context))
+    }
+}
+
+/// Run a tool that has no machine-readable progress (pikman, Flatpak) behind
+/// a spinner showing its latest output line
+pub fn run_with_spinner(label: &str, cmd: &str, args: &[&str], sudo: bool) -> Result<()> {
+    if !show_progress() {
+        return run_command_interactive(cmd, args, sudo);
+    }
+
+    let mut child = elevated_command(cmd, args, sudo)?
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let stderr = collect_stderr(child.stderr.take().expect("stderr is piped"));
+
+    let spinner = ProgressBar::new_spinner().with_style(spinner_style()).with_prefix(label.to_string());
+    spinner.enable_steady_tick(Duration::from_millis(120));
+    let mut context = Vec::new();
+    if let Some(stdout) = child.stdout.take() {
+        // Progress updates are redrawn with carriage returns, so split on those too
+        for chunk in BufReader::new(stdout).split(b'\n').map_while(|c| c.ok()) {
+            for part in String::from_utf8_lossy(&chunk).split('\r') {
+                let part = part.trim();
+                if !part.is_empty() {
+                    spinner.set_message(part.to_string());
+                    push_context(&mut context, part);
+                }
+            }
+        }
+    }
+
+    let status = child.wait()?;
+    let stderr = stderr.join().unwrap_or_default();
+    if status.success() {
+        spinner.finish_and_clear();
+        Ok(())
+    } else {
+        spinner.abandon();
+        Err(failure(cmd, status.code(), &stderr, &context))
+    }
+}
@@ -0,0 +1,164 @@
+//! HTTP and HTTPS proxies: the ones apt and the environment already use, and
+//! one set in BirdNest's config for everything it runs.
+//!
+//! A configured proxy is kept here, never in BirdNest's own environment,
+//! which other threads read while they start programs.
+//! [`CommandBuilder`](crate::utils::CommandBuilder) sets `http_proxy`,
+//! `https_proxy` and `no_proxy` on each command it builds, so Flatpak,
+//! pikman, curl and elevated commands get them alike. apt also gets the
+//! proxy as `-o Acquire::http::Proxy` options, since its own configuration
+//! would win over the environment. With no proxy in the config nothing
+//! changes: apt keeps its configuration and programs get the variables
+//! BirdNest was started with.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+
+use crate::utils::run_command;
+
+/// The variables programs read their proxy from
+pub const ENV_VARS: [&str; 3] = ["http_proxy", "https_proxy", "no_proxy"];
+
+// URL schemes apt, curl and Flatpak all understand for a proxy
+const SCHEMES: [&str; 3] = ["http://", "https://", "socks5h://"];
+
+// The proxy applied from the config, for commands' variables and apt's options
+static CONFIGURED: RwLock<ProxyConfig> = RwLock::new(ProxyConfig { http: None, https: None });
+
+/// The `proxy` section of the config, or a proxy found on the system
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProxyConfig {
+    /// Proxy for plain HTTP downloads, e.g. "http://proxy.example.com:3128"
+    pub http: Option<String>,
+    /// Proxy for HTTPS downloads; the HTTP one when unset
+    pub https: Option<String>,
+}
+
+impl ProxyConfig {
+    pub fn is_set(&self) -> bool {
+        self.http.is_some() || self.https.is_some()
+    }
+
+    /// The proxy for HTTPS, which goes through the HTTP proxy unless it has
+    /// its own, as with apt
+    pub fn https_or_http(&self) -> Option<&str> {
+        self.https.as_deref().or(self.http.as_deref())
+    }
+
+    /// "http://proxy:3128", or "http://proxy:3128 (HTTPS: http://other:8080)"
+    pub fn describe(&self) -> String {
+        match (self.http.as_deref(), self.https.as_deref()) {
+            (Some(http), Some(https)) if http != https => format!("{} (HTTPS: {})", http, https),
+            (Some(http), _) => http.to_string(),
+            (None, Some(https)) => format!("HTTPS only: {}", https),
+            (None, None) => "none".to_string(),
+        }
+    }
+
+    fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+        Self {
+            http: var("http_proxy").or_else(|| var("HTTP_PROXY")),
+            https: var("https_proxy").or_else(|| var("HTTPS_PROXY")),
+        }
+    }
+}
+
+/// The proxies already in place, shown beside BirdNest's own
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SystemProxy {
+    /// apt's `Acquire::http::Proxy` and `Acquire::https::Proxy`
+    pub apt: ProxyConfig,
+    /// The environment BirdNest was started with, which Flatpak and the
+    /// rest of the desktop follow
+    pub environment: ProxyConfig,
+}
+
+/// apt's proxies from `apt-config dump`, which merges apt.conf and
+/// apt.conf.d: `Acquire::http::Proxy "http://proxy:3128/";`. "DIRECT" and
+/// "false" turn a proxy off, and per-host entries
+/// (`Acquire::http::Proxy::deb.example.com`) aren't the default.
+pub fn parse_apt_config(dump: &str) -> ProxyConfig {
+    let mut config = ProxyConfig::default();
+    for line in dump.lines() {
+        let Some((key, value)) = line.trim().split_once(' ') else {
+            continue;
+        };
+        let value = value.trim().trim_end_matches(';').trim_matches('"');
+        let proxy = (!value.is_empty() && !value.eq_ignore_ascii_case("direct") && value != "false").then(|| value.to_string());
+        match key.to_ascii_lowercase().as_str() {
+            "acquire::http::proxy" => config.http = proxy,
+            "acquire::https::proxy" => config.https = proxy,
+            _ => {}
+        }
+    }
+    config
+}
+
+/// What apt and the environment use for a proxy
+pub fn detect() -> SystemProxy {
+    let apt = match run_command("apt-config", &["dump"], false) {
+        Ok(dump) => parse_apt_config(&dump),
+        Err(e) => {
+            tracing::debug!("proxy: no apt configuration: {}", e);
+            ProxyConfig::default()
+        }
+    };
+    let environment = ProxyConfig::from_env();
+    SystemProxy { apt, environment }
+}
+
+/// A proxy URL as typed into a settings field; empty means none
+pub fn parse(value: &str) -> Result<Option<String>> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    let Some(rest) = SCHEMES.iter().find_map(|scheme| value.strip_prefix(scheme)) else {
+        anyhow::bail!("\"{}\" isn't a proxy URL; it should look like http://proxy.example.com:3128", value);
+    };
+    if rest.trim_end_matches('/').is_empty() || rest.contains(char::is_whitespace) {
+        anyhow::bail!("\"{}\" has no proxy host", value);
+    }
+    Ok(Some(value.to_string()))
+}
+
+/// Makes `config` the proxy for everything BirdNest runs from now on; an
+/// empty one puts back the environment BirdNest started with
+pub fn apply(config: &ProxyConfig) {
+    *CONFIGURED.write().unwrap() = if config.is_set() {
+        ProxyConfig { http: config.http.clone(), https: config.https_or_http().map(str::to_string) }
+    } else {
+        ProxyConfig::default()
+    };
+}
+
+/// The proxy variables for a command: the configured proxy, or the ones
+/// BirdNest was started with. Hosts in `no_proxy` skip either.
+pub fn env() -> Vec<(&'static str, String)> {
+    let configured = CONFIGURED.read().unwrap();
+    let proxies = if configured.is_set() { configured.clone() } else { ProxyConfig::from_env() };
+    let var = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+    let no_proxy = var("no_proxy").or_else(|| var("NO_PROXY"));
+    ENV_VARS.into_iter().zip([proxies.http, proxies.https, no_proxy]).filter_map(|(name, value)| Some((name, value?))).collect()
+}
+
+/// The options that make `program` use the configured proxy over its own
+/// configuration: apt's `-o Acquire::…::Proxy`; none for other programs or
+/// without a configured proxy
+pub fn options(program: &str) -> Vec<String> {
+    if !matches!(program, "apt" | "apt-get") {
+        return Vec::new();
+    }
+    let configured = CONFIGURED.read().unwrap();
+    let mut options = Vec::new();
+    for (scheme, proxy) in [("http", configured.http.as_deref()), ("https", configured.https_or_http())] {
+        if let Some(proxy) = proxy {
+            options.push("-o".to_string());
+            options.push(format!("Acquire::{}::Proxy={}", scheme, proxy));
+        }
+    }
+    options
+}
@@ -0,0 +1,603 @@
+//! APT package sources: `/etc/apt/sources.list` and `/etc/apt/sources.list.d`.
+//!
+//! Both formats apt reads are handled: one-line entries
+//! (`deb [signed-by=...] URI suite components`) in `sources.list` and `.list`
+//! files, and deb822 stanzas in `.sources` files. Edits work on the file text,
+//! so comments and the other entries stay as they were.
+//!
+//! [`apply`] writes the files and needs root. Frontends run it through
+//! `birdnest sources` with the session's elevation method, see [`edit_command`].
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use crate::package_manager::BackendCommand;
+use crate::sources_backup;
+
+/// Where apt keeps its configuration
+pub const APT_DIR: &str = "/etc/apt";
+
+// The archives PikaOS and the distributions it builds on publish from;
+// sources pointing anywhere else are third-party
+const OFFICIAL_HOSTS: &[&str] = &["pika-os.com", "debian.org", "ubuntu.com"];
+const DEBIAN_COMPONENTS: &[&str] = &["main", "contrib", "non-free", "non-free-firmware"];
+const UBUNTU_COMPONENTS: &[&str] = &["main", "restricted", "universe", "multiverse"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// `deb URI suite components` lines
+    OneLine,
+    /// deb822 stanzas in a `.sources` file
+    Deb822,
+}
+
+impl Format {
+    pub fn of(path: &Path) -> Self {
+        if path.extension().is_some_and(|ext| ext == "sources") {
+            Format::Deb822
+        } else {
+            Format::OneLine
+        }
+    }
+}
+
+/// One entry: a line of a `.list` file or a stanza of a `.sources` file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Source {
+    pub file: PathBuf,
+    /// Position among the file's entries, which edits refer to it by
+    pub entry: usize,
+    pub format: Format,
+    pub enabled: bool,
+    /// `deb` and/or `deb-src`
+    pub types: Vec<String>,
+    pub uris: Vec<String>,
+    pub suites: Vec<String>,
+    pub components: Vec<String>,
+}
+
+impl Source {
+    /// Points at none of the distribution's own archives
+    pub fn is_third_party(&self) -> bool {
+        !self.uris.iter().any(|uri| is_official(uri))
+    }
+
+    /// Components that can be switched on and off: the archive's standard
+    /// ones, then any others the entry lists
+    pub fn offered_components(&self) -> Vec<String> {
+        let standard: &[&str] = match self.uris.first().map(|uri| host(uri)) {
+            Some(host) if host_matches(host, "ubuntu.com") => UBUNTU_COMPONENTS,
+            Some(host) if host_matches(host, "debian.org") => DEBIAN_COMPONENTS,
+            _ => &[],
+        };
+        let mut offered: Vec<String> = standard.iter().map(|c| c.to_string()).collect();
+        for component in &self.components {
+            if !offered.contains(component) {
+                offered.push(component.clone());
+            }
+        }
+        offered
+    }
+
+    /// "deb https://example.com/apt stable main", for lists and confirmations
+    pub fn summary(&self) -> String {
+        [self.types.join(" "), self.uris.join(" "), self.suites.join(" "), self.components.join(" ")]
+            .iter()
+            .filter(|part| !part.is_empty())
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+// "https://user@deb.example.com:8080/apt" -> "deb.example.com"
+fn host(uri: &str) -> &str {
+    let rest = uri.split_once("://").map_or(uri, |(_, rest)| rest);
+    let authority = rest.split('/').next().unwrap_or(rest);
+    let authority = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+    authority.split(':').next().unwrap_or(authority)
+}
+
+fn host_matches(host: &str, domain: &str) -> bool {
+    host == domain || host.strip_suffix(domain).is_some_and(|sub| sub.ends_with('.'))
+}
+
+fn is_official(uri: &str) -> bool {
+    let host = host(uri);
+    OFFICIAL_HOSTS.iter().any(|domain| host_matches(host, domain))
+}
+
+// A `deb`/`deb-src` line, enabled or commented out
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Line {
+    enabled: bool,
+    kind: String,
+    options: Option<String>,
+    uri: String,
+    suite: String,
+    components: Vec<String>,
+}
+
+impl Line {
+    fn parse(line: &str) -> Option<Self> {
+        let trimmed = line.trim();
+        let (enabled, rest) = match trimmed.strip_prefix('#') {
+            Some(rest) => (false, rest.trim_start()),
+            None => (true, trimmed),
+        };
+        let (kind, rest) = rest.split_once(char::is_whitespace)?;
+        if kind != "deb" && kind != "deb-src" {
+            return None;
+        }
+        let mut rest = rest.trim_start();
+        let mut options = None;
+        if let Some(bracketed) = rest.strip_prefix('[') {
+            let (inner, after) = bracketed.split_once(']')?;
+            options = Some(inner.trim().to_string());
+            rest = after.trim_start();
+        }
+        let mut words = rest.split_whitespace();
+        let uri = words.next()?.to_string();
+        let suite = words.next()?.to_string();
+        // Anything after a # is a comment
+        let components = words.take_while(|word| !word.starts_with('#')).map(str::to_string).collect();
+        Some(Line { enabled, kind: kind.to_string(), options, uri, suite, components })
+    }
+
+    fn render(&self) -> String {
+        let mut line = String::new();
+        if !self.enabled {
+            line.push_str("# ");
+        }
+        line.push_str(&self.kind);
+        if let Some(options) = &self.options {
+            line.push_str(&format!(" [{}]", options));
+        }
+        line.push_str(&format!(" {} {}", self.uri, self.suite));
+        for component in &self.components {
+            line.push(' ');
+            line.push_str(component);
+        }
+        line
+    }
+
+    // The value of an option like `signed-by=/usr/share/keyrings/x.gpg`
+    fn option(&self, key: &str) -> Option<String> {
+        self.options.as_deref()?.split_whitespace().find_map(|option| {
+            let (name, value) = option.split_once('=')?;
+            name.eq_ignore_ascii_case(key).then(|| value.to_string())
+        })
+    }
+}
+
+fn is_field_line(line: &str) -> bool {
+    !line.starts_with('#') && !line.starts_with(char::is_whitespace) && line.contains(':')
+}
+
+// Runs of non-blank lines that hold at least one field; lines that are all
+// comments don't make a stanza
+fn stanzas(lines: &[&str]) -> Vec<Range<usize>> {
+    let mut stanzas = Vec::new();
+    let mut start = None;
+    for index in 0..=lines.len() {
+        let blank = lines.get(index).is_none_or(|line| line.trim().is_empty());
+        match (start, blank) {
+            (None, false) => start = Some(index),
+            (Some(from), true) => {
+                if lines[from..index].iter().any(|line| is_field_line(line)) {
+                    stanzas.push(from..index);
+                }
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    stanzas
+}
+
+// The lines a field spans in `stanza`: its own and its continuation lines
+fn field_lines(lines: &[&str], stanza: &Range<usize>, key: &str) -> Option<Range<usize>> {
+    let start = stanza.clone().find(|&index| {
+        is_field_line(lines[index])
+            && lines[index].split_once(':').is_some_and(|(name, _)| name.trim().eq_ignore_ascii_case(key))
+    })?;
+    let end = (start + 1..stanza.end)
+        .find(|&index| !lines[index].starts_with(char::is_whitespace))
+        .unwrap_or(stanza.end);
+    Some(start..end)
+}
+
+fn field(lines: &[&str], stanza: &Range<usize>, key: &str) -> Option<String> {
+    let range = field_lines(lines, stanza, key)?;
+    let first = lines[range.start].split_once(':').map_or("", |(_, value)| value);
+    let value = std::iter::once(first)
+        .chain(lines[range.start + 1..range.end].iter().copied())
+        .map(str::trim)
+        .filter(|part| !part.is_empty() && *part != ".")
+        .collect::<Vec<_>>()
+        .join(" ");
+    Some(value)
+}
+
+fn words(value: Option<String>) -> Vec<String> {
+    value.unwrap_or_default().split_whitespace().map(str::to_string).collect()
+}
+
+/// The entries in one sources file
+pub fn parse(file: &Path, contents: &str) -> Vec<Source> {
+    let format = Format::of(file);
+    let lines: Vec<&str> = contents.lines().collect();
+    match format {
+        Format::OneLine => lines
+            .iter()
+            .filter_map(|line| Line::parse(line))
+            .enumerate()
+            .map(|(entry, line)| Source {
+                file: file.to_path_buf(),
+                entry,
+                format,
+                enabled: line.enabled,
+                types: vec![line.kind],
+                uris: vec![line.uri],
+                suites: vec![line.suite],
+                components: line.components,
+            })
+            .collect(),
+        Format::Deb822 => stanzas(&lines)
+            .iter()
+            .enumerate()
+            .map(|(entry, stanza)| Source {
+                file: file.to_path_buf(),
+                entry,
+                format,
+                enabled: field(&lines, stanza, "Enabled").is_none_or(|value| !value.eq_ignore_ascii_case("no")),
+                types: words(field(&lines, stanza, "Types")),
+                uris: words(field(&lines, stanza, "URIs")),
+                suites: words(field(&lines, stanza, "Suites")),
+                components: words(field(&lines, stanza, "Components")),
+            })
+            .collect(),
+    }
+}
+
+fn join_lines(lines: Vec<String>) -> String {
+    if lines.is_empty() {
+        return String::new();
+    }
+    lines.join("\n") + "\n"
+}
+
+fn missing_entry(entry: usize) -> anyhow::Error {
+    anyhow::anyhow!("There is no entry {} in this file", entry)
+}
+
+// Rewrite a one-line entry with `change`
+fn edit_line(contents: &str, entry: usize, change: impl FnOnce(&mut Line) -> Result<()>) -> Result<String> {
+    let mut lines: Vec<String> = contents.lines().map(str::to_string).collect();
+    let index = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| Line::parse(line).is_some())
+        .map(|(index, _)| index)
+        .nth(entry)
+        .ok_or_else(|| missing_entry(entry))?;
+    let mut line = Line::parse(&lines[index]).ok_or_else(|| missing_entry(entry))?;
+    change(&mut line)?;
+    lines[index] = line.render();
+    Ok(join_lines(lines))
+}
+
+// Set a deb822 field in one stanza, or drop it when `value` is None
+fn set_field(contents: &str, entry: usize, key: &str, value: Option<&str>) -> Result<String> {
+    let lines: Vec<&str> = contents.lines().collect();
+    let stanza = stanzas(&lines).into_iter().nth(entry).ok_or_else(|| missing_entry(entry))?;
+    let (replace, at) = match field_lines(&lines, &stanza, key) {
+        Some(range) => (range.clone(), range.start),
+        None => (stanza.end..stanza.end, stanza.end),
+    };
+    let mut edited: Vec<String> = lines[..at].iter().map(|line| line.to_string()).collect();
+    if let Some(value) = value {
+        edited.push(format!("{}: {}", key, value));
+    }
+    edited.extend(lines[replace.end..].iter().map(|line| line.to_string()));
+    Ok(join_lines(edited))
+}
+
+/// Enable or disable a whole entry: commenting the line out, or `Enabled: no`
+pub fn set_enabled(contents: &str, format: Format, entry: usize, enabled: bool) -> Result<String> {
+    match format {
+        Format::OneLine => edit_line(contents, entry, |line| {
+            line.enabled = enabled;
+            Ok(())
+        }),
+        Format::Deb822 => set_field(contents, entry, "Enabled", (!enabled).then_some("no")),
+    }
+}
+
+fn switch_component(components: &mut Vec<String>, component: &str, enabled: bool) -> Result<()> {
+    if enabled {
+        if !components.iter().any(|c| c == component) {
+            components.push(component.to_string());
+        }
+    } else {
+        components.retain(|c| c != component);
+        if components.is_empty() {
+            anyhow::bail!("{} is the only component left; disable the whole source instead", component);
+        }
+    }
+    Ok(())
+}
+
+/// Add or drop one component of an entry
+pub fn set_component(contents: &str, format: Format, entry: usize, component: &str, enabled: bool) -> Result<String> {
+    check_word("component", component)?;
+    match format {
+        Format::OneLine => edit_line(contents, entry, |line| switch_component(&mut line.components, component, enabled)),
+        Format::Deb822 => {
+            let lines: Vec<&str> = contents.lines().collect();
+            let stanza = stanzas(&lines).into_iter().nth(entry).ok_or_else(|| missing_entry(entry))?;
+            let mut components = words(field(&lines, &stanza, "Components"));
+            switch_component(&mut components, component, enabled)?;
+            set_field(contents, entry, "Components", Some(&components.join(" ")))
+        }
+    }
+}
+
+/// Drop an entry, with the blank line after a deb822 stanza
+pub fn remove_entry(contents: &str, format: Format, entry: usize) -> Result<String> {
+    let lines: Vec<&str> = contents.lines().collect();
+    let range = match format {
+        Format::OneLine => {
+            let index = lines
+                .iter()
+                .enumerate()
+                .filter(|(_, line)| Line::parse(line).is_some())
+                .map(|(index, _)| index)
+                .nth(entry)
+                .ok_or_else(|| missing_entry(entry))?;
+            index..index + 1
+        }
+        Format::Deb822 => {
+            let stanza = stanzas(&lines).into_iter().nth(entry).ok_or_else(|| missing_entry(entry))?;
+            let end = if lines.get(stanza.end).is_some_and(|line| line.trim().is_empty()) { stanza.end + 1 } else { stanza.end };
+            stanza.start..end
+        }
+    };
+    let kept = lines.iter().enumerate().filter(|(index, _)| !range.contains(index)).map(|(_, line)| line.to_string());
+    Ok(join_lines(kept.collect()))
+}
+
+fn check_word(what: &str, value: &str) -> Result<()> {
+    if value.is_empty() || value.contains(char::is_whitespace) || value.contains(['#', '[', ']']) {
+        anyhow::bail!("Invalid {}: '{}'", what, value);
+    }
+    Ok(())
+}
+
+/// A repository to add, written as a deb822 `.sources` file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewSource {
+    pub uri: String,
+    pub suite: String,
+    pub components: Vec<String>,
+    /// Keyring that signs the repository, as in `signed-by=`
+    pub signed_by: Option<String>,
+}
+
+impl NewSource {
+    /// A one-line entry pasted from a project's install instructions
+    pub fn from_line(line: &str) -> Result<Self> {
+        let parsed = Line::parse(line).filter(|line| line.enabled).ok_or_else(|| {
+            anyhow::anyhow!("Not a sources line: '{}' (expected deb URI suite components)", line.trim())
+        })?;
+        let source = NewSource {
+            signed_by: parsed.option("signed-by"),
+            uri: parsed.uri,
+            suite: parsed.suite,
+            components: parsed.components,
+        };
+        source.validate()?;
+        Ok(source)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        const SCHEMES: &[&str] = &["http://", "https://", "ftp://", "file:", "mirror+", "tor+"];
+        if !SCHEMES.iter().any(|scheme| self.uri.starts_with(scheme)) {
+            anyhow::bail!("'{}' is not a repository URL (expected http:// or https://)", self.uri);
+        }
+        check_word("URL", &self.uri)?;
+        check_word("suite", &self.suite)?;
+        // A suite ending in / is a flat repository, which has no components
+        if self.components.is_empty() && !self.suite.ends_with('/') {
+            anyhow::bail!("No components given for suite {}", self.suite);
+        }
+        for component in &self.components {
+            check_word("component", component)?;
+        }
+        if let Some(keyring) = &self.signed_by {
+            check_word("keyring path", keyring)?;
+        }
+        Ok(())
+    }
+
+    /// `download-example-com-apt.sources` for `https://download.example.com/apt`
+    pub fn file_name(&self) -> String {
+        let rest = self.uri.split_once("://").map_or(self.uri.as_str(), |(_, rest)| rest);
+        let slug: Vec<String> = rest
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|part| !part.is_empty())
+            .map(str::to_ascii_lowercase)
+            .collect();
+        format!("{}.sources", slug.join("-"))
+    }
+
+    pub fn render(&self) -> String {
+        let mut stanza = format!("Types: deb\nURIs: {}\nSuites: {}\n", self.uri, self.suite);
+        if !self.components.is_empty() {
+            stanza.push_str(&format!("Components: {}\n", self.components.join(" ")));
+        }
+        if let Some(keyring) = &self.signed_by {
+            stanza.push_str(&format!("Signed-By: {}\n", keyring));
+        }
+        stanza
+    }
+}
+
+/// The release codename new sources default to, from `/etc/os-release`
+pub fn codename() -> Option<String> {
+    let os_release = fs::read_to_string("/etc/os-release").ok()?;
+    os_release.lines().find_map(|line| {
+        let value = line.strip_prefix("VERSION_CODENAME=")?.trim_matches('"');
+        (!value.is_empty()).then(|| value.to_string())
+    })
+}
+
+/// A change to the sources, made as root by [`apply`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Edit {
+    SetEnabled { file: PathBuf, entry: usize, enabled: bool },
+    SetComponent { file: PathBuf, entry: usize, component: String, enabled: bool },
+    Add(NewSource),
+    Remove { file: PathBuf, entry: usize },
+    /// Put back a snapshot from [`sources_backup`], by its id
+    Restore { backup: String },
+}
+
+impl Edit {
+    /// The `birdnest` arguments that make this edit
+    pub fn args(&self) -> Vec<String> {
+        let toggle = |enabled: bool| if enabled { "enable" } else { "disable" };
+        let mut args = vec!["sources".to_string()];
+        match self {
+            Edit::SetEnabled { file, entry, enabled } => {
+                args.extend([toggle(*enabled).to_string(), file.display().to_string(), "--entry".to_string(), entry.to_string()]);
+            }
+            Edit::SetComponent { file, entry, component, enabled } => {
+                args.extend([
+                    toggle(*enabled).to_string(),
+                    file.display().to_string(),
+                    "--entry".to_string(),
+                    entry.to_string(),
+                    "--component".to_string(),
+                    component.clone(),
+                ]);
+            }
+            Edit::Add(source) => {
+                args.extend(["add".to_string(), source.uri.clone(), "--suite".to_string(), source.suite.clone()]);
+                for component in &source.components {
+                    args.extend(["--component".to_string(), component.clone()]);
+                }
+                if let Some(keyring) = &source.signed_by {
+                    args.extend(["--signed-by".to_string(), keyring.clone()]);
+                }
+            }
+            Edit::Remove { file, entry } => {
+                args.extend(["remove".to_string(), file.display().to_string(), "--entry".to_string(), entry.to_string()]);
+            }
+            Edit::Restore { backup } => args.extend(["restore".to_string(), backup.clone()]),
+        }
+        args
+    }
+}
+
+/// The root command that makes `edit` through `exe sources ...`
+pub fn edit_command(exe: &Path, edit: &Edit) -> BackendCommand {
+    BackendCommand { program: exe.display().to_string(), args: edit.args(), as_user: false }
+}
+
+/// Whether this system keeps apt sources
+pub fn supported() -> bool {
+    Path::new(APT_DIR).join("sources.list.d").is_dir()
+}
+
+/// Every entry in the system's sources files
+pub fn load() -> Result<Vec<Source>> {
+    load_from(Path::new(APT_DIR))
+}
+
+/// Every entry in `apt_dir`'s `sources.list` and `sources.list.d`, file by file
+pub fn load_from(apt_dir: &Path) -> Result<Vec<Source>> {
+    let mut files = vec![apt_dir.join("sources.list")];
+    if let Ok(entries) = fs::read_dir(apt_dir.join("sources.list.d")) {
+        let mut listed: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "list" || ext == "sources"))
+            .collect();
+        listed.sort();
+        files.extend(listed);
+    }
+
+    let mut sources = Vec::new();
+    for file in files {
+        match fs::read_to_string(&file) {
+            Ok(contents) => sources.extend(parse(&file, &contents)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => tracing::warn!("repos: can't read {}: {}", file.display(), e),
+        }
+    }
+    Ok(sources)
+}
+
+// Edits run as root, so they only ever touch apt's own sources files
+fn check_path(apt_dir: &Path, file: &Path) -> Result<()> {
+    let in_list_d = file.parent() == Some(apt_dir.join("sources.list.d").as_path())
+        && file.extension().is_some_and(|ext| ext == "list" || ext == "sources");
+    if file != apt_dir.join("sources.list") && !in_list_d {
+        anyhow::bail!("{} is not an apt sources file", file.display());
+    }
+    Ok(())
+}
+
+fn read(file: &Path) -> Result<String> {
+    fs::read_to_string(file).with_context(|| format!("Failed to read {}", file.display()))
+}
+
+/// Make `edit` to the system's sources; runs as root
+pub fn apply(edit: &Edit) -> Result<()> {
+    apply_in(Path::new(APT_DIR), edit)
+}
+
+/// Make `edit` to the sources under `apt_dir`
+pub fn apply_in(apt_dir: &Path, edit: &Edit) -> Result<()> {
+    match edit {
+        Edit::SetEnabled { file, entry, enabled } => {
+            check_path(apt_dir, file)?;
+            let edited = set_enabled(&read(file)?, Format::of(file), *entry, *enabled)?;
+            fs::write(file, edited)?;
+        }
+        Edit::SetComponent { file, entry, component, enabled } => {
+            check_path(apt_dir, file)?;
+            let edited = set_component(&read(file)?, Format::of(file), *entry, component, *enabled)?;
+            fs::write(file, edited)?;
+        }
+        Edit::Add(source) => {
+            source.validate()?;
+            let file = apt_dir.join("sources.list.d").join(source.file_name());
+            if file.exists() {
+                anyhow::bail!("{} already exists", file.display());
+            }
+            fs::write(&file, source.render())?;
+        }
+        Edit::Remove { file, entry } => {
+            check_path(apt_dir, file)?;
+            let contents = read(file)?;
+            let source = parse(file, &contents).into_iter().nth(*entry).ok_or_else(|| missing_entry(*entry))?;
+            if !source.is_third_party() {
+                anyhow::bail!("{} is one of the distribution's own sources; disable it instead", source.summary());
+            }
+            let edited = remove_entry(&contents, source.format, *entry)?;
+            // A file in sources.list.d with nothing left goes away entirely
+            if parse(file, &edited).is_empty() && file.parent() == Some(apt_dir.join("sources.list.d").as_path()) {
+                fs::remove_file(file)?;
+            } else {
+                fs::write(file, edited)?;
+            }
+        }
+        Edit::Restore { backup } => sources_backup::restore_in(apt_dir, Path::new(sources_backup::BACKUP_DIR), backup)?,
+    }
+    tracing::debug!("repos: {}", edit.args().join(" "));
+    Ok(())
+}
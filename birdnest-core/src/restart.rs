@@ -0,0 +1,223 @@
+//! What still runs old code after a transaction: a reboot requested by a
+//! package (`/run/reboot-required`), a newer kernel than the running one, and
+//! services that needrestart reports as using replaced libraries.
+//!
+//! needrestart can only see every process as root, so it is skipped when
+//! root isn't available without a password. Without it, services are found
+//! the way checkrestart did: processes mapping a deleted file, grouped by
+//! their systemd unit - which only covers what this user may inspect. The
+//! reboot checks need no privileges.
+
+use std::path::Path;
+use std::process::Stdio;
+
+use crate::elevation;
+use crate::package_manager::BackendCommand;
+use crate::utils::CommandBuilder;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RestartStatus {
+    /// A package asked for a reboot through `/run/reboot-required`
+    pub reboot_required: bool,
+    /// The packages that asked, from `/run/reboot-required.pkgs`
+    pub reboot_packages: Vec<String>,
+    /// Running and newest installed kernel, when they differ
+    pub kernel: Option<(String, String)>,
+    /// systemd units running outdated binaries or libraries
+    pub services: Vec<String>,
+}
+
+impl RestartStatus {
+    pub fn needs_reboot(&self) -> bool {
+        self.reboot_required || self.kernel.is_some()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !self.needs_reboot() && self.services.is_empty()
+    }
+
+    /// One line per finding, for the CLI and the GUI banner
+    pub fn summary(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        if let Some((running, installed)) = &self.kernel {
+            lines.push(format!("Kernel {} is installed, but {} is still running", installed, running));
+        }
+        if self.reboot_required {
+            if self.reboot_packages.is_empty() {
+                lines.push("A reboot is required to finish the update".to_string());
+            } else {
+                lines.push(format!("A reboot is required by: {}", self.reboot_packages.join(", ")));
+            }
+        }
+        if !self.services.is_empty() {
+            lines.push(format!("Services using outdated libraries: {}", self.services.join(", ")));
+        }
+        lines
+    }
+}
+
+/// Check the running system
+pub fn check() -> RestartStatus {
+    let running = std::fs::read_to_string("/proc/sys/kernel/osrelease").unwrap_or_default();
+    let mut status = check_in(Path::new("/"), running.trim());
+    match run_needrestart() {
+        Some(output) => {
+            let report = parse_needrestart(&output);
+            status.services = report.services;
+            if status.kernel.is_none() {
+                status.kernel = report.kernel;
+            }
+        }
+        None => status.services = outdated_services_in(Path::new("/proc")),
+    }
+    tracing::debug!(
+        "Restart check: reboot={}, kernel={:?}, services={}",
+        status.reboot_required,
+        status.kernel,
+        status.services.len()
+    );
+    status
+}
+
+/// The file-based checks against the system rooted at `root`
+pub fn check_in(root: &Path, running_kernel: &str) -> RestartStatus {
+    let reboot_required = root.join("run/reboot-required").exists();
+    let reboot_packages = std::fs::read_to_string(root.join("run/reboot-required.pkgs"))
+        .map(|pkgs| {
+            let mut pkgs: Vec<String> = pkgs.lines().map(str::trim).filter(|p| !p.is_empty()).map(String::from).collect();
+            pkgs.dedup();
+            pkgs
+        })
+        .unwrap_or_default();
+    let kernel = newest_kernel(&root.join("boot"))
+        .filter(|newest| !running_kernel.is_empty() && newest != running_kernel)
+        .map(|newest| (running_kernel.to_string(), newest));
+
+    RestartStatus {
+        reboot_required,
+        reboot_packages,
+        kernel,
+        services: Vec::new(),
+    }
+}
+
+// The most recently installed kernel image, by modification time
+fn newest_kernel(boot: &Path) -> Option<String> {
+    std::fs::read_dir(boot)
+        .ok()?
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            let version = name.strip_prefix("vmlinuz-")?.to_string();
+            let modified = entry.metadata().and_then(|meta| meta.modified()).ok()?;
+            Some((modified, version))
+        })
+        .max()
+        .map(|(_, version)| version)
+}
+
+/// What `needrestart -b` reported
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NeedrestartReport {
+    pub services: Vec<String>,
+    pub kernel: Option<(String, String)>,
+}
+
+/// Parse needrestart's batch output (`NEEDRESTART-SVC: cron.service` lines and
+/// the KCUR/KEXP/KSTA kernel fields)
+pub fn parse_needrestart(output: &str) -> NeedrestartReport {
+    let mut report = NeedrestartReport::default();
+    let (mut current, mut expected, mut kernel_status) = (None, None, 0);
+    for line in output.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim() {
+            "NEEDRESTART-SVC" if !value.is_empty() => report.services.push(value.to_string()),
+            "NEEDRESTART-KCUR" => current = Some(value.to_string()),
+            "NEEDRESTART-KEXP" => expected = Some(value.to_string()),
+            "NEEDRESTART-KSTA" => kernel_status = value.parse().unwrap_or(0),
+            _ => {}
+        }
+    }
+    // KSTA 2 and 3 mean an ABI-compatible or a new kernel version is pending
+    if kernel_status >= 2 {
+        if let (Some(current), Some(expected)) = (current, expected) {
+            report.kernel = Some((current, expected));
+        }
+    }
+    report.services.sort();
+    report.services.dedup();
+    report
+}
+
+/// Services with a process that still maps a replaced (deleted) binary or
+/// library, found by scanning `proc`
+pub fn outdated_services_in(proc: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(proc) else {
+        return Vec::new();
+    };
+    let mut services: Vec<String> = entries
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().bytes().all(|b| b.is_ascii_digit()))
+        .filter_map(|entry| {
+            // Unreadable for other users' processes unless running as root
+            let maps = std::fs::read_to_string(entry.path().join("maps")).ok()?;
+            if !maps_outdated(&maps) {
+                return None;
+            }
+            unit_from_cgroup(&std::fs::read_to_string(entry.path().join("cgroup")).ok()?)
+        })
+        .collect();
+    services.sort();
+    services.dedup();
+    services
+}
+
+/// Whether a `/proc/<pid>/maps` listing has an executable or shared library
+/// that was replaced on disk
+pub fn maps_outdated(maps: &str) -> bool {
+    maps.lines().any(|line| {
+        let Some(path) = line.split_whitespace().nth(5) else {
+            return false;
+        };
+        // Shared memory and memfd mappings are deleted by design
+        line.ends_with(" (deleted)")
+            && (path.starts_with("/usr/") || path.starts_with("/lib") || path.starts_with("/bin/") || path.starts_with("/sbin/"))
+    })
+}
+
+/// The system service a process belongs to, from `/proc/<pid>/cgroup`;
+/// user sessions and scopes aren't restartable with `systemctl restart`
+pub fn unit_from_cgroup(cgroup: &str) -> Option<String> {
+    cgroup.lines().find_map(|line| {
+        let path = line.splitn(3, ':').nth(2)?;
+        let unit = path.strip_prefix("/system.slice/")?.split('/').next()?;
+        unit.ends_with(".service").then(|| unit.to_string())
+    })
+}
+
+fn run_needrestart() -> Option<String> {
+    if !elevation::passwordless() {
+        tracing::debug!("Restart check: skipping needrestart, root needs a password");
+        return None;
+    }
+    let (mut command, _) = CommandBuilder::new("needrestart").args(&["-b", "-r", "l"]).elevated(true).c_locale().build().ok()?;
+    let output = command.stdin(Stdio::null()).stderr(Stdio::null()).output().ok()?;
+    if !output.status.success() {
+        tracing::debug!("Restart check: needrestart exited with {}", output.status);
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// `systemctl restart` for the given units; needs root
+pub fn restart_services_command(services: &[String]) -> BackendCommand {
+    BackendCommand::new("systemctl", &["restart"], services)
+}
+
+/// `systemctl reboot`; logind asks polkit itself, so it runs unelevated
+pub fn reboot_command() -> BackendCommand {
+    BackendCommand::new("systemctl", &["reboot"], &[])
+}
@@ -0,0 +1,336 @@
+//! Software bills of materials: everything installed on this machine - system
+//! packages, Flatpak apps and runtimes, and programs exported from pikman
+//! containers - as an SPDX 2.3 or CycloneDX 1.5 JSON document, for audits and
+//! fleet inventories.
+
+use anyhow::Result;
+use serde_json::{json, Value};
+
+use crate::package_manager::host_backend;
+use crate::pikman_exports::{self, ExportedApp};
+use crate::utils::{now, run_command, utc_timestamp};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SbomFormat {
+    Spdx,
+    CycloneDx,
+}
+
+impl SbomFormat {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_str() {
+            "spdx" | "spdx-json" => Ok(SbomFormat::Spdx),
+            "cyclonedx" | "cdx" => Ok(SbomFormat::CycloneDx),
+            other => anyhow::bail!("Unknown SBOM format '{}' (expected spdx or cyclonedx)", other),
+        }
+    }
+}
+
+/// Where a component comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ComponentKind {
+    /// A system package, with its package URL type: deb, rpm or alpm
+    Package { purl_type: &'static str },
+    Flatpak { remote: Option<String>, branch: Option<String>, runtime: bool },
+    /// A program a pikman container exports to the host
+    Exported { container: String, command: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SbomComponent {
+    pub name: String,
+    pub version: Option<String>,
+    pub arch: Option<String>,
+    /// The package's maintainer, when the package database says
+    pub supplier: Option<String>,
+    pub kind: ComponentKind,
+}
+
+impl SbomComponent {
+    /// The component's package URL; `distro` is the os-release ID system
+    /// packages are namespaced under. Exported programs have none, since
+    /// the host can't see which package in the container they came from.
+    pub fn purl(&self, distro: &str) -> Option<String> {
+        let (base, mut qualifiers) = match &self.kind {
+            ComponentKind::Package { purl_type } => (format!("pkg:{}/{}/{}", purl_type, encode(distro), encode(&self.name)), Vec::new()),
+            ComponentKind::Flatpak { remote, branch, .. } => {
+                let qualifiers = [("branch", branch), ("repository", remote)]
+                    .into_iter()
+                    .filter_map(|(key, value)| Some((key, value.clone()?)))
+                    .collect();
+                (format!("pkg:generic/flatpak/{}", encode(&self.name)), qualifiers)
+            }
+            ComponentKind::Exported { .. } => return None,
+        };
+        if let Some(arch) = &self.arch {
+            qualifiers.push(("arch", arch.clone()));
+        }
+        qualifiers.sort();
+        let mut purl = base;
+        if let Some(version) = &self.version {
+            purl.push('@');
+            purl.push_str(&encode(version));
+        }
+        for (index, (key, value)) in qualifiers.iter().enumerate() {
+            purl.push(if index == 0 { '?' } else { '&' });
+            purl.push_str(&format!("{}={}", key, encode(value)));
+        }
+        Some(purl)
+    }
+
+    /// apt, rpm, alpm, flatpak or pikman
+    pub fn source(&self) -> &str {
+        match &self.kind {
+            ComponentKind::Package { purl_type: "deb" } => "apt",
+            ComponentKind::Package { purl_type } => purl_type,
+            ComponentKind::Flatpak { .. } => "flatpak",
+            ComponentKind::Exported { .. } => "pikman",
+        }
+    }
+}
+
+impl From<ExportedApp> for SbomComponent {
+    fn from(app: ExportedApp) -> Self {
+        Self {
+            name: app.name,
+            version: None,
+            arch: None,
+            supplier: None,
+            kind: ComponentKind::Exported { container: app.container, command: app.command },
+        }
+    }
+}
+
+// Percent-encodes everything but purl's unreserved characters
+fn encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'.' | b'-' | b'_' | b'~' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// What an SBOM document describes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sbom {
+    pub host: String,
+    /// The os-release ID, e.g. "pika"
+    pub distro: String,
+    /// Unix time the inventory was taken
+    pub created: i64,
+    /// A UUID naming this document
+    pub serial: String,
+    pub components: Vec<SbomComponent>,
+}
+
+const DPKG_FORMAT: &str = "-f=${db:Status-Abbrev}\t${Package}\t${Version}\t${Architecture}\t${Maintainer}\n";
+
+impl Sbom {
+    /// Inventory this machine. Flatpak and pikman are optional: without
+    /// them the document only lists system packages.
+    pub fn collect() -> Result<Self> {
+        let backend = host_backend();
+        let mut components = if backend.name() == "apt" {
+            parse_dpkg_inventory(&run_command("dpkg-query", &["-W", DPKG_FORMAT], false)?)
+        } else {
+            let purl_type = if backend.name() == "pacman" { "alpm" } else { "rpm" };
+            backend
+                .list_installed()?
+                .into_iter()
+                .map(|package| SbomComponent {
+                    name: package.name,
+                    version: Some(package.version).filter(|v| !v.is_empty()),
+                    arch: None,
+                    supplier: None,
+                    kind: ComponentKind::Package { purl_type },
+                })
+                .collect()
+        };
+        for (kind, runtime) in [("--app", false), ("--runtime", true)] {
+            match run_command("flatpak", &["list", kind, "--columns=application,version,branch,arch,origin"], false) {
+                Ok(output) => components.extend(parse_flatpak_inventory(&output, runtime)),
+                Err(e) => tracing::debug!("sbom: no Flatpaks listed: {}", e),
+            }
+        }
+        components.extend(pikman_exports::list().into_iter().map(SbomComponent::from));
+
+        let read = |path: &str| std::fs::read_to_string(path).map(|text| text.trim().to_string()).unwrap_or_default();
+        let distro = read("/etc/os-release")
+            .lines()
+            .find_map(|line| line.strip_prefix("ID="))
+            .map(|id| id.trim_matches('"').to_string())
+            .unwrap_or_else(|| "debian".to_string());
+        Ok(Sbom {
+            host: read("/proc/sys/kernel/hostname"),
+            distro,
+            created: now() as i64,
+            serial: read("/proc/sys/kernel/random/uuid"),
+            components,
+        })
+    }
+
+    pub fn render(&self, format: SbomFormat) -> String {
+        let document = match format {
+            SbomFormat::Spdx => self.spdx(),
+            SbomFormat::CycloneDx => self.cyclonedx(),
+        };
+        let mut text = serde_json::to_string_pretty(&document).unwrap_or_default();
+        text.push('\n');
+        text
+    }
+
+    fn spdx(&self) -> Value {
+        let ids: Vec<String> = self
+            .components
+            .iter()
+            .enumerate()
+            .map(|(index, component)| {
+                let name: String = component.name.chars().map(|c| if c.is_ascii_alphanumeric() || c == '.' { c } else { '-' }).collect();
+                format!("SPDXRef-{}-{}-{}", component.source(), index + 1, name)
+            })
+            .collect();
+        let packages: Vec<Value> = self
+            .components
+            .iter()
+            .zip(&ids)
+            .map(|(component, id)| {
+                let mut package = json!({
+                    "name": component.name,
+                    "SPDXID": id,
+                    "versionInfo": component.version.as_deref().unwrap_or("NOASSERTION"),
+                    "supplier": component.supplier.as_ref().map_or("NOASSERTION".to_string(), |s| format!("Person: {}", s)),
+                    "downloadLocation": "NOASSERTION",
+                    "filesAnalyzed": false,
+                    "licenseConcluded": "NOASSERTION",
+                    "licenseDeclared": "NOASSERTION",
+                    "copyrightText": "NOASSERTION",
+                });
+                if let Some(purl) = component.purl(&self.distro) {
+                    package["externalRefs"] = json!([{
+                        "referenceCategory": "PACKAGE-MANAGER",
+                        "referenceType": "purl",
+                        "referenceLocator": purl,
+                    }]);
+                }
+                if let ComponentKind::Exported { container, command } = &component.kind {
+                    package["comment"] = json!(format!("Runs `{}` in the pikman container {}", command, container));
+                }
+                package
+            })
+            .collect();
+        let relationships: Vec<Value> = ids
+            .iter()
+            .map(|id| json!({ "spdxElementId": "SPDXRef-DOCUMENT", "relationshipType": "DESCRIBES", "relatedSpdxElement": id }))
+            .collect();
+        json!({
+            "spdxVersion": "SPDX-2.3",
+            "dataLicense": "CC0-1.0",
+            "SPDXID": "SPDXRef-DOCUMENT",
+            "name": format!("{} software inventory", self.host),
+            "documentNamespace": format!("https://birdnest.invalid/spdx/{}/{}", self.host, self.serial),
+            "creationInfo": {
+                "created": utc_timestamp(self.created),
+                "creators": [format!("Tool: birdnest-{}", env!("CARGO_PKG_VERSION"))],
+            },
+            "packages": packages,
+            "relationships": relationships,
+        })
+    }
+
+    fn cyclonedx(&self) -> Value {
+        let components: Vec<Value> = self
+            .components
+            .iter()
+            .enumerate()
+            .map(|(index, component)| {
+                let kind = match component.kind {
+                    ComponentKind::Flatpak { runtime: true, .. } => "framework",
+                    ComponentKind::Package { .. } => "library",
+                    _ => "application",
+                };
+                let mut properties = vec![json!({ "name": "birdnest:source", "value": component.source() })];
+                match &component.kind {
+                    ComponentKind::Flatpak { remote: Some(remote), .. } => properties.push(json!({ "name": "birdnest:flatpak-remote", "value": remote })),
+                    ComponentKind::Exported { container, command } => {
+                        properties.push(json!({ "name": "birdnest:container", "value": container }));
+                        properties.push(json!({ "name": "birdnest:command", "value": command }));
+                    }
+                    _ => {}
+                }
+                let mut value = json!({
+                    "type": kind,
+                    "bom-ref": format!("{}-{}", component.source(), index + 1),
+                    "name": component.name,
+                    "properties": properties,
+                });
+                if let Some(version) = &component.version {
+                    value["version"] = json!(version);
+                }
+                if let Some(supplier) = &component.supplier {
+                    value["supplier"] = json!({ "name": supplier });
+                }
+                if let Some(purl) = component.purl(&self.distro) {
+                    value["purl"] = json!(purl);
+                }
+                value
+            })
+            .collect();
+        json!({
+            "bomFormat": "CycloneDX",
+            "specVersion": "1.5",
+            "serialNumber": format!("urn:uuid:{}", self.serial),
+            "version": 1,
+            "metadata": {
+                "timestamp": utc_timestamp(self.created),
+                "tools": [{ "vendor": "BirdNest", "name": "birdnest", "version": env!("CARGO_PKG_VERSION") }],
+                "component": { "type": "operating-system", "name": self.host, "bom-ref": "host" },
+            },
+            "components": components,
+        })
+    }
+}
+
+/// Parse `dpkg-query -W` with [`DPKG_FORMAT`]'s columns, keeping installed
+/// packages
+pub fn parse_dpkg_inventory(output: &str) -> Vec<SbomComponent> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut columns = line.split('\t');
+            let status = columns.next()?;
+            // "ii" and "hi": the second letter is the current state
+            if status.chars().nth(1) != Some('i') {
+                return None;
+            }
+            let name = columns.next()?.trim().to_string();
+            let text = |column: Option<&str>| column.map(str::trim).filter(|c| !c.is_empty()).map(str::to_string);
+            Some(SbomComponent {
+                name,
+                version: text(columns.next()),
+                arch: text(columns.next()),
+                supplier: text(columns.next()),
+                kind: ComponentKind::Package { purl_type: "deb" },
+            })
+        })
+        .collect()
+}
+
+/// Parse `flatpak list --columns=application,version,branch,arch,origin`
+pub fn parse_flatpak_inventory(output: &str, runtime: bool) -> Vec<SbomComponent> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let columns: Vec<Option<String>> = line.split('\t').map(|c| Some(c.trim().to_string()).filter(|c| !c.is_empty())).collect();
+            let column = |index: usize| columns.get(index).cloned().flatten();
+            Some(SbomComponent {
+                name: column(0)?,
+                version: column(1),
+                arch: column(3),
+                supplier: None,
+                kind: ComponentKind::Flatpak { remote: column(4), branch: column(2), runtime },
+            })
+        })
+        .collect()
+}
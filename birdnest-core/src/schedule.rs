@@ -0,0 +1,124 @@
+//! When background refreshes may use the network: a window of hours and
+//! whether metered connections are off limits.
+//!
+//! The GUI's periodic refresh and `birdnest update --scheduled` check the
+//! [`RefreshSchedule`] from the config before touching the network;
+//! refreshes the user starts themselves ignore it.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::process::Command;
+
+/// Hours of the day, local time, from `start` up to but not including
+/// `end`; a window past midnight (22 to 6) wraps around
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HourWindow {
+    pub start: u8,
+    pub end: u8,
+}
+
+impl HourWindow {
+    pub fn contains(&self, hour: u8) -> bool {
+        if self.start <= self.end {
+            (self.start..self.end).contains(&hour)
+        } else {
+            hour >= self.start || hour < self.end
+        }
+    }
+}
+
+impl fmt::Display for HourWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}:00-{:02}:00", self.start, self.end)
+    }
+}
+
+/// The `refresh_schedule` section of the config
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RefreshSchedule {
+    /// How often the GUI refreshes metadata on its own; never when unset
+    #[serde(default)]
+    pub every_hours: Option<u32>,
+    /// Only refresh in the background during these hours
+    #[serde(default)]
+    pub hours: Option<HourWindow>,
+    /// Skip background refreshes while the connection is metered
+    #[serde(default)]
+    pub unmetered_only: bool,
+}
+
+/// Whether a background refresh may run now, and why not
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Decision {
+    Allowed,
+    OutsideHours(HourWindow),
+    Metered,
+}
+
+impl fmt::Display for Decision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Decision::Allowed => write!(f, "allowed"),
+            Decision::OutsideHours(window) => write!(f, "background refreshes only run {}", window),
+            Decision::Metered => write!(f, "the connection is metered"),
+        }
+    }
+}
+
+impl RefreshSchedule {
+    /// `metered` is None when NetworkManager can't tell, which counts as unmetered
+    pub fn check(&self, hour: u8, metered: Option<bool>) -> Decision {
+        if let Some(window) = self.hours {
+            if !window.contains(hour) {
+                return Decision::OutsideHours(window);
+            }
+        }
+        if self.unmetered_only && metered == Some(true) {
+            return Decision::Metered;
+        }
+        Decision::Allowed
+    }
+
+    pub fn check_now(&self) -> Decision {
+        // Asking NetworkManager is only worth it when the answer matters
+        let metered = if self.unmetered_only { connection_metered() } else { None };
+        self.check(local_hour(), metered)
+    }
+}
+
+pub fn local_hour() -> u8 {
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    unsafe {
+        let now = libc::time(std::ptr::null_mut());
+        libc::localtime_r(&now, &mut tm);
+    }
+    tm.tm_hour as u8
+}
+
+/// NetworkManager's view of the primary connection; None without NetworkManager
+pub fn connection_metered() -> Option<bool> {
+    let output = Command::new("busctl")
+        .args([
+            "get-property",
+            "org.freedesktop.NetworkManager",
+            "/org/freedesktop/NetworkManager",
+            "org.freedesktop.NetworkManager",
+            "Metered",
+        ])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_nm_metered(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Parse busctl's "u 4" for NetworkManager's NMMetered: 1 and 3 are
+/// (guessed) metered, 2 and 4 (guessed) unmetered, 0 unknown
+pub fn parse_nm_metered(output: &str) -> Option<bool> {
+    match output.split_whitespace().nth(1)?.parse::<u32>().ok()? {
+        1 | 3 => Some(true),
+        2 | 4 => Some(false),
+        _ => None,
+    }
+}
@@ -0,0 +1,180 @@
+//! Shell script and Ansible task generation for package selections.
+
+use anyhow::Result;
+use crate::utils::run_command;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptFormat {
+    Shell,
+    Ansible,
+}
+
+impl ScriptFormat {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_str() {
+            "shell" | "sh" | "bash" => Ok(ScriptFormat::Shell),
+            "ansible" | "yaml" | "yml" => Ok(ScriptFormat::Ansible),
+            other => anyhow::bail!("Unknown script format '{}' (expected shell or ansible)", other),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlatpakRef {
+    pub application: String,
+    pub remote: Option<String>,
+}
+
+/// Packages that a generated script should reproduce
+#[derive(Debug, Clone, Default)]
+pub struct ScriptSelection {
+    pub apt_packages: Vec<String>,
+    pub flatpaks: Vec<FlatpakRef>,
+}
+
+impl ScriptSelection {
+    pub fn is_empty(&self) -> bool {
+        self.apt_packages.is_empty() && self.flatpaks.is_empty()
+    }
+}
+
+// Remote used when a Flatpak was picked from search results without origin info
+const DEFAULT_FLATPAK_REMOTE: &str = "flathub";
+
+pub fn render(selection: &ScriptSelection, format: ScriptFormat) -> String {
+    match format {
+        ScriptFormat::Shell => render_shell(selection),
+        ScriptFormat::Ansible => render_ansible(selection),
+    }
+}
+
+fn render_shell(selection: &ScriptSelection) -> String {
+    let mut script = String::new();
+    script.push_str("#!/bin/sh\n");
+    script.push_str("# Generated by BirdNest\n");
+    script.push_str("set -e\n");
+
+    if !selection.apt_packages.is_empty() {
+        script.push_str("\nsudo apt-get update\n");
+        script.push_str("sudo apt-get install -y");
+        for pkg in &selection.apt_packages {
+            script.push(' ');
+            script.push_str(&shell_quote(pkg));
+        }
+        script.push('\n');
+    }
+
+    if !selection.flatpaks.is_empty() {
+        script.push('\n');
+        // Group by remote so each remote gets a single install command
+        for (remote, apps) in group_by_remote(&selection.flatpaks) {
+            script.push_str("flatpak install -y --noninteractive ");
+            script.push_str(&shell_quote(&remote));
+            for app in apps {
+                script.push(' ');
+                script.push_str(&shell_quote(&app));
+            }
+            script.push('\n');
+        }
+    }
+
+    script
+}
+
+fn render_ansible(selection: &ScriptSelection) -> String {
+    let mut tasks = String::new();
+    tasks.push_str("# Generated by BirdNest\n");
+
+    if !selection.apt_packages.is_empty() {
+        tasks.push_str("- name: Install apt packages\n");
+        tasks.push_str("  ansible.builtin.apt:\n");
+        tasks.push_str("    name:\n");
+        for pkg in &selection.apt_packages {
+            tasks.push_str(&format!("      - {}\n", yaml_quote(pkg)));
+        }
+        tasks.push_str("    state: present\n");
+        tasks.push_str("    update_cache: true\n");
+        tasks.push_str("  become: true\n");
+    }
+
+    for (remote, apps) in group_by_remote(&selection.flatpaks) {
+        if tasks.lines().count() > 1 {
+            tasks.push('\n');
+        }
+        tasks.push_str(&format!("- name: Install Flatpak applications from {}\n", remote));
+        tasks.push_str("  community.general.flatpak:\n");
+        tasks.push_str("    name:\n");
+        for app in apps {
+            tasks.push_str(&format!("      - {}\n", yaml_quote(&app)));
+        }
+        tasks.push_str(&format!("    remote: {}\n", yaml_quote(&remote)));
+        tasks.push_str("    state: present\n");
+    }
+
+    tasks
+}
+
+fn group_by_remote(flatpaks: &[FlatpakRef]) -> Vec<(String, Vec<String>)> {
+    let mut groups: Vec<(String, Vec<String>)> = Vec::new();
+    for fp in flatpaks {
+        let remote = fp.remote.clone().unwrap_or_else(|| DEFAULT_FLATPAK_REMOTE.to_string());
+        match groups.iter_mut().find(|(r, _)| *r == remote) {
+            Some((_, apps)) => apps.push(fp.application.clone()),
+            None => groups.push((remote, vec![fp.application.clone()])),
+        }
+    }
+    groups
+}
+
+fn shell_quote(value: &str) -> String {
+    let is_safe = !value.is_empty() && value
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "-_.+:=/@".contains(c));
+    if is_safe {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', "'\\''"))
+    }
+}
+
+fn yaml_quote(value: &str) -> String {
+    let is_plain = !value.is_empty() && value
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "-_.+".contains(c));
+    if is_plain {
+        value.to_string()
+    } else {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+}
+
+// Build a selection from what is installed on this machine: manually installed
+// apt packages plus Flatpak applications (runtimes are pulled in automatically)
+pub fn load_installed_selection() -> Result<ScriptSelection> {
+    let mut selection = ScriptSelection::default();
+
+    let manual = run_command("apt-mark", &["showmanual"], false)?;
+    selection.apt_packages = manual
+        .lines()
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty())
+        .collect();
+
+    // Flatpak is optional - an empty list is fine if it isn't installed
+    if let Ok(output) = run_command("flatpak", &["list", "--app", "--columns=application,origin"], false) {
+        selection.flatpaks = output
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.split('\t');
+                let application = parts.next()?.trim().to_string();
+                if application.is_empty() {
+                    return None;
+                }
+                let remote = parts.next().map(|r| r.trim().to_string()).filter(|r| !r.is_empty());
+                Some(FlatpakRef { application, remote })
+            })
+            .collect();
+    }
+
+    Ok(selection)
+}
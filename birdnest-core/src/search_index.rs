@@ -0,0 +1,170 @@
+//! In-memory index of every package a backend can install, for ranked,
+//! fuzzy as-you-type search without spawning `apt-cache search` per query.
+
+use anyhow::Result;
+
+use crate::package_manager::{PackageBackend, PackageSummary};
+
+#[derive(Debug)]
+struct Entry {
+    package: PackageSummary,
+    // Lowercased once here rather than on every keystroke
+    name: String,
+    description: String,
+}
+
+/// The best matches for a query and how many there were in all
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Matches {
+    pub packages: Vec<PackageSummary>,
+    pub total: usize,
+}
+
+/// Names and descriptions of all available packages
+#[derive(Debug, Default)]
+pub struct SearchIndex {
+    entries: Vec<Entry>,
+}
+
+impl SearchIndex {
+    pub fn new(packages: Vec<PackageSummary>) -> Self {
+        let entries = packages
+            .into_iter()
+            .map(|package| Entry {
+                name: package.name.to_lowercase(),
+                description: package.description.to_lowercase(),
+                package,
+            })
+            .collect();
+        Self { entries }
+    }
+
+    /// Index everything `backend` can list; fails for backends that can't
+    pub fn build(backend: &dyn PackageBackend) -> Result<Self> {
+        Ok(Self::new(backend.all_packages()?))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The best `limit` matches for `query`, best first. Every word of the
+    /// query has to match the name (exactly, as a prefix, a substring or
+    /// fuzzily) or appear in the description; name matches rank higher.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<PackageSummary> {
+        self.search_filtered(query, limit, false, |_| true).packages
+    }
+
+    /// Like [`search`](Self::search), but counting every match.
+    /// `names_only` leaves descriptions out, and packages `keep` turns
+    /// down don't match at all.
+    pub fn search_filtered(&self, query: &str, limit: usize, names_only: bool, keep: impl Fn(&PackageSummary) -> bool) -> Matches {
+        let query = query.to_lowercase();
+        let terms: Vec<&str> = query.split_whitespace().collect();
+        if terms.is_empty() {
+            return Matches::default();
+        }
+
+        let mut matches: Vec<(u32, &Entry)> = self
+            .entries
+            .iter()
+            .filter_map(|entry| {
+                let description = if names_only { "" } else { entry.description.as_str() };
+                terms
+                    .iter()
+                    .map(|term| term_score(term, &entry.name, description))
+                    .sum::<Option<u32>>()
+                    .filter(|_| keep(&entry.package))
+                    .map(|score| (score, entry))
+            })
+            .collect();
+        // Shorter names first among equals: "vim" before "vim-gtk3"
+        matches.sort_by(|(a_score, a), (b_score, b)| {
+            b_score
+                .cmp(a_score)
+                .then(a.name.len().cmp(&b.name.len()))
+                .then(a.name.cmp(&b.name))
+        });
+        let total = matches.len();
+        let packages = matches.into_iter().take(limit).map(|(_, entry)| entry.package.clone()).collect();
+        Matches { packages, total }
+    }
+
+    /// Up to `limit` package names starting with `prefix`, ignoring case,
+    /// shortest first, for completing a name as it is typed
+    pub fn complete(&self, prefix: &str, limit: usize) -> Vec<String> {
+        let prefix = prefix.trim().to_lowercase();
+        if prefix.is_empty() {
+            return Vec::new();
+        }
+        let mut names: Vec<&Entry> = self.entries.iter().filter(|entry| entry.name.starts_with(&prefix)).collect();
+        names.sort_by(|a, b| a.name.len().cmp(&b.name.len()).then(a.name.cmp(&b.name)));
+        names.dedup_by(|a, b| a.name == b.name);
+        names.into_iter().take(limit).map(|entry| entry.package.name.clone()).collect()
+    }
+}
+
+// How well one lowercased query word matches a package; None rules it out
+fn term_score(term: &str, name: &str, description: &str) -> Option<u32> {
+    if name == term {
+        return Some(1000);
+    }
+    if name.starts_with(term) {
+        return Some(800);
+    }
+    if let Some(position) = name.find(term) {
+        // Matches at a word boundary ("python3-requests" for "requests") beat ones mid-word
+        let at_boundary = name[..position].ends_with(['-', '.', '+']);
+        return Some(if at_boundary { 700 } else { 600 });
+    }
+    if let Some(score) = fuzzy_score(term, name) {
+        return Some(score);
+    }
+    description.contains(term).then_some(100)
+}
+
+/// Score for `query` as a subsequence of `name` (each character in order,
+/// gaps allowed), between 200 and 400: runs of consecutive characters and
+/// matches at word starts score higher. Single characters are too
+/// unselective to count.
+pub fn fuzzy_score(query: &str, name: &str) -> Option<u32> {
+    if query.chars().count() < 2 {
+        return None;
+    }
+    let mut score: u32 = 0;
+    let mut query_chars = query.chars().peekable();
+    let mut previous_matched = false;
+    let mut previous: Option<char> = None;
+
+    for c in name.chars() {
+        let Some(&wanted) = query_chars.peek() else {
+            break;
+        };
+        let word_start = previous.is_none_or(|p| matches!(p, '-' | '.' | '+' | '_'));
+        if c == wanted {
+            query_chars.next();
+            score += 10;
+            if previous_matched {
+                score += 15;
+            }
+            if word_start {
+                score += 20;
+            }
+            previous_matched = true;
+        } else {
+            previous_matched = false;
+        }
+        previous = Some(c);
+    }
+
+    if query_chars.peek().is_some() {
+        return None;
+    }
+    // Long names with the same characters scattered about rank lower
+    let spread = name.chars().count().saturating_sub(query.chars().count()) as u32;
+    Some((200 + score).saturating_sub(spread * 2).clamp(200, 400))
+}
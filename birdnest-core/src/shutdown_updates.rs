@@ -0,0 +1,164 @@
+//! Updates staged to install while the system shuts down.
+//!
+//! Staging downloads the updates, then (as root) records them in
+//! `/var/lib/birdnest/shutdown-updates.json` and enables a systemd unit
+//! whose `ExecStop` runs `birdnest apply-shutdown-updates`. The unit is
+//! started at boot and stopped at shutdown, before filesystems go away, so
+//! the install runs once the session has closed. Stopping the unit any other
+//! way installs nothing: [`apply`] checks that the system is shutting down.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use crate::package_manager::{BackendCommand, PackageBackend};
+use crate::utils::{run_command, CommandBuilder};
+
+pub const UNIT_NAME: &str = "birdnest-shutdown-updates.service";
+const UNIT_PATH: &str = "/etc/systemd/system/birdnest-shutdown-updates.service";
+const STATE_PATH: &str = "/var/lib/birdnest/shutdown-updates.json";
+
+/// Package names and Flatpak application IDs waiting for the next shutdown
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StagedUpdates {
+    pub packages: Vec<String>,
+    pub flatpaks: Vec<String>,
+}
+
+impl StagedUpdates {
+    pub fn len(&self) -> usize {
+        self.packages.len() + self.flatpaks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// The unit that runs `exe apply-shutdown-updates` on its way down
+pub fn unit_file(exe: &Path) -> String {
+    format!(
+        "[Unit]\n\
+         Description=Install updates staged by BirdNest before shutting down\n\
+         After=local-fs.target network.target\n\
+         \n\
+         [Service]\n\
+         Type=oneshot\n\
+         RemainAfterExit=yes\n\
+         ExecStart=/bin/true\n\
+         ExecStop={} apply-shutdown-updates\n\
+         TimeoutStopSec=1h\n\
+         \n\
+         [Install]\n\
+         WantedBy=multi-user.target\n",
+        exe.display()
+    )
+}
+
+/// What is staged; None when nothing is
+pub fn load() -> Option<StagedUpdates> {
+    let content = fs::read_to_string(STATE_PATH).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Whether `backend` can download updates ahead of the shutdown
+pub fn supported(backend: &dyn PackageBackend) -> bool {
+    !backend.download_commands(&[]).is_empty()
+}
+
+/// Commands that download `staged` and then hand it to `exe`'s
+/// `stage-shutdown-updates`, which needs root
+pub fn stage_commands(backend: &dyn PackageBackend, exe: &Path, staged: &StagedUpdates) -> Result<Vec<BackendCommand>> {
+    if !staged.packages.is_empty() && !supported(backend) {
+        anyhow::bail!("The {} backend can't download updates ahead of a shutdown", backend.name());
+    }
+    let mut commands = Vec::new();
+    if !staged.packages.is_empty() {
+        commands.extend(backend.download_commands(&staged.packages));
+    }
+    if !staged.flatpaks.is_empty() {
+        // flatpak asks polkit itself for the system installation
+        commands.push(BackendCommand::as_user("flatpak", &["update", "--system", "--no-deploy", "-y", "--noninteractive"], &staged.flatpaks));
+    }
+    let mut args = vec!["stage-shutdown-updates"];
+    for app in &staged.flatpaks {
+        args.extend(["--flatpak", app]);
+    }
+    commands.push(BackendCommand::new(&exe.display().to_string(), &args, &staged.packages));
+    Ok(commands)
+}
+
+/// The root command that unstages everything
+pub fn cancel_command(exe: &Path) -> BackendCommand {
+    BackendCommand::new(&exe.display().to_string(), &["stage-shutdown-updates", "--cancel"], &[])
+}
+
+/// Record `staged` and enable the unit; runs as root
+pub fn install(staged: &StagedUpdates, exe: &Path) -> Result<()> {
+    if let Some(parent) = Path::new(STATE_PATH).parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(STATE_PATH, serde_json::to_string_pretty(staged)?)?;
+    fs::write(UNIT_PATH, unit_file(exe))?;
+    run_command("systemctl", &["daemon-reload"], false)?;
+    // Started now too, so this shutdown already stops it
+    run_command("systemctl", &["enable", "--now", UNIT_NAME], false)?;
+    tracing::debug!("shutdown_updates: staged {} update(s)", staged.len());
+    Ok(())
+}
+
+/// Unstage and remove the unit; runs as root
+pub fn cancel() -> Result<()> {
+    // Gone first, so stopping the unit below finds nothing to install
+    if Path::new(STATE_PATH).exists() {
+        fs::remove_file(STATE_PATH)?;
+    }
+    if Path::new(UNIT_PATH).exists() {
+        run_command("systemctl", &["disable", "--now", UNIT_NAME], false)?;
+        fs::remove_file(UNIT_PATH)?;
+        run_command("systemctl", &["daemon-reload"], false)?;
+    }
+    Ok(())
+}
+
+/// `systemctl is-system-running` prints "stopping" once shutdown has begun
+pub fn is_shutting_down(state: &str) -> bool {
+    state.trim() == "stopping"
+}
+
+/// Install what is staged, from the unit's `ExecStop`; does nothing unless
+/// the system is shutting down. On failure everything stays staged, and the
+/// unit tries again at the next shutdown.
+pub fn apply(backend: &dyn PackageBackend) -> Result<()> {
+    // Exits non-zero whenever the system isn't "running", so only stdout counts
+    let state = Command::new("systemctl").arg("is-system-running").output()?;
+    if !is_shutting_down(&String::from_utf8_lossy(&state.stdout)) {
+        tracing::debug!("shutdown_updates: unit stopped outside a shutdown, leaving updates staged");
+        return Ok(());
+    }
+    let Some(staged) = load() else {
+        return Ok(());
+    };
+
+    if !staged.packages.is_empty() {
+        for command in backend.upgrade_commands(&staged.packages) {
+            let (mut std_cmd, _) = CommandBuilder::backend(&command).noninteractive().build()?;
+            let status = std_cmd.status()?;
+            if !status.success() {
+                anyhow::bail!("{} exited with code {}", command.display(), status.code().unwrap_or(-1));
+            }
+        }
+    }
+    if !staged.flatpaks.is_empty() {
+        let mut args = vec!["update", "--system", "--no-pull", "-y", "--noninteractive"];
+        args.extend(staged.flatpaks.iter().map(String::as_str));
+        run_command("flatpak", &args, false)?;
+    }
+
+    fs::remove_file(STATE_PATH)?;
+    // Only the enablement goes: stopping the unit from its own ExecStop would wait on itself
+    run_command("systemctl", &["disable", UNIT_NAME], false)?;
+    Ok(())
+}
@@ -0,0 +1,135 @@
+//! Snapshots of the apt sources, taken before BirdNest edits them.
+//!
+//! Every root command that changes the sources (`birdnest sources` and
+//! `birdnest ppa`, whichever elevation method ran it) first copies
+//! `sources.list` and the `.list` and `.sources` files in `sources.list.d`
+//! to a directory under [`BACKUP_DIR`] named for the time, unless nothing
+//! changed since the last snapshot. Restoring one puts those files back and
+//! removes sources files added since; it is an edit like any other, so the
+//! configuration it replaces is snapshotted first. Only the newest [`KEEP`]
+//! snapshots are kept. Keyrings aren't part of them, so a restored PPA may
+//! need adding again for its key.
+
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::repos::APT_DIR;
+use crate::utils::{now, utc_timestamp};
+
+pub const BACKUP_DIR: &str = "/var/lib/birdnest/sources-backups";
+
+/// Snapshots kept; older ones are deleted as new ones are taken
+pub const KEEP: usize = 20;
+
+/// One snapshot of the sources
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    /// The directory name, the Unix time it was taken
+    pub id: String,
+    pub created: i64,
+    /// The sources files in it, relative to the apt directory
+    pub files: Vec<PathBuf>,
+}
+
+impl Snapshot {
+    /// "2024-05-06T12:45:00Z, 4 files"
+    pub fn summary(&self) -> String {
+        let files = match self.files.len() {
+            1 => "1 file".to_string(),
+            count => format!("{} files", count),
+        };
+        format!("{}, {}", utc_timestamp(self.created), files)
+    }
+}
+
+fn is_sources_file(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "list" || ext == "sources")
+}
+
+// The sources files under `dir` (an apt directory or a snapshot) and their
+// contents, by path relative to it
+fn read_files(dir: &Path) -> Result<BTreeMap<PathBuf, Vec<u8>>> {
+    let mut files = BTreeMap::new();
+    let list = dir.join("sources.list");
+    if list.is_file() {
+        files.insert(PathBuf::from("sources.list"), fs::read(&list).with_context(|| format!("Failed to read {}", list.display()))?);
+    }
+    if let Ok(entries) = fs::read_dir(dir.join("sources.list.d")) {
+        for path in entries.flatten().map(|entry| entry.path()).filter(|path| path.is_file() && is_sources_file(path)) {
+            let contents = fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+            files.insert(Path::new("sources.list.d").join(path.file_name().unwrap_or_default()), contents);
+        }
+    }
+    Ok(files)
+}
+
+fn write_files(dir: &Path, files: &BTreeMap<PathBuf, Vec<u8>>) -> Result<()> {
+    fs::create_dir_all(dir.join("sources.list.d"))?;
+    for (file, contents) in files {
+        fs::write(dir.join(file), contents)?;
+    }
+    Ok(())
+}
+
+/// The snapshots in `backup_dir`, newest first
+pub fn list_in(backup_dir: &Path) -> Vec<Snapshot> {
+    let mut snapshots: Vec<Snapshot> = fs::read_dir(backup_dir)
+        .map(|entries| entries.flatten().map(|entry| entry.path()).collect::<Vec<_>>())
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|path| {
+            let id = path.file_name()?.to_str()?.to_string();
+            let created = id.parse().ok()?;
+            let files = read_files(&path).ok()?.into_keys().collect();
+            Some(Snapshot { id, created, files })
+        })
+        .collect();
+    snapshots.sort_by_key(|snapshot| std::cmp::Reverse(snapshot.created));
+    snapshots
+}
+
+/// The system's snapshots, newest first
+pub fn list() -> Vec<Snapshot> {
+    list_in(Path::new(BACKUP_DIR))
+}
+
+/// Copy the sources in `apt_dir` to a new snapshot taken at `now`, unless
+/// the newest one already holds the same files. Returns the new snapshot.
+pub fn snapshot_in(apt_dir: &Path, backup_dir: &Path, now: i64) -> Result<Option<Snapshot>> {
+    let files = read_files(apt_dir)?;
+    let snapshots = list_in(backup_dir);
+    if let Some(newest) = snapshots.first() {
+        if read_files(&backup_dir.join(&newest.id))? == files {
+            return Ok(None);
+        }
+    }
+    // Two edits in one second get the next free second
+    let created = (now..).find(|time| !backup_dir.join(time.to_string()).exists()).unwrap_or(now);
+    let dir = backup_dir.join(created.to_string());
+    write_files(&dir, &files).with_context(|| format!("Failed to back the sources up to {}", dir.display()))?;
+    for old in snapshots.iter().skip(KEEP - 1) {
+        let _ = fs::remove_dir_all(backup_dir.join(&old.id));
+    }
+    tracing::debug!("sources_backup: saved {}", dir.display());
+    Ok(Some(Snapshot { id: created.to_string(), created, files: files.into_keys().collect() }))
+}
+
+/// Snapshot the system's sources before an edit; runs as root
+pub fn snapshot() -> Result<Option<Snapshot>> {
+    snapshot_in(Path::new(APT_DIR), Path::new(BACKUP_DIR), now() as i64)
+}
+
+/// Make the sources in `apt_dir` what they were in snapshot `id`
+pub fn restore_in(apt_dir: &Path, backup_dir: &Path, id: &str) -> Result<()> {
+    let dir = backup_dir.join(id);
+    if id.parse::<i64>().is_err() || !dir.is_dir() {
+        anyhow::bail!("There is no sources backup {}", id);
+    }
+    let files = read_files(&dir)?;
+    for file in read_files(apt_dir)?.into_keys().filter(|file| !files.contains_key(file)) {
+        fs::remove_file(apt_dir.join(&file)).with_context(|| format!("Failed to remove {}", apt_dir.join(&file).display()))?;
+    }
+    write_files(apt_dir, &files)
+}
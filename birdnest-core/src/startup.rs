@@ -0,0 +1,350 @@
+//! What starts at login: XDG autostart entries and systemd user services,
+//! each with the installed package it came from and what that package's
+//! processes use right now.
+//!
+//! An entry in `~/.config/autostart` overrides the one of the same name in
+//! `/etc/xdg/autostart`; desktops turn a packaged entry off by copying it
+//! there with `Hidden=true`, and so does [`set_enabled`]. Services are
+//! turned off with `systemctl --user disable`, or masked when they are
+//! enabled for every user. Either takes effect from the next login.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+use crate::footprint::{self, Footprint, Programs};
+use crate::package_manager::PackageBackend;
+use crate::utils::{find_in_path, run_command};
+
+/// Autostart entries packages install
+pub const SYSTEM_AUTOSTART: &str = "/etc/xdg/autostart";
+/// Where systemd finds user units after `~/.config/systemd/user`, in its order
+pub const USER_UNIT_DIRS: [&str; 4] = ["/etc/systemd/user", "/usr/local/lib/systemd/user", "/usr/lib/systemd/user", "/lib/systemd/user"];
+// Targets a user service starts at login with when it is enabled
+const LOGIN_TARGETS: [&str; 3] = ["default.target", "graphical-session.target", "xdg-desktop-autostart.target"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Autostart,
+    Service,
+}
+
+impl Kind {
+    pub fn label(self) -> &'static str {
+        match self {
+            Kind::Autostart => "Autostart",
+            Kind::Service => "User service",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StartupItem {
+    pub kind: Kind,
+    /// File name of the entry or unit: "nm-applet.desktop", "pipewire.service"
+    pub id: String,
+    /// The entry's Name or the unit's Description, else the id
+    pub name: String,
+    /// The file in effect, the user's own copy when there is one
+    pub path: PathBuf,
+    /// The program it runs
+    pub program: Option<String>,
+    pub enabled: bool,
+    /// The installed package it came from; None for entries apps or the user
+    /// wrote, when the program isn't packaged either
+    pub package: Option<String>,
+    /// What the package's processes use now
+    pub footprint: Footprint,
+}
+
+/// The keys of a desktop entry or unit file this module reads
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Entry {
+    pub name: Option<String>,
+    pub exec: Option<String>,
+    /// `Hidden=true` or `X-GNOME-Autostart-enabled=false` turn an entry off
+    pub hidden: bool,
+    pub only_show_in: Vec<String>,
+    pub not_show_in: Vec<String>,
+    /// The unit's WantedBy targets
+    pub wanted_by: Vec<String>,
+}
+
+impl Entry {
+    /// Whether it starts on a desktop that calls itself any of `desktops`
+    /// (`XDG_CURRENT_DESKTOP`, split at ':')
+    pub fn shown_in(&self, desktops: &[String]) -> bool {
+        let listed = |list: &[String]| list.iter().any(|desktop| desktops.contains(desktop));
+        (self.only_show_in.is_empty() || listed(&self.only_show_in)) && !listed(&self.not_show_in)
+    }
+}
+
+// key=value pairs of one [group], without translated keys such as Name[de]
+fn group<'a>(contents: &'a str, name: &str) -> Vec<(&'a str, &'a str)> {
+    let mut current = None;
+    let mut pairs = Vec::new();
+    for line in contents.lines().map(str::trim) {
+        if let Some(header) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+            current = Some(header);
+        } else if current == Some(name) && !line.starts_with('#') {
+            if let Some((key, value)) = line.split_once('=') {
+                pairs.push((key.trim(), value.trim()));
+            }
+        }
+    }
+    pairs
+}
+
+fn list(value: &str) -> Vec<String> {
+    value.split([';', ' ']).filter(|item| !item.is_empty()).map(str::to_string).collect()
+}
+
+/// The `[Desktop Entry]` group of an autostart file
+pub fn parse_desktop_entry(contents: &str) -> Entry {
+    let mut entry = Entry::default();
+    for (key, value) in group(contents, "Desktop Entry") {
+        match key {
+            "Name" => entry.name = Some(value.to_string()),
+            "Exec" => entry.exec = Some(value.to_string()),
+            "Hidden" => entry.hidden |= value == "true",
+            "X-GNOME-Autostart-enabled" => entry.hidden |= value == "false",
+            "OnlyShowIn" => entry.only_show_in = list(value),
+            "NotShowIn" => entry.not_show_in = list(value),
+            _ => {}
+        }
+    }
+    entry
+}
+
+/// A unit's Description, ExecStart and WantedBy
+pub fn parse_unit(contents: &str) -> Entry {
+    let mut entry = Entry::default();
+    for (key, value) in group(contents, "Unit") {
+        if key == "Description" {
+            entry.name = Some(value.to_string());
+        }
+    }
+    for (key, value) in group(contents, "Service") {
+        if key == "ExecStart" && entry.exec.is_none() {
+            entry.exec = Some(value.to_string());
+        }
+    }
+    for (key, value) in group(contents, "Install") {
+        if key == "WantedBy" {
+            entry.wanted_by.extend(list(value));
+        }
+    }
+    entry
+}
+
+/// The program an Exec or ExecStart line runs, past `env` and its variables
+/// and systemd's `-`, `@` and `+` prefixes
+pub fn program(exec: &str) -> Option<String> {
+    exec.split_whitespace()
+        .map(|word| word.trim_start_matches(['-', '@', '+', '!', ':']))
+        .find(|word| !word.is_empty() && *word != "env" && !word.contains('='))
+        .map(str::to_string)
+}
+
+/// Autostart entries in `system_dirs`, overridden by those of the same name
+/// in `user_dir`, leaving out those for other desktops
+pub fn autostart_in(user_dir: &Path, system_dirs: &[PathBuf], desktops: &[String]) -> Vec<StartupItem> {
+    let mut files: BTreeMap<String, PathBuf> = BTreeMap::new();
+    for dir in system_dirs.iter().map(PathBuf::as_path).chain([user_dir]) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let id = entry.file_name().to_string_lossy().into_owned();
+            if id.ends_with(".desktop") {
+                files.insert(id, entry.path());
+            }
+        }
+    }
+    files
+        .into_iter()
+        .filter_map(|(id, path)| {
+            let entry = parse_desktop_entry(&std::fs::read_to_string(&path).ok()?);
+            if !entry.shown_in(desktops) {
+                return None;
+            }
+            Some(StartupItem {
+                kind: Kind::Autostart,
+                name: entry.name.unwrap_or_else(|| id.trim_end_matches(".desktop").to_string()),
+                id,
+                path,
+                program: entry.exec.as_deref().and_then(program),
+                enabled: !entry.hidden,
+                package: None,
+                footprint: Footprint::default(),
+            })
+        })
+        .collect()
+}
+
+/// Units and their state from `systemctl --user list-unit-files
+/// --type=service --no-legend`
+pub fn parse_unit_files(output: &str) -> Vec<(String, String)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            Some((fields.next()?.to_string(), fields.next()?.to_string()))
+        })
+        .collect()
+}
+
+// Not a mask, which is a link to /dev/null
+fn is_unit_file(path: &Path) -> bool {
+    std::fs::canonicalize(path).is_ok_and(|real| real != Path::new("/dev/null"))
+}
+
+/// The unit's file in the first of `dirs` that has it, past any mask
+pub fn unit_path(unit: &str, dirs: &[PathBuf]) -> Option<PathBuf> {
+    dirs.iter().map(|dir| dir.join(unit)).find(|path| is_unit_file(path))
+}
+
+/// User services that start at login, and those turned off that would
+/// if they were on, from `systemctl --user` states and the unit files in `dirs`
+pub fn services_in(units: &[(String, String)], dirs: &[PathBuf]) -> Vec<StartupItem> {
+    units
+        .iter()
+        .filter_map(|(unit, state)| {
+            let path = unit_path(unit, dirs)?;
+            let entry = parse_unit(&std::fs::read_to_string(&path).ok()?);
+            let starts_at_login = entry.wanted_by.iter().any(|target| LOGIN_TARGETS.contains(&target.as_str()));
+            let enabled = match state.as_str() {
+                "enabled" => true,
+                "disabled" | "masked" if starts_at_login => false,
+                _ => return None,
+            };
+            Some(StartupItem {
+                kind: Kind::Service,
+                id: unit.clone(),
+                name: entry.name.unwrap_or_else(|| unit.clone()),
+                path,
+                program: entry.exec.as_deref().and_then(program),
+                enabled,
+                package: None,
+                footprint: Footprint::default(),
+            })
+        })
+        .collect()
+}
+
+/// `contents` of an autostart entry, turned on or off
+pub fn autostart_override(contents: &str, enabled: bool) -> String {
+    let mut out = String::with_capacity(contents.len() + 64);
+    let mut in_entry = false;
+    for line in contents.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            in_entry = trimmed == "[Desktop Entry]";
+            out.push_str(line);
+            out.push('\n');
+            if in_entry {
+                out.push_str(&format!("Hidden={}\nX-GNOME-Autostart-enabled={}\n", !enabled, enabled));
+            }
+            continue;
+        }
+        let key = trimmed.split_once('=').map(|(key, _)| key.trim());
+        if in_entry && matches!(key, Some("Hidden" | "X-GNOME-Autostart-enabled")) {
+            continue;
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    out
+}
+
+fn home() -> PathBuf {
+    PathBuf::from(std::env::var("HOME").unwrap_or_default())
+}
+
+fn user_autostart() -> PathBuf {
+    home().join(".config/autostart")
+}
+
+fn user_unit_dirs() -> Vec<PathBuf> {
+    std::iter::once(home().join(".config/systemd/user")).chain(USER_UNIT_DIRS.map(PathBuf::from)).collect()
+}
+
+// The package owning the first of `paths` one does; merged-/usr systems
+// find files where dpkg recorded them without the /usr
+fn owner(backend: &dyn PackageBackend, paths: &[PathBuf]) -> Option<String> {
+    paths.iter().find_map(|path| {
+        let path = path.to_str()?;
+        let unmerged = path.strip_prefix("/usr").filter(|rest| rest.starts_with("/lib/") || rest.starts_with("/bin/"));
+        std::iter::once(path).chain(unmerged).find_map(|path| backend.owner(path).ok().flatten())
+    })
+}
+
+/// Everything that starts at login on this desktop, with packages and
+/// footprints; samples CPU for [`footprint::SAMPLE`], so it belongs in a
+/// background task
+pub fn report(backend: &dyn PackageBackend) -> Vec<StartupItem> {
+    let desktops: Vec<String> = std::env::var("XDG_CURRENT_DESKTOP").unwrap_or_default().split(':').map(str::to_string).collect();
+    let mut items = autostart_in(&user_autostart(), &[PathBuf::from(SYSTEM_AUTOSTART)], &desktops);
+    match run_command("systemctl", &["--user", "list-unit-files", "--type=service", "--no-legend", "--no-pager"], false) {
+        Ok(output) => items.extend(services_in(&parse_unit_files(&output), &user_unit_dirs())),
+        Err(e) => tracing::debug!("startup: no user services: {}", e),
+    }
+
+    let home = home();
+    for item in &mut items {
+        // The user's own files belong to no package, but the program may
+        let mut paths = Vec::new();
+        if !item.path.starts_with(&home) {
+            paths.push(item.path.clone());
+        }
+        if item.kind == Kind::Autostart {
+            paths.push(Path::new(SYSTEM_AUTOSTART).join(&item.id));
+        }
+        if let Some(program) = &item.program {
+            paths.extend(if program.starts_with('/') { Some(PathBuf::from(program)) } else { find_in_path(program) });
+        }
+        item.package = owner(backend, &paths);
+    }
+
+    let programs: Vec<Programs> = items
+        .iter()
+        .map(|item| {
+            let files = item.package.as_ref().and_then(|package| backend.files(package).ok()).unwrap_or_default();
+            Programs::from_files(&files)
+        })
+        .collect();
+    for (item, footprint) in items.iter_mut().zip(footprint::measure_each(&programs)) {
+        item.footprint = footprint;
+    }
+    // The heaviest first
+    items.sort_by(|a, b| b.footprint.memory.cmp(&a.footprint.memory).then_with(|| a.name.cmp(&b.name)));
+    tracing::debug!("startup: {} items start at login", items.len());
+    items
+}
+
+/// Turn an item on or off from the next login
+pub fn set_enabled(item: &StartupItem, enabled: bool) -> Result<()> {
+    match item.kind {
+        Kind::Autostart => {
+            let contents = std::fs::read_to_string(&item.path)?;
+            let dir = user_autostart();
+            std::fs::create_dir_all(&dir)?;
+            std::fs::write(dir.join(&item.id), autostart_override(&contents, enabled))?;
+        }
+        Kind::Service if enabled => {
+            // Unmasking a unit that isn't masked does nothing
+            run_command("systemctl", &["--user", "unmask", &item.id], false)?;
+            run_command("systemctl", &["--user", "enable", &item.id], false)?;
+        }
+        Kind::Service => {
+            run_command("systemctl", &["--user", "disable", &item.id], false)?;
+            // Units enabled for every user stay enabled until masked;
+            // is-enabled fails once the unit is off
+            if run_command("systemctl", &["--user", "is-enabled", &item.id], false).is_ok_and(|state| state.trim() == "enabled") {
+                run_command("systemctl", &["--user", "mask", &item.id], false)?;
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,77 @@
+//! Opening commands in the user's terminal emulator.
+//!
+//! The GUI has no terminal of its own, so interactive commands such as
+//! `pikman enter` run in a terminal window it starts beside itself. The
+//! terminal is the config's `terminal` when set, then `$TERMINAL`, then the
+//! first of [`KNOWN`] that is installed. Each one takes the command to run
+//! with its own flag, which [`launch_args`] knows; anything else gets `-e`.
+
+use anyhow::{anyhow, Result};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::utils;
+
+/// Terminals looked for, in order, when none is configured
+pub const KNOWN: &[&str] = &[
+    "x-terminal-emulator",
+    "ptyxis",
+    "gnome-terminal",
+    "konsole",
+    "xfce4-terminal",
+    "kitty",
+    "alacritty",
+    "foot",
+    "wezterm",
+    "xterm",
+];
+
+/// The terminal to use: `preferred` (from the config), `$TERMINAL` or the
+/// first of [`KNOWN`] found on PATH
+pub fn find(preferred: Option<&str>) -> Option<String> {
+    let env = std::env::var("TERMINAL").ok();
+    preferred
+        .into_iter()
+        .chain(env.as_deref())
+        .map(str::trim)
+        .find(|terminal| !terminal.is_empty())
+        .map(str::to_string)
+        .or_else(|| KNOWN.iter().find(|terminal| utils::find_in_path(terminal).is_some()).map(|terminal| terminal.to_string()))
+}
+
+/// Arguments that make `terminal` run `command` in its window
+pub fn launch_args(terminal: &str, command: &[String]) -> Vec<String> {
+    let name = Path::new(terminal).file_name().and_then(|name| name.to_str()).unwrap_or(terminal);
+    let flag: &[&str] = match name {
+        "gnome-terminal" | "ptyxis" => &["--"],
+        "xfce4-terminal" => &["-x"],
+        "wezterm" => &["start", "--"],
+        "kitty" | "foot" => &[],
+        _ => &["-e"],
+    };
+    flag.iter().map(|flag| flag.to_string()).chain(command.iter().cloned()).collect()
+}
+
+/// `command`, followed by its exit status and a prompt to press Enter, so
+/// the window stays open to read its output
+pub fn held(command: &[String]) -> Vec<String> {
+    let script = r#""$@"; status=$?; echo; printf 'Finished with exit status %s. Press Enter to close.' "$status"; read -r _"#;
+    ["sh", "-c", script, "sh"].iter().map(|arg| arg.to_string()).chain(command.iter().cloned()).collect()
+}
+
+/// Start `command` in a new terminal window without waiting for it,
+/// returning the terminal used
+pub fn open(preferred: Option<&str>, command: &[String]) -> Result<String> {
+    let terminal = find(preferred).ok_or_else(|| {
+        anyhow!("No terminal emulator found. Install one, or set `terminal` in ~/.config/birdnest/config.toml")
+    })?;
+    tracing::debug!("terminal: {} {:?}", terminal, command);
+    Command::new(&terminal)
+        .args(launch_args(&terminal, command))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| anyhow!("Couldn't start {}: {}", terminal, e))?;
+    Ok(terminal)
+}
@@ -0,0 +1,146 @@
+//! Trimming preinstalled software: a curated catalog of the optional apps a
+//! PikaOS install comes with, grouped by category, each with what taking
+//! it out means.
+//!
+//! The desktop meta-packages depend on some of these. apt would take the
+//! meta-package out with them, and with it the new defaults and desktop
+//! updates it brings, so an app is only offered while removing it leaves
+//! every meta-package installed. Picks are checked again together before
+//! they go, since a meta-package can depend on "one of a or b".
+
+use std::collections::HashSet;
+
+use anyhow::Result;
+
+use crate::dpkg::{self, InstalledPackage};
+use crate::package_manager::PackageBackend;
+use crate::plan::{self, TransactionPlan};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Category {
+    Internet,
+    Office,
+    Media,
+    Games,
+    Utilities,
+}
+
+impl Category {
+    pub const ALL: [Category; 5] = [Category::Internet, Category::Office, Category::Media, Category::Games, Category::Utilities];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Category::Internet => "Internet",
+            Category::Office => "Office",
+            Category::Media => "Photos, Music and Video",
+            Category::Games => "Games",
+            Category::Utilities => "Utilities",
+        }
+    }
+}
+
+/// An optional app PikaOS installs by default
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Candidate {
+    pub package: &'static str,
+    pub name: &'static str,
+    pub category: Category,
+    /// What goes with it, and what stays
+    pub consequence: &'static str,
+}
+
+const fn candidate(package: &'static str, name: &'static str, category: Category, consequence: &'static str) -> Candidate {
+    Candidate { package, name, category, consequence }
+}
+
+/// The optional default apps, in the order they are offered
+pub const CATALOG: &[Candidate] = &[
+    candidate("thunderbird", "Thunderbird", Category::Internet, "Mail, calendars and contacts stay in ~/.thunderbird for a reinstall; mailto: links stop opening."),
+    candidate("transmission-gtk", "Transmission", Category::Internet, "Torrent downloads stop and magnet links no longer open; downloaded files stay."),
+    candidate("remmina", "Remmina", Category::Internet, "Saved remote desktop connections stay in ~/.local/share/remmina."),
+    candidate("libreoffice-writer", "LibreOffice Writer", Category::Office, "Documents stay, but .odt and .docx files open in another app or not at all."),
+    candidate("libreoffice-calc", "LibreOffice Calc", Category::Office, "Spreadsheets stay, but .ods and .xlsx files open in another app or not at all."),
+    candidate("libreoffice-impress", "LibreOffice Impress", Category::Office, "Presentations stay, but .odp and .pptx files open in another app or not at all."),
+    candidate("libreoffice-draw", "LibreOffice Draw", Category::Office, "Drawings stay; PDF editing in LibreOffice goes with it."),
+    candidate("simple-scan", "Document Scanner", Category::Office, "Scanning from the desktop stops; scanner drivers stay installed."),
+    candidate("rhythmbox", "Rhythmbox", Category::Media, "Your music files stay; playlists and ratings are kept in ~/.local/share/rhythmbox."),
+    candidate("elisa", "Elisa", Category::Media, "Your music files stay; playlists are kept for a reinstall."),
+    candidate("totem", "Videos", Category::Media, "Video files open in another player, or not at all until you install one."),
+    candidate("shotwell", "Shotwell", Category::Media, "Photos stay where they are; Shotwell's tags and albums are kept in ~/.local/share/shotwell."),
+    candidate("cheese", "Cheese", Category::Media, "Photos and videos taken with it stay in ~/Pictures and ~/Videos."),
+    candidate("gnome-mahjongg", "Mahjongg", Category::Games, "High scores are lost."),
+    candidate("gnome-mines", "Mines", Category::Games, "High scores are lost."),
+    candidate("gnome-sudoku", "Sudoku", Category::Games, "Saved puzzles and high scores are lost."),
+    candidate("aisleriot", "AisleRiot Solitaire", Category::Games, "Game statistics are lost."),
+    candidate("kpat", "KPatience", Category::Games, "Game statistics and saved games are lost."),
+    candidate("gnome-weather", "Weather", Category::Utilities, "The weather in the top bar's calendar goes too."),
+    candidate("gnome-maps", "Maps", Category::Utilities, "Addresses in contacts and calendars no longer open on a map."),
+    candidate("gnome-contacts", "Contacts", Category::Utilities, "Contacts stay in your online accounts and Evolution Data Server; only the app goes."),
+    candidate("gnome-tour", "Tour", Category::Utilities, "Only the welcome tour goes; nothing else uses it."),
+    candidate("yelp", "Help", Category::Utilities, "Help buttons in apps stop opening their manuals."),
+];
+
+/// An installed catalog app, as offered for removal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Offer {
+    pub candidate: Candidate,
+    pub installed_bytes: Option<u64>,
+    /// Meta-packages removing it would take too; it can't be picked while
+    /// there are any
+    pub protected_by: Vec<String>,
+}
+
+impl Offer {
+    pub fn is_protected(&self) -> bool {
+        !self.protected_by.is_empty()
+    }
+}
+
+/// The catalog apps in `installed`, in catalog order
+pub fn installed_offers(installed: &[InstalledPackage]) -> Vec<Offer> {
+    CATALOG
+        .iter()
+        .filter_map(|candidate| {
+            let package = installed.iter().find(|p| p.name == candidate.package)?;
+            Some(Offer { candidate: *candidate, installed_bytes: package.installed_bytes, protected_by: Vec::new() })
+        })
+        .collect()
+}
+
+/// Meta-packages among a removal plan's packages
+pub fn meta_removals(plan: &TransactionPlan, meta: &HashSet<String>) -> Vec<String> {
+    plan.remove.iter().filter(|p| meta.contains(&p.name)).map(|p| p.name.clone()).collect()
+}
+
+/// Mark the offers whose removal takes a meta-package with it. `simulate`
+/// plans a removal; all offers are tried at once first, and one by one
+/// only when that takes a meta-package.
+pub fn protect(offers: &mut [Offer], meta: &HashSet<String>, simulate: impl Fn(&[String]) -> Result<TransactionPlan>) -> Result<()> {
+    let all: Vec<String> = offers.iter().map(|offer| offer.candidate.package.to_string()).collect();
+    if all.is_empty() || meta_removals(&simulate(&all)?, meta).is_empty() {
+        return Ok(());
+    }
+    for offer in offers.iter_mut() {
+        offer.protected_by = meta_removals(&simulate(&[offer.candidate.package.to_string()])?, meta);
+    }
+    Ok(())
+}
+
+/// The installed catalog apps, each marked with the meta-packages that
+/// protect it. Only apt has meta-packages that go with their dependencies;
+/// dnf and pacman groups aren't installed packages.
+pub fn offers(backend: &dyn PackageBackend) -> Result<Vec<Offer>> {
+    let mut offers = installed_offers(&backend.list_installed()?);
+    if backend.name() == "apt" {
+        protect(&mut offers, &dpkg::meta_packages()?, |packages| plan::plan_remove(packages, false))?;
+    }
+    Ok(offers)
+}
+
+/// The meta-packages removing `packages` together would take with them
+pub fn check_picks(backend: &dyn PackageBackend, packages: &[String]) -> Result<Vec<String>> {
+    if backend.name() != "apt" || packages.is_empty() {
+        return Ok(Vec::new());
+    }
+    Ok(meta_removals(&plan::plan_remove(packages, false)?, &dpkg::meta_packages()?))
+}
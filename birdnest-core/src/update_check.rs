@@ -0,0 +1,82 @@
+//! Periodic checks for available updates, for the GUI's badge and desktop
+//! notifications.
+//!
+//! System packages are counted from the package lists as they are, which the
+//! distribution's own timer (apt-daily on PikaOS) keeps fresh, so a check
+//! needs no root. Flatpak asks its remotes. Checks follow the config's
+//! [`RefreshSchedule`](crate::schedule::RefreshSchedule) like other
+//! background work.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::flatpak::FlatpakManager;
+use crate::package_manager::PackageBackend;
+
+/// The `update_check` section of the config
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UpdateCheck {
+    /// How often the GUI checks; 0 turns the checks off
+    #[serde(default = "default_every_hours")]
+    pub every_hours: u32,
+    /// Notify when the count goes up, on the channels in the config's
+    /// [`notifications`](crate::notify::NotificationConfig) section
+    #[serde(default)]
+    pub notify: bool,
+}
+
+fn default_every_hours() -> u32 {
+    6
+}
+
+impl Default for UpdateCheck {
+    fn default() -> Self {
+        Self { every_hours: default_every_hours(), notify: false }
+    }
+}
+
+/// Updates available when last checked
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UpdateCount {
+    pub packages: usize,
+    pub flatpaks: usize,
+}
+
+impl UpdateCount {
+    pub fn total(&self) -> usize {
+        self.packages + self.flatpaks
+    }
+
+    /// "3 updates available (2 system, 1 Flatpak)"
+    pub fn summary(&self) -> String {
+        match self.total() {
+            0 => "Everything is up to date".to_string(),
+            1 => format!("1 update available ({})", self.split()),
+            total => format!("{} updates available ({})", total, self.split()),
+        }
+    }
+
+    fn split(&self) -> String {
+        format!("{} system, {} Flatpak", self.packages, self.flatpaks)
+    }
+}
+
+/// Count what `backend` and Flatpak have to upgrade; a Flatpak failure (no
+/// network, not installed) counts as none rather than failing the check
+pub fn check(backend: &dyn PackageBackend) -> Result<UpdateCount> {
+    let packages = backend.list_upgradable()?.len();
+    let flatpaks = match FlatpakManager::new().and_then(|fm| fm.list_updates()) {
+        Ok(updates) => updates.len(),
+        Err(e) => {
+            tracing::warn!("update_check: Flatpak updates unavailable: {}", e);
+            0
+        }
+    };
+    Ok(UpdateCount { packages, flatpaks })
+}
+
+/// Notify only about news: when there are more updates than last time the
+/// user was told, not on every check
+pub fn should_notify(notified: usize, count: &UpdateCount) -> bool {
+    count.total() > notified
+}
@@ -0,0 +1,267 @@
+//! Command execution and terminal output helpers.
+
+use anyhow::Result;
+use colored::*;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::elevation::{self, ElevationMethod};
+use crate::helper;
+use crate::package_manager::BackendCommand;
+use crate::proxy;
+
+// The config's auto_confirm, set at startup
+static AUTO_CONFIRM: AtomicBool = AtomicBool::new(false);
+
+/// Answer every [`confirm`] with yes, as `--yes` does for one command
+pub fn set_auto_confirm(auto: bool) {
+    AUTO_CONFIRM.store(auto, Ordering::Relaxed);
+}
+
+pub fn confirm(prompt: &str) -> Result<bool> {
+    use std::io::{self, Write};
+    
+    if AUTO_CONFIRM.load(Ordering::Relaxed) {
+        return Ok(true);
+    }
+    print!("{} [y/N]: ", prompt.yellow().bold());
+    io::stdout().flush()?;
+    
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    
+    Ok(input.trim().to_lowercase() == "y" || input.trim().to_lowercase() == "yes")
+}
+
+pub fn is_root() -> bool {
+    std::env::var("USER").unwrap_or_default() == "root" || 
+    unsafe { libc::geteuid() == 0 }
+}
+
+/// Where `program` would be run from, if it is on PATH at all
+pub fn find_in_path(program: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .map(|dir| dir.join(program))
+        .find(|candidate| is_executable(candidate))
+}
+
+pub fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path)
+        .map(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+pub fn run_command(cmd: &str, args: &[&str], sudo: bool) -> Result<String> {
+    // Callers parse the output, so keep it in English
+    let (mut command, method) = CommandBuilder::new(cmd).args(args).elevated(sudo).c_locale().build()?;
+    if let Some(method) = method {
+        print_info(&format!("Elevated privileges required, using {}...", method.command_prefix()));
+    }
+    let output = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Command failed: {}", stderr);
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+// Build a command, elevated when `sudo` is set: directly as root, otherwise
+// with the user's elevation method (pkexec, sudo, sudo -A or the helper)
+pub fn elevated_command(cmd: &str, args: &[&str], sudo: bool) -> Result<Command> {
+    let (command, method) = CommandBuilder::new(cmd).args(args).elevated(sudo).build()?;
+    if let Some(method) = method {
+        print_info(&format!("Elevated privileges required, using {}...", method.command_prefix()));
+    }
+    Ok(command)
+}
+
+// What a polkit agent or askpass dialog needs to show up in the user's
+// session; passed to the elevating process, not to the program it runs
+const SESSION_VARS: [&str; 3] = ["DISPLAY", "XAUTHORITY", "WAYLAND_DISPLAY"];
+
+/// Builds every command BirdNest spawns, so each backend gets the same
+/// environment whether it runs as the user or through pkexec, sudo or the helper
+#[derive(Debug, Clone)]
+pub struct CommandBuilder {
+    program: String,
+    args: Vec<String>,
+    elevated: bool,
+    env: Vec<(&'static str, &'static str)>,
+}
+
+impl CommandBuilder {
+    pub fn new(program: &str) -> Self {
+        Self { program: program.to_string(), args: Vec::new(), elevated: false, env: Vec::new() }
+    }
+
+    /// A backend command, elevated unless it is marked to run as the user
+    pub fn backend(command: &BackendCommand) -> Self {
+        Self::new(&command.program).args(&command.args()).elevated(!command.as_user)
+    }
+
+    pub fn args(mut self, args: &[&str]) -> Self {
+        self.args.extend(args.iter().map(|a| a.to_string()));
+        self
+    }
+
+    /// Run as root when set and not root already
+    pub fn elevated(mut self, elevated: bool) -> Self {
+        self.elevated = elevated;
+        self
+    }
+
+    /// Untranslated output, for commands whose output gets parsed
+    pub fn c_locale(self) -> Self {
+        self.env("LC_ALL", "C.UTF-8")
+    }
+
+    /// No debconf prompts, for commands the user already confirmed and that
+    /// have no terminal to answer on
+    pub fn noninteractive(self) -> Self {
+        self.env("DEBIAN_FRONTEND", "noninteractive")
+    }
+
+    fn env(mut self, key: &'static str, value: &'static str) -> Self {
+        self.env.retain(|(k, _)| *k != key);
+        self.env.push((key, value));
+        self
+    }
+
+    /// The variables set for the program itself
+    pub fn env_vars(&self) -> &[(&'static str, &'static str)] {
+        &self.env
+    }
+
+    // The arguments, after the options that make the program use the
+    // configured proxy
+    fn program_args(&self) -> Vec<String> {
+        let mut args = proxy::options(&self.program);
+        args.extend(self.args.iter().cloned());
+        args
+    }
+
+    /// The command and, when it elevates, the method used. PATH is inherited
+    /// by whatever process starts first, which is the one that resolves the program
+    pub fn build(&self) -> Result<(Command, Option<ElevationMethod>)> {
+        if !self.elevated || is_root() {
+            let mut command = Command::new(&self.program);
+            command.args(self.program_args()).envs(self.env.iter().copied()).envs(proxy::env());
+            return Ok((command, None));
+        }
+
+        let method = elevation::detect().resolve(elevation::preference())?;
+        Ok((self.build_with(method)?, Some(method)))
+    }
+
+    /// Elevated with a specific, already resolved method
+    pub fn build_with(&self, method: ElevationMethod) -> Result<Command> {
+        let program_args = self.program_args();
+        let args: Vec<&str> = program_args.iter().map(|a| a.as_str()).collect();
+        let proxy = proxy::env();
+        // Only the helper's client keeps the environment; what the daemon
+        // doesn't run falls back to pkexec like the rest
+        let resets_env = method != ElevationMethod::Helper || !helper::runs(&self.program, &args);
+        let mut command = match method {
+            // pkexec and sudo reset the environment, so it goes through env(1)
+            _ if resets_env && (!self.env.is_empty() || !proxy.is_empty()) => {
+                let assignments: Vec<String> = self
+                    .env
+                    .iter()
+                    .map(|(k, v)| format!("{}={}", k, v))
+                    .chain(proxy.iter().map(|(k, v)| format!("{}={}", k, v)))
+                    .collect();
+                let env_args: Vec<&str> = assignments
+                    .iter()
+                    .map(|a| a.as_str())
+                    .chain(std::iter::once(self.program.as_str()))
+                    .chain(args.iter().copied())
+                    .collect();
+                elevation::command_with(method, "env", &env_args)?
+            }
+            // The helper client hands its own variables on to the daemon
+            _ => {
+                let mut command = elevation::command_with(method, &self.program, &args)?;
+                command.envs(self.env.iter().copied()).envs(proxy);
+                command
+            }
+        };
+        for var in SESSION_VARS {
+            if let Some(value) = std::env::var_os(var) {
+                command.env(var, value);
+            }
+        }
+        Ok(command)
+    }
+}
+
+pub fn run_command_interactive(cmd: &str, args: &[&str], sudo: bool) -> Result<()> {
+    let status = elevated_command(cmd, args, sudo)?.status()?;
+
+    if !status.success() {
+        // Check if it's a password cancellation
+        if status.code() == Some(1) {
+            anyhow::bail!("Authentication failed or cancelled. Please try again.");
+        }
+        anyhow::bail!("Command failed with exit code: {:?}", status.code());
+    }
+
+    Ok(())
+}
+
+/// Seconds since the Unix epoch
+pub fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or(Duration::ZERO).as_secs()
+}
+
+/// YYYY-MM-DD of a Unix timestamp, in UTC
+pub fn utc_date(timestamp: i64) -> String {
+    let (year, month, day) = civil_date(timestamp.div_euclid(86_400));
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// A Unix timestamp in RFC 3339 form, like 2024-05-06T13:45:00Z
+pub fn utc_timestamp(timestamp: i64) -> String {
+    let seconds = timestamp.rem_euclid(86_400);
+    format!("{}T{:02}:{:02}:{:02}Z", utc_date(timestamp), seconds / 3600, seconds / 60 % 60, seconds % 60)
+}
+
+// Days since 1970-01-01 to a date, after Howard Hinnant's civil_from_days
+fn civil_date(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    (year_of_era + era * 400 + i64::from(month <= 2), month, day)
+}
+
+pub fn print_success(message: &str) {
+    println!("{} {}", "✓".green(), message);
+}
+
+#[allow(dead_code)]
+pub fn print_error(message: &str) {
+    eprintln!("{} {}", "✗".red(), message);
+}
+
+pub fn print_info(message: &str) {
+    println!("{} {}", "ℹ".blue(), message);
+}
+
+#[allow(dead_code)]
+pub fn print_warning(message: &str) {
+    println!("{} {}", "⚠".yellow(), message);
+}
+
@@ -0,0 +1,144 @@
+use birdnest_core::appimage::{self, UpdateInfo, UpdateStatus, ZsyncHeader};
+use std::io::Cursor;
+use std::path::Path;
+
+// An ELF64 file with the AppImage type 2 magic and a .upd_info section
+// holding `update_info`, laid out the way the runtime's linker leaves it
+fn runtime(update_info: &str) -> Vec<u8> {
+    let names = b"\0.shstrtab\0.upd_info\0";
+    let mut info = update_info.as_bytes().to_vec();
+    info.resize(64, 0);
+
+    let mut file = vec![0u8; 64];
+    file[..4].copy_from_slice(b"\x7fELF");
+    file[4] = 2; // 64-bit
+    file[5] = 1; // little-endian
+    file[8..11].copy_from_slice(b"AI\x02");
+    let names_at = file.len() as u64;
+    file.extend_from_slice(names);
+    let info_at = file.len() as u64;
+    file.extend_from_slice(&info);
+    let table = file.len() as u64;
+    file[0x28..0x30].copy_from_slice(&table.to_le_bytes());
+    file[0x3A..0x3C].copy_from_slice(&64u16.to_le_bytes());
+    file[0x3C..0x3E].copy_from_slice(&3u16.to_le_bytes());
+    file[0x3E..0x40].copy_from_slice(&1u16.to_le_bytes());
+
+    // The null section, the section names and .upd_info
+    for (name, offset, size) in [(0u32, 0u64, 0u64), (1, names_at, names.len() as u64), (11, info_at, info.len() as u64)] {
+        let mut section = [0u8; 64];
+        section[..4].copy_from_slice(&name.to_le_bytes());
+        section[0x18..0x20].copy_from_slice(&offset.to_le_bytes());
+        section[0x20..0x28].copy_from_slice(&size.to_le_bytes());
+        file.extend_from_slice(&section);
+    }
+    file
+}
+
+#[test]
+fn update_information_is_read_from_the_runtime() {
+    let info = "gh-releases-zsync|KDE|krita|latest|krita-*-x86_64.AppImage.zsync";
+    let file = runtime(info);
+    assert!(appimage::is_appimage(&file));
+    assert_eq!(appimage::read_update_info(&mut Cursor::new(file)).unwrap().as_deref(), Some(info));
+    assert_eq!(appimage::read_update_info(&mut Cursor::new(runtime(""))).unwrap(), None);
+    assert!(!appimage::is_appimage(b"\x7fELF\x02\x01\x01\0\0\0\0\0"));
+
+    assert_eq!(
+        UpdateInfo::parse(info),
+        Some(UpdateInfo::GithubReleases {
+            owner: "KDE".to_string(),
+            repo: "krita".to_string(),
+            tag: "latest".to_string(),
+            pattern: "krita-*-x86_64.AppImage.zsync".to_string(),
+        })
+    );
+    assert_eq!(
+        UpdateInfo::parse("zsync|https://example.com/App-latest.AppImage.zsync"),
+        Some(UpdateInfo::Zsync { url: "https://example.com/App-latest.AppImage.zsync".to_string() })
+    );
+    assert_eq!(UpdateInfo::parse("pling-v1-zsync|1234").unwrap().transport(), "pling-v1-zsync");
+}
+
+#[test]
+fn names_and_versions_come_from_file_names() {
+    assert_eq!(appimage::parse_file_name("krita-5.2.2-x86_64"), ("krita".to_string(), Some("5.2.2".to_string())));
+    assert_eq!(appimage::parse_file_name("MuseScore-Studio-4.3.0.241231431-x86_64"), ("MuseScore Studio".to_string(), Some("4.3.0.241231431".to_string())));
+    assert_eq!(appimage::parse_file_name("Obsidian_v1.5.3"), ("Obsidian".to_string(), Some("1.5.3".to_string())));
+    assert_eq!(appimage::parse_file_name("Nextcloud-x86_64"), ("Nextcloud".to_string(), None));
+}
+
+#[test]
+fn integrated_entries_run_the_appimage() {
+    let embedded = "[Desktop Entry]
+Name=Krita
+Exec=krita %F
+TryExec=krita
+Icon=krita
+X-AppImage-Version=5.2.2
+
+[Desktop Action new]
+Exec=krita --new-image
+";
+    let path = Path::new("/home/me/Applications/krita 5.2.2.AppImage");
+    let entry = appimage::integrated_entry(embedded, path, Some(Path::new("/home/me/.local/share/icons/birdnest-appimage-krita.png")));
+    assert!(entry.contains("[Desktop Entry]\nTryExec=\"/home/me/Applications/krita 5.2.2.AppImage\"\n"));
+    assert!(entry.contains("Exec=\"/home/me/Applications/krita 5.2.2.AppImage\" %F\n"));
+    assert!(entry.contains("Exec=\"/home/me/Applications/krita 5.2.2.AppImage\" --new-image\n"));
+    assert!(entry.contains("Icon=/home/me/.local/share/icons/birdnest-appimage-krita.png\n"));
+    assert!(!entry.contains("TryExec=krita"));
+    assert_eq!(appimage::entry_id(path), "birdnest-appimage-krita-5.2.2");
+}
+
+#[test]
+fn the_configured_directories_are_scanned() {
+    let root = std::env::temp_dir().join(format!("birdnest-appimage-{}", std::process::id()));
+    let (home, extra, applications) = (root.join("Applications"), root.join("Tools"), root.join("applications"));
+    for dir in [&home, &extra, &applications] {
+        std::fs::create_dir_all(dir).unwrap();
+    }
+    std::fs::write(home.join("krita-5.2.2-x86_64.AppImage"), runtime("zsync|https://example.com/krita.zsync")).unwrap();
+    std::fs::write(extra.join("Obsidian-1.5.3.AppImage"), runtime("")).unwrap();
+    std::fs::write(extra.join("notes.txt"), "not an AppImage").unwrap();
+    // An integrated AppImage is listed under its entry's name and version
+    std::fs::write(applications.join("birdnest-appimage-obsidian-1.5.3.desktop"), "[Desktop Entry]\nName=Obsidian Notes\nX-AppImage-Version=1.5.3-1\n").unwrap();
+
+    let found = appimage::scan_in(&[home.clone(), extra.clone(), root.join("missing")], &applications);
+    let _ = std::fs::remove_dir_all(&root);
+
+    assert_eq!(found.len(), 2);
+    assert_eq!((found[0].name.as_str(), found[0].version.as_deref()), ("krita", Some("5.2.2")));
+    assert_eq!(found[0].update_info.as_ref().map(UpdateInfo::transport), Some("zsync"));
+    assert!(!found[0].is_integrated());
+    assert_eq!((found[1].name.as_str(), found[1].version.as_deref()), ("Obsidian Notes", Some("1.5.3-1")));
+    assert_eq!(found[1].update_info, None);
+    assert_eq!(found[1].desktop_entry, Some(applications.join("birdnest-appimage-obsidian-1.5.3.desktop")));
+    assert_eq!(found[1].path, extra.join("Obsidian-1.5.3.AppImage"));
+}
+
+#[test]
+fn zsync_metadata_says_whether_a_build_is_newer() {
+    let zsync = b"zsync: 0.6.2\nFilename: krita-5.2.3-x86_64.AppImage\nMTime: Tue, 04 Jun 2024 10:00:00 +0000\nBlocksize: 2048\nLength: 210763072\nURL: krita-5.2.3-x86_64.AppImage\nSHA-1: 3F786850E387550FDAB836ED7E6DC881DE23001B\n\n\x00\x01binary";
+    let header = appimage::parse_zsync_header(zsync);
+    assert_eq!(
+        header,
+        ZsyncHeader {
+            filename: Some("krita-5.2.3-x86_64.AppImage".to_string()),
+            url: Some("krita-5.2.3-x86_64.AppImage".to_string()),
+            sha1: Some("3f786850e387550fdab836ed7e6dc881de23001b".to_string()),
+            length: Some(210763072),
+        }
+    );
+    assert_eq!(appimage::compare(&header, "3f786850e387550fdab836ed7e6dc881de23001b\n"), UpdateStatus::UpToDate);
+    assert_eq!(appimage::compare(&header, "89e6c98d92887913cadf06b2adb97f26cde4849b"), UpdateStatus::Available(Some("5.2.3".to_string())));
+
+    let release = r#"{"tag_name": "v5.2.3", "assets": [
+        {"name": "krita-5.2.3-x86_64.AppImage", "browser_download_url": "https://github.com/KDE/krita/releases/download/v5.2.3/krita-5.2.3-x86_64.AppImage"},
+        {"name": "krita-5.2.3-x86_64.AppImage.zsync", "browser_download_url": "https://github.com/KDE/krita/releases/download/v5.2.3/krita-5.2.3-x86_64.AppImage.zsync"}
+    ]}"#;
+    assert_eq!(
+        appimage::github_asset(release, "krita-*-x86_64.AppImage.zsync").as_deref(),
+        Some("https://github.com/KDE/krita/releases/download/v5.2.3/krita-5.2.3-x86_64.AppImage.zsync")
+    );
+    assert!(appimage::matches_pattern("*.zsync", "a.zsync") && !appimage::matches_pattern("krita-*", "gimp-2.10"));
+}
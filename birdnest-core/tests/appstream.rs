@@ -0,0 +1,161 @@
+use birdnest_core::appstream::{parse_collection, parse_dep11, Catalog, Component};
+use std::path::{Path, PathBuf};
+
+const DEP11: &str = r#"---
+File: DEP-11
+Version: '0.16'
+Origin: ubuntu-noble-main
+MediaBaseUrl: https://appstream.ubuntu.com/media/noble
+---
+Type: desktop-application
+ID: org.gnome.gedit
+Package: gedit
+Name:
+  C: Text Editor
+  de: Texteditor
+Summary:
+  C: Edit text files
+Categories:
+- GNOME
+- Utility
+Icon:
+  cached:
+  - name: gedit_org.gnome.gedit.png
+    width: 128
+    height: 128
+  - name: gedit_org.gnome.gedit.png
+    width: 64
+    height: 64
+  stock: org.gnome.gedit
+Screenshots:
+- thumbnails:
+  - url: org/gnome/gedit/second_224x126.png
+    width: 224
+    height: 126
+- default: true
+  thumbnails:
+  - url: org/gnome/gedit/first_224x126.png
+    width: 224
+    height: 126
+  - url: org/gnome/gedit/first_1248x702.png
+    width: 1248
+    height: 702
+  source-image:
+    url: org/gnome/gedit/first_orig.png
+---
+Type: console-application
+ID: htop
+Package: htop
+Name: [not, a, map]
+"#;
+
+const XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<components version="0.14" origin="flathub">
+  <component type="desktop-application">
+    <id>org.mozilla.firefox.desktop</id>
+    <name>Firefox</name>
+    <name xml:lang="fr">Firefox (fr)</name>
+    <summary>Fast, private web browser</summary>
+    <icon type="stock">org.mozilla.firefox</icon>
+    <icon type="cached" width="64" height="64">org.mozilla.firefox.png</icon>
+    <categories>
+      <category>Network</category>
+      <category>WebBrowser</category>
+    </categories>
+    <screenshots>
+      <screenshot type="default">
+        <image type="source">https://example.org/full.png</image>
+      </screenshot>
+    </screenshots>
+  </component>
+  <component type="desktop-application">
+    <id>org.gnome.Calculator</id>
+    <pkgname>gnome-calculator</pkgname>
+  </component>
+</components>
+"#;
+
+#[test]
+fn reads_dep11_names_icons_screenshots_and_categories() {
+    let components = parse_dep11(DEP11, Path::new("/var/lib/swcatalog/icons")).unwrap();
+    // htop's Name isn't a translation map, so it is skipped rather than failing the catalog
+    assert_eq!(components.len(), 1);
+
+    let gedit = &components[0];
+    assert_eq!(gedit.id, "org.gnome.gedit");
+    assert_eq!(gedit.package.as_deref(), Some("gedit"));
+    assert_eq!(gedit.name, "Text Editor");
+    assert_eq!(gedit.summary, "Edit text files");
+    assert_eq!(gedit.categories, ["GNOME", "Utility"]);
+    assert_eq!(
+        gedit.icon,
+        Some(PathBuf::from("/var/lib/swcatalog/icons/ubuntu-noble-main/64x64/gedit_org.gnome.gedit.png"))
+    );
+    // The default screenshot first, as the largest thumbnail that fits the pane
+    assert_eq!(
+        gedit.screenshots,
+        [
+            "https://appstream.ubuntu.com/media/noble/org/gnome/gedit/first_224x126.png",
+            "https://appstream.ubuntu.com/media/noble/org/gnome/gedit/second_224x126.png",
+        ]
+    );
+}
+
+#[test]
+fn reads_appstream_xml_collections() {
+    let components = parse_collection(XML, |origin| Path::new("/flatpak/icons").join(origin)).unwrap();
+    assert_eq!(components.len(), 2);
+
+    let firefox = &components[0];
+    assert_eq!(firefox.name, "Firefox");
+    assert_eq!(firefox.package, None);
+    assert_eq!(firefox.icon, Some(PathBuf::from("/flatpak/icons/flathub/64x64/org.mozilla.firefox.png")));
+    assert_eq!(firefox.categories, ["Network", "WebBrowser"]);
+    assert_eq!(firefox.screenshots, ["https://example.org/full.png"]);
+
+    // Without a <name> the ID stands in
+    assert_eq!(components[1].name, "org.gnome.Calculator");
+    assert!(parse_collection("<components", |_| PathBuf::new()).is_err());
+}
+
+#[test]
+fn catalog_looks_up_packages_and_app_ids() {
+    let mut components = parse_collection(XML, |_| PathBuf::new()).unwrap();
+    components.extend(parse_dep11(DEP11, Path::new("/icons")).unwrap());
+    let catalog = Catalog::new(components);
+
+    assert_eq!(catalog.len(), 3);
+    assert_eq!(catalog.for_package("gedit").map(|c| c.name.as_str()), Some("Text Editor"));
+    assert_eq!(catalog.for_package("gnome-calculator").map(|c| c.id.as_str()), Some("org.gnome.Calculator"));
+    assert!(catalog.for_package("firefox").is_none());
+    // Flatpak IDs match whether or not the catalog appended .desktop
+    assert_eq!(catalog.for_app("org.mozilla.firefox").map(|c| c.name.as_str()), Some("Firefox"));
+    assert!(catalog.for_app("org.gnome.gedit.desktop").is_some());
+
+    // Completion goes by package or app name; Flatpak entries have no package
+    assert_eq!(catalog.packages_starting_with("text"), ["gedit"]);
+    assert_eq!(catalog.packages_starting_with("GNOME-c"), ["gnome-calculator"]);
+    assert!(catalog.packages_starting_with("fire").is_empty());
+}
+
+#[test]
+fn catalog_lists_categories_by_name_without_duplicates() {
+    let app = |id: &str, name: &str, categories: &[&str]| Component {
+        id: id.to_string(),
+        name: name.to_string(),
+        categories: categories.iter().map(|c| c.to_string()).collect(),
+        ..Default::default()
+    };
+    let catalog = Catalog::new(vec![
+        app("org.gimp.GIMP", "GIMP", &["Graphics", "2DGraphics"]),
+        app("org.kde.krita", "krita", &["Graphics"]),
+        app("org.gnome.Calculator", "Calculator", &["Utility"]),
+        // A second catalog describing the same app doesn't list it twice
+        app("org.gimp.GIMP.desktop", "GIMP (older catalog)", &["Graphics"]),
+        app("org.inkscape.Inkscape", "Inkscape", &["2DGraphics"]),
+    ]);
+
+    let names: Vec<&str> = catalog.in_categories(&["Graphics", "2DGraphics"]).iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(names, ["GIMP", "Inkscape", "krita"]);
+    assert!(catalog.in_categories(&["Game"]).is_empty());
+}
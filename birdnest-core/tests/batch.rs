@@ -0,0 +1,39 @@
+use birdnest_core::batch::{self, Action, Group, Item, Origin};
+use birdnest_core::dnf::DnfBackend;
+
+#[test]
+fn batches_remove_first_and_run_one_step_per_source() {
+    let aur = Origin::pikman("AUR").unwrap();
+    let items = [
+        Item::new("yay-bin", aur, Action::Install),
+        Item::new("org.gimp.GIMP", Origin::Flatpak, Action::Install),
+        Item::new("htop", Origin::System, Action::Install),
+        Item::new("gimp", Origin::System, Action::Remove),
+        Item::new("btop", Origin::System, Action::Install),
+        Item::new("htop", Origin::System, Action::Install),
+    ];
+    let groups = batch::groups(&items);
+    let labels: Vec<String> = groups.iter().map(Group::label).collect();
+    assert_eq!(labels, ["Remove 1 system package", "Install 2 system packages", "Install 1 Flatpak app", "Install 1 AUR package"]);
+    assert_eq!(groups[1].names, ["btop", "htop"]);
+    assert_eq!(Origin::pikman("debian"), None);
+
+    let backend = DnfBackend;
+    assert_eq!(groups[0].commands(&backend)[0].display(), "dnf remove -y gimp");
+    let flatpak = groups[2].commands(&backend);
+    assert_eq!(flatpak[0].display(), "flatpak install -y --noninteractive org.gimp.GIMP");
+    assert!(flatpak[0].as_user);
+    assert_eq!(groups[3].commands(&backend)[0].display(), "pikman install --aur -y yay-bin");
+}
+
+#[test]
+fn the_summary_names_each_failed_step() {
+    let groups = batch::groups(&[
+        Item::new("htop", Origin::System, Action::Install),
+        Item::new("btop", Origin::System, Action::Install),
+        Item::new("yay-bin", Origin::Pikman("aur"), Action::Install),
+    ]);
+    let results = vec![(groups[0].clone(), Ok(())), (groups[1].clone(), Err("pikman exited with code 1".to_string()))];
+    assert_eq!(batch::summary(&results), "2 of 3 changes applied; Install 1 AUR package failed: pikman exited with code 1");
+    assert_eq!(batch::summary(&results[..1]), "All 2 changes applied");
+}
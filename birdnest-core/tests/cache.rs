@@ -0,0 +1,84 @@
+use birdnest_core::cache;
+use birdnest_core::dpkg::{self, Arch, InstalledPackage, Provenance};
+use std::path::Path;
+
+fn packages(writer: usize, count: usize) -> Vec<InstalledPackage> {
+    (0..count)
+        .map(|index| InstalledPackage {
+            name: format!("pkg{}-{}", writer, index),
+            version: "1.0-1".to_string(),
+            installed_bytes: Some(index as u64),
+            // Every other one a foreign library, to round-trip both
+            arch: (index % 2 == 1).then(|| Arch { name: "i386".to_string(), multi_arch: Some("same".to_string()) }),
+            description: format!("package number {}", index),
+            // Every third one a dependency from a repository
+            provenance: Provenance {
+                essential: index == 0,
+                auto_installed: index % 3 == 1,
+                origin: (index % 3 == 1).then(|| "deb.debian.org/debian bookworm/main".to_string()),
+            },
+        })
+        .collect()
+}
+
+#[test]
+fn concurrent_writers_leave_one_whole_cache() {
+    // The cache is only trusted when it is newer than dpkg's status file
+    if !Path::new(dpkg::STATUS_PATH).exists() {
+        return;
+    }
+    let home = std::env::temp_dir().join(format!("birdnest-cache-{}", std::process::id()));
+    std::fs::create_dir_all(&home).unwrap();
+    std::env::set_var("HOME", &home);
+
+    // Writers of different sizes, as the main window and a dialog would be
+    let writers: Vec<_> = (1..=6)
+        .map(|writer| {
+            std::thread::spawn(move || {
+                for _ in 0..20 {
+                    cache::save_installed(&packages(writer, writer * 300));
+                    if let Some(loaded) = cache::load_installed() {
+                        let first = loaded[0].name.split('-').next().unwrap().to_string();
+                        assert!(loaded.iter().all(|pkg| pkg.name.starts_with(&format!("{}-", first))), "entries from two writers");
+                    }
+                }
+            })
+        })
+        .collect();
+    for writer in writers {
+        writer.join().unwrap();
+    }
+
+    let loaded = cache::load_installed().expect("the last write is intact");
+    assert_eq!(loaded.len() % 300, 0);
+    assert_eq!(loaded, packages(loaded.len() / 300, loaded.len()));
+    let dir = home.join(".cache/birdnest");
+    let leftovers: Vec<_> = std::fs::read_dir(&dir).unwrap().flatten().map(|entry| entry.file_name()).filter(|name| name.to_string_lossy().contains(".tmp")).collect();
+    assert!(leftovers.is_empty(), "temp files left behind: {:?}", leftovers);
+
+    cache::invalidate_installed();
+    assert!(cache::load_installed().is_none());
+    let _ = std::fs::remove_dir_all(&home);
+}
+
+#[test]
+fn damaged_or_outdated_caches_are_refused() {
+    let list = packages(1, 50);
+    let data = cache::encode(&list);
+    assert_eq!(cache::decode(&data), Ok(list));
+    assert_eq!(cache::decode(&cache::encode(&[])), Ok(Vec::new()));
+
+    let mut flipped = data.clone();
+    *flipped.last_mut().unwrap() ^= 0x40;
+    assert_eq!(cache::decode(&flipped), Err("checksum mismatch".to_string()));
+    assert!(cache::decode(&data[..data.len() - 1]).unwrap_err().contains("header says"));
+    assert!(cache::decode(&data[..10]).unwrap_err().contains("too short"));
+
+    // A cache written for another layout of the entries is never parsed
+    let mut older = data.clone();
+    older[4..8].copy_from_slice(&(cache::CACHE_VERSION - 1).to_le_bytes());
+    assert!(cache::decode(&older).unwrap_err().contains("unsupported version"));
+    let mut foreign = data;
+    foreign[..4].copy_from_slice(b"PK\x03\x04");
+    assert_eq!(cache::decode(&foreign), Err("bad magic number".to_string()));
+}
@@ -0,0 +1,110 @@
+use birdnest_core::appstream::{parse_collection, Catalog};
+use birdnest_core::changelog::{self, ChangelogEntry};
+use std::path::PathBuf;
+
+const DEBIAN: &str = "\
+htop (3.3.0-5) unstable; urgency=high
+
+  [ Jane Doe ]
+  * Fix the CPU meter on systems with
+    offline cores.
+  * Show the I/O priority column again.
+
+ -- Daniel Lange <DLange@debian.org>  Mon, 12 Aug 2024 10:00:00 +0200
+
+htop (3.3.0-4) unstable; urgency=medium
+
+  * Build with libsensors.
+
+ -- Daniel Lange <DLange@debian.org>  Tue, 02 Apr 2024 09:30:00 +0200
+
+htop (3.3.0-3) unstable; urgency=low
+
+  * Initial upload of 3.3.0.
+
+ -- Daniel Lange <DLange@debian.org>  Sat, 06 Jan 2024 12:00:00 +0100
+";
+
+const FLATPAK: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<components version="0.8" origin="flathub">
+  <component type="desktop-application">
+    <id>org.gimp.GIMP</id>
+    <name>GIMP</name>
+    <releases>
+      <release version="2.10.38" timestamp="1714953600" urgency="high">
+        <description>
+          <p>Fixes for <em>tablet</em> input.</p>
+          <p xml:lang="de">Korrekturen für Tablets.</p>
+          <ul>
+            <li>Wayland crash on start</li>
+            <li>Slow font loading</li>
+          </ul>
+        </description>
+      </release>
+      <release version="2.10.36" date="2023-11-07"/>
+    </releases>
+  </component>
+</components>"#;
+
+#[test]
+fn debian_changelogs_parse_into_entries() {
+    let entries = changelog::parse_debian(DEBIAN);
+    assert_eq!(entries.len(), 3);
+    assert_eq!(
+        entries[0],
+        ChangelogEntry {
+            version: "3.3.0-5".to_string(),
+            urgency: Some("high".to_string()),
+            date: Some("Mon, 12 Aug 2024 10:00:00 +0200".to_string()),
+            author: Some("Daniel Lange <DLange@debian.org>".to_string()),
+            changes: vec![
+                "Fix the CPU meter on systems with offline cores.".to_string(),
+                "Show the I/O priority column again.".to_string(),
+            ],
+        }
+    );
+    assert_eq!(entries[2].urgency.as_deref(), Some("low"));
+}
+
+#[test]
+fn only_versions_above_the_installed_one_are_new() {
+    let entries = changelog::parse_debian(DEBIAN);
+    let versions = |entries: Vec<ChangelogEntry>| entries.into_iter().map(|e| e.version).collect::<Vec<_>>();
+
+    assert_eq!(versions(changelog::newer_than(entries.clone(), "3.3.0-3")), ["3.3.0-5", "3.3.0-4"]);
+    assert!(changelog::newer_than(entries.clone(), "3.3.0-5").is_empty());
+    // An installed version the changelog doesn't mention hides nothing
+    assert_eq!(changelog::newer_than(entries, "3.2.2-1").len(), 3);
+}
+
+#[test]
+fn flatpak_release_notes_come_from_appstream() {
+    let catalog = Catalog::new(parse_collection(FLATPAK, |_| PathBuf::new()).unwrap());
+    let entries = changelog::for_app(&catalog, "org.gimp.GIMP").unwrap();
+
+    assert_eq!(entries[0].version, "2.10.38");
+    assert_eq!(entries[0].date.as_deref(), Some("2024-05-06"));
+    assert_eq!(entries[0].urgency.as_deref(), Some("high"));
+    // Untranslated paragraphs and list items, inline markup flattened
+    assert_eq!(entries[0].changes, ["Fixes for tablet input.", "Wayland crash on start", "Slow font loading"]);
+    assert_eq!(entries[1].date.as_deref(), Some("2023-11-07"));
+    assert!(entries[1].changes.is_empty());
+
+    assert!(changelog::for_app(&catalog, "org.kde.krita").is_err());
+}
+
+#[test]
+fn rendering_flags_urgent_versions() {
+    let text = changelog::render(&changelog::parse_debian(DEBIAN)[..2]);
+    assert!(text.starts_with("3.3.0-5 (Mon, 12 Aug 2024 10:00:00 +0200) [urgency: high]\n  * Fix the CPU meter"));
+    assert!(text.contains("3.3.0-4 (Tue, 02 Apr 2024 09:30:00 +0200)\n  * Build with libsensors."));
+}
+
+#[test]
+fn a_batch_is_as_urgent_as_its_most_urgent_version() {
+    let entries = changelog::parse_debian(DEBIAN);
+    assert_eq!(changelog::urgency(&entries), Some("high"));
+    assert_eq!(changelog::urgency(&entries[1..]), Some("medium"));
+    assert_eq!(changelog::urgency(&[]), None);
+    assert!(changelog::is_urgent("emergency") && !changelog::is_urgent("medium"));
+}
@@ -0,0 +1,48 @@
+use birdnest_core::config::{Config, Preload, ThemePreference};
+use std::time::Duration;
+
+#[test]
+fn config_toml_fills_in_missing_settings() {
+    // A config.toml from before the Settings window
+    let config = Config::from_toml("package_manager = \"auto\"\nauto_confirm = false\nflatpak_enabled = true\n").unwrap();
+    assert_eq!(config.theme, ThemePreference::Dark);
+    assert_eq!(config.border_radius, 24.0);
+    assert_eq!(config.default_tab, None);
+    assert_eq!(config.cache_ttl(), None);
+    assert_eq!(config.preload, Preload::default());
+    assert!(config.preload.flatpak && config.preload.pikman);
+    assert_eq!(config.search_limit(), Some(200));
+    assert!(Config::from_toml("theme = \"light\"").is_err());
+}
+
+#[test]
+fn config_round_trips_through_toml() {
+    let config = Config {
+        package_manager: "dnf".to_string(),
+        theme: ThemePreference::Light,
+        border_radius: 6.0,
+        default_tab: Some("flatpak".to_string()),
+        cache_ttl_hours: Some(12),
+        preload: Preload { pikman: false, ..Preload::default() },
+        ..Config::default()
+    };
+    let toml = config.to_toml().unwrap();
+    assert!(toml.contains("theme = \"light\""));
+
+    let read = Config::from_toml(&toml).unwrap();
+    assert_eq!(read.package_manager, "dnf");
+    assert_eq!(read.theme, ThemePreference::Light);
+    assert_eq!(read.border_radius, 6.0);
+    assert_eq!(read.default_tab.as_deref(), Some("flatpak"));
+    assert_eq!(read.cache_ttl(), Some(Duration::from_secs(12 * 3600)));
+    assert_eq!(read.preload, Preload { flatpak: true, pikman: false, delay_secs: 3 });
+}
+
+#[test]
+fn search_limit_is_the_lower_of_the_setting_and_the_low_memory_cap() {
+    let limit = |search_result_limit, low_memory_mode| Config { search_result_limit, low_memory_mode, ..Config::default() }.search_limit();
+    assert_eq!(limit(50, false), Some(50));
+    assert_eq!(limit(500, true), Some(Config::LOW_MEMORY_RESULT_LIMIT));
+    assert_eq!(limit(0, false), None);
+    assert_eq!(limit(0, true), Some(Config::LOW_MEMORY_RESULT_LIMIT));
+}
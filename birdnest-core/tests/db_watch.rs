@@ -0,0 +1,56 @@
+use birdnest_core::db_watch::{self, Database, Target};
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime};
+
+#[test]
+fn only_the_database_file_counts() {
+    let status = Target { database: Database::Packages, dir: "/var/lib/dpkg".into(), file: Some("status") };
+    assert!(status.matches(Path::new("/var/lib/dpkg/status")));
+    assert!(!status.matches(Path::new("/var/lib/dpkg/status-old")));
+    assert!(!status.matches(Path::new("/var/lib/dpkg/info/status")));
+
+    let pacman = Target { database: Database::Packages, dir: "/var/lib/pacman/local".into(), file: None };
+    assert!(pacman.matches(Path::new("/var/lib/pacman/local/htop-3.3.0-1")));
+    assert!(!pacman.matches(Path::new("/var/lib/pacman/sync/core.db")));
+}
+
+#[test]
+fn a_rewritten_status_file_is_reported_once_it_settles() {
+    let dir = std::env::temp_dir().join(format!("birdnest-db-watch-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let targets = vec![
+        Target { database: Database::Packages, dir: dir.clone(), file: Some("status") },
+        Target { database: Database::Flatpak, dir: dir.join("flatpak"), file: Some(".changed") },
+    ];
+    std::fs::create_dir_all(dir.join("flatpak")).unwrap();
+    let quiet = Duration::from_millis(300);
+    let mut watch = db_watch::watch(targets, quiet).unwrap();
+
+    // Files beside it, as dpkg and apt write them, don't count
+    std::fs::write(dir.join("lock"), "").unwrap();
+    std::thread::sleep(quiet * 2);
+    assert!(watch.changes.try_recv().is_err());
+
+    // dpkg writes status-new and renames it over status, again and again
+    let started = SystemTime::now();
+    for _ in 0..3 {
+        std::fs::write(dir.join("status-new"), "Package: htop\n").unwrap();
+        std::fs::rename(dir.join("status-new"), dir.join("status")).unwrap();
+        std::thread::sleep(quiet / 4);
+    }
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let change = loop {
+        match watch.changes.try_recv() {
+            Ok(change) => break change,
+            Err(_) if Instant::now() < deadline => std::thread::sleep(Duration::from_millis(50)),
+            Err(_) => panic!("no change reported"),
+        }
+    };
+    assert_eq!(change.database, Database::Packages);
+    assert!(change.at >= started);
+    std::thread::sleep(quiet * 2);
+    assert!(watch.changes.try_recv().is_err(), "one change per settled burst");
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,49 @@
+use birdnest_core::deferred_removal::{desktop_files, hide_in, merge, DeferredRemoval};
+use std::fs;
+use std::path::PathBuf;
+
+fn entry(name: &str, remove_after: u64) -> DeferredRemoval {
+    DeferredRemoval { name: name.to_string(), flatpak: false, remove_after, hidden: Vec::new() }
+}
+
+#[test]
+fn finds_the_desktop_files_a_package_owns() {
+    let paths: Vec<String> = ["/usr/bin/gimp", "/usr/share/applications/gimp.desktop", "/usr/share/gimp/extra.desktop"]
+        .iter()
+        .map(|p| p.to_string())
+        .collect();
+    assert_eq!(desktop_files(&paths), ["gimp.desktop"]);
+    assert!(desktop_files(&["/usr/bin/htop".to_string()]).is_empty());
+}
+
+#[test]
+fn hiding_leaves_the_users_own_entries_alone() {
+    let dir = std::env::temp_dir().join(format!("birdnest-deferred-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("mine.desktop"), "[Desktop Entry]\nName=Mine\n").unwrap();
+
+    let hidden = hide_in(&dir, &["gimp.desktop".to_string(), "mine.desktop".to_string()]).unwrap();
+    assert_eq!(hidden, [dir.join("gimp.desktop")]);
+    assert!(fs::read_to_string(dir.join("gimp.desktop")).unwrap().contains("Hidden=true"));
+    assert_eq!(fs::read_to_string(dir.join("mine.desktop")).unwrap(), "[Desktop Entry]\nName=Mine\n");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn removals_come_due_after_their_days() {
+    let day = 24 * 3600;
+    let gimp = entry("gimp", 10 * day);
+    assert!(!gimp.is_due(3 * day));
+    assert_eq!(gimp.days_left(3 * day), 7);
+    // Part of a day left still counts as one
+    assert_eq!(gimp.days_left(10 * day - 60), 1);
+    assert!(gimp.is_due(10 * day));
+    assert_eq!(gimp.days_left(11 * day), 0);
+
+    // Removing an app again restarts its wait rather than listing it twice
+    let mut deferred = vec![gimp, entry("vlc", day)];
+    merge(&mut deferred, DeferredRemoval { hidden: vec![PathBuf::from("/tmp/gimp.desktop")], ..entry("gimp", 20 * day) });
+    assert_eq!(deferred.len(), 2);
+    assert_eq!(deferred.iter().find(|d| d.name == "gimp").unwrap().remove_after, 20 * day);
+}
@@ -0,0 +1,82 @@
+use birdnest_core::digest::{build, parse_dpkg_log, parse_history, ChangeKind};
+use birdnest_core::sources_backup::Snapshot;
+use std::path::PathBuf;
+
+const HISTORY: &str = "\
+Start-Date: 2024-04-20  09:00:00
+Commandline: apt install htop
+Install: htop:amd64 (3.2.2-2)
+End-Date: 2024-04-20  09:00:05
+
+Start-Date: 2024-05-06  12:45:00
+Commandline: apt install vlc
+Requested-By: alice (1000)
+Install: vlc:amd64 (3.0.20-3), libvlc5:amd64 (3.0.20-3, automatic)
+Upgrade: libc6:amd64 (2.36-9, 2.36-9+deb12u4)
+End-Date: 2024-05-06  12:45:30
+
+Start-Date: 2024-05-07  08:10:00
+Commandline: apt purge gimp
+Requested-By: bob (1001)
+Purge: gimp:amd64 (2.10.34-1)
+End-Date: 2024-05-07  08:10:12
+";
+
+const DPKG_LOG: &str = "\
+2024-05-06 12:45:10 install vlc:amd64 <none> 3.0.20-3
+2024-05-06 12:45:11 status half-installed vlc:amd64 3.0.20-3
+2024-05-06 12:45:12 configure vlc:amd64 3.0.20-3 <none>
+2024-05-07 08:10:05 remove gimp:amd64 2.10.34-1 <none>
+2024-05-07 08:10:06 purge gimp:amd64 2.10.34-1 <none>
+2024-05-08 17:30:00 install zoom:amd64 <none> 6.0.2
+2024-05-08 17:31:00 upgrade zoom:amd64 6.0.2 6.0.2
+";
+
+#[test]
+fn history_entries_carry_their_command_and_user() {
+    let changes = parse_history(HISTORY, "2024-05-01");
+    let names: Vec<&str> = changes.iter().map(|c| c.package.as_str()).collect();
+    assert_eq!(names, ["vlc", "libvlc5", "libc6", "gimp"]);
+
+    assert_eq!(changes[1].new_version.as_deref(), Some("3.0.20-3"));
+    assert_eq!(changes[2].kind, ChangeKind::Upgraded);
+    assert_eq!(changes[2].versions(), "2.36-9 → 2.36-9+deb12u4");
+    assert_eq!(changes[0].origin().as_deref(), Some("alice: apt install vlc"));
+    assert_eq!(changes[3].kind, ChangeKind::Removed);
+    assert_eq!(changes[3].time, "2024-05-07 08:10:00");
+}
+
+#[test]
+fn dpkg_log_keeps_only_real_changes() {
+    let changes = parse_dpkg_log(DPKG_LOG, "2024-05-01");
+    let kinds: Vec<(&str, ChangeKind)> = changes.iter().map(|c| (c.package.as_str(), c.kind)).collect();
+    // Status and configure lines, and the reinstall, change nothing
+    assert_eq!(
+        kinds,
+        [("vlc", ChangeKind::Installed), ("gimp", ChangeKind::Removed), ("gimp", ChangeKind::Removed), ("zoom", ChangeKind::Installed)]
+    );
+    assert_eq!(changes[0].old_version, None);
+    assert_eq!(parse_dpkg_log(DPKG_LOG, "2024-05-08").len(), 1);
+}
+
+#[test]
+fn digest_merges_both_logs_and_source_changes() {
+    let snapshot = |created: i64| Snapshot { id: created.to_string(), created, files: vec![PathBuf::from("sources.list")] };
+    // 2024-05-01T00:00:00Z
+    let since_time = 1_714_521_600;
+    let digest = build(HISTORY, DPKG_LOG, vec![snapshot(since_time + 3600), snapshot(since_time - 3600)], "2024-05-01", since_time);
+
+    // What apt did isn't repeated from dpkg's log; the .deb installed with dpkg is added
+    let names: Vec<&str> = digest.changes.iter().map(|c| c.package.as_str()).collect();
+    assert_eq!(names, ["zoom", "gimp", "vlc", "libvlc5", "libc6"]);
+    assert_eq!(digest.changes[0].origin(), None);
+    assert_eq!(digest.source_changes.len(), 1);
+    assert_eq!(digest.summary(), "Since 2024-05-01: 3 installed, 1 upgraded, 1 removed, sources changed once");
+
+    let report = digest.render();
+    assert!(report.contains("\nRemoved (1)\n  2024-05-07 08:10:00  gimp 2.10.34-1  (bob: apt purge gimp)\n"));
+    assert!(report.contains("\nSources changed (1)\n"));
+
+    let quiet = build("", "", Vec::new(), "2024-05-01", since_time);
+    assert_eq!(quiet.summary(), "No package or sources changes since 2024-05-01");
+}
@@ -0,0 +1,24 @@
+use birdnest_core::disk_usage::{parse_flatpak_sizes, sort_largest, strips, total, Usage};
+
+fn usage(name: &str, bytes: u64, flatpak: bool) -> Usage {
+    Usage { name: name.to_string(), bytes, flatpak }
+}
+
+#[test]
+fn flatpak_sizes_are_parsed_and_split_into_treemap_rows() {
+    let mut all = parse_flatpak_sizes("org.gimp.GIMP\t412.5\u{a0}MB\norg.broken.App\tunknown\n");
+    assert_eq!(all, [usage("org.gimp.GIMP", 412_500_000, true)]);
+
+    all.extend([usage("firefox", 250_000_000, false), usage("vim", 4_000_000, false), usage("bash", 7_000_000, false)]);
+    sort_largest(&mut all);
+    let names: Vec<&str> = all.iter().map(|u| u.name.as_str()).collect();
+    assert_eq!(names, ["org.gimp.GIMP", "firefox", "bash", "vim"]);
+    assert_eq!(total(&all, false), 261_000_000);
+    assert_eq!(total(&all, true), 412_500_000);
+
+    // GIMP alone fills half, so it gets a row to itself
+    let rows = strips(&all, 2);
+    let row_names: Vec<Vec<&str>> = rows.iter().map(|row| row.iter().map(|u| u.name.as_str()).collect()).collect();
+    assert_eq!(row_names, [vec!["org.gimp.GIMP"], vec!["firefox", "bash", "vim"]]);
+    assert!(strips(&all, 0).is_empty());
+}
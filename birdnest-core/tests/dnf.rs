@@ -0,0 +1,87 @@
+use birdnest_core::dnf::{parse_dnf_info, parse_dnf_search, parse_dnf_size, parse_dnf_upgrades, parse_rpm_installed};
+use birdnest_core::dpkg::InstalledPackage;
+use birdnest_core::package_manager::BackendKind;
+
+const DNF4_SEARCH: &str = "\
+Last metadata expiration check: 0:12:01 ago on Mon 01 Jan 2024.
+======================== Name Exactly Matched: hello ========================
+hello.x86_64 : Prints a familiar, friendly greeting
+hello.i686 : Prints a familiar, friendly greeting
+===================== Name & Summary Matched: hello =====================
+python3.12.x86_64 : Version 3.12 of the Python interpreter
+";
+
+const DNF5_SEARCH: &str = "\
+Matched fields: name (exact)
+ hello.x86_64\tPrints a familiar, friendly greeting
+Matched fields: name, summary
+ hello-devel.noarch\tDevelopment files for hello
+";
+
+const DNF_INFO: &str = "\
+Installed Packages
+Name         : hello
+Version      : 2.12.1
+Release      : 2.fc40
+Architecture : x86_64
+Size         : 83 k
+Summary      : Prints a familiar, friendly greeting
+Description  : The GNU Hello program produces a familiar, friendly greeting.
+             : Seriously, though: this is an example.
+
+Available Packages
+Name         : hello
+Version      : 2.12.1
+Release      : 3.fc40
+";
+
+#[test]
+fn parses_dnf4_and_dnf5_search_output() {
+    let dnf4 = parse_dnf_search(DNF4_SEARCH);
+    let names: Vec<&str> = dnf4.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, ["hello", "python3.12"]);
+    assert_eq!(dnf4[0].description, "Prints a familiar, friendly greeting");
+
+    let dnf5 = parse_dnf_search(DNF5_SEARCH);
+    let names: Vec<&str> = dnf5.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, ["hello", "hello-devel"]);
+}
+
+#[test]
+fn parses_first_dnf_info_record() {
+    let details = parse_dnf_info(DNF_INFO).unwrap();
+    assert_eq!(details.name, "hello");
+    assert_eq!(details.version, "2.12.1-2.fc40");
+    assert_eq!(details.download_size, Some(83 * 1024));
+    assert_eq!(
+        details.description,
+        "The GNU Hello program produces a familiar, friendly greeting. Seriously, though: this is an example."
+    );
+    assert_eq!(parse_dnf_size("1.5 MiB"), Some(1024 * 1024 * 3 / 2));
+}
+
+#[test]
+fn lists_rpm_packages_and_dnf_upgrades() {
+    let installed = parse_rpm_installed("zlib\t1.3-1.fc40\t204800\ngpg-pubkey\tabc-123\nhello\t2.12.1-2.fc40\n");
+    assert_eq!(
+        installed,
+        [
+            InstalledPackage { name: "hello".into(), version: "2.12.1-2.fc40".into(), ..InstalledPackage::default() },
+            InstalledPackage { name: "zlib".into(), version: "1.3-1.fc40".into(), installed_bytes: Some(204800), ..InstalledPackage::default() },
+        ]
+    );
+
+    let upgrades = parse_dnf_upgrades("Available Upgrades\nhello.x86_64    1:2.12.1-3.fc40    updates\n", &installed);
+    assert_eq!(upgrades.len(), 1);
+    assert_eq!(upgrades[0].name, "hello");
+    assert_eq!(upgrades[0].current_version, "2.12.1-2.fc40");
+    assert_eq!(upgrades[0].new_version, "2.12.1-3.fc40");
+}
+
+#[test]
+fn detects_dnf_only_without_apt() {
+    assert_eq!(BackendKind::detect("auto", |p| p == "dnf"), Some(BackendKind::Dnf));
+    assert_eq!(BackendKind::detect("auto", |p| p == "apt" || p == "dnf"), Some(BackendKind::Apt));
+    assert_eq!(BackendKind::detect("dnf", |_| false), Some(BackendKind::Dnf));
+    assert_eq!(BackendKind::detect("auto", |_| false), None);
+}
@@ -0,0 +1,188 @@
+use birdnest_core::dpkg::{
+    package_of_list_file, parse_descriptions, parse_extended_states, parse_meta_packages, parse_owner, parse_policy_origins, parse_query_output, parse_status,
+    parse_tab_descriptions, Arch, InstalledPackage, Provenance, LOCAL_ORIGIN,
+};
+use birdnest_core::package_manager::parse_apt_cache_search;
+
+fn pkg(name: &str, version: &str) -> InstalledPackage {
+    InstalledPackage {
+        name: name.to_string(),
+        version: version.to_string(),
+        ..InstalledPackage::default()
+    }
+}
+
+fn described(name: &str, version: &str, description: &str) -> InstalledPackage {
+    InstalledPackage { description: description.to_string(), ..pkg(name, version) }
+}
+
+#[test]
+fn status_file_keeps_installed_and_config_files_entries() {
+    let status = include_str!("../fixtures/dpkg_status");
+    let bash = InstalledPackage {
+        provenance: Provenance { essential: true, ..Provenance::default() },
+        ..described("bash", "5.2.21-2", "GNU Bourne Again SHell")
+    };
+    assert_eq!(
+        parse_status(status),
+        vec![
+            bash,
+            described("oldlib", "1.0-1", "removed, config files left behind"),
+            described("git", "1:2.43.0-1", "fast, scalable, distributed revision control system"),
+        ]
+    );
+}
+
+#[test]
+fn status_file_without_trailing_newline_keeps_last_entry() {
+    let status = "Package: vim\nStatus: install ok installed\nVersion: 2:9.1.0016-1";
+    assert_eq!(parse_status(status), vec![pkg("vim", "2:9.1.0016-1")]);
+}
+
+#[test]
+fn dpkg_query_output_is_tab_separated() {
+    let output = "bash\t5.2.21-2\n\n\t1.0\ngit\t1:2.43.0-1\n";
+    assert_eq!(
+        parse_query_output(output),
+        vec![pkg("bash", "5.2.21-2"), pkg("git", "1:2.43.0-1")]
+    );
+}
+
+#[test]
+fn installed_size_is_read_in_kib() {
+    let status = "Package: vim\nStatus: install ok installed\nInstalled-Size: 4096\nVersion: 2:9.1.0016-1\n";
+    assert_eq!(parse_status(status)[0].installed_bytes, Some(4096 * 1024));
+
+    let output = "vim\t2:9.1.0016-1\t4096\nbash\t5.2.21-2\t\n";
+    let sizes: Vec<Option<u64>> = parse_query_output(output).iter().map(|p| p.installed_bytes).collect();
+    assert_eq!(sizes, [Some(4096 * 1024), None]);
+}
+
+#[test]
+fn architecture_and_multi_arch_are_read() {
+    let status = "Package: libc6\nStatus: install ok installed\nArchitecture: i386\nMulti-Arch: same\nVersion: 2.39-0ubuntu8\n\n\
+                  Package: bash\nStatus: install ok installed\nArchitecture: amd64\nMulti-Arch: no\nVersion: 5.2.21-2\n";
+    let arches: Vec<Option<Arch>> = parse_status(status).into_iter().map(|p| p.arch).collect();
+    assert_eq!(
+        arches,
+        [
+            Some(Arch { name: "i386".to_string(), multi_arch: Some("same".to_string()) }),
+            Some(Arch { name: "amd64".to_string(), multi_arch: None }),
+        ]
+    );
+    let libc = arches[0].as_ref().unwrap();
+    assert!(libc.is_foreign("amd64"));
+    assert_eq!(libc.label(), "i386, Multi-Arch: same");
+    assert!(!Arch { name: "all".to_string(), multi_arch: None }.is_foreign("amd64"));
+
+    let output = "libc6\t2.39-0ubuntu8\t12000\ti386\tsame\nbash\t5.2.21-2\t\n";
+    let arches: Vec<Option<Arch>> = parse_query_output(output).into_iter().map(|p| p.arch).collect();
+    assert_eq!(arches, [Some(Arch { name: "i386".to_string(), multi_arch: Some("same".to_string()) }), None]);
+}
+
+#[test]
+fn apt_cache_search_drops_duplicates() {
+    let output = "git - fast, scalable, distributed revision control system\n\
+                  git/noble - duplicate entry\n\
+                  git-lfs - Git Large File Support\n";
+    let results = parse_apt_cache_search(output);
+    let names: Vec<&str> = results.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, ["git", "git-lfs"]);
+    assert_eq!(results[1].description, "Git Large File Support");
+}
+
+#[test]
+fn descriptions_are_read_for_the_packages_asked_about() {
+    let status = include_str!("../fixtures/dpkg_status");
+    let descriptions = parse_descriptions(status, &["git".to_string(), "bash".to_string(), "zsh".to_string()]);
+    assert_eq!(descriptions.len(), 2);
+    assert_eq!(descriptions["git"], "fast, scalable, distributed revision control system");
+    assert_eq!(descriptions["bash"], "GNU Bourne Again SHell");
+
+    let output = "git\tfast, scalable, distributed revision control system\nfonts-noto\t\n";
+    assert_eq!(parse_tab_descriptions(output).len(), 1);
+}
+
+#[test]
+fn dpkg_query_gives_essential_and_the_summary() {
+    let output = "bash\t5.2.21-2\t7000\tamd64\t\tyes\tGNU Bourne Again SHell\nvim\t2:9.1.0016-1\t\t\t\tno\tVi IMproved\n";
+    let packages = parse_query_output(output);
+    assert!(packages[0].provenance.essential);
+    assert_eq!(packages[0].arch.as_ref().map(|arch| arch.name.as_str()), Some("amd64"));
+    assert_eq!(packages[0].description, "GNU Bourne Again SHell");
+    assert!(!packages[1].provenance.essential);
+    assert_eq!(packages[1].arch, None);
+    assert_eq!(packages[1].description, "Vi IMproved");
+}
+
+#[test]
+fn apt_marks_the_packages_it_installed_as_dependencies() {
+    let states = "Package: libvlc5\nArchitecture: amd64\nAuto-Installed: 1\n\n\
+                  Package: vlc\nArchitecture: amd64\nAuto-Installed: 0\n\n\
+                  Package: libc6\nArchitecture: i386\nAuto-Installed: 1\n";
+    let auto = parse_extended_states(states);
+    assert_eq!(auto.len(), 2);
+    assert_eq!(auto["libvlc5"], ["amd64"]);
+    assert_eq!(auto["libc6"], ["i386"]);
+}
+
+#[test]
+fn apt_policy_gives_the_source_of_each_installed_version() {
+    let output = "vim:
+  Installed: 2:9.1.0016-1
+  Candidate: 2:9.1.0016-2
+  Version table:
+     2:9.1.0016-2 500
+        500 http://deb.debian.org/debian trixie/main amd64 Packages
+ *** 2:9.1.0016-1 100
+        500 http://deb.debian.org/debian bookworm/main amd64 Packages
+        100 /var/lib/dpkg/status
+discord:
+  Installed: 0.0.54
+  Candidate: 0.0.54
+  Version table:
+ *** 0.0.54 100
+        100 /var/lib/dpkg/status
+libc6:i386:
+  Installed: 2.36-9
+  Candidate: 2.36-9
+  Version table:
+ *** 2.36-9 500
+        500 https://ppa.launchpadcontent.net/user/ppa/ubuntu/ jammy/main i386 Packages
+        100 /var/lib/dpkg/status
+";
+    let origins = parse_policy_origins(output);
+    assert_eq!(origins["vim"], "deb.debian.org/debian bookworm/main");
+    assert_eq!(origins["discord"], LOCAL_ORIGIN);
+    assert_eq!(origins["libc6"], "ppa.launchpadcontent.net/user/ppa/ubuntu jammy/main");
+
+    let provenance = Provenance { essential: true, auto_installed: true, origin: Some(origins["vim"].clone()) };
+    assert_eq!(provenance.labels(), ["Essential", "Automatically installed", "From deb.debian.org/debian bookworm/main"]);
+    let local = Provenance { origin: Some(LOCAL_ORIGIN.to_string()), ..Provenance::default() };
+    assert_eq!(local.labels(), ["Local: no source offers this version"]);
+}
+
+#[test]
+fn file_lists_name_their_package() {
+    assert_eq!(package_of_list_file("vlc.list"), Some("vlc"));
+    assert_eq!(package_of_list_file("libc6:amd64.list"), Some("libc6"));
+    assert_eq!(package_of_list_file("vlc.postinst"), None);
+    assert_eq!(package_of_list_file(".list"), None);
+}
+
+#[test]
+fn dpkg_query_names_the_owner_of_a_path() {
+    let output = "diversion by dash from: /bin/sh\ndiversion by dash to: /bin/sh.distrib\ndash: /bin/sh\n";
+    assert_eq!(parse_owner(output, "/bin/sh").as_deref(), Some("dash"));
+    let shared = "libc6:amd64, libc6:i386: /usr/share/doc/libc6\n";
+    assert_eq!(parse_owner(shared, "/usr/share/doc/libc6").as_deref(), Some("libc6"));
+    assert_eq!(parse_owner("network-manager-gnome: /etc/xdg/autostart\n", "/etc/xdg/autostart/nm-applet.desktop"), None);
+}
+
+#[test]
+fn meta_packages_are_the_installed_ones_in_the_metapackages_section() {
+    let output = "ubuntu-desktop\tmetapackages\tii \nkubuntu-desktop\tuniverse/metapackages\tii \nxubuntu-desktop\tuniverse/metapackages\tun \nfirefox\tweb\tii \n";
+    let meta = parse_meta_packages(output);
+    assert_eq!(meta.len(), 2);
+    assert!(meta.contains("ubuntu-desktop") && meta.contains("kubuntu-desktop"));
+}
@@ -0,0 +1,115 @@
+use birdnest_core::elevation::{Availability, ElevationMethod};
+use birdnest_core::utils::CommandBuilder;
+use std::path::PathBuf;
+
+fn availability() -> Availability {
+    Availability {
+        pkexec: true,
+        polkit_agent: true,
+        sudo: true,
+        askpass: None,
+        helper: false,
+        graphical: true,
+    }
+}
+
+#[test]
+fn method_names_round_trip() {
+    for method in ElevationMethod::ALL {
+        assert_eq!(ElevationMethod::parse(method.name()).unwrap(), method);
+    }
+    assert!(ElevationMethod::parse("doas").is_err());
+}
+
+#[test]
+fn auto_prefers_pkexec_with_a_polkit_agent() {
+    assert_eq!(availability().resolve(ElevationMethod::Auto).unwrap(), ElevationMethod::Pkexec);
+}
+
+#[test]
+fn auto_falls_back_to_askpass_without_an_agent() {
+    let without_agent = Availability {
+        polkit_agent: false,
+        askpass: Some(PathBuf::from("/usr/bin/ssh-askpass")),
+        ..availability()
+    };
+    assert!(!without_agent.supports(ElevationMethod::Pkexec));
+    assert_eq!(without_agent.resolve(ElevationMethod::Auto).unwrap(), ElevationMethod::SudoAskpass);
+}
+
+#[test]
+fn auto_uses_sudo_in_a_terminal() {
+    let terminal = Availability { graphical: false, polkit_agent: false, ..availability() };
+    assert_eq!(terminal.resolve(ElevationMethod::Auto).unwrap(), ElevationMethod::Sudo);
+}
+
+#[test]
+fn explicit_choice_must_be_available() {
+    assert!(availability().resolve(ElevationMethod::SudoAskpass).is_err());
+    assert!(availability().resolve(ElevationMethod::Helper).is_err());
+    assert_eq!(availability().resolve(ElevationMethod::Sudo).unwrap(), ElevationMethod::Sudo);
+}
+
+#[test]
+fn auto_prefers_the_helper_when_pkexec_can_start_it() {
+    let installed = Availability { helper: true, ..availability() };
+    assert_eq!(installed.resolve(ElevationMethod::Auto).unwrap(), ElevationMethod::Helper);
+
+    let without_agent = Availability {
+        polkit_agent: false,
+        askpass: Some(PathBuf::from("/usr/bin/ssh-askpass")),
+        ..installed
+    };
+    assert!(!without_agent.supports(ElevationMethod::Helper));
+    assert_eq!(without_agent.resolve(ElevationMethod::Auto).unwrap(), ElevationMethod::SudoAskpass);
+}
+
+#[test]
+fn config_defaults_to_auto() {
+    let config: birdnest_core::config::Config =
+        serde_json::from_str(r#"{"package_manager":"auto","auto_confirm":false,"flatpak_enabled":true}"#).unwrap();
+    assert_eq!(config.elevation, ElevationMethod::Auto);
+}
+
+#[test]
+fn builder_passes_its_environment_through_sudo() {
+    let builder = CommandBuilder::new("apt-get").args(&["install", "-y", "hello"]).noninteractive().c_locale();
+    assert_eq!(builder.env_vars(), [("DEBIAN_FRONTEND", "noninteractive"), ("LC_ALL", "C.UTF-8")]);
+
+    // sudo resets the environment, so the variables ride along through env(1)
+    let sudo = builder.build_with(ElevationMethod::Sudo).unwrap();
+    assert_eq!(sudo.get_program(), "sudo");
+    let args: Vec<_> = sudo.get_args().collect();
+    assert_eq!(args, ["env", "DEBIAN_FRONTEND=noninteractive", "LC_ALL=C.UTF-8", "apt-get", "install", "-y", "hello"]);
+
+    // The helper's client forwards the variables itself, so they're set on it directly
+    let helper = builder.build_with(ElevationMethod::Helper).unwrap();
+    let args: Vec<_> = helper.get_args().collect();
+    assert_eq!(args, ["helper", "run", "apt-get", "--", "install", "-y", "hello"]);
+    assert!(helper.get_envs().any(|(key, _)| key == "DEBIAN_FRONTEND"));
+
+    // Programs the helper doesn't run still go through pkexec
+    let script = CommandBuilder::new("sh").args(&["-c", "true"]).build_with(ElevationMethod::Helper).unwrap();
+    assert_eq!(script.get_program(), "pkexec");
+    // So do package tool commands it doesn't run, asking each time, with
+    // the variables through env(1) since pkexec clears them
+    let local = CommandBuilder::new("apt-get")
+        .args(&["install", "-y", "/tmp/hello.deb"])
+        .noninteractive()
+        .c_locale()
+        .build_with(ElevationMethod::Helper)
+        .unwrap();
+    assert_eq!(local.get_program(), "pkexec");
+    let args: Vec<_> = local.get_args().map(|arg| arg.to_str().unwrap()).collect();
+    assert_eq!(args[..3], ["env", "DEBIAN_FRONTEND=noninteractive", "LC_ALL=C.UTF-8"]);
+    assert_eq!(args[args.len() - 4..], ["apt-get", "install", "-y", "/tmp/hello.deb"]);
+}
+
+#[test]
+fn unelevated_builder_sets_the_environment_directly() {
+    let (command, method) = CommandBuilder::new("apt").args(&["list"]).c_locale().build().unwrap();
+    assert_eq!(method, None);
+    assert_eq!(command.get_program(), "apt");
+    let envs: Vec<_> = command.get_envs().collect();
+    assert_eq!(envs, [(std::ffi::OsStr::new("LC_ALL"), Some(std::ffi::OsStr::new("C.UTF-8")))]);
+}
@@ -0,0 +1,58 @@
+use birdnest_core::environments::{builtin, check_container, load_from, Template};
+
+const TEMPLATE: &str = r#"
+name = "Go on Fedora"
+manager = "fedora"
+container = "go-dev"
+packages = ["golang", "gopls"]
+export = ["gopls"]
+"#;
+
+#[test]
+fn builtin_templates_parse() {
+    let names: Vec<String> = builtin().into_iter().map(|t| t.name).collect();
+    assert!(names.contains(&"Rust dev on Arch".to_string()));
+    assert!(names.contains(&"Python data science on Fedora".to_string()));
+}
+
+#[test]
+fn a_template_creates_installs_and_exports_in_order() {
+    let template = Template::from_toml(TEMPLATE).unwrap();
+    assert_eq!(template.description, "");
+    let steps: Vec<String> = template.steps("my-go", &template.export).iter().map(|s| s.display()).collect();
+    assert_eq!(
+        steps,
+        [
+            "pikman init my-go --manager fedora",
+            "pikman install --fedora --name my-go -y golang gopls",
+            "pikman export gopls --name my-go",
+        ]
+    );
+    assert!(template.steps("my-go", &template.export).iter().all(|s| s.as_user));
+    assert_eq!(Template::from_toml(&template.to_toml()).unwrap(), template);
+}
+
+#[test]
+fn templates_that_could_smuggle_flags_are_rejected() {
+    assert!(Template::from_toml(&TEMPLATE.replace("fedora", "gentoo")).is_err());
+    assert!(Template::from_toml(&TEMPLATE.replace("\"gopls\"]", "\"--privileged\"]")).is_err());
+    assert!(Template::from_toml(&TEMPLATE.replace("go-dev", "go dev")).is_err());
+    assert!(check_container("rust-dev").is_ok());
+    assert!(check_container("../home").is_err());
+}
+
+#[test]
+fn user_templates_add_to_and_replace_builtin_ones() {
+    let dir = std::env::temp_dir().join(format!("birdnest-environments-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("go.toml"), TEMPLATE).unwrap();
+    std::fs::write(dir.join("rust.toml"), TEMPLATE.replace("Go on Fedora", "Rust dev on Arch")).unwrap();
+    std::fs::write(dir.join("broken.toml"), "name = ").unwrap();
+
+    let templates = load_from(Some(&dir));
+    std::fs::remove_dir_all(&dir).unwrap();
+    let rust = templates.iter().find(|t| t.name == "Rust dev on Arch").unwrap();
+    assert_eq!(rust.manager, "fedora");
+    assert!(templates.iter().any(|t| t.name == "Go on Fedora"));
+    assert_eq!(templates.len(), builtin().len() + 1);
+}
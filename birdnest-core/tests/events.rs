@@ -0,0 +1,19 @@
+use birdnest_core::cache;
+use birdnest_core::events::{self, Event};
+
+#[test]
+fn subscribers_each_get_every_event_published_after_they_joined() {
+    events::publish(Event::Progress { id: 7, line: "before anyone listened".to_string() });
+    let mut first = events::subscribe();
+    let mut second = events::subscribe();
+
+    events::publish(Event::TransactionStarted { id: 7, label: "apt install htop".to_string() });
+    cache::invalidate_installed();
+
+    for receiver in [&mut first, &mut second] {
+        let received: Vec<Event> = std::iter::from_fn(|| receiver.try_recv().ok()).collect();
+        assert!(received.contains(&Event::TransactionStarted { id: 7, label: "apt install htop".to_string() }));
+        assert!(received.contains(&Event::CacheInvalidated));
+        assert!(!received.iter().any(|event| matches!(event, Event::Progress { .. })));
+    }
+}
@@ -0,0 +1,172 @@
+use birdnest_core::flatpak::{
+    bundle_file, parse_app_runtimes, parse_flatpak_size, parse_flatpak_updates, parse_flatpakref, parse_remote_info,
+    preferred_remote, runtime_dependents, usage_change, AppPermissions, FlatpakFile, FlatpakInstallation, InstallSize,
+    InstalledRef, Permission, PermissionKind, PermissionSet, PulledRef, RefFile,
+};
+
+#[test]
+fn reports_space_freed_by_a_repair() {
+    assert_eq!(usage_change(Some(3_000_000), Some(1_000_000)), "3.0 MB -> 1.0 MB (freed 2.0 MB)");
+    assert!(usage_change(Some(1024), Some(4096)).contains("grew by"));
+    assert_eq!(usage_change(None, None), "disk usage unknown");
+}
+
+#[test]
+fn installations_map_to_flatpak_flags() {
+    assert_eq!(FlatpakInstallation::User.flag(), "--user");
+    assert_eq!(FlatpakInstallation::System.flag(), "--system");
+    assert!(FlatpakInstallation::System.needs_root());
+    assert!(!FlatpakInstallation::User.needs_root());
+}
+
+#[test]
+fn flatpak_updates_pair_with_installed_versions() {
+    let updates = "org.mozilla.firefox\t129.0\norg.gnome.Platform\t\norg.mozilla.firefox\t129.0\n";
+    let installed = "org.mozilla.firefox\t128.0\norg.gnome.Platform\t\n";
+    let parsed = parse_flatpak_updates(updates, installed);
+    assert_eq!(parsed.len(), 2);
+    assert_eq!(parsed[0].name, "org.mozilla.firefox");
+    assert_eq!(parsed[0].current_version, "128.0");
+    assert_eq!(parsed[0].new_version, "129.0");
+    assert_eq!(parsed[1].new_version, "");
+}
+
+#[test]
+fn prefers_the_configured_remote_then_flathub() {
+    let remotes = vec!["fedora".to_string(), "flathub".to_string(), "flathub-beta".to_string()];
+    assert_eq!(preferred_remote(&remotes, None), Some("flathub"));
+    assert_eq!(preferred_remote(&remotes, Some("flathub-beta")), Some("flathub-beta"));
+    assert_eq!(preferred_remote(&remotes, Some("kdeapps")), Some("flathub"));
+    assert_eq!(preferred_remote(&remotes[..1], None), Some("fedora"));
+    assert_eq!(preferred_remote(&[], None), None);
+}
+
+#[test]
+fn user_overrides_win_over_shipped_permissions() {
+    let granted = "[Context]\nshared=network;ipc;\nsockets=x11;wayland;pulseaudio;\ndevices=dri;\nfilesystems=xdg-download;home:ro;\n\n[Session Bus Policy]\norg.freedesktop.Flatpak=talk\n";
+    let overrides = "[Context]\nsockets=!x11;\nfilesystems=!home;host-etc:ro;\n";
+    let permissions = AppPermissions { granted: PermissionSet::parse(granted), overrides: PermissionSet::parse(overrides) };
+    let perm = |kind, value| Permission { kind, value };
+
+    assert!(permissions.is_enabled(perm(PermissionKind::Shared, "network")));
+    assert!(permissions.is_enabled(perm(PermissionKind::Sockets, "wayland")));
+    assert!(!permissions.is_enabled(perm(PermissionKind::Sockets, "x11")));
+    assert!(permissions.is_overridden(perm(PermissionKind::Sockets, "x11")));
+    assert!(!permissions.is_enabled(perm(PermissionKind::Filesystems, "home")));
+    assert!(permissions.is_enabled(perm(PermissionKind::Filesystems, "host-etc")));
+    assert!(!permissions.is_enabled(perm(PermissionKind::Devices, "usb")));
+    // Keys outside [Context] aren't permissions
+    assert_eq!(PermissionSet::parse(granted).entries.len(), 8);
+}
+
+#[test]
+fn permission_toggles_map_to_override_flags() {
+    let x11 = Permission { kind: PermissionKind::Sockets, value: "x11" };
+    assert_eq!(x11.override_arg(false), "--nosocket=x11");
+    assert_eq!(x11.override_arg(true), "--socket=x11");
+    let home = Permission { kind: PermissionKind::Filesystems, value: "home" };
+    assert_eq!(home.override_arg(false), "--nofilesystem=home");
+    let network = Permission { kind: PermissionKind::Shared, value: "network" };
+    assert_eq!(network.override_arg(false), "--unshare=network");
+}
+
+#[test]
+fn install_size_counts_runtimes_the_app_pulls_in() {
+    let output = "\n        Firefox - Fast, Private & Safe Web Browser\n\n\
+                  \x20         ID: org.mozilla.firefox\n\
+                  \x20        Ref: app/org.mozilla.firefox/x86_64/stable\n\
+                  \x20   Download: 112.3\u{a0}MB\n\
+                  \x20  Installed: 268.9\u{a0}MB\n\
+                  \x20    Runtime: org.freedesktop.Platform/x86_64/23.08\n\
+                  \x20        Sdk: org.freedesktop.Sdk/x86_64/23.08\n";
+    let info = parse_remote_info(output);
+    assert_eq!(info.download, Some(112_300_000));
+    assert_eq!(info.installed, Some(268_900_000));
+    assert_eq!(info.runtime.as_deref(), Some("org.freedesktop.Platform/x86_64/23.08"));
+    assert_eq!(parse_flatpak_size("356.0 kB"), Some(356_000));
+    assert_eq!(parse_flatpak_size("512 bytes"), Some(512));
+    assert_eq!(parse_flatpak_size("1.2 GiB"), None);
+
+    let mut size = InstallSize {
+        refs: vec![
+            PulledRef { name: "org.mozilla.firefox".to_string(), runtime: false, download: info.download, installed: info.installed },
+            PulledRef { name: info.runtime.unwrap(), runtime: true, download: Some(200_000_000), installed: Some(700_000_000) },
+        ],
+    };
+    assert_eq!(size.runtimes().count(), 1);
+    assert_eq!(size.totals_line(), "Download 312.3 MB, 968.9 MB on disk");
+    // A runtime the remote wouldn't describe makes the totals a lower bound
+    size.refs.push(PulledRef { name: "org.gnome.Platform/x86_64/46".to_string(), runtime: true, download: None, installed: None });
+    assert_eq!(size.totals_line(), "Download at least 312.3 MB, 968.9 MB on disk");
+    assert_eq!(size.refs[2].sizes_line(), "org.gnome.Platform/x86_64/46: size unknown");
+}
+
+#[test]
+fn flatpakrefs_name_their_app_and_any_remote_to_add() {
+    let keyfile = "[Flatpak Ref]\n\
+                   Title=GNOME Builder\n\
+                   Name=org.gnome.Builder\n\
+                   Branch=stable\n\
+                   Url=https://dl.flathub.org/repo/\n\
+                   SuggestRemoteName=flathub\n\
+                   IsRuntime=false\n";
+    let app = parse_flatpakref(keyfile).unwrap();
+    assert_eq!(app.name, "org.gnome.Builder");
+    assert_eq!(app.title.as_deref(), Some("GNOME Builder"));
+    assert_eq!(app.branch.as_deref(), Some("stable"));
+
+    // Known by URL, whatever the remote is called here
+    assert_eq!(app.new_remote("flathub-mirror\thttps://dl.flathub.org/repo\nfedora\toci+https://registry.fedoraproject.org\n"), None);
+    assert_eq!(app.new_remote("fedora\toci+https://registry.fedoraproject.org\n").as_deref(), Some("flathub"));
+    let unnamed = RefFile { suggested_remote: None, ..app };
+    assert_eq!(unnamed.new_remote("").as_deref(), Some("org.gnome.Builder-origin"));
+    assert!(parse_flatpakref("[Flatpak Repo]\nUrl=https://example.org\n").is_err());
+
+    let dir = std::env::temp_dir().join(format!("birdnest-flatpakref-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("builder.flatpakref");
+    std::fs::write(&path, keyfile).unwrap();
+    let file = FlatpakFile::open(&path).unwrap();
+    assert!(FlatpakFile::is_file_arg(path.to_str().unwrap()));
+    assert_eq!(file.name(), "org.gnome.Builder");
+    assert_eq!(file.source_args(), ["--from".to_string(), path.display().to_string()]);
+    let bundle = dir.join("app.flatpak");
+    std::fs::write(&bundle, b"").unwrap();
+    assert_eq!(FlatpakFile::open(&bundle).unwrap().source_args()[0], "--bundle");
+    assert!(FlatpakFile::open(&dir.join("app.deb")).is_err());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn installed_apps_export_from_their_installation_repository() {
+    let gimp = InstalledRef::parse(FlatpakInstallation::System, "app/org.gimp.GIMP/x86_64/stable").unwrap();
+    let file = bundle_file(std::path::Path::new("/home/me/Downloads"), "org.gimp.GIMP");
+    assert_eq!(
+        gimp.bundle_args(&file),
+        [
+            "build-bundle",
+            "--arch=x86_64",
+            "/var/lib/flatpak/repo",
+            "/home/me/Downloads/org.gimp.GIMP.flatpak",
+            "org.gimp.GIMP",
+            "stable",
+        ]
+    );
+    // Runtimes aren't apps to bundle
+    assert!(InstalledRef::parse(FlatpakInstallation::User, "runtime/org.gnome.Platform/x86_64/46").is_none());
+    assert!(InstalledRef::parse(FlatpakInstallation::User, "app/org.gimp.GIMP/x86_64").is_none());
+}
+
+#[test]
+fn finds_the_apps_a_runtime_removal_would_break() {
+    let apps = parse_app_runtimes(
+        "org.gimp.GIMP\torg.gnome.Platform/x86_64/46\n\
+         org.gnome.Maps\torg.gnome.Platform/x86_64/45\n\
+         com.spotify.Client\torg.freedesktop.Platform/x86_64/23.08\n\
+         org.gimp.GIMP\torg.gnome.Platform/x86_64/46\n",
+    );
+    assert_eq!(runtime_dependents("org.gnome.Platform", &apps, &[]), ["org.gimp.GIMP", "org.gnome.Maps"]);
+    assert_eq!(runtime_dependents("runtime/org.gnome.Platform/x86_64/46", &apps, &[]), ["org.gimp.GIMP"]);
+    assert_eq!(runtime_dependents("org.gnome.Platform//45", &apps, &["org.gnome.Maps".to_string()]), Vec::<String>::new());
+    assert!(runtime_dependents("org.gimp.GIMP", &apps, &[]).is_empty());
+}
@@ -0,0 +1,84 @@
+use birdnest_core::footprint::{parse_stat_ticks, parse_status_memory, Footprint, Programs, Sample};
+use std::os::unix::fs::{symlink, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+fn scratch(name: &str) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("birdnest-footprint-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(&root).unwrap();
+    root
+}
+
+// A /proc/<pid> entry with the given executable, arguments, CPU ticks and memory
+fn process(proc: &Path, pid: u32, exe: &Path, cmdline: &[&str], ticks: u64, kilobytes: u64) {
+    let dir = proc.join(pid.to_string());
+    std::fs::create_dir_all(&dir).unwrap();
+    symlink(exe, dir.join("exe")).unwrap();
+    std::fs::write(dir.join("cmdline"), cmdline.join("\0")).unwrap();
+    std::fs::write(
+        dir.join("stat"),
+        format!("{} (Web Content) S 1 {} {} 0 -1 4194560 500 0 0 0 {} {} 0 0 20 0 30 0", pid, pid, pid, ticks, ticks),
+    )
+    .unwrap();
+    std::fs::write(dir.join("status"), format!("Name:\tfirefox\nVmRSS:\t  {} kB\nThreads:\t30\n", kilobytes)).unwrap();
+}
+
+#[test]
+fn proc_files_give_cpu_time_and_resident_memory() {
+    let stat = "4242 (tmux: server (1)) S 1 4242 4242 0 -1 4194560 812 0 0 0 150 25 0 0 20 0 1 0 8000";
+    assert_eq!(parse_stat_ticks(stat), Some(175));
+    assert_eq!(parse_stat_ticks("4242 (bash"), None);
+    assert_eq!(parse_status_memory("Name:\tbash\nVmRSS:\t    5120 kB\n"), Some(5 * 1024 * 1024));
+    assert_eq!(parse_status_memory("Name:\tkthreadd\n"), None);
+
+    assert_eq!(Footprint::default().summary(), "Not running");
+    let footprint = Footprint { processes: 2, memory: 350_200_000, cpu: 4.4 };
+    assert_eq!(footprint.summary(), "2 processes using 350.2 MB of memory and 4% CPU");
+}
+
+#[test]
+fn processes_started_from_a_package_file_are_added_up() {
+    let root = scratch("sample");
+    let usr_bin = root.join("usr/bin");
+    std::fs::create_dir_all(&usr_bin).unwrap();
+    let firefox = usr_bin.join("firefox");
+    let meld = usr_bin.join("meld");
+    let python = usr_bin.join("python3");
+    for program in [&firefox, &meld, &python] {
+        std::fs::write(program, "").unwrap();
+        std::fs::set_permissions(program, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+    std::fs::write(usr_bin.join("README"), "").unwrap();
+    // Listed through a symlink, as dpkg lists /bin on merged-/usr systems
+    symlink(&usr_bin, root.join("bin")).unwrap();
+    let listed = |name: &str| root.join("bin").join(name).to_string_lossy().into_owned();
+
+    let programs = Programs::from_files(&[listed("firefox"), listed("meld"), listed("README"), listed("missing")]);
+    assert!(!programs.is_empty());
+
+    let firefox_path = firefox.to_string_lossy();
+    let meld_path = meld.to_string_lossy();
+    let sample = |name: &str, ticks: u64| {
+        let proc = root.join(name);
+        process(&proc, 100, &firefox, &[&firefox_path], ticks, 200_000);
+        process(&proc, 101, &firefox, &[&firefox_path, "-contentproc"], ticks, 100_000);
+        process(&proc, 200, &python, &["python3", &meld_path], ticks, 50_000);
+        process(&proc, 300, &python, &["python3", "-c", "print()"], ticks, 10_000);
+        Sample::take(&proc, &programs)
+    };
+    let before = sample("before", 100);
+    let after = sample("after", 150);
+
+    let footprint = after.since(&before, Duration::from_secs(2), 100);
+    assert_eq!(footprint.processes, 3);
+    assert_eq!(footprint.memory, 350_000 * 1024);
+    // 3 processes each used 2 x 50 ticks over 2 seconds at 100 ticks a second
+    assert_eq!(footprint.cpu, 150.0);
+
+    // Processes that start between the samples count from zero
+    let started = after.since(&Sample::default(), Duration::from_secs(2), 100);
+    assert_eq!(started.cpu, 450.0);
+
+    let _ = std::fs::remove_dir_all(root);
+}
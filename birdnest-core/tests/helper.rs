@@ -0,0 +1,89 @@
+use birdnest_core::helper::{read_replies, respond, runs, Reply, Request};
+
+fn run(program: &str, env: &[(&str, &str)]) -> Request {
+    Request::Run {
+        program: program.to_string(),
+        args: vec!["install".to_string(), "-y".to_string(), "htop".to_string()],
+        env: env.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+    }
+}
+
+#[test]
+fn only_package_tools_run_under_the_helper() {
+    assert!(runs("apt-get", &["install", "-y", "htop"]));
+    assert!(runs("flatpak", &["update", "-y", "--noninteractive", "org.gnome.Calculator"]));
+    assert!(!runs("sh", &["-c", "true"]));
+    assert!(!runs("/usr/bin/apt-get", &["update"]));
+
+    assert_eq!(run("apt-get", &[("DEBIAN_FRONTEND", "noninteractive")]).check(), Ok(()));
+    assert!(run("apt-get", &[("LD_PRELOAD", "/tmp/evil.so")]).check().is_err());
+    assert!(run("bash", &[]).check().is_err());
+    assert_eq!(Request::Stop.check(), Ok(()));
+}
+
+#[test]
+fn only_the_commands_birdnest_builds_run_under_the_helper() {
+    // As the backends, the progress bar and the proxy build them
+    assert!(runs("apt-get", &["-o", "Acquire::http::Proxy=http://proxy:3128", "install", "-y", "-t", "bookworm-backports", "htop"]));
+    assert!(runs("apt-get", &["upgrade", "-y", "-o", "APT::Status-Fd=1", "-o", "Dpkg::Use-Pty=0"]));
+    assert!(runs("pikman", &["--aur", "install", "-y", "--name", "arch", "btop"]));
+    assert!(runs("flatpak", &["install", "-y", "--noninteractive", "flathub", "org.gimp.GIMP"]));
+    assert!(runs("pacman", &["-S", "--needed", "--noconfirm", "htop"]));
+    assert!(runs("needrestart", &["-b", "-r", "l"]));
+
+    // Hooks and configuration that would run anything as root
+    assert!(!runs("apt-get", &["-o", "APT::Update::Pre-Invoke::=touch /tmp/owned", "update"]));
+    assert!(!runs("apt-get", &["-o", "Dpkg::Options::=--force-all", "install", "htop"]));
+    assert!(!runs("apt-get", &["-c", "/tmp/apt.conf", "update"]));
+    assert!(!runs("apt-get", &["--option", "Dir::Etc=/tmp", "update"]));
+    // Local packages bring their own maintainer scripts
+    assert!(!runs("apt-get", &["install", "-y", "/tmp/evil.deb"]));
+    assert!(!runs("apt", &["install", "-y", "./evil.deb"]));
+    assert!(!runs("dpkg", &["-i", "/tmp/evil.deb"]));
+    assert!(!runs("dnf", &["install", "-y", "https://example.com/evil.rpm"]));
+    assert!(!runs("pacman", &["-U", "--noconfirm", "evil.pkg.tar.zst"]));
+    assert!(!runs("flatpak", &["install", "-y", "--from", "/tmp/evil.flatpakref"]));
+    // Relative paths and .changes files apt would read from the working directory
+    assert!(!runs("apt-get", &["install", "-y", "tmp/x.changes"]));
+    assert!(!runs("apt-get", &["install", "-y", "x.changes"]));
+    assert!(!runs("apt-get", &["install", "-y", "./foo/bar"]));
+    assert!(!runs("apt-get", &["install", "-y", "foo/bar"]));
+    assert!(!runs("flatpak", &["install", "-y", "flathub", "org.gimp.GIMP//beta"]));
+    // Verbs BirdNest never asks for
+    assert!(!runs("apt-get", &["source", "htop"]));
+    assert!(!runs("pikman", &["run", "arch", "sh"]));
+    assert!(!runs("apt-get", &["install", "-t"]));
+
+    let request = Request::Run { program: "dpkg".to_string(), args: vec!["-i".to_string(), "/tmp/evil.deb".to_string()], env: Vec::new() };
+    assert!(request.check().is_err());
+}
+
+#[test]
+fn refused_requests_answer_without_running_anything() {
+    let mut out = Vec::new();
+    respond(&run("sh", &[]), &mut out).unwrap();
+    let reply: Reply = serde_json::from_slice(&out).unwrap();
+    assert_eq!(reply, Reply::Refused("The privileged helper doesn't run sh".to_string()));
+
+    let error = read_replies(out.as_slice(), &mut Vec::new(), &mut Vec::new()).unwrap_err();
+    assert_eq!(error.to_string(), "The privileged helper doesn't run sh");
+}
+
+#[test]
+fn replies_stream_to_stdout_and_stderr_until_the_exit_code() {
+    let replies = [
+        Reply::Stdout("Reading package lists...".to_string()),
+        Reply::Stderr("W: an old key".to_string()),
+        Reply::Stdout("Setting up htop (3.3.0-4) ...".to_string()),
+        Reply::Exit(0),
+    ];
+    let wire: String = replies.iter().map(|r| serde_json::to_string(r).unwrap() + "\n").collect();
+    let (mut stdout, mut stderr) = (Vec::new(), Vec::new());
+    assert_eq!(read_replies(wire.as_bytes(), &mut stdout, &mut stderr).unwrap(), 0);
+    assert_eq!(String::from_utf8(stdout).unwrap(), "Reading package lists...\nSetting up htop (3.3.0-4) ...\n");
+    assert_eq!(String::from_utf8(stderr).unwrap(), "W: an old key\n");
+
+    // A daemon that dies mid-command leaves no exit code
+    let cut = wire.lines().next().unwrap().to_string() + "\n";
+    assert!(read_replies(cut.as_bytes(), &mut Vec::new(), &mut Vec::new()).is_err());
+}
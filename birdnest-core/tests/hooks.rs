@@ -0,0 +1,42 @@
+use birdnest_core::config::Config;
+use birdnest_core::hooks::{run, Hook, HookEvent};
+
+const BASE: &str = "package_manager = \"auto\"\nauto_confirm = false\nflatpak_enabled = true\n";
+
+#[test]
+fn hooks_are_read_from_config_toml() {
+    let config = Config::from_toml(&format!(
+        "{}[[hooks]]\ncommand = \"stow -R shell\"\non = [\"install\", \"upgrade\"]\n\n[[hooks]]\ncommand = \"notify-me\"\n",
+        BASE
+    ))
+    .unwrap();
+    assert_eq!(config.hooks.len(), 2);
+    assert_eq!(config.hooks[0].on, vec![HookEvent::Install, HookEvent::Upgrade]);
+    assert!(!config.hooks[0].runs_on(HookEvent::Remove));
+    // No `on` means after every transaction
+    assert!(config.hooks[1].runs_on(HookEvent::Remove));
+
+    let read = Config::from_toml(&config.to_toml().unwrap()).unwrap();
+    assert_eq!(read.hooks, config.hooks);
+    assert!(Config::from_toml(&format!("{}[[hooks]]\ncommand = \"x\"\non = [\"purge\"]\n", BASE)).is_err());
+}
+
+#[test]
+fn hooks_get_the_packages_as_arguments_and_environment() {
+    let hooks = vec![
+        Hook { command: "echo \"$BIRDNEST_HOOK_EVENT:$BIRDNEST_FLATPAK:$BIRDNEST_PACKAGES:$#:$1\"".to_string(), on: Vec::new() },
+        Hook { command: "echo never".to_string(), on: vec![HookEvent::Install] },
+        Hook { command: "echo broken >&2; exit 3".to_string(), on: vec![HookEvent::Remove] },
+    ];
+    let packages = vec!["htop".to_string(), "vim".to_string()];
+    let outcomes = run(&hooks, HookEvent::Remove, &packages, false);
+
+    assert_eq!(outcomes.len(), 2);
+    assert_eq!(outcomes[0].output, "remove:0:htop vim:2:htop\n");
+    assert_eq!(outcomes[0].result, Ok(()));
+    assert_eq!(outcomes[1].output, "broken\n");
+    assert_eq!(outcomes[1].summary(), "Hook `echo broken >&2; exit 3` failed: exited with code 3");
+
+    let flatpak = run(&hooks[..1], HookEvent::Upgrade, &[], true);
+    assert_eq!(flatpak[0].output, "upgrade:1::0:\n");
+}
@@ -0,0 +1,43 @@
+use birdnest_core::immutable::{detect_in, SystemKind};
+use std::path::PathBuf;
+
+// A scratch root filesystem containing just the given files
+fn fake_root(name: &str, files: &[&str]) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("birdnest-immutable-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    for file in files {
+        let path = root.join(file);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, "").unwrap();
+    }
+    std::fs::create_dir_all(&root).unwrap();
+    root
+}
+
+#[test]
+fn detects_system_kind_from_markers() {
+    let traditional = fake_root("traditional", &["var/lib/dpkg/status"]);
+    let abroot = fake_root("abroot", &["usr/share/abroot/abroot.json"]);
+    let ostree = fake_root("ostree", &["run/ostree-booted"]);
+
+    assert_eq!(detect_in(&traditional), SystemKind::Traditional);
+    assert_eq!(detect_in(&abroot), SystemKind::Abroot);
+    assert_eq!(detect_in(&ostree), SystemKind::Ostree);
+
+    for root in [traditional, abroot, ostree] {
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
+
+#[test]
+fn abroot_stages_then_applies() {
+    let packages = ["htop".to_string()];
+    let commands: Vec<String> = SystemKind::Abroot.install_commands(&packages).iter().map(|c| c.display()).collect();
+    assert_eq!(commands, ["abroot pkg add htop", "abroot pkg apply"]);
+
+    let commands: Vec<String> = SystemKind::Ostree.remove_commands(&packages).iter().map(|c| c.display()).collect();
+    assert_eq!(commands, ["rpm-ostree uninstall -y htop"]);
+
+    assert!(SystemKind::Traditional.install_commands(&packages).is_empty());
+    assert!(!SystemKind::Traditional.is_immutable());
+}
@@ -0,0 +1,54 @@
+use birdnest_core::config::Config;
+use birdnest_core::install_options::{optional, InstallOptions};
+use birdnest_core::package_manager::BackendCommand;
+
+fn packages(names: &[&str]) -> Vec<String> {
+    names.iter().map(|n| n.to_string()).collect()
+}
+
+#[test]
+fn apt_flags_go_on_apt_installs_only() {
+    let options = InstallOptions {
+        no_install_recommends: true,
+        target_release: Some("noble-backports".to_string()),
+        ..InstallOptions::default()
+    };
+    let commands = options.apply_apt(vec![
+        BackendCommand::new("apt", &["install", "-y"], &packages(&["htop"])),
+        BackendCommand::new("abroot", &["pkg", "add"], &packages(&["htop"])),
+    ]);
+    assert_eq!(commands[0].display(), "apt install --no-install-recommends -t noble-backports -y htop");
+    assert_eq!(commands[1].display(), "abroot pkg add htop");
+    assert!(InstallOptions::default().apt_args().is_empty());
+}
+
+#[test]
+fn flatpak_branch_and_pikman_container_shape_their_commands() {
+    let options = InstallOptions {
+        flatpak_branch: Some("beta".to_string()),
+        pikman_container: Some("arch".to_string()),
+        ..InstallOptions::default()
+    };
+    assert_eq!(options.flatpak_ref("org.gimp.GIMP"), "org.gimp.GIMP//beta");
+    assert_eq!(options.pikman_args(), ["--name", "arch"]);
+    assert_eq!(InstallOptions::default().flatpak_ref("org.gimp.GIMP"), "org.gimp.GIMP");
+}
+
+#[test]
+fn values_that_could_pass_for_flags_are_refused() {
+    let with_release = |release: &str| InstallOptions { target_release: Some(release.to_string()), ..InstallOptions::default() };
+    assert!(with_release("bookworm-backports").check().is_ok());
+    assert!(with_release("--allow-downgrades").check().is_err());
+    assert!(with_release("stable unstable").check().is_err());
+    assert_eq!(optional("  "), None);
+    assert_eq!(optional(" beta "), Some("beta".to_string()));
+}
+
+#[test]
+fn defaults_are_saved_in_the_config() {
+    let older = "package_manager = \"auto\"\nauto_confirm = false\nflatpak_enabled = true\n";
+    assert_eq!(Config::from_toml(older).unwrap().install_options, InstallOptions::default());
+    let config = Config::from_toml(&format!("{}[install_options]\nno_install_recommends = true\n", older)).unwrap();
+    assert!(config.install_options.no_install_recommends);
+    assert_eq!(config.install_options.pikman_container, None);
+}
@@ -0,0 +1,76 @@
+use birdnest_core::manifest::{self, FlatpakApp, Manifest, PikmanPackages};
+
+fn manifest() -> Manifest {
+    Manifest {
+        version: manifest::VERSION,
+        host: "pika-laptop".to_string(),
+        created: 1714995900,
+        apt: vec!["git".to_string(), "htop".to_string()],
+        flatpak: manifest::parse_flatpak_apps(
+            "org.gimp.GIMP\tflathub\norg.kde.krita\tflathub\ncom.example.Tool\tinternal\n",
+            "flathub\thttps://dl.flathub.org/repo/\ninternal\t\n",
+        ),
+        pikman: PikmanPackages { aur: vec!["yay-bin".to_string()], ..PikmanPackages::default() },
+    }
+}
+
+#[test]
+fn manifests_round_trip_through_toml() {
+    let manifest = manifest();
+    assert_eq!(
+        manifest.flatpak[0],
+        FlatpakApp { app: "org.gimp.GIMP".to_string(), remote: Some("flathub".to_string()), url: Some("https://dl.flathub.org/repo/".to_string()) }
+    );
+    assert_eq!(manifest.flatpak[2].url, None);
+
+    let text = manifest.to_toml();
+    assert!(text.starts_with("# Installed software, exported by BirdNest"));
+    assert!(text.contains("[pikman]\naur = [\"yay-bin\"]"));
+    assert!(!text.contains("fedora"));
+    assert_eq!(Manifest::from_toml(&text).unwrap(), manifest);
+    assert_eq!(manifest.len(), 6);
+}
+
+#[test]
+fn manifests_from_elsewhere_are_checked() {
+    let minimal = Manifest::from_toml("version = 1\napt = [\"vim\"]\n").unwrap();
+    assert_eq!((minimal.apt.len(), minimal.flatpak.len()), (1, 0));
+    assert!(Manifest::from_toml("version = 2\n").is_err());
+    assert!(Manifest::from_toml("version = 1\napt = [\"--allow-unauthenticated\"]\n").is_err());
+    assert!(Manifest::from_toml("version = 1\n[pikman]\naur = [\"a b\"]\n").is_err());
+}
+
+#[test]
+fn importing_installs_only_what_is_missing() {
+    let installed = Manifest {
+        apt: vec!["git".to_string(), "bash".to_string()],
+        flatpak: vec![FlatpakApp { app: "org.kde.krita".to_string(), remote: None, url: None }],
+        ..Manifest::default()
+    };
+    let missing = manifest().missing(&installed);
+    assert_eq!(missing.apt, ["htop"]);
+    assert_eq!(missing.len(), 4);
+
+    let steps: Vec<String> = missing.steps().iter().map(|step| step.display()).collect();
+    assert_eq!(
+        steps,
+        [
+            "apt-get install -y htop",
+            "flatpak remote-add --if-not-exists flathub https://dl.flathub.org/repo/",
+            "flatpak install -y --noninteractive flathub org.gimp.GIMP",
+            "flatpak install -y --noninteractive internal com.example.Tool",
+            "pikman install --aur -y yay-bin",
+        ]
+    );
+    assert!(!missing.steps()[0].as_user && missing.steps()[1..].iter().all(|step| step.as_user));
+    assert!(manifest().missing(&manifest()).steps().is_empty());
+}
+
+#[test]
+fn pikman_listings_give_package_names() {
+    let apt_style = "Listing...\nyay-bin/aur 12.3.5-1 x86_64 [installed]\nneovim/extra 0.10.0-1 x86_64 [installed]\n";
+    assert_eq!(manifest::parse_pikman_installed(apt_style), ["yay-bin", "neovim"]);
+    let dnf_style = "Installed Packages:\npython3-numpy.x86_64  1.26.4-1.fc40  @fedora\nvim-enhanced.x86_64 2:9.1-1.fc40 @updates\n";
+    assert_eq!(manifest::parse_pikman_installed(dnf_style), ["python3-numpy", "vim-enhanced"]);
+    assert_eq!(manifest::parse_pikman_installed("build-base 0.5-r3\n"), ["build-base"]);
+}
@@ -0,0 +1,124 @@
+#![cfg(feature = "mock")]
+
+use birdnest_core::mock::MockBackend;
+use birdnest_core::package_manager::PackageBackend;
+
+fn installed_names(backend: &MockBackend) -> Vec<String> {
+    backend
+        .list_installed()
+        .unwrap()
+        .into_iter()
+        .map(|pkg| pkg.name)
+        .collect()
+}
+
+#[test]
+fn search_matches_names_and_descriptions() {
+    let backend = MockBackend::with_default_fixture();
+    let names: Vec<String> = backend.search("revision control").unwrap().into_iter().map(|p| p.name).collect();
+    assert_eq!(names, ["git", "gitk"]);
+
+    let names: Vec<String> = backend.search("FIREFOX").unwrap().into_iter().map(|p| p.name).collect();
+    assert_eq!(names, ["firefox", "firefox-esr"]);
+}
+
+#[test]
+fn install_and_remove_update_installed_list() {
+    let backend = MockBackend::with_default_fixture();
+    assert!(!installed_names(&backend).contains(&"btop".to_string()));
+
+    backend.install(&["btop".to_string()]).unwrap();
+    assert!(installed_names(&backend).contains(&"btop".to_string()));
+
+    backend.remove(&["btop".to_string(), "htop".to_string()]).unwrap();
+    let names = installed_names(&backend);
+    assert!(!names.contains(&"btop".to_string()));
+    assert!(!names.contains(&"htop".to_string()));
+
+    assert_eq!(backend.transactions(), ["install btop", "remove btop htop"]);
+}
+
+#[test]
+fn unknown_packages_fail_without_side_effects() {
+    let backend = MockBackend::with_default_fixture();
+    let before = installed_names(&backend);
+
+    assert!(backend.install(&["btop".to_string(), "no-such-package".to_string()]).is_err());
+    assert!(backend.remove(&["thunderbird".to_string()]).is_err());
+
+    assert_eq!(installed_names(&backend), before);
+    assert!(backend.transactions().is_empty());
+}
+
+#[test]
+fn fixture_json_can_be_supplied_inline() {
+    let backend = MockBackend::from_json(r#"{ "installed": [{ "name": "zsh", "version": "5.9-6" }] }"#).unwrap();
+    assert_eq!(installed_names(&backend), ["zsh"]);
+    assert!(backend.search("zsh").unwrap().is_empty());
+}
+
+#[test]
+fn descriptions_cover_installed_packages_only() {
+    let backend = MockBackend::from_json(
+        r#"{ "installed": [{ "name": "zsh", "version": "5.9-6", "description": "shell with lots of features" }, { "name": "mksh", "version": "59c-1" }],
+             "available": [{ "name": "fish", "version": "3.7.0-1", "description": "friendly interactive shell" }] }"#,
+    )
+    .unwrap();
+    let descriptions = backend.descriptions(&["zsh".to_string(), "mksh".to_string(), "fish".to_string()]).unwrap();
+    assert_eq!(descriptions.len(), 1);
+    assert_eq!(descriptions["zsh"], "shell with lots of features");
+}
+
+#[test]
+fn files_name_their_installed_package() {
+    let backend = MockBackend::with_default_fixture();
+    assert_eq!(backend.owner("/usr/bin/htop").unwrap().as_deref(), Some("htop"));
+    assert_eq!(backend.owner("/usr/bin/nothing").unwrap(), None);
+}
+
+#[test]
+fn info_covers_installed_and_available_packages() {
+    let backend = MockBackend::with_default_fixture();
+    assert_eq!(backend.info("htop").unwrap().name, "htop");
+    assert!(!backend.info("btop").unwrap().version.is_empty());
+    assert!(backend.info("no-such-package").is_err());
+    assert!(backend.install_commands(&["btop".to_string()]).is_empty());
+}
+
+#[test]
+fn upgrades_bring_installed_versions_up_to_date() {
+    let backend = MockBackend::with_default_fixture();
+    let names: Vec<String> = backend.list_upgradable().unwrap().into_iter().map(|u| u.name).collect();
+    assert_eq!(names, ["firefox", "htop"]);
+
+    backend.upgrade(&["htop".to_string()]).unwrap();
+    let remaining = backend.list_upgradable().unwrap();
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].name, "firefox");
+    assert!(backend.upgrade(&["btop".to_string()]).is_err());
+    assert_eq!(backend.transactions(), ["upgrade htop"]);
+}
+
+#[test]
+fn changelogs_come_from_the_fixture() {
+    let backend = MockBackend::with_default_fixture();
+    let entries = backend.changelog("htop").unwrap();
+    assert_eq!(entries.iter().map(|e| e.version.as_str()).collect::<Vec<_>>(), ["3.3.0-5", "3.3.0-4"]);
+    assert_eq!(entries[0].changes[1], "Show the I/O priority column again when running without root.");
+    assert!(backend.changelog("bash").is_err());
+}
+
+#[test]
+fn removal_closure_follows_dependents_of_dependents() {
+    use birdnest_core::package_manager::removal_closure;
+
+    let backend = MockBackend::from_json(
+        r#"{ "installed": [{ "name": "libvlc5", "version": "3.0.20-3" },
+                           { "name": "vlc-plugins", "version": "3.0.20-3", "depends": ["libvlc5"] },
+                           { "name": "vlc", "version": "3.0.20-3", "depends": ["vlc-plugins"] },
+                           { "name": "zsh", "version": "5.9-6" }] }"#,
+    )
+    .unwrap();
+    assert_eq!(removal_closure(&backend, &["libvlc5".to_string()]).unwrap(), ["libvlc5", "vlc", "vlc-plugins"]);
+    assert_eq!(removal_closure(&backend, &["zsh".to_string()]).unwrap(), ["zsh"]);
+}
@@ -0,0 +1,85 @@
+use std::sync::{Arc, Mutex};
+
+use birdnest_core::config::Config;
+use birdnest_core::notify::{Badge, Context, DESKTOP_ID, Notification, NotificationConfig, Notifier, NotifierKind, Notifiers, Terminal};
+use birdnest_core::update_check::UpdateCount;
+
+// Records what it was sent, or fails every time
+#[derive(Debug)]
+struct Recorder {
+    kind: NotifierKind,
+    fails: bool,
+    sent: Arc<Mutex<Vec<String>>>,
+}
+
+impl Notifier for Recorder {
+    fn kind(&self) -> NotifierKind {
+        self.kind
+    }
+
+    fn notify(&self, notification: &Notification) -> anyhow::Result<()> {
+        if self.fails {
+            anyhow::bail!("no session bus");
+        }
+        self.sent.lock().unwrap().push(notification.summary.clone());
+        Ok(())
+    }
+}
+
+#[test]
+fn transactions_and_update_checks_read_as_notifications() {
+    let done = Notification::finished("apt install -y vlc", &Ok(()));
+    assert_eq!(done.summary, "Finished: apt install -y vlc");
+    assert_eq!(Terminal::line(&done), "\x07✓ Finished: apt install -y vlc");
+    assert_eq!(Badge::properties(&done), "{'urgent': <true>}");
+
+    let failed = Notification::finished("apt install -y vlc", &Err("apt exited with code 100".to_string()));
+    assert!(failed.failed);
+    assert_eq!(failed.body, "apt exited with code 100");
+    assert_eq!(Terminal::line(&failed), "\x07✗ Failed: apt install -y vlc");
+
+    let updates = Notification::updates(&UpdateCount { packages: 2, flatpaks: 1 });
+    assert_eq!(updates.summary, "3 updates available (2 system, 1 Flatpak)");
+    assert_eq!(Badge::properties(&updates), "{'count': <int64 3>, 'count-visible': <true>}");
+}
+
+#[test]
+fn channels_are_chosen_per_frontend_in_the_config() {
+    let defaults = NotificationConfig::default();
+    assert_eq!(defaults.kinds(Context::Gui), [NotifierKind::Desktop, NotifierKind::Badge]);
+    assert_eq!(defaults.kinds(Context::Cli), [NotifierKind::Terminal]);
+
+    let config = Config::from_toml(
+        r#"package_manager = "auto"
+auto_confirm = false
+flatpak_enabled = true
+
+[notifications]
+cli = ["terminal", "desktop"]
+on_finish = false
+"#,
+    )
+    .unwrap();
+    assert_eq!(config.notifications.kinds(Context::Cli), [NotifierKind::Terminal, NotifierKind::Desktop]);
+    assert_eq!(config.notifications.kinds(Context::Gui), defaults.gui);
+    assert!(!config.notifications.on_finish);
+}
+
+#[test]
+fn one_failing_channel_does_not_stop_the_others() {
+    let sent = Arc::new(Mutex::new(Vec::new()));
+    let notifiers = Notifiers::new(vec![
+        Box::new(Recorder { kind: NotifierKind::Desktop, fails: true, sent: sent.clone() }),
+        Box::new(Recorder { kind: NotifierKind::Badge, fails: false, sent: sent.clone() }),
+    ]);
+    assert_eq!(notifiers.kinds(), [NotifierKind::Desktop, NotifierKind::Badge]);
+    notifiers.notify(&Notification::finished("flatpak update", &Ok(())));
+    notifiers.dismiss();
+    assert_eq!(*sent.lock().unwrap(), ["Finished: flatpak update"]);
+}
+
+#[test]
+fn the_badge_names_the_desktop_entry_the_installer_writes() {
+    let installer = std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/../build-and-install.sh")).unwrap();
+    assert!(installer.contains(&format!("DESKTOP_FILE=\"$DESKTOP_DIR/{}\"", DESKTOP_ID)));
+}
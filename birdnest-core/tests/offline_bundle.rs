@@ -0,0 +1,84 @@
+use std::path::Path;
+
+use birdnest_core::manifest::{FlatpakApp, Manifest};
+use birdnest_core::offline_bundle::{self, Bundle};
+
+fn bundle() -> Bundle {
+    let debs = ["vlc_3.0.20-3_amd64.deb", "libvlc5_3.0.20-3_amd64.deb", "libc6_2.36-9%3a1_amd64.deb"]
+        .iter()
+        .map(|name| offline_bundle::parse_deb_filename(name).unwrap())
+        .collect();
+    Bundle {
+        version: offline_bundle::VERSION,
+        host: "pika-desktop".to_string(),
+        created: 1714995900,
+        architecture: "amd64".to_string(),
+        apt: vec!["vlc".to_string()],
+        debs,
+        flatpak: vec![FlatpakApp {
+            app: "org.gimp.GIMP".to_string(),
+            remote: Some("flathub".to_string()),
+            url: Some("https://dl.flathub.org/repo/".to_string()),
+        }],
+    }
+}
+
+#[test]
+fn apt_cache_depends_gives_the_real_packages_reached() {
+    let output = "vlc
+  Depends: libvlc5
+  Depends: <mail-transport-agent>
+    exim4-daemon-light
+libvlc5
+  Depends: libc6
+<mail-transport-agent>
+exim4-daemon-light
+libc6
+libvlc5
+";
+    assert_eq!(offline_bundle::parse_depends_closure(output), ["vlc", "libvlc5", "exim4-daemon-light", "libc6"]);
+}
+
+#[test]
+fn deb_file_names_give_package_version_and_architecture() {
+    let libc = offline_bundle::parse_deb_filename("libc6_2.36-9%3a1_amd64.deb").unwrap();
+    assert_eq!((libc.package.as_str(), libc.version.as_str(), libc.architecture.as_str()), ("libc6", "2.36-9:1", "amd64"));
+    assert_eq!(libc.file, "debs/libc6_2.36-9%3a1_amd64.deb");
+    assert!(offline_bundle::parse_deb_filename("Packages.gz").is_none());
+    assert!(offline_bundle::parse_deb_filename("vlc_3.0.20-3.deb").is_none());
+}
+
+#[test]
+fn bundles_round_trip_and_stay_in_their_directory() {
+    let bundle = bundle();
+    assert_eq!(Bundle::from_toml(&bundle.to_toml()).unwrap(), bundle);
+    assert_eq!(bundle.summary(), "1 apt packages (3 .debs with dependencies) and 1 Flatpak apps");
+
+    let escaping = bundle.to_toml().replace("debs/libc6", "debs/../../etc/libc6");
+    assert!(Bundle::from_toml(&escaping).is_err());
+    let newer = bundle.to_toml().replace("version = 1", "version = 99");
+    assert!(Bundle::from_toml(&newer).is_err());
+}
+
+#[test]
+fn importing_installs_what_the_machine_lacks_from_the_bundle() {
+    // libc6 is there already; vlc and GIMP aren't
+    let installed = Manifest { apt: vec!["libc6".to_string()], ..Manifest::default() };
+    let steps: Vec<String> = bundle().steps(Path::new("/media/usb/bundle"), &installed).iter().map(|step| step.display()).collect();
+    assert_eq!(
+        steps,
+        [
+            "apt-get install -y /media/usb/bundle/debs/vlc_3.0.20-3_amd64.deb /media/usb/bundle/debs/libvlc5_3.0.20-3_amd64.deb",
+            "apt-mark auto libvlc5",
+            "flatpak remote-add --if-not-exists flathub https://dl.flathub.org/repo/",
+            "flatpak install -y --noninteractive --sideload-repo=/media/usb/bundle/.ostree/repo flathub org.gimp.GIMP",
+        ]
+    );
+
+    let everything = Manifest {
+        apt: vec!["vlc".to_string(), "libvlc5".to_string(), "libc6".to_string()],
+        flatpak: bundle().flatpak,
+        ..Manifest::default()
+    };
+    assert!(bundle().steps(Path::new("/media/usb/bundle"), &everything).is_empty());
+}
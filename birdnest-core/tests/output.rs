@@ -0,0 +1,67 @@
+use birdnest_core::output::{parse_apt_listing, parse_flatpak_columns, render_table, set_color, PackageRow, Source};
+
+const APT_SEARCH: &str = "Sorting...
+Full Text Search...
+hello/stable 2.10-3 amd64
+  example package based on GNU hello
+
+hello-traditional/stable,now 2.10-6 amd64 [installed]
+  example package based on GNU hello (traditional version)
+";
+
+#[test]
+fn parses_apt_search_results() {
+    let rows = parse_apt_listing(APT_SEARCH, Source::Apt);
+    assert_eq!(rows.len(), 2);
+    assert_eq!(
+        rows[0],
+        PackageRow {
+            name: "hello".to_string(),
+            version: "2.10-3".to_string(),
+            source: Source::Apt,
+            installed: false,
+            detail: "example package based on GNU hello".to_string(),
+        }
+    );
+    assert!(rows[1].installed);
+    assert_eq!(rows[1].name, "hello-traditional");
+}
+
+#[test]
+fn parses_upgradable_listing() {
+    let output = "Listing...\nbash/testing 5.2.37-1 amd64 [upgradable from: 5.2.32-1]\n";
+    let rows = parse_apt_listing(output, Source::Pikman);
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].version, "5.2.37-1");
+    assert_eq!(rows[0].detail, "upgradable from 5.2.32-1");
+    assert!(rows[0].installed);
+}
+
+#[test]
+fn parses_flatpak_columns() {
+    let output = "org.gnome.Calculator\t46.1\tPerform arithmetic\nNo matches found\n";
+    let rows = parse_flatpak_columns(output, false);
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].name, "org.gnome.Calculator");
+    assert_eq!(rows[0].source, Source::Flatpak);
+    assert_eq!(rows[0].detail, "Perform arithmetic");
+}
+
+#[test]
+fn renders_aligned_columns_without_color() {
+    set_color(false);
+    let rows = parse_apt_listing(APT_SEARCH, Source::Apt);
+    let table = render_table(&rows, None);
+    let lines: Vec<&str> = table.lines().collect();
+    assert_eq!(lines[0], "  [apt]     hello              2.10-3  example package based on GNU hello");
+    assert_eq!(lines[1], "✓ [apt]     hello-traditional  2.10-6  example package based on GNU hello (traditional version)");
+}
+
+#[test]
+fn truncates_descriptions_to_width() {
+    set_color(false);
+    let rows = parse_apt_listing(APT_SEARCH, Source::Apt);
+    let table = render_table(&rows, Some(50));
+    assert!(table.lines().all(|line| line.chars().count() <= 50), "{}", table);
+    assert!(table.contains('…'));
+}
@@ -0,0 +1,113 @@
+use birdnest_core::package_manager::{
+    parse_apt_depends, parse_apt_rdepends, parse_apt_show, parse_apt_upgradable, parse_file_list, render_details, BackendCommand, PackageDetails,
+    ReverseDependency,
+};
+
+const APT_CACHE_SHOW: &str = "\
+Package: hello
+Version: 2.10-3
+Installed-Size: 280
+Architecture: amd64
+Multi-Arch: foreign
+Maintainer: Santiago Vila <sanvila@debian.org>
+Depends: libc6 (>= 2.34)
+Description-en: example package based on GNU hello
+ The GNU hello program produces a familiar, friendly greeting.
+ .
+ Seriously, though: this is an example.
+Size: 53000
+
+Package: hello
+Version: 2.10-2
+Installed-Size: 276
+";
+
+#[test]
+fn parses_first_apt_cache_record() {
+    let details = parse_apt_show(APT_CACHE_SHOW).unwrap();
+    assert_eq!(details.name, "hello");
+    assert_eq!(details.version, "2.10-3");
+    assert_eq!(details.installed_size, Some(280 * 1024));
+    assert_eq!(details.download_size, Some(53000));
+    assert_eq!(details.maintainer.as_deref(), Some("Santiago Vila <sanvila@debian.org>"));
+    assert_eq!(details.dependencies, ["libc6"]);
+    assert_eq!(details.architecture.as_ref().map(|arch| arch.label()).as_deref(), Some("amd64, Multi-Arch: foreign"));
+    assert_eq!(
+        details.description,
+        "example package based on GNU hello The GNU hello program produces a familiar, friendly greeting. Seriously, though: this is an example."
+    );
+    assert_eq!(parse_apt_show("N: Unable to locate package nope\n"), None);
+}
+
+#[test]
+fn renders_known_fields_only() {
+    let details = PackageDetails {
+        name: "hello".to_string(),
+        version: "2.10-3".to_string(),
+        description: "greeting".to_string(),
+        installed_size: Some(2_000_000),
+        download_size: None,
+        homepage: None,
+        maintainer: None,
+        dependencies: Vec::new(),
+        architecture: None,
+    };
+    assert_eq!(
+        render_details(&details),
+        "Package: hello\nVersion: 2.10-3\nInstalled-Size: 2.0 MB\nDescription: greeting\n"
+    );
+}
+
+#[test]
+fn backend_commands_append_packages() {
+    let command = BackendCommand::new("apt-get", &["remove", "-y"], &["hello".to_string(), "htop".to_string()]);
+    assert_eq!(command.args(), ["remove", "-y", "hello", "htop"]);
+    assert_eq!(command.display(), "apt-get remove -y hello htop");
+}
+
+#[test]
+fn parses_apt_list_upgradable() {
+    let output = "\
+Listing... Done
+firefox/nest 129.0-1pika1 amd64 [upgradable from: 128.0-1pika1]
+libssl3t64/nest-updates 3.2.2-1 amd64 [upgradable from: 3.2.1-3]
+";
+    let updates = parse_apt_upgradable(output);
+    assert_eq!(updates.len(), 2);
+    assert_eq!(updates[0].name, "firefox");
+    assert_eq!(updates[0].current_version, "128.0-1pika1");
+    assert_eq!(updates[0].new_version, "129.0-1pika1");
+    assert_eq!(updates[1].name, "libssl3t64");
+}
+
+#[test]
+fn apt_depends_drop_versions_and_keep_alternatives() {
+    assert_eq!(
+        parse_apt_depends("libc6 (>= 2.34), python3:any, default-mta | mail-transport-agent"),
+        ["libc6", "python3", "default-mta | mail-transport-agent"]
+    );
+}
+
+#[test]
+fn file_lists_skip_the_dpkg_root_entry() {
+    assert_eq!(parse_file_list("/.\n/usr\n/usr/bin/hello\n\n"), ["/usr", "/usr/bin/hello"]);
+}
+
+#[test]
+fn apt_rdepends_marks_alternatives_unless_also_required() {
+    let output = "\
+libfoo1
+Reverse Depends:
+  foo-utils
+ |mail-client
+  foo-utils
+ |bar
+  bar
+";
+    let dependent = |name: &str, alternative| ReverseDependency { name: name.to_string(), alternative };
+    assert_eq!(
+        parse_apt_rdepends(output),
+        [dependent("foo-utils", false), dependent("mail-client", true), dependent("bar", false)]
+    );
+    assert!(parse_apt_rdepends("libfoo1\nReverse Depends:\n").is_empty());
+}
@@ -0,0 +1,77 @@
+use birdnest_core::package_manager::{BackendCommand, BackendKind, PackageBackend};
+use birdnest_core::pacman::{parse_pacman_descriptions, parse_pacman_info, parse_pacman_search, parse_pacman_upgrades, PacmanBackend};
+
+const PACMAN_SEARCH: &str = "\
+extra/hello 2.12.1-2 [installed]
+    Prints a friendly greeting
+aur/hello-git r12.abc123-1 (+3 0.00) (Installed)
+    Hello from git
+";
+
+const PACMAN_INFO: &str = "\
+Repository      : extra
+Name            : hello
+Version         : 2.12.1-2
+Description     : Prints a friendly greeting
+URL             : https://www.gnu.org/software/hello/
+Depends On      : glibc  ncurses>=6
+Packager        : Some Packager <packager@archlinux.org>
+Download Size   : 53.20 KiB
+Installed Size  : 180.00 KiB
+
+Repository      : extra-testing
+Name            : hello
+Version         : 2.12.1-3
+";
+
+#[test]
+fn parses_repo_and_aur_search_output() {
+    let packages = parse_pacman_search(PACMAN_SEARCH);
+    assert_eq!(packages.len(), 2);
+    assert_eq!(packages[0].name, "hello");
+    assert_eq!(packages[0].version, "2.12.1-2");
+    assert_eq!(packages[0].description, "Prints a friendly greeting");
+    assert_eq!(packages[1].name, "hello-git");
+}
+
+#[test]
+fn parses_first_pacman_info_record_and_upgrades() {
+    let details = parse_pacman_info(PACMAN_INFO).unwrap();
+    assert_eq!(details.name, "hello");
+    assert_eq!(details.version, "2.12.1-2");
+    assert_eq!(details.download_size, Some((53.2 * 1024.0) as u64));
+    assert_eq!(details.installed_size, Some(180 * 1024));
+    assert_eq!(details.dependencies, ["glibc", "ncurses"]);
+    assert_eq!(details.maintainer.as_deref(), Some("Some Packager <packager@archlinux.org>"));
+    let descriptions = parse_pacman_descriptions(PACMAN_INFO);
+    assert_eq!(descriptions.len(), 1);
+    assert_eq!(descriptions["hello"], "Prints a friendly greeting");
+
+    let upgrades = parse_pacman_upgrades("hello 2.12.1-2 -> 2.12.1-3\nlinux 6.9.1 -> 6.9.2 [ignored]\n");
+    assert_eq!(upgrades.len(), 1);
+    assert_eq!(upgrades[0].current_version, "2.12.1-2");
+    assert_eq!(upgrades[0].new_version, "2.12.1-3");
+}
+
+#[test]
+fn aur_helper_installs_run_as_the_user() {
+    let packages = vec!["hello-git".to_string()];
+
+    let repo_only = PacmanBackend { aur_helper: None };
+    assert_eq!(
+        repo_only.install_commands(&packages),
+        [BackendCommand::new("pacman", &["-S", "--needed", "--noconfirm"], &packages)]
+    );
+
+    let with_paru = PacmanBackend { aur_helper: Some("paru".to_string()) };
+    let commands = with_paru.install_commands(&packages);
+    assert_eq!(commands.len(), 1);
+    assert_eq!(commands[0].program, "paru");
+    assert!(commands[0].as_user);
+    assert!(commands[0].args.contains(&"--sudo".to_string()));
+    assert_eq!(commands[0].args.last().map(String::as_str), Some("hello-git"));
+    // Removal never needs the helper
+    assert!(!with_paru.remove_commands(&packages)[0].as_user);
+
+    assert_eq!(BackendKind::detect("auto", |p| p == "pacman"), Some(BackendKind::Pacman));
+}
@@ -0,0 +1,60 @@
+use birdnest_core::pikman_exports::{self, ExportKind};
+use std::path::Path;
+
+const DESKTOP: &str = "[Desktop Entry]
+Name=JupyterLab
+Exec=/usr/bin/distrobox-enter  -n python-data  --   jupyter-lab  %U
+Type=Application
+
+[Desktop Action new]
+Name=New notebook
+";
+
+const WRAPPER: &str = "#!/bin/sh
+# distrobox_binary
+# name: rust-dev
+if [ -z \"${CONTAINER_ID}\" ]; then
+\texec \"/usr/bin/distrobox-enter\"  -n rust-dev  --  '/usr/bin/gdb'  \"$@\"
+else
+\texec '/usr/bin/gdb' \"$@\"
+fi
+";
+
+#[test]
+fn exported_entries_name_their_container() {
+    let app = pikman_exports::parse_desktop_entry(DESKTOP, Path::new("python-data-jupyterlab.desktop")).unwrap();
+    assert_eq!((app.name.as_str(), app.container.as_str(), app.command.as_str()), ("JupyterLab", "python-data", "jupyter-lab"));
+    assert_eq!(app.kind, ExportKind::Application);
+
+    let gdb = pikman_exports::parse_binary(WRAPPER, Path::new("/home/me/.local/bin/gdb")).unwrap();
+    assert_eq!((gdb.name.as_str(), gdb.container.as_str(), gdb.command.as_str()), ("gdb", "rust-dev", "/usr/bin/gdb"));
+
+    // The host's own entries and scripts aren't exports
+    assert_eq!(pikman_exports::parse_desktop_entry("[Desktop Entry]\nName=Files\nExec=nautilus %U\n", Path::new("f.desktop")), None);
+    assert_eq!(pikman_exports::parse_binary("#!/bin/sh\nexec distrobox-enter -n box -- ls\n", Path::new("ls")), None);
+}
+
+#[test]
+fn exports_are_listed_from_both_directories() {
+    let root = std::env::temp_dir().join(format!("birdnest-exports-{}", std::process::id()));
+    let (applications, bin) = (root.join("applications"), root.join("bin"));
+    std::fs::create_dir_all(&applications).unwrap();
+    std::fs::create_dir_all(&bin).unwrap();
+    std::fs::write(applications.join("python-data-jupyterlab.desktop"), DESKTOP).unwrap();
+    std::fs::write(applications.join("org.gnome.Nautilus.desktop"), "[Desktop Entry]\nName=Files\nExec=nautilus\n").unwrap();
+    std::fs::write(bin.join("gdb"), WRAPPER).unwrap();
+
+    let names: Vec<(String, ExportKind)> = pikman_exports::list_in(&applications, &bin).into_iter().map(|app| (app.name, app.kind)).collect();
+    assert_eq!(names, [("JupyterLab".to_string(), ExportKind::Application), ("gdb".to_string(), ExportKind::Binary)]);
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn exports_are_undone_by_their_command_and_container() {
+    assert_eq!(pikman_exports::export_args("gdb", "fedora"), ["--fedora", "export", "gdb"]);
+
+    let gdb = pikman_exports::parse_binary(WRAPPER, Path::new("/home/me/.local/bin/gdb")).unwrap();
+    assert_eq!(pikman_exports::unexport_args(&gdb), ["unexport", "gdb", "--name", "rust-dev"]);
+    let app = pikman_exports::parse_desktop_entry(DESKTOP, Path::new("python-data-jupyterlab.desktop")).unwrap();
+    assert_eq!(pikman_exports::unexport_args(&app), ["unexport", "jupyter-lab", "--name", "python-data"]);
+}
@@ -0,0 +1,131 @@
+use birdnest_core::plan::{
+    follow_up_line, follow_up_removals, format_size, parse_simulation, recommended_extras, recommends_line, TransactionPlan,
+};
+
+const SIMULATION: &str = "\
+NOTE: This is only a simulation!
+      apt-get needs root privileges for real execution.
+Reading package lists...
+Building dependency tree...
+The following NEW packages will be installed:
+  git git-man liberror-perl
+The following packages will be upgraded:
+  libcurl3-gnutls
+The following packages will be REMOVED:
+  git-core
+1 upgraded, 3 newly installed, 1 to remove and 0 not upgraded.
+Remv git-core [1:2.39.2-1]
+Inst libcurl3-gnutls [7.88.1-10] (7.88.1-10+deb12u5 Debian-Security:12/stable-security [amd64])
+Inst liberror-perl (0.17029-2 Debian:12/stable [all])
+Inst git-man (1:2.39.2-1.1 Debian:12/stable [all])
+Inst git (1:2.39.2-1.1 Debian:12/stable [amd64])
+Conf liberror-perl (0.17029-2 Debian:12/stable [all])
+Conf git (1:2.39.2-1.1 Debian:12/stable [amd64])
+";
+
+#[test]
+fn simulation_output_is_split_into_install_upgrade_remove() {
+    let plan = parse_simulation(SIMULATION);
+
+    let installs: Vec<&str> = plan.install.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(installs, ["liberror-perl", "git-man", "git"]);
+    assert_eq!(plan.install[2].new_version.as_deref(), Some("1:2.39.2-1.1"));
+    assert_eq!(plan.install[2].old_version, None);
+
+    assert_eq!(plan.upgrade.len(), 1);
+    assert_eq!(plan.upgrade[0].old_version.as_deref(), Some("7.88.1-10"));
+    assert_eq!(plan.upgrade[0].new_version.as_deref(), Some("7.88.1-10+deb12u5"));
+
+    assert_eq!(plan.remove.len(), 1);
+    assert_eq!(plan.remove[0].name, "git-core");
+    assert_eq!(plan.remove[0].old_version.as_deref(), Some("1:2.39.2-1"));
+
+    assert_eq!(plan.counts_line(), "1 upgraded, 3 newly installed, 1 to remove");
+}
+
+#[test]
+fn nothing_to_do_yields_an_empty_plan() {
+    let plan = parse_simulation("Package 'nano' is not installed, so not removed\n0 upgraded, 0 newly installed, 0 to remove and 0 not upgraded.\n");
+    assert!(plan.is_empty());
+}
+
+#[test]
+fn sizes_line_reports_download_and_freed_space() {
+    let plan = TransactionPlan {
+        download_bytes: Some(12_300_000),
+        disk_delta_bytes: Some(-2_048_000),
+        ..TransactionPlan::default()
+    };
+    assert_eq!(
+        plan.sizes_line().as_deref(),
+        Some("Need to get 12.3 MB of archives. After this operation, 2.0 MB disk space will be freed.")
+    );
+    assert_eq!(format_size(999), "999 B");
+    assert_eq!(format_size(45_000), "45 kB");
+}
+
+#[test]
+fn dependencies_are_the_installs_nobody_asked_for() {
+    let mut plan = parse_simulation(SIMULATION);
+    let requested = vec!["git".to_string()];
+    let dependencies: Vec<&str> = plan.dependencies(&requested).iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(dependencies, ["liberror-perl", "git-man"]);
+    // Unknown sizes leave the size out rather than guessing
+    assert_eq!(plan.dependencies_line(&requested).as_deref(), Some("2 additional dependencies"));
+
+    plan.install[0].download_bytes = Some(25_000);
+    plan.install[0].installed_bytes = Some(90_000);
+    plan.install[1].download_bytes = Some(2_000_000);
+    plan.install[1].installed_bytes = Some(12_000_000);
+    assert_eq!(
+        plan.dependencies_line(&requested).as_deref(),
+        Some("2 additional dependencies (2.0 MB download, 12.1 MB installed)")
+    );
+
+    let everything: Vec<String> = plan.install.iter().map(|p| p.name.clone()).collect();
+    assert_eq!(plan.dependencies_line(&everything), None);
+}
+
+#[test]
+fn recommends_are_what_the_simulation_without_them_leaves_out() {
+    let with = parse_simulation(SIMULATION);
+    let mut without = with.clone();
+    without.install.retain(|p| p.name != "git-man");
+
+    let mut extras = recommended_extras(&with, &without);
+    let names: Vec<&str> = extras.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, ["git-man"]);
+    assert_eq!(recommends_line(&extras, false).as_deref(), Some("Recommends add 1 package"));
+
+    extras[0].installed_bytes = Some(12_000_000);
+    assert_eq!(
+        recommends_line(&extras, true).as_deref(),
+        Some("Leaving out 1 recommended package (12.0 MB installed)")
+    );
+    assert_eq!(recommends_line(&recommended_extras(&with, &with), false), None);
+}
+
+#[test]
+fn follow_up_is_what_only_becomes_unused_after_the_removal() {
+    let without = parse_simulation("Remv vlc [3.0.20-1]\n");
+    let with = parse_simulation("Remv vlc [3.0.20-1]\nRemv vlc-data [3.0.20-1]\nRemv libvlc5 [3.0.20-1]\nRemv linux-image-6.1.0-9 [6.1.27-1]\n");
+    // Already unused before the removal, so not something it leaves behind
+    let unused = parse_simulation("Remv linux-image-6.1.0-9 [6.1.27-1]\n");
+    let requested = vec!["vlc".to_string()];
+
+    let mut follow_up = follow_up_removals(&with, &without, &unused);
+    let names: Vec<&str> = follow_up.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, ["vlc-data", "libvlc5"]);
+    assert_eq!(
+        follow_up_line(&requested, &follow_up).as_deref(),
+        Some("Removing vlc will later allow removing vlc-data, libvlc5")
+    );
+
+    follow_up[0].installed_bytes = Some(8_000_000);
+    follow_up[1].installed_bytes = Some(4_000_000);
+    assert_eq!(
+        follow_up_line(&requested, &follow_up).as_deref(),
+        Some("Removing vlc will later allow removing vlc-data, libvlc5 (12.0 MB freed)")
+    );
+    assert_eq!(follow_up_line(&requested, &follow_up_removals(&without, &without, &unused)), None);
+}
@@ -0,0 +1,39 @@
+use birdnest_core::ppa::{self, Ppa};
+use birdnest_core::repos::parse;
+use std::path::Path;
+
+#[test]
+fn parses_ppa_names_and_their_sources() {
+    let ppa = Ppa::parse("ppa:graphics-drivers/ppa").unwrap();
+    assert_eq!(ppa, Ppa::parse("ppa:graphics-drivers").unwrap());
+    assert_eq!(ppa.uri(), "https://ppa.launchpadcontent.net/graphics-drivers/ppa/ubuntu");
+    assert_eq!(ppa.keyring_path(), Path::new("/etc/apt/keyrings/graphics-drivers-ubuntu-ppa.asc"));
+    assert!(Ppa::parse("ppa:owner/name\nURIs: http://evil").is_err());
+    assert!(Ppa::parse("ppa:").is_err());
+
+    let source = ppa.source("noble").render();
+    assert!(source.contains("Suites: noble\nComponents: main\nSigned-By: /etc/apt/keyrings/graphics-drivers-ubuntu-ppa.asc\n"));
+
+    // Sources written by add-apt-repository on older releases use the old host
+    let list = "deb http://ppa.launchpad.net/mozillateam/ppa/ubuntu jammy main\ndeb https://repo.example.com/apt stable main\n";
+    let sources = parse(Path::new("/etc/apt/sources.list.d/mozillateam.list"), list);
+    let ppas = ppa::list(&sources);
+    assert_eq!(ppas.len(), 1);
+    assert_eq!(ppas[0].0.spec(), "ppa:mozillateam/ppa");
+}
+
+#[test]
+fn checks_the_key_against_launchpads_fingerprint() {
+    let api = r#"{"name": "ppa", "signing_key_fingerprint": "0ab215679c571d1c8325275b9bdb3d89ce49ec21"}"#;
+    assert_eq!(ppa::parse_fingerprint(api).unwrap(), "0AB215679C571D1C8325275B9BDB3D89CE49EC21");
+    assert!(ppa::parse_fingerprint(r#"{"signing_key_fingerprint": null}"#).is_err());
+
+    let colons = "\
+pub:-:4096:1:9BDB3D89CE49EC21:1224697040:::-:::scSC::::::23::0:
+fpr:::::::::0AB215679C571D1C8325275B9BDB3D89CE49EC21:
+uid:-::::1224697040::0B2DFF6B06DA1EF96DB9DD4E0327C7C21F9EAA4B::Launchpad PPA for Mozilla Team::::::::::0:
+sub:-:2048:1:1111222233334444:1224697040::::::e::::::23:
+fpr:::::::::AAAABBBBCCCCDDDDEEEEFFFF1111222233334444:
+";
+    assert_eq!(ppa::primary_fingerprints(colons), ["0AB215679C571D1C8325275B9BDB3D89CE49EC21"]);
+}
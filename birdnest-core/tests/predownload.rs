@@ -0,0 +1,41 @@
+use birdnest_core::flatpak::{InstallSize, PulledRef};
+use birdnest_core::predownload::{self, PendingRuntime, OVERNIGHT};
+use birdnest_core::schedule::{Decision, HourWindow, RefreshSchedule};
+
+fn pulled(name: &str, runtime: bool) -> PulledRef {
+    PulledRef { name: name.to_string(), runtime, download: Some(1), installed: Some(1) }
+}
+
+#[test]
+fn runtimes_queue_once_for_every_app_that_needs_them() {
+    let gnome = InstallSize { refs: vec![pulled("org.gnome.Builder", false), pulled("org.gnome.Platform/x86_64/46", true)] };
+    let mut pending = Vec::new();
+    predownload::merge(&mut pending, "org.gnome.Builder", "flathub", &gnome);
+    predownload::merge(&mut pending, "org.gnome.Builder", "flathub", &gnome);
+    predownload::merge(&mut pending, "org.gnome.Maps", "flathub", &gnome);
+    // An app whose runtime is installed adds nothing
+    predownload::merge(&mut pending, "org.kde.kate", "flathub", &InstallSize { refs: vec![pulled("org.kde.kate", false)] });
+
+    assert_eq!(
+        pending,
+        [PendingRuntime {
+            runtime: "org.gnome.Platform/x86_64/46".to_string(),
+            remote: "flathub".to_string(),
+            apps: vec!["org.gnome.Builder".to_string(), "org.gnome.Maps".to_string()],
+        }]
+    );
+}
+
+#[test]
+fn predownloads_run_overnight_unless_the_schedule_sets_hours() {
+    let schedule = predownload::schedule(&RefreshSchedule::default());
+    assert_eq!(schedule.hours, Some(OVERNIGHT));
+    assert_eq!(schedule.check(3, None), Decision::Allowed);
+    assert_eq!(schedule.check(14, None), Decision::OutsideHours(OVERNIGHT));
+
+    let evenings = HourWindow { start: 20, end: 23 };
+    let refresh = RefreshSchedule { hours: Some(evenings), unmetered_only: true, ..Default::default() };
+    let schedule = predownload::schedule(&refresh);
+    assert_eq!(schedule.check(21, Some(true)), Decision::Metered);
+    assert_eq!(schedule.check(21, Some(false)), Decision::Allowed);
+}
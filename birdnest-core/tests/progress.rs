@@ -0,0 +1,91 @@
+use birdnest_core::progress::{parse_apt_status, AptStatus};
+
+#[test]
+fn parses_download_and_dpkg_status_lines() {
+    assert_eq!(
+        parse_apt_status("dlstatus:1:42.5:Retrieving file 1 of 3"),
+        Some(AptStatus::Download { percent: 42.5, message: "Retrieving file 1 of 3".to_string() })
+    );
+    assert_eq!(
+        parse_apt_status("pmstatus:hello:75:Configuring hello (amd64)"),
+        Some(AptStatus::Package {
+            package: "hello".to_string(),
+            percent: 75.0,
+            message: "Configuring hello (amd64)".to_string(),
+        })
+    );
+}
+
+#[test]
+fn keeps_colons_in_messages() {
+    assert_eq!(
+        parse_apt_status("pmerror:/var/cache/apt/archives/foo.deb:50:trying to overwrite '/usr/bin/foo': file exists"),
+        Some(AptStatus::Error {
+            package: "/var/cache/apt/archives/foo.deb".to_string(),
+            message: "trying to overwrite '/usr/bin/foo': file exists".to_string(),
+        })
+    );
+}
+
+#[test]
+fn ignores_ordinary_output() {
+    assert_eq!(parse_apt_status("Reading package lists..."), None);
+    assert_eq!(parse_apt_status("Setting up hello (2.10-3) ..."), None);
+    assert_eq!(parse_apt_status("media-change:cdrom:Debian:/dev/sr0"), None);
+}
+
+#[test]
+fn transaction_progress_follows_dpkg_phases_and_flatpak_operations() {
+    use birdnest_core::progress::TransactionProgress;
+
+    let mut progress = TransactionProgress::new(&["vlc".to_string(), "org.gnome.Calculator".to_string()]);
+    assert!(progress.feed("dlstatus:1:100:Retrieving file 2 of 2"));
+    assert_eq!(progress.packages()[0].fraction, 0.25);
+    assert!(progress.feed("pmstatus:libvlc5:20:Unpacking libvlc5 (amd64)"));
+    assert!(progress.feed("pmstatus:vlc:90:Configuring vlc (amd64)"));
+    assert!(progress.feed("pmstatus:dpkg-exec:95:Running dpkg"));
+    assert!(!progress.feed("Setting up vlc (3.0.20-3) ..."));
+    // Dependencies get a bar of their own as they turn up
+    let names: Vec<&str> = progress.packages().iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, ["vlc", "org.gnome.Calculator", "libvlc5"]);
+    assert_eq!(progress.packages()[0].fraction, 0.8);
+
+    assert!(!progress.feed("Updating app/org.gnome.Calculator/x86_64/stable"));
+    assert!(progress.feed("\r 10%\r 60%"));
+    assert_eq!(progress.packages()[1].fraction, 0.6);
+    assert!(!progress.feed("Updates complete."));
+    assert_eq!(progress.packages()[1].fraction, 1.0);
+
+    progress.finish();
+    assert_eq!(progress.overall(), 1.0);
+}
+
+#[test]
+fn transaction_progress_follows_removals() {
+    use birdnest_core::progress::TransactionProgress;
+
+    let mut progress = TransactionProgress::new(&["vlc".to_string()]);
+    assert_eq!(progress.status(), None);
+    assert!(progress.feed("pmstatus:vlc:10:Preparing for removal of vlc (amd64)"));
+    assert_eq!(progress.packages()[0].fraction, 0.25);
+    assert!(progress.feed("pmstatus:vlc:40:Removing vlc (amd64)"));
+    assert_eq!(progress.packages()[0].fraction, 0.6);
+    assert_eq!(progress.status(), Some("Removing vlc (amd64)"));
+    assert!(progress.feed("pmstatus:libvlc5:60:Completely removing libvlc5 (amd64)"));
+    assert!(progress.feed("pmstatus:vlc:90:Removed vlc (amd64)"));
+    assert_eq!(progress.packages()[0].fraction, 1.0);
+    assert_eq!(progress.packages()[1].fraction, 0.6);
+    // dpkg's own lines stay in the log
+    assert!(!progress.feed("Removing vlc (3.0.20-3) ..."));
+}
+
+#[test]
+fn status_fd_is_only_added_to_apt() {
+    use birdnest_core::package_manager::BackendCommand;
+    use birdnest_core::progress::with_status_fd;
+
+    let apt = with_status_fd(BackendCommand::new("apt-get", &["install", "--only-upgrade", "-y"], &["hello".to_string()]));
+    assert_eq!(apt.args(), ["-o", "APT::Status-Fd=1", "-o", "Dpkg::Use-Pty=0", "install", "--only-upgrade", "-y", "hello"]);
+    let dnf = with_status_fd(BackendCommand::new("dnf", &["upgrade", "-y"], &[]));
+    assert_eq!(dnf.args(), ["upgrade", "-y"]);
+}
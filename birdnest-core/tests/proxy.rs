@@ -0,0 +1,88 @@
+use birdnest_core::elevation::ElevationMethod;
+use birdnest_core::helper::{self, Request};
+use birdnest_core::proxy::{self, ProxyConfig};
+use birdnest_core::utils::CommandBuilder;
+
+#[test]
+fn apt_config_dump_gives_the_default_proxies() {
+    let dump = r#"APT "";
+APT::Architecture "amd64";
+Acquire::http::Proxy "http://proxy.example.com:3128/";
+Acquire::http::Proxy::deb.example.com "DIRECT";
+Acquire::https::Proxy "DIRECT";
+Acquire::Retries "3";
+"#;
+    let apt = proxy::parse_apt_config(dump);
+    assert_eq!(apt.http.as_deref(), Some("http://proxy.example.com:3128/"));
+    // DIRECT turns the HTTPS proxy off rather than naming one
+    assert_eq!(apt.https, None);
+    assert_eq!(apt.describe(), "http://proxy.example.com:3128/");
+
+    assert!(!proxy::parse_apt_config("APT::Architecture \"amd64\";\n").is_set());
+}
+
+#[test]
+fn https_goes_through_the_http_proxy_unless_it_has_its_own() {
+    let shared = ProxyConfig { http: Some("http://proxy:3128".to_string()), https: None };
+    assert_eq!(shared.https_or_http(), Some("http://proxy:3128"));
+
+    let separate = ProxyConfig { https: Some("http://secure:8080".to_string()), ..shared };
+    assert_eq!(separate.https_or_http(), Some("http://secure:8080"));
+    assert_eq!(separate.describe(), "http://proxy:3128 (HTTPS: http://secure:8080)");
+    assert_eq!(ProxyConfig::default().describe(), "none");
+}
+
+#[test]
+fn typed_proxies_must_be_urls() {
+    assert_eq!(proxy::parse("  ").unwrap(), None);
+    assert_eq!(proxy::parse(" http://proxy:3128 ").unwrap().as_deref(), Some("http://proxy:3128"));
+    assert!(proxy::parse("socks5h://localhost:1080").is_ok());
+    assert!(proxy::parse("proxy:3128").is_err());
+    assert!(proxy::parse("http://").is_err());
+    assert!(proxy::parse("http://proxy 3128").is_err());
+}
+
+// The only test here that configures a proxy
+#[test]
+fn a_configured_proxy_reaches_commands_and_apt() {
+    proxy::apply(&ProxyConfig { http: Some("http://proxy:3128".to_string()), https: None });
+    // Set on each command rather than on this process
+    let (flatpak, _) = CommandBuilder::new("flatpak").args(&["update"]).build().unwrap();
+    let envs: Vec<_> = flatpak.get_envs().filter_map(|(key, value)| Some((key.to_str()?, value?.to_str()?))).collect();
+    assert!(envs.contains(&("http_proxy", "http://proxy:3128")) && envs.contains(&("https_proxy", "http://proxy:3128")));
+
+    // sudo clears the environment, and apt's own configuration would win over it
+    let builder = CommandBuilder::new("apt-get").args(&["update"]).elevated(true);
+    let sudo = builder.build_with(ElevationMethod::Sudo).unwrap();
+    let args: Vec<_> = sudo.get_args().collect();
+    assert_eq!(
+        args,
+        [
+            "env",
+            "http_proxy=http://proxy:3128",
+            "https_proxy=http://proxy:3128",
+            "apt-get",
+            "-o",
+            "Acquire::http::Proxy=http://proxy:3128",
+            "-o",
+            "Acquire::https::Proxy=http://proxy:3128",
+            "update",
+        ]
+    );
+    // Flatpak reads the environment alone
+    assert!(proxy::options("flatpak").is_empty());
+
+    // The helper forwards the proxy from the client's environment
+    let request = Request::Run {
+        program: "apt-get".to_string(),
+        args: vec!["update".to_string()],
+        env: vec![("http_proxy".to_string(), "http://proxy:3128".to_string())],
+    };
+    assert!(helper::ENV_VARS.contains(&"https_proxy"));
+    assert_eq!(request.check(), Ok(()));
+
+    // Clearing it puts back what BirdNest was started with, here nothing
+    proxy::apply(&ProxyConfig::default());
+    assert!(proxy::env().is_empty());
+    assert!(proxy::options("apt-get").is_empty());
+}
@@ -0,0 +1,117 @@
+use birdnest_core::repos::{self, apply_in, load_from, parse, Edit, Format, NewSource};
+use std::fs;
+use std::path::Path;
+
+const LIST: &str = "\
+# Ubuntu archive
+deb http://archive.ubuntu.com/ubuntu noble main restricted universe
+# deb-src http://archive.ubuntu.com/ubuntu noble main restricted
+deb [arch=amd64 signed-by=/usr/share/keyrings/docker.gpg] https://download.docker.com/linux/ubuntu noble stable # Docker
+";
+
+const SOURCES: &str = "\
+Types: deb
+URIs: https://ppa.pika-os.com/
+Suites: pika
+Components: canary
+
+# Vendor repository
+Types: deb deb-src
+URIs: https://repo.example.com/apt
+Suites: stable
+Components: main extras
+Signed-By: /usr/share/keyrings/example.gpg
+";
+
+#[test]
+fn reads_one_line_and_deb822_entries() {
+    let list = parse(Path::new("/etc/apt/sources.list"), LIST);
+    assert_eq!(list.len(), 3);
+    assert_eq!(list[0].components, ["main", "restricted", "universe"]);
+    assert!(!list[1].enabled && list[1].types == ["deb-src"]);
+    // Options and trailing comments aren't components
+    assert_eq!(list[2].components, ["stable"]);
+    assert!(list[2].is_third_party() && !list[0].is_third_party());
+    assert_eq!(list[0].offered_components(), ["main", "restricted", "universe", "multiverse"]);
+
+    let sources = parse(Path::new("/etc/apt/sources.list.d/vendor.sources"), SOURCES);
+    assert_eq!(sources.len(), 2);
+    assert_eq!(sources[0].format, Format::Deb822);
+    assert!(!sources[0].is_third_party());
+    assert_eq!(sources[1].types, ["deb", "deb-src"]);
+    assert_eq!(sources[1].summary(), "deb deb-src https://repo.example.com/apt stable main extras");
+}
+
+#[test]
+fn edits_keep_the_rest_of_the_file() {
+    let disabled = repos::set_enabled(LIST, Format::OneLine, 0, false).unwrap();
+    assert!(disabled.contains("# deb http://archive.ubuntu.com/ubuntu noble main restricted universe\n"));
+    assert!(disabled.starts_with("# Ubuntu archive\n"));
+
+    let added = repos::set_component(LIST, Format::OneLine, 0, "multiverse", true).unwrap();
+    assert!(added.contains("noble main restricted universe multiverse\n"));
+    assert!(repos::set_component(LIST, Format::OneLine, 2, "stable", false).is_err());
+
+    let stanza = repos::set_enabled(SOURCES, Format::Deb822, 1, false).unwrap();
+    assert!(stanza.contains("Signed-By: /usr/share/keyrings/example.gpg\nEnabled: no\n"));
+    assert!(!parse(Path::new("x.sources"), &stanza)[1].enabled);
+    let enabled = repos::set_enabled(&stanza, Format::Deb822, 1, true).unwrap();
+    assert_eq!(enabled, SOURCES);
+
+    let components = repos::set_component(SOURCES, Format::Deb822, 1, "extras", false).unwrap();
+    assert!(components.contains("Components: main\n"));
+
+    let removed = repos::remove_entry(SOURCES, Format::Deb822, 0).unwrap();
+    assert!(removed.starts_with("# Vendor repository\n"));
+    assert_eq!(parse(Path::new("x.sources"), &removed).len(), 1);
+}
+
+#[test]
+fn new_sources_come_from_a_url_or_a_pasted_line() {
+    let line = "deb [signed-by=/usr/share/keyrings/example.gpg] https://repo.example.com/apt stable main";
+    let source = NewSource::from_line(line).unwrap();
+    assert_eq!(source.signed_by.as_deref(), Some("/usr/share/keyrings/example.gpg"));
+    assert_eq!(source.file_name(), "repo-example-com-apt.sources");
+    assert_eq!(
+        source.render(),
+        "Types: deb\nURIs: https://repo.example.com/apt\nSuites: stable\nComponents: main\nSigned-By: /usr/share/keyrings/example.gpg\n"
+    );
+
+    let bad = NewSource { uri: "example.com".to_string(), suite: "stable".to_string(), components: vec!["main".to_string()], signed_by: None };
+    assert!(bad.validate().is_err());
+    let injected = NewSource { suite: "stable\nURIs: http://evil".to_string(), uri: "https://example.com".to_string(), ..bad };
+    assert!(injected.validate().is_err());
+
+    let edit = Edit::SetComponent { file: "/etc/apt/sources.list".into(), entry: 0, component: "universe".to_string(), enabled: false };
+    assert_eq!(edit.args(), ["sources", "disable", "/etc/apt/sources.list", "--entry", "0", "--component", "universe"]);
+    assert_eq!(Edit::Restore { backup: "1714999500".to_string() }.args(), ["sources", "restore", "1714999500"]);
+}
+
+#[test]
+fn applies_edits_only_to_apt_sources_files() {
+    let dir = std::env::temp_dir().join(format!("birdnest-repos-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("sources.list.d")).unwrap();
+    fs::write(dir.join("sources.list"), LIST).unwrap();
+    fs::write(dir.join("sources.list.d/vendor.sources"), SOURCES).unwrap();
+
+    let source = NewSource::from_line("deb https://apt.example.org/repo bookworm main").unwrap();
+    apply_in(&dir, &Edit::Add(source.clone())).unwrap();
+    assert!(apply_in(&dir, &Edit::Add(source)).is_err());
+    assert_eq!(load_from(&dir).unwrap().len(), 6);
+
+    // The distribution's own sources can only be disabled
+    let vendor = dir.join("sources.list.d/vendor.sources");
+    assert!(apply_in(&dir, &Edit::Remove { file: vendor.clone(), entry: 0 }).is_err());
+    apply_in(&dir, &Edit::Remove { file: vendor.clone(), entry: 1 }).unwrap();
+    apply_in(&dir, &Edit::SetEnabled { file: vendor.clone(), entry: 0, enabled: false }).unwrap();
+    let added = dir.join("sources.list.d/apt-example-org-repo.sources");
+    apply_in(&dir, &Edit::Remove { file: added.clone(), entry: 0 }).unwrap();
+    assert!(!added.exists());
+
+    let sources = load_from(&dir).unwrap();
+    assert_eq!(sources.len(), 4);
+    assert!(!sources[3].enabled);
+    assert!(apply_in(&dir, &Edit::SetEnabled { file: "/etc/passwd".into(), entry: 0, enabled: false }).is_err());
+    fs::remove_dir_all(&dir).unwrap();
+}
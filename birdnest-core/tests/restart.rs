@@ -0,0 +1,74 @@
+use birdnest_core::restart::{check_in, maps_outdated, parse_needrestart, unit_from_cgroup};
+use std::path::PathBuf;
+
+// A scratch root filesystem with the given files and contents
+fn fake_root(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("birdnest-restart-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    for (file, contents) in files {
+        let path = root.join(file);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+    std::fs::create_dir_all(&root).unwrap();
+    root
+}
+
+#[test]
+fn reboot_flag_and_new_kernel_need_a_reboot() {
+    let clean = fake_root("clean", &[("boot/vmlinuz-6.11.0-pikaos", "")]);
+    let status = check_in(&clean, "6.11.0-pikaos");
+    assert!(status.is_empty());
+
+    let pending = fake_root(
+        "pending",
+        &[
+            ("run/reboot-required", ""),
+            ("run/reboot-required.pkgs", "linux-image-6.12.0-pikaos\nlibc6\nlibc6\n"),
+            ("boot/vmlinuz-6.12.0-pikaos", ""),
+        ],
+    );
+    let status = check_in(&pending, "6.11.0-pikaos");
+    assert!(status.needs_reboot());
+    assert_eq!(status.reboot_packages, ["linux-image-6.12.0-pikaos", "libc6"]);
+    assert_eq!(status.kernel, Some(("6.11.0-pikaos".to_string(), "6.12.0-pikaos".to_string())));
+    assert_eq!(status.summary().len(), 2);
+
+    for root in [clean, pending] {
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
+
+#[test]
+fn parses_needrestart_batch_output() {
+    let output = "\
+NEEDRESTART-VER: 3.6
+NEEDRESTART-KCUR: 6.11.0-pikaos
+NEEDRESTART-KEXP: 6.12.0-pikaos
+NEEDRESTART-KSTA: 3
+NEEDRESTART-SVC: ssh.service
+NEEDRESTART-SVC: cron.service
+";
+    let report = parse_needrestart(output);
+    assert_eq!(report.services, ["cron.service", "ssh.service"]);
+    assert_eq!(report.kernel, Some(("6.11.0-pikaos".to_string(), "6.12.0-pikaos".to_string())));
+
+    // KSTA 1: the running kernel is the newest one
+    let report = parse_needrestart("NEEDRESTART-KCUR: 6.12.0-pikaos\nNEEDRESTART-KEXP: 6.12.0-pikaos\nNEEDRESTART-KSTA: 1\n");
+    assert!(report.kernel.is_none());
+    assert!(report.services.is_empty());
+}
+
+#[test]
+fn finds_services_mapping_replaced_libraries() {
+    let current = "\
+7f1c2a000000-7f1c2a028000 r--p 00000000 fd:01 1311 /usr/lib/x86_64-linux-gnu/libc.so.6
+7f1c2a400000-7f1c2a401000 rw-s 00000000 00:01 2048 /memfd:wayland-shm (deleted)
+";
+    let outdated = "7f1c2a000000-7f1c2a028000 r--p 00000000 fd:01 1311 /usr/lib/x86_64-linux-gnu/libssl.so.3 (deleted)\n";
+    assert!(!maps_outdated(current));
+    assert!(maps_outdated(outdated));
+
+    assert_eq!(unit_from_cgroup("0::/system.slice/cron.service\n"), Some("cron.service".to_string()));
+    assert_eq!(unit_from_cgroup("0::/user.slice/user-1000.slice/session-2.scope\n"), None);
+}
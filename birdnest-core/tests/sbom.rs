@@ -0,0 +1,77 @@
+use birdnest_core::sbom::{self, ComponentKind, Sbom, SbomComponent, SbomFormat};
+use serde_json::Value;
+
+fn inventory() -> Sbom {
+    let mut components = sbom::parse_dpkg_inventory(
+        "ii \thtop\t3.3.0-4\tamd64\tDaniel Lange <DLange@debian.org>\n\
+         rc \told-tool\t1.0\tamd64\t\n\
+         hi \tgit\t1:2.43.0-1\tamd64\tJonathan Nieder <jrnieder@gmail.com>\n",
+    );
+    components.extend(sbom::parse_flatpak_inventory("org.gimp.GIMP\t2.10.38\tstable\tx86_64\tflathub\n", false));
+    components.extend(sbom::parse_flatpak_inventory("org.gnome.Platform\t\t46\tx86_64\tflathub\n", true));
+    components.push(SbomComponent {
+        name: "gdb".to_string(),
+        version: None,
+        arch: None,
+        supplier: None,
+        kind: ComponentKind::Exported { container: "rust-dev".to_string(), command: "/usr/bin/gdb".to_string() },
+    });
+    Sbom {
+        host: "pika-laptop".to_string(),
+        distro: "pika".to_string(),
+        created: 1714995900,
+        serial: "3f1c0d9e-5b1a-4c8e-9a53-3c2d1e0f4b7a".to_string(),
+        components,
+    }
+}
+
+#[test]
+fn formats_are_named_like_their_specs() {
+    assert_eq!(SbomFormat::parse("SPDX").unwrap(), SbomFormat::Spdx);
+    assert_eq!(SbomFormat::parse("cdx").unwrap(), SbomFormat::CycloneDx);
+    assert!(SbomFormat::parse("xml").is_err());
+}
+
+#[test]
+fn inventories_keep_installed_packages_with_package_urls() {
+    let sbom = inventory();
+    let names: Vec<&str> = sbom.components.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(names, ["htop", "git", "org.gimp.GIMP", "org.gnome.Platform", "gdb"]);
+
+    let purls: Vec<Option<String>> = sbom.components.iter().map(|c| c.purl(&sbom.distro)).collect();
+    assert_eq!(purls[0].as_deref(), Some("pkg:deb/pika/htop@3.3.0-4?arch=amd64"));
+    // The epoch's colon is encoded
+    assert_eq!(purls[1].as_deref(), Some("pkg:deb/pika/git@1%3A2.43.0-1?arch=amd64"));
+    assert_eq!(purls[2].as_deref(), Some("pkg:generic/flatpak/org.gimp.GIMP@2.10.38?arch=x86_64&branch=stable&repository=flathub"));
+    assert_eq!(purls[3].as_deref(), Some("pkg:generic/flatpak/org.gnome.Platform?arch=x86_64&branch=46&repository=flathub"));
+    assert_eq!(purls[4], None);
+}
+
+#[test]
+fn spdx_documents_describe_every_component() {
+    let document: Value = serde_json::from_str(&inventory().render(SbomFormat::Spdx)).unwrap();
+    assert_eq!(document["spdxVersion"], "SPDX-2.3");
+    assert_eq!(document["creationInfo"]["created"], "2024-05-06T11:45:00Z");
+    let packages = document["packages"].as_array().unwrap();
+    assert_eq!(packages.len(), 5);
+    assert_eq!(packages[0]["SPDXID"], "SPDXRef-apt-1-htop");
+    assert_eq!(packages[0]["supplier"], "Person: Daniel Lange <DLange@debian.org>");
+    assert_eq!(packages[0]["externalRefs"][0]["referenceLocator"], "pkg:deb/pika/htop@3.3.0-4?arch=amd64");
+    assert_eq!(packages[4]["versionInfo"], "NOASSERTION");
+    assert_eq!(packages[4]["comment"], "Runs `/usr/bin/gdb` in the pikman container rust-dev");
+    assert_eq!(document["relationships"].as_array().unwrap().len(), 5);
+}
+
+#[test]
+fn cyclonedx_documents_mark_runtimes_and_containers() {
+    let document: Value = serde_json::from_str(&inventory().render(SbomFormat::CycloneDx)).unwrap();
+    assert_eq!(document["bomFormat"], "CycloneDX");
+    assert_eq!(document["serialNumber"], "urn:uuid:3f1c0d9e-5b1a-4c8e-9a53-3c2d1e0f4b7a");
+    let components = document["components"].as_array().unwrap();
+    assert_eq!(components[0]["type"], "library");
+    assert_eq!(components[2]["type"], "application");
+    assert_eq!(components[3]["type"], "framework");
+    assert!(components[3].get("version").is_none());
+    let properties = components[4]["properties"].as_array().unwrap();
+    assert!(properties.iter().any(|p| p["name"] == "birdnest:container" && p["value"] == "rust-dev"));
+}
@@ -0,0 +1,44 @@
+use birdnest_core::config::Config;
+use birdnest_core::schedule::{parse_nm_metered, Decision, HourWindow, RefreshSchedule};
+
+#[test]
+fn hour_windows_wrap_past_midnight() {
+    let night = HourWindow { start: 22, end: 6 };
+    assert!(night.contains(23) && night.contains(0) && night.contains(5));
+    assert!(!night.contains(6) && !night.contains(12));
+
+    let day = HourWindow { start: 9, end: 17 };
+    assert!(day.contains(9) && !day.contains(17));
+    assert_eq!(night.to_string(), "22:00-06:00");
+}
+
+#[test]
+fn schedule_rules_out_other_hours_and_metered_connections() {
+    let anytime = RefreshSchedule::default();
+    assert_eq!(anytime.check(14, Some(true)), Decision::Allowed);
+
+    let schedule = RefreshSchedule {
+        every_hours: Some(4),
+        hours: Some(HourWindow { start: 1, end: 6 }),
+        unmetered_only: true,
+    };
+    assert_eq!(schedule.check(14, Some(false)), Decision::OutsideHours(HourWindow { start: 1, end: 6 }));
+    assert_eq!(schedule.check(3, Some(true)), Decision::Metered);
+    // Without NetworkManager there's no telling, which isn't held against it
+    assert_eq!(schedule.check(3, None), Decision::Allowed);
+}
+
+#[test]
+fn parses_network_manager_metered_property() {
+    assert_eq!(parse_nm_metered("u 1\n"), Some(true));
+    assert_eq!(parse_nm_metered("u 4\n"), Some(false));
+    assert_eq!(parse_nm_metered("u 0\n"), None);
+    assert_eq!(parse_nm_metered(""), None);
+}
+
+#[test]
+fn configs_without_a_schedule_never_refresh_in_the_background() {
+    let config: Config = serde_json::from_str(r#"{"package_manager": "auto", "auto_confirm": false, "flatpak_enabled": true}"#).unwrap();
+    assert_eq!(config.refresh_schedule, RefreshSchedule::default());
+    assert_eq!(config.refresh_schedule.every_hours, None);
+}
@@ -0,0 +1,85 @@
+use birdnest_core::package_manager::PackageSummary;
+use birdnest_core::search_index::{fuzzy_score, SearchIndex};
+
+fn index(packages: &[(&str, &str)]) -> SearchIndex {
+    SearchIndex::new(
+        packages
+            .iter()
+            .map(|(name, description)| PackageSummary {
+                name: name.to_string(),
+                version: String::new(),
+                description: description.to_string(),
+            })
+            .collect(),
+    )
+}
+
+fn names(results: &[PackageSummary]) -> Vec<&str> {
+    results.iter().map(|p| p.name.as_str()).collect()
+}
+
+#[test]
+fn ranks_exact_then_prefix_then_substring_then_fuzzy_then_description() {
+    let index = index(&[
+        ("neovim", "heavily refactored vim fork"),
+        ("vim-gtk3", "Vi IMproved - GTK3 GUI"),
+        ("vim", "Vi IMproved - enhanced vi editor"),
+        ("python3-vim-helpers", "helpers"),
+        ("vifm-media", "vi file manager"),
+        ("nano", "small editor, not vim"),
+        ("htop", "interactive processes viewer"),
+    ]);
+    assert_eq!(
+        names(&index.search("vim", 10)),
+        ["vim", "vim-gtk3", "python3-vim-helpers", "neovim", "vifm-media", "nano"]
+    );
+    assert_eq!(names(&index.search("VIM", 2)), ["vim", "vim-gtk3"]);
+}
+
+#[test]
+fn every_query_word_has_to_match() {
+    let index = index(&[
+        ("firefox", "Mozilla Firefox web browser"),
+        ("firefox-esr", "Mozilla Firefox web browser - Extended Support Release"),
+        ("thunderbird", "Email client from Mozilla"),
+    ]);
+    assert_eq!(names(&index.search("mozilla esr", 10)), ["firefox-esr"]);
+    assert!(index.search("   ", 10).is_empty());
+}
+
+#[test]
+fn filtered_searches_count_every_match() {
+    let index = index(&[
+        ("firefox", "Mozilla Firefox web browser"),
+        ("firefox-esr", "Mozilla Firefox web browser - Extended Support Release"),
+        ("thunderbird", "Email client from Mozilla"),
+        ("seamonkey", "Mozilla internet suite"),
+    ]);
+    let all = index.search_filtered("mozilla", 2, false, |_| true);
+    assert_eq!((names(&all.packages), all.total), (vec!["firefox", "seamonkey"], 4));
+    assert_eq!(index.search_filtered("mozilla", 10, true, |_| true).total, 0);
+    let names_only = index.search_filtered("fire", 10, true, |package| package.name != "firefox-esr");
+    assert_eq!((names(&names_only.packages), names_only.total), (vec!["firefox"], 1));
+}
+
+#[test]
+fn fuzzy_matches_need_characters_in_order() {
+    assert!(fuzzy_score("ffx", "firefox").is_some());
+    assert!(fuzzy_score("xff", "firefox").is_none());
+    assert!(fuzzy_score("f", "firefox").is_none());
+    // Word starts count for more than scattered letters
+    assert!(fuzzy_score("gl", "git-lfs") > fuzzy_score("gl", "gimp-plugin"));
+}
+
+#[test]
+fn completes_names_by_prefix_shortest_first() {
+    let index = index(&[
+        ("vim-gtk3", "Vi IMproved - GTK3 GUI"),
+        ("vim", "Vi IMproved - enhanced vi editor"),
+        ("neovim", "heavily refactored vim fork"),
+        ("vifm", "vi file manager"),
+    ]);
+    assert_eq!(index.complete("Vim", 10), ["vim", "vim-gtk3"]);
+    assert_eq!(index.complete("vi", 2), ["vim", "vifm"]);
+    assert!(index.complete(" ", 10).is_empty());
+}
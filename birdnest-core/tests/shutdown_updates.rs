@@ -0,0 +1,47 @@
+use birdnest_core::dnf::DnfBackend;
+use birdnest_core::pacman::PacmanBackend;
+use birdnest_core::shutdown_updates::{is_shutting_down, stage_commands, supported, unit_file, StagedUpdates};
+use std::path::Path;
+
+#[test]
+fn the_unit_installs_from_its_stop_command() {
+    let unit = unit_file(Path::new("/usr/bin/birdnest"));
+    assert!(unit.contains("ExecStop=/usr/bin/birdnest apply-shutdown-updates\n"));
+    assert!(unit.contains("RemainAfterExit=yes\n"));
+    assert!(unit.contains("WantedBy=multi-user.target\n"));
+
+    assert!(is_shutting_down("stopping\n"));
+    assert!(!is_shutting_down("running\n"));
+    assert!(!is_shutting_down("degraded"));
+}
+
+#[test]
+fn staging_downloads_first_then_hands_over_to_root() {
+    let staged = StagedUpdates { packages: vec!["kernel".to_string()], flatpaks: vec!["org.gnome.Builder".to_string()] };
+    assert_eq!(staged.len(), 2);
+
+    let commands = stage_commands(&DnfBackend, Path::new("/usr/bin/birdnest"), &staged).unwrap();
+    let lines: Vec<String> = commands.iter().map(|c| c.display()).collect();
+    assert_eq!(
+        lines,
+        [
+            "dnf upgrade --downloadonly -y kernel",
+            "flatpak update --system --no-deploy -y --noninteractive org.gnome.Builder",
+            "/usr/bin/birdnest stage-shutdown-updates --flatpak org.gnome.Builder kernel",
+        ]
+    );
+    assert!(commands[1].as_user);
+    assert!(!commands[2].as_user);
+}
+
+#[test]
+fn backends_that_cant_download_ahead_only_stage_flatpaks() {
+    let pacman = PacmanBackend { aur_helper: None };
+    assert!(!supported(&pacman));
+    assert!(supported(&DnfBackend));
+
+    let packages = StagedUpdates { packages: vec!["linux".to_string()], flatpaks: Vec::new() };
+    assert!(stage_commands(&pacman, Path::new("birdnest"), &packages).is_err());
+    let flatpaks = StagedUpdates { packages: Vec::new(), flatpaks: vec!["org.gnome.Builder".to_string()] };
+    assert_eq!(stage_commands(&pacman, Path::new("birdnest"), &flatpaks).unwrap().len(), 2);
+}
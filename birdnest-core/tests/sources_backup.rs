@@ -0,0 +1,59 @@
+use birdnest_core::sources_backup::{list_in, restore_in, snapshot_in, KEEP};
+use std::fs;
+use std::path::PathBuf;
+
+fn temp_dirs(name: &str) -> (PathBuf, PathBuf) {
+    let root = std::env::temp_dir().join(format!("birdnest-sources-backup-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    let apt = root.join("apt");
+    fs::create_dir_all(apt.join("sources.list.d")).unwrap();
+    fs::write(apt.join("sources.list"), "deb http://deb.debian.org/debian bookworm main\n").unwrap();
+    fs::write(apt.join("sources.list.d/vendor.sources"), "Types: deb\nURIs: https://repo.example.com\nSuites: stable\nComponents: main\n").unwrap();
+    fs::write(apt.join("sources.list.d/notes.txt"), "not a source").unwrap();
+    (apt, root.join("backups"))
+}
+
+#[test]
+fn snapshots_are_taken_only_when_the_sources_changed() {
+    let (apt, backups) = temp_dirs("snapshot");
+    let first = snapshot_in(&apt, &backups, 1714999500).unwrap().unwrap();
+    assert_eq!(first.files, [PathBuf::from("sources.list"), PathBuf::from("sources.list.d/vendor.sources")]);
+    assert_eq!(first.summary(), "2024-05-06T12:45:00Z, 2 files");
+    assert_eq!(snapshot_in(&apt, &backups, 1714999600).unwrap(), None);
+
+    // A second change within the same second still gets its own snapshot
+    fs::write(apt.join("sources.list.d/extra.list"), "deb https://apt.example.org stable main\n").unwrap();
+    let second = snapshot_in(&apt, &backups, 1714999500).unwrap().unwrap();
+    assert_eq!(second.id, "1714999501");
+    let ids: Vec<String> = list_in(&backups).into_iter().map(|snapshot| snapshot.id).collect();
+    assert_eq!(ids, ["1714999501", "1714999500"]);
+
+    for time in 0..KEEP as i64 {
+        fs::write(apt.join("sources.list.d/extra.list"), format!("# {}\n", time)).unwrap();
+        snapshot_in(&apt, &backups, 1715000000 + time).unwrap();
+    }
+    let kept = list_in(&backups);
+    assert_eq!(kept.len(), KEEP);
+    assert!(kept.iter().all(|snapshot| snapshot.created >= 1715000000));
+    fs::remove_dir_all(apt.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn restoring_puts_back_the_files_and_drops_later_ones() {
+    let (apt, backups) = temp_dirs("restore");
+    let snapshot = snapshot_in(&apt, &backups, 1714999500).unwrap().unwrap();
+    fs::write(apt.join("sources.list"), "").unwrap();
+    fs::remove_file(apt.join("sources.list.d/vendor.sources")).unwrap();
+    fs::write(apt.join("sources.list.d/extra.list"), "deb https://apt.example.org stable main\n").unwrap();
+
+    restore_in(&apt, &backups, &snapshot.id).unwrap();
+    assert_eq!(fs::read_to_string(apt.join("sources.list")).unwrap(), "deb http://deb.debian.org/debian bookworm main\n");
+    assert!(apt.join("sources.list.d/vendor.sources").exists());
+    assert!(!apt.join("sources.list.d/extra.list").exists());
+    // Only sources files are touched
+    assert!(apt.join("sources.list.d/notes.txt").exists());
+
+    assert!(restore_in(&apt, &backups, "1").is_err());
+    assert!(restore_in(&apt, &backups, "../apt").is_err());
+    fs::remove_dir_all(apt.parent().unwrap()).unwrap();
+}
@@ -0,0 +1,108 @@
+use birdnest_core::startup::{
+    autostart_in, autostart_override, parse_desktop_entry, parse_unit, parse_unit_files, program, services_in, Kind,
+};
+use std::path::PathBuf;
+
+// A scratch directory with the given files and contents
+fn fake_dir(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("birdnest-startup-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    for (file, contents) in files {
+        let path = root.join(file);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+    std::fs::create_dir_all(&root).unwrap();
+    root
+}
+
+const NM_APPLET: &str = "[Desktop Entry]
+Name=Network
+Name[de]=Netzwerk
+Exec=nm-applet
+NotShowIn=KDE;
+X-GNOME-Autostart-enabled=true
+
+[Desktop Action Quit]
+Hidden=true
+";
+
+#[test]
+fn entries_and_units_say_what_they_run() {
+    let entry = parse_desktop_entry(NM_APPLET);
+    assert_eq!(entry.name.as_deref(), Some("Network"));
+    assert!(!entry.hidden);
+    assert!(entry.shown_in(&["GNOME".to_string()]));
+    assert!(!entry.shown_in(&["KDE".to_string()]));
+    assert!(parse_desktop_entry("[Desktop Entry]\nX-GNOME-Autostart-enabled=false\n").hidden);
+
+    let unit = parse_unit(
+        "[Unit]\nDescription=Sound Service\n\n[Service]\nExecStart=/usr/bin/pipewire\n\n[Install]\nWantedBy=default.target\n",
+    );
+    assert_eq!(unit.name.as_deref(), Some("Sound Service"));
+    assert_eq!(unit.wanted_by, ["default.target"]);
+
+    assert_eq!(program("env LANG=C /usr/bin/syncthing serve").as_deref(), Some("/usr/bin/syncthing"));
+    assert_eq!(program("-/usr/lib/tracker-miner-fs-3").as_deref(), Some("/usr/lib/tracker-miner-fs-3"));
+    assert_eq!(program(""), None);
+
+    let units = parse_unit_files("pipewire.service enabled enabled\nsyncthing.service disabled enabled\n\n");
+    assert_eq!(units, [("pipewire.service".to_string(), "enabled".to_string()), ("syncthing.service".to_string(), "disabled".to_string())]);
+}
+
+#[test]
+fn a_users_copy_overrides_the_packaged_entry() {
+    let system = fake_dir(
+        "system",
+        &[
+            ("nm-applet.desktop", NM_APPLET),
+            ("org.kde.kdeconnect.daemon.desktop", "[Desktop Entry]\nName=KDE Connect\nExec=kdeconnectd\nOnlyShowIn=KDE;\n"),
+            ("tracker.desktop", "[Desktop Entry]\nName=Tracker\nExec=tracker-miner-fs-3\n"),
+        ],
+    );
+    let user = fake_dir("user", &[("tracker.desktop", &autostart_override("[Desktop Entry]\nName=Tracker\nExec=tracker-miner-fs-3\n", false))]);
+
+    let items = autostart_in(&user, std::slice::from_ref(&system), &["GNOME".to_string()]);
+    let listed: Vec<(&str, bool)> = items.iter().map(|item| (item.name.as_str(), item.enabled)).collect();
+    assert_eq!(listed, [("Network", true), ("Tracker", false)]);
+    assert_eq!(items[1].path, user.join("tracker.desktop"));
+    assert_eq!(items[1].program.as_deref(), Some("tracker-miner-fs-3"));
+    assert_eq!(items[0].kind, Kind::Autostart);
+
+    // Turning it back on keeps the rest of the entry and the other groups
+    let on = autostart_override(NM_APPLET, true);
+    assert!(!parse_desktop_entry(&on).hidden);
+    assert!(on.contains("Name[de]=Netzwerk") && on.contains("[Desktop Action Quit]\nHidden=true"));
+    assert_eq!(on.matches("X-GNOME-Autostart-enabled").count(), 1);
+
+    for dir in [system, user] {
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
+
+#[test]
+fn services_that_start_at_login_are_listed_on_or_off() {
+    let user = fake_dir("units-user", &[]);
+    let lib = fake_dir(
+        "units-lib",
+        &[
+            ("pipewire.service", "[Unit]\nDescription=PipeWire Multimedia Service\n[Service]\nExecStart=/usr/bin/pipewire\n[Install]\nWantedBy=default.target\n"),
+            ("syncthing.service", "[Unit]\nDescription=Syncthing\n[Service]\nExecStart=/usr/bin/syncthing serve\n[Install]\nWantedBy=default.target\n"),
+            ("gpg-agent.service", "[Unit]\nDescription=GnuPG agent\n[Service]\nExecStart=/usr/bin/gpg-agent --supervised\n"),
+        ],
+    );
+    // A mask in the user's directory hides the packaged unit but not its description
+    std::os::unix::fs::symlink("/dev/null", user.join("syncthing.service")).unwrap();
+    let units = parse_unit_files("pipewire.service enabled enabled\nsyncthing.service masked enabled\ngpg-agent.service disabled enabled\nmissing.service enabled enabled\n");
+
+    let items = services_in(&units, &[user.clone(), lib.clone()]);
+    let listed: Vec<(&str, bool)> = items.iter().map(|item| (item.name.as_str(), item.enabled)).collect();
+    assert_eq!(listed, [("PipeWire Multimedia Service", true), ("Syncthing", false)]);
+    assert_eq!(items[1].path, lib.join("syncthing.service"));
+    assert_eq!(items[0].program.as_deref(), Some("/usr/bin/pipewire"));
+    assert_eq!(items[0].kind.label(), "User service");
+
+    for dir in [user, lib] {
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,29 @@
+use birdnest_core::terminal::{find, held, launch_args};
+
+fn args(items: &[&str]) -> Vec<String> {
+    items.iter().map(|item| item.to_string()).collect()
+}
+
+#[test]
+fn each_terminal_gets_its_own_flag() {
+    let command = args(&["pikman", "enter", "arch"]);
+    assert_eq!(launch_args("gnome-terminal", &command), args(&["--", "pikman", "enter", "arch"]));
+    assert_eq!(launch_args("/usr/bin/konsole", &command), args(&["-e", "pikman", "enter", "arch"]));
+    assert_eq!(launch_args("xfce4-terminal", &command), args(&["-x", "pikman", "enter", "arch"]));
+    assert_eq!(launch_args("kitty", &command), command);
+    // Terminals it doesn't know are assumed to take -e, as most do
+    assert_eq!(launch_args("cool-retro-term", &command), args(&["-e", "pikman", "enter", "arch"]));
+}
+
+#[test]
+fn held_commands_wait_for_enter() {
+    let command = held(&args(&["pikman", "run", "arch", "uname", "-a"]));
+    assert_eq!(command[..2], args(&["sh", "-c"]));
+    assert!(command[2].contains("read -r _"));
+    assert_eq!(command[3..], args(&["sh", "pikman", "run", "arch", "uname", "-a"]));
+}
+
+#[test]
+fn the_configured_terminal_comes_first() {
+    assert_eq!(find(Some("foot")).as_deref(), Some("foot"));
+}
@@ -0,0 +1,57 @@
+use birdnest_core::dpkg::InstalledPackage;
+use birdnest_core::plan::{PlannedPackage, TransactionPlan};
+use birdnest_core::trim::{installed_offers, protect, Category, CATALOG};
+use std::cell::RefCell;
+use std::collections::HashSet;
+
+fn installed(name: &str, bytes: u64) -> InstalledPackage {
+    InstalledPackage { name: name.to_string(), version: "1.0".to_string(), installed_bytes: Some(bytes), ..InstalledPackage::default() }
+}
+
+fn removing(names: &[&str]) -> TransactionPlan {
+    let remove = names
+        .iter()
+        .map(|name| PlannedPackage { name: name.to_string(), old_version: None, new_version: None, download_bytes: None, installed_bytes: None })
+        .collect();
+    TransactionPlan { remove, ..TransactionPlan::default() }
+}
+
+#[test]
+fn every_catalog_app_has_a_category_and_a_consequence() {
+    let packages: HashSet<&str> = CATALOG.iter().map(|candidate| candidate.package).collect();
+    assert_eq!(packages.len(), CATALOG.len(), "each app is listed once");
+    assert!(CATALOG.iter().all(|candidate| !candidate.consequence.is_empty()));
+    assert!(Category::ALL.iter().all(|category| CATALOG.iter().any(|candidate| candidate.category == *category)));
+}
+
+#[test]
+fn apps_a_meta_package_depends_on_are_protected() {
+    let offers = installed_offers(&[installed("vim", 1), installed("gnome-mines", 2_000), installed("thunderbird", 250_000_000)]);
+    let names: Vec<&str> = offers.iter().map(|offer| offer.candidate.package).collect();
+    assert_eq!(names, ["thunderbird", "gnome-mines"]);
+    assert_eq!(offers[0].installed_bytes, Some(250_000_000));
+
+    let meta: HashSet<String> = ["pika-gnome-desktop".to_string()].into();
+    // The desktop meta-package depends on Thunderbird
+    let simulated = RefCell::new(Vec::new());
+    let simulate = |packages: &[String]| {
+        simulated.borrow_mut().push(packages.to_vec());
+        Ok(if packages.iter().any(|p| p == "thunderbird") { removing(&["thunderbird", "pika-gnome-desktop"]) } else { removing(&["gnome-mines"]) })
+    };
+    let mut checked = offers.clone();
+    protect(&mut checked, &meta, simulate).unwrap();
+    assert_eq!(checked[0].protected_by, ["pika-gnome-desktop"]);
+    assert!(checked[0].is_protected() && !checked[1].is_protected());
+    assert_eq!(simulated.borrow().len(), 3, "all at once, then one by one");
+
+    // Nothing protected needs only the one simulation
+    simulated.borrow_mut().clear();
+    let mut free = offers;
+    protect(&mut free, &meta, |packages| {
+        simulated.borrow_mut().push(packages.to_vec());
+        Ok(removing(&["thunderbird", "gnome-mines"]))
+    })
+    .unwrap();
+    assert!(free.iter().all(|offer| !offer.is_protected()));
+    assert_eq!(simulated.borrow().len(), 1);
+}
@@ -0,0 +1,29 @@
+use birdnest_core::config::Config;
+use birdnest_core::update_check::{should_notify, UpdateCount};
+
+#[test]
+fn counts_read_as_a_summary() {
+    assert_eq!(UpdateCount::default().summary(), "Everything is up to date");
+    assert_eq!(UpdateCount { packages: 1, flatpaks: 0 }.summary(), "1 update available (1 system, 0 Flatpak)");
+    assert_eq!(UpdateCount { packages: 2, flatpaks: 3 }.summary(), "5 updates available (2 system, 3 Flatpak)");
+}
+
+#[test]
+fn notifications_only_announce_new_updates() {
+    let five = UpdateCount { packages: 2, flatpaks: 3 };
+    assert!(should_notify(0, &five));
+    assert!(!should_notify(5, &five));
+    assert!(should_notify(4, &five));
+    assert!(!should_notify(0, &UpdateCount::default()));
+}
+
+#[test]
+fn checks_run_every_six_hours_unless_configured() {
+    let config = Config::from_toml("package_manager = \"auto\"\nauto_confirm = false\nflatpak_enabled = true\n").unwrap();
+    assert_eq!(config.update_check.every_hours, 6);
+    assert!(!config.update_check.notify);
+
+    let off = Config::from_toml("package_manager = \"auto\"\nauto_confirm = false\nflatpak_enabled = true\n[update_check]\nevery_hours = 0\nnotify = true\n").unwrap();
+    assert_eq!(off.update_check.every_hours, 0);
+    assert!(off.update_check.notify);
+}
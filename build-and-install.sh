@@ -33,7 +33,10 @@ BINARY_NAME="birdnest"
 INSTALL_DIR="/usr/local/bin"
 DESKTOP_DIR="/usr/share/applications"
 ICON_DIR="/usr/share/pixmaps"
+MAN_DIR="/usr/local/share/man/man1"
 DESKTOP_FILE="$DESKTOP_DIR/com.github.birdnest.desktop"
+HELPER_PATH="/usr/libexec/birdnest/birdnest-helper"
+POLKIT_DIR="/usr/share/polkit-1/actions"
 
 # Find PikaOS logo
 PIKA_ICON=""
@@ -52,6 +55,45 @@ echo -e "${GREEN}Installing binary to $INSTALL_DIR...${NC}"
 $SUDO cp "target/release/$BINARY_NAME" "$INSTALL_DIR/$BINARY_NAME"
 $SUDO chmod +x "$INSTALL_DIR/$BINARY_NAME"
 
+echo -e "${GREEN}Installing the privileged helper...${NC}"
+# pkexec starts it as root once per session, and the policy keeps an active
+# session's answer for a few minutes
+$SUDO mkdir -p "$(dirname "$HELPER_PATH")" "$POLKIT_DIR"
+printf '#!/bin/sh\nexec %s helper serve\n' "$INSTALL_DIR/$BINARY_NAME" | $SUDO tee "$HELPER_PATH" >/dev/null
+$SUDO chmod 755 "$HELPER_PATH"
+cat > /tmp/birdnest-helper.policy << EOF
+<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE policyconfig PUBLIC "-//freedesktop//DTD PolicyKit Policy Configuration 1.0//EN"
+ "http://www.freedesktop.org/standards/PolicyKit/1/policyconfig.dtd">
+<policyconfig>
+  <action id="com.github.birdnest.helper">
+    <description>Install, remove and upgrade software</description>
+    <message>Authentication is required to manage software for this session</message>
+    <defaults>
+      <allow_any>auth_admin</allow_any>
+      <allow_inactive>auth_admin</allow_inactive>
+      <allow_active>auth_admin_keep</allow_active>
+    </defaults>
+    <annotate key="org.freedesktop.policykit.exec.path">$HELPER_PATH</annotate>
+  </action>
+</policyconfig>
+EOF
+$SUDO cp /tmp/birdnest-helper.policy "$POLKIT_DIR/com.github.birdnest.helper.policy"
+$SUDO chmod 644 "$POLKIT_DIR/com.github.birdnest.helper.policy"
+
+echo -e "${GREEN}Installing man pages to $MAN_DIR...${NC}"
+MAN_TMP=$(mktemp -d)
+"target/release/$BINARY_NAME" man --out-dir "$MAN_TMP" >/dev/null 2>&1
+$SUDO mkdir -p "$MAN_DIR"
+$SUDO cp "$MAN_TMP"/*.1 "$MAN_DIR/"
+rm -rf "$MAN_TMP"
+
+echo -e "${GREEN}Installing shell completions...${NC}"
+$SUDO mkdir -p /usr/local/share/bash-completion/completions /usr/local/share/zsh/site-functions /usr/local/share/fish/vendor_completions.d
+"target/release/$BINARY_NAME" completions bash 2>/dev/null | $SUDO tee /usr/local/share/bash-completion/completions/birdnest >/dev/null
+"target/release/$BINARY_NAME" completions zsh 2>/dev/null | $SUDO tee /usr/local/share/zsh/site-functions/_birdnest >/dev/null
+"target/release/$BINARY_NAME" completions fish 2>/dev/null | $SUDO tee /usr/local/share/fish/vendor_completions.d/birdnest.fish >/dev/null
+
 echo -e "${GREEN}Creating desktop entry...${NC}"
 
 # Create desktop file
@@ -62,11 +104,12 @@ Type=Application
 Name=BirdNest
 GenericName=Package Manager
 Comment=Unified package manager for PikaOS supporting pikman, apt, and flatpak
-Exec=$INSTALL_DIR/$BINARY_NAME
+Exec=$INSTALL_DIR/$BINARY_NAME %f
 Icon=${PIKA_ICON:-application-x-executable}
 Terminal=false
 Categories=System;PackageManager;
 Keywords=package;manager;pikman;apt;flatpak;
+MimeType=application/vnd.flatpak.ref;application/vnd.flatpak;
 StartupNotify=true
 EOF
 
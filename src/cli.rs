@@ -1,22 +1,58 @@
 use anyhow::Result;
 use clap::{Parser, Subcommand};
+use clap_complete::Shell;
+use std::path::PathBuf;
 
-use crate::package_manager::PackageManager;
-use crate::flatpak::FlatpakManager;
+use birdnest_core::package_manager::{host_backend, PackageManager};
+use birdnest_core::flatpak::{self, FlatpakFile, FlatpakInstallation, FlatpakManager};
+use birdnest_core::{appimage, cache, changelog, deferred_removal, digest, elevation, environments, helper, offline_bundle, ppa, predownload, repos, shutdown_updates, sources_backup, update_check, utils};
+use birdnest_core::appstream::Catalog;
+use birdnest_core::config::Config;
+use birdnest_core::elevation::ElevationMethod;
+use birdnest_core::sbom::{Sbom, SbomFormat};
+use birdnest_core::manifest::{self, Manifest};
+use birdnest_core::notify::{self, Context, Notification, Notifier, Notifiers};
+use birdnest_core::schedule::Decision;
+use birdnest_core::script_export::{self, FlatpakRef, ScriptFormat, ScriptSelection};
+use birdnest_core::shutdown_updates::StagedUpdates;
+
+use crate::{completions, manpage, renderer, tui};
 
 #[derive(Parser)]
 #[clap(name = "birdnest")]
 #[clap(about = "A unified package manager for PikaOS", long_about = "A unified package manager for PikaOS supporting pikman, apt, and flatpak.\n\nPikman can install packages from multiple distributions:\n  --aur: Install Arch packages (including from the AUR)\n  --fedora: Install Fedora packages\n  --alpine: Install Alpine packages\n\nUse 'pikman' subcommand for pikman-specific commands:\n  autoremove, enter, export, init, log, purge, run, upgrades, unexport")]
 pub struct Cli {
     #[clap(subcommand)]
-    pub command: Commands,
+    pub command: Option<Commands>,
+    /// Print a timing breakdown of startup (GUI when no command is given)
+    #[clap(long)]
+    pub profile_startup: bool,
+    /// Show raw tool output instead of progress bars (for logs and scripts)
+    #[clap(long, global = true)]
+    pub plain: bool,
+    /// Show debug messages on the terminal, not just in the log file
+    #[clap(short, long, global = true, conflicts_with = "quiet")]
+    pub verbose: bool,
+    /// Show only errors on the terminal
+    #[clap(short, long, global = true)]
+    pub quiet: bool,
+    /// Draw windows with the software renderer, for drivers that show blank windows
+    #[clap(long, global = true)]
+    pub software_rendering: bool,
+    /// How to gain root for this session: auto, pkexec, sudo, sudo-askpass or helper
+    #[clap(long, global = true, value_name = "METHOD")]
+    pub elevation: Option<String>,
+    /// A .flatpakref or .flatpak bundle to open in the install dialog (how the
+    /// file manager hands them over)
+    #[clap(value_name = "FILE")]
+    pub file: Option<PathBuf>,
 }
 
 #[derive(Subcommand)]
 pub enum Commands {
     /// Install packages
     Install {
-        /// Package names to install
+        /// Package names to install, or .flatpakref / .flatpak bundle files
         packages: Vec<String>,
         /// Use flatpak instead of system package manager
         #[clap(short, long)]
@@ -30,6 +66,12 @@ pub enum Commands {
         /// Install Alpine packages via pikman
         #[clap(long, conflicts_with = "aur", conflicts_with = "fedora")]
         alpine: bool,
+        /// Leave out recommended packages this time (apt only)
+        #[clap(long, conflicts_with = "with-recommends")]
+        no_recommends: bool,
+        /// Include recommended packages even if install_options turns them off
+        #[clap(long)]
+        with_recommends: bool,
         /// Don't ask for confirmation
         #[clap(short, long)]
         yes: bool,
@@ -60,20 +102,35 @@ pub enum Commands {
     PikmanSearch {
         /// Search query
         query: String,
+        /// Search Arch packages (including the AUR)
+        #[clap(long, conflicts_with = "fedora", conflicts_with = "alpine")]
+        aur: bool,
+        /// Search Fedora packages
+        #[clap(long, conflicts_with = "aur", conflicts_with = "alpine")]
+        fedora: bool,
+        /// Search Alpine packages
+        #[clap(long, conflicts_with = "aur", conflicts_with = "fedora")]
+        alpine: bool,
     },
     /// Update package lists
     Update {
         /// Update flatpak repositories
         #[clap(short, long)]
         flatpak: bool,
+        /// Skip the update outside the config's refresh_schedule (for timers and cron)
+        #[clap(long)]
+        scheduled: bool,
     },
     /// Upgrade installed packages
     Upgrade {
         /// Package names to upgrade (if empty, upgrade all)
         packages: Vec<String>,
         /// Use flatpak instead of system package manager
-        #[clap(short, long)]
+        #[clap(short, long, alias = "flatpak-only")]
         flatpak: bool,
+        /// Upgrade system packages, then Flatpak apps
+        #[clap(long, conflicts_with = "flatpak", conflicts_with = "packages")]
+        all: bool,
         /// Don't ask for confirmation
         #[clap(short, long)]
         yes: bool,
@@ -95,6 +152,17 @@ pub enum Commands {
         #[clap(short, long)]
         flatpak: bool,
     },
+    /// Show what changed in a package, newest version first
+    Changelog {
+        /// Package name, or Flatpak application ID with --flatpak
+        package: String,
+        /// Read a Flatpak app's release notes from its remote's AppStream data
+        #[clap(short, long)]
+        flatpak: bool,
+        /// Show the whole history, not only what upgrading the installed version brings
+        #[clap(long)]
+        all: bool,
+    },
     /// Show install dialog (internal use)
     InstallDialog {
         /// Package names to install
@@ -111,6 +179,14 @@ pub enum Commands {
         #[clap(long)]
         flatpak: bool,
     },
+    /// Show upgrade dialog (internal use)
+    UpgradeDialog {
+        /// System package names to upgrade
+        packages: Vec<String>,
+        /// Flatpak application IDs to upgrade
+        #[clap(long)]
+        flatpak: Vec<String>,
+    },
     /// Show conflict dialog (internal use)
     ConflictDialog {
         /// Package names that couldn't be removed (space-separated)
@@ -122,6 +198,30 @@ pub enum Commands {
         #[clap(long)]
         output: Option<String>,
     },
+    /// Show CLI reference window (internal use)
+    CliReferenceDialog,
+    /// Show the settings window (internal use)
+    SettingsDialog,
+    /// Show the log viewer window (internal use)
+    LogViewerDialog,
+    /// Record updates for the shutdown unit and enable it, as root (internal use)
+    StageShutdownUpdates {
+        /// System packages to upgrade at shutdown
+        packages: Vec<String>,
+        /// Flatpak application IDs to update at shutdown
+        #[clap(long = "flatpak")]
+        flatpaks: Vec<String>,
+        /// Unstage everything and remove the unit instead
+        #[clap(long)]
+        cancel: bool,
+    },
+    /// Install staged updates; the shutdown unit's ExecStop (internal use)
+    ApplyShutdownUpdates,
+    /// Show a Flatpak app's permission editor (internal use)
+    PermissionsDialog {
+        /// Flatpak application ID
+        app: String,
+    },
     /// Clean package cache
     Clean {
         /// Clean flatpak cache
@@ -130,6 +230,76 @@ pub enum Commands {
     },
     /// Show package manager status
     Status,
+    /// Count available system and Flatpak updates (for status bars and timers)
+    CheckUpdates {
+        /// Also send a desktop notification when there are any
+        #[clap(long)]
+        notify: bool,
+    },
+    /// Hold packages at their installed version so upgrades skip them
+    Hold {
+        /// Package names to hold (none lists the held packages)
+        packages: Vec<String>,
+    },
+    /// Let held packages upgrade again
+    Unhold {
+        /// Package names to release
+        #[clap(required = true)]
+        packages: Vec<String>,
+    },
+    /// Bring back apps hidden by deferred removal instead of uninstalling them
+    Restore {
+        /// Package names or Flatpak application IDs to restore (none lists the hidden apps)
+        apps: Vec<String>,
+    },
+    /// Generate a shell script or Ansible tasks that reproduce installs
+    Script {
+        /// apt package names to include
+        packages: Vec<String>,
+        /// Flatpak application IDs to include
+        #[clap(long = "flatpak-app")]
+        flatpak_apps: Vec<String>,
+        /// Output format (shell or ansible)
+        #[clap(long, default_value = "shell")]
+        format: String,
+        /// Include all manually installed apt packages and Flatpak apps
+        #[clap(long)]
+        installed: bool,
+    },
+    /// Print a software bill of materials for everything installed
+    Sbom {
+        /// Document format (spdx or cyclonedx)
+        #[clap(long, default_value = "spdx")]
+        format: String,
+        /// Write the document to this file instead of stdout
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Summarize the packages installed, upgraded and removed lately, and changes to the sources
+    Digest {
+        /// How many days back to go
+        #[clap(long, default_value_t = digest::DEFAULT_DAYS)]
+        days: u32,
+    },
+    /// Write a TOML manifest of manually installed apt packages, Flatpaks and pikman packages
+    Export {
+        /// Write the manifest to this file instead of stdout
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Install everything a manifest from `birdnest export` lists that isn't installed yet
+    Import {
+        /// The manifest to restore
+        file: PathBuf,
+        /// Don't ask for confirmation
+        #[clap(short, long)]
+        yes: bool,
+    },
+    /// Download packages and Flatpaks into a directory, to install on a machine without a network
+    Bundle {
+        #[clap(subcommand)]
+        subcommand: BundleSubcommand,
+    },
     /// Install flatpak packages
     FlatpakInstall {
         /// Flatpak package names to install
@@ -145,6 +315,62 @@ pub enum Commands {
     },
     /// Update flatpak repositories
     FlatpakUpdate,
+    /// Flatpak commands (install, remove, search, update, upgrade, list, show, clean)
+    Flatpak {
+        #[clap(subcommand)]
+        subcommand: FlatpakSubcommand,
+    },
+    /// Show available elevation methods, or save the preferred one
+    Elevation {
+        /// auto, pkexec, sudo, sudo-askpass or helper
+        method: Option<String>,
+    },
+    /// The privileged helper behind the `helper` elevation method (internal use)
+    Helper {
+        #[clap(subcommand)]
+        subcommand: HelperSubcommand,
+    },
+    /// Generate man pages from the command definitions
+    Man {
+        /// Write birdnest.1 and one page per subcommand into this directory
+        /// (prints birdnest.1 to stdout when omitted)
+        #[clap(long, value_name = "DIR")]
+        out_dir: Option<PathBuf>,
+    },
+    /// Browse, install and remove packages in a terminal UI, for systems without a display
+    Tui,
+    /// Print a shell completion script (bash, zsh, fish, powershell or elvish)
+    Completions {
+        #[clap(value_enum)]
+        shell: Shell,
+    },
+    /// Print installed package names, for shell completion (internal use)
+    CompleteInstalled {
+        /// List installed Flatpak application IDs instead
+        #[clap(long)]
+        flatpak: bool,
+    },
+    /// Manage BirdNest's installed-package cache
+    Cache {
+        #[clap(subcommand)]
+        subcommand: CacheSubcommand,
+    },
+    /// List and edit APT sources (sources.list and sources.list.d)
+    Sources {
+        #[clap(subcommand)]
+        subcommand: SourcesSubcommand,
+    },
+    /// Add, remove and list Launchpad PPAs (Ubuntu-based systems)
+    Ppa {
+        #[clap(subcommand)]
+        subcommand: PpaSubcommand,
+    },
+    /// AppImages in ~/Applications and the configured directories
+    #[clap(name = "appimage")]
+    AppImage {
+        #[clap(subcommand)]
+        subcommand: AppImageSubcommand,
+    },
     /// Pikman-specific commands (autoremove, enter, export, init, log, purge, run, upgrades, unexport)
     Pikman {
         #[clap(subcommand)]
@@ -152,6 +378,160 @@ pub enum Commands {
     },
 }
 
+#[derive(Subcommand)]
+pub enum FlatpakSubcommand {
+    /// Install flatpak applications
+    Install {
+        /// Application IDs to install, or .flatpakref / .flatpak bundle files
+        packages: Vec<String>,
+        /// Don't ask for confirmation
+        #[clap(short, long)]
+        yes: bool,
+    },
+    /// Remove flatpak applications
+    Remove {
+        /// Application IDs to remove
+        packages: Vec<String>,
+        /// Don't ask for confirmation
+        #[clap(short, long)]
+        yes: bool,
+    },
+    /// Search flatpak remotes
+    Search {
+        /// Search query
+        query: String,
+    },
+    /// Update flatpak repositories
+    Update,
+    /// Upgrade flatpak applications
+    Upgrade {
+        /// Application IDs to upgrade (if empty, upgrade all)
+        packages: Vec<String>,
+        /// Don't ask for confirmation
+        #[clap(short, long)]
+        yes: bool,
+    },
+    /// List installed flatpak applications
+    List {
+        /// Show only upgradable applications
+        #[clap(short, long)]
+        upgradable: bool,
+    },
+    /// Show flatpak application information
+    Show {
+        /// Application ID
+        package: String,
+    },
+    /// Remove unused runtimes
+    Clean,
+    /// Write an installed app to a single-file .flatpak bundle for another machine
+    Export {
+        /// Application ID
+        app: String,
+        /// Bundle to write; <app ID>.flatpak in the current directory by default
+        file: Option<PathBuf>,
+    },
+    /// Verify and repair a Flatpak installation (both when neither flag is given)
+    Repair {
+        /// Repair the per-user installation
+        #[clap(long)]
+        user: bool,
+        /// Repair the system-wide installation
+        #[clap(long)]
+        system: bool,
+        /// Don't ask for confirmation
+        #[clap(short, long)]
+        yes: bool,
+    },
+    /// Queue the runtimes apps need to download overnight, ahead of installing them
+    Predownload {
+        /// Application IDs whose runtimes to fetch (none lists the queue)
+        packages: Vec<String>,
+        /// Remote the apps install from; flatpak_default_remote, else flathub
+        #[clap(long)]
+        remote: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum CacheSubcommand {
+    /// Delete the installed-package cache so it is rebuilt on next use
+    Clear,
+}
+
+#[derive(Subcommand)]
+pub enum HelperSubcommand {
+    /// Run the root daemon; `run` starts it through pkexec
+    Serve,
+    /// Run a package tool as root through the daemon, starting it when needed
+    Run {
+        /// apt, apt-get, dpkg, flatpak, pikman or another package tool
+        program: String,
+        /// The program's arguments, after `--`
+        #[clap(last = true)]
+        args: Vec<String>,
+    },
+    /// Stop the daemon, so the next operation asks for the password again
+    Stop,
+}
+
+#[derive(Subcommand)]
+pub enum SourcesSubcommand {
+    /// List every source with its file and entry number
+    List,
+    /// Add a repository by URL, or as a whole "deb URI suite components" line
+    Add {
+        /// Repository URL, or a one-line entry in quotes
+        uri: String,
+        /// Release to use (defaults to this system's codename)
+        #[clap(long)]
+        suite: Option<String>,
+        /// Component to use; repeat for several (defaults to main)
+        #[clap(long = "component")]
+        components: Vec<String>,
+        /// Keyring the repository is signed with
+        #[clap(long, value_name = "KEYRING")]
+        signed_by: Option<String>,
+    },
+    /// Remove a third-party source
+    Remove {
+        /// The sources file, as shown by `sources list`
+        file: PathBuf,
+        /// Which of the file's entries
+        #[clap(long, default_value = "0")]
+        entry: usize,
+    },
+    /// Enable a source, or one of its components
+    Enable {
+        /// The sources file, as shown by `sources list`
+        file: PathBuf,
+        /// Which of the file's entries
+        #[clap(long, default_value = "0")]
+        entry: usize,
+        /// Enable just this component, e.g. universe
+        #[clap(long)]
+        component: Option<String>,
+    },
+    /// Disable a source, or one of its components
+    Disable {
+        /// The sources file, as shown by `sources list`
+        file: PathBuf,
+        /// Which of the file's entries
+        #[clap(long, default_value = "0")]
+        entry: usize,
+        /// Disable just this component, e.g. universe
+        #[clap(long)]
+        component: Option<String>,
+    },
+    /// List the backups taken before each change to the sources
+    Backups,
+    /// Put the sources back the way a backup has them
+    Restore {
+        /// The backup's id, as shown by `sources backups`
+        backup: String,
+    },
+}
+
 #[derive(Subcommand)]
 pub enum PikmanSubcommand {
     /// Remove all unused packages
@@ -208,35 +588,380 @@ pub enum PikmanSubcommand {
         #[clap(short, long)]
         name: Option<String>,
     },
+    /// Development environments: a container with a package set and exported tools, from a TOML template
+    Env {
+        #[clap(subcommand)]
+        subcommand: EnvSubcommand,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum EnvSubcommand {
+    /// List the built-in templates and the ones in ~/.config/birdnest/environments
+    List,
+    /// Print a template as TOML, to save and adjust
+    Show {
+        /// Template name, or its default container name
+        template: String,
+    },
+    /// Create the container, install the template's packages and export its tools
+    Create {
+        /// Template name, its default container name, or a .toml file
+        template: String,
+        /// Container name (defaults to the template's)
+        #[clap(short, long)]
+        name: Option<String>,
+        /// Don't ask for confirmation
+        #[clap(short, long)]
+        yes: bool,
+    },
 }
 
 // SystemUpdateSubcommand removed - system updates handled by separate app
 
+// `.flatpakref` and `.flatpak` bundle arguments, installed one at a time
+fn install_flatpak_files(files: &[String], yes: bool) -> Result<()> {
+    if files.is_empty() {
+        return Ok(());
+    }
+    let flatpak = FlatpakManager::new()?;
+    for file in files {
+        flatpak.install_file(&FlatpakFile::open(std::path::Path::new(file))?, yes)?;
+    }
+    Ok(())
+}
+
+// apt-mark hold/unhold, or whatever the host's backend holds packages with
+fn set_held(packages: &[String], held: bool) -> Result<()> {
+    let backend = host_backend();
+    let commands = if held { backend.hold_commands(packages) } else { backend.unhold_commands(packages) };
+    if commands.is_empty() {
+        anyhow::bail!("Packages can't be held with {} on this system", backend.name());
+    }
+    for command in commands {
+        utils::run_command_interactive(&command.program, &command.args(), !command.as_user)?;
+    }
+    Ok(())
+}
+
+#[derive(Subcommand)]
+pub enum AppImageSubcommand {
+    /// List the AppImages found, with their versions and update information
+    List,
+    /// Add an AppImage to the application menu with its own icon
+    Integrate {
+        /// File path or name
+        appimage: String,
+    },
+    /// Take an AppImage out of the application menu, keeping the file
+    Unintegrate { appimage: String },
+    /// Check AppImages for newer builds (all of them when none is given)
+    Check { appimage: Option<String> },
+    /// Replace an AppImage with its newest build
+    Update { appimage: String },
+    /// Delete an AppImage with its menu entry and icon
+    Remove {
+        appimage: String,
+        /// Don't ask for confirmation
+        #[clap(short, long)]
+        yes: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum BundleSubcommand {
+    /// Download apt packages with their dependencies, and copy installed Flatpak apps with their runtimes
+    Export {
+        /// Directory to write the bundle to, such as a USB stick's
+        dir: PathBuf,
+        /// apt package names to include
+        packages: Vec<String>,
+        /// Flatpak application IDs to include
+        #[clap(long = "flatpak-app")]
+        flatpak_apps: Vec<String>,
+    },
+    /// Install what a bundle has and this machine lacks, without the network
+    Import {
+        /// The bundle's directory
+        dir: PathBuf,
+        /// Don't ask for confirmation
+        #[clap(short, long)]
+        yes: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum PpaSubcommand {
+    /// List the PPAs in the APT sources
+    List,
+    /// Add a PPA with its signing key, e.g. ppa:owner/name
+    Add {
+        ppa: String,
+        /// Ubuntu release to use (defaults to the one this system is based on)
+        #[clap(long)]
+        suite: Option<String>,
+    },
+    /// Remove a PPA's sources and its signing key
+    Remove { ppa: String },
+}
+
+// The edit a `sources` subcommand asks for; None for `list`
+pub(crate) fn sources_edit(subcommand: SourcesSubcommand) -> Result<Option<repos::Edit>> {
+    let toggle = |file: PathBuf, entry: usize, component: Option<String>, enabled: bool| match component {
+        Some(component) => repos::Edit::SetComponent { file, entry, component, enabled },
+        None => repos::Edit::SetEnabled { file, entry, enabled },
+    };
+    Ok(Some(match subcommand {
+        SourcesSubcommand::List | SourcesSubcommand::Backups => return Ok(None),
+        SourcesSubcommand::Add { uri, .. } if uri.trim_start().starts_with("deb") => {
+            repos::Edit::Add(repos::NewSource::from_line(&uri)?)
+        }
+        SourcesSubcommand::Add { uri, suite, components, signed_by } => {
+            let suite = suite
+                .or_else(repos::codename)
+                .ok_or_else(|| anyhow::anyhow!("Can't tell this system's release; pass --suite"))?;
+            let components = if components.is_empty() { vec!["main".to_string()] } else { components };
+            let source = repos::NewSource { uri, suite, components, signed_by };
+            source.validate()?;
+            repos::Edit::Add(source)
+        }
+        SourcesSubcommand::Remove { file, entry } => repos::Edit::Remove { file, entry },
+        SourcesSubcommand::Enable { file, entry, component } => toggle(file, entry, component, true),
+        SourcesSubcommand::Disable { file, entry, component } => toggle(file, entry, component, false),
+        SourcesSubcommand::Restore { backup } => repos::Edit::Restore { backup },
+    }))
+}
+
+fn list_sources() -> Result<()> {
+    let sources = repos::load()?;
+    if sources.is_empty() {
+        utils::print_info("No APT sources found");
+    }
+    for source in &sources {
+        let mut flags = Vec::new();
+        if !source.enabled {
+            flags.push("disabled");
+        }
+        if source.is_third_party() {
+            flags.push("third-party");
+        }
+        let flags = if flags.is_empty() { String::new() } else { format!(" ({})", flags.join(", ")) };
+        println!("{} --entry {}{}\n    {}", source.file.display(), source.entry, flags, source.summary());
+    }
+    Ok(())
+}
+
+// Sources files belong to root: as a user, commands that edit them run
+// themselves again elevated
+fn rerun_as_root(args: &[String]) -> Result<()> {
+    let exe = std::env::current_exe()?.display().to_string();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    utils::run_command_interactive(&exe, &args, true)
+}
+
+fn list_source_backups() -> Result<()> {
+    let backups = sources_backup::list();
+    if backups.is_empty() {
+        utils::print_info("No sources backups yet; one is taken before each change");
+    }
+    for backup in &backups {
+        println!("{}  {}", backup.id, backup.summary());
+    }
+    Ok(())
+}
+
+fn backup_sources() -> Result<()> {
+    if let Some(backup) = sources_backup::snapshot()? {
+        utils::print_info(&format!("Backed up the sources as {}", backup.id));
+    }
+    Ok(())
+}
+
+fn edit_sources(edit: &repos::Edit) -> Result<()> {
+    if !utils::is_root() {
+        return rerun_as_root(&edit.args());
+    }
+    backup_sources()?;
+    repos::apply(edit)?;
+    utils::print_success("Sources updated, refreshing package lists");
+    utils::run_command_interactive("apt-get", &["update"], false)
+}
+
+// An AppImage by its path, or by name when that is unambiguous
+fn find_appimage(found: &[appimage::AppImage], wanted: &str) -> Result<appimage::AppImage> {
+    let path = std::fs::canonicalize(wanted).unwrap_or_else(|_| PathBuf::from(wanted));
+    if let Some(appimage) = found.iter().find(|appimage| appimage.path == path) {
+        return Ok(appimage.clone());
+    }
+    let named: Vec<&appimage::AppImage> = found.iter().filter(|appimage| appimage.name.eq_ignore_ascii_case(wanted)).collect();
+    match named.as_slice() {
+        [appimage] => Ok((*appimage).clone()),
+        [] => anyhow::bail!("No AppImage named {} was found; `birdnest appimage list` shows what there is", wanted),
+        _ => anyhow::bail!("Several AppImages are named {}; pass the file's path", wanted),
+    }
+}
+
+fn manage_appimages(subcommand: AppImageSubcommand) -> Result<()> {
+    let found = appimage::scan(&appimage::directories(&Config::load().unwrap_or_default()));
+    match subcommand {
+        AppImageSubcommand::List => {
+            if found.is_empty() {
+                utils::print_info("No AppImages found");
+            }
+            for appimage in &found {
+                let version = appimage.version.as_deref().map(|v| format!(" {}", v)).unwrap_or_default();
+                let mut flags = vec![birdnest_core::plan::format_size(appimage.size)];
+                if appimage.is_integrated() {
+                    flags.push("in the app menu".to_string());
+                }
+                match &appimage.update_info {
+                    Some(info) => flags.push(format!("updates through {}", info.transport())),
+                    None => flags.push("no update information".to_string()),
+                }
+                println!("{}{} ({})\n    {}", appimage.name, version, flags.join(", "), appimage.path.display());
+            }
+        }
+        AppImageSubcommand::Integrate { appimage: wanted } => {
+            let appimage = find_appimage(&found, &wanted)?;
+            let entry = appimage::integrate(&appimage)?;
+            utils::print_success(&format!("Added {} to the app menu ({})", appimage.name, entry.display()));
+        }
+        AppImageSubcommand::Unintegrate { appimage: wanted } => {
+            let appimage = find_appimage(&found, &wanted)?;
+            appimage::unintegrate(&appimage)?;
+            utils::print_success(&format!("Took {} out of the app menu", appimage.name));
+        }
+        AppImageSubcommand::Check { appimage: wanted } => {
+            let targets = match wanted {
+                Some(wanted) => vec![find_appimage(&found, &wanted)?],
+                None => found.into_iter().filter(|appimage| appimage.update_info.is_some()).collect(),
+            };
+            for appimage in targets {
+                match appimage::check_update(&appimage) {
+                    Ok(appimage::UpdateStatus::UpToDate) => utils::print_info(&format!("{} is up to date", appimage.name)),
+                    Ok(appimage::UpdateStatus::Available(Some(version))) => utils::print_success(&format!("{} {} is available", appimage.name, version)),
+                    Ok(appimage::UpdateStatus::Available(None)) => utils::print_success(&format!("An update for {} is available", appimage.name)),
+                    Err(e) => utils::print_warning(&format!("{}: {}", appimage.name, e)),
+                }
+            }
+        }
+        AppImageSubcommand::Update { appimage: wanted } => {
+            let appimage = find_appimage(&found, &wanted)?;
+            utils::print_info(&format!("Updating {}", appimage.name));
+            appimage::update(&appimage)?;
+            utils::print_success(&format!("Updated {}", appimage.name));
+        }
+        AppImageSubcommand::Remove { appimage: wanted, yes } => {
+            let appimage = find_appimage(&found, &wanted)?;
+            if !yes && !utils::confirm(&format!("Delete {}?", appimage.path.display()))? {
+                return Ok(());
+            }
+            appimage::remove(&appimage)?;
+            utils::print_success(&format!("Deleted {}", appimage.name));
+        }
+    }
+    Ok(())
+}
+
+fn manage_ppas(subcommand: PpaSubcommand) -> Result<()> {
+    match subcommand {
+        PpaSubcommand::List => {
+            let sources = repos::load()?;
+            let ppas = ppa::list(&sources);
+            if ppas.is_empty() {
+                utils::print_info("No PPAs added");
+            }
+            for (ppa, source) in ppas {
+                let state = if source.enabled { "" } else { " (disabled)" };
+                println!("{}{}\n    {} --entry {}", ppa.spec(), state, source.file.display(), source.entry);
+            }
+        }
+        PpaSubcommand::Add { ppa, suite } => {
+            let ppa = ppa::Ppa::parse(&ppa)?;
+            if !utils::is_root() {
+                let mut args = vec!["ppa".to_string(), "add".to_string(), ppa.spec()];
+                args.extend(suite.into_iter().flat_map(|suite| ["--suite".to_string(), suite]));
+                return rerun_as_root(&args);
+            }
+            let suite = suite
+                .or_else(ppa::ubuntu_codename)
+                .ok_or_else(|| anyhow::anyhow!("PPAs are built for Ubuntu releases and this system isn't based on one; pass --suite"))?;
+            utils::print_info(&format!("Fetching the signing key for {}", ppa.spec()));
+            backup_sources()?;
+            ppa::add(&ppa, &suite)?;
+            utils::print_success(&format!("Added {}, refreshing package lists", ppa.spec()));
+            utils::run_command_interactive("apt-get", &["update"], false)?;
+        }
+        PpaSubcommand::Remove { ppa } => {
+            let ppa = ppa::Ppa::parse(&ppa)?;
+            if !utils::is_root() {
+                return rerun_as_root(&["ppa".to_string(), "remove".to_string(), ppa.spec()]);
+            }
+            backup_sources()?;
+            ppa::remove(&ppa)?;
+            utils::print_success(&format!("Removed {}, refreshing package lists", ppa.spec()));
+            utils::run_command_interactive("apt-get", &["update"], false)?;
+        }
+    }
+    Ok(())
+}
+
+// Runs `work`, then says how it ended on the CLI's notification channels
+fn announced(label: String, work: impl FnOnce() -> Result<()>) -> Result<()> {
+    let result = work();
+    let config = Config::load().unwrap_or_default().notifications;
+    if config.on_finish {
+        let outcome = result.as_ref().map(|_| ()).map_err(|e| e.to_string());
+        Notifiers::select(&config, Context::Cli).notify(&Notification::finished(&label, &outcome));
+    }
+    result
+}
+
+// Map the mutually exclusive --aur/--fedora/--alpine flags to pikman's distro name
+fn distro_flag(aur: bool, fedora: bool, alpine: bool) -> Option<&'static str> {
+    if aur {
+        Some("aur")
+    } else if fedora {
+        Some("fedora")
+    } else if alpine {
+        Some("alpine")
+    } else {
+        None
+    }
+}
+
 impl Cli {
     pub fn run(self) -> Result<()> {
-        match self.command {
-            Commands::Install { packages, flatpak, aur, fedora, alpine, yes } => {
-                if flatpak {
-                    FlatpakManager::new()?.install(&packages, yes)?;
-                } else {
-                    let distro = if aur {
-                        Some("aur")
-                    } else if fedora {
-                        Some("fedora")
-                    } else if alpine {
-                        Some("alpine")
+        let Some(command) = self.command else {
+            anyhow::bail!("No command specified");
+        };
+        match command {
+            Commands::Install { packages, flatpak, aur, fedora, alpine, no_recommends, with_recommends, yes } => {
+                announced(format!("install {}", packages.join(" ")), || {
+                    let (files, packages): (Vec<String>, Vec<String>) = packages.into_iter().partition(|p| FlatpakFile::is_file_arg(p));
+                    install_flatpak_files(&files, yes)?;
+                    if packages.is_empty() && !files.is_empty() {
+                        return Ok(());
+                    }
+                    if flatpak {
+                        FlatpakManager::new()?.install(&packages, yes)
                     } else {
-                        None
-                    };
-                    PackageManager::new()?.install(&packages, yes, distro)?;
-                }
+                        let mut options = Config::load().unwrap_or_default().install_options;
+                        if no_recommends || with_recommends {
+                            options.no_install_recommends = no_recommends;
+                        }
+                        PackageManager::new()?.install_with(&packages, yes, distro_flag(aur, fedora, alpine), &options)
+                    }
+                })?;
             }
             Commands::Remove { packages, flatpak, yes, autoremove } => {
-                if flatpak {
-                    FlatpakManager::new()?.remove(&packages, yes)?;
-                } else {
-                    PackageManager::new()?.remove(&packages, yes, autoremove)?;
-                }
+                announced(format!("remove {}", packages.join(" ")), || {
+                    if flatpak {
+                        FlatpakManager::new()?.remove(&packages, yes)
+                    } else {
+                        PackageManager::new()?.remove(&packages, yes, autoremove)
+                    }
+                })?;
             }
             Commands::Search { query, flatpak } => {
                 if flatpak {
@@ -245,23 +970,42 @@ impl Cli {
                     PackageManager::new()?.search(&query)?;
                 }
             }
-            Commands::PikmanSearch { query } => {
-                PackageManager::new()?.pikman_search(&query)?;
+            Commands::PikmanSearch { query, aur, fedora, alpine } => {
+                PackageManager::new()?.pikman_search(&query, distro_flag(aur, fedora, alpine))?;
             }
-            Commands::Update { flatpak } => {
+            Commands::Update { flatpak, scheduled } => {
+                if scheduled {
+                    let decision = Config::load().unwrap_or_default().refresh_schedule.check_now();
+                    if decision != Decision::Allowed {
+                        utils::print_info(&format!("Skipping update: {}", decision));
+                        return Ok(());
+                    }
+                }
                 if flatpak {
                     FlatpakManager::new()?.update()?;
                 } else {
                     PackageManager::new()?.update()?;
                 }
-            }
-            Commands::Upgrade { packages, flatpak, yes } => {
-                if flatpak {
-                    FlatpakManager::new()?.upgrade(&packages, yes)?;
-                } else {
-                    PackageManager::new()?.upgrade(&packages, yes)?;
+                // Queued runtimes ride along on the timer, within their overnight window
+                let schedule = Config::load().unwrap_or_default().refresh_schedule;
+                if scheduled && !predownload::load().is_empty() && predownload::check_now(&schedule) == Decision::Allowed {
+                    let downloaded = predownload::run_pending()?;
+                    utils::print_success(&format!("Downloaded {} runtime(s) ahead of their apps", downloaded));
                 }
             }
+            Commands::Upgrade { packages, flatpak, all, yes } => {
+                let label = if all || packages.is_empty() { "upgrade".to_string() } else { format!("upgrade {}", packages.join(" ")) };
+                announced(label, || {
+                    if all {
+                        PackageManager::new()?.upgrade(&[], yes)?;
+                        FlatpakManager::new()?.upgrade(&[], yes)
+                    } else if flatpak {
+                        FlatpakManager::new()?.upgrade(&packages, yes)
+                    } else {
+                        PackageManager::new()?.upgrade(&packages, yes)
+                    }
+                })?;
+            }
             Commands::List { upgradable, flatpak } => {
                 if flatpak {
                     FlatpakManager::new()?.list(upgradable)?;
@@ -276,6 +1020,31 @@ impl Cli {
                     PackageManager::new()?.show(&package)?;
                 }
             }
+            Commands::Changelog { package, flatpak, all } => {
+                let (entries, installed) = if flatpak {
+                    let entries = changelog::for_app(&Catalog::load_flatpak(), &package)?;
+                    // Only an app with an update pending says which version it has
+                    let update = FlatpakManager::new()?.list_updates()?.into_iter().find(|update| update.name == package);
+                    (entries, update.map(|update| update.current_version))
+                } else {
+                    let backend = host_backend();
+                    let entries = backend.changelog(&package)?;
+                    let installed = backend.list_installed()?.into_iter().find(|installed| installed.name == package);
+                    (entries, installed.map(|installed| installed.version))
+                };
+                let entries = match installed.filter(|_| !all) {
+                    Some(installed) => {
+                        let newer = changelog::newer_than(entries, &installed);
+                        if newer.is_empty() {
+                            utils::print_info(&format!("{} {} is the newest version in its changelog", package, installed));
+                            return Ok(());
+                        }
+                        newer
+                    }
+                    None => entries,
+                };
+                print!("{}", changelog::render(&entries));
+            }
             // SystemUpdate command removed - handled by separate app
             Commands::Clean { flatpak } => {
                 if flatpak {
@@ -286,6 +1055,98 @@ impl Cli {
             }
             Commands::Status => {
                 PackageManager::new()?.status()?;
+                if let Some(choice) = renderer::active() {
+                    println!("\nGUI renderer: {}", choice.describe());
+                }
+                println!("Vulkan drivers: {}", if renderer::vulkan_installed() { "installed" } else { "not found (wgpu uses OpenGL)" });
+            }
+            Commands::CheckUpdates { notify } => {
+                let count = update_check::check(host_backend().as_ref())?;
+                println!("{}", count.summary());
+                if notify && count.total() > 0 {
+                    notify::Desktop.notify(&Notification::updates(&count))?;
+                }
+            }
+            Commands::Hold { packages } if packages.is_empty() => {
+                let held = host_backend().held()?;
+                if held.is_empty() {
+                    utils::print_info("No packages are held");
+                }
+                for package in &held {
+                    println!("{}", package);
+                }
+            }
+            Commands::Hold { packages } => {
+                set_held(&packages, true)?;
+                utils::print_success(&format!("Held {} at the installed version", packages.join(", ")));
+            }
+            Commands::Unhold { packages } => {
+                set_held(&packages, false)?;
+                utils::print_success(&format!("{} will upgrade again", packages.join(", ")));
+            }
+            Commands::Restore { apps } => {
+                for app in &apps {
+                    deferred_removal::restore(app)?;
+                    utils::print_success(&format!("{} restored and kept installed", app));
+                }
+                if apps.is_empty() {
+                    let hidden = deferred_removal::load();
+                    if hidden.is_empty() {
+                        utils::print_info("No removed apps are waiting to be uninstalled");
+                    }
+                    let now = utils::now();
+                    for entry in &hidden {
+                        println!("{} (uninstalled in {} day(s))", entry.name, entry.days_left(now));
+                    }
+                }
+            }
+            Commands::Script { packages, flatpak_apps, format, installed } => {
+                let format = ScriptFormat::parse(&format)?;
+                let mut selection = if installed {
+                    script_export::load_installed_selection()?
+                } else {
+                    ScriptSelection::default()
+                };
+                selection.apt_packages.extend(packages);
+                selection.flatpaks.extend(flatpak_apps.into_iter().map(|application| FlatpakRef {
+                    application,
+                    remote: None,
+                }));
+                if selection.is_empty() {
+                    anyhow::bail!("No packages specified (pass package names, --flatpak-app or --installed)");
+                }
+                print!("{}", script_export::render(&selection, format));
+            }
+            Commands::Sbom { format, output } => {
+                let format = SbomFormat::parse(&format)?;
+                let sbom = Sbom::collect()?;
+                let document = sbom.render(format);
+                match output {
+                    Some(path) => {
+                        std::fs::write(&path, document)?;
+                        utils::print_success(&format!("Wrote {} components to {}", sbom.components.len(), path.display()));
+                    }
+                    None => print!("{}", document),
+                }
+            }
+            Commands::Digest { days } => {
+                print!("{}", digest::collect(days).render());
+            }
+            Commands::Export { output } => {
+                let manifest = manifest::export(output.as_deref())?;
+                if let Some(path) = output {
+                    utils::print_success(&format!("Wrote {} packages and apps to {}", manifest.len(), path.display()));
+                }
+            }
+            Commands::Import { file, yes } => {
+                manifest::import(&Manifest::load(&file)?, yes)?;
+            }
+            Commands::Bundle { subcommand: BundleSubcommand::Export { dir, packages, flatpak_apps } } => {
+                let bundle = offline_bundle::export(&dir, &packages, &flatpak_apps)?;
+                utils::print_success(&format!("Wrote {} to {}", bundle.summary(), dir.display()));
+            }
+            Commands::Bundle { subcommand: BundleSubcommand::Import { dir, yes } } => {
+                offline_bundle::import(&dir, yes)?;
             }
             Commands::FlatpakInstall { packages, yes } => {
                 FlatpakManager::new()?.install(&packages, yes)?;
@@ -296,6 +1157,127 @@ impl Cli {
             Commands::FlatpakUpdate => {
                 FlatpakManager::new()?.update()?;
             }
+            Commands::Flatpak { subcommand } => {
+                let flatpak_manager = FlatpakManager::new()?;
+                match subcommand {
+                    FlatpakSubcommand::Install { packages, yes } => {
+                        let (files, packages): (Vec<String>, Vec<String>) = packages.into_iter().partition(|p| FlatpakFile::is_file_arg(p));
+                        install_flatpak_files(&files, yes)?;
+                        if !packages.is_empty() || files.is_empty() {
+                            flatpak_manager.install(&packages, yes)?;
+                        }
+                    }
+                    FlatpakSubcommand::Remove { packages, yes } => {
+                        flatpak_manager.remove(&packages, yes)?;
+                    }
+                    FlatpakSubcommand::Search { query } => {
+                        flatpak_manager.search(&query)?;
+                    }
+                    FlatpakSubcommand::Update => {
+                        flatpak_manager.update()?;
+                    }
+                    FlatpakSubcommand::Upgrade { packages, yes } => {
+                        flatpak_manager.upgrade(&packages, yes)?;
+                    }
+                    FlatpakSubcommand::List { upgradable } => {
+                        flatpak_manager.list(upgradable)?;
+                    }
+                    FlatpakSubcommand::Show { package } => {
+                        flatpak_manager.show(&package)?;
+                    }
+                    FlatpakSubcommand::Clean => {
+                        flatpak_manager.clean()?;
+                    }
+                    FlatpakSubcommand::Export { app, file } => {
+                        let file = file.unwrap_or_else(|| flatpak::bundle_file(std::path::Path::new("."), &app));
+                        flatpak_manager.export_bundle(&app, &file)?;
+                    }
+                    FlatpakSubcommand::Repair { user, system, yes } => {
+                        let both = !user && !system;
+                        for installation in FlatpakInstallation::ALL {
+                            let wanted = match installation {
+                                FlatpakInstallation::User => user || both,
+                                FlatpakInstallation::System => system || both,
+                            };
+                            if wanted {
+                                flatpak_manager.repair(installation, yes)?;
+                            }
+                        }
+                    }
+                    FlatpakSubcommand::Predownload { packages, remote } => {
+                        if !packages.is_empty() {
+                            let remote = remote
+                                .or(Config::load().unwrap_or_default().flatpak_default_remote)
+                                .unwrap_or_else(|| "flathub".to_string());
+                            let apps: Vec<(String, String)> = packages.into_iter().map(|app| (app, remote.clone())).collect();
+                            predownload::queue(&apps)?;
+                        }
+                        let pending = predownload::load();
+                        if pending.is_empty() {
+                            utils::print_info("No runtimes are queued to download");
+                        }
+                        for runtime in &pending {
+                            println!("{} ({}) for {}", runtime.runtime, runtime.remote, runtime.apps.join(", "));
+                        }
+                    }
+                }
+            }
+            Commands::Elevation { method } => match method {
+                Some(method) => {
+                    let method = ElevationMethod::parse(&method)?;
+                    let mut config = Config::load()?;
+                    config.elevation = method;
+                    config.save()?;
+                    utils::print_success(&format!("Elevation method set to {}", method.name()));
+                    if !elevation::detect().supports(method) {
+                        utils::print_warning(&format!("{} is not usable in this session right now", method.name()));
+                    }
+                }
+                None => print_elevation_status(),
+            },
+            Commands::Man { out_dir } => match out_dir {
+                Some(dir) => {
+                    let written = manpage::write_all(&dir)?;
+                    utils::print_success(&format!("Wrote {} man pages to {}", written.len(), dir.display()));
+                }
+                None => print!("{}", manpage::main_page()?),
+            },
+            Commands::Tui => tui::run()?,
+            Commands::Completions { shell } => print!("{}", completions::script(shell)),
+            Commands::CompleteInstalled { flatpak } => {
+                let names = if flatpak {
+                    FlatpakManager::new()?.installed_apps()?
+                } else {
+                    host_backend().list_installed()?.into_iter().map(|package| package.name).collect()
+                };
+                for name in names {
+                    println!("{}", name);
+                }
+            }
+            Commands::Sources { subcommand: SourcesSubcommand::Backups } => list_source_backups()?,
+            Commands::Sources { subcommand } => match sources_edit(subcommand)? {
+                Some(edit) => edit_sources(&edit)?,
+                None => list_sources()?,
+            },
+            Commands::Ppa { subcommand } => manage_ppas(subcommand)?,
+            Commands::AppImage { subcommand } => manage_appimages(subcommand)?,
+            Commands::Helper { subcommand } => match subcommand {
+                HelperSubcommand::Serve => helper::serve()?,
+                HelperSubcommand::Run { program, args } => std::process::exit(helper::run(&program, &args)?),
+                HelperSubcommand::Stop => {
+                    if helper::stop()? {
+                        utils::print_success("Privileged helper stopped");
+                    } else {
+                        utils::print_info("The privileged helper isn't running");
+                    }
+                }
+            },
+            Commands::Cache { subcommand } => match subcommand {
+                CacheSubcommand::Clear => {
+                    cache::invalidate_installed();
+                    utils::print_success("Installed-package cache cleared");
+                }
+            },
             Commands::Pikman { subcommand } => {
                 let pkg_manager = PackageManager::new()?;
                 match subcommand {
@@ -326,6 +1308,20 @@ impl Cli {
                     PikmanSubcommand::Unexport { package, name } => {
                         pkg_manager.pikman_unexport(&package, name.as_deref())?;
                     }
+                    PikmanSubcommand::Env { subcommand } => match subcommand {
+                        EnvSubcommand::List => {
+                            for template in environments::load() {
+                                println!("{} ({}, container {})", template.name, template.manager, template.container);
+                                if !template.description.is_empty() {
+                                    println!("    {}", template.description);
+                                }
+                            }
+                        }
+                        EnvSubcommand::Show { template } => print!("{}", environments::find(&template)?.to_toml()),
+                        EnvSubcommand::Create { template, name, yes } => {
+                            environments::create(&environments::find(&template)?, name.as_deref(), yes)?;
+                        }
+                    },
                 }
             }
             Commands::InstallDialog { packages, flatpak } => {
@@ -337,14 +1333,72 @@ impl Cli {
                 use crate::gui::remove_dialog::RemoveDialog;
                 RemoveDialog::run_separate_window_with_flatpak_flag(packages, flatpak)?;
             }
+            Commands::UpgradeDialog { packages, flatpak } => {
+                use crate::gui::upgrade_dialog::UpgradeDialog;
+                UpgradeDialog::run_separate_window(packages, flatpak)?;
+            }
             Commands::ConflictDialog { packages, message, output } => {
                 use crate::gui::conflict_dialog::ConflictDialog;
                 let conflict_msg = message.unwrap_or_else(|| "Unknown conflict".to_string());
                 let terminal_output = output.unwrap_or_default();
                 ConflictDialog::run_separate_window(packages, conflict_msg, terminal_output)?;
             }
+            Commands::CliReferenceDialog => {
+                use crate::gui::cli_reference_dialog::CliReferenceDialog;
+                CliReferenceDialog::run_separate_window()?;
+            }
+            Commands::StageShutdownUpdates { packages, flatpaks, cancel } => {
+                if !utils::is_root() {
+                    anyhow::bail!("stage-shutdown-updates must run as root");
+                }
+                if cancel {
+                    shutdown_updates::cancel()?;
+                } else {
+                    shutdown_updates::install(&StagedUpdates { packages, flatpaks }, &std::env::current_exe()?)?;
+                }
+            }
+            Commands::ApplyShutdownUpdates => {
+                shutdown_updates::apply(host_backend().as_ref())?;
+            }
+            Commands::SettingsDialog => {
+                use crate::gui::settings_dialog::SettingsDialog;
+                SettingsDialog::run_separate_window()?;
+            }
+            Commands::LogViewerDialog => {
+                use crate::gui::log_viewer_dialog::LogViewerDialog;
+                LogViewerDialog::run_separate_window()?;
+            }
+            Commands::PermissionsDialog { app } => {
+                use crate::gui::permissions_dialog::PermissionsDialog;
+                PermissionsDialog::run_separate_window(app)?;
+            }
         }
         Ok(())
     }
 }
 
+fn print_elevation_status() {
+    let availability = elevation::detect();
+    let preferred = elevation::preference();
+    println!("Preferred: {}", preferred.name());
+    match availability.resolve(preferred) {
+        Ok(method) => {
+            println!("In use:    {}", method.name());
+            let password = if utils::is_root() || elevation::is_passwordless(method) { "no" } else { "yes" };
+            println!("Password:  {}", password);
+        }
+        Err(e) => println!("In use:    none ({})", e),
+    }
+    println!();
+    for method in ElevationMethod::ALL.into_iter().skip(1) {
+        let mark = if availability.supports(method) { "yes" } else { "no" };
+        println!("  {:<13} {}", method.name(), mark);
+    }
+    if availability.helper {
+        println!();
+        println!("Helper:    {}", if helper::is_running() { "running" } else { "not running" });
+    }
+    if availability.graphical && availability.pkexec && !availability.polkit_agent {
+        utils::print_warning("pkexec is installed but no polkit authentication agent is running");
+    }
+}
@@ -0,0 +1,98 @@
+use clap::CommandFactory;
+use clap_complete::Shell;
+
+use crate::cli::Cli;
+use crate::manpage;
+
+// Completion scripts come from the clap definitions in cli.rs, like the man
+// pages. clap only knows the static command line, so for bash, zsh and fish
+// a wrapper completes the package arguments of `remove` and `flatpak remove`
+// from `birdnest complete-installed`. These wrappers look at the words right
+// after `birdnest`, so global options in front of the subcommand turn them off
+
+const BASH_INSTALLED: &str = r#"
+# Installed package names for `remove` and `flatpak remove`
+_birdnest_installed() {
+    local cur="${COMP_WORDS[COMP_CWORD]}" flatpak=
+    if [[ $cur != -* && ${COMP_WORDS[1]} == remove ]] && (( COMP_CWORD > 1 )); then
+        [[ " ${COMP_WORDS[*]} " == *" -f "* || " ${COMP_WORDS[*]} " == *" --flatpak "* ]] && flatpak=--flatpak
+    elif [[ $cur != -* && ${COMP_WORDS[1]} == flatpak && ${COMP_WORDS[2]} == remove ]] && (( COMP_CWORD > 2 )); then
+        flatpak=--flatpak
+    else
+        _birdnest "$@"
+        return
+    fi
+    COMPREPLY=($(compgen -W "$(birdnest complete-installed $flatpak 2>/dev/null)" -- "$cur"))
+}
+
+complete -F _birdnest_installed -o bashdefault -o default birdnest
+"#;
+
+// Replaces the `_birdnest "$@"` the generated script ends with
+const ZSH_INSTALLED: &str = r#"# Installed package names for `remove` and `flatpak remove`
+_birdnest() {
+    local flatpak
+    if [[ $PREFIX != -* && $words[2] == remove ]] && (( CURRENT > 2 )); then
+        (( ${words[(I)(-f|--flatpak)]} )) && flatpak=--flatpak
+    elif [[ $PREFIX != -* && $words[2] == flatpak && $words[3] == remove ]] && (( CURRENT > 3 )); then
+        flatpak=--flatpak
+    else
+        _birdnest_static "$@"
+        return
+    fi
+    local -a installed
+    installed=(${(f)"$(birdnest complete-installed $flatpak 2>/dev/null)"})
+    compadd -a installed
+}
+
+_birdnest "$@"
+"#;
+
+const FISH_INSTALLED: &str = r#"
+# Installed package names for `remove` and `flatpak remove`
+complete -c birdnest -n "__fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from flatpak; and not __fish_contains_opt -s f flatpak" -f -a "(birdnest complete-installed 2>/dev/null)"
+complete -c birdnest -n "__fish_seen_subcommand_from remove; and not __fish_seen_subcommand_from flatpak; and __fish_contains_opt -s f flatpak" -f -a "(birdnest complete-installed --flatpak 2>/dev/null)"
+complete -c birdnest -n "__fish_seen_subcommand_from flatpak; and __fish_seen_subcommand_from remove" -f -a "(birdnest complete-installed --flatpak 2>/dev/null)"
+"#;
+
+// clap_complete 3 offers hidden subcommands too, so dialog launchers and
+// other internal commands are taken out of the generated script: zsh and
+// fish list them with their about text, bash in its `opts` word lists
+fn without_internal(script: &str, internal: &[String]) -> String {
+    let mut kept = String::with_capacity(script.len());
+    for line in script.lines() {
+        if line.contains("(internal use)") {
+            continue;
+        }
+        match line.trim_start().strip_prefix("opts=\"") {
+            Some(words) => {
+                let indent = &line[..line.len() - line.trim_start().len()];
+                let words: Vec<&str> = words.trim_end_matches('"').split(' ').filter(|word| !internal.iter().any(|name| name == word)).collect();
+                kept.push_str(&format!("{}opts=\"{}\"", indent, words.join(" ")));
+            }
+            None => kept.push_str(line),
+        }
+        kept.push('\n');
+    }
+    kept
+}
+
+/// The completion script for `shell`; PowerShell and Elvish only get the
+/// static one
+pub fn script(shell: Shell) -> String {
+    let mut cmd = Cli::command();
+    let internal: Vec<String> = cmd.get_subcommands().filter(|sub| manpage::is_internal(sub)).map(|sub| sub.get_name().to_string()).collect();
+    let mut buf = Vec::new();
+    clap_complete::generate(shell, &mut cmd, "birdnest", &mut buf);
+    let script = without_internal(&String::from_utf8_lossy(&buf), &internal);
+    match shell {
+        Shell::Bash => script + BASH_INSTALLED,
+        Shell::Zsh => {
+            let script = script.replacen("\n_birdnest() {", "\n_birdnest_static() {", 1);
+            let script = script.strip_suffix("_birdnest \"$@\"\n").unwrap_or(&script);
+            format!("{}{}", script, ZSH_INSTALLED)
+        }
+        Shell::Fish => script + FISH_INSTALLED,
+        _ => script,
+    }
+}
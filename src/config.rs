@@ -1,53 +0,0 @@
-use anyhow::Result;
-use serde::{Deserialize, Serialize};
-use std::fs;
-use std::path::PathBuf;
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Config {
-    pub package_manager: String,
-    pub auto_confirm: bool,
-    pub flatpak_enabled: bool,
-}
-
-impl Default for Config {
-    fn default() -> Self {
-        Self {
-            package_manager: "auto".to_string(),
-            auto_confirm: false,
-            flatpak_enabled: true,
-        }
-    }
-}
-
-impl Config {
-    pub fn load() -> Result<Self> {
-        let config_path = Self::config_path()?;
-        
-        if config_path.exists() {
-            let content = fs::read_to_string(&config_path)?;
-            let config: Config = serde_json::from_str(&content)?;
-            Ok(config)
-        } else {
-            let config = Config::default();
-            config.save()?;
-            Ok(config)
-        }
-    }
-
-    pub fn save(&self) -> Result<()> {
-        let config_path = Self::config_path()?;
-        if let Some(parent) = config_path.parent() {
-            fs::create_dir_all(parent)?;
-        }
-        let content = serde_json::to_string_pretty(self)?;
-        fs::write(&config_path, content)?;
-        Ok(())
-    }
-
-    fn config_path() -> Result<PathBuf> {
-        let home = std::env::var("HOME")?;
-        Ok(PathBuf::from(home).join(".config").join("birdnest").join("config.json"))
-    }
-}
-
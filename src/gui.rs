@@ -1,15 +1,38 @@
 use iced::{
-    alignment, executor, Color,
-    widget::{button, checkbox, column, container, row, scrollable, text, text_input, Space},
-    Application, Command, Element, Length, Pixels, Settings, Theme as IcedTheme, Padding,
+    alignment, event, executor, keyboard, Color, Event,
+    multi_window::Application,
+    widget::{button, checkbox, column, container, image, row, scrollable, text, text_input, Space},
+    window, Command, Element, Length, Pixels, Settings, Subscription, Theme as IcedTheme, Padding,
 };
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::SystemTime;
 use tokio::sync::mpsc;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use futures::future;
 
-use crate::package_manager::PackageManager;
-use crate::flatpak::FlatpakManager;
+use birdnest_core::package_manager::{PackageBackend, PackageDetails, PackageManager, PackageSummary};
+use birdnest_core::flatpak::{self, FlatpakFile, FlatpakInstallation, FlatpakManager};
+use birdnest_core::immutable::{self, SystemKind};
+use birdnest_core::restart::RestartStatus;
+use birdnest_core::config::{Config, Preload, ThemePreference};
+use birdnest_core::install_options::InstallOptions;
+use crate::profiling;
+use birdnest_core::{batch, cache, dpkg, predownload};
+use birdnest_core::deferred_removal::{self, DeferredRemoval};
+use birdnest_core::startup::StartupItem;
+use birdnest_core::shutdown_updates::{self, StagedUpdates};
+use birdnest_core::update_check::{self, UpdateCheck, UpdateCount};
+use birdnest_core::notify::{self, Notification, Notifiers};
+use birdnest_core::script_export::{self, FlatpakRef, ScriptFormat, ScriptSelection};
+use birdnest_core::search_index::SearchIndex;
+use birdnest_core::appstream::{Catalog, Component};
+use birdnest_core::changelog::{self, ChangelogEntry};
+use birdnest_core::footprint::Footprint;
+use birdnest_core::schedule::{Decision, RefreshSchedule};
+use birdnest_core::utils::CommandBuilder;
+use birdnest_core::events::{self as bus, Event as BusEvent};
+use birdnest_core::db_watch::{self, Database};
 
 mod theme;
 mod styles;
@@ -17,22 +40,93 @@ pub mod install_dialog;
 pub mod remove_dialog;
 pub mod conflict_dialog;
 pub mod pikman_install_dialog;
+pub mod cli_reference_dialog;
+pub mod log_viewer_dialog;
+pub mod digest_dialog;
+pub mod permissions_dialog;
+pub mod settings_dialog;
+pub mod upgrade_dialog;
+pub mod batch_dialog;
+mod plan_view;
+mod command_stream;
+mod maintenance;
+mod updates;
+mod restart_banner;
+mod global_search;
+mod all_sources;
+mod search_filters;
+mod descriptions;
+mod details_pane;
+mod changelog_view;
+mod transaction_queue;
+mod batch_bar;
+mod scaling;
+mod adaptive;
+mod browse;
+mod row_menu;
+mod status_bar;
+mod disk_usage;
+mod architectures;
+mod pikman_terminal;
+mod pikman_exports;
+mod list_nav;
+mod result_pages;
+mod sources;
+mod appimage;
+mod activity;
+mod autocomplete;
+mod advanced_options;
+mod environment_dialog;
+mod trim_dialog;
+mod windows;
+#[cfg(test)]
+mod tests;
 
 use theme::Theme as AppTheme;
+use windows::{Dialog, DialogMessage, Notice};
+use row_menu::{RowAction, RowTarget};
+use result_pages::{InstalledFacts, Pager, SortOrder};
+use global_search::ExtraSource;
+use cli_reference_dialog::CliReferenceDialog;
+use environment_dialog::EnvironmentDialog;
+use log_viewer_dialog::LogViewerDialog;
+use digest_dialog::DigestDialog;
+use trim_dialog::TrimDialog;
+use install_dialog::InstallDialog;
+use permissions_dialog::PermissionsDialog;
+use settings_dialog::SettingsDialog;
+use pikman_install_dialog::PikmanInstallDialog;
+use remove_dialog::RemoveDialog;
+use upgrade_dialog::UpgradeDialog;
+use batch_dialog::BatchDialog;
 use styles::{RoundedButtonStyle, RoundedContainerStyle, CustomScrollableStyle, YellowTextInputStyle, YellowCheckboxStyle};
 
-#[derive(Debug, Clone)]
+// Pause in typing before the Search tab queries the index
+const SEARCH_DEBOUNCE: std::time::Duration = std::time::Duration::from_millis(150);
+// How often queued runtime downloads check whether their window has come
+const PREDOWNLOAD_CHECK: std::time::Duration = std::time::Duration::from_secs(30 * 60);
+
+const MAIN_WINDOW_SIZE: iced::Size = iced::Size::new(1200.0, 800.0);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PackageInfo {
     pub name: String,
     pub description: String,
     pub version: String,
     pub size: String,
+    // Installed packages only, for sorting by size
+    pub installed_bytes: Option<u64>,
+    // Installed packages only, from the dpkg database
+    pub arch: Option<dpkg::Arch>,
+    // Installed packages only: Essential, auto-installed and where from
+    pub provenance: Option<dpkg::Provenance>,
     pub source: PackageSource,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PackageSource {
     Default,
+    Flatpak,
     Aur,
     Fedora,
     Alpine,
@@ -42,6 +136,7 @@ impl PackageSource {
     pub fn as_str(&self) -> &str {
         match self {
             PackageSource::Default => "System",
+            PackageSource::Flatpak => "Flatpak",
             PackageSource::Aur => "AUR",
             PackageSource::Fedora => "Fedora",
             PackageSource::Alpine => "Alpine",
@@ -51,6 +146,7 @@ impl PackageSource {
     pub fn badge_color(&self) -> Color {
         match self {
             PackageSource::Default => Color::from_rgb(0.5, 0.5, 0.5),
+            PackageSource::Flatpak => Color::from_rgb(0.45, 0.7, 0.55), // Calm green
             PackageSource::Aur => Color::from_rgb(0.8, 0.5, 0.5), // Calm red - lighter for better contrast
             PackageSource::Fedora => Color::from_rgb(0.5, 0.65, 0.9), // Calm blue - lighter for better contrast
             PackageSource::Alpine => Color::from_rgb(0.4, 0.65, 0.85), // Calm cyan-blue
@@ -66,6 +162,36 @@ impl PackageSource {
     }
 }
 
+impl From<dpkg::InstalledPackage> for PackageInfo {
+    fn from(pkg: dpkg::InstalledPackage) -> Self {
+        PackageInfo {
+            name: pkg.name,
+            version: pkg.version,
+            description: pkg.description,
+            size: pkg.installed_bytes.map(birdnest_core::plan::format_size).unwrap_or_default(),
+            installed_bytes: pkg.installed_bytes,
+            arch: pkg.arch,
+            provenance: Some(pkg.provenance),
+            source: PackageSource::Default,
+        }
+    }
+}
+
+impl From<PackageSummary> for PackageInfo {
+    fn from(pkg: PackageSummary) -> Self {
+        PackageInfo {
+            name: pkg.name,
+            version: pkg.version,
+            description: pkg.description,
+            size: String::new(),
+            installed_bytes: None,
+            arch: None,
+            provenance: None,
+            source: PackageSource::Default,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct PackageDetail {
     pub name: String,
@@ -84,6 +210,8 @@ pub struct FlatpakInfo {
     pub description: String,
     pub version: String,
     pub application: String,
+    /// Remotes offering the app, as `flatpak search` lists them
+    pub remotes: Vec<String>,
 }
 
 // UpdateInfo struct removed - system updates handled by separate app
@@ -106,54 +234,105 @@ fn find_pika_icon_path() -> Option<String> {
     None
 }
 
+// Mock builds serve fixture data (BIRDNEST_MOCK_FIXTURE or the bundled one)
+// so the GUI can be exercised without touching the real system
+#[cfg(feature = "mock-backend")]
+pub(crate) fn create_backend() -> Arc<dyn PackageBackend> {
+    use birdnest_core::mock::MockBackend;
+    match std::env::var_os("BIRDNEST_MOCK_FIXTURE") {
+        Some(path) => match MockBackend::from_file(std::path::Path::new(&path)) {
+            Ok(backend) => Arc::new(backend),
+            Err(e) => {
+                tracing::error!("{:#}, using bundled fixture", e);
+                Arc::new(MockBackend::with_default_fixture())
+            }
+        },
+        None => Arc::new(MockBackend::with_default_fixture()),
+    }
+}
+
+#[cfg(not(feature = "mock-backend"))]
+pub(crate) fn create_backend() -> Arc<dyn PackageBackend> {
+    birdnest_core::package_manager::host_backend()
+}
+
+// Detail fields as the install and remove views show them
+pub(crate) fn package_description(details: &PackageDetails) -> String {
+    if details.description.is_empty() {
+        "No description available".to_string()
+    } else {
+        details.description.clone()
+    }
+}
+
+pub(crate) fn package_size(details: &PackageDetails) -> String {
+    details
+        .installed_size
+        .map(birdnest_core::plan::format_size)
+        .unwrap_or_else(|| "Unknown".to_string())
+}
+
 pub fn run() -> iced::Result {
-    eprintln!("[DEBUG] gui::run() called - initializing GUI...");
+    tracing::debug!("gui::run() called - initializing GUI...");
+    
+    let config = Config::load().unwrap_or_default();
+    if config.low_memory_mode {
+        tracing::debug!("Low-memory mode enabled - skipping icon and shadows");
+    }
+    styles::set_reduced_effects(config.low_memory_mode);
+    scaling::set_ui_scale(config.ui_scale);
+    profiling::mark("config loaded");
     
-    eprintln!("[DEBUG] Creating window settings...");
+    tracing::debug!("Creating window settings...");
     
     let mut window_settings = iced::window::Settings {
-        size: iced::Size::new(1200.0, 800.0),
+        size: scaling::fit(MAIN_WINDOW_SIZE),
         resizable: true,
-        min_size: Some(iced::Size::new(800.0, 600.0)),
+        // Narrow enough to tile to half a laptop screen
+        min_size: Some(scaling::fit(iced::Size::new(480.0, 480.0))),
         ..Default::default()
     };
     
     // Set window icon if found
     // Note: Iced window icons typically need PNG format, not SVG
     // The desktop file will handle the SVG icon for the application launcher
-    if let Some(icon_path) = find_pika_icon_path() {
-        eprintln!("[DEBUG] Attempting to load PikaOS icon from: {}", icon_path);
+    if config.low_memory_mode {
+        tracing::debug!("Window icon disabled in low-memory mode");
+    } else if let Some(icon_path) = find_pika_icon_path() {
+        tracing::debug!("Attempting to load PikaOS icon from: {}", icon_path);
         // Try to load as PNG first, then fall back to SVG if PNG loading fails
         if icon_path.ends_with(".png") {
             if let Ok(icon_image) = iced::window::icon::from_file(&icon_path) {
                 window_settings.icon = Some(icon_image);
-                eprintln!("[DEBUG] Window icon set successfully from PNG");
+                tracing::debug!("Window icon set successfully from PNG");
             }
         } else {
             // For SVG, we'll rely on the desktop file for the icon
             // Window icon might not support SVG directly
-            eprintln!("[DEBUG] SVG icon found, will use desktop file for icon display");
+            tracing::debug!("SVG icon found, will use desktop file for icon display");
         }
     } else {
-        eprintln!("[DEBUG] PikaOS icon not found, using default");
+        tracing::debug!("PikaOS icon not found, using default");
     }
     
-    eprintln!("[DEBUG] Creating application settings...");
+    tracing::debug!("Creating application settings...");
     let settings = Settings {
         window: window_settings,
         default_text_size: Pixels(14.0),
-        antialiasing: true,
+        antialiasing: !config.low_memory_mode,
+        flags: config,
         ..Default::default()
     };
+    profiling::mark("window settings ready");
     
-    eprintln!("[DEBUG] Starting BirdNestGUI application...");
+    tracing::debug!("Starting BirdNestGUI application...");
     match BirdNestGUI::run(settings) {
         Ok(_) => {
-            eprintln!("[DEBUG] BirdNestGUI exited successfully");
+            tracing::debug!("BirdNestGUI exited successfully");
             Ok(())
         }
         Err(e) => {
-            eprintln!("[ERROR] BirdNestGUI failed: {:?}", e);
+            tracing::error!("BirdNestGUI failed: {:?}", e);
             Err(e)
         }
     }
@@ -165,6 +344,16 @@ pub enum Message {
     SearchQueryChanged(String),
     Search,
     SearchResults(Vec<PackageInfo>),
+    // As-you-type search against the in-memory package index
+    SearchIndexBuilt(Option<Arc<SearchIndex>>),
+    // The system's and the Flatpak remotes' AppStream catalogs
+    AppCatalogsLoaded(Arc<Catalog>, Arc<Catalog>),
+    SearchDebounced(u64),
+    InstantSearchResults(u64, Vec<PackageInfo>, usize),
+    SearchNamesOnly(bool),
+    SearchCategoryChanged(Option<usize>),
+    SearchSortChanged(SortOrder),
+    SearchPage(usize),
     TogglePackage(String),
     InstallSelected,
     InstallPackage(String),
@@ -174,19 +363,28 @@ pub enum Message {
     OutputReceived(String),
     #[allow(dead_code)]
     ErrorReceived(String),
-    #[allow(dead_code)]
     ClearOutput,
+    ToggleActivity,
+    InitializeBackends,
     LoadInstalledPackages,
     InstalledPackagesLoaded(Vec<PackageInfo>),
+    InstalledScrolled(f32),
+    DescriptionsLoaded(Result<HashMap<String, String>, String>),
     ToggleInstalledPackage(String),
     RemoveSelectedPackages,
     LoadFlatpakApps,
     FlatpakAppsLoaded(Vec<FlatpakInfo>),
+    // Warm-start loads once the window is up
+    Preload,
+    FlatpakPreloadFailed(String),
+    PikmanContainerLoaded(String, Result<Vec<String>, String>),
     RefreshLists,
     ThemeToggled,
     FlatpakSearchQueryChanged(String),
+    FlatpakInstalledFilterChanged(String),
     FlatpakSearch,
     FlatpakSearchResults(Vec<FlatpakInfo>),
+    FlatpakRemoteChosen { application: String, remote: String },
     FlatpakInstallPackage(String),
     FlatpakUpdateRepos,
     FlatpakUpgradeAll,
@@ -202,6 +400,16 @@ pub enum Message {
     RemovePackageDetailLoaded(PackageDetail),
     RemovePackageDetailsLoaded(Vec<PackageDetail>),
     InstalledSearchQueryChanged(String),
+    InstalledSortChanged(SortOrder),
+    InstalledPage(usize),
+    // When dpkg last changed each installed package, for the Recently Updated order
+    ChangeTimesLoaded(HashMap<String, i64>),
+    ToggleDiskUsage,
+    ToggleInstalledArch,
+    ToggleForeignArch,
+    NativeArchLoaded(Option<String>),
+    ListKey(list_nav::ListKey),
+    FlatpakUsageLoaded(Vec<birdnest_core::disk_usage::Usage>),
     // Pikman messages
     PikmanSearchQueryChanged(String),
     PikmanSearch,
@@ -211,61 +419,321 @@ pub enum Message {
     PikmanInstallPackage(String),
     PikmanAutoremove,
     PikmanEnter(String),
-    PikmanExport { package: String, name: Option<String> },
+    PikmanContainerNameChanged(String),
+    PikmanCommandChanged(String),
+    PikmanExport { package: String, distro: &'static str },
+    PikmanExportPackageChanged(String),
+    PikmanExportDistroChanged(&'static str),
+    PikmanExportsLoaded(Vec<birdnest_core::pikman_exports::ExportedApp>),
+    PikmanExportFinished(Result<String, String>),
     PikmanInit { name: String, manager: Option<String> },
     PikmanLog,
     PikmanPurge(Vec<String>),
     PikmanRun { name: String, command: Vec<String> },
     PikmanUpgrades,
-    PikmanUnexport { package: String, name: Option<String> },
+    PikmanUnexport(birdnest_core::pikman_exports::ExportedApp),
+    ShowEnvironments,
     TogglePikmanPackage(String),
+    CopySelectionAsScript(ScriptFormat),
+    /// Open one dialog applying everything ticked in every tab
+    ReviewBatch,
+    ClearBatch,
+    ShowCliReference,
+    ShowLogs,
+    ShowSettings,
+    /// The last week's package and sources changes, from the Maintenance tab
+    ShowDigest,
+    /// Pick preinstalled apps to remove, from the Maintenance tab
+    ShowTrim,
+    MaintenanceUsageLoaded(Vec<(FlatpakInstallation, Option<u64>)>),
+    RepairFlatpak(FlatpakInstallation),
+    FlatpakRepairEvent(maintenance::RepairEvent),
+    DeferredLoaded(Vec<DeferredRemoval>),
+    RestoreDeferred(String),
+    DeferredRestored(Result<String, String>),
+    UninstallDeferred(String),
+    DeferredUninstalled(Result<usize, String>),
+    /// List what starts at login again
+    CheckStartup,
+    StartupLoaded(Vec<StartupItem>),
+    /// Turn a startup item, by id, on or off
+    ToggleStartup(String, bool),
+    StartupToggled(String, bool, Result<(), String>),
+    SourcesLoaded(Result<(Vec<birdnest_core::repos::Source>, Option<String>), String>),
+    SourceBackupsLoaded(Vec<birdnest_core::sources_backup::Snapshot>),
+    /// Ask before restoring this backup, or stop asking (None)
+    ConfirmRestoreSources(Option<String>),
+    SourceUriChanged(String),
+    SourceSuiteChanged(String),
+    SourceComponentsChanged(String),
+    AddSource,
+    EditSource(birdnest_core::repos::Edit),
+    /// Add (true) or remove (false) a PPA with its signing key
+    EditPpa(birdnest_core::ppa::Ppa, bool),
+    ConfirmRemoveSource(Option<(PathBuf, usize)>),
+    SourceEdited(Result<String, String>),
+    LoadAppImages,
+    AppImagesLoaded(Vec<birdnest_core::appimage::AppImage>),
+    AppImageAction(PathBuf, appimage::Action),
+    CheckAppImageUpdates,
+    ConfirmRemoveAppImage(Option<PathBuf>),
+    AppImageDone(PathBuf, appimage::Action, Result<Option<birdnest_core::appimage::UpdateStatus>, String>),
+    LoadUpdates,
+    UpdatesLoaded(Result<Vec<updates::UpdateInfo>, String>),
+    ToggleUpdate((bool, String)),
+    // Open or close a row to show what its update changes
+    ToggleUpdateChangelog((bool, String)),
+    ExpandAllUpdates,
+    UpdateChangelogLoaded((bool, String), Result<Vec<ChangelogEntry>, String>),
+    SelectAllUpdates,
+    UpgradeSelected,
+    RestartStatusLoaded(RestartStatus),
+    ToggleRestartService(String),
+    RestartServices,
+    Reboot,
+    RestartActionFinished(Result<(), String>),
+    ShutdownUpdatesToggled(bool),
+    ShutdownUpdatesLoaded(Option<StagedUpdates>),
+    ShutdownUpdatesChanged(Result<String, String>),
+    // Transactions queued or run by any window
+    QueueChanged(Vec<transaction_queue::QueueItem>),
+    ClearFinishedTransactions,
+    // Published by backends and transactions in any window
+    Bus(BusEvent),
+    // Another tool changed the package or Flatpak database
+    DatabaseChanged(db_watch::Change),
+    // Periodic metadata refresh, within the configured schedule
+    BackgroundRefresh,
+    BackgroundRefreshFinished(Result<bool, String>),
+    UpdateCheckTick,
+    UpdateCheckFinished(Result<Option<UpdateCount>, String>),
+    SourceHealthChecked(Vec<status_bar::SourceHealth>),
+    // Queue the selected Flatpaks' runtimes to download overnight
+    PredownloadSelected,
+    PredownloadQueued(Result<usize, String>),
+    PredownloadTick,
+    PredownloadsFinished(Result<usize, String>),
+    // The header search field, which drives the active tab's search
+    HeaderQueryChanged(String),
+    HeaderSearch,
+    // Package names offered under the header field as it is typed in
+    SuggestionPicked(String),
+    AcceptSuggestion,
+    DismissSuggestions,
+    SearchAlsoIn(ExtraSource),
+    ExtraResultsLoaded(ExtraSource, String, global_search::ExtraResults),
+    InstallExtra(ExtraSource, String),
+    // "All sources" on the Search tab
+    ToggleAllSources(bool),
+    AllSourcesLoaded(PackageSource, String, all_sources::SourceResults),
+    InstallFrom(PackageInfo),
+    // The details pane next to the Search and Installed lists
+    DetailsLoaded(String, Result<PackageDetails, String>),
+    ScreenshotLoaded(String, Option<PathBuf>),
+    LoadChangelog,
+    ChangelogLoaded(String, Result<Vec<ChangelogEntry>, String>),
+    MeasureFootprint,
+    FootprintLoaded(String, Result<Footprint, String>),
+    CloseDetails,
+    // Quick actions on list rows
+    RowMenuToggled(RowTarget),
+    RowAction(RowTarget, RowAction),
+    RowActionFinished(Result<String, String>),
+    // Hold or release an installed package (the lock on its row)
+    ToggleHold(String),
+    HoldChanged(Result<String, String>),
+    HeldLoaded(HashSet<String>),
+    RowFilesLoaded(RowTarget, Result<Vec<String>, String>),
+    // A message for the dialog open in a child window
+    Dialog(window::Id, DialogMessage),
+    WindowClosed(window::Id),
+    WindowResized(window::Id, f32),
+    WindowFocused(window::Id, bool),
+    // A file dropped on the main window; .flatpakref and .flatpak bundles install
+    FileDropped(PathBuf),
+    // The Browse tab's category grid
+    BrowseCategory(usize),
+    BrowseShowMore,
+    BrowseDetails(String),
+    // Opens or closes the menu the tab bar collapses into in narrow windows
+    ToggleNavMenu,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Tab {
     Search,
+    Browse,
     Installed,
     Flatpak,
     Pikman,
+    AppImage,
+    Updates,
+    Sources,
+    Maintenance,
+}
+
+// The tab a config's default_tab names, by label in any case
+fn tab_named(name: &str) -> Option<Tab> {
+    TABS.iter().find(|(label, _)| label.eq_ignore_ascii_case(name)).map(|(_, tab)| *tab)
+}
+
+fn theme_from(preference: ThemePreference) -> AppTheme {
+    match preference {
+        ThemePreference::Dark => AppTheme::Dark,
+        ThemePreference::Light => AppTheme::Light,
+    }
 }
 
+const TABS: [(&str, Tab); 9] = [
+    ("Search", Tab::Search),
+    ("Browse", Tab::Browse),
+    ("Installed", Tab::Installed),
+    ("Flatpak", Tab::Flatpak),
+    ("Pikman", Tab::Pikman),
+    ("AppImage", Tab::AppImage),
+    ("Updates", Tab::Updates),
+    ("Sources", Tab::Sources),
+    ("Maintenance", Tab::Maintenance),
+];
+
 #[derive(Debug)]
 pub struct BirdNestGUI {
     current_tab: Tab,
     theme: AppTheme,
     search_query: String,
     search_results: Vec<PackageInfo>,
+    // Built in the background at startup; until then searches spawn apt-cache
+    search_index: Option<Arc<SearchIndex>>,
+    // Display names, icons and screenshots for packages and Flatpaks; empty
+    // until loaded or where no catalog is installed
+    app_catalog: Arc<Catalog>,
+    flatpak_catalog: Arc<Catalog>,
+    browse: browse::Browse,
+    // Bumped on every keystroke so stale debounce timers and results are dropped
+    search_generation: u64,
     selected_packages: HashSet<String>,
     installed_packages: Vec<PackageInfo>,
     installed_search_query: String,
+    installed_sort: SortOrder,
+    installed_pager: Pager,
+    // Sizes and change times of installed packages, for sorting by them
+    installed_facts: InstalledFacts,
+    disk_usage: disk_usage::DiskUsageState,
+    architectures: architectures::Architectures,
+    // The row type-ahead and Home/End/PageUp/PageDown moved to
+    installed_cursor: list_nav::ListCursor,
+    // Descriptions of installed packages, fetched for the rows in view
+    installed_descriptions: descriptions::Descriptions,
     selected_installed: HashSet<String>,
     flatpak_apps: Vec<FlatpakInfo>,
     flatpak_search_query: String,
+    // Narrows the installed list, unlike the search query which searches remotes
+    flatpak_installed_filter: String,
     flatpak_search_results: Vec<FlatpakInfo>,
     selected_flatpak: HashSet<String>,
+    // Remote picked per search result (app ID -> remote), overriding the preferred one
+    flatpak_remote_choice: HashMap<String, String>,
+    flatpak_default_remote: Option<String>,
+    // Flags the install dialogs start with
+    install_options: InstallOptions,
     // Pikman state
     pikman_search_query: String,
     pikman_search_results: Vec<PackageInfo>,
-    selected_pikman: HashSet<String>,
+    // With the source each came from
+    selected_pikman: HashMap<String, PackageSource>,
     pikman_filter: Option<String>, // "aur", "fedora", "alpine", None for default
     pikman_loading: bool,
+    // Each container's installed packages, by distro flag, once preloaded;
+    // None while pikman is still listing them
+    pikman_installed: HashMap<String, Option<Result<Vec<String>, String>>>,
+    pikman_terminal: pikman_terminal::PikmanTerminal,
+    pikman_exports: pikman_exports::PikmanExports,
+    // Terminal emulator interactive commands open in; found on PATH when unset
+    terminal: Option<String>,
     #[allow(dead_code)]
     install_dialog: Option<PackageDetail>,
     #[allow(dead_code)]
     remove_dialog: Option<PackageDetail>,
     #[allow(dead_code)]
     packages_to_remove: Vec<String>, // Store list of packages for batch removal
-    output_log: Vec<String>,
-    error_log: Vec<String>,
+    activity: activity::Activity,
     #[allow(dead_code)]
     command_tx: Option<Arc<mpsc::UnboundedSender<GuiCommand>>>,
     border_radius: f32,
+    // The main window's width, which picks the narrow layout
+    window_width: f32,
+    nav_open: bool,
     // Loading state flags for lazy loading
     installed_loaded: bool,
     flatpak_loaded: bool,
     // Loading indicators
     installed_loading: bool,
     flatpak_loading: bool,
+    // A search without the index (still building, or unavailable) is running
+    searching: bool,
+    refreshing: bool,
+    // Which package sources' tools are installed, for the status bar
+    source_health: Vec<status_bar::SourceHealth>,
+    // The backend can hold packages, so installed rows get a lock toggle
+    can_hold: bool,
+    // Cap on rendered/stored result rows (low-memory mode)
+    result_limit: Option<usize>,
+    // Low-memory mode leaves out app icons and screenshots
+    low_memory: bool,
+    // Rows to a page on Search and Installed, and the filters offered past that
+    search_limit: Option<usize>,
+    search_filters: search_filters::SearchFilters,
+    search_sort: SortOrder,
+    // Positions in search_results, in search_sort's order
+    search_order: Vec<usize>,
+    search_pager: Pager,
+    // How often and when to refresh metadata without being asked
+    refresh_schedule: RefreshSchedule,
+    // Queued runtimes are downloading; the next tick leaves them be
+    predownloading: bool,
+    // Days removed GUI apps stay hidden before they are uninstalled
+    deferred_removal_days: Option<u32>,
+    // How often to count available updates for the Updates tab's badge
+    update_check: UpdateCheck,
+    // What loads in the background once the window is up
+    preload: Preload,
+    // The badge; None until the first check or Updates tab load
+    update_count: Option<UpdateCount>,
+    // The count the last notification announced, so checks only notify about news
+    notified_updates: usize,
+    checking_updates: bool,
+    // Channels update counts and finished transactions are announced on
+    notifiers: Notifiers,
+    notify_on_finish: bool,
+    // When every installed list was last reloaded; database changes from
+    // before it are already in them
+    lists_refreshed_at: Option<SystemTime>,
+    // The BirdNest window with focus; finished transactions are only
+    // announced while none has it
+    focused_window: Option<window::Id>,
+    // Something asked for attention since a window last had focus
+    announced: bool,
+    // The user's zoom on top of the desktop's scaling, for every window
+    ui_scale: f32,
+    // Source of search results and the installed list (real system or mock fixtures)
+    backend: Arc<dyn PackageBackend>,
+    updates: updates::UpdatesState,
+    maintenance: maintenance::MaintenanceState,
+    sources: sources::SourcesState,
+    appimages: appimage::AppImageState,
+    // ABRoot/OSTree: native package changes only apply after a reboot
+    system: SystemKind,
+    // Install/remove/upgrade dialogs open as child windows
+    windows: windows::Windows,
+    // Key presses carry no window, so list navigation waits for the main window to have focus
+    main_focused: bool,
+    // The quick-actions menu open on a list row, if any
+    row_menu: row_menu::RowMenu,
+    global_search: global_search::GlobalSearch,
+    all_sources: all_sources::AllSources,
+    autocomplete: autocomplete::Autocomplete,
+    details: details_pane::DetailsPane,
+    transactions: Vec<transaction_queue::QueueItem>,
 }
 
 #[derive(Debug, Clone)]
@@ -282,81 +750,105 @@ impl Application for BirdNestGUI {
     type Message = Message;
     type Theme = IcedTheme;
     type Executor = executor::Default;
-    type Flags = ();
+    type Flags = Config;
 
-    fn new(_flags: ()) -> (BirdNestGUI, Command<Message>) {
-        eprintln!("[DEBUG] Application::new() called - initializing BirdNestGUI...");
-        
-        eprintln!("[DEBUG] Creating message channel...");
-        let (tx, _rx) = mpsc::unbounded_channel();
-        eprintln!("[DEBUG] Message channel created successfully");
-        
-        eprintln!("[DEBUG] Creating BirdNestGUI struct...");
-        let gui = BirdNestGUI {
-            current_tab: Tab::Search,
-            theme: AppTheme::Dark,
-            search_query: String::new(),
-            search_results: Vec::new(),
-            selected_packages: HashSet::new(),
-            installed_packages: Vec::new(),
-            installed_search_query: String::new(),
-            selected_installed: HashSet::new(),
-            flatpak_apps: Vec::new(),
-            flatpak_search_query: String::new(),
-            flatpak_search_results: Vec::new(),
-            selected_flatpak: HashSet::new(),
-            install_dialog: None,
-            remove_dialog: None,
-            packages_to_remove: Vec::new(),
-            output_log: Vec::new(),
-            error_log: Vec::new(),
-            command_tx: Some(Arc::new(tx)),
-            border_radius: 24.0, // EXTREME rounded for maximum bubble effect
-            installed_loaded: false,
-            flatpak_loaded: false,
-            installed_loading: true, // Start loading immediately
-            flatpak_loading: false,
-            pikman_search_query: String::new(),
-            pikman_search_results: Vec::new(),
-            selected_pikman: HashSet::new(),
-            pikman_filter: None,
-            pikman_loading: false,
-        };
-        eprintln!("[DEBUG] BirdNestGUI struct created successfully");
-        
-        eprintln!("[DEBUG] Starting preload of installed packages...");
-        let cmd = Command::perform(load_installed_packages(), Message::InstalledPackagesLoaded);
-        eprintln!("[DEBUG] Preload command created, returning from Application::new()");
-        
-        (gui, cmd)
+    fn new(config: Config) -> (BirdNestGUI, Command<Message>) {
+        BirdNestGUI::with_backend(config, create_backend(), immutable::detect())
     }
 
-    fn title(&self) -> String {
-        String::from("BirdNest - Package Manager")
+    fn title(&self, window: window::Id) -> String {
+        self.windows
+            .title(window)
+            .unwrap_or_else(|| String::from("BirdNest - Package Manager"))
     }
 
     fn update(&mut self, message: Message) -> Command<Message> {
-        eprintln!("[DEBUG] update() called with message: {:?}", std::mem::discriminant(&message));
+        tracing::debug!("update() called with message: {:?}", std::mem::discriminant(&message));
         match message {
             Message::SearchQueryChanged(query) => {
                 self.search_query = query;
+                self.search_generation += 1;
+                if self.search_index.is_none() {
+                    return Command::none();
+                }
+                if self.search_query.trim().is_empty() {
+                    self.set_search_results(Vec::new());
+                    return Command::none();
+                }
+                let generation = self.search_generation;
+                Command::perform(tokio::time::sleep(SEARCH_DEBOUNCE), move |_| Message::SearchDebounced(generation))
+            }
+            Message::SearchDebounced(generation) => {
+                if generation != self.search_generation {
+                    return Command::none();
+                }
+                self.instant_search()
+            }
+            Message::InstantSearchResults(generation, results, total) => {
+                if generation == self.search_generation {
+                    self.search_filters.set_total(total, self.search_limit);
+                    self.set_search_results(results);
+                    self.global_search.searched = true;
+                }
+                Command::none()
+            }
+            Message::SearchIndexBuilt(index) => {
+                if let Some(index) = &index {
+                    tracing::debug!("SearchIndexBuilt: {} packages indexed", index.len());
+                    profiling::mark("search index built");
+                }
+                self.search_index = index;
+                Command::none()
+            }
+            Message::AppCatalogsLoaded(system, flatpak) => {
+                self.app_catalog = system;
+                self.flatpak_catalog = flatpak;
                 Command::none()
             }
             Message::Search => {
                 let query = self.search_query.clone();
-                if !query.is_empty() {
-                    self.output_log.push(format!("Searching for: {}", query));
-                    Command::perform(search_packages(query), Message::SearchResults)
+                if self.all_sources.enabled && !query.trim().is_empty() {
+                    self.search_all_sources(query)
+                } else if self.search_index.is_some() && !query.trim().is_empty() {
+                    // Enter skips the debounce
+                    self.search_generation += 1;
+                    self.instant_search()
+                } else if !query.is_empty() {
+                    self.activity.info(format!("Searching for: {}", query));
+                    self.searching = true;
+                    Command::perform(search_packages(self.backend.clone(), query), Message::SearchResults)
                 } else {
                     Command::none()
                 }
             }
-            Message::SearchResults(results) => {
-                self.search_results = results;
+            Message::SearchNamesOnly(names_only) => {
+                self.search_filters.names_only = names_only;
+                self.update(Message::Search)
+            }
+            Message::SearchCategoryChanged(category) => {
+                self.search_filters.category = category;
+                self.update(Message::Search)
+            }
+            Message::SearchResults(mut results) => {
+                self.searching = false;
+                self.search_filters.apply(&mut results, &self.search_query, &self.app_catalog, self.search_limit);
+                self.cap_results(&mut results);
+                self.set_search_results(results);
+                self.global_search.searched = true;
                 Command::none()
             }
+            Message::SearchSortChanged(order) => {
+                self.search_sort = order;
+                self.search_order = self.installed_facts.order(&self.search_results, order);
+                self.search_pager.reset();
+                scrollable::snap_to(search_list_id(), scrollable::RelativeOffset::START)
+            }
+            Message::SearchPage(page) => {
+                self.search_pager.page = page;
+                scrollable::snap_to(search_list_id(), scrollable::RelativeOffset::START)
+            }
             Message::InstallPackage(package) => {
-                Command::perform(load_package_detail(package, false), |result| {
+                Command::perform(load_package_detail(self.backend.clone(), package, false), |result| {
                     match result {
                         Ok(detail) => Message::PackageDetailLoaded(detail),
                         Err(e) => Message::ErrorReceived(format!("Failed to load package details: {}", e)),
@@ -364,26 +856,8 @@ impl Application for BirdNestGUI {
                 })
             }
             Message::PackageDetailLoaded(detail) => {
-                // Launch separate install window as a separate process
-                let package_names = vec![detail.name.clone()];
-                let is_flatpak = detail.is_flatpak;
-                Command::perform(
-                    async move {
-                        use tokio::process::Command as TokioCommand;
-                        let exe_path = std::env::current_exe()
-                            .unwrap_or_else(|_| std::path::PathBuf::from("birdnest"));
-                        let mut cmd = TokioCommand::new(&exe_path);
-                        cmd.arg("install-dialog");
-                        for pkg in &package_names {
-                            cmd.arg(pkg);
-                        }
-                        if is_flatpak {
-                            cmd.arg("--flatpak");
-                        }
-                        let _ = cmd.spawn();
-                    },
-                    |_| Message::InstalledPackagesLoaded(Vec::new()),
-                )
+                let dialog = self.install_dialog(vec![detail.name], detail.is_flatpak);
+                self.open_dialog(Dialog::Install(Box::new(dialog)))
             }
             Message::ShowInstallDialog(detail) => {
                 // Launch separate install window
@@ -404,26 +878,8 @@ impl Application for BirdNestGUI {
                     self.selected_packages.clear();
                 }
                 
-                // Launch separate install window as a separate process
-                let package_names_clone = package_names.clone();
-                let is_flatpak = detail.is_flatpak;
-                Command::perform(
-                    async move {
-                        use tokio::process::Command as TokioCommand;
-                        let exe_path = std::env::current_exe()
-                            .unwrap_or_else(|_| std::path::PathBuf::from("birdnest"));
-                        let mut cmd = TokioCommand::new(&exe_path);
-                        cmd.arg("install-dialog");
-                        for pkg in &package_names_clone {
-                            cmd.arg(pkg);
-                        }
-                        if is_flatpak {
-                            cmd.arg("--flatpak");
-                        }
-                        let _ = cmd.spawn();
-                    },
-                    |_| Message::InstalledPackagesLoaded(Vec::new()),
-                )
+                let dialog = self.install_dialog(package_names, detail.is_flatpak);
+                self.open_dialog(Dialog::Install(Box::new(dialog)))
             }
             Message::HideInstallDialog => {
                 // No longer needed with separate windows
@@ -436,25 +892,7 @@ impl Application for BirdNestGUI {
             Message::RemovePackage(package) => {
                 // Check if it's a Flatpak (contains a period, like org.example.App)
                 let is_flatpak = package.contains('.');
-                // Launch separate remove window as a separate process
-                let packages = vec![package];
-                Command::perform(
-                    async move {
-                        use tokio::process::Command as TokioCommand;
-                        let exe_path = std::env::current_exe()
-                            .unwrap_or_else(|_| std::path::PathBuf::from("birdnest"));
-                        let mut cmd = TokioCommand::new(&exe_path);
-                        cmd.arg("remove-dialog");
-                        for pkg in &packages {
-                            cmd.arg(pkg);
-                        }
-                        if is_flatpak {
-                            cmd.arg("--flatpak");
-                        }
-                        let _ = cmd.spawn();
-                    },
-                    |_| Message::InstalledPackagesLoaded(Vec::new()),
-                )
+                self.open_dialog(Dialog::Remove(self.remove_dialog(vec![package], is_flatpak)))
             }
             Message::RemovePackageDetailLoaded(_detail) => {
                 // No longer needed - handled in separate window
@@ -471,22 +909,7 @@ impl Application for BirdNestGUI {
                 
                 self.selected_installed.clear();
                 
-                // Launch separate remove window as a separate process
-                let package_names_clone = package_names.clone();
-                Command::perform(
-                    async move {
-                        use tokio::process::Command as TokioCommand;
-                        let exe_path = std::env::current_exe()
-                            .unwrap_or_else(|_| std::path::PathBuf::from("birdnest"));
-                        let mut cmd = TokioCommand::new(&exe_path);
-                        cmd.arg("remove-dialog");
-                        for pkg in &package_names_clone {
-                            cmd.arg(pkg);
-                        }
-                        let _ = cmd.spawn();
-                    },
-                    |_| Message::InstalledPackagesLoaded(Vec::new()),
-                )
+                self.open_dialog(Dialog::Remove(self.remove_dialog(package_names, false)))
             }
             Message::HideRemoveDialog => {
                 // No longer needed with separate windows
@@ -497,295 +920,1161 @@ impl Application for BirdNestGUI {
                 Command::none()
             }
             Message::TabChanged(tab) => {
-                eprintln!("[DEBUG] Tab changed to: {:?}", tab);
+                tracing::debug!("Tab changed to: {:?}", tab);
                 self.current_tab = tab;
-                match tab {
+                self.nav_open = false;
+                self.details.close();
+                self.autocomplete.close();
+                self.global_search.searched = false;
+                self.global_search.extras.clear();
+                let load = match tab {
                     Tab::Flatpak => {
-                        eprintln!("[DEBUG] Flatpak tab selected - loaded: {}, loading: {}", self.flatpak_loaded, self.flatpak_loading);
+                        tracing::debug!("Flatpak tab selected - loaded: {}, loading: {}", self.flatpak_loaded, self.flatpak_loading);
                         // Clear search results and query when switching to Flatpak tab
-                        // This ensures we show installed apps by default after installation
+                        // This ensures we show installed apps by default after installation.
+                        // Ticked apps stay ticked for the batch.
                         self.flatpak_search_query.clear();
                         self.flatpak_search_results.clear();
-                        self.selected_flatpak.clear();
                         // Always reload installed Flatpak apps to show newly installed packages
                         if !self.flatpak_loading {
-                            eprintln!("[DEBUG] Starting to load Flatpak apps...");
+                            tracing::debug!("Starting to load Flatpak apps...");
                             self.flatpak_loading = true;
                             Command::perform(load_flatpak_apps(), |result| {
                                 match result {
                                     Ok(apps) => {
-                                        eprintln!("[DEBUG] Flatpak apps loaded successfully: {} apps", apps.len());
+                                        tracing::debug!("Flatpak apps loaded successfully: {} apps", apps.len());
                                         Message::FlatpakAppsLoaded(apps)
                                     }
                                     Err(e) => {
-                                        eprintln!("[ERROR] Failed to load Flatpak apps: {}", e);
+                                        tracing::error!("Failed to load Flatpak apps: {}", e);
                                         Message::ErrorReceived(e.to_string())
                                     }
                                 }
                             })
                         } else {
-                            eprintln!("[DEBUG] Flatpak apps already loading, skipping reload");
+                            tracing::debug!("Flatpak apps already loading, skipping reload");
                             Command::none()
                         }
                     }
                     Tab::Installed => {
-                        eprintln!("[DEBUG] Installed tab selected - loaded: {}, loading: {}", self.installed_loaded, self.installed_loading);
+                        tracing::debug!("Installed tab selected - loaded: {}, loading: {}", self.installed_loaded, self.installed_loading);
                         // If already loaded, show immediately. If loading, wait. Otherwise start loading.
                         if self.installed_loaded {
-                            eprintln!("[DEBUG] Installed packages already loaded, showing immediately");
+                            tracing::debug!("Installed packages already loaded, showing immediately");
                             Command::none()
                         } else if self.installed_loading {
                             Command::none() // Already loading from startup
                         } else {
                             self.installed_loading = true;
-                            Command::perform(load_installed_packages(), Message::InstalledPackagesLoaded)
+                            Command::perform(load_installed_packages(self.backend.clone()), Message::InstalledPackagesLoaded)
+                        }
+                    }
+                    Tab::Updates => self.update(Message::LoadUpdates),
+                    Tab::Maintenance => {
+                        // Apps hidden since the last visit are listed too
+                        let deferred = Command::perform(maintenance::load_deferred(), Message::DeferredLoaded);
+                        let startup = self.update(Message::CheckStartup);
+                        // Sizes change with every install, so re-measure unless a repair is running
+                        if self.maintenance.repairing.is_some() || self.maintenance.usage_loading {
+                            Command::batch([deferred, startup])
+                        } else {
+                            self.maintenance.usage_loading = true;
+                            Command::batch([Command::perform(maintenance::load_usage(), Message::MaintenanceUsageLoaded), deferred, startup])
                         }
                     }
+                    // Re-read on every visit; other tools edit these files too
+                    Tab::Sources if !self.sources.loading => {
+                        self.sources.loading = true;
+                        Command::batch([
+                            Command::perform(sources::load(), Message::SourcesLoaded),
+                            Command::perform(sources::load_backups(), Message::SourceBackupsLoaded),
+                        ])
+                    }
+                    // Downloads land in ~/Applications while BirdNest is open
+                    Tab::AppImage => self.update(Message::LoadAppImages),
+                    // pikman isn't the only way to export from a container
+                    Tab::Pikman => Command::perform(pikman_exports::load(), Message::PikmanExportsLoaded),
                     _ => Command::none(),
+                };
+                // The header query carries over, searching the new tab's sources
+                if self.global_search.query.trim().is_empty() || matches!(tab, Tab::AppImage | Tab::Updates | Tab::Sources | Tab::Maintenance) {
+                    return load;
                 }
+                let forward = self.forward_header_query();
+                Command::batch([load, forward, self.search_active_tab()])
             }
-            Message::InstalledPackagesLoaded(packages) => {
-                eprintln!("[DEBUG] InstalledPackagesLoaded: {} packages loaded", packages.len());
-                self.installed_packages = packages;
-                self.installed_loaded = true;
-                self.installed_loading = false;
-                eprintln!("[DEBUG] Installed packages state updated - loaded: true, loading: false");
+            Message::LoadUpdates => {
+                if self.updates.loading {
+                    return Command::none();
+                }
+                self.updates.loading = true;
+                // Upgrades run in a separate window, so pending restarts are picked up here
+                Command::batch([
+                    Command::perform(updates::load_updates(self.backend.clone()), Message::UpdatesLoaded),
+                    Command::perform(restart_banner::check(), Message::RestartStatusLoaded),
+                    Command::perform(updates::load_staged(), Message::ShutdownUpdatesLoaded),
+                    Command::perform(updates::load_held(self.backend.clone()), Message::HeldLoaded),
+                ])
+            }
+            Message::RestartStatusLoaded(status) => {
+                self.updates.restart.set_status(status);
                 Command::none()
             }
-            Message::InstalledSearchQueryChanged(query) => {
-                self.installed_search_query = query;
+            Message::ToggleRestartService(service) => {
+                self.updates.restart.toggle(service);
                 Command::none()
             }
-            Message::ToggleInstalledPackage(package) => {
-                if self.selected_installed.contains(&package) {
-                    self.selected_installed.remove(&package);
-                } else {
-                    self.selected_installed.insert(package);
-                }
+            Message::RestartServices => {
+                Command::perform(restart_banner::restart_services(self.updates.restart.selection()), Message::RestartActionFinished)
+            }
+            Message::Reboot => {
+                Command::perform(restart_banner::reboot(), Message::RestartActionFinished)
+            }
+            Message::QueueChanged(items) => {
+                self.transactions = items;
                 Command::none()
             }
-            Message::RemoveSelectedPackages => {
-                let packages: Vec<String> = self.selected_installed.iter().cloned().collect();
-                if packages.is_empty() {
+            Message::ClearFinishedTransactions => {
+                transaction_queue::clear_finished();
+                Command::none()
+            }
+            Message::Bus(event) => self.bus_event(event),
+            Message::DatabaseChanged(change) => self.database_changed(change),
+            Message::BackgroundRefresh => {
+                self.refreshing = true;
+                Command::perform(background_refresh(self.refresh_schedule.clone()), Message::BackgroundRefreshFinished)
+            }
+            Message::UpdateCheckTick => {
+                if self.checking_updates || self.update_check.every_hours == 0 {
                     return Command::none();
                 }
-                
-                // Invalidate installed packages cache - will reload when user switches back to tab
-                self.installed_loaded = false;
-                invalidate_packages_cache();
-                
-                // Launch separate remove window as a separate process
-                let packages_clone = packages.clone();
-                self.selected_installed.clear();
-                
+                self.checking_updates = true;
+                Command::perform(check_updates(self.backend.clone(), self.refresh_schedule.clone()), Message::UpdateCheckFinished)
+            }
+            Message::UpdateCheckFinished(result) => {
+                self.checking_updates = false;
+                match result {
+                    Ok(Some(count)) => {
+                        if self.update_check.notify && update_check::should_notify(self.notified_updates, &count) {
+                            self.notifiers.notify(&Notification::updates(&count));
+                        }
+                        self.notified_updates = count.total();
+                        self.update_count = Some(count);
+                    }
+                    Ok(None) => {}
+                    Err(e) => tracing::warn!("Update check failed: {}", e),
+                }
+                Command::none()
+            }
+            Message::BackgroundRefreshFinished(result) => {
+                self.refreshing = false;
+                match result {
+                    Ok(true) => self.update(Message::LoadUpdates),
+                    Ok(false) => Command::none(),
+                    Err(e) => {
+                        tracing::warn!("Background refresh failed: {}", e);
+                        Command::none()
+                    }
+                }
+            }
+            Message::PredownloadSelected => {
+                let default_remote = self.flatpak_default_remote.clone();
+                let apps: Vec<(String, String)> = self
+                    .selected_flatpak
+                    .iter()
+                    .map(|app| {
+                        let remote = self.flatpak_remote(app).or(default_remote.as_deref()).unwrap_or("flathub").to_string();
+                        (app.clone(), remote)
+                    })
+                    .collect();
+                self.activity.info(format!("Looking up the runtimes {} app(s) need...", apps.len()));
                 Command::perform(
                     async move {
-                        use tokio::process::Command as TokioCommand;
-                        let exe_path = std::env::current_exe()
-                            .unwrap_or_else(|_| std::path::PathBuf::from("birdnest"));
-                        let mut cmd = TokioCommand::new(&exe_path);
-                        cmd.arg("remove-dialog");
-                        for pkg in &packages_clone {
-                            cmd.arg(pkg);
-                        }
-                        let _ = cmd.spawn();
+                        tokio::task::spawn_blocking(move || predownload::queue(&apps))
+                            .await
+                            .map_err(|e| format!("Task error: {}", e))?
+                            .map_err(|e| e.to_string())
                     },
-                    |_| Message::InstalledPackagesLoaded(Vec::new()),
+                    Message::PredownloadQueued,
                 )
             }
-            Message::RemovePackageDetailsLoaded(_details) => {
-                // No longer needed - handled in separate window
-                Command::none()
-            }
-            Message::LoadFlatpakApps => {
-                #[cfg(debug_assertions)]
-                eprintln!("[FLATPAK DEBUG] Message::LoadFlatpakApps received");
-                Command::perform(load_flatpak_apps(), |result| {
-                    match result {
-                        Ok(apps) => {
-                            #[cfg(debug_assertions)]
-                            eprintln!("[FLATPAK DEBUG] LoadFlatpakApps: Success, loaded {} apps", apps.len());
-                            Message::FlatpakAppsLoaded(apps)
-                        },
-                        Err(e) => {
-                            #[cfg(debug_assertions)]
-                            eprintln!("[FLATPAK DEBUG] LoadFlatpakApps: ERROR - {}", e);
-                            Message::ErrorReceived(e.to_string())
-                        },
+            Message::PredownloadQueued(result) => {
+                self.activity.info(match result {
+                    Ok(0) => "Their runtimes are already installed; nothing to download ahead".to_string(),
+                    Ok(pending) => {
+                        let window = predownload::schedule(&self.refresh_schedule).hours.unwrap_or(predownload::OVERNIGHT);
+                        format!("{} runtime(s) queued to download during {}", pending, window)
                     }
-                })
-            }
-            Message::FlatpakAppsLoaded(apps) => {
-                eprintln!("[DEBUG] FlatpakAppsLoaded: {} apps loaded", apps.len());
-                self.flatpak_apps = apps;
-                self.flatpak_loaded = true;
-                self.flatpak_loading = false;
-                eprintln!("[DEBUG] Flatpak apps state updated - loaded: true, loading: false");
+                    Err(e) => format!("Couldn't queue runtimes: {}", e),
+                });
                 Command::none()
             }
-            Message::RefreshLists => {
-                // Reset loaded flags to force reload
-                self.installed_loaded = false;
-                self.flatpak_loaded = false;
-                // Invalidate cache to force fresh load
-                invalidate_packages_cache();
-                Command::perform(load_installed_packages(), Message::InstalledPackagesLoaded)
-            }
-            Message::LoadInstalledPackages => {
-                if !self.installed_loading {
-                    self.installed_loading = true;
-                    self.installed_loaded = false;
-                    Command::perform(load_installed_packages(), Message::InstalledPackagesLoaded)
-                } else {
-                    Command::none()
+            Message::PredownloadTick => {
+                if self.predownloading {
+                    return Command::none();
                 }
+                self.predownloading = true;
+                Command::perform(run_predownloads(self.refresh_schedule.clone()), Message::PredownloadsFinished)
             }
-            Message::TogglePackage(package) => {
-                #[cfg(debug_assertions)]
-                eprintln!("[FLATPAK DEBUG] TogglePackage: Toggling package: '{}'", package);
-                // Check if it's a flatpak or regular package
-                if self.flatpak_search_results.iter().any(|f| f.application == package) {
-                    #[cfg(debug_assertions)]
-                    eprintln!("[FLATPAK DEBUG] TogglePackage: Package '{}' is a Flatpak (application ID)", package);
-                    if self.selected_flatpak.contains(&package) {
-                        self.selected_flatpak.remove(&package);
-                        #[cfg(debug_assertions)]
-                        eprintln!("[FLATPAK DEBUG] TogglePackage: Removed '{}' from selected_flatpak", package);
-                    } else {
-                        self.selected_flatpak.insert(package.clone());
-                        #[cfg(debug_assertions)]
-                        eprintln!("[FLATPAK DEBUG] TogglePackage: Added '{}' to selected_flatpak", package);
-                    }
-                } else {
-                    if self.selected_packages.contains(&package) {
-                        self.selected_packages.remove(&package);
-                    } else {
-                        self.selected_packages.insert(package);
-                    }
+            Message::PredownloadsFinished(result) => {
+                self.predownloading = false;
+                match result {
+                    Ok(0) => {}
+                    Ok(downloaded) => self.activity.info(format!("Downloaded {} runtime(s) ahead of their apps", downloaded)),
+                    Err(e) => tracing::warn!("Runtime pre-download failed: {}", e),
                 }
                 Command::none()
             }
-            Message::InstallSelected => {
-                // Check if we're installing flatpaks or regular packages
-                if !self.selected_flatpak.is_empty() {
-                    let packages: Vec<String> = self.selected_flatpak.iter().cloned().collect();
-                    #[cfg(debug_assertions)]
-                    eprintln!("[FLATPAK DEBUG] InstallSelected: Installing {} Flatpak packages: {:?}", packages.len(), packages);
-                    // Validate that all packages are valid Flatpak application IDs
-                    for pkg in &packages {
-                        if !pkg.contains('.') {
-                            #[cfg(debug_assertions)]
-                            eprintln!("[FLATPAK DEBUG] InstallSelected: ERROR - Invalid Flatpak ID detected: '{}' (missing period)", pkg);
-                            let error_msg = format!("Invalid Flatpak application ID: '{}'. Please select packages from the search results.", pkg);
-                            return Command::perform(
-                                async move { },
-                                move |_| Message::ErrorReceived(error_msg),
-                            );
-                        }
-                    }
-                    self.selected_flatpak.clear();
-                    // Launch install dialog for flatpaks
-                    let packages_clone = packages.clone();
-                    Command::perform(
-                        async move {
-                            use tokio::process::Command as TokioCommand;
-                            let exe_path = std::env::current_exe()
-                                .unwrap_or_else(|_| std::path::PathBuf::from("birdnest"));
-                            let mut cmd = TokioCommand::new(&exe_path);
-                            cmd.arg("install-dialog");
-                            for pkg in &packages_clone {
-                                #[cfg(debug_assertions)]
-                                eprintln!("[FLATPAK DEBUG] InstallSelected: Adding package to install dialog: '{}'", pkg);
-                                cmd.arg(pkg);
-                            }
-                            // Mark as flatpak by adding a special flag
-                            cmd.arg("--flatpak");
-                            let _ = cmd.spawn();
-                        },
-                        |_| Message::InstalledPackagesLoaded(Vec::new()),
-                    )
-                } else {
-                    let packages: Vec<String> = self.selected_packages.iter().cloned().collect();
-                    // Invalidate installed packages cache
-                    self.installed_loaded = false;
-                    invalidate_packages_cache();
-                    if packages.is_empty() {
-                        return Command::none();
-                    }
-                    self.selected_packages.clear();
-                    // Launch install dialog for regular packages
-                    let packages_clone = packages.clone();
-                    Command::perform(
-                        async move {
-                            use tokio::process::Command as TokioCommand;
-                            let exe_path = std::env::current_exe()
-                                .unwrap_or_else(|_| std::path::PathBuf::from("birdnest"));
-                            let mut cmd = TokioCommand::new(&exe_path);
-                            cmd.arg("install-dialog");
-                            for pkg in &packages_clone {
-                                cmd.arg(pkg);
-                            }
-                            // Regular packages, not flatpak
-                            let _ = cmd.spawn();
-                        },
-                        |_| Message::InstalledPackagesLoaded(Vec::new()),
-                    )
+            Message::HeaderQueryChanged(query) => {
+                self.global_search.query = query;
+                self.global_search.searched = false;
+                self.global_search.extras.clear();
+                self.autocomplete.set(self.suggestions());
+                self.forward_header_query()
+            }
+            Message::HeaderSearch => {
+                // Enter on a highlighted suggestion searches for it
+                if let Some(name) = self.autocomplete.selected().map(str::to_string) {
+                    return self.update(Message::SuggestionPicked(name));
                 }
+                self.autocomplete.close();
+                if matches!(self.current_tab, Tab::AppImage | Tab::Updates | Tab::Sources | Tab::Maintenance) {
+                    self.current_tab = Tab::Search;
+                }
+                self.global_search.extras.clear();
+                self.search_active_tab()
             }
-            Message::ThemeToggled => {
-                self.theme = match self.theme {
-                    AppTheme::Light => AppTheme::Dark,
-                    AppTheme::Dark => AppTheme::Light,
+            Message::SuggestionPicked(name) => {
+                self.autocomplete.close();
+                self.global_search.query = name;
+                self.global_search.extras.clear();
+                let forward = self.forward_header_query();
+                Command::batch([forward, self.update(Message::HeaderSearch)])
+            }
+            Message::AcceptSuggestion => {
+                if !self.main_focused {
+                    return Command::none();
+                }
+                let Some(name) = self.autocomplete.selected().or(self.autocomplete.suggestions.first().map(String::as_str)) else {
+                    return Command::none();
                 };
-                Command::none()
+                let name = name.to_string();
+                self.autocomplete.close();
+                self.update(Message::HeaderQueryChanged(name))
             }
-            Message::FlatpakSearchQueryChanged(query) => {
-                self.flatpak_search_query = query;
+            Message::DismissSuggestions => {
+                self.autocomplete.close();
+                Command::none()
+            }
+            Message::SearchAlsoIn(source) => {
+                if !self.global_search.expand(source) {
+                    return Command::none();
+                }
+                let query = self.global_search.query.clone();
+                Command::perform(global_search::search(source, query.clone()), move |results| {
+                    Message::ExtraResultsLoaded(source, query, results)
+                })
+            }
+            Message::ExtraResultsLoaded(source, query, results) => {
+                // Typing since has closed the sources this was for
+                if query == self.global_search.query {
+                    self.global_search.set_results(source, results);
+                }
+                Command::none()
+            }
+            Message::InstallExtra(source, id) => match source {
+                ExtraSource::Flatpak => self.update(Message::FlatpakInstallPackage(id)),
+                ExtraSource::Aur => {
+                    let mut dialog = self.pikman_install_dialog(vec![id]);
+                    dialog.selected_distro = Some(pikman_install_dialog::DistroType::Aur);
+                    self.open_dialog(Dialog::PikmanInstall(dialog))
+                }
+            },
+            Message::ToggleAllSources(enabled) => {
+                self.all_sources.enabled = enabled;
+                let query = self.search_query.clone();
+                if enabled && !query.trim().is_empty() && query != self.all_sources.query {
+                    self.search_all_sources(query)
+                } else {
+                    Command::none()
+                }
+            }
+            Message::AllSourcesLoaded(source, query, mut results) => {
+                // apt's hits show as the app AppStream says they are
+                if let Ok(hits) = &mut results {
+                    for hit in hits.iter_mut().filter(|hit| hit.package.source == PackageSource::Default) {
+                        if let Some(app) = self.app_catalog.for_package(&hit.package.name) {
+                            hit.title = app.name.clone();
+                            hit.app_id = Some(app.id.clone());
+                        }
+                    }
+                }
+                self.all_sources.set_results(&source, &query, results);
+                Command::none()
+            }
+            Message::InstallFrom(package) => match package.source {
+                PackageSource::Default => self.update(Message::InstallPackage(package.name)),
+                PackageSource::Flatpak => self.update(Message::FlatpakInstallPackage(package.name)),
+                PackageSource::Aur | PackageSource::Fedora | PackageSource::Alpine => {
+                    let distro = match package.source {
+                        PackageSource::Aur => pikman_install_dialog::DistroType::Aur,
+                        PackageSource::Fedora => pikman_install_dialog::DistroType::Fedora,
+                        _ => pikman_install_dialog::DistroType::Alpine,
+                    };
+                    let mut dialog = self.pikman_install_dialog(vec![package.name]);
+                    dialog.selected_distro = Some(distro);
+                    self.open_dialog(Dialog::PikmanInstall(dialog))
+                }
+            },
+            Message::DetailsLoaded(package, details) => {
+                // Only if the pane hasn't moved on to another package meanwhile
+                if self.details.is_showing(&package) {
+                    self.details.details = Some(details);
+                }
+                Command::none()
+            }
+            Message::ScreenshotLoaded(package, screenshot) => {
+                if self.details.is_showing(&package) {
+                    self.details.screenshot = screenshot;
+                }
+                Command::none()
+            }
+            Message::LoadChangelog => {
+                let Some(package) = self.details.package.clone() else {
+                    return Command::none();
+                };
+                self.details.changelog = changelog_view::Changelog::Loading;
+                Command::perform(changelog_view::load(self.backend.clone(), package.clone(), None), move |result| {
+                    Message::ChangelogLoaded(package, result)
+                })
+            }
+            Message::ChangelogLoaded(package, result) => {
+                if self.details.is_showing(&package) {
+                    self.details.changelog.set(result);
+                }
+                Command::none()
+            }
+            Message::MeasureFootprint => {
+                let Some(package) = self.details.package.clone() else {
+                    return Command::none();
+                };
+                self.measure_footprint(package)
+            }
+            Message::FootprintLoaded(package, footprint) => {
+                if self.details.is_showing(&package) {
+                    self.details.footprint = details_pane::Usage::Measured(footprint);
+                }
+                Command::none()
+            }
+            Message::CloseDetails => {
+                self.details.close();
+                Command::none()
+            }
+            Message::RowMenuToggled(target) => {
+                self.row_menu.toggle(target);
+                Command::none()
+            }
+            Message::RowAction(target, action) => self.row_action(target, action),
+            Message::RowActionFinished(result) => match result {
+                Ok(done) => {
+                    self.activity.info(done);
+                    Command::none()
+                }
+                Err(e) => {
+                    self.activity.error(e);
+                    Command::none()
+                }
+            },
+            Message::ToggleHold(package) => {
+                let held = !self.updates.held.contains(&package);
+                self.set_held(package, held)
+            }
+            Message::HoldChanged(result) => {
+                match result {
+                    Ok(done) => self.activity.info(done),
+                    Err(e) => self.activity.error(e),
+                }
+                Command::perform(updates::load_held(self.backend.clone()), Message::HeldLoaded)
+            }
+            Message::HeldLoaded(held) => {
+                self.updates.held = held;
+                // A package held since it was ticked can't be upgraded
+                let keys: Vec<(bool, String)> = self.updates.selected.iter().filter(|(flatpak, name)| !flatpak && self.updates.held.contains(name)).cloned().collect();
+                for key in keys {
+                    self.updates.selected.remove(&key);
+                }
+                Command::none()
+            }
+            Message::RowFilesLoaded(target, files) => {
+                // The menu may have moved to another row meanwhile
+                if self.row_menu.is_open(&target) {
+                    self.row_menu.files = Some(files);
+                }
+                Command::none()
+            }
+            Message::Dialog(window, message) => {
+                let (command, notice) = self.windows.update(window, message);
+                let follow_up = match notice {
+                    Some(Notice::Conflict(dialog)) => self.open_dialog(Dialog::Conflict(dialog)),
+                    Some(Notice::SettingsSaved(config)) => {
+                        self.apply_settings(&config);
+                        Command::none()
+                    }
+                    Some(Notice::Trim(packages)) => {
+                        self.installed_loaded = false;
+                        invalidate_packages_cache();
+                        self.open_dialog(Dialog::Remove(self.remove_dialog(packages, false)))
+                    }
+                    None => Command::none(),
+                };
+                Command::batch([command, follow_up])
+            }
+            Message::WindowClosed(window) => {
+                self.windows.closed(window);
+                Command::none()
+            }
+            Message::FileDropped(path) => match FlatpakFile::open(&path) {
+                Ok(file) => {
+                    let mut dialog = InstallDialog::for_file(file);
+                    dialog.options = self.install_options.clone();
+                    self.open_dialog(Dialog::Install(Box::new(dialog)))
+                }
+                Err(e) => {
+                    self.activity.error(format!("Can't install {}: {}", path.display(), e));
+                    Command::none()
+                }
+            },
+            Message::WindowFocused(window, focused) => {
+                if window == window::Id::MAIN {
+                    self.main_focused = focused;
+                }
+                if focused {
+                    self.focused_window = Some(window);
+                    if std::mem::take(&mut self.announced) {
+                        self.notifiers.dismiss();
+                    }
+                } else if self.focused_window == Some(window) {
+                    self.focused_window = None;
+                }
+                Command::none()
+            }
+            Message::WindowResized(window, width) => {
+                if window == window::Id::MAIN {
+                    self.window_width = width;
+                    if !self.is_narrow() {
+                        self.nav_open = false;
+                    }
+                }
+                Command::none()
+            }
+            Message::BrowseCategory(category) => {
+                self.browse.open(category);
+                self.details.close();
+                Command::none()
+            }
+            Message::BrowseShowMore => {
+                self.browse.shown += browse::PAGE;
+                Command::none()
+            }
+            Message::BrowseDetails(package) => self.show_details(package),
+            Message::ToggleNavMenu => {
+                self.nav_open = !self.nav_open;
+                Command::none()
+            }
+            Message::RestartActionFinished(result) => match result {
+                Ok(()) => Command::perform(restart_banner::check(), Message::RestartStatusLoaded),
+                Err(e) => {
+                    self.activity.error(e);
+                    Command::none()
+                }
+            },
+            Message::ShutdownUpdatesToggled(enabled) => {
+                let exe = std::env::current_exe().unwrap_or_else(|_| std::path::PathBuf::from("birdnest"));
+                let (commands, done) = if enabled {
+                    let (packages, flatpaks) = self.updates.pending();
+                    match shutdown_updates::stage_commands(self.backend.as_ref(), &exe, &StagedUpdates { packages, flatpaks }) {
+                        Ok(commands) => (commands, "Updates download now and install when you next shut down".to_string()),
+                        Err(e) => {
+                            self.activity.error(e.to_string());
+                            return Command::none();
+                        }
+                    }
+                } else {
+                    (vec![shutdown_updates::cancel_command(&exe)], "Updates no longer install at shutdown".to_string())
+                };
+                self.updates.shutdown_busy = true;
+                Command::perform(row_menu::run(commands, done), Message::ShutdownUpdatesChanged)
+            }
+            Message::ShutdownUpdatesLoaded(staged) => {
+                self.updates.shutdown = staged;
+                Command::none()
+            }
+            Message::ShutdownUpdatesChanged(result) => {
+                self.updates.shutdown_busy = false;
+                match result {
+                    Ok(done) => self.activity.info(done),
+                    Err(e) => self.activity.error(e),
+                }
+                Command::perform(updates::load_staged(), Message::ShutdownUpdatesLoaded)
+            }
+            Message::UpdatesLoaded(result) => {
+                self.updates.loading = false;
+                match result {
+                    Ok(entries) => {
+                        self.updates.error = None;
+                        self.updates.set_entries(entries);
+                        // The badge agrees with the list just loaded
+                        let flatpaks = self.updates.entries.iter().filter(|e| e.is_flatpak).count();
+                        self.update_count = Some(UpdateCount { packages: self.updates.entries.len() - flatpaks, flatpaks });
+                    }
+                    Err(e) => {
+                        tracing::error!("Failed to check for updates: {}", e);
+                        self.updates.error = Some(e);
+                    }
+                }
+                Command::none()
+            }
+            Message::ToggleUpdate(key) => {
+                self.updates.toggle(key);
+                Command::none()
+            }
+            Message::ToggleUpdateChangelog(key) => {
+                self.updates.toggle_expanded(key);
+                self.fetch_changelogs()
+            }
+            Message::ExpandAllUpdates => {
+                self.updates.expand_all();
+                self.fetch_changelogs()
+            }
+            Message::UpdateChangelogLoaded(key, result) => {
+                // Unless a refresh has since listed another version
+                if let Some(changelog) = self.updates.changelogs.get_mut(&key).filter(|c| **c == changelog_view::Changelog::Loading) {
+                    changelog.set(result);
+                }
+                self.fetch_changelogs()
+            }
+            Message::SelectAllUpdates => {
+                self.updates.select_all();
+                Command::none()
+            }
+            Message::UpgradeSelected => {
+                let (packages, flatpaks) = self.updates.selection();
+                if packages.is_empty() && flatpaks.is_empty() {
+                    return Command::none();
+                }
+                self.updates.selected.clear();
+                self.open_dialog(Dialog::Upgrade(UpgradeDialog::new(packages, flatpaks)))
+            }
+            Message::MaintenanceUsageLoaded(usage) => {
+                self.maintenance.usage_loading = false;
+                self.maintenance.usage = usage.into_iter().collect();
+                Command::none()
+            }
+            Message::RepairFlatpak(installation) => {
+                if self.maintenance.repairing.is_some() {
+                    return Command::none();
+                }
+                tracing::debug!("Repairing {} Flatpak installation", installation.label());
+                self.maintenance.repairing = Some(installation);
+                self.maintenance.output.clear();
+                self.maintenance.last_result = None;
+                Command::run(maintenance::repair_stream(installation), Message::FlatpakRepairEvent)
+            }
+            Message::FlatpakRepairEvent(event) => {
+                self.maintenance.apply(event);
+                Command::none()
+            }
+            Message::SourcesLoaded(result) => {
+                self.sources.loading = false;
+                match result {
+                    Ok((sources, codename)) => {
+                        self.sources.sources = sources;
+                        self.sources.codename = codename;
+                        self.sources.ppas_supported = birdnest_core::ppa::is_ubuntu_based();
+                    }
+                    Err(e) => self.sources.error = Some(e),
+                }
+                Command::none()
+            }
+            Message::SourceBackupsLoaded(backups) => {
+                self.sources.backups = backups;
+                Command::none()
+            }
+            Message::ConfirmRestoreSources(backup) => {
+                self.sources.confirm_restore = backup;
+                Command::none()
+            }
+            Message::SourceUriChanged(uri) => {
+                self.sources.new_uri = uri;
+                Command::none()
+            }
+            Message::SourceSuiteChanged(suite) => {
+                self.sources.new_suite = suite;
+                Command::none()
+            }
+            Message::SourceComponentsChanged(components) => {
+                self.sources.new_components = components;
+                Command::none()
+            }
+            Message::AddSource if self.sources.new_uri.trim().starts_with("ppa:") => {
+                match birdnest_core::ppa::Ppa::parse(&self.sources.new_uri) {
+                    Ok(ppa) => self.update(Message::EditPpa(ppa, true)),
+                    Err(e) => {
+                        self.sources.error = Some(e.to_string());
+                        Command::none()
+                    }
+                }
+            }
+            Message::AddSource => match self.sources.new_source() {
+                Ok(source) => self.update(Message::EditSource(birdnest_core::repos::Edit::Add(source))),
+                Err(e) => {
+                    self.sources.error = Some(e);
+                    Command::none()
+                }
+            },
+            Message::ConfirmRemoveSource(entry) => {
+                self.sources.confirm_remove = entry;
+                Command::none()
+            }
+            Message::EditSource(edit) => {
+                let exe = std::env::current_exe().unwrap_or_else(|_| std::path::PathBuf::from("birdnest"));
+                let command = birdnest_core::repos::edit_command(&exe, &edit);
+                self.edit_sources(command, sources::done_message(&edit))
+            }
+            Message::EditPpa(ppa, add) => {
+                let exe = std::env::current_exe().unwrap_or_else(|_| std::path::PathBuf::from("birdnest"));
+                if add {
+                    self.edit_sources(birdnest_core::ppa::add_command(&exe, &ppa), format!("Added {}", ppa.spec()))
+                } else {
+                    self.edit_sources(birdnest_core::ppa::remove_command(&exe, &ppa), format!("Removed {}", ppa.spec()))
+                }
+            }
+            Message::SourceEdited(result) => {
+                self.sources.busy = false;
+                match result {
+                    Ok(done) => {
+                        self.sources.clear_form();
+                        self.activity.info(done);
+                    }
+                    Err(e) => {
+                        self.sources.error = Some(e.clone());
+                        self.activity.error(e);
+                    }
+                }
+                self.sources.loading = true;
+                Command::batch([
+                    Command::perform(sources::load(), Message::SourcesLoaded),
+                    Command::perform(sources::load_backups(), Message::SourceBackupsLoaded),
+                ])
+            }
+            Message::LoadAppImages => {
+                if self.appimages.loading {
+                    return Command::none();
+                }
+                self.appimages.loading = true;
+                Command::perform(appimage::load(self.appimages.directories.clone()), Message::AppImagesLoaded)
+            }
+            Message::AppImagesLoaded(found) => {
+                self.appimages.loading = false;
+                // Check results stay with the AppImages that are still there
+                self.appimages.updates.retain(|path, _| found.iter().any(|appimage| &appimage.path == path));
+                self.appimages.appimages = found;
+                Command::none()
+            }
+            Message::AppImageAction(path, action) => self.run_appimage(path, action),
+            Message::CheckAppImageUpdates => {
+                let checks: Vec<Command<Message>> = self
+                    .appimages
+                    .checkable()
+                    .into_iter()
+                    .map(|found| self.run_appimage(found.path, appimage::Action::CheckUpdate))
+                    .collect();
+                Command::batch(checks)
+            }
+            Message::ConfirmRemoveAppImage(path) => {
+                self.appimages.confirm_remove = path;
+                Command::none()
+            }
+            Message::AppImageDone(path, action, result) => {
+                self.appimages.busy.remove(&path);
+                let name = self.appimages.get(&path).map_or_else(|| path.display().to_string(), |found| found.name.clone());
+                match result {
+                    Ok(status) => {
+                        self.activity.info(appimage::done_message(&name, action, status.as_ref()));
+                        match status {
+                            Some(status) => {
+                                self.appimages.updates.insert(path, status);
+                            }
+                            None if matches!(action, appimage::Action::Update | appimage::Action::Remove) => {
+                                self.appimages.updates.remove(&path);
+                            }
+                            None => {}
+                        }
+                    }
+                    Err(e) => {
+                        self.appimages.error = Some(format!("{}: {}", name, e));
+                        self.activity.error(format!("{}: {}", name, e));
+                    }
+                }
+                // Everything but a check changes the file or its menu entry
+                if action == appimage::Action::CheckUpdate {
+                    Command::none()
+                } else {
+                    self.update(Message::LoadAppImages)
+                }
+            }
+            Message::DeferredLoaded(deferred) => {
+                // Apps whose days are up are uninstalled as soon as BirdNest sees them
+                let now = birdnest_core::utils::now();
+                let due: Vec<DeferredRemoval> = deferred.iter().filter(|d| d.is_due(now)).cloned().collect();
+                self.maintenance.deferred = deferred;
+                self.uninstall_deferred(due)
+            }
+            Message::RestoreDeferred(name) => Command::perform(
+                async move {
+                    let restored = name.clone();
+                    tokio::task::spawn_blocking(move || deferred_removal::restore(&restored))
+                        .await
+                        .map_err(|e| format!("Task error: {}", e))?
+                        .map(|_| name)
+                        .map_err(|e| e.to_string())
+                },
+                Message::DeferredRestored,
+            ),
+            Message::DeferredRestored(result) => {
+                self.activity.info(match result {
+                    Ok(name) => format!("{} is back in the app menu and stays installed", name),
+                    Err(e) => format!("Couldn't restore: {}", e),
+                });
+                Command::perform(maintenance::load_deferred(), Message::DeferredLoaded)
+            }
+            Message::UninstallDeferred(name) => {
+                let entry = self.maintenance.deferred.iter().filter(|d| d.name == name).cloned().collect();
+                self.uninstall_deferred(entry)
+            }
+            Message::DeferredUninstalled(result) => {
+                self.maintenance.uninstalling_deferred = false;
+                match result {
+                    Ok(0) => {}
+                    Ok(removed) => {
+                        self.activity.info(format!("Uninstalled {} app(s) hidden by deferred removal", removed));
+                        self.installed_loaded = false;
+                        invalidate_packages_cache();
+                    }
+                    Err(e) => self.activity.info(format!("Deferred removal failed: {}", e)),
+                }
+                Command::perform(maintenance::load_deferred(), Message::DeferredLoaded)
+            }
+            Message::CheckStartup => {
+                if self.maintenance.startup_loading {
+                    return Command::none();
+                }
+                self.maintenance.startup_loading = true;
+                Command::perform(maintenance::load_startup(self.backend.clone()), Message::StartupLoaded)
+            }
+            Message::StartupLoaded(items) => {
+                self.maintenance.startup_loading = false;
+                self.maintenance.startup = Some(items);
+                Command::none()
+            }
+            Message::ToggleStartup(id, enabled) => {
+                let Some(item) = self.maintenance.startup.iter().flatten().find(|item| item.id == id).cloned() else {
+                    return Command::none();
+                };
+                self.maintenance.startup_switching.insert(id.clone());
+                Command::perform(maintenance::set_startup(item, enabled), move |result| Message::StartupToggled(id.clone(), enabled, result))
+            }
+            Message::StartupToggled(id, enabled, result) => {
+                self.maintenance.startup_switching.remove(&id);
+                let Some(item) = self.maintenance.startup.iter_mut().flatten().find(|item| item.id == id) else {
+                    return Command::none();
+                };
+                match result {
+                    Ok(()) => {
+                        item.enabled = enabled;
+                        self.activity.info(format!(
+                            "{} {} at the next login",
+                            item.name,
+                            if enabled { "starts again" } else { "no longer starts" }
+                        ));
+                    }
+                    Err(e) => self.activity.error(format!("Couldn't change whether {} starts at login: {}", item.name, e)),
+                }
+                Command::none()
+            }
+            Message::InitializeBackends => {
+                tracing::debug!("InitializeBackends: starting background load of installed packages");
+                profiling::mark("backend init started");
+                self.installed_loading = true;
+                Command::batch([
+                    Command::perform(load_installed_packages(self.backend.clone()), Message::InstalledPackagesLoaded),
+                    Command::perform(build_search_index(self.backend.clone()), Message::SearchIndexBuilt),
+                    Command::perform(load_app_catalogs(), |(system, flatpak)| Message::AppCatalogsLoaded(system, flatpak)),
+                    Command::perform(maintenance::load_deferred(), Message::DeferredLoaded),
+                    Command::perform(future::ready(()), |_| Message::UpdateCheckTick),
+                    Command::perform(status_bar::check_health(self.backend.clone()), Message::SourceHealthChecked),
+                    Command::perform(updates::load_held(self.backend.clone()), Message::HeldLoaded),
+                    Command::perform(architectures::load_native(), Message::NativeArchLoaded),
+                    Command::perform(tokio::time::sleep(std::time::Duration::from_secs(self.preload.delay_secs)), |_| Message::Preload),
+                ])
+            }
+            Message::Preload => {
+                let mut loads = Vec::new();
+                if self.preload.flatpak && !self.flatpak_loaded && !self.flatpak_loading {
+                    tracing::debug!("Preloading Flatpak apps");
+                    self.flatpak_loading = true;
+                    loads.push(Command::perform(load_flatpak_apps(), |result| match result {
+                        Ok(apps) => Message::FlatpakAppsLoaded(apps),
+                        Err(e) => Message::FlatpakPreloadFailed(e.to_string()),
+                    }));
+                }
+                if self.preload.pikman && self.pikman_installed.is_empty() && birdnest_core::utils::find_in_path("pikman").is_some() {
+                    tracing::debug!("Preloading pikman containers");
+                    for distro in birdnest_core::manifest::PIKMAN_DISTROS {
+                        self.pikman_installed.insert(distro.to_string(), None);
+                        loads.push(Command::perform(load_pikman_container(distro.to_string()), move |result| {
+                            Message::PikmanContainerLoaded(distro.to_string(), result)
+                        }));
+                    }
+                }
+                Command::batch(loads)
+            }
+            // Nobody asked for the list yet, so a failure isn't worth a dialog;
+            // visiting the tab tries again and reports it
+            Message::FlatpakPreloadFailed(e) => {
+                tracing::warn!("Preloading Flatpak apps failed: {}", e);
+                self.flatpak_loading = false;
+                Command::none()
+            }
+            Message::PikmanContainerLoaded(distro, result) => {
+                if let Err(e) = &result {
+                    tracing::debug!("Preloading the {} container failed: {}", distro, e);
+                }
+                self.pikman_installed.insert(distro, Some(result));
+                Command::none()
+            }
+            Message::SourceHealthChecked(health) => {
+                self.source_health = health;
+                Command::none()
+            }
+            Message::InstalledPackagesLoaded(packages) => {
+                tracing::debug!("InstalledPackagesLoaded: {} packages loaded", packages.len());
+                self.installed_packages = packages;
+                self.installed_loaded = true;
+                self.installed_loading = false;
+                tracing::debug!("Installed packages state updated - loaded: true, loading: false");
+                profiling::mark_once("installed packages ready");
+                profiling::report();
+                self.installed_facts.set_sizes(&self.installed_packages);
+                self.installed_descriptions.reset();
+                Command::batch([
+                    self.describe_installed(),
+                    Command::perform(result_pages::load_change_times(), Message::ChangeTimesLoaded),
+                ])
+            }
+            Message::ChangeTimesLoaded(changed) => {
+                self.installed_facts.changed = changed;
+                Command::none()
+            }
+            Message::InstalledSearchQueryChanged(query) => {
+                self.installed_search_query = query;
+                self.installed_pager.reset();
+                self.installed_cursor.reset();
+                self.describe_installed()
+            }
+            Message::InstalledSortChanged(sort) => {
+                self.installed_sort = sort;
+                self.installed_pager.reset();
+                self.installed_cursor.reset();
+                self.describe_installed()
+            }
+            Message::InstalledPage(page) => {
+                self.installed_pager.page = page;
+                self.installed_cursor.reset();
+                self.installed_descriptions.offset = 0.0;
+                Command::batch([
+                    scrollable::snap_to(installed_list_id(), scrollable::RelativeOffset::START),
+                    self.describe_installed(),
+                ])
+            }
+            Message::InstalledScrolled(offset) => {
+                self.installed_descriptions.offset = offset;
+                self.describe_installed()
+            }
+            Message::DescriptionsLoaded(Ok(found)) => {
+                descriptions::fill(&mut self.installed_packages, &found);
+                Command::none()
+            }
+            // Backends that can't describe installed packages leave the rows as they were
+            Message::DescriptionsLoaded(Err(e)) => {
+                tracing::debug!("Couldn't load package descriptions: {}", e);
+                Command::none()
+            }
+            Message::ListKey(key) => {
+                if self.main_focused && self.autocomplete.is_open() && matches!(key, list_nav::ListKey::Up | list_nav::ListKey::Down) {
+                    if key == list_nav::ListKey::Up {
+                        self.autocomplete.up();
+                    } else {
+                        self.autocomplete.down();
+                    }
+                    return Command::none();
+                }
+                if !self.main_focused || self.current_tab != Tab::Installed || self.disk_usage.open {
+                    return Command::none();
+                }
+                let names: Vec<String> = self.installed_page().iter().map(|pkg| pkg.name.clone()).collect();
+                let len = names.len();
+                match self.installed_cursor.handle(&key, &names, std::time::Instant::now()) {
+                    Some(index) => {
+                        // Snapping doesn't report a scroll, so follow the cursor here
+                        self.installed_descriptions.offset = if len > 1 { index as f32 / (len - 1) as f32 } else { 0.0 };
+                        Command::batch([list_nav::scroll_to(installed_list_id(), index, len), self.describe_installed()])
+                    }
+                    None => Command::none(),
+                }
+            }
+            Message::ToggleDiskUsage => {
+                self.disk_usage.open = !self.disk_usage.open;
+                // Re-measured on every open, since installs and removals change it
+                if !self.disk_usage.open || self.disk_usage.loading {
+                    return Command::none();
+                }
+                self.disk_usage.loading = true;
+                Command::perform(disk_usage::load_flatpaks(), Message::FlatpakUsageLoaded)
+            }
+            Message::ToggleInstalledArch => {
+                self.architectures.shown = !self.architectures.shown;
+                Command::none()
+            }
+            Message::ToggleForeignArch => {
+                self.architectures.foreign_only = !self.architectures.foreign_only;
+                self.installed_pager.reset();
+                self.installed_cursor.reset();
+                self.describe_installed()
+            }
+            Message::NativeArchLoaded(native) => {
+                self.architectures.native = native;
+                Command::none()
+            }
+            Message::FlatpakUsageLoaded(usage) => {
+                self.disk_usage.loading = false;
+                self.disk_usage.flatpaks = Some(usage);
+                Command::none()
+            }
+            Message::ToggleInstalledPackage(package) => {
+                if self.selected_installed.contains(&package) {
+                    self.selected_installed.remove(&package);
+                } else {
+                    self.selected_installed.insert(package.clone());
+                }
+                self.show_details(package)
+            }
+            Message::RemoveSelectedPackages => {
+                let packages: Vec<String> = self.selected_installed.iter().cloned().collect();
+                if packages.is_empty() {
+                    return Command::none();
+                }
+                
+                // Invalidate installed packages cache - will reload when user switches back to tab
+                self.installed_loaded = false;
+                invalidate_packages_cache();
+                
+                self.selected_installed.clear();
+                self.open_dialog(Dialog::Remove(self.remove_dialog(packages, false)))
+            }
+            Message::RemovePackageDetailsLoaded(_details) => {
+                // No longer needed - handled in separate window
+                Command::none()
+            }
+            Message::LoadFlatpakApps => {
+                #[cfg(debug_assertions)]
+                tracing::debug!("Message::LoadFlatpakApps received");
+                Command::perform(load_flatpak_apps(), |result| {
+                    match result {
+                        Ok(apps) => {
+                            #[cfg(debug_assertions)]
+                            tracing::debug!("LoadFlatpakApps: Success, loaded {} apps", apps.len());
+                            Message::FlatpakAppsLoaded(apps)
+                        },
+                        Err(e) => {
+                            #[cfg(debug_assertions)]
+                            tracing::warn!("LoadFlatpakApps: {}", e);
+                            Message::ErrorReceived(e.to_string())
+                        },
+                    }
+                })
+            }
+            Message::FlatpakAppsLoaded(apps) => {
+                tracing::debug!("FlatpakAppsLoaded: {} apps loaded", apps.len());
+                // All of them, for the count, the filter and the row menu;
+                // low-memory mode only draws the first few
+                self.flatpak_apps = apps;
+                self.flatpak_loaded = true;
+                self.flatpak_loading = false;
+                tracing::debug!("Flatpak apps state updated - loaded: true, loading: false");
+                Command::none()
+            }
+            Message::RefreshLists => {
+                self.lists_refreshed_at = Some(SystemTime::now());
+                // Reset loaded flags to force reload
+                self.installed_loaded = false;
+                self.flatpak_loaded = false;
+                // Invalidate cache to force fresh load
+                invalidate_packages_cache();
+                // The package lists may have been updated since the index was built
+                Command::batch([
+                    Command::perform(load_installed_packages(self.backend.clone()), Message::InstalledPackagesLoaded),
+                    Command::perform(build_search_index(self.backend.clone()), Message::SearchIndexBuilt),
+                    Command::perform(load_app_catalogs(), |(system, flatpak)| Message::AppCatalogsLoaded(system, flatpak)),
+                    Command::perform(updates::load_held(self.backend.clone()), Message::HeldLoaded),
+                ])
+            }
+            Message::LoadInstalledPackages => {
+                if !self.installed_loading {
+                    self.installed_loading = true;
+                    self.installed_loaded = false;
+                    Command::perform(load_installed_packages(self.backend.clone()), Message::InstalledPackagesLoaded)
+                } else {
+                    Command::none()
+                }
+            }
+            Message::TogglePackage(package) => {
+                #[cfg(debug_assertions)]
+                tracing::debug!("TogglePackage: Toggling package: '{}'", package);
+                // Check if it's a flatpak or regular package
+                if self.flatpak_search_results.iter().any(|f| f.application == package) {
+                    #[cfg(debug_assertions)]
+                    tracing::debug!("TogglePackage: Package '{}' is a Flatpak (application ID)", package);
+                    if self.selected_flatpak.contains(&package) {
+                        self.selected_flatpak.remove(&package);
+                        #[cfg(debug_assertions)]
+                        tracing::debug!("TogglePackage: Removed '{}' from selected_flatpak", package);
+                    } else {
+                        self.selected_flatpak.insert(package.clone());
+                        #[cfg(debug_assertions)]
+                        tracing::debug!("TogglePackage: Added '{}' to selected_flatpak", package);
+                    }
+                } else {
+                    if self.selected_packages.contains(&package) {
+                        self.selected_packages.remove(&package);
+                    } else {
+                        self.selected_packages.insert(package.clone());
+                    }
+                    return self.show_details(package);
+                }
+                Command::none()
+            }
+            Message::InstallSelected => {
+                // Each tab's button installs what is ticked in that tab; picks
+                // from other tabs wait for their own button or the batch
+                if self.current_tab == Tab::Flatpak {
+                    let packages: Vec<String> = self.selected_flatpak.iter().cloned().collect();
+                    if packages.is_empty() {
+                        return Command::none();
+                    }
+                    #[cfg(debug_assertions)]
+                    tracing::debug!("InstallSelected: Installing {} Flatpak packages: {:?}", packages.len(), packages);
+                    // Validate that all packages are valid Flatpak application IDs
+                    for pkg in &packages {
+                        if !pkg.contains('.') {
+                            #[cfg(debug_assertions)]
+                            tracing::warn!("InstallSelected: Invalid Flatpak ID detected: '{}' (missing period)", pkg);
+                            let error_msg = format!("Invalid Flatpak application ID: '{}'. Please select packages from the search results.", pkg);
+                            return Command::perform(
+                                async move { },
+                                move |_| Message::ErrorReceived(error_msg),
+                            );
+                        }
+                    }
+                    self.selected_flatpak.clear();
+                    let dialog = self.install_dialog(packages, true);
+                    self.open_dialog(Dialog::Install(Box::new(dialog)))
+                } else {
+                    let packages: Vec<String> = self.selected_packages.iter().cloned().collect();
+                    // Invalidate installed packages cache
+                    self.installed_loaded = false;
+                    invalidate_packages_cache();
+                    if packages.is_empty() {
+                        return Command::none();
+                    }
+                    self.selected_packages.clear();
+                    self.open_dialog(Dialog::Install(Box::new(self.install_dialog(packages, false))))
+                }
+            }
+            Message::ShowCliReference => {
+                self.open_dialog(Dialog::CliReference(CliReferenceDialog::new()))
+            }
+            Message::ShowLogs => self.open_dialog(Dialog::LogViewer(LogViewerDialog::new())),
+            Message::ShowDigest => self.open_dialog(Dialog::Digest(DigestDialog::new())),
+            Message::ShowTrim => self.open_dialog(Dialog::Trim(TrimDialog::new())),
+            Message::ShowEnvironments => self.open_dialog(Dialog::Environment(EnvironmentDialog::new())),
+            Message::ShowSettings => self.open_dialog(Dialog::Settings(Box::new(SettingsDialog::new()))),
+            Message::ThemeToggled => {
+                self.theme = match self.theme {
+                    AppTheme::Light => AppTheme::Dark,
+                    AppTheme::Dark => AppTheme::Light,
+                };
+                Command::none()
+            }
+            Message::FlatpakSearchQueryChanged(query) => {
+                self.flatpak_search_query = query;
+                Command::none()
+            }
+            Message::FlatpakInstalledFilterChanged(filter) => {
+                self.flatpak_installed_filter = filter;
                 Command::none()
             }
             Message::FlatpakSearch => {
                 let query = self.flatpak_search_query.clone();
                 #[cfg(debug_assertions)]
-                eprintln!("[FLATPAK DEBUG] Message::FlatpakSearch received, query: '{}'", query);
+                tracing::debug!("Message::FlatpakSearch received, query: '{}'", query);
                 Command::perform(search_flatpak(query), |result| {
                     match result {
                         Ok(results) => {
                             #[cfg(debug_assertions)]
-                            eprintln!("[FLATPAK DEBUG] FlatpakSearch: Success, found {} results", results.len());
+                            tracing::debug!("FlatpakSearch: Success, found {} results", results.len());
                             Message::FlatpakSearchResults(results)
                         },
                         Err(e) => {
                             #[cfg(debug_assertions)]
-                            eprintln!("[FLATPAK DEBUG] FlatpakSearch: ERROR - {}", e);
+                            tracing::warn!("FlatpakSearch: {}", e);
                             Message::ErrorReceived(e.to_string())
                         },
                     }
                 })
             }
-            Message::FlatpakSearchResults(results) => {
+            Message::FlatpakSearchResults(mut results) => {
+                self.cap_results(&mut results);
+                group_flatpak_results(&mut results, self.flatpak_default_remote.as_deref());
+                self.flatpak_remote_choice.clear();
                 self.flatpak_search_results = results;
+                self.global_search.searched = true;
+                Command::none()
+            }
+            Message::FlatpakRemoteChosen { application, remote } => {
+                self.flatpak_remote_choice.insert(application, remote);
                 Command::none()
             }
             Message::FlatpakInstallPackage(package) => {
                 #[cfg(debug_assertions)]
-                eprintln!("[FLATPAK DEBUG] Message::FlatpakInstallPackage received for package: '{}'", package);
-                Command::perform(load_package_detail(package, true), |result| {
+                tracing::debug!("Message::FlatpakInstallPackage received for package: '{}'", package);
+                Command::perform(load_package_detail(self.backend.clone(), package, true), |result| {
                     match result {
                         Ok(detail) => {
                             #[cfg(debug_assertions)]
-                            eprintln!("[FLATPAK DEBUG] FlatpakInstallPackage: Successfully loaded package detail: {}", detail.name);
+                            tracing::debug!("FlatpakInstallPackage: Successfully loaded package detail: {}", detail.name);
                             Message::PackageDetailLoaded(detail)
                         },
                         Err(e) => {
                             #[cfg(debug_assertions)]
-                            eprintln!("[FLATPAK DEBUG] FlatpakInstallPackage: ERROR - Failed to load package details: {}", e);
+                            tracing::warn!("FlatpakInstallPackage: Failed to load package details: {}", e);
                             Message::ErrorReceived(format!("Failed to load package details: {}", e))
                         },
                     }
                 })
             }
             Message::FlatpakUpdateRepos => {
-                self.output_log.push("Updating Flatpak repositories...".to_string());
+                self.activity.info("Updating Flatpak repositories...".to_string());
                 Command::perform(update_flatpak_repos(), |result| {
                     match result {
                         Ok(msg) => Message::OutputReceived(msg),
@@ -796,7 +2085,7 @@ impl Application for BirdNestGUI {
             Message::FlatpakUpgradeAll => {
                 // Invalidate flatpak cache
                 self.flatpak_loaded = false;
-                self.output_log.push("Upgrading all Flatpaks...".to_string());
+                self.activity.info("Upgrading all Flatpaks...".to_string());
                 Command::batch(vec![
                     Command::perform(upgrade_all_flatpaks(), |result| {
                         match result {
@@ -817,42 +2106,13 @@ impl Application for BirdNestGUI {
             }
             Message::FlatpakShowPackage(package) => {
                 #[cfg(debug_assertions)]
-                eprintln!("[FLATPAK DEBUG] Message::FlatpakShowPackage received for package: '{}'", package);
-                // Launch install dialog to show package info (it will show info even if not installing)
-                let package_names = vec![package.clone()];
-                Command::perform(
-                    async move {
-                        #[cfg(debug_assertions)]
-                        eprintln!("[FLATPAK DEBUG] FlatpakShowPackage: Launching install dialog...");
-                        use tokio::process::Command as TokioCommand;
-                        let exe_path = std::env::current_exe()
-                            .unwrap_or_else(|_| std::path::PathBuf::from("birdnest"));
-                        #[cfg(debug_assertions)]
-                        eprintln!("[FLATPAK DEBUG] FlatpakShowPackage: Executable path: {:?}", exe_path);
-                        let mut cmd = TokioCommand::new(&exe_path);
-                        cmd.arg("install-dialog");
-                        for pkg in &package_names {
-                            cmd.arg(pkg);
-                        }
-                        cmd.arg("--flatpak");
-                        #[cfg(debug_assertions)]
-                        eprintln!("[FLATPAK DEBUG] FlatpakShowPackage: Spawning process with args: install-dialog {} --flatpak", package_names.join(" "));
-                        match cmd.spawn() {
-                            Ok(_) => {
-                                #[cfg(debug_assertions)]
-                                eprintln!("[FLATPAK DEBUG] FlatpakShowPackage: Process spawned successfully");
-                            },
-                            Err(_e) => {
-                                #[cfg(debug_assertions)]
-                                eprintln!("[FLATPAK DEBUG] FlatpakShowPackage: ERROR - Failed to spawn process: {}", _e);
-                            },
-                        }
-                    },
-                    |_| Message::InstalledPackagesLoaded(Vec::new()),
-                )
+                tracing::debug!("Message::FlatpakShowPackage received for package: '{}'", package);
+                // The install dialog shows package info even if not installing
+                let dialog = self.install_dialog(vec![package], true);
+                self.open_dialog(Dialog::Install(Box::new(dialog)))
             }
             Message::FlatpakClean => {
-                self.output_log.push("Cleaning Flatpak cache...".to_string());
+                self.activity.info("Cleaning Flatpak cache...".to_string());
                 Command::perform(clean_flatpak(), |result| {
                     match result {
                         Ok(msg) => Message::OutputReceived(msg),
@@ -861,23 +2121,30 @@ impl Application for BirdNestGUI {
                 })
             }
             Message::OutputReceived(msg) => {
-                self.output_log.push(msg);
+                self.activity.info(msg);
                 Command::none()
             }
             Message::ErrorReceived(msg) => {
-                eprintln!("[ERROR] ErrorReceived: {}", msg);
-                self.error_log.push(msg.clone());
+                tracing::error!("ErrorReceived: {}", msg);
+                self.activity.error(msg.clone());
                 // Reset loading flags on error
                 self.installed_loading = false;
                 self.flatpak_loading = false;
-                eprintln!("[DEBUG] Loading flags reset due to error");
+                tracing::warn!("Loading flags reset due to error");
                 Command::none()
             }
             Message::ClearOutput => {
-                self.output_log.clear();
-                self.error_log.clear();
+                self.activity.clear();
                 Command::none()
             }
+            Message::ToggleActivity => {
+                self.activity.toggle();
+                if self.activity.open {
+                    scrollable::snap_to(activity::scroll_id(), scrollable::RelativeOffset::END)
+                } else {
+                    Command::none()
+                }
+            }
             // Pikman messages
             Message::PikmanSearchQueryChanged(query) => {
                 self.pikman_search_query = query;
@@ -898,9 +2165,11 @@ impl Application for BirdNestGUI {
                     })
                 }
             }
-            Message::PikmanSearchResults(results) => {
+            Message::PikmanSearchResults(mut results) => {
+                self.cap_results(&mut results);
                 self.pikman_search_results = results;
                 self.pikman_loading = false;
+                self.global_search.searched = true;
                 Command::none()
             }
             Message::PikmanFilterChanged(filter) => {
@@ -908,43 +2177,30 @@ impl Application for BirdNestGUI {
                 Command::none()
             }
             Message::TogglePikmanPackage(package) => {
-                if self.selected_pikman.contains(&package) {
-                    self.selected_pikman.remove(&package);
-                } else {
-                    self.selected_pikman.insert(package);
+                if self.selected_pikman.remove(&package).is_none() {
+                    let source = self
+                        .pikman_search_results
+                        .iter()
+                        .find(|result| result.name == package)
+                        .map_or(PackageSource::Default, |result| result.source.clone());
+                    self.selected_pikman.insert(package, source);
                 }
                 Command::none()
             }
             Message::PikmanInstallSelected => {
-                let packages: Vec<String> = self.selected_pikman.iter().cloned().collect();
+                let packages: Vec<String> = self.selected_pikman.keys().cloned().collect();
                 if packages.is_empty() {
                     Command::none()
                 } else {
-                    use crate::gui::pikman_install_dialog::PikmanInstallDialog;
-                    std::process::Command::new("birdnest")
-                        .arg("install-dialog")
-                        .args(&packages)
-                        .spawn()
-                        .ok();
-                    // Also launch the pikman install dialog
-                    let packages_clone = packages.clone();
-                    std::thread::spawn(move || {
-                        let _ = PikmanInstallDialog::run_separate_window(packages_clone);
-                    });
                     self.selected_pikman.clear();
-                    Command::none()
+                    self.open_dialog(Dialog::PikmanInstall(self.pikman_install_dialog(packages)))
                 }
             }
             Message::PikmanInstallPackage(package) => {
-                use crate::gui::pikman_install_dialog::PikmanInstallDialog;
-                let packages = vec![package];
-                std::thread::spawn(move || {
-                    let _ = PikmanInstallDialog::run_separate_window(packages);
-                });
-                Command::none()
+                self.open_dialog(Dialog::PikmanInstall(self.pikman_install_dialog(vec![package])))
             }
             Message::PikmanAutoremove => {
-                self.output_log.push("Running pikman autoremove...".to_string());
+                self.activity.info("Running pikman autoremove...".to_string());
                 Command::perform(pikman_autoremove(), |result| {
                     match result {
                         Ok(msg) => Message::OutputReceived(msg),
@@ -953,159 +2209,1027 @@ impl Application for BirdNestGUI {
                 })
             }
             Message::PikmanEnter(name) => {
-                // This would need a dialog for container name input
-                self.output_log.push(format!("Entering container: {}", name));
-                Command::perform(pikman_enter(name), |result| {
+                Command::perform(pikman_terminal::open(self.terminal.clone(), pikman_terminal::enter_command(&name)), move |result| {
+                    match result {
+                        Ok(terminal) => Message::OutputReceived(format!("Entered container {} in {}", name, terminal)),
+                        Err(e) => Message::ErrorReceived(e),
+                    }
+                })
+            }
+            Message::PikmanContainerNameChanged(name) => {
+                self.pikman_terminal.container = name;
+                Command::none()
+            }
+            Message::PikmanCommandChanged(command) => {
+                self.pikman_terminal.command = command;
+                Command::none()
+            }
+            Message::PikmanExport { package, distro } => {
+                self.activity.info(format!("Exporting package: {}", package));
+                self.pikman_exports.busy = true;
+                Command::perform(pikman_exports::export(package, distro), Message::PikmanExportFinished)
+            }
+            Message::PikmanExportPackageChanged(package) => {
+                self.pikman_exports.package = package;
+                Command::none()
+            }
+            Message::PikmanExportDistroChanged(distro) => {
+                self.pikman_exports.distro = distro;
+                Command::none()
+            }
+            Message::PikmanExportsLoaded(exports) => {
+                self.pikman_exports.exports = Some(exports);
+                Command::none()
+            }
+            Message::PikmanExportFinished(result) => {
+                self.pikman_exports.busy = false;
+                self.pikman_exports.removing = None;
+                match result {
+                    Ok(msg) => {
+                        self.pikman_exports.package.clear();
+                        self.activity.info(msg);
+                    }
+                    Err(e) => self.activity.error(e),
+                }
+                // Whatever pikman managed, the list shows what is on disk now
+                Command::perform(pikman_exports::load(), Message::PikmanExportsLoaded)
+            }
+            Message::PikmanInit { name, manager } => {
+                self.activity.info(format!("Initializing container: {}", name));
+                Command::perform(pikman_init(name, manager), |result| {
                     match result {
                         Ok(msg) => Message::OutputReceived(msg),
                         Err(e) => Message::ErrorReceived(e.to_string()),
                     }
                 })
             }
-            Message::PikmanExport { package, name } => {
-                self.output_log.push(format!("Exporting package: {}", package));
-                Command::perform(pikman_export(package, name), |result| {
+            Message::PikmanLog => {
+                Command::perform(pikman_log(), |result| {
                     match result {
                         Ok(msg) => Message::OutputReceived(msg),
                         Err(e) => Message::ErrorReceived(e.to_string()),
                     }
                 })
             }
-            Message::PikmanInit { name, manager } => {
-                self.output_log.push(format!("Initializing container: {}", name));
-                Command::perform(pikman_init(name, manager), |result| {
+            Message::PikmanPurge(packages) => {
+                self.activity.info(format!("Purging packages: {:?}", packages));
+                Command::perform(pikman_purge(packages), |result| {
+                    match result {
+                        Ok(msg) => Message::OutputReceived(msg),
+                        Err(e) => Message::ErrorReceived(e.to_string()),
+                    }
+                })
+            }
+            Message::PikmanRun { name, command } => {
+                Command::perform(pikman_terminal::open(self.terminal.clone(), pikman_terminal::run_command(&name, &command)), move |result| {
+                    match result {
+                        Ok(terminal) => Message::OutputReceived(format!("Running a command in container {} in {}", name, terminal)),
+                        Err(e) => Message::ErrorReceived(e),
+                    }
+                })
+            }
+            Message::PikmanUpgrades => {
+                Command::perform(pikman_upgrades(), |result| {
                     match result {
                         Ok(msg) => Message::OutputReceived(msg),
                         Err(e) => Message::ErrorReceived(e.to_string()),
                     }
-                })
-            }
-            Message::PikmanLog => {
-                Command::perform(pikman_log(), |result| {
-                    match result {
-                        Ok(msg) => Message::OutputReceived(msg),
-                        Err(e) => Message::ErrorReceived(e.to_string()),
+                })
+            }
+            Message::CopySelectionAsScript(format) => {
+                let selection = self.script_selection();
+                if selection.is_empty() {
+                    return Command::none();
+                }
+                let script = script_export::render(&selection, format);
+                self.activity.info(format!(
+                    "Copied {} script for {} package(s) to clipboard",
+                    match format {
+                        ScriptFormat::Shell => "shell",
+                        ScriptFormat::Ansible => "Ansible",
+                    },
+                    selection.apt_packages.len() + selection.flatpaks.len()
+                ));
+                iced::clipboard::write(script)
+            }
+            Message::ReviewBatch => {
+                let items = self.batch_items();
+                if items.is_empty() {
+                    return Command::none();
+                }
+                self.installed_loaded = false;
+                invalidate_packages_cache();
+                self.clear_batch();
+                self.open_dialog(Dialog::Batch(BatchDialog::new(&items)))
+            }
+            Message::ClearBatch => {
+                self.clear_batch();
+                Command::none()
+            }
+            Message::PikmanUnexport(app) => {
+                self.activity.info(format!("Unexporting {}", app.name));
+                self.pikman_exports.removing = Some(app.path.clone());
+                Command::perform(pikman_exports::unexport(app), Message::PikmanExportFinished)
+            }
+        }
+    }
+
+    fn view(&self, window: window::Id) -> Element<Message> {
+        if let Some(dialog) = self.windows.view(window) {
+            return dialog;
+        }
+        profiling::mark_once("first frame built");
+        let theme = self.theme;
+        let content = match self.current_tab {
+            Tab::Search => self.view_search(),
+            Tab::Browse => self.view_browse(),
+            Tab::Installed => self.view_installed(),
+            Tab::Flatpak => self.view_flatpak(),
+            Tab::Pikman => self.view_pikman(),
+            Tab::Updates => updates::view(&self.updates, theme, self.border_radius),
+            Tab::Sources => sources::view(&self.sources, theme, self.border_radius),
+            Tab::AppImage => appimage::view(&self.appimages, theme, self.border_radius),
+            Tab::Maintenance => maintenance::view(&self.maintenance, theme, self.border_radius),
+        };
+
+        // All sources already lists what the extras would offer
+        let extras = if self.current_tab == Tab::Search && self.all_sources.enabled {
+            Space::with_height(Length::Fixed(0.0)).into()
+        } else {
+            global_search::view_extras(
+                &self.global_search,
+                &self.merged_results(),
+                global_search::offered(self.current_tab, self.pikman_filter.as_deref()),
+                self.active_tab_results(),
+                theme,
+                self.border_radius,
+            )
+        };
+        let main_content = column![
+            self.view_header(),
+            self.view_tabs(),
+            self.view_system_notice(),
+            transaction_queue::view(&self.transactions, theme, self.border_radius),
+            batch_bar::view(&batch::groups(&self.batch_items()), theme, self.border_radius),
+            extras,
+            content,
+            if self.activity.open { activity::view(&self.activity, theme, self.border_radius) } else { Space::with_height(Length::Fixed(0.0)).into() },
+            status_bar::view(self.status(), theme, self.border_radius),
+        ]
+        .spacing(15)
+        .padding(Padding::new(24.0));
+        
+        // Main background - dark, no elevation
+        container(main_content)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+                radius: 0.0, // No radius for main background
+                background: Some(theme.background()), // Use dark background, not card
+                elevation: 0.0, // No elevation for main background
+            })))
+            .padding(Padding::new(16.0))
+            .into()
+    }
+
+    fn subscription(&self) -> Subscription<Message> {
+        let background_refresh = match self.refresh_schedule.every_hours {
+            Some(hours) if hours > 0 => {
+                iced::time::every(std::time::Duration::from_secs(u64::from(hours) * 3600)).map(|_| Message::BackgroundRefresh)
+            }
+            _ => Subscription::none(),
+        };
+        let update_check = match self.update_check.every_hours {
+            0 => Subscription::none(),
+            hours => iced::time::every(std::time::Duration::from_secs(u64::from(hours) * 3600)).map(|_| Message::UpdateCheckTick),
+        };
+        Subscription::batch([
+            self.windows.subscription(),
+            event::listen_with(window_event),
+            transaction_queue::subscription().map(Message::QueueChanged),
+            bus_events(),
+            database_changes(),
+            background_refresh,
+            update_check,
+            iced::time::every(PREDOWNLOAD_CHECK).map(|_| Message::PredownloadTick),
+        ])
+    }
+
+    fn theme(&self, _window: window::Id) -> IcedTheme {
+        match self.theme {
+            AppTheme::Light => IcedTheme::Light,
+            AppTheme::Dark => IcedTheme::Dark,
+        }
+    }
+
+    fn scale_factor(&self, _window: window::Id) -> f64 {
+        self.ui_scale as f64
+    }
+}
+
+// Dialogs are dropped once their window is gone; closing the main window
+// leaves running dialogs open, and the app exits with the last window
+fn bus_events() -> Subscription<Message> {
+    use tokio::sync::broadcast::error::RecvError;
+
+    iced::subscription::unfold("event-bus", bus::subscribe(), |mut events| async move {
+        loop {
+            match events.recv().await {
+                Ok(event) => return (Message::Bus(event), events),
+                Err(RecvError::Lagged(missed)) => tracing::warn!("Event bus: missed {} events", missed),
+                // The sender is a static, so this never happens
+                Err(RecvError::Closed) => std::future::pending::<()>().await,
+            }
+        }
+    })
+}
+
+// Started by the runtime, so tests that drive update() don't watch the
+// real databases
+fn database_changes() -> Subscription<Message> {
+    iced::subscription::unfold("database-watch", None, |watch: Option<db_watch::DatabaseWatch>| async move {
+        let mut watch = match watch {
+            Some(watch) => watch,
+            None => match db_watch::watch(db_watch::targets(), db_watch::QUIET) {
+                Ok(watch) => watch,
+                Err(e) => {
+                    tracing::warn!("Can't watch the package databases, lists only refresh on request: {}", e);
+                    std::future::pending().await
+                }
+            },
+        };
+        match watch.changes.recv().await {
+            Some(change) => (Message::DatabaseChanged(change), Some(watch)),
+            // The watch thread only stops when the watch is dropped
+            None => std::future::pending().await,
+        }
+    })
+}
+
+fn window_event(event: Event, status: event::Status) -> Option<Message> {
+    match event {
+        Event::Window(id, window::Event::Closed) => Some(Message::WindowClosed(id)),
+        Event::Window(id, window::Event::Resized { width, .. }) => Some(Message::WindowResized(id, width as f32)),
+        Event::Window(id, window::Event::Focused) => Some(Message::WindowFocused(id, true)),
+        Event::Window(id, window::Event::Unfocused) => Some(Message::WindowFocused(id, false)),
+        Event::Window(id, window::Event::FileDropped(path)) if id == window::Id::MAIN => Some(Message::FileDropped(path)),
+        // The search field takes Escape to let go of the keyboard; the suggestions close with it
+        Event::Keyboard(keyboard::Event::KeyPressed { key: keyboard::Key::Named(keyboard::key::Named::Escape), .. }) => {
+            Some(Message::DismissSuggestions)
+        }
+        // Keys a text field or another widget took don't move the list
+        Event::Keyboard(keyboard::Event::KeyPressed { key, modifiers, .. }) if status == event::Status::Ignored => {
+            match key {
+                keyboard::Key::Named(keyboard::key::Named::Tab) if modifiers.is_empty() => Some(Message::AcceptSuggestion),
+                _ => list_nav::ListKey::from_key(&key, modifiers).map(Message::ListKey),
+            }
+        }
+        _ => None,
+    }
+}
+
+fn installed_list_id() -> scrollable::Id {
+    scrollable::Id::new("installed-list")
+}
+
+fn search_list_id() -> scrollable::Id {
+    scrollable::Id::new("search-list")
+}
+
+impl BirdNestGUI {
+    fn with_backend(config: Config, backend: Arc<dyn PackageBackend>, system: SystemKind) -> (BirdNestGUI, Command<Message>) {
+        tracing::debug!("Application::new() called - initializing BirdNestGUI...");
+        
+        tracing::debug!("Creating message channel...");
+        let (tx, _rx) = mpsc::unbounded_channel();
+        tracing::debug!("Message channel created successfully");
+        
+        // ABRoot and OSTree stage their own updates for the next boot
+        let shutdown_supported = !system.is_immutable() && shutdown_updates::supported(backend.as_ref());
+
+        tracing::debug!("Creating BirdNestGUI struct...");
+        let gui = BirdNestGUI {
+            // Native packages need a reboot on immutable systems, so open on Flatpak there
+            current_tab: config.default_tab.as_deref().and_then(tab_named).unwrap_or(if system.is_immutable() {
+                Tab::Flatpak
+            } else {
+                Tab::Search
+            }),
+            theme: theme_from(config.theme),
+            search_query: String::new(),
+            search_results: Vec::new(),
+            search_index: None,
+            app_catalog: Arc::default(),
+            flatpak_catalog: Arc::default(),
+            browse: browse::Browse::default(),
+            search_generation: 0,
+            selected_packages: HashSet::new(),
+            installed_packages: Vec::new(),
+            installed_search_query: String::new(),
+            installed_sort: SortOrder::Name,
+            installed_pager: Pager::default(),
+            installed_facts: InstalledFacts::default(),
+            disk_usage: disk_usage::DiskUsageState::default(),
+            architectures: architectures::Architectures::default(),
+            installed_cursor: list_nav::ListCursor::default(),
+            installed_descriptions: descriptions::Descriptions::default(),
+            selected_installed: HashSet::new(),
+            flatpak_apps: Vec::new(),
+            flatpak_search_query: String::new(),
+            flatpak_installed_filter: String::new(),
+            flatpak_search_results: Vec::new(),
+            flatpak_remote_choice: HashMap::new(),
+            flatpak_default_remote: config.flatpak_default_remote.clone(),
+            install_options: config.install_options.clone(),
+            selected_flatpak: HashSet::new(),
+            install_dialog: None,
+            remove_dialog: None,
+            packages_to_remove: Vec::new(),
+            activity: activity::Activity::default(),
+            command_tx: Some(Arc::new(tx)),
+            border_radius: config.border_radius,
+            window_width: scaling::fit(MAIN_WINDOW_SIZE).width,
+            nav_open: false,
+            installed_loaded: false,
+            flatpak_loaded: false,
+            installed_loading: true, // Start loading immediately
+            flatpak_loading: false,
+            searching: false,
+            refreshing: false,
+            source_health: Vec::new(),
+            can_hold: !backend.hold_commands(&[]).is_empty(),
+            pikman_search_query: String::new(),
+            pikman_search_results: Vec::new(),
+            selected_pikman: HashMap::new(),
+            pikman_filter: None,
+            pikman_loading: false,
+            pikman_installed: HashMap::new(),
+            pikman_terminal: pikman_terminal::PikmanTerminal::default(),
+            pikman_exports: pikman_exports::PikmanExports::default(),
+            terminal: config.terminal.clone(),
+            result_limit: config.result_limit(),
+            low_memory: config.low_memory_mode,
+            search_limit: config.search_limit(),
+            search_filters: search_filters::SearchFilters::default(),
+            search_sort: SortOrder::default(),
+            search_order: Vec::new(),
+            search_pager: Pager::default(),
+            refresh_schedule: config.refresh_schedule.clone(),
+            predownloading: false,
+            deferred_removal_days: config.deferred_removal_days,
+            update_check: config.update_check.clone(),
+            preload: config.preload.clone(),
+            update_count: None,
+            notified_updates: 0,
+            checking_updates: false,
+            notifiers: Notifiers::select(&config.notifications, notify::Context::Gui),
+            notify_on_finish: config.notifications.on_finish,
+            lists_refreshed_at: None,
+            focused_window: Some(window::Id::MAIN),
+            announced: false,
+            ui_scale: scaling::clamp(config.ui_scale),
+            backend,
+            updates: updates::UpdatesState { shutdown_supported, ..updates::UpdatesState::default() },
+            maintenance: maintenance::MaintenanceState::default(),
+            sources: sources::SourcesState::default(),
+            appimages: appimage::AppImageState { directories: birdnest_core::appimage::directories(&config), ..appimage::AppImageState::default() },
+            system,
+            windows: windows::Windows::default(),
+            main_focused: true,
+            row_menu: row_menu::RowMenu::default(),
+            global_search: global_search::GlobalSearch::default(),
+            all_sources: all_sources::AllSources::default(),
+            autocomplete: autocomplete::Autocomplete::default(),
+            details: details_pane::DetailsPane::default(),
+            transactions: Vec::new(),
+        };
+        tracing::debug!("BirdNestGUI struct created successfully");
+        profiling::mark("application state created");
+        
+        // Backends are initialized after the window is up so the first frame
+        // isn't blocked on dpkg parsing; the tab bar shows a loading state meanwhile
+        let mut cmd = Command::perform(future::ready(()), |_| Message::InitializeBackends);
+        if gui.current_tab == Tab::Flatpak {
+            cmd = Command::batch([cmd, Command::perform(future::ready(()), |_| Message::TabChanged(Tab::Flatpak))]);
+        }
+        tracing::debug!("Deferred backend init scheduled, returning from Application::new()");
+        
+        (gui, cmd)
+    }
+
+    // Flatpaks found by a search install from the remote picked for them
+    fn install_dialog(&self, package_names: Vec<String>, is_flatpak: bool) -> InstallDialog {
+        let mut dialog = InstallDialog::new(package_names, is_flatpak);
+        dialog.options = self.install_options.clone();
+        if is_flatpak {
+            dialog.flatpak_remotes = dialog
+                .package_names
+                .iter()
+                .filter_map(|app| Some((app.clone(), self.flatpak_remote(app)?.to_string())))
+                .collect();
+        }
+        dialog
+    }
+
+    fn pikman_install_dialog(&self, package_names: Vec<String>) -> PikmanInstallDialog {
+        let mut dialog = PikmanInstallDialog::new(package_names);
+        dialog.options = self.install_options.clone();
+        dialog
+    }
+
+    fn remove_dialog(&self, package_names: Vec<String>, is_flatpak: bool) -> RemoveDialog {
+        let mut dialog = RemoveDialog::new(package_names, is_flatpak);
+        dialog.defer_days = self.deferred_removal_days;
+        dialog
+    }
+
+    fn flatpak_remote(&self, application: &str) -> Option<&str> {
+        if let Some(remote) = self.flatpak_remote_choice.get(application) {
+            return Some(remote);
+        }
+        let result = self.flatpak_search_results.iter().find(|r| r.application == application)?;
+        flatpak::preferred_remote(&result.remotes, self.flatpak_default_remote.as_deref())
+    }
+
+    // Saved in the Settings window or as an install dialog's defaults; the
+    // package backend waits for a restart
+    fn apply_settings(&mut self, config: &Config) {
+        self.theme = theme_from(config.theme);
+        self.border_radius = config.border_radius;
+        self.deferred_removal_days = config.deferred_removal_days;
+        self.update_check = config.update_check.clone();
+        self.notifiers = Notifiers::select(&config.notifications, notify::Context::Gui);
+        self.notify_on_finish = config.notifications.on_finish;
+        self.preload = config.preload.clone();
+        self.terminal = config.terminal.clone();
+        self.search_limit = config.search_limit();
+        self.flatpak_default_remote = config.flatpak_default_remote.clone();
+        self.install_options = config.install_options.clone();
+        self.appimages.directories = birdnest_core::appimage::directories(config);
+        birdnest_core::utils::set_auto_confirm(config.auto_confirm);
+        cache::set_ttl(config.cache_ttl());
+        birdnest_core::proxy::apply(&config.proxy);
+    }
+
+    // One run at a time; a run that fails leaves the rest for the next load
+    fn uninstall_deferred(&mut self, entries: Vec<DeferredRemoval>) -> Command<Message> {
+        if entries.is_empty() || self.maintenance.uninstalling_deferred {
+            return Command::none();
+        }
+        self.maintenance.uninstalling_deferred = true;
+        Command::perform(maintenance::uninstall_deferred(self.backend.clone(), entries), Message::DeferredUninstalled)
+    }
+
+    fn open_dialog(&mut self, dialog: Dialog) -> Command<Message> {
+        let (_, command) = self.windows.open(dialog, &self.backend, self.system, self.theme, self.border_radius);
+        command
+    }
+
+    // The header field stands in for each tab's own search box
+    fn forward_header_query(&mut self) -> Command<Message> {
+        let query = self.global_search.query.clone();
+        match self.current_tab {
+            Tab::Search | Tab::AppImage | Tab::Updates | Tab::Sources | Tab::Maintenance => self.update(Message::SearchQueryChanged(query)),
+            Tab::Installed => self.update(Message::InstalledSearchQueryChanged(query)),
+            // The Browse grid reads the header query as it renders
+            Tab::Browse => Command::none(),
+            Tab::Flatpak => self.update(Message::FlatpakSearchQueryChanged(query)),
+            Tab::Pikman => self.update(Message::PikmanSearchQueryChanged(query)),
+        }
+    }
+
+    // Sends the query to every source at once; each answers on its own
+    fn search_all_sources(&mut self, query: String) -> Command<Message> {
+        self.activity.info(format!("Searching every source for: {}", query));
+        self.all_sources.start(&query);
+        Command::batch(all_sources::SOURCES.iter().map(|source| {
+            let (source, query) = (source.clone(), query.clone());
+            Command::perform(all_sources::search(self.backend.clone(), source.clone(), query.clone()), move |results| {
+                Message::AllSourcesLoaded(source, query, results)
+            })
+        }))
+    }
+
+    fn search_active_tab(&mut self) -> Command<Message> {
+        match self.current_tab {
+            Tab::Search | Tab::AppImage | Tab::Updates | Tab::Sources | Tab::Maintenance => self.update(Message::Search),
+            // Filtering happens as you type
+            Tab::Installed | Tab::Browse => Command::none(),
+            Tab::Flatpak => self.update(Message::FlatpakSearch),
+            Tab::Pikman => self.update(Message::PikmanSearch),
+        }
+    }
+
+    // Extra results that are the same app as a Search tab row are offered on
+    // that row; elsewhere they only fold into each other
+    fn merged_results(&self) -> global_search::Merged {
+        let tab_keys: Vec<Vec<String>> = match self.current_tab {
+            Tab::Search => self
+                .search_results
+                .iter()
+                .map(|pkg| global_search::merge_keys(&pkg.name, self.app_catalog.for_package(&pkg.name).map(|app| app.id.as_str())))
+                .collect(),
+            _ => Vec::new(),
+        };
+        global_search::merge(&tab_keys, &self.global_search.extras)
+    }
+
+    fn active_tab_results(&self) -> usize {
+        match self.current_tab {
+            Tab::Search => self.search_results.len(),
+            Tab::Flatpak => self.flatpak_search_results.len(),
+            Tab::Pikman => self.pikman_search_results.len(),
+            Tab::Installed | Tab::Browse | Tab::AppImage | Tab::Updates | Tab::Sources | Tab::Maintenance => 0,
+        }
+    }
+
+    fn row_action(&mut self, target: RowTarget, action: RowAction) -> Command<Message> {
+        tracing::debug!("Row action {:?} on {}", action, target.name);
+        // The file list shows inside the menu; everything else is done with it
+        if action != RowAction::ShowFiles {
+            self.row_menu.close();
+        }
+        match action {
+            RowAction::Install => {
+                let dialog = self.install_dialog(vec![target.name], target.flatpak);
+                self.open_dialog(Dialog::Install(Box::new(dialog)))
+            }
+            RowAction::Remove => self.open_dialog(Dialog::Remove(self.remove_dialog(vec![target.name], target.flatpak))),
+            RowAction::Reinstall => {
+                let commands = row_menu::commands(action, &target, self.backend.as_ref());
+                Command::perform(row_menu::run(commands, format!("Reinstalled {}", target.name)), Message::RowActionFinished)
+            }
+            RowAction::Hold => self.set_held(target.name, true),
+            RowAction::CopyName => {
+                self.activity.info(format!("Copied {} to clipboard", target.name));
+                iced::clipboard::write(target.name)
+            }
+            RowAction::OpenHomepage => Command::perform(row_menu::open_homepage(self.backend.clone(), target), Message::RowActionFinished),
+            RowAction::ShowFiles => Command::perform(row_menu::files(self.backend.clone(), target.name.clone()), move |files| {
+                Message::RowFilesLoaded(target, files)
+            }),
+            RowAction::Permissions => self.open_dialog(Dialog::Permissions(PermissionsDialog::new(target.name))),
+            RowAction::ExportBundle => {
+                self.activity.info(format!("Exporting {} to a bundle", target.name));
+                Command::perform(row_menu::export_bundle(target.name), Message::RowActionFinished)
+            }
+        }
+    }
+
+    fn set_held(&mut self, package: String, held: bool) -> Command<Message> {
+        let names = std::slice::from_ref(&package);
+        let (commands, done) = if held {
+            (self.backend.hold_commands(names), format!("Held {} at its installed version", package))
+        } else {
+            (self.backend.unhold_commands(names), format!("Released {}; it upgrades again", package))
+        };
+        if commands.is_empty() {
+            return Command::none();
+        }
+        Command::perform(row_menu::run(commands, done), Message::HoldChanged)
+    }
+
+    // Flatpak release notes are already in the remotes' catalogs; apt
+    // changelogs download one after another, each from where the last left off
+    fn fetch_changelogs(&mut self) -> Command<Message> {
+        let flatpaks: Vec<(bool, String)> = self
+            .updates
+            .entries
+            .iter()
+            .map(updates::UpdateInfo::key)
+            .filter(|key| key.0 && self.updates.expanded.contains(key) && !self.updates.changelogs.contains_key(key))
+            .collect();
+        for key in flatpaks {
+            let installed = self.updates.entries.iter().find(|e| e.key() == key).map(|e| e.current_version.clone()).unwrap_or_default();
+            let notes = changelog::for_app(&self.flatpak_catalog, &key.1).map(|entries| changelog::newer_than(entries, &installed));
+            let mut changelog = changelog_view::Changelog::default();
+            changelog.set(notes.map_err(|e| e.to_string()));
+            self.updates.changelogs.insert(key, changelog);
+        }
+
+        let Some(entry) = self.updates.next_changelog() else {
+            return Command::none();
+        };
+        let key = entry.key();
+        let installed = Some(entry.current_version.clone()).filter(|version| !version.is_empty());
+        self.updates.changelogs.insert(key.clone(), changelog_view::Changelog::Loading);
+        Command::perform(changelog_view::load(self.backend.clone(), key.1.clone(), installed), move |result| {
+            Message::UpdateChangelogLoaded(key, result)
+        })
+    }
+
+    fn show_details(&mut self, package: String) -> Command<Message> {
+        if !self.details.show(package.clone()) {
+            return Command::none();
+        }
+        self.details.app = self.app_catalog.for_package(&package).cloned();
+        let details = Command::perform(details_pane::load(self.backend.clone(), package.clone()), {
+            let package = package.clone();
+            move |details| Message::DetailsLoaded(package, details)
+        });
+        // Only installed packages have processes to measure
+        let details = if self.installed_packages.iter().any(|pkg| pkg.name == package) {
+            Command::batch([details, self.measure_footprint(package.clone())])
+        } else {
+            details
+        };
+        // AppStream's screenshot if it has one; screenshots.debian.net only
+        // knows Debian package names
+        let url = self.details.app.as_ref().and_then(|app| app.screenshots.first().cloned());
+        if self.low_memory || (url.is_none() && self.backend.name() != "apt") {
+            return details;
+        }
+        Command::batch([
+            details,
+            Command::perform(details_pane::screenshot(package.clone(), url), move |path| Message::ScreenshotLoaded(package, path)),
+        ])
+    }
+
+    fn measure_footprint(&mut self, package: String) -> Command<Message> {
+        self.details.footprint = details_pane::Usage::Measuring;
+        Command::perform(details_pane::measure(self.backend.clone(), package.clone()), move |footprint| {
+            Message::FootprintLoaded(package, footprint)
+        })
+    }
+
+    fn with_details_pane<'a>(&'a self, list: Element<'a, Message>) -> Element<'a, Message> {
+        if self.details.package.is_none() {
+            return list;
+        }
+        // No room for both side by side; Close goes back to the list
+        if self.is_narrow() {
+            return details_pane::view(&self.details, self.theme, self.border_radius, Length::Fill);
+        }
+        row![list, details_pane::view(&self.details, self.theme, self.border_radius, Length::Fixed(details_pane::PANE_WIDTH))]
+            .spacing(16)
+            .height(Length::Fill)
+            .into()
+    }
+
+    // What the row menu offers is only worked out for the row it is open on
+    fn with_row_menu<'a>(&'a self, row: Element<'a, Message>, target: RowTarget) -> Element<'a, Message> {
+        let actions = if self.row_menu.is_open(&target) {
+            let installed = if target.flatpak {
+                self.flatpak_apps.iter().any(|app| app.application == target.name)
+            } else {
+                self.installed_packages.iter().any(|pkg| pkg.name == target.name)
+            };
+            row_menu::actions(&target, installed, self.backend.as_ref())
+        } else {
+            Vec::new()
+        };
+        row_menu::wrap(row, target, &self.row_menu, actions, self.theme, self.border_radius)
+    }
+
+    fn bus_event(&mut self, event: BusEvent) -> Command<Message> {
+        match event {
+            BusEvent::Finished { id, result } => {
+                self.announce_finished(id, &result);
+                match result {
+                    Ok(()) => {
+                        tracing::debug!("Transaction #{} finished, refreshing lists", id);
+                        self.refresh_after_transaction()
+                    }
+                    Err(e) => {
+                        tracing::warn!("Transaction failed: {}", e);
+                        Command::none()
                     }
-                })
+                }
             }
-            Message::PikmanPurge(packages) => {
-                self.output_log.push(format!("Purging packages: {:?}", packages));
-                Command::perform(pikman_purge(packages), |result| {
-                    match result {
-                        Ok(msg) => Message::OutputReceived(msg),
-                        Err(e) => Message::ErrorReceived(e.to_string()),
-                    }
-                })
+            BusEvent::NeedsInput { prompt, .. } => {
+                self.activity.info(prompt);
+                Command::none()
             }
-            Message::PikmanRun { name, command } => {
-                self.output_log.push(format!("Running command in container: {}", name));
-                Command::perform(pikman_run(name, command), |result| {
-                    match result {
-                        Ok(msg) => Message::OutputReceived(msg),
-                        Err(e) => Message::ErrorReceived(e.to_string()),
-                    }
-                })
+            BusEvent::CacheInvalidated => {
+                // Reloaded the next time the Installed tab opens
+                self.installed_loaded = false;
+                Command::none()
             }
-            Message::PikmanUpgrades => {
-                Command::perform(pikman_upgrades(), |result| {
-                    match result {
-                        Ok(msg) => Message::OutputReceived(msg),
-                        Err(e) => Message::ErrorReceived(e.to_string()),
-                    }
-                })
+            BusEvent::TransactionStarted { .. } | BusEvent::Progress { .. } => Command::none(),
+        }
+    }
+
+    // Finished transactions are announced to a user who is in another app
+    fn announce_finished(&mut self, id: u64, result: &Result<(), String>) {
+        if !self.notify_on_finish || self.focused_window.is_some() {
+            return;
+        }
+        let label = transaction_queue::label(id).unwrap_or_else(|| "Package transaction".to_string());
+        self.notifiers.notify(&Notification::finished(&label, result));
+        self.announced = true;
+    }
+
+    // Another tool installed or removed something. BirdNest's own
+    // transactions reload the lists when they finish, so changes while one
+    // runs, or from before the last reload, are left alone.
+    fn database_changed(&mut self, change: db_watch::Change) -> Command<Message> {
+        let running = self.transactions.iter().any(|item| !item.is_finished());
+        if running || self.lists_refreshed_at.is_some_and(|at| at >= change.at) {
+            return Command::none();
+        }
+        tracing::debug!("{:?} database changed outside BirdNest, reloading", change.database);
+        match change.database {
+            Database::Packages => {
+                invalidate_packages_cache();
+                self.installed_loading = true;
+                self.installed_loaded = false;
+                Command::perform(load_installed_packages(self.backend.clone()), Message::InstalledPackagesLoaded)
             }
-            Message::PikmanUnexport { package, name } => {
-                self.output_log.push(format!("Unexporting package: {}", package));
-                Command::perform(pikman_unexport(package, name), |result| {
-                    match result {
-                        Ok(msg) => Message::OutputReceived(msg),
-                        Err(e) => Message::ErrorReceived(e.to_string()),
-                    }
+            Database::Flatpak => self.update(Message::LoadFlatpakApps),
+        }
+    }
+
+    // A transaction finished, so every list it may have changed is reloaded
+    fn refresh_after_transaction(&mut self) -> Command<Message> {
+        self.lists_refreshed_at = Some(SystemTime::now());
+        invalidate_packages_cache();
+        self.installed_loading = true;
+        self.installed_loaded = false;
+        Command::batch([
+            Command::perform(load_installed_packages(self.backend.clone()), Message::InstalledPackagesLoaded),
+            self.update(Message::LoadFlatpakApps),
+            self.update(Message::LoadUpdates),
+        ])
+    }
+
+    fn instant_search(&self) -> Command<Message> {
+        let Some(index) = self.search_index.clone() else {
+            return Command::none();
+        };
+        let (query, generation) = (self.search_query.clone(), self.search_generation);
+        // Every match, to sort and page through; low-memory mode keeps the first few
+        let limit = self.result_limit.unwrap_or(usize::MAX);
+        let (names_only, category, catalog) = (self.search_filters.names_only, self.search_filters.category, self.app_catalog.clone());
+        Command::perform(
+            async move {
+                tokio::task::spawn_blocking(move || {
+                    index.search_filtered(&query, limit, names_only, |package| search_filters::in_category(&catalog, &package.name, category))
                 })
-            }
+                .await
+                .unwrap_or_default()
+            },
+            move |matches| Message::InstantSearchResults(generation, matches.packages.into_iter().map(PackageInfo::from).collect(), matches.total),
+        )
+    }
+
+    // New results start on their first page, in the order picked
+    fn set_search_results(&mut self, results: Vec<PackageInfo>) {
+        self.search_order = self.installed_facts.order(&results, self.search_sort);
+        self.search_results = results;
+        self.search_pager.reset();
+    }
+
+    // Positions in search_results of the page on show
+    fn search_page(&self) -> &[usize] {
+        &self.search_order[self.search_pager.range(self.search_order.len(), self.search_limit)]
+    }
+
+    fn cap_results<T>(&self, results: &mut Vec<T>) {
+        if let Some(limit) = self.result_limit {
+            results.truncate(limit);
         }
     }
 
-    fn view(&self) -> Element<Message> {
-        let theme = self.theme;
-        let content = match self.current_tab {
-            Tab::Search => self.view_search(),
-            Tab::Installed => self.view_installed(),
-            Tab::Flatpak => self.view_flatpak(),
-            Tab::Pikman => self.view_pikman(),
+    // Everything ticked in every tab, as one batch
+    fn batch_items(&self) -> Vec<batch::Item> {
+        let system = |names: &HashSet<String>, action| {
+            names.iter().map(move |name| batch::Item::new(name, batch::Origin::System, action)).collect::<Vec<_>>()
         };
+        let mut items = system(&self.selected_installed, batch::Action::Remove);
+        items.extend(system(&self.selected_packages, batch::Action::Install));
+        items.extend(self.selected_flatpak.iter().map(|id| batch::Item::new(id, batch::Origin::Flatpak, batch::Action::Install)));
+        items.extend(
+            self.selected_pikman
+                .iter()
+                .map(|(name, source)| batch::Item::new(name, batch_bar::pikman_origin(source), batch::Action::Install)),
+        );
+        items
+    }
 
-        let main_content = column![
-            self.view_header(),
-            self.view_tabs(),
-            content,
-        ]
-        .spacing(15)
-        .padding(Padding::new(24.0));
-        
-        // Main background - dark, no elevation
-        container(main_content)
-            .width(Length::Fill)
-            .height(Length::Fill)
-            .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
-                radius: 0.0, // No radius for main background
-                background: Some(theme.background()), // Use dark background, not card
-                elevation: 0.0, // No elevation for main background
-            })))
-            .padding(Padding::new(16.0))
-            .into()
+    fn clear_batch(&mut self) {
+        self.selected_packages.clear();
+        self.selected_installed.clear();
+        self.selected_flatpak.clear();
+        self.selected_pikman.clear();
     }
 
-    fn theme(&self) -> IcedTheme {
-        match self.theme {
-            AppTheme::Light => IcedTheme::Light,
-            AppTheme::Dark => IcedTheme::Dark,
+    // Everything currently ticked in the Search, Installed and Flatpak tabs
+    fn script_selection(&self) -> ScriptSelection {
+        let mut apt_packages: Vec<String> = self.selected_packages
+            .union(&self.selected_installed)
+            .cloned()
+            .collect();
+        apt_packages.sort();
+        let mut flatpaks: Vec<FlatpakRef> = self.selected_flatpak
+            .iter()
+            .map(|application| FlatpakRef { application: application.clone(), remote: None })
+            .collect();
+        flatpaks.sort_by(|a, b| a.application.cmp(&b.application));
+        ScriptSelection { apt_packages, flatpaks }
+    }
+
+    fn view_script_export_buttons(&self) -> Element<'_, Message> {
+        let theme = self.theme;
+        if self.script_selection().is_empty() {
+            return Element::from(Space::with_width(Length::Fixed(0.0)));
         }
+        row![
+            button("Copy as Script")
+                .on_press(Message::CopySelectionAsScript(ScriptFormat::Shell))
+                .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                    is_primary: false,
+                    radius: self.border_radius,
+                    primary_color: theme.primary(),
+                    text_color: Color::WHITE,
+                    background_color: theme.background(),
+                })))
+                .padding(Padding::new(10.0)),
+            button("Copy as Ansible")
+                .on_press(Message::CopySelectionAsScript(ScriptFormat::Ansible))
+                .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                    is_primary: false,
+                    radius: self.border_radius,
+                    primary_color: theme.primary(),
+                    text_color: Color::WHITE,
+                    background_color: theme.background(),
+                })))
+                .padding(Padding::new(10.0)),
+        ]
+        .spacing(8)
+        .into()
     }
-}
 
-impl BirdNestGUI {
     fn view_header(&self) -> Element<Message> {
-        // Header removed - no longer needed
-        Element::from(Space::with_height(Length::Fixed(0.0)))
+        let field = global_search::view_field(&self.global_search, self.current_tab, self.theme, self.border_radius);
+        if !self.autocomplete.is_open() {
+            return field;
+        }
+        column![field, autocomplete::view(&self.autocomplete, self.theme, self.border_radius)].spacing(4).into()
+    }
+
+    // Searching tabs complete every package name; the Installed tab only filters its own
+    fn suggestions(&self) -> Vec<String> {
+        let query = &self.global_search.query;
+        let installed = self.installed_packages.iter().map(|pkg| pkg.name.as_str());
+        match self.current_tab {
+            Tab::Search | Tab::AppImage | Tab::Updates | Tab::Sources | Tab::Maintenance => {
+                autocomplete::suggest(query, installed, self.search_index.as_deref(), &self.app_catalog)
+            }
+            Tab::Installed => autocomplete::suggest(query, installed, None, &Catalog::default()),
+            Tab::Browse | Tab::Flatpak | Tab::Pikman => Vec::new(),
+        }
     }
 
     fn view_tabs(&self) -> Element<Message> {
         let theme = self.theme;
-        container(
-            row![
-                self.tab_button("Search", Tab::Search),
-                self.tab_button("Installed", Tab::Installed),
-                self.tab_button("Flatpak", Tab::Flatpak),
-                self.tab_button("Pikman", Tab::Pikman),
+        let plain_button = |label| {
+            button(label)
+                .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                    is_primary: false,
+                    radius: self.border_radius,
+                    primary_color: theme.primary(),
+                    text_color: Color::WHITE,
+                    background_color: theme.background(),
+                })))
+                .padding(Padding::new(14.0))
+        };
+        let help = row![
+            plain_button("Settings").on_press(Message::ShowSettings),
+            plain_button("Help").on_press(Message::ShowCliReference),
+            plain_button("Logs").on_press(Message::ShowLogs),
+        ]
+        .spacing(12);
+        let theme_toggle = plain_button(if theme == AppTheme::Dark { "Light" } else { "Dark" }).on_press(Message::ThemeToggled);
+
+        let bar: Element<Message> = if self.is_narrow() {
+            // Only the current tab stays in the bar; the others, Settings, Help, Logs and
+            // the theme toggle move into a menu below it
+            let current = TABS.iter().find(|(_, tab)| *tab == self.current_tab).map_or("", |(label, _)| *label);
+            let mut bar = column![row![
+                self.tab_button(current, self.current_tab),
                 Space::with_width(Length::Fill),
-                button(if theme == AppTheme::Dark { "Light" } else { "Dark" })
-                    .on_press(Message::ThemeToggled)
-                    .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
-                        is_primary: false,
-                        radius: self.border_radius,
-                        primary_color: theme.primary(),
-                        text_color: Color::WHITE,
-                        background_color: theme.background(),
-                    })))
-                    .padding(Padding::new(14.0)),
+                plain_button(if self.nav_open { "Close" } else { "Menu" }).on_press(Message::ToggleNavMenu),
             ]
             .spacing(12)
-            .align_items(alignment::Alignment::Center)
-            .padding(Padding::new(16.0))
-        )
-        .width(Length::Fill)
-        .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
-            radius: self.border_radius,
-            background: Some(theme.card_background()),
-            elevation: 1.0, // Bubble effect for tab bar
-        })))
+            .align_items(alignment::Alignment::Center)]
+            .spacing(8);
+            if self.nav_open {
+                for (label, tab) in TABS.iter().filter(|(_, tab)| *tab != self.current_tab) {
+                    bar = bar.push(self.tab_button(label, *tab));
+                }
+                bar = bar.push(row![help, theme_toggle].spacing(12));
+            }
+            bar.padding(Padding::new(16.0)).into()
+        } else {
+            let mut bar = row![].spacing(12).align_items(alignment::Alignment::Center);
+            for (label, tab) in TABS {
+                bar = bar.push(self.tab_button(label, tab));
+            }
+            bar.push(Space::with_width(Length::Fill))
+                .push(help)
+                .push(theme_toggle)
+                .padding(Padding::new(16.0))
+                .into()
+        };
+        container(bar)
+            .width(Length::Fill)
+            .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+                radius: self.border_radius,
+                background: Some(theme.card_background()),
+                elevation: 1.0, // Bubble effect for tab bar
+            })))
+            .into()
+    }
+
+    fn is_narrow(&self) -> bool {
+        adaptive::is_narrow(self.window_width)
+    }
+
+    // On immutable systems, point native package tabs at the options that apply right away
+    fn view_system_notice(&self) -> Element<'_, Message> {
+        let theme = self.theme;
+        if !self.system.is_immutable() || !matches!(self.current_tab, Tab::Search | Tab::Installed) {
+            return Element::from(Space::with_height(Length::Fixed(0.0)));
+        }
+        text(format!(
+            "{} system: package changes here apply after a reboot. Flatpak apps and Pikman containers install right away.",
+            self.system.name()
+        ))
+        .size(14.0)
+        .style(iced::theme::Text::Color(theme.secondary_text()))
         .into()
     }
 
+    // Run one root command that changes the sources, then reload them
+    fn edit_sources(&mut self, command: birdnest_core::package_manager::BackendCommand, done: String) -> Command<Message> {
+        if self.sources.busy {
+            return Command::none();
+        }
+        tracing::debug!("Editing sources: {}", command.args.join(" "));
+        self.sources.busy = true;
+        self.sources.error = None;
+        self.sources.confirm_remove = None;
+        self.sources.confirm_restore = None;
+        Command::perform(row_menu::run(vec![command], done), Message::SourceEdited)
+    }
+
+    // One action per AppImage at a time
+    fn run_appimage(&mut self, path: PathBuf, action: appimage::Action) -> Command<Message> {
+        let Some(found) = self.appimages.get(&path).cloned() else {
+            return Command::none();
+        };
+        if !self.appimages.busy.insert(path.clone()) {
+            return Command::none();
+        }
+        self.appimages.error = None;
+        self.appimages.confirm_remove = None;
+        Command::perform(appimage::run(found, action), move |result| Message::AppImageDone(path, action, result))
+    }
+
+    // Background loads still in flight, shown in the tab bar
+    // Whatever is running in the background, whichever tab started it
+    fn status(&self) -> status_bar::Status<'_> {
+        let mut tasks: Vec<String> = self
+            .transactions
+            .iter()
+            .filter(|item| item.status == transaction_queue::ItemStatus::Running)
+            .map(|item| item.label.clone())
+            .collect();
+        let flags = [
+            (self.installed_loading, "Loading installed packages"),
+            (self.flatpak_loading, "Loading Flatpak apps"),
+            (self.searching, "Searching"),
+            (self.pikman_loading, "Searching with pikman"),
+            (self.updates.loading, "Loading updates"),
+            (self.refreshing, "Refreshing package lists"),
+            (self.checking_updates, "Checking for updates"),
+            (self.predownloading, "Downloading runtimes"),
+            (self.maintenance.repairing.is_some(), "Repairing Flatpak"),
+            (self.disk_usage.loading, "Measuring Flatpak apps"),
+            (self.sources.loading, "Loading sources"),
+            (self.appimages.loading, "Looking for AppImages"),
+            (self.all_sources.is_searching(), "Searching every source"),
+            (self.pikman_installed.values().any(Option::is_none), "Starting pikman containers"),
+        ];
+        tasks.extend(flags.into_iter().filter(|(running, _)| *running).map(|(_, task)| task.to_string()));
+        status_bar::Status {
+            tasks,
+            health: &self.source_health,
+            updates: self.update_count,
+            renderer: crate::renderer::active(),
+            activity_open: self.activity.open,
+            unseen_errors: self.activity.unseen_errors,
+        }
+    }
+
     fn tab_button(&self, label: &str, tab: Tab) -> Element<Message> {
         let theme = self.theme;
         let is_active = self.current_tab == tab;
+        // The Updates tab carries the last check's count as a badge
+        let label = match self.update_count {
+            Some(count) if tab == Tab::Updates && count.total() > 0 => format!("{} ({})", label, count.total()),
+            _ => label.to_string(),
+        };
         button(text(label)
             .size(if is_active { 20.0 } else { 16.0 }))
             .on_press(Message::TabChanged(tab))
@@ -1126,37 +3250,16 @@ impl BirdNestGUI {
         // Search section with rounded container
         let search_section = container(
             column![
-                // Search bar with Search button
-                row![
-                    text_input("Search packages...", &self.search_query)
-                        .on_input(Message::SearchQueryChanged)
-                        .on_submit(Message::Search)
-                        .padding(Padding::new(12.0))
-                        .width(Length::Fill)
-                        .style(iced::theme::TextInput::Custom(Box::new(YellowTextInputStyle {
-                            radius: self.border_radius,
-                            primary_color: theme.primary(),
-                            background_color: theme.background(),
-                            text_color: Color::BLACK,
-                        }))),
-                    button(text("Search")
-                        .size(16.0))
-                        .on_press(Message::Search)
-                        .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
-                            is_primary: true,
-                            radius: self.border_radius,
-                            primary_color: theme.primary(),
-                            text_color: Color::BLACK,
-                            background_color: theme.background(),
-                        })))
-                        .padding(Padding::new(16.0)),
-                ]
-                .spacing(10)
-                .width(Length::Fill),
-                Space::with_height(Length::Fixed(10.0)),
+                advanced_options::toggle(
+                    "All sources: also search Flatpak, AUR, Fedora and Alpine",
+                    self.all_sources.enabled,
+                    Message::ToggleAllSources,
+                    theme,
+                ),
                 // Install button row
-                row![
-                    Space::with_width(Length::Fill),
+                adaptive::action_bar(
+                    self.is_narrow(),
+                    self.view_script_export_buttons(),
                     {
                         if !self.search_results.is_empty() {
                             if self.selected_packages.is_empty() {
@@ -1186,10 +3289,7 @@ impl BirdNestGUI {
                             Element::from(Space::with_width(Length::Fixed(0.0)))
                         }
                     },
-                ]
-                .spacing(8)
-                .width(Length::Fill)
-                .align_items(alignment::Alignment::Center),
+                ),
             ]
             .spacing(16)
         )
@@ -1202,7 +3302,10 @@ impl BirdNestGUI {
         })));
 
         // Search results or empty state
-        let content_section = if self.search_results.is_empty() {
+        let merged = self.merged_results();
+        let content_section = if self.all_sources.enabled {
+            all_sources::view(&self.all_sources, theme, self.border_radius)
+        } else if self.search_results.is_empty() {
             container(
                 text(if self.search_query.is_empty() {
                     "Enter a search query to find packages"
@@ -1217,16 +3320,20 @@ impl BirdNestGUI {
             .center_y()
             .into()
         } else {
-            // Show search results
-            Element::from(
+            // Show a page of search results, in the order picked
+            Element::from(column![
+                result_pages::sort_choices(&SortOrder::SEARCH, Some(self.search_sort), Message::SearchSortChanged, theme, self.border_radius),
+                result_pages::view(self.search_pager, self.search_results.len(), self.search_limit, Message::SearchPage, theme, self.border_radius),
                 container(
                     scrollable(
                         column(
-                            self.search_results
+                            self.search_page()
                                 .iter()
-                                .map(|pkg| {
+                                .map(|&index| {
+                                    let pkg = &self.search_results[index];
                                     let is_selected = self.selected_packages.contains(&pkg.name);
-                                    button(
+                                    let app = self.app_catalog.for_package(&pkg.name);
+                                    let card = button(
                                         container(
                                             row![
                                                 checkbox("", is_selected)
@@ -1234,8 +3341,9 @@ impl BirdNestGUI {
                                                         radius: 4.0,
                                                         primary_color: theme.primary(),
                                                     }))),
+                                                app_icon(app, !self.app_catalog.is_empty(), !self.low_memory),
                                                 column![
-                                                    text(&pkg.name)
+                                                    text(app.map_or(&pkg.name, |app| &app.name))
                                                         .size(if is_selected { 26.0 } else { 24.0 })
                                                         .style(iced::theme::Text::Color(if is_selected { Color::BLACK } else { Color::WHITE }))
                                                         .width(Length::Fill),
@@ -1263,6 +3371,15 @@ impl BirdNestGUI {
                                                                     .style(iced::theme::Text::Color(if is_selected { Color::BLACK } else { Color::WHITE })))
                                                             );
                                                         }
+                                                        if let Some(app) = app {
+                                                            // The title shows the app's name, so say which package it is
+                                                            info_row = info_row.push(
+                                                                Element::from(text(format!("Package: {}", pkg.name))
+                                                                    .size(if is_selected { 13.0 } else { 11.0 })
+                                                                    .style(iced::theme::Text::Color(if is_selected { Color::BLACK } else { Color::WHITE })))
+                                                            );
+                                                            info_row = info_row.push(view_categories(app, is_selected));
+                                                        }
                                                         Element::from(info_row)
                                                     },
                                                 ]
@@ -1291,58 +3408,152 @@ impl BirdNestGUI {
                                         text_color: Color::WHITE,
                                         background_color: Color::TRANSPARENT,
                                     })))
-                                    .into()
+                                    ;
+                                    // The same app from the extra sources installs from this row too
+                                    let card = match merged.for_tab.get(&index) {
+                                        Some(options) => column![
+                                            card,
+                                            container(global_search::view_install_options("Also from", options, theme, self.border_radius))
+                                                .padding(Padding::from([0.0, 12.0])),
+                                        ]
+                                        .spacing(4)
+                                        .into(),
+                                        None => Element::from(card),
+                                    };
+                                    self.with_row_menu(card, RowTarget::package(&pkg.name))
                                 })
                                 .collect::<Vec<_>>(),
                         )
                         .spacing(6)
                         .padding(10)
                     )
+                    .id(search_list_id())
                     .style(iced::theme::Scrollable::Custom(Box::new(CustomScrollableStyle {
                         background_color: theme.background(),
                         border_radius: self.border_radius,
                     })))
                 )
                 .width(Length::Fill)
-                .height(Length::Fill)
-            )
+                .height(Length::Fill),
+            ]
+            .spacing(8))
+        };
+
+        let banner: Element<Message> = if self.all_sources.enabled {
+            Space::with_height(Length::Fixed(0.0)).into()
+        } else {
+            search_filters::view(&self.search_filters, self.search_results.len(), theme)
         };
 
         column![
             search_section,
+            banner,
             Space::with_height(Length::Fixed(16.0)),
-            content_section,
+            self.with_details_pane(content_section),
         ]
         .spacing(20)
         .padding(Padding::new(24.0))
         .into()
     }
 
+    fn view_browse(&self) -> Element<'_, Message> {
+        let category = &browse::CATEGORIES[self.browse.category];
+        let apps = browse::apps(&self.app_catalog, &self.flatpak_catalog, category, &self.global_search.query);
+        // The main window's padding, and the details pane when it sits beside the grid
+        let mut width = self.window_width - 80.0;
+        if self.details.package.is_some() && !self.is_narrow() {
+            width -= details_pane::PANE_WIDTH + 16.0;
+        }
+        let grid = browse::view(&self.browse, apps, browse::columns(width), !self.low_memory, self.theme, self.border_radius);
+        column![self.with_details_pane(grid)].padding(Padding::new(24.0)).into()
+    }
+
+    // The lock on an installed row: held packages show it closed, and
+    // clicking holds or releases the package
+    fn view_hold_toggle(&self, package: &str) -> Element<'_, Message> {
+        let held = self.updates.held.contains(package);
+        if !self.can_hold && !held {
+            return Space::with_width(Length::Shrink).into();
+        }
+        let mut toggle = button(text(if held { "🔒 Held" } else { "🔓 Hold" }).size(13))
+            .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                is_primary: held,
+                radius: self.border_radius,
+                primary_color: self.theme.primary(),
+                text_color: if held { Color::BLACK } else { Color::WHITE },
+                background_color: self.theme.background(),
+            })))
+            .padding(Padding::from([6.0, 10.0]));
+        if self.can_hold {
+            toggle = toggle.on_press(Message::ToggleHold(package.to_string()));
+        }
+        toggle.into()
+    }
+
+    // The installed packages matching the filter, in the chosen order
+    // Asks for the descriptions of the Installed rows in view that lack one
+    fn describe_installed(&mut self) -> Command<Message> {
+        let names = self.installed_descriptions.wanted(&self.installed_page());
+        if names.is_empty() {
+            return Command::none();
+        }
+        self.installed_descriptions.mark_requested(&names);
+        Command::perform(descriptions::load(self.backend.clone(), names), Message::DescriptionsLoaded)
+    }
+
+    fn filtered_installed(&self) -> Vec<&PackageInfo> {
+        let mut packages: Vec<&PackageInfo> = if self.installed_search_query.is_empty() {
+            self.installed_packages.iter().filter(|pkg| self.architectures.keeps(pkg)).collect()
+        } else {
+            let query_lower = self.installed_search_query.to_lowercase();
+            self.installed_packages
+                .iter()
+                .filter(|pkg| self.architectures.keeps(pkg))
+                .filter(|pkg| {
+                    pkg.name.to_lowercase().contains(&query_lower) ||
+                    (!pkg.description.is_empty() && pkg.description.to_lowercase().contains(&query_lower)) ||
+                    pkg.version.to_lowercase().contains(&query_lower) ||
+                    // "essential", "automatically" or a source such as "backports"
+                    pkg.provenance.iter().flat_map(dpkg::Provenance::labels).any(|label| label.to_lowercase().contains(&query_lower))
+                })
+                .collect()
+        };
+        // Sorted by name first, so packages the same size or age stay in name order
+        packages.sort_by(|a, b| a.name.cmp(&b.name));
+        self.installed_facts.sort(&mut packages, self.installed_sort);
+        packages
+    }
+
+    // The page of filtered_installed on show
+    fn installed_page(&self) -> Vec<&PackageInfo> {
+        let packages = self.filtered_installed();
+        packages[self.installed_pager.range(packages.len(), self.search_limit)].to_vec()
+    }
+
     fn view_installed(&self) -> Element<Message> {
         let theme = self.theme;
         
         // Search section with rounded container
         let search_section = container(
             column![
-                // Search bar with Search button
-                row![
-                    text_input("Search installed packages...", &self.installed_search_query)
-                        .on_input(Message::InstalledSearchQueryChanged)
-                        .padding(Padding::new(12.0))
-                        .width(Length::Fill)
-                        .style(iced::theme::TextInput::Custom(Box::new(YellowTextInputStyle {
-                            radius: self.border_radius,
-                            primary_color: theme.primary(),
-                            background_color: theme.background(),
-                            text_color: Color::BLACK,
-                        }))),
-                ]
-                .spacing(10)
-                .width(Length::Fill),
-                Space::with_height(Length::Fixed(10.0)),
                 // Remove button row
-                row![
-                    Space::with_width(Length::Fill),
+                adaptive::action_bar(
+                    self.is_narrow(),
+                    row![
+                        self.view_script_export_buttons(),
+                        result_pages::sort_choices(
+                            &SortOrder::INSTALLED,
+                            (!self.disk_usage.open).then_some(self.installed_sort),
+                            Message::InstalledSortChanged,
+                            theme,
+                            self.border_radius,
+                        ),
+                        disk_usage::toggle_button("Disk Usage", self.disk_usage.open, Message::ToggleDiskUsage, theme, self.border_radius),
+                        architectures::toggles(&self.architectures, &self.installed_packages, theme, self.border_radius),
+                    ]
+                    .spacing(8)
+                    .align_items(alignment::Alignment::Center)
+                    .into(),
                     {
                         if !self.installed_packages.is_empty() {
                             if self.selected_installed.is_empty() {
@@ -1371,10 +3582,7 @@ impl BirdNestGUI {
                             Element::from(Space::with_width(Length::Fixed(0.0)))
                         }
                     },
-                ]
-                .spacing(8)
-                .width(Length::Fill)
-                .align_items(alignment::Alignment::Center),
+                ),
             ]
             .spacing(16)
         )
@@ -1407,32 +3615,25 @@ impl BirdNestGUI {
             .center_x()
             .center_y()
             .into()
+        } else if self.disk_usage.open {
+            disk_usage::view(&self.disk_usage, &self.installed_packages, &self.selected_installed, theme, self.border_radius)
         } else {
-            // Filter packages based on search query
-            let filtered_packages: Vec<&PackageInfo> = if self.installed_search_query.is_empty() {
-                self.installed_packages.iter().collect()
-            } else {
-                let query_lower = self.installed_search_query.to_lowercase();
-                self.installed_packages
-                    .iter()
-                    .filter(|pkg| {
-                        pkg.name.to_lowercase().contains(&query_lower) ||
-                        (!pkg.description.is_empty() && pkg.description.to_lowercase().contains(&query_lower)) ||
-                        pkg.version.to_lowercase().contains(&query_lower)
-                    })
-                    .collect()
-            };
+            let filtered_packages = self.filtered_installed();
+            let page = self.installed_pager.range(filtered_packages.len(), self.search_limit);
 
-            // Show package list
-            Element::from(
+            // Show a page of the package list
+            Element::from(column![
+                result_pages::view(self.installed_pager, filtered_packages.len(), self.search_limit, Message::InstalledPage, theme, self.border_radius),
                 container(
                     scrollable(
                         column(
-                            filtered_packages
+                            filtered_packages[page]
                                 .iter()
-                                .map(|pkg| {
+                                .enumerate()
+                                .map(|(index, pkg)| {
                                     let is_selected = self.selected_installed.contains(&pkg.name);
-                                    button(
+                                    let at_cursor = self.installed_cursor.index == Some(index);
+                                    let card = button(
                                         container(
                                             row![
                                                 checkbox("", is_selected)
@@ -1464,7 +3665,28 @@ impl BirdNestGUI {
                                                         let mut info_row = row![].spacing(12).width(Length::Fill);
                                                         if !pkg.version.is_empty() {
                                                             info_row = info_row.push(
-                                                                Element::from(text(format!("Version: {}", pkg.version))
+                                                                Element::from(text(format!("Version: {}", pkg.version))
+                                                                    .size(if is_selected { 13.0 } else { 11.0 })
+                                                                    .style(iced::theme::Text::Color(if is_selected { Color::BLACK } else { Color::WHITE })))
+                                                            );
+                                                        }
+                                                        if !pkg.size.is_empty() {
+                                                            info_row = info_row.push(
+                                                                Element::from(text(format!("Size: {}", pkg.size))
+                                                                    .size(if is_selected { 13.0 } else { 11.0 })
+                                                                    .style(iced::theme::Text::Color(if is_selected { Color::BLACK } else { Color::WHITE })))
+                                                            );
+                                                        }
+                                                        if let Some(arch) = pkg.arch.as_ref().filter(|_| self.architectures.shown) {
+                                                            info_row = info_row.push(
+                                                                Element::from(text(format!("Architecture: {}", arch.label()))
+                                                                    .size(if is_selected { 13.0 } else { 11.0 })
+                                                                    .style(iced::theme::Text::Color(if is_selected { Color::BLACK } else { Color::WHITE })))
+                                                            );
+                                                        }
+                                                        for label in pkg.provenance.iter().flat_map(dpkg::Provenance::labels) {
+                                                            info_row = info_row.push(
+                                                                Element::from(text(label)
                                                                     .size(if is_selected { 13.0 } else { 11.0 })
                                                                     .style(iced::theme::Text::Color(if is_selected { Color::BLACK } else { Color::WHITE })))
                                                             );
@@ -1474,6 +3696,7 @@ impl BirdNestGUI {
                                                 ]
                                                 .spacing(4)
                                                 .width(Length::Fill),
+                                                self.view_hold_toggle(&pkg.name),
                                             ]
                                             .spacing(12)
                                             .align_items(alignment::Alignment::Center)
@@ -1486,7 +3709,8 @@ impl BirdNestGUI {
                                             } else {
                                                 Some(theme.card_background())
                                             },
-                                            elevation: 1.0, // Subtle bubble effect for each package card
+                                            // The keyboard cursor's row stands out with a stronger edge
+                                            elevation: if at_cursor { 2.0 } else { 1.0 },
                                         })))
                                     )
                                     .on_press(Message::ToggleInstalledPackage(pkg.name.clone()))
@@ -1497,27 +3721,31 @@ impl BirdNestGUI {
                                         text_color: Color::WHITE,
                                         background_color: Color::TRANSPARENT,
                                     })))
-                                    .into()
+                                    ;
+                                    self.with_row_menu(card.into(), RowTarget::package(&pkg.name))
                                 })
                                 .collect::<Vec<_>>(),
                         )
                         .spacing(6)
                         .padding(10)
                     )
+                    .id(installed_list_id())
+                    .on_scroll(|viewport| Message::InstalledScrolled(viewport.relative_offset().y))
                     .style(iced::theme::Scrollable::Custom(Box::new(CustomScrollableStyle {
                         background_color: theme.background(),
                         border_radius: self.border_radius,
                     })))
                 )
                 .width(Length::Fill)
-                .height(Length::Fill)
-            )
+                .height(Length::Fill),
+            ]
+            .spacing(8))
         };
 
         column![
             search_section,
             Space::with_height(Length::Fixed(16.0)),
-            content_section,
+            self.with_details_pane(content_section),
         ]
         .spacing(20)
         .padding(Padding::new(24.0))
@@ -1525,72 +3753,105 @@ impl BirdNestGUI {
     }
 
 
+    // Heading over each remote's group of search results, when they span several remotes
+    fn view_flatpak_remote_header(&self, index: usize, grouped: bool) -> Option<Element<'_, Message>> {
+        let default_remote = self.flatpak_default_remote.as_deref();
+        let group = |index: usize| flatpak::preferred_remote(&self.flatpak_search_results[index].remotes, default_remote);
+        let remote = group(index);
+        if !grouped || (index > 0 && group(index - 1) == remote) {
+            return None;
+        }
+        let label = match remote {
+            Some(remote) if remote == default_remote.unwrap_or("flathub") => format!("From {} (default)", remote),
+            Some(remote) => format!("From {}", remote),
+            None => "Remote unknown".to_string(),
+        };
+        Some(
+            text(label)
+                .size(14)
+                .style(iced::theme::Text::Color(self.theme.primary()))
+                .into(),
+        )
+    }
+
+    // An app on several remotes gets a button per remote to pick where it installs from
+    fn view_flatpak_remote_picker<'a>(&'a self, fpkg: &'a FlatpakInfo, is_selected: bool) -> Element<'a, Message> {
+        let theme = self.theme;
+        let chosen = self.flatpak_remote(&fpkg.application);
+        let mut picker = row![
+            text("Remote:")
+                .size(11)
+                .style(iced::theme::Text::Color(if is_selected { Color::BLACK } else { Color::WHITE })),
+        ]
+        .spacing(4)
+        .align_items(alignment::Alignment::Center);
+        for remote in &fpkg.remotes {
+            let is_chosen = chosen == Some(remote.as_str());
+            // The selected card is already primary-coloured, so the tick marks the choice there
+            let is_primary = is_chosen && !is_selected;
+            picker = picker.push(
+                button(text(if is_chosen { format!("✓ {}", remote) } else { remote.clone() }).size(11))
+                    .on_press(Message::FlatpakRemoteChosen {
+                        application: fpkg.application.clone(),
+                        remote: remote.clone(),
+                    })
+                    .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                        is_primary,
+                        radius: self.border_radius,
+                        primary_color: theme.primary(),
+                        text_color: if is_primary { Color::BLACK } else { Color::WHITE },
+                        background_color: theme.background(),
+                    })))
+                    .padding(Padding::from([2.0, 8.0])),
+            );
+        }
+        picker.into()
+    }
+
     fn view_flatpak(&self) -> Element<Message> {
         let theme = self.theme;
         
         // Search section with rounded container
         let search_section = container(
             column![
-                // Search bar with Search button
-                row![
-                    text_input("Search Flatpak packages...", &self.flatpak_search_query)
-                        .on_input(Message::FlatpakSearchQueryChanged)
-                        .on_submit(Message::FlatpakSearch)
-                        .padding(Padding::new(12.0))
-                        .width(Length::Fill)
-                        .style(iced::theme::TextInput::Custom(Box::new(YellowTextInputStyle {
-                            radius: self.border_radius,
-                            primary_color: theme.primary(),
-                            background_color: theme.background(),
-                            text_color: Color::BLACK,
-                        }))),
-                    button("Search")
-                        .on_press(Message::FlatpakSearch)
-                        .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
-                            is_primary: true,
-                            radius: self.border_radius,
-                            primary_color: theme.primary(),
-                            text_color: Color::BLACK,
-                            background_color: theme.background(),
-                        })))
-                        .padding(Padding::new(16.0)),
-                ]
-                .spacing(10)
-                .width(Length::Fill),
-                Space::with_height(Length::Fixed(10.0)),
                 // Action buttons and Install button row
-                row![
-                    button("Update Repos")
-                        .on_press(Message::FlatpakUpdateRepos)
-                        .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
-                            is_primary: false,
-                            radius: self.border_radius,
-                            primary_color: theme.primary(),
-                            text_color: Color::WHITE,
-                            background_color: theme.background(),
-                        })))
-                        .padding(Padding::new(14.0)),
-                    button("Upgrade All")
-                        .on_press(Message::FlatpakUpgradeAll)
-                        .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
-                            is_primary: false,
-                            radius: self.border_radius,
-                            primary_color: theme.primary(),
-                            text_color: Color::WHITE,
-                            background_color: theme.background(),
-                        })))
-                        .padding(Padding::new(14.0)),
-                    button("Clean")
-                        .on_press(Message::FlatpakClean)
-                        .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
-                            is_primary: false,
-                            radius: self.border_radius,
-                            primary_color: theme.primary(),
-                            text_color: Color::WHITE,
-                            background_color: theme.background(),
-                        })))
-                        .padding(Padding::new(14.0)),
-                    Space::with_width(Length::Fill),
+                adaptive::action_bar(
+                    self.is_narrow(),
+                    row![
+                        button("Update Repos")
+                            .on_press(Message::FlatpakUpdateRepos)
+                            .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                                is_primary: false,
+                                radius: self.border_radius,
+                                primary_color: theme.primary(),
+                                text_color: Color::WHITE,
+                                background_color: theme.background(),
+                            })))
+                            .padding(Padding::new(14.0)),
+                        button("Upgrade All")
+                            .on_press(Message::FlatpakUpgradeAll)
+                            .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                                is_primary: false,
+                                radius: self.border_radius,
+                                primary_color: theme.primary(),
+                                text_color: Color::WHITE,
+                                background_color: theme.background(),
+                            })))
+                            .padding(Padding::new(14.0)),
+                        button("Clean")
+                            .on_press(Message::FlatpakClean)
+                            .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                                is_primary: false,
+                                radius: self.border_radius,
+                                primary_color: theme.primary(),
+                                text_color: Color::WHITE,
+                                background_color: theme.background(),
+                            })))
+                            .padding(Padding::new(14.0)),
+                    ]
+                    .spacing(8)
+                    .align_items(alignment::Alignment::Center)
+                    .into(),
                     {
                         if !self.flatpak_search_results.is_empty() {
                             if self.selected_flatpak.is_empty() {
@@ -1604,26 +3865,36 @@ impl BirdNestGUI {
                                     })))
                                     .padding(Padding::new(10.0)))
                             } else {
-                                Element::from(button(text(format!("Install {} Selected", self.selected_flatpak.len()))
-                                    .size(16.0))
-                                    .on_press(Message::InstallSelected)
-                                    .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
-                                        is_primary: true,
-                                        radius: self.border_radius,
-                                        primary_color: theme.primary(),
-                                        text_color: Color::BLACK,
-                                        background_color: theme.background(),
-                                    })))
-                                    .padding(Padding::new(10.0)))
+                                Element::from(row![
+                                    button(text("Download Runtimes Tonight").size(16.0))
+                                        .on_press(Message::PredownloadSelected)
+                                        .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                                            is_primary: false,
+                                            radius: self.border_radius,
+                                            primary_color: theme.primary(),
+                                            text_color: Color::WHITE,
+                                            background_color: theme.background(),
+                                        })))
+                                        .padding(Padding::new(10.0)),
+                                    button(text(format!("Install {} Selected", self.selected_flatpak.len()))
+                                        .size(16.0))
+                                        .on_press(Message::InstallSelected)
+                                        .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                                            is_primary: true,
+                                            radius: self.border_radius,
+                                            primary_color: theme.primary(),
+                                            text_color: Color::BLACK,
+                                            background_color: theme.background(),
+                                        })))
+                                        .padding(Padding::new(10.0)),
+                                ]
+                                .spacing(8))
                             }
                         } else {
                             Element::from(Space::with_width(Length::Fixed(0.0)))
                         }
                     },
-                ]
-                .spacing(8)
-                .width(Length::Fill)
-                .align_items(alignment::Alignment::Center),
+                ),
             ]
             .spacing(16)
         )
@@ -1635,8 +3906,8 @@ impl BirdNestGUI {
             elevation: 1.5, // Elevated search section
         })));
 
-        // Content section
-        let content_section = if self.flatpak_loading {
+        // Content section; a preloaded list stays up while the tab refreshes it
+        let content_section = if self.flatpak_loading && !self.flatpak_loaded {
             container(
                 text("Loading Flatpak applications...")
                     .size(16)
@@ -1647,89 +3918,116 @@ impl BirdNestGUI {
             .center_y()
             .into()
         } else if !self.flatpak_search_results.is_empty() {
-            // Show search results
+            // Show search results, headed by remote when they come from several
+            let default_remote = self.flatpak_default_remote.as_deref();
+            let grouped = self
+                .flatpak_search_results
+                .iter()
+                .map(|r| flatpak::preferred_remote(&r.remotes, default_remote))
+                .collect::<HashSet<_>>()
+                .len()
+                > 1;
             Element::from(
                 container(
                     scrollable(
                         column(
                             self.flatpak_search_results
                                 .iter()
-                                .map(|fpkg| {
-                                    let is_selected = self.selected_flatpak.contains(&fpkg.application);
-                                    let pkg_name = fpkg.application.clone();
-                                    button(
-                                        container(
-                                            row![
-                                                checkbox("", is_selected)
-                                                    .style(iced::theme::Checkbox::Custom(Box::new(YellowCheckboxStyle {
-                                                        radius: 4.0,
-                                                        primary_color: theme.primary(),
-                                                    }))),
-                                                column![
-                                                    text(&fpkg.name)
-                                                        .size(if is_selected { 26.0 } else { 24.0 })
-                                                        .style(iced::theme::Text::Color(if is_selected { Color::BLACK } else { Color::WHITE }))
-                                                        .width(Length::Fill),
-                                                    {
-                                                        if !fpkg.description.is_empty() {
-                                                            let display_text = if fpkg.description.len() > 120 {
-                                                                format!("{}...", &fpkg.description[..120])
+                                .enumerate()
+                                .flat_map(|(index, fpkg)| {
+                                    let header = self.view_flatpak_remote_header(index, grouped);
+                                    let card: Element<Message> = {
+                                        let is_selected = self.selected_flatpak.contains(&fpkg.application);
+                                        let pkg_name = fpkg.application.clone();
+                                        let app = self.flatpak_catalog.for_app(&fpkg.application);
+                                        button(
+                                            container(
+                                                row![
+                                                    checkbox("", is_selected)
+                                                        .style(iced::theme::Checkbox::Custom(Box::new(YellowCheckboxStyle {
+                                                            radius: 4.0,
+                                                            primary_color: theme.primary(),
+                                                        }))),
+                                                    app_icon(app, !self.flatpak_catalog.is_empty(), !self.low_memory),
+                                                    column![
+                                                        text(&fpkg.name)
+                                                            .size(if is_selected { 26.0 } else { 24.0 })
+                                                            .style(iced::theme::Text::Color(if is_selected { Color::BLACK } else { Color::WHITE }))
+                                                            .width(Length::Fill),
+                                                        {
+                                                            if !fpkg.description.is_empty() {
+                                                                let display_text = if fpkg.description.len() > 120 {
+                                                                    format!("{}...", &fpkg.description[..120])
+                                                                } else {
+                                                                    fpkg.description.clone()
+                                                                };
+                                                                Element::from(text(&display_text)
+                                                                    .size(if is_selected { 14.0 } else { 12.0 })
+                                                                    .style(iced::theme::Text::Color(if is_selected { Color::BLACK } else { Color::WHITE }))
+                                                                    .width(Length::Fill))
                                                             } else {
-                                                                fpkg.description.clone()
-                                                            };
-                                                            Element::from(text(&display_text)
-                                                                .size(if is_selected { 14.0 } else { 12.0 })
-                                                                .style(iced::theme::Text::Color(if is_selected { Color::BLACK } else { Color::WHITE }))
-                                                                .width(Length::Fill))
-                                                        } else {
-                                                            Element::from(Space::with_height(Length::Shrink))
-                                                        }
-                                                    },
-                                                    {
-                                                        let mut info_row = row![].spacing(12).width(Length::Fill);
-                                                        if !fpkg.version.is_empty() {
-                                                            info_row = info_row.push(
-                                                                Element::from(text(format!("Version: {}", fpkg.version))
-                                                                    .size(11)
-                                                                    .style(iced::theme::Text::Color(if is_selected { Color::BLACK } else { Color::WHITE })))
-                                                            );
-                                                        }
-                                                        if !fpkg.application.is_empty() {
-                                                            info_row = info_row.push(
-                                                                Element::from(text(format!("ID: {}", fpkg.application))
-                                                                    .size(if is_selected { 13.0 } else { 11.0 })
-                                                                    .style(iced::theme::Text::Color(if is_selected { Color::BLACK } else { Color::WHITE })))
-                                                            );
-                                                        }
-                                                        Element::from(info_row)
-                                                    },
+                                                                Element::from(Space::with_height(Length::Shrink))
+                                                            }
+                                                        },
+                                                        {
+                                                            let mut info_row = row![].spacing(12).width(Length::Fill);
+                                                            if !fpkg.version.is_empty() {
+                                                                info_row = info_row.push(
+                                                                    Element::from(text(format!("Version: {}", fpkg.version))
+                                                                        .size(11)
+                                                                        .style(iced::theme::Text::Color(if is_selected { Color::BLACK } else { Color::WHITE })))
+                                                                );
+                                                            }
+                                                            if !fpkg.application.is_empty() {
+                                                                info_row = info_row.push(
+                                                                    Element::from(text(format!("ID: {}", fpkg.application))
+                                                                        .size(if is_selected { 13.0 } else { 11.0 })
+                                                                        .style(iced::theme::Text::Color(if is_selected { Color::BLACK } else { Color::WHITE })))
+                                                                );
+                                                            }
+                                                            if let Some(app) = app {
+                                                                info_row = info_row.push(view_categories(app, is_selected));
+                                                            }
+                                                            if fpkg.remotes.len() > 1 {
+                                                                info_row = info_row.push(self.view_flatpak_remote_picker(fpkg, is_selected));
+                                                            } else if let Some(remote) = fpkg.remotes.first() {
+                                                                info_row = info_row.push(
+                                                                    Element::from(text(format!("Remote: {}", remote))
+                                                                        .size(11)
+                                                                        .style(iced::theme::Text::Color(if is_selected { Color::BLACK } else { Color::WHITE })))
+                                                                );
+                                                            }
+                                                            Element::from(info_row)
+                                                        },
+                                                    ]
+                                                    .spacing(4)
+                                                    .width(Length::Fill),
                                                 ]
-                                                .spacing(4)
-                                                .width(Length::Fill),
-                                            ]
-                                            .spacing(12)
-                                            .align_items(alignment::Alignment::Center)
-                                            .padding(Padding::new(12.0))
+                                                .spacing(12)
+                                                .align_items(alignment::Alignment::Center)
+                                                .padding(Padding::new(12.0))
+                                            )
+                                            .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+                                                radius: self.border_radius,
+                                                background: if is_selected {
+                                                    Some(theme.primary().into())
+                                                } else {
+                                                    Some(theme.card_background())
+                                                },
+                                                elevation: 1.0, // Subtle bubble effect for each package card
+                                            })))
                                         )
-                                        .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+                                        .on_press(Message::TogglePackage(pkg_name.clone()))
+                                        .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                                            is_primary: false,
                                             radius: self.border_radius,
-                                            background: if is_selected {
-                                                Some(theme.primary().into())
-                                            } else {
-                                                Some(theme.card_background())
-                                            },
-                                            elevation: 1.0, // Subtle bubble effect for each package card
+                                            primary_color: theme.primary(),
+                                            text_color: Color::WHITE,
+                                            background_color: Color::TRANSPARENT,
                                         })))
-                                    )
-                                    .on_press(Message::TogglePackage(pkg_name.clone()))
-                                    .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
-                                        is_primary: false,
-                                        radius: self.border_radius,
-                                        primary_color: theme.primary(),
-                                        text_color: Color::WHITE,
-                                        background_color: Color::TRANSPARENT,
-                                    })))
-                                    .into()
+                                        .into()
+                                    };
+                                    header.into_iter().chain(std::iter::once(self.with_row_menu(card, RowTarget::flatpak(&fpkg.application))))
                                 })
                                 .collect::<Vec<_>>(),
                         )
@@ -1745,12 +4043,37 @@ impl BirdNestGUI {
                 .height(Length::Fill)
             )
         } else if !self.flatpak_apps.is_empty() {
+                let filtered_apps = filter_flatpak_apps(&self.flatpak_apps, &self.flatpak_installed_filter);
+                let limit = self.result_limit.unwrap_or(usize::MAX);
+                let mut count_text = if self.flatpak_installed_filter.is_empty() {
+                    format!("{} Flatpak applications installed", self.flatpak_apps.len())
+                } else {
+                    format!("{} of {} Flatpak applications installed", filtered_apps.len(), self.flatpak_apps.len())
+                };
+                if filtered_apps.len() > limit {
+                    count_text.push_str(&format!(", first {} shown", limit));
+                }
                 Element::from(
                     column![
                         container(
-                            text(format!("{} Flatpak applications installed", self.flatpak_apps.len()))
-                                .size(16)
-                                .style(iced::theme::Text::Color(theme.text()))
+                            row![
+                                text(count_text)
+                                    .size(16)
+                                    .style(iced::theme::Text::Color(theme.text()))
+                                    .width(Length::Fill),
+                                text_input("Filter installed apps...", &self.flatpak_installed_filter)
+                                    .on_input(Message::FlatpakInstalledFilterChanged)
+                                    .padding(Padding::new(10.0))
+                                    .width(Length::Fixed(320.0))
+                                    .style(iced::theme::TextInput::Custom(Box::new(YellowTextInputStyle {
+                                        radius: self.border_radius,
+                                        primary_color: theme.primary(),
+                                        background_color: theme.background(),
+                                        text_color: Color::BLACK,
+                                    }))),
+                            ]
+                            .spacing(12)
+                            .align_items(alignment::Alignment::Center)
                         )
                         .padding(Padding::new(16.0))
                         .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
@@ -1761,11 +4084,12 @@ impl BirdNestGUI {
                         .width(Length::Fill),
                         scrollable(
                             column(
-                                self.flatpak_apps
-                                    .iter()
+                                filtered_apps
+                                    .into_iter()
+                                    .take(limit)
                                     .map(|app| {
                                         let app_id = app.application.clone();
-                                        container(
+                                        let card = container(
                                             row![
                                                 text(&app.name)
                                                     .size(16)
@@ -1801,8 +4125,8 @@ impl BirdNestGUI {
                                             background: Some(theme.surface()),
                                             elevation: 1.0, // Subtle bubble effect for package cards
                                         })))
-                                        .width(Length::Fill)
-                                        .into()
+                                        .width(Length::Fill);
+                                        self.with_row_menu(card.into(), RowTarget::flatpak(&app.application))
                                     })
                                     .collect::<Vec<Element<Message>>>(),
                             )
@@ -1839,88 +4163,105 @@ impl BirdNestGUI {
         .into()
     }
 
+    // What the preload found installed in the selected source's container;
+    // nothing for the default source or before the preload runs
+    fn view_pikman_container(&self) -> Element<Message> {
+        let theme = self.theme;
+        let Some((distro, loaded)) = self.pikman_filter.as_ref().and_then(|distro| self.pikman_installed.get(distro).map(|loaded| (distro, loaded))) else {
+            return Space::with_height(Length::Fixed(0.0)).into();
+        };
+        let summary = match loaded {
+            None => "Starting the container...".to_string(),
+            Some(Err(e)) => format!("Couldn't list the container's packages: {}", e),
+            Some(Ok(packages)) if packages.is_empty() => "Nothing is installed in this container yet".to_string(),
+            Some(Ok(packages)) => packages.join(", "),
+        };
+        column![
+            container(
+                column![
+                    text(format!("Installed in the {} container", match distro.as_str() {
+                        "aur" => "AUR",
+                        "fedora" => "Fedora",
+                        "alpine" => "Alpine",
+                        other => other,
+                    }))
+                        .size(16)
+                        .style(iced::theme::Text::Color(theme.text())),
+                    text(summary)
+                        .size(13)
+                        .style(iced::theme::Text::Color(theme.secondary_text())),
+                ]
+                .spacing(10)
+            )
+            .padding(Padding::new(16.0))
+            .width(Length::Fill)
+            .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+                radius: self.border_radius,
+                background: Some(theme.background()),
+                elevation: 1.5,
+            }))),
+            Space::with_height(Length::Fixed(15.0)),
+        ]
+        .into()
+    }
+
     fn view_pikman(&self) -> Element<Message> {
         let theme = self.theme;
         
-        // Responsive layout - stack vertically on small screens
-        let _is_small_screen = false; // Could be made dynamic based on window size
-        
         let search_section = container(
             column![
-                // Search bar with Search button
-                row![
-                    text_input("Search packages...", &self.pikman_search_query)
-                        .on_input(Message::PikmanSearchQueryChanged)
-                        .on_submit(Message::PikmanSearch)
-                        .padding(Padding::new(12.0))
-                        .width(Length::Fill)
-                        .style(iced::theme::TextInput::Custom(Box::new(YellowTextInputStyle {
-                            radius: self.border_radius,
-                            primary_color: theme.primary(),
-                            background_color: theme.background(),
-                            text_color: Color::BLACK,
-                        }))),
-                    button("Search")
-                        .on_press(Message::PikmanSearch)
-                        .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
-                            is_primary: true,
-                            radius: self.border_radius,
-                            primary_color: theme.primary(),
-                            text_color: Color::BLACK,
-                            background_color: theme.background(),
-                        })))
-                        .padding(Padding::new(16.0)),
-                ]
-                .spacing(10)
-                .width(Length::Fill),
-                Space::with_height(Length::Fixed(10.0)),
                 // Filter buttons and Install button row
-                row![
-                    text("Source:")
-                        .size(14)
-                        .style(iced::theme::Text::Color(theme.text())),
-                    Space::with_width(Length::Fixed(10.0)),
-                    button(if self.pikman_filter.is_none() { "✓ Default" } else { "Default" })
-                        .on_press(Message::PikmanFilterChanged(None))
-                        .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
-                            is_primary: self.pikman_filter.is_none(),
-                            radius: self.border_radius,
-                            primary_color: theme.primary(),
-                            text_color: if self.pikman_filter.is_none() { Color::BLACK } else { Color::WHITE },
-                            background_color: theme.background(),
-                        })))
-                        .padding(Padding::new(14.0)),
-                    button(if self.pikman_filter.as_ref().map(|s| s == "aur").unwrap_or(false) { "✓ AUR" } else { "AUR" })
-                        .on_press(Message::PikmanFilterChanged(Some("aur".to_string())))
-                        .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
-                            is_primary: self.pikman_filter.as_ref().map(|s| s == "aur").unwrap_or(false),
-                            radius: self.border_radius,
-                            primary_color: theme.primary(),
-                            text_color: if self.pikman_filter.as_ref().map(|s| s == "aur").unwrap_or(false) { Color::BLACK } else { Color::WHITE },
-                            background_color: theme.background(),
-                        })))
-                        .padding(Padding::new(14.0)),
-                    button(if self.pikman_filter.as_ref().map(|s| s == "fedora").unwrap_or(false) { "✓ Fedora" } else { "Fedora" })
-                        .on_press(Message::PikmanFilterChanged(Some("fedora".to_string())))
-                        .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
-                            is_primary: self.pikman_filter.as_ref().map(|s| s == "fedora").unwrap_or(false),
-                            radius: self.border_radius,
-                            primary_color: theme.primary(),
-                            text_color: if self.pikman_filter.as_ref().map(|s| s == "fedora").unwrap_or(false) { Color::BLACK } else { Color::WHITE },
-                            background_color: theme.background(),
-                        })))
-                        .padding(Padding::new(14.0)),
-                    button(if self.pikman_filter.as_ref().map(|s| s == "alpine").unwrap_or(false) { "✓ Alpine" } else { "Alpine" })
-                        .on_press(Message::PikmanFilterChanged(Some("alpine".to_string())))
-                        .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
-                            is_primary: self.pikman_filter.as_ref().map(|s| s == "alpine").unwrap_or(false),
-                            radius: self.border_radius,
-                            primary_color: theme.primary(),
-                            text_color: if self.pikman_filter.as_ref().map(|s| s == "alpine").unwrap_or(false) { Color::BLACK } else { Color::WHITE },
-                            background_color: theme.background(),
-                        })))
-                        .padding(Padding::new(14.0)),
-                    Space::with_width(Length::Fill),
+                adaptive::action_bar(
+                    self.is_narrow(),
+                    row![
+                        text("Source:")
+                            .size(14)
+                            .style(iced::theme::Text::Color(theme.text())),
+                        Space::with_width(Length::Fixed(10.0)),
+                        button(if self.pikman_filter.is_none() { "✓ Default" } else { "Default" })
+                            .on_press(Message::PikmanFilterChanged(None))
+                            .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                                is_primary: self.pikman_filter.is_none(),
+                                radius: self.border_radius,
+                                primary_color: theme.primary(),
+                                text_color: if self.pikman_filter.is_none() { Color::BLACK } else { Color::WHITE },
+                                background_color: theme.background(),
+                            })))
+                            .padding(Padding::new(14.0)),
+                        button(if self.pikman_filter.as_ref().map(|s| s == "aur").unwrap_or(false) { "✓ AUR" } else { "AUR" })
+                            .on_press(Message::PikmanFilterChanged(Some("aur".to_string())))
+                            .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                                is_primary: self.pikman_filter.as_ref().map(|s| s == "aur").unwrap_or(false),
+                                radius: self.border_radius,
+                                primary_color: theme.primary(),
+                                text_color: if self.pikman_filter.as_ref().map(|s| s == "aur").unwrap_or(false) { Color::BLACK } else { Color::WHITE },
+                                background_color: theme.background(),
+                            })))
+                            .padding(Padding::new(14.0)),
+                        button(if self.pikman_filter.as_ref().map(|s| s == "fedora").unwrap_or(false) { "✓ Fedora" } else { "Fedora" })
+                            .on_press(Message::PikmanFilterChanged(Some("fedora".to_string())))
+                            .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                                is_primary: self.pikman_filter.as_ref().map(|s| s == "fedora").unwrap_or(false),
+                                radius: self.border_radius,
+                                primary_color: theme.primary(),
+                                text_color: if self.pikman_filter.as_ref().map(|s| s == "fedora").unwrap_or(false) { Color::BLACK } else { Color::WHITE },
+                                background_color: theme.background(),
+                            })))
+                            .padding(Padding::new(14.0)),
+                        button(if self.pikman_filter.as_ref().map(|s| s == "alpine").unwrap_or(false) { "✓ Alpine" } else { "Alpine" })
+                            .on_press(Message::PikmanFilterChanged(Some("alpine".to_string())))
+                            .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                                is_primary: self.pikman_filter.as_ref().map(|s| s == "alpine").unwrap_or(false),
+                                radius: self.border_radius,
+                                primary_color: theme.primary(),
+                                text_color: if self.pikman_filter.as_ref().map(|s| s == "alpine").unwrap_or(false) { Color::BLACK } else { Color::WHITE },
+                                background_color: theme.background(),
+                            })))
+                            .padding(Padding::new(14.0)),
+                    ]
+                    .spacing(8)
+                    .align_items(alignment::Alignment::Center)
+                    .into(),
                     {
                         if !self.pikman_search_results.is_empty() {
                             if self.selected_pikman.is_empty() {
@@ -1950,10 +4291,7 @@ impl BirdNestGUI {
                             Element::from(Space::with_width(Length::Fixed(0.0)))
                         }
                     },
-                ]
-                .spacing(8)
-                .width(Length::Fill)
-                .align_items(alignment::Alignment::Center),
+                ),
             ]
             .spacing(16)
         )
@@ -1985,7 +4323,7 @@ impl BirdNestGUI {
                                 self.pikman_search_results
                                     .iter()
                                 .map(|pkg| {
-                                    let is_selected = self.selected_pikman.contains(&pkg.name);
+                                    let is_selected = self.selected_pikman.contains_key(&pkg.name);
                                     button(
                                             container(
                                                 row![
@@ -2112,8 +4450,8 @@ impl BirdNestGUI {
                                                 background_color: theme.background(),
                                             })))
                                             .padding(Padding::new(14.0)),
-                                        button("Enter Container")
-                                            .on_press(Message::PikmanEnter(String::new()))
+                                        button("Environments")
+                                            .on_press(Message::ShowEnvironments)
                                             .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
                                                 is_primary: false,
                                                 radius: self.border_radius,
@@ -2124,6 +4462,7 @@ impl BirdNestGUI {
                                             .padding(Padding::new(14.0)),
                                     ]
                                     .spacing(10),
+                                    pikman_terminal::view(&self.pikman_terminal, theme, self.border_radius),
                                 ]
                                 .spacing(10)
                             )
@@ -2134,6 +4473,9 @@ impl BirdNestGUI {
                                             elevation: 1.5, // Elevated section for package details
                             }))),
                             Space::with_height(Length::Fixed(15.0)),
+                            self.view_pikman_container(),
+                            pikman_exports::view(&self.pikman_exports, theme, self.border_radius),
+                            Space::with_height(Length::Fixed(15.0)),
                             // Package management
                             container(
                                 column![
@@ -2220,89 +4562,165 @@ impl BirdNestGUI {
     // System update functionality removed - handled by separate app
 
     // Dialog view methods removed - dialogs are now separate windows
+}
+
+// Async functions for package operations
+// None when the backend can't list its packages; searches then go through it per query
+// The periodic refresh updates Flatpak's appstream metadata, which needs no
+// password, then the Updates tab re-reads what is upgradable. False when the
+// schedule ruled it out.
+async fn background_refresh(schedule: RefreshSchedule) -> Result<bool, String> {
+    let decision = tokio::task::spawn_blocking(move || schedule.check_now())
+        .await
+        .map_err(|e| format!("Task error: {}", e))?;
+    if decision != Decision::Allowed {
+        tracing::debug!("Skipping background refresh: {}", decision);
+        return Ok(false);
+    }
+    let (command, _) = CommandBuilder::new("flatpak")
+        .args(&["update", "--appstream", "--noninteractive"])
+        .build()
+        .map_err(|e| e.to_string())?;
+    let output = tokio::process::Command::from(command)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run flatpak: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(true)
+}
+
+// None when the refresh schedule holds background checks off right now
+async fn check_updates(backend: Arc<dyn PackageBackend>, schedule: RefreshSchedule) -> Result<Option<UpdateCount>, String> {
+    tokio::task::spawn_blocking(move || {
+        let decision = schedule.check_now();
+        if decision != Decision::Allowed {
+            tracing::debug!("Skipping update check: {}", decision);
+            return Ok(None);
+        }
+        update_check::check(backend.as_ref()).map(Some).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task error: {}", e))?
+}
+
+// Runtimes queued on the Flatpak tab download one by one through the
+// transaction queue once the schedule's window opens; those that fail stay
+// queued for the next tick
+async fn run_predownloads(schedule: RefreshSchedule) -> Result<usize, String> {
+    let pending = tokio::task::spawn_blocking(move || {
+        let pending = predownload::load();
+        if pending.is_empty() || predownload::check_now(&schedule) != Decision::Allowed {
+            return Vec::new();
+        }
+        pending
+    })
+    .await
+    .map_err(|e| format!("Task error: {}", e))?;
+
+    let mut downloaded = 0;
+    for runtime in pending {
+        let label = format!("Download runtime {}", runtime.runtime);
+        let result = transaction_queue::serialized(label, async move {
+            tokio::task::spawn_blocking(move || predownload::download(&runtime).and_then(|_| predownload::finish(&runtime.runtime)))
+                .await
+                .map_err(|e| format!("Task error: {}", e))?
+                .map(|_| String::new())
+                .map_err(|e| e.to_string())
+        })
+        .await;
+        match result {
+            Ok(_) => downloaded += 1,
+            Err(e) => tracing::debug!("run_predownloads: {}", e),
+        }
+    }
+    Ok(downloaded)
+}
+
+const APP_ICON_SIZE: f32 = 48.0;
 
-    #[allow(dead_code)]
-    fn view_output(&self) -> Element<Message> {
-        let theme = self.theme;
-        let output_text = if !self.output_log.is_empty() {
-            self.output_log.join("\n")
-        } else if !self.error_log.is_empty() {
-            format!("Errors:\n{}", self.error_log.join("\n"))
-        } else {
-            String::new()
-        };
+// The app's cached AppStream icon; rows without one keep the space when
+// others may have one, so names line up. Without `icons` (low-memory mode)
+// no row has one.
+fn app_icon<'a>(app: Option<&Component>, reserve: bool, icons: bool) -> Element<'a, Message> {
+    if !icons {
+        return Space::with_width(Length::Fixed(0.0)).into();
+    }
+    match app.and_then(|app| app.icon.as_ref()).filter(|icon| icon.exists()) {
+        Some(icon) => image(image::Handle::from_path(icon))
+            .width(Length::Fixed(APP_ICON_SIZE))
+            .height(Length::Fixed(APP_ICON_SIZE))
+            .into(),
+        None if reserve => Space::with_width(Length::Fixed(APP_ICON_SIZE)).into(),
+        None => Space::with_width(Length::Fixed(0.0)).into(),
+    }
+}
 
-        container(
-            scrollable(
-                text(output_text)
-                    .size(12)
-                    .style(iced::theme::Text::Color(if !self.error_log.is_empty() {
-                        theme.danger()
-                    } else {
-                        theme.secondary_text()
-                    })),
-            )
-            .style(iced::theme::Scrollable::Custom(Box::new(CustomScrollableStyle {
-                background_color: theme.background(),
-                border_radius: self.border_radius,
-            })))
-            .height(Length::Fixed(150.0)),
-        )
-        .width(Length::Fill)
-        .padding(Padding::new(12.0))
-        .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
-            radius: self.border_radius,
-            background: Some(theme.surface()),
-            elevation: 1.0,
-        })))
-        .into()
+fn view_categories<'a>(app: &Component, is_selected: bool) -> Element<'a, Message> {
+    if app.categories.is_empty() {
+        return Space::with_width(Length::Fixed(0.0)).into();
     }
+    text(app.categories.join(", "))
+        .size(if is_selected { 13.0 } else { 11.0 })
+        .style(iced::theme::Text::Color(if is_selected { Color::BLACK } else { Color::WHITE }))
+        .into()
 }
 
-// Async functions for package operations
-async fn search_packages(query: String) -> Vec<PackageInfo> {
+async fn load_app_catalogs() -> (Arc<Catalog>, Arc<Catalog>) {
+    tokio::task::spawn_blocking(|| (Arc::new(Catalog::load_system()), Arc::new(Catalog::load_flatpak())))
+        .await
+        .unwrap_or_default()
+}
+
+async fn build_search_index(backend: Arc<dyn PackageBackend>) -> Option<Arc<SearchIndex>> {
+    tokio::task::spawn_blocking(move || match SearchIndex::build(backend.as_ref()) {
+        Ok(index) => Some(Arc::new(index)),
+        Err(e) => {
+            tracing::debug!("build_search_index: {}, searching per query instead", e);
+            None
+        }
+    })
+    .await
+    .ok()
+    .flatten()
+}
+
+async fn search_packages(backend: Arc<dyn PackageBackend>, query: String) -> Vec<PackageInfo> {
     tokio::task::spawn_blocking(move || {
-        match PackageManager::new() {
-            Ok(_pm) => {
-                use crate::utils::run_command;
-                // Use apt-cache search which searches both names and descriptions
-                // but returns results in a format we can parse
-                let mut packages = match run_command("apt-cache", &["search", &query], false) {
-                    Ok(output) => parse_apt_cache_search_output(&output),
-                    Err(_) => vec![],
-                };
-                
-                // Sort results to prioritize packages with query in name
-                let query_lower = query.to_lowercase();
-                packages.sort_by(|a, b| {
-                    let a_name_lower = a.name.to_lowercase();
-                    let b_name_lower = b.name.to_lowercase();
-                    
-                    // Check if query appears in name
-                    let a_has_in_name = a_name_lower.contains(&query_lower);
-                    let b_has_in_name = b_name_lower.contains(&query_lower);
-                    
-                    // Check if query is at the start of name
-                    let a_starts_with = a_name_lower.starts_with(&query_lower);
-                    let b_starts_with = b_name_lower.starts_with(&query_lower);
-                    
-                    match (a_starts_with, b_starts_with) {
+        let mut packages: Vec<PackageInfo> = match backend.search(&query) {
+            Ok(results) => results.into_iter().map(PackageInfo::from).collect(),
+            Err(_) => vec![],
+        };
+        
+        // Sort results to prioritize packages with query in name
+        let query_lower = query.to_lowercase();
+        packages.sort_by(|a, b| {
+            let a_name_lower = a.name.to_lowercase();
+            let b_name_lower = b.name.to_lowercase();
+            
+            // Check if query appears in name
+            let a_has_in_name = a_name_lower.contains(&query_lower);
+            let b_has_in_name = b_name_lower.contains(&query_lower);
+            
+            // Check if query is at the start of name
+            let a_starts_with = a_name_lower.starts_with(&query_lower);
+            let b_starts_with = b_name_lower.starts_with(&query_lower);
+            
+            match (a_starts_with, b_starts_with) {
+                (true, false) => std::cmp::Ordering::Less,
+                (false, true) => std::cmp::Ordering::Greater,
+                _ => {
+                    match (a_has_in_name, b_has_in_name) {
                         (true, false) => std::cmp::Ordering::Less,
                         (false, true) => std::cmp::Ordering::Greater,
-                        _ => {
-                            match (a_has_in_name, b_has_in_name) {
-                                (true, false) => std::cmp::Ordering::Less,
-                                (false, true) => std::cmp::Ordering::Greater,
-                                _ => a.name.cmp(&b.name), // Alphabetical if both match equally
-                            }
-                        }
+                        _ => a.name.cmp(&b.name), // Alphabetical if both match equally
                     }
-                });
-                
-                packages
+                }
             }
-            Err(_) => vec![],
-        }
+        });
+        
+        packages
     })
     .await
     .unwrap_or_default()
@@ -2340,6 +4758,9 @@ fn parse_search_output(output: &str) -> Vec<PackageInfo> {
                     version,
                     description,
                     size: String::new(),
+                    installed_bytes: None,
+                    arch: None,
+                    provenance: None,
                     source: PackageSource::Default,
                 });
             }
@@ -2357,306 +4778,31 @@ fn parse_search_output(output: &str) -> Vec<PackageInfo> {
     packages
 }
 
-// Parse apt-cache search output format: "package - description"
-fn parse_apt_cache_search_output(output: &str) -> Vec<PackageInfo> {
-    let mut packages = Vec::new();
-    let mut seen = std::collections::HashSet::new();
-    
-    for line in output.lines() {
-        let line = line.trim();
-        if line.is_empty() {
-            continue;
-        }
-        
-        // Format: "package - description" or "package/version - description"
-        if let Some(dash_pos) = line.find(" - ") {
-            let name_part = line[..dash_pos].trim();
-            let description = line[dash_pos + 3..].trim().to_string();
-            
-            // Handle package/version format
-            let (name, version) = if name_part.contains('/') {
-                let parts: Vec<&str> = name_part.splitn(2, '/').collect();
-                (parts[0].to_string(), parts.get(1).unwrap_or(&"").to_string())
-            } else {
-                (name_part.to_string(), String::new())
-            };
-            
-            // Skip duplicates
-            if seen.contains(&name) {
-                continue;
-            }
-            seen.insert(name.clone());
-            
-            packages.push(PackageInfo {
-                name,
-                version,
-                description,
-                size: String::new(),
-                source: PackageSource::Default,
-            });
-        }
-    }
-    
-    packages
-}
-
-async fn load_installed_packages() -> Vec<PackageInfo> {
-    eprintln!("[DEBUG] load_installed_packages() async function called");
-    tokio::task::spawn_blocking(|| {
-        eprintln!("[DEBUG] load_installed_packages: Inside spawn_blocking");
+async fn load_installed_packages(backend: Arc<dyn PackageBackend>) -> Vec<PackageInfo> {
+    tracing::debug!("load_installed_packages() async function called");
+    tokio::task::spawn_blocking(move || {
         let start_time = std::time::Instant::now();
-        
-        // Try to load from cache first
-        eprintln!("[DEBUG] load_installed_packages: Attempting to load from cache...");
-        if let Some(cached) = load_packages_cache() {
-            eprintln!("[DEBUG] load_installed_packages: Loaded {} packages from cache in {:?}", cached.len(), start_time.elapsed());
-            return cached;
-        }
-        
-        eprintln!("[DEBUG] load_installed_packages: Cache miss or invalid, loading from dpkg status file");
-        
-        // Read directly from dpkg status file - much faster than spawning dpkg-query
-        use std::fs;
-        let status_path = std::path::PathBuf::from("/var/lib/dpkg/status");
-        let status_content = match fs::read_to_string(&status_path) {
-            Ok(content) => content,
-            Err(e) => {
-                eprintln!("[DEBUG] load_installed_packages: Failed to read status file: {}, using fallback", e);
-                return load_installed_packages_fallback();
+        // The system backend serves this from the installed-package cache when
+        // it is fresh and falls back to parsing the dpkg status file
+        match backend.list_installed() {
+            Ok(installed) => {
+                tracing::debug!("load_installed_packages: Loaded {} packages in {:?}", installed.len(), start_time.elapsed());
+                profiling::mark("installed package list read");
+                installed.into_iter().map(PackageInfo::from).collect()
             }
-        };
-        
-        eprintln!("[DEBUG] load_installed_packages: Read status file in {:?}, length: {}", start_time.elapsed(), status_content.len());
-        
-        // Parse status file directly - much faster than spawning a process
-        let parse_start = std::time::Instant::now();
-        let mut packages = Vec::new();
-        let mut current_name = String::new();
-        let mut current_version = String::new();
-        let mut is_installed = false;
-        
-        // Optimized parsing: single pass through the file
-        for line in status_content.lines() {
-            if line.starts_with("Package: ") {
-                // Save previous package if it was installed
-                if is_installed && !current_name.is_empty() {
-                    packages.push(PackageInfo {
-                        name: std::mem::take(&mut current_name),
-                        version: std::mem::take(&mut current_version),
-                        description: String::new(),
-                        size: String::new(),
-                        source: PackageSource::Default,
-                    });
-                }
-                current_name = line[9..].trim().to_string();
-                current_version.clear();
-                is_installed = false;
-            } else if line.starts_with("Version: ") {
-                current_version = line[9..].trim().to_string();
-            } else if line.starts_with("Status: ") {
-                // Check if package is installed
-                let status = &line[8..];
-                is_installed = status.contains("install ok installed") || status.contains("install ok config-files");
-            } else if line.is_empty() {
-                // End of package entry - save if installed
-                if is_installed && !current_name.is_empty() {
-                    packages.push(PackageInfo {
-                        name: std::mem::take(&mut current_name),
-                        version: std::mem::take(&mut current_version),
-                        description: String::new(),
-                        size: String::new(),
-                        source: PackageSource::Default,
-                    });
-                }
-                is_installed = false;
+            Err(e) => {
+                tracing::error!("Error loading installed packages: {}", e);
+                vec![]
             }
         }
-        
-        // Don't forget the last package if file doesn't end with newline
-        if is_installed && !current_name.is_empty() {
-            packages.push(PackageInfo {
-                name: current_name,
-                version: current_version,
-                description: String::new(),
-                size: String::new(),
-                source: PackageSource::Default,
-            });
-        }
-        
-        eprintln!("[DEBUG] load_installed_packages: Parsed {} packages in {:?}, total: {:?}", 
-                 packages.len(), parse_start.elapsed(), start_time.elapsed());
-        
-        // Save to cache for next time
-        save_packages_cache(&packages);
-        
-        packages
     })
     .await
     .unwrap_or_default()
 }
 
-// Cache functions
-fn get_cache_path() -> Option<std::path::PathBuf> {
-    let home = std::env::var("HOME").ok()?;
-    Some(std::path::PathBuf::from(home).join(".config").join("birdnest").join("installed_packages.cache"))
-}
-
-fn get_dpkg_status_mtime() -> Option<std::time::SystemTime> {
-    use std::fs;
-    let status_path = std::path::PathBuf::from("/var/lib/dpkg/status");
-    fs::metadata(&status_path).ok()?.modified().ok()
-}
-
-fn load_packages_cache() -> Option<Vec<PackageInfo>> {
-    let cache_path = get_cache_path()?;
-    let status_mtime = get_dpkg_status_mtime()?;
-    
-    use std::fs;
-    let cache_metadata = fs::metadata(&cache_path).ok()?;
-    let cache_mtime = cache_metadata.modified().ok()?;
-    
-    // Check if cache is newer than dpkg status file
-    if cache_mtime < status_mtime {
-        eprintln!("[DEBUG] load_packages_cache: Cache is older than dpkg status, invalidating");
-        let _ = fs::remove_file(&cache_path);
-        return None;
-    }
-    
-    // Try to load cache
-    let cache_data = fs::read(&cache_path).ok()?;
-    
-    // Use a simple binary format: first 8 bytes = count, then name\0version\0 pairs
-    if cache_data.len() < 8 {
-        return None;
-    }
-    
-    let count = u64::from_le_bytes([
-        cache_data[0], cache_data[1], cache_data[2], cache_data[3],
-        cache_data[4], cache_data[5], cache_data[6], cache_data[7],
-    ]) as usize;
-    
-    let mut packages = Vec::with_capacity(count);
-    let mut pos = 8;
-    
-    for _ in 0..count {
-        if pos >= cache_data.len() {
-            return None; // Corrupted cache
-        }
-        
-        // Read name (null-terminated)
-        let name_start = pos;
-        while pos < cache_data.len() && cache_data[pos] != 0 {
-            pos += 1;
-        }
-        if pos >= cache_data.len() {
-            return None;
-        }
-        let name = String::from_utf8_lossy(&cache_data[name_start..pos]).to_string();
-        pos += 1; // Skip null terminator
-        
-        // Read version (null-terminated)
-        if pos >= cache_data.len() {
-            return None;
-        }
-        let version_start = pos;
-        while pos < cache_data.len() && cache_data[pos] != 0 {
-            pos += 1;
-        }
-        if pos >= cache_data.len() {
-            return None;
-        }
-        let version = String::from_utf8_lossy(&cache_data[version_start..pos]).to_string();
-        pos += 1; // Skip null terminator
-        
-        packages.push(PackageInfo {
-            name,
-            version,
-            description: String::new(),
-            size: String::new(),
-            source: PackageSource::Default,
-        });
-    }
-    
-    eprintln!("[DEBUG] load_packages_cache: Loaded {} packages from cache", packages.len());
-    Some(packages)
-}
-
-fn save_packages_cache(packages: &[PackageInfo]) {
-    let cache_path = match get_cache_path() {
-        Some(p) => p,
-        None => {
-            eprintln!("[DEBUG] save_packages_cache: Could not get cache path");
-            return;
-        }
-    };
-    
-    use std::fs;
-    // Create directory if it doesn't exist
-    if let Some(parent) = cache_path.parent() {
-        let _ = fs::create_dir_all(parent);
-    }
-    
-    // Use simple binary format for speed: count (8 bytes) + name\0version\0 pairs
-    let mut cache_data = Vec::with_capacity(packages.len() * 50); // Estimate 50 bytes per package
-    
-    // Write count
-    let count = packages.len() as u64;
-    cache_data.extend_from_slice(&count.to_le_bytes());
-    
-    // Write packages
-    for pkg in packages {
-        cache_data.extend_from_slice(pkg.name.as_bytes());
-        cache_data.push(0); // Null terminator
-        cache_data.extend_from_slice(pkg.version.as_bytes());
-        cache_data.push(0); // Null terminator
-    }
-    
-    if let Err(e) = fs::write(&cache_path, cache_data) {
-        eprintln!("[DEBUG] save_packages_cache: Failed to write cache: {}", e);
-    } else {
-        eprintln!("[DEBUG] save_packages_cache: Saved {} packages to cache", packages.len());
-    }
-}
-
 // Function to invalidate cache (call after install/remove operations)
 pub fn invalidate_packages_cache() {
-    if let Some(cache_path) = get_cache_path() {
-        let _ = std::fs::remove_file(&cache_path);
-        eprintln!("[DEBUG] invalidate_packages_cache: Cache invalidated");
-    }
-}
-
-// Fallback method using utils::run_command
-fn load_installed_packages_fallback() -> Vec<PackageInfo> {
-    use crate::utils::run_command;
-    match run_command("dpkg-query", &["-W", "-f=${Package}\t${Version}\n"], false) {
-        Ok(output) => {
-            let mut packages = Vec::with_capacity(2000);
-            for line in output.lines() {
-                if line.trim().is_empty() {
-                    continue;
-                }
-                
-                if let Some((name, version)) = line.split_once('\t') {
-                    let name = name.trim();
-                    if !name.is_empty() {
-                        packages.push(PackageInfo {
-                            name: name.to_string(),
-                            version: version.trim().to_string(),
-                            description: String::new(),
-                            size: String::new(),
-                            source: PackageSource::Default,
-                        });
-                    }
-                }
-            }
-            packages
-        }
-        Err(e) => {
-            eprintln!("Error loading installed packages: {}", e);
-            vec![]
-        }
-    }
+    cache::invalidate_installed();
 }
 
 // check_updates function removed - system updates handled by separate app
@@ -2665,7 +4811,7 @@ async fn _check_updates_removed() -> Vec<String> {
     tokio::task::spawn_blocking(|| {
         match PackageManager::new() {
             Ok(_pm) => {
-                use crate::utils::run_command;
+                use birdnest_core::utils::run_command;
                 match run_command("apt", &["list", "--upgradable"], false) {
                     Ok(output) => {
                         output.lines()
@@ -2689,26 +4835,38 @@ async fn _check_updates_removed() -> Vec<String> {
     .unwrap_or_default()
 }
 
+// Lists a pikman container's installed packages, which starts the container
+// as a side effect so the first search in it is quick
+async fn load_pikman_container(distro: String) -> Result<Vec<String>, String> {
+    tokio::task::spawn_blocking(move || {
+        let flag = format!("--{}", distro);
+        birdnest_core::utils::run_command("pikman", &[&flag, "list", "--installed"], false).map(|output| birdnest_core::manifest::parse_pikman_installed(&output))
+    })
+    .await
+    .map_err(|e| format!("Task error: {}", e))?
+    .map_err(|e| e.to_string())
+}
+
 async fn load_flatpak_apps() -> Result<Vec<FlatpakInfo>, anyhow::Error> {
     #[cfg(debug_assertions)]
-    eprintln!("[FLATPAK DEBUG] load_flatpak_apps() async function called");
+    tracing::debug!("load_flatpak_apps() async function called");
     tokio::task::spawn_blocking(|| {
         #[cfg(debug_assertions)]
-        eprintln!("[FLATPAK DEBUG] load_flatpak_apps: Inside spawn_blocking");
+        tracing::debug!("load_flatpak_apps: Inside spawn_blocking");
         #[cfg(debug_assertions)]
-        eprintln!("[FLATPAK DEBUG] load_flatpak_apps: Creating FlatpakManager...");
+        tracing::debug!("load_flatpak_apps: Creating FlatpakManager...");
         match FlatpakManager::new() {
             Ok(_fm) => {
                 #[cfg(debug_assertions)]
-                eprintln!("[FLATPAK DEBUG] load_flatpak_apps: FlatpakManager created, running 'flatpak list --columns=name,application'...");
-                use crate::utils::run_command;
+                tracing::debug!("load_flatpak_apps: FlatpakManager created, running 'flatpak list --columns=name,application'...");
+                use birdnest_core::utils::run_command;
                 // Use --columns=name,application to get both display name and application ID
                 match run_command("flatpak", &["list", "--columns=name,application"], false) {
                     Ok(output) => {
                         #[cfg(debug_assertions)]
-                        eprintln!("[FLATPAK DEBUG] load_flatpak_apps: 'flatpak list --columns=name,application' succeeded, parsing output...");
+                        tracing::debug!("load_flatpak_apps: 'flatpak list --columns=name,application' succeeded, parsing output...");
                         #[cfg(debug_assertions)]
-                        eprintln!("[FLATPAK DEBUG] load_flatpak_apps: Output (first 200 chars): {}", output.chars().take(200).collect::<String>());
+                        tracing::debug!("load_flatpak_apps: Output (first 200 chars): {}", output.chars().take(200).collect::<String>());
                         // Parse tab-separated output: name<TAB>application
                         let apps: Vec<FlatpakInfo> = output.lines()
                             .filter_map(|line| {
@@ -2719,16 +4877,17 @@ async fn load_flatpak_apps() -> Result<Vec<FlatpakInfo>, anyhow::Error> {
                                         let name = parts[0].trim().to_string();
                                         let application = parts[1].trim().to_string();
                                         #[cfg(debug_assertions)]
-                                        eprintln!("[FLATPAK DEBUG] load_flatpak_apps: Found app - name: '{}', ID: '{}'", name, application);
+                                        tracing::debug!("load_flatpak_apps: Found app - name: '{}', ID: '{}'", name, application);
                                         Some(FlatpakInfo {
                                             name,
                                             description: String::new(),
                                             version: String::new(),
                                             application,
+                                            remotes: Vec::new(),
                                         })
                                     } else {
                                         #[cfg(debug_assertions)]
-                                        eprintln!("[FLATPAK DEBUG] load_flatpak_apps: WARNING - Invalid line format: '{}'", trimmed);
+                                        tracing::debug!("load_flatpak_apps: WARNING - Invalid line format: '{}'", trimmed);
                                         None
                                     }
                                 } else {
@@ -2737,19 +4896,19 @@ async fn load_flatpak_apps() -> Result<Vec<FlatpakInfo>, anyhow::Error> {
                             })
                             .collect();
                         #[cfg(debug_assertions)]
-                        eprintln!("[FLATPAK DEBUG] load_flatpak_apps: Parsed {} Flatpak apps", apps.len());
+                        tracing::debug!("load_flatpak_apps: Parsed {} Flatpak apps", apps.len());
                         Ok(apps)
                     }
                     Err(e) => {
                         #[cfg(debug_assertions)]
-                        eprintln!("[FLATPAK DEBUG] load_flatpak_apps: ERROR - 'flatpak list' failed: {}", e);
+                        tracing::warn!("load_flatpak_apps: 'flatpak list' failed: {}", e);
                         Err(e)
                     }
                 }
             }
             Err(e) => {
                 #[cfg(debug_assertions)]
-                eprintln!("[FLATPAK DEBUG] load_flatpak_apps: ERROR - Failed to create FlatpakManager: {}", e);
+                tracing::warn!("load_flatpak_apps: Failed to create FlatpakManager: {}", e);
                 Err(e)
             }
         }
@@ -2757,7 +4916,7 @@ async fn load_flatpak_apps() -> Result<Vec<FlatpakInfo>, anyhow::Error> {
     .await
     .unwrap_or_else(|_| {
         #[cfg(debug_assertions)]
-        eprintln!("[FLATPAK DEBUG] load_flatpak_apps: ERROR - Task failed");
+        tracing::warn!("load_flatpak_apps: Task failed");
         Err(anyhow::anyhow!("Failed to load flatpak apps"))
     })
 }
@@ -2766,25 +4925,8 @@ async fn load_flatpak_apps() -> Result<Vec<FlatpakInfo>, anyhow::Error> {
 async fn install_package(package: String) -> Result<String, anyhow::Error> {
     use tokio::process::Command as TokioCommand;
     
-    // Use pkexec for GUI privilege escalation (like Rustora)
-    let mut cmd = TokioCommand::new("pkexec");
-    cmd.arg("apt");
-    cmd.arg("install");
-    cmd.arg("-y");
-    cmd.arg(&package);
-    
-    // Ensure DISPLAY is set for GUI password dialog
-    if let Ok(display) = std::env::var("DISPLAY") {
-        cmd.env("DISPLAY", display);
-    }
-    if let Ok(xauth) = std::env::var("XAUTHORITY") {
-        cmd.env("XAUTHORITY", xauth);
-    }
-    if let Ok(wayland) = std::env::var("WAYLAND_DISPLAY") {
-        cmd.env("WAYLAND_DISPLAY", wayland);
-    }
-    
-    let output = cmd
+    let (std_cmd, _) = CommandBuilder::new("apt").args(&["install", "-y", &package]).elevated(true).noninteractive().build()?;
+    let output = TokioCommand::from(std_cmd)
         .output()
         .await
         .map_err(|e| anyhow::anyhow!("Failed to execute installation: {}. Make sure polkit is installed.", e))?;
@@ -2805,25 +4947,8 @@ async fn install_package(package: String) -> Result<String, anyhow::Error> {
 async fn remove_package(package: String) -> Result<String, anyhow::Error> {
     use tokio::process::Command as TokioCommand;
     
-    // Use pkexec for GUI privilege escalation (like Rustora)
-    let mut cmd = TokioCommand::new("pkexec");
-    cmd.arg("apt");
-    cmd.arg("remove");
-    cmd.arg("-y");
-    cmd.arg(&package);
-    
-    // Ensure DISPLAY is set for GUI password dialog
-    if let Ok(display) = std::env::var("DISPLAY") {
-        cmd.env("DISPLAY", display);
-    }
-    if let Ok(xauth) = std::env::var("XAUTHORITY") {
-        cmd.env("XAUTHORITY", xauth);
-    }
-    if let Ok(wayland) = std::env::var("WAYLAND_DISPLAY") {
-        cmd.env("WAYLAND_DISPLAY", wayland);
-    }
-    
-    let output = cmd
+    let (std_cmd, _) = CommandBuilder::new("apt").args(&["remove", "-y", &package]).elevated(true).noninteractive().build()?;
+    let output = TokioCommand::from(std_cmd)
         .output()
         .await
         .map_err(|e| anyhow::anyhow!("Failed to execute removal: {}. Make sure polkit is installed.", e))?;
@@ -2845,23 +4970,8 @@ async fn remove_package(package: String) -> Result<String, anyhow::Error> {
 async fn _update_lists_removed() -> Result<String, anyhow::Error> {
     use tokio::process::Command as TokioCommand;
     
-    // Use pkexec for GUI privilege escalation (like Rustora)
-    let mut cmd = TokioCommand::new("pkexec");
-    cmd.arg("apt");
-    cmd.arg("update");
-    
-    // Ensure DISPLAY is set for GUI password dialog
-    if let Ok(display) = std::env::var("DISPLAY") {
-        cmd.env("DISPLAY", display);
-    }
-    if let Ok(xauth) = std::env::var("XAUTHORITY") {
-        cmd.env("XAUTHORITY", xauth);
-    }
-    if let Ok(wayland) = std::env::var("WAYLAND_DISPLAY") {
-        cmd.env("WAYLAND_DISPLAY", wayland);
-    }
-    
-    let output = cmd
+    let (std_cmd, _) = CommandBuilder::new("apt").args(&["update"]).elevated(true).noninteractive().build()?;
+    let output = TokioCommand::from(std_cmd)
         .output()
         .await
         .map_err(|e| anyhow::anyhow!("Failed to execute update: {}. Make sure polkit is installed.", e))?;
@@ -2883,24 +4993,8 @@ async fn _update_lists_removed() -> Result<String, anyhow::Error> {
 async fn _upgrade_all_removed() -> Result<String, anyhow::Error> {
     use tokio::process::Command as TokioCommand;
     
-    // Use pkexec for GUI privilege escalation (like Rustora)
-    let mut cmd = TokioCommand::new("pkexec");
-    cmd.arg("apt");
-    cmd.arg("upgrade");
-    cmd.arg("-y");
-    
-    // Ensure DISPLAY is set for GUI password dialog
-    if let Ok(display) = std::env::var("DISPLAY") {
-        cmd.env("DISPLAY", display);
-    }
-    if let Ok(xauth) = std::env::var("XAUTHORITY") {
-        cmd.env("XAUTHORITY", xauth);
-    }
-    if let Ok(wayland) = std::env::var("WAYLAND_DISPLAY") {
-        cmd.env("WAYLAND_DISPLAY", wayland);
-    }
-    
-    let output = cmd
+    let (std_cmd, _) = CommandBuilder::new("apt").args(&["upgrade", "-y"]).elevated(true).noninteractive().build()?;
+    let output = TokioCommand::from(std_cmd)
         .output()
         .await
         .map_err(|e| anyhow::anyhow!("Failed to execute upgrade: {}. Make sure polkit is installed.", e))?;
@@ -2919,38 +5013,38 @@ async fn _upgrade_all_removed() -> Result<String, anyhow::Error> {
 
 async fn search_flatpak(query: String) -> Result<Vec<FlatpakInfo>, anyhow::Error> {
     #[cfg(debug_assertions)]
-    eprintln!("[FLATPAK DEBUG] search_flatpak() called with query: '{}'", query);
+    tracing::debug!("search_flatpak() called with query: '{}'", query);
     tokio::task::spawn_blocking(move || {
         #[cfg(debug_assertions)]
-        eprintln!("[FLATPAK DEBUG] search_flatpak: Creating FlatpakManager...");
+        tracing::debug!("search_flatpak: Creating FlatpakManager...");
         match FlatpakManager::new() {
             Ok(_fm) => {
                 #[cfg(debug_assertions)]
-                eprintln!("[FLATPAK DEBUG] search_flatpak: FlatpakManager created, executing search...");
-                use crate::utils::run_command;
+                tracing::debug!("search_flatpak: FlatpakManager created, executing search...");
+                use birdnest_core::utils::run_command;
                 #[cfg(debug_assertions)]
-                eprintln!("[FLATPAK DEBUG] search_flatpak: Executing command: flatpak search {}", query);
+                tracing::debug!("search_flatpak: Executing command: flatpak search {}", query);
                 match run_command("flatpak", &["search", &query], false) {
                     Ok(output) => {
                         #[cfg(debug_assertions)]
-                        eprintln!("[FLATPAK DEBUG] search_flatpak: Search completed, output length: {} bytes", output.len());
+                        tracing::debug!("search_flatpak: Search completed, output length: {} bytes", output.len());
                         #[cfg(debug_assertions)]
-                        eprintln!("[FLATPAK DEBUG] search_flatpak: Parsing search output...");
+                        tracing::debug!("search_flatpak: Parsing search output...");
                         let results = parse_flatpak_search_output(&output);
                         #[cfg(debug_assertions)]
-                        eprintln!("[FLATPAK DEBUG] search_flatpak: Parsed {} results", results.len());
+                        tracing::debug!("search_flatpak: Parsed {} results", results.len());
                         Ok(results)
                     },
                     Err(e) => {
                         #[cfg(debug_assertions)]
-                        eprintln!("[FLATPAK DEBUG] search_flatpak: ERROR - Command failed: {}", e);
+                        tracing::warn!("search_flatpak: Command failed: {}", e);
                         Err(e)
                     },
                 }
             }
             Err(e) => {
                 #[cfg(debug_assertions)]
-                eprintln!("[FLATPAK DEBUG] search_flatpak: ERROR - FlatpakManager creation failed: {}", e);
+                tracing::warn!("search_flatpak: FlatpakManager creation failed: {}", e);
                 Err(e)
             },
         }
@@ -2958,14 +5052,36 @@ async fn search_flatpak(query: String) -> Result<Vec<FlatpakInfo>, anyhow::Error
     .await
     .unwrap_or_else(|_| {
         #[cfg(debug_assertions)]
-        eprintln!("[FLATPAK DEBUG] search_flatpak: ERROR - Task failed");
+        tracing::warn!("search_flatpak: Task failed");
         Err(anyhow::anyhow!("Failed to search flatpak"))
     })
 }
 
+// Installed apps whose name or app ID contains `filter`, ignoring case
+fn filter_flatpak_apps<'a>(apps: &'a [FlatpakInfo], filter: &str) -> Vec<&'a FlatpakInfo> {
+    let filter = filter.trim().to_lowercase();
+    apps.iter()
+        .filter(|app| {
+            filter.is_empty()
+                || app.name.to_lowercase().contains(&filter)
+                || app.application.to_lowercase().contains(&filter)
+        })
+        .collect()
+}
+
+// Search results grouped by the remote they'd install from, the preferred
+// remote's group first; search order is kept within a group
+fn group_flatpak_results(results: &mut [FlatpakInfo], default_remote: Option<&str>) {
+    let preferred = default_remote.unwrap_or("flathub");
+    results.sort_by_cached_key(|result| {
+        let remote = flatpak::preferred_remote(&result.remotes, default_remote).unwrap_or_default().to_string();
+        (remote != preferred, remote)
+    });
+}
+
 fn parse_flatpak_search_output(output: &str) -> Vec<FlatpakInfo> {
     #[cfg(debug_assertions)]
-    eprintln!("[FLATPAK DEBUG] parse_flatpak_search_output() called, input length: {} bytes", output.len());
+    tracing::debug!("parse_flatpak_search_output() called, input length: {} bytes", output.len());
     let mut packages = Vec::new();
     let mut current_pkg: Option<FlatpakInfo> = None;
     
@@ -2976,7 +5092,7 @@ fn parse_flatpak_search_output(output: &str) -> Vec<FlatpakInfo> {
         {
             line_count += 1;
             if line_count <= 5 {
-                eprintln!("[FLATPAK DEBUG] parse_flatpak_search_output: Processing line {}: '{}'", line_count, line);
+                tracing::debug!("parse_flatpak_search_output: Processing line {}: '{}'", line_count, line);
             }
         }
         if line.trim().is_empty() {
@@ -2994,9 +5110,14 @@ fn parse_flatpak_search_output(output: &str) -> Vec<FlatpakInfo> {
             let description = parts.get(1).map(|s| s.trim()).unwrap_or("").to_string();
             let application = parts[2].trim().to_string();
             let version = parts.get(3).map(|s| s.trim()).unwrap_or("").to_string();
+            // One row per app; the last column lists every remote that has it
+            let remotes: Vec<String> = parts
+                .get(5)
+                .map(|remotes| remotes.split(',').map(str::trim).filter(|r| !r.is_empty()).map(String::from).collect())
+                .unwrap_or_default();
             
             #[cfg(debug_assertions)]
-            eprintln!("[FLATPAK DEBUG] parse_flatpak_search_output: Parsed - name: '{}', description: '{}', application: '{}', version: '{}'", name, description, application, version);
+            tracing::debug!("parse_flatpak_search_output: Parsed - name: '{}', description: '{}', application: '{}', version: '{}'", name, description, application, version);
             
             if let Some(pkg) = current_pkg.take() {
                 packages.push(pkg);
@@ -3007,6 +5128,7 @@ fn parse_flatpak_search_output(output: &str) -> Vec<FlatpakInfo> {
                 description: if description.is_empty() { "No description".to_string() } else { description },
                 version,
                 application,
+                remotes,
             });
         } else if let Some(ref mut pkg) = current_pkg {
             // Continuation line for description
@@ -3035,16 +5157,16 @@ async fn install_flatpak(package: String) -> Result<String, anyhow::Error> {
 
 async fn update_flatpak_repos() -> Result<String, anyhow::Error> {
     #[cfg(debug_assertions)]
-    eprintln!("[FLATPAK DEBUG] update_flatpak_repos() called");
+    tracing::debug!("update_flatpak_repos() called");
     tokio::task::spawn_blocking(|| {
         #[cfg(debug_assertions)]
-        eprintln!("[FLATPAK DEBUG] update_flatpak_repos: Creating FlatpakManager...");
+        tracing::debug!("update_flatpak_repos: Creating FlatpakManager...");
         let fm = FlatpakManager::new()?;
         #[cfg(debug_assertions)]
-        eprintln!("[FLATPAK DEBUG] update_flatpak_repos: Calling fm.update()...");
+        tracing::debug!("update_flatpak_repos: Calling fm.update()...");
         fm.update()?;
         #[cfg(debug_assertions)]
-        eprintln!("[FLATPAK DEBUG] update_flatpak_repos: Update completed successfully");
+        tracing::debug!("update_flatpak_repos: Update completed successfully");
         Ok("Flatpak repositories updated".to_string())
     })
     .await
@@ -3053,16 +5175,16 @@ async fn update_flatpak_repos() -> Result<String, anyhow::Error> {
 
 async fn upgrade_all_flatpaks() -> Result<String, anyhow::Error> {
     #[cfg(debug_assertions)]
-    eprintln!("[FLATPAK DEBUG] upgrade_all_flatpaks() called");
+    tracing::debug!("upgrade_all_flatpaks() called");
     tokio::task::spawn_blocking(|| {
         #[cfg(debug_assertions)]
-        eprintln!("[FLATPAK DEBUG] upgrade_all_flatpaks: Creating FlatpakManager...");
+        tracing::debug!("upgrade_all_flatpaks: Creating FlatpakManager...");
         let fm = FlatpakManager::new()?;
         #[cfg(debug_assertions)]
-        eprintln!("[FLATPAK DEBUG] upgrade_all_flatpaks: Calling fm.upgrade()...");
+        tracing::debug!("upgrade_all_flatpaks: Calling fm.upgrade()...");
         fm.upgrade(&[], true)?;
         #[cfg(debug_assertions)]
-        eprintln!("[FLATPAK DEBUG] upgrade_all_flatpaks: Upgrade completed successfully");
+        tracing::debug!("upgrade_all_flatpaks: Upgrade completed successfully");
         Ok("All Flatpaks upgraded".to_string())
     })
     .await
@@ -3072,17 +5194,17 @@ async fn upgrade_all_flatpaks() -> Result<String, anyhow::Error> {
 #[allow(dead_code)]
 async fn show_flatpak_info(package: String) -> Result<String, anyhow::Error> {
     #[cfg(debug_assertions)]
-    eprintln!("[FLATPAK DEBUG] show_flatpak_info() called for package: '{}'", package);
+    tracing::debug!("show_flatpak_info() called for package: '{}'", package);
     tokio::task::spawn_blocking(move || {
         #[cfg(debug_assertions)]
-        eprintln!("[FLATPAK DEBUG] show_flatpak_info: Creating FlatpakManager...");
+        tracing::debug!("show_flatpak_info: Creating FlatpakManager...");
         let _fm = FlatpakManager::new()?;
-        use crate::utils::run_command;
+        use birdnest_core::utils::run_command;
         #[cfg(debug_assertions)]
-        eprintln!("[FLATPAK DEBUG] show_flatpak_info: Executing command: flatpak info {}", package);
+        tracing::debug!("show_flatpak_info: Executing command: flatpak info {}", package);
         let output = run_command("flatpak", &["info", &package], false)?;
         #[cfg(debug_assertions)]
-        eprintln!("[FLATPAK DEBUG] show_flatpak_info: Command completed, output length: {} bytes", output.len());
+        tracing::debug!("show_flatpak_info: Command completed, output length: {} bytes", output.len());
         Ok(output)
     })
     .await
@@ -3091,84 +5213,84 @@ async fn show_flatpak_info(package: String) -> Result<String, anyhow::Error> {
 
 async fn clean_flatpak() -> Result<String, anyhow::Error> {
     #[cfg(debug_assertions)]
-    eprintln!("[FLATPAK DEBUG] clean_flatpak() called");
+    tracing::debug!("clean_flatpak() called");
     tokio::task::spawn_blocking(|| {
         #[cfg(debug_assertions)]
-        eprintln!("[FLATPAK DEBUG] clean_flatpak: Creating FlatpakManager...");
+        tracing::debug!("clean_flatpak: Creating FlatpakManager...");
         let fm = FlatpakManager::new()?;
         #[cfg(debug_assertions)]
-        eprintln!("[FLATPAK DEBUG] clean_flatpak: Calling fm.clean()...");
+        tracing::debug!("clean_flatpak: Calling fm.clean()...");
         fm.clean()?;
         #[cfg(debug_assertions)]
-        eprintln!("[FLATPAK DEBUG] clean_flatpak: Clean completed successfully");
+        tracing::debug!("clean_flatpak: Clean completed successfully");
         Ok("Flatpak cache cleaned".to_string())
     })
     .await
     .unwrap()
 }
 
-async fn load_package_detail(package: String, is_flatpak: bool) -> Result<PackageDetail, anyhow::Error> {
+async fn load_package_detail(backend: Arc<dyn PackageBackend>, package: String, is_flatpak: bool) -> Result<PackageDetail, anyhow::Error> {
     #[cfg(debug_assertions)]
-    eprintln!("[FLATPAK DEBUG] load_package_detail() called for package: '{}', is_flatpak: {}", package, is_flatpak);
+    tracing::debug!("load_package_detail() called for package: '{}', is_flatpak: {}", package, is_flatpak);
     tokio::task::spawn_blocking(move || {
-        use crate::utils::run_command;
+        use birdnest_core::utils::run_command;
         
         if is_flatpak {
             #[cfg(debug_assertions)]
-            eprintln!("[FLATPAK DEBUG] load_package_detail: Loading flatpak package info...");
+            tracing::debug!("load_package_detail: Loading flatpak package info...");
             // Get flatpak info
             #[cfg(debug_assertions)]
-            eprintln!("[FLATPAK DEBUG] load_package_detail: Executing command: flatpak info {}", package);
+            tracing::debug!("load_package_detail: Executing command: flatpak info {}", package);
             let info_output = run_command("flatpak", &["info", &package], false)?;
             #[cfg(debug_assertions)]
-            eprintln!("[FLATPAK DEBUG] load_package_detail: Command completed, output length: {} bytes", info_output.len());
+            tracing::debug!("load_package_detail: Command completed, output length: {} bytes", info_output.len());
             let mut version = String::new();
             let mut description = String::new();
             let mut size = String::new();
             
             #[cfg(debug_assertions)]
-            eprintln!("[FLATPAK DEBUG] load_package_detail: Parsing info output...");
+            tracing::debug!("load_package_detail: Parsing info output...");
             for line in info_output.lines() {
                 if line.starts_with("Version:") {
                     version = line.replace("Version:", "").trim().to_string();
                     #[cfg(debug_assertions)]
-                    eprintln!("[FLATPAK DEBUG] load_package_detail: Found version: {}", version);
+                    tracing::debug!("load_package_detail: Found version: {}", version);
                 } else if line.starts_with("Description:") {
                     description = line.replace("Description:", "").trim().to_string();
                     #[cfg(debug_assertions)]
-                    eprintln!("[FLATPAK DEBUG] load_package_detail: Found description (length: {})", description.len());
+                    tracing::debug!("load_package_detail: Found description (length: {})", description.len());
                 } else if line.starts_with("Installed size:") {
                     size = line.replace("Installed size:", "").trim().to_string();
                     #[cfg(debug_assertions)]
-                    eprintln!("[FLATPAK DEBUG] load_package_detail: Found size: {}", size);
+                    tracing::debug!("load_package_detail: Found size: {}", size);
                 }
             }
             
             // If size not found, try to get it from flatpak list
             if size.is_empty() {
                 #[cfg(debug_assertions)]
-                eprintln!("[FLATPAK DEBUG] load_package_detail: Size not found, trying flatpak list...");
+                tracing::debug!("load_package_detail: Size not found, trying flatpak list...");
                 if let Ok(list_output) = run_command("flatpak", &["list", "--columns=name,size"], false) {
                     #[cfg(debug_assertions)]
-                    eprintln!("[FLATPAK DEBUG] load_package_detail: Got list output, searching for package...");
+                    tracing::debug!("load_package_detail: Got list output, searching for package...");
                     for line in list_output.lines() {
                         if line.contains(&package) {
                             let parts: Vec<&str> = line.split('\t').collect();
                             if parts.len() >= 2 {
                                 size = parts[1].trim().to_string();
                                 #[cfg(debug_assertions)]
-                                eprintln!("[FLATPAK DEBUG] load_package_detail: Found size from list: {}", size);
+                                tracing::debug!("load_package_detail: Found size from list: {}", size);
                             }
                         }
                     }
                 } else {
                     #[cfg(debug_assertions)]
-                    eprintln!("[FLATPAK DEBUG] load_package_detail: Failed to get list output");
+                    tracing::debug!("load_package_detail: Failed to get list output");
                 }
             }
             
             #[cfg(debug_assertions)]
-            eprintln!("[FLATPAK DEBUG] load_package_detail: Creating PackageDetail - name: {}, version: {}, size: {}", package, version, size);
+            tracing::debug!("load_package_detail: Creating PackageDetail - name: {}, version: {}, size: {}", package, version, size);
             Ok(PackageDetail {
                 name: package,
                 version: if version.is_empty() { "Unknown".to_string() } else { version },
@@ -3177,48 +5299,12 @@ async fn load_package_detail(package: String, is_flatpak: bool) -> Result<Packag
                 is_flatpak: true,
             })
         } else {
-            // Get apt package info
-            let show_output = run_command("apt", &["show", &package], false)?;
-            let mut version = String::new();
-            let mut description = String::new();
-            let mut size = String::new();
-            
-            for line in show_output.lines() {
-                if line.starts_with("Version:") {
-                    version = line.replace("Version:", "").trim().to_string();
-                } else if line.starts_with("Description:") {
-                    description = line.replace("Description:", "").trim().to_string();
-                } else if line.starts_with("Installed-Size:") {
-                    let size_kb = line.replace("Installed-Size:", "").trim().to_string();
-                    if let Ok(kb) = size_kb.parse::<f64>() {
-                        if kb >= 1024.0 {
-                            size = format!("{:.2} MB", kb / 1024.0);
-                        } else {
-                            size = format!("{} KB", size_kb);
-                        }
-                    } else {
-                        size = format!("{} KB", size_kb);
-                    }
-                }
-            }
-            
-            // If description is multi-line, get the full description
-            if description.is_empty() {
-                for line in show_output.lines() {
-                    if line.starts_with("Description:") {
-                        description = line.replace("Description:", "").trim().to_string();
-                    } else if !description.is_empty() && line.starts_with(" ") {
-                        description.push_str(" ");
-                        description.push_str(line.trim());
-                    }
-                }
-            }
-            
+            let details = backend.info(&package)?;
             Ok(PackageDetail {
                 name: package,
-                version: if version.is_empty() { "Unknown".to_string() } else { version },
-                description: if description.is_empty() { "No description available".to_string() } else { description },
-                size: if size.is_empty() { "Unknown".to_string() } else { size },
+                version: details.version.clone(),
+                description: package_description(&details),
+                size: package_size(&details),
                 is_flatpak: false,
             })
         }
@@ -3228,10 +5314,10 @@ async fn load_package_detail(package: String, is_flatpak: bool) -> Result<Packag
 }
 
 #[allow(dead_code)]
-async fn load_multiple_package_details(packages: Vec<String>) -> Vec<PackageDetail> {
+async fn load_multiple_package_details(backend: Arc<dyn PackageBackend>, packages: Vec<String>) -> Vec<PackageDetail> {
     // Load all package details in parallel
     let futures: Vec<_> = packages.into_iter()
-        .map(|pkg| load_package_detail(pkg, false))
+        .map(|pkg| load_package_detail(backend.clone(), pkg, false))
         .collect();
     
     let results: Vec<Result<PackageDetail, anyhow::Error>> = future::join_all(futures).await;
@@ -3242,7 +5328,7 @@ async fn load_multiple_package_details(packages: Vec<String>) -> Vec<PackageDeta
         match result {
             Ok(detail) => details.push(detail),
             Err(e) => {
-                eprintln!("Warning: Failed to load package detail: {}", e);
+                tracing::warn!("Failed to load package detail: {}", e);
                 // Continue with other packages even if one fails
             }
         }
@@ -3259,7 +5345,8 @@ async fn pikman_search(query: String, filter: Option<String>) -> Result<Vec<Pack
     
     // Try without sudo first
     // Note: --aur, --fedora, --alpine are GLOBAL options and must come BEFORE the command
-    let mut cmd = TokioCommand::new("pikman");
+    let (std_cmd, _) = CommandBuilder::new("pikman").build()?;
+    let mut cmd = TokioCommand::from(std_cmd);
     
     // Add global flags before the command
     if let Some(ref f) = filter {
@@ -3295,45 +5382,20 @@ async fn pikman_search(query: String, filter: Option<String>) -> Result<Vec<Pack
                      stderr.contains("denied") ||
                      output.status.code() == Some(1) && stderr.contains("sudo");
     
-    // Only use pkexec if we actually need sudo
+    // Only elevate if we actually need sudo
     if needs_sudo {
-        let mut cmd = TokioCommand::new("pkexec");
-        cmd.arg("pikman");
-        cmd.arg("search");
-        cmd.arg(&query);
-        
-        // Add global flags before the command
-        if let Some(ref f) = filter {
-            match f.as_str() {
-                "aur" => {
-                    cmd.arg("--aur");
-                }
-                "fedora" => {
-                    cmd.arg("--fedora");
-                }
-                "alpine" => {
-                    cmd.arg("--alpine");
-                }
-                _ => {}
-            }
+        // Global flags go before the command
+        let mut args = Vec::new();
+        match filter.as_deref() {
+            Some("aur") => args.push("--aur"),
+            Some("fedora") => args.push("--fedora"),
+            Some("alpine") => args.push("--alpine"),
+            _ => {}
         }
+        args.extend(["search", query.as_str()]);
         
-        cmd.arg("search");
-        cmd.arg(&query);
-        
-        // Preserve environment variables for GUI password dialog
-        if let Ok(display) = std::env::var("DISPLAY") {
-            cmd.env("DISPLAY", display);
-        }
-        if let Ok(xauth) = std::env::var("XAUTHORITY") {
-            cmd.env("XAUTHORITY", xauth);
-        }
-        if let Ok(wayland) = std::env::var("WAYLAND_DISPLAY") {
-            cmd.env("WAYLAND_DISPLAY", wayland);
-        }
-        if let Ok(path) = std::env::var("PATH") {
-            cmd.env("PATH", path);
-        }
+        let (std_cmd, _) = CommandBuilder::new("pikman").args(&args).elevated(true).build()?;
+        let mut cmd = TokioCommand::from(std_cmd);
         
         let output = cmd.output().await?;
         
@@ -3428,6 +5490,9 @@ fn parse_pikman_search_output(output: &str, filter: Option<String>) -> Vec<Packa
                                 version,
                                 description: String::new(),
                                 size,
+                                installed_bytes: None,
+                                arch: None,
+                                provenance: None,
                                 source: source.clone(),
                             });
                         }
@@ -3479,6 +5544,9 @@ fn parse_pikman_search_output(output: &str, filter: Option<String>) -> Vec<Packa
                         version: String::new(), // Fedora output doesn't show version in search
                         description,
                         size: String::new(), // Fedora search doesn't show size
+                        installed_bytes: None,
+                        arch: None,
+                        provenance: None,
                         source: source.clone(),
                     });
                 } else if parts.len() == 1 && !parts[0].is_empty() {
@@ -3495,6 +5563,9 @@ fn parse_pikman_search_output(output: &str, filter: Option<String>) -> Vec<Packa
                         version: String::new(),
                         description: String::new(),
                         size: String::new(),
+                        installed_bytes: None,
+                        arch: None,
+                        provenance: None,
                         source: source.clone(),
                     });
                 }
@@ -3526,6 +5597,9 @@ fn parse_pikman_search_output(output: &str, filter: Option<String>) -> Vec<Packa
                             version,
                             description: String::new(),
                             size: String::new(), // Alpine search doesn't show size
+                            installed_bytes: None,
+                            arch: None,
+                            provenance: None,
                             source: source.clone(),
                         });
                     } else {
@@ -3535,6 +5609,9 @@ fn parse_pikman_search_output(output: &str, filter: Option<String>) -> Vec<Packa
                             version: String::new(),
                             description: String::new(),
                             size: String::new(),
+                            installed_bytes: None,
+                            arch: None,
+                            provenance: None,
                             source: source.clone(),
                         });
                     }
@@ -3544,6 +5621,9 @@ fn parse_pikman_search_output(output: &str, filter: Option<String>) -> Vec<Packa
                         version: String::new(),
                         description: String::new(),
                         size: String::new(),
+                        installed_bytes: None,
+                        arch: None,
+                        provenance: None,
                         source: source.clone(),
                     });
                 }
@@ -3586,6 +5666,9 @@ fn parse_pikman_search_output(output: &str, filter: Option<String>) -> Vec<Packa
                             version,
                             description,
                             size: String::new(), // Default search doesn't show size
+                            installed_bytes: None,
+                            arch: None,
+                            provenance: None,
                             source: source.clone(),
                         });
                     }
@@ -3609,7 +5692,7 @@ fn parse_pikman_search_output(output: &str, filter: Option<String>) -> Vec<Packa
 
 async fn pikman_autoremove() -> Result<String, anyhow::Error> {
     tokio::task::spawn_blocking(|| {
-        use crate::utils::run_command_interactive;
+        use birdnest_core::utils::run_command_interactive;
         run_command_interactive("pikman", &["autoremove", "-y"], false)?;
         Ok("Autoremove completed".to_string())
     })
@@ -3617,34 +5700,9 @@ async fn pikman_autoremove() -> Result<String, anyhow::Error> {
     .unwrap()
 }
 
-async fn pikman_enter(name: String) -> Result<String, anyhow::Error> {
-    tokio::task::spawn_blocking(move || {
-        use crate::utils::run_command_interactive;
-        run_command_interactive("pikman", &["enter", &name], false)?;
-        Ok(format!("Entered container: {}", name))
-    })
-    .await
-    .unwrap()
-}
-
-async fn pikman_export(package: String, name: Option<String>) -> Result<String, anyhow::Error> {
-    tokio::task::spawn_blocking(move || {
-        use crate::utils::run_command_interactive;
-        let mut args = vec!["export", &package];
-        if let Some(ref n) = name {
-            args.push("-n");
-            args.push(n);
-        }
-        run_command_interactive("pikman", &args, false)?;
-        Ok(format!("Exported package: {}", package))
-    })
-    .await
-    .unwrap()
-}
-
 async fn pikman_init(name: String, manager: Option<String>) -> Result<String, anyhow::Error> {
     tokio::task::spawn_blocking(move || {
-        use crate::utils::run_command_interactive;
+        use birdnest_core::utils::run_command_interactive;
         let mut args = vec!["init", &name];
         if let Some(ref m) = manager {
             args.push("-m");
@@ -3659,7 +5717,7 @@ async fn pikman_init(name: String, manager: Option<String>) -> Result<String, an
 
 async fn pikman_log() -> Result<String, anyhow::Error> {
     tokio::task::spawn_blocking(|| {
-        use crate::utils::run_command;
+        use birdnest_core::utils::run_command;
         let output = run_command("pikman", &["log"], false)?;
         Ok(output)
     })
@@ -3669,7 +5727,7 @@ async fn pikman_log() -> Result<String, anyhow::Error> {
 
 async fn pikman_purge(packages: Vec<String>) -> Result<String, anyhow::Error> {
     tokio::task::spawn_blocking(move || {
-        use crate::utils::run_command_interactive;
+        use birdnest_core::utils::run_command_interactive;
         let mut args = vec!["purge", "-y"];
         args.extend(packages.iter().map(|s| s.as_str()));
         run_command_interactive("pikman", &args, false)?;
@@ -3679,39 +5737,12 @@ async fn pikman_purge(packages: Vec<String>) -> Result<String, anyhow::Error> {
     .unwrap()
 }
 
-async fn pikman_run(name: String, command: Vec<String>) -> Result<String, anyhow::Error> {
-    tokio::task::spawn_blocking(move || {
-        use crate::utils::run_command_interactive;
-        let mut args = vec!["run", &name];
-        args.extend(command.iter().map(|s| s.as_str()));
-        run_command_interactive("pikman", &args, false)?;
-        Ok(format!("Command executed in container: {}", name))
-    })
-    .await
-    .unwrap()
-}
-
 async fn pikman_upgrades() -> Result<String, anyhow::Error> {
     tokio::task::spawn_blocking(|| {
-        use crate::utils::run_command;
+        use birdnest_core::utils::run_command;
         let output = run_command("pikman", &["upgrades"], false)?;
         Ok(output)
     })
     .await
     .unwrap()
 }
-
-async fn pikman_unexport(package: String, name: Option<String>) -> Result<String, anyhow::Error> {
-    tokio::task::spawn_blocking(move || {
-        use crate::utils::run_command_interactive;
-        let mut args = vec!["unexport", &package];
-        if let Some(ref n) = name {
-            args.push("-n");
-            args.push(n);
-        }
-        run_command_interactive("pikman", &args, false)?;
-        Ok(format!("Unexported package: {}", package))
-    })
-    .await
-    .unwrap()
-}
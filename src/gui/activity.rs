@@ -0,0 +1,145 @@
+use iced::{
+    alignment,
+    widget::{button, column, container, row, scrollable, text, Column, Space},
+    Element, Length, Padding,
+};
+
+use crate::gui::theme::Theme as AppTheme;
+use crate::gui::styles::{CustomScrollableStyle, RoundedButtonStyle, RoundedContainerStyle};
+use crate::gui::Message;
+
+// What the GUI did this session: operations started and finished, and what
+// failed. The status bar's Activity button opens it as a drawer above the bar.
+// Every entry also goes to the log file, so earlier sessions can be read in
+// the Logs window.
+
+const LOG_TARGET: &str = "birdnest::activity";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// Local wall-clock time, "14:03:27"
+    pub time: String,
+    pub message: String,
+    pub error: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct Activity {
+    pub entries: Vec<Entry>,
+    pub open: bool,
+    /// Errors since the drawer was last open, counted on its button
+    pub unseen_errors: usize,
+}
+
+impl Activity {
+    pub fn info(&mut self, message: impl Into<String>) {
+        let message = message.into();
+        tracing::info!(target: LOG_TARGET, "{}", message);
+        self.entries.push(Entry { time: clock(), message, error: false });
+    }
+
+    pub fn error(&mut self, message: impl Into<String>) {
+        let message = message.into();
+        tracing::warn!(target: LOG_TARGET, "{}", message);
+        self.entries.push(Entry { time: clock(), message, error: true });
+        if !self.open {
+            self.unseen_errors += 1;
+        }
+    }
+
+    /// The newest message that wasn't an error
+    #[cfg(test)]
+    pub fn last_info(&self) -> Option<&str> {
+        self.entries.iter().rev().find(|entry| !entry.error).map(|entry| entry.message.as_str())
+    }
+
+    #[cfg(test)]
+    pub fn error_count(&self) -> usize {
+        self.entries.iter().filter(|entry| entry.error).count()
+    }
+
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+        self.unseen_errors = 0;
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.unseen_errors = 0;
+    }
+}
+
+fn clock() -> String {
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    unsafe {
+        let now = libc::time(std::ptr::null_mut());
+        libc::localtime_r(&now, &mut tm);
+    }
+    format!("{:02}:{:02}:{:02}", tm.tm_hour, tm.tm_min, tm.tm_sec)
+}
+
+pub fn scroll_id() -> scrollable::Id {
+    scrollable::Id::new("activity-drawer")
+}
+
+// Oldest first, like a terminal; the drawer is scrolled to the end when opened
+pub fn view(activity: &Activity, theme: AppTheme, radius: f32) -> Element<'_, Message> {
+    let small_button = |label: &'static str, message: Option<Message>| {
+        button(text(label).size(13.0))
+            .on_press_maybe(message)
+            .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                is_primary: false,
+                radius,
+                primary_color: theme.primary(),
+                text_color: theme.text(),
+                background_color: theme.background(),
+            })))
+            .padding(Padding::from([4.0, 10.0]))
+    };
+
+    let mut list = Column::new().spacing(4);
+    for entry in &activity.entries {
+        list = list.push(
+            row![
+                text(&entry.time).size(12.0).font(iced::Font::MONOSPACE).style(iced::theme::Text::Color(theme.secondary_text())),
+                text(&entry.message)
+                    .size(13.0)
+                    .style(iced::theme::Text::Color(if entry.error { theme.danger() } else { theme.text() }))
+                    .width(Length::Fill),
+            ]
+            .spacing(10),
+        );
+    }
+    if activity.entries.is_empty() {
+        list = list.push(text("Nothing has happened yet this session").size(13.0).style(iced::theme::Text::Color(theme.secondary_text())));
+    }
+
+    container(
+        column![
+            row![
+                text("Activity").size(15.0).style(iced::theme::Text::Color(theme.text())),
+                Space::with_width(Length::Fill),
+                small_button("Clear", (!activity.entries.is_empty()).then_some(Message::ClearOutput)),
+                small_button("Close", Some(Message::ToggleActivity)),
+            ]
+            .spacing(8)
+            .align_items(alignment::Alignment::Center),
+            scrollable(list.padding(Padding::from([0.0, 12.0, 0.0, 0.0])))
+                .id(scroll_id())
+                .style(iced::theme::Scrollable::Custom(Box::new(CustomScrollableStyle {
+                    background_color: theme.surface(),
+                    border_radius: radius,
+                })))
+                .height(Length::Fixed(160.0)),
+        ]
+        .spacing(8),
+    )
+    .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+        radius,
+        background: Some(theme.surface()),
+        elevation: 1.0,
+    })))
+    .width(Length::Fill)
+    .padding(Padding::new(12.0))
+    .into()
+}
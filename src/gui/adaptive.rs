@@ -0,0 +1,29 @@
+use iced::{
+    alignment,
+    widget::{column, row, Space},
+    Element, Length,
+};
+
+use crate::gui::Message;
+
+// Below this the main window is about half a laptop screen: the tab bar
+// collapses into a menu and action rows stack
+pub const NARROW_WIDTH: f32 = 700.0;
+
+pub fn is_narrow(width: f32) -> bool {
+    width < NARROW_WIDTH
+}
+
+/// The row above a result list: `leading` buttons on the left and `trailing`
+/// (the install or remove button) on the right, or stacked when narrow
+pub fn action_bar<'a>(narrow: bool, leading: Element<'a, Message>, trailing: Element<'a, Message>) -> Element<'a, Message> {
+    if narrow {
+        column![leading, trailing].spacing(8).width(Length::Fill).into()
+    } else {
+        row![leading, Space::with_width(Length::Fill), trailing]
+            .spacing(8)
+            .width(Length::Fill)
+            .align_items(alignment::Alignment::Center)
+            .into()
+    }
+}
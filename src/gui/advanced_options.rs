@@ -0,0 +1,126 @@
+use iced::{
+    alignment, Color,
+    widget::{button, checkbox, column, container, row, text, text_input, Column},
+    Element, Length, Padding,
+};
+
+use birdnest_core::config::Config;
+
+use crate::gui::theme::Theme as AppTheme;
+use crate::gui::styles::{RoundedButtonStyle, RoundedContainerStyle, YellowCheckboxStyle, YellowTextInputStyle};
+
+// The "Advanced options" expander the install dialogs share: source-specific
+// flags for this install, and a button that keeps them as the defaults in
+// config.toml. The main window picks saved defaults up like other settings.
+
+/// Reads config.toml, applies `edit` and writes it back, returning what was
+/// saved so the main window can use it
+pub async fn save_defaults(edit: impl FnOnce(&mut Config) + Send + 'static) -> Result<Box<Config>, String> {
+    tokio::task::spawn_blocking(move || {
+        let mut config = Config::load().map_err(|e| format!("Couldn't read config.toml: {}", e))?;
+        edit(&mut config);
+        config.save().map_err(|e| format!("Couldn't save config.toml: {}", e))?;
+        Ok(Box::new(config))
+    })
+    .await
+    .map_err(|e| format!("Task error: {}", e))?
+}
+
+/// One labelled text field
+pub fn field<'a, M: Clone + 'a>(
+    label: &str,
+    placeholder: &str,
+    value: &str,
+    on_input: impl Fn(String) -> M + 'a,
+    theme: AppTheme,
+    radius: f32,
+) -> Element<'a, M> {
+    row![
+        text(label.to_string()).size(13).width(Length::Fixed(130.0)).style(iced::theme::Text::Color(theme.text())),
+        text_input(placeholder, value)
+            .on_input(on_input)
+            .padding(Padding::new(8.0))
+            .width(Length::Fixed(240.0))
+            .style(iced::theme::TextInput::Custom(Box::new(YellowTextInputStyle {
+                radius,
+                primary_color: theme.primary(),
+                background_color: theme.background(),
+                text_color: Color::BLACK,
+            }))),
+    ]
+    .spacing(10)
+    .align_items(alignment::Alignment::Center)
+    .into()
+}
+
+pub fn toggle<'a, M: 'a>(label: &str, checked: bool, on_toggle: impl Fn(bool) -> M + 'a, theme: AppTheme) -> Element<'a, M> {
+    checkbox(label.to_string(), checked)
+        .on_toggle(on_toggle)
+        .text_size(13.0)
+        .style(iced::theme::Checkbox::Custom(Box::new(YellowCheckboxStyle {
+            radius: 4.0,
+            primary_color: theme.primary(),
+        })))
+        .into()
+}
+
+/// The expander's heading, and while `open`, `fields` with a "Save as
+/// defaults" button and how the last save went
+pub fn view<'a, M: Clone + 'a>(
+    open: bool,
+    on_toggle: M,
+    fields: Vec<Element<'a, M>>,
+    on_save: M,
+    status: Option<&Result<String, String>>,
+    theme: AppTheme,
+    radius: f32,
+) -> Element<'a, M> {
+    let heading = button(text(if open { "▾ Advanced options" } else { "▸ Advanced options" }).size(14))
+        .on_press(on_toggle)
+        .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+            is_primary: false,
+            radius,
+            primary_color: theme.primary(),
+            text_color: theme.text(),
+            background_color: theme.background(),
+        })))
+        .padding(Padding::from([6.0, 12.0]));
+    if !open {
+        return heading.into();
+    }
+
+    let mut body = Column::with_children(fields).spacing(10);
+    let save = button(text("Save as defaults").size(13))
+        .on_press(on_save)
+        .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+            is_primary: false,
+            radius,
+            primary_color: theme.primary(),
+            text_color: theme.text(),
+            background_color: theme.background(),
+        })))
+        .padding(Padding::from([6.0, 12.0]));
+    let mut footer = row![save].spacing(10).align_items(alignment::Alignment::Center);
+    if let Some(status) = status {
+        let (message, color) = match status {
+            Ok(message) => (message.clone(), theme.secondary_text()),
+            Err(e) => (e.clone(), theme.danger()),
+        };
+        footer = footer.push(text(message).size(12).style(iced::theme::Text::Color(color)));
+    }
+    body = body.push(footer);
+
+    column![
+        heading,
+        container(body)
+            .width(Length::Fill)
+            .padding(Padding::new(12.0))
+            .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+                radius,
+                background: Some(theme.surface()),
+                elevation: 0.5,
+            }))),
+    ]
+    .spacing(8)
+    .into()
+}
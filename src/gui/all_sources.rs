@@ -0,0 +1,281 @@
+use iced::{
+    alignment, Color,
+    widget::{button, column, container, row, scrollable, text, Column, Row as RowWidget},
+    Element, Length, Padding,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use birdnest_core::package_manager::PackageBackend;
+
+use crate::gui::theme::Theme as AppTheme;
+use crate::gui::styles::{RoundedButtonStyle, RoundedContainerStyle, CustomScrollableStyle};
+use crate::gui::global_search::merge_keys;
+use crate::gui::{pikman_search, search_flatpak, search_packages, Message, PackageInfo, PackageSource};
+
+// "All sources" on the Search tab: one query sent to apt, Flatpak and each
+// pikman source at once, the same app from several of them folded into one
+// row with a badge per source to install it from.
+
+/// Every source searched, in the order rows and badges list them
+pub const SOURCES: [PackageSource; 5] = [
+    PackageSource::Default,
+    PackageSource::Flatpak,
+    PackageSource::Aur,
+    PackageSource::Fedora,
+    PackageSource::Alpine,
+];
+
+/// A match from one source. `package.name` is what installing it takes
+/// (a Flatpak's application ID); `title` is what the row shows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hit {
+    pub title: String,
+    pub app_id: Option<String>,
+    pub package: PackageInfo,
+}
+
+/// What searching one source found, or why it failed
+pub type SourceResults = Result<Vec<Hit>, String>;
+
+/// One app, with every source offering it in [`SOURCES`] order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Row {
+    pub title: String,
+    pub description: String,
+    pub options: Vec<PackageInfo>,
+}
+
+#[derive(Debug, Default)]
+pub struct AllSources {
+    pub enabled: bool,
+    /// The query the results are for
+    pub query: String,
+    /// Each source's results, None while it is still searching
+    pub results: Vec<(PackageSource, Option<SourceResults>)>,
+}
+
+impl AllSources {
+    /// Starts a search for `query`, forgetting the last one's results
+    pub fn start(&mut self, query: &str) {
+        self.query = query.to_string();
+        self.results = SOURCES.iter().map(|source| (source.clone(), None)).collect();
+    }
+
+    /// Stores what `source` found, unless a newer query has replaced `query`
+    pub fn set_results(&mut self, source: &PackageSource, query: &str, results: SourceResults) {
+        if query != self.query {
+            return;
+        }
+        if let Some((_, slot)) = self.results.iter_mut().find(|(s, _)| s == source) {
+            *slot = Some(results);
+        }
+    }
+
+    pub fn is_searching(&self) -> bool {
+        self.results.iter().any(|(_, results)| results.is_none())
+    }
+
+    pub fn rows(&self) -> Vec<Row> {
+        merge(&self.results)
+    }
+}
+
+// A Flatpak's package name is its application ID, which is also its AppStream ID
+fn keys(source: &PackageSource, hit: &Hit) -> Vec<String> {
+    match source {
+        PackageSource::Flatpak => merge_keys(hit.package.name.rsplit('.').next().unwrap_or(&hit.package.name), Some(&hit.package.name)),
+        _ => merge_keys(&hit.package.name, hit.app_id.as_deref()),
+    }
+}
+
+/// Folds hits that are the same app, by AppStream ID or package name, into
+/// the row of the first source that found it. A source listing the same app
+/// twice keeps its first match.
+pub fn merge(results: &[(PackageSource, Option<SourceResults>)]) -> Vec<Row> {
+    let mut owners: HashMap<String, usize> = HashMap::new();
+    let mut rows: Vec<Row> = Vec::new();
+    for (source, results) in results {
+        let Some(Ok(hits)) = results else {
+            continue;
+        };
+        for hit in hits {
+            let keys = keys(source, hit);
+            let index = match keys.iter().find_map(|key| owners.get(key)).copied() {
+                Some(index) => index,
+                None => {
+                    rows.push(Row { title: hit.title.clone(), description: hit.package.description.clone(), options: Vec::new() });
+                    rows.len() - 1
+                }
+            };
+            for key in keys {
+                owners.entry(key).or_insert(index);
+            }
+            let row = &mut rows[index];
+            if row.description.is_empty() {
+                row.description = hit.package.description.clone();
+            }
+            if !row.options.iter().any(|option| &option.source == source) {
+                row.options.push(hit.package.clone());
+            }
+        }
+    }
+    for row in &mut rows {
+        row.options.sort_by_key(|option| SOURCES.iter().position(|s| *s == option.source));
+    }
+    rows
+}
+
+/// Searches `source` for `query`; apt's hits get their AppStream app later
+pub async fn search(backend: Arc<dyn PackageBackend>, source: PackageSource, query: String) -> SourceResults {
+    let plain = |packages: Vec<PackageInfo>| -> Vec<Hit> {
+        packages.into_iter().map(|package| Hit { title: package.name.clone(), app_id: None, package }).collect()
+    };
+    match source {
+        PackageSource::Default => Ok(plain(search_packages(backend, query).await)),
+        PackageSource::Flatpak => search_flatpak(query)
+            .await
+            .map(|apps| {
+                apps.into_iter()
+                    .map(|app| Hit {
+                        title: app.name,
+                        app_id: Some(app.application.clone()),
+                        package: PackageInfo {
+                            name: app.application,
+                            description: app.description,
+                            version: app.version,
+                            size: String::new(),
+                            installed_bytes: None,
+                            arch: None,
+                            provenance: None,
+                            source: PackageSource::Flatpak,
+                        },
+                    })
+                    .collect()
+            })
+            .map_err(|e| e.to_string()),
+        PackageSource::Aur | PackageSource::Fedora | PackageSource::Alpine => {
+            let filter = source.as_str().to_lowercase();
+            pikman_search(query, Some(filter)).await.map(plain).map_err(|e| e.to_string())
+        }
+    }
+}
+
+fn badge<'a>(source: &PackageSource, theme: AppTheme) -> Element<'a, Message> {
+    container(
+        text(source.as_str())
+            .size(10)
+            .style(iced::theme::Text::Color(source.badge_text_color(matches!(theme, AppTheme::Dark)))),
+    )
+    .padding(Padding::new(6.0))
+    .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+        radius: 4.0,
+        background: Some(source.badge_color()),
+        elevation: 0.5,
+    })))
+    .into()
+}
+
+fn install_button<'a>(label: String, option: &PackageInfo, theme: AppTheme, radius: f32) -> Element<'a, Message> {
+    button(text(label).size(13.0))
+        .on_press(Message::InstallFrom(option.clone()))
+        .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+            is_primary: false,
+            radius,
+            primary_color: theme.primary(),
+            text_color: Color::WHITE,
+            background_color: theme.background(),
+        })))
+        .padding(Padding::new(8.0))
+        .into()
+}
+
+// The app with a badge per source, and an Install button, or "Install from"
+// and a button per source when there is a choice
+fn view_row<'a>(row: Row, theme: AppTheme, radius: f32) -> Element<'a, Message> {
+    let badges = row.options.iter().fold(RowWidget::new().spacing(6), |badges, option| badges.push(badge(&option.source, theme)));
+    let installs = match row.options.as_slice() {
+        [option] => install_button("Install".to_string(), option, theme, radius),
+        options => options
+            .iter()
+            .fold(
+                RowWidget::new()
+                    .push(text("Install from").size(13.0).style(iced::theme::Text::Color(theme.text())))
+                    .spacing(8)
+                    .align_items(alignment::Alignment::Center),
+                |buttons, option| {
+                    let label = match option.version.is_empty() {
+                        true => option.source.as_str().to_string(),
+                        false => format!("{} {}", option.source.as_str(), option.version),
+                    };
+                    buttons.push(install_button(label, option, theme, radius))
+                },
+            )
+            .into(),
+    };
+
+    container(
+        row![
+            column![
+                row![text(row.title).size(16.0).style(iced::theme::Text::Color(theme.text())), badges]
+                    .spacing(8)
+                    .align_items(alignment::Alignment::Center),
+                text(row.description).size(12.0).style(iced::theme::Text::Color(theme.secondary_text())),
+            ]
+            .spacing(4)
+            .width(Length::Fill),
+            installs,
+        ]
+        .spacing(10)
+        .align_items(alignment::Alignment::Center),
+    )
+    .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+        radius,
+        background: Some(theme.card_background()),
+        elevation: 1.0,
+    })))
+    .width(Length::Fill)
+    .padding(Padding::new(12.0))
+    .into()
+}
+
+// Which sources are still searching or failed, then one card per app
+pub fn view(state: &AllSources, theme: AppTheme, radius: f32) -> Element<'_, Message> {
+    let mut notes = Column::new().spacing(4);
+    for (source, results) in &state.results {
+        match results {
+            None => notes = notes.push(text(format!("Searching {}...", source.as_str())).size(13.0).style(iced::theme::Text::Color(theme.secondary_text()))),
+            Some(Err(e)) => notes = notes.push(text(format!("{}: {}", source.as_str(), e)).size(13.0).style(iced::theme::Text::Color(theme.danger()))),
+            Some(Ok(_)) => {}
+        }
+    }
+
+    let rows = state.rows();
+    let found = !rows.is_empty();
+    let mut list = Column::new().spacing(8);
+    for row in rows {
+        list = list.push(view_row(row, theme, radius));
+    }
+    if !found {
+        let message = if state.query.is_empty() {
+            "Enter a search query to look in every source"
+        } else if state.is_searching() {
+            "Searching every source..."
+        } else {
+            "No source has a match"
+        };
+        list = list.push(text(message).size(16.0).style(iced::theme::Text::Color(theme.text())));
+    }
+
+    column![
+        notes,
+        scrollable(list.padding(Padding::new(4.0)))
+            .style(iced::theme::Scrollable::Custom(Box::new(CustomScrollableStyle {
+                background_color: theme.background(),
+                border_radius: radius,
+            })))
+            .height(Length::Fill),
+    ]
+    .spacing(10)
+    .into()
+}
@@ -0,0 +1,240 @@
+use iced::{
+    alignment, Color,
+    widget::{button, column, container, row, scrollable, text, Column, Space},
+    Element, Length, Padding,
+};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use birdnest_core::appimage::{self, AppImage, UpdateStatus};
+use birdnest_core::plan;
+
+use crate::gui::theme::Theme as AppTheme;
+use crate::gui::styles::{RoundedButtonStyle, RoundedContainerStyle, CustomScrollableStyle};
+use crate::gui::Message;
+
+// The AppImage tab: the AppImages in ~/Applications and the configured
+// directories, one card each, with menu integration, update checks,
+// updates and removal.
+
+/// What a card's buttons do to its AppImage
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Integrate,
+    Unintegrate,
+    CheckUpdate,
+    Update,
+    Remove,
+}
+
+#[derive(Debug, Default)]
+pub struct AppImageState {
+    pub appimages: Vec<AppImage>,
+    pub loading: bool,
+    /// Where to look, from the config
+    pub directories: Vec<PathBuf>,
+    /// AppImages with an action running; their buttons wait for it
+    pub busy: HashSet<PathBuf>,
+    /// What the last update check found, per AppImage
+    pub updates: HashMap<PathBuf, UpdateStatus>,
+    /// The AppImage whose Remove was pressed, waiting for confirmation
+    pub confirm_remove: Option<PathBuf>,
+    pub error: Option<String>,
+}
+
+impl AppImageState {
+    pub fn get(&self, path: &Path) -> Option<&AppImage> {
+        self.appimages.iter().find(|appimage| appimage.path == path)
+    }
+
+    /// The AppImages that say where their updates come from, for Check All
+    pub fn checkable(&self) -> Vec<AppImage> {
+        self.appimages
+            .iter()
+            .filter(|appimage| appimage.update_info.is_some() && !self.busy.contains(&appimage.path))
+            .cloned()
+            .collect()
+    }
+}
+
+pub async fn load(directories: Vec<PathBuf>) -> Vec<AppImage> {
+    tokio::task::spawn_blocking(move || appimage::scan(&directories)).await.unwrap_or_default()
+}
+
+/// Run `action` on `appimage`; a check answers with what it found
+pub async fn run(appimage: AppImage, action: Action) -> Result<Option<UpdateStatus>, String> {
+    tokio::task::spawn_blocking(move || match action {
+        Action::Integrate => appimage::integrate(&appimage).map(|_| None),
+        Action::Unintegrate => appimage::unintegrate(&appimage).map(|_| None),
+        Action::CheckUpdate => appimage::check_update(&appimage).map(Some),
+        Action::Update => appimage::update(&appimage).map(|_| None),
+        Action::Remove => appimage::remove(&appimage).map(|_| None),
+    })
+    .await
+    .map_err(|e| format!("Task error: {}", e))?
+    .map_err(|e| e.to_string())
+}
+
+/// What the activity log says once `action` is done with `name`
+pub fn done_message(name: &str, action: Action, status: Option<&UpdateStatus>) -> String {
+    match (action, status) {
+        (Action::Integrate, _) => format!("Added {} to the app menu", name),
+        (Action::Unintegrate, _) => format!("Took {} out of the app menu", name),
+        (Action::CheckUpdate, Some(UpdateStatus::Available(Some(version)))) => format!("{} {} is available", name, version),
+        (Action::CheckUpdate, Some(UpdateStatus::Available(None))) => format!("An update for {} is available", name),
+        (Action::CheckUpdate, _) => format!("{} is up to date", name),
+        (Action::Update, _) => format!("Updated {}", name),
+        (Action::Remove, _) => format!("Deleted {}", name),
+    }
+}
+
+fn view_appimage<'a>(state: &AppImageState, appimage: &'a AppImage, theme: AppTheme, radius: f32) -> Element<'a, Message> {
+    let busy = state.busy.contains(&appimage.path);
+    let small_button = |label: &'a str, primary: bool, message: Option<Message>| {
+        button(text(label).size(13.0))
+            .on_press_maybe(if busy { None } else { message })
+            .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                is_primary: primary,
+                radius,
+                primary_color: theme.primary(),
+                text_color: if primary { Color::BLACK } else { Color::WHITE },
+                background_color: theme.background(),
+            })))
+            .padding(Padding::new(8.0))
+    };
+    let action = |action| Some(Message::AppImageAction(appimage.path.clone(), action));
+
+    let mut details = vec![plan::format_size(appimage.size)];
+    if appimage.is_integrated() {
+        details.push("In the app menu".to_string());
+    }
+    match (&appimage.update_info, state.updates.get(&appimage.path)) {
+        (None, _) => details.push("No update information".to_string()),
+        (Some(info), None) => details.push(format!("Updates through {}", info.transport())),
+        (Some(_), Some(UpdateStatus::UpToDate)) => details.push("Up to date".to_string()),
+        (Some(_), Some(UpdateStatus::Available(_))) => {}
+    }
+    let title = match &appimage.version {
+        Some(version) => format!("{} {}", appimage.name, version),
+        None => appimage.name.clone(),
+    };
+    let mut heading = row![text(title).size(16.0).style(iced::theme::Text::Color(theme.text())).width(Length::Fill)]
+        .spacing(8)
+        .align_items(alignment::Alignment::Center);
+    if let Some(UpdateStatus::Available(version)) = state.updates.get(&appimage.path) {
+        let label = match version {
+            Some(version) => format!("{} available", version),
+            None => "Update available".to_string(),
+        };
+        heading = heading.push(text(label).size(13.0).style(iced::theme::Text::Color(theme.primary())));
+    }
+
+    let mut actions = row![
+        text(if busy { "Working...".to_string() } else { details.join(" · ") })
+            .size(13.0)
+            .style(iced::theme::Text::Color(theme.secondary_text())),
+        Space::with_width(Length::Fill),
+    ]
+    .spacing(8)
+    .align_items(alignment::Alignment::Center);
+    if appimage.update_info.is_some() {
+        actions = match state.updates.get(&appimage.path) {
+            Some(UpdateStatus::Available(_)) => actions.push(small_button("Update", true, action(Action::Update))),
+            _ => actions.push(small_button("Check for Update", false, action(Action::CheckUpdate))),
+        };
+    }
+    actions = if appimage.is_integrated() {
+        actions.push(small_button("Remove from Menu", false, action(Action::Unintegrate)))
+    } else {
+        actions.push(small_button("Add to Menu", false, action(Action::Integrate)))
+    };
+    actions = if state.confirm_remove.as_ref() == Some(&appimage.path) {
+        actions
+            .push(small_button("Delete for Good", true, action(Action::Remove)))
+            .push(small_button("Keep", false, Some(Message::ConfirmRemoveAppImage(None))))
+    } else {
+        actions.push(small_button("Delete", false, Some(Message::ConfirmRemoveAppImage(Some(appimage.path.clone())))))
+    };
+
+    container(
+        column![
+            heading,
+            text(appimage.path.display().to_string())
+                .size(12.0)
+                .font(iced::Font::MONOSPACE)
+                .style(iced::theme::Text::Color(theme.secondary_text())),
+            actions,
+        ]
+        .spacing(6),
+    )
+    .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+        radius,
+        background: Some(theme.card_background()),
+        elevation: 1.0,
+    })))
+    .width(Length::Fill)
+    .padding(Padding::new(12.0))
+    .into()
+}
+
+// A toolbar with the directories scanned and Check All, then one card per AppImage
+pub fn view(state: &AppImageState, theme: AppTheme, radius: f32) -> Element<'_, Message> {
+    let toolbar_button = |label, message: Option<Message>| {
+        button(text(label).size(14.0))
+            .on_press_maybe(message)
+            .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                is_primary: false,
+                radius,
+                primary_color: theme.primary(),
+                text_color: Color::WHITE,
+                background_color: theme.background(),
+            })))
+            .padding(Padding::new(10.0))
+    };
+    let directories: Vec<String> = state.directories.iter().map(|dir| dir.display().to_string()).collect();
+    let can_check = !state.loading && !state.checkable().is_empty();
+    let toolbar = row![
+        column![
+            text("AppImages").size(20.0).style(iced::theme::Text::Color(theme.text())),
+            text(format!("Looking in {}", directories.join(", ")))
+                .size(13.0)
+                .style(iced::theme::Text::Color(theme.secondary_text())),
+        ]
+        .spacing(2),
+        Space::with_width(Length::Fill),
+        toolbar_button("Rescan", (!state.loading).then_some(Message::LoadAppImages)),
+        toolbar_button("Check All for Updates", can_check.then_some(Message::CheckAppImageUpdates)),
+    ]
+    .spacing(8)
+    .align_items(alignment::Alignment::Center);
+
+    let mut list = Column::new().spacing(8);
+    for appimage in &state.appimages {
+        list = list.push(view_appimage(state, appimage, theme, radius));
+    }
+    if state.appimages.is_empty() {
+        list = list.push(
+            text(if state.loading { "Looking for AppImages..." } else { "No AppImages found. Save them to ~/Applications to manage them here." })
+                .size(16.0)
+                .style(iced::theme::Text::Color(theme.text())),
+        );
+    }
+
+    let status: Element<'_, Message> = match &state.error {
+        Some(error) => text(error).size(13.0).style(iced::theme::Text::Color(theme.danger())).into(),
+        None => Space::with_height(Length::Fixed(0.0)).into(),
+    };
+
+    column![
+        toolbar,
+        status,
+        scrollable(list.padding(Padding::new(4.0)))
+            .style(iced::theme::Scrollable::Custom(Box::new(CustomScrollableStyle {
+                background_color: theme.background(),
+                border_radius: radius,
+            })))
+            .height(Length::Fill),
+    ]
+    .spacing(12)
+    .into()
+}
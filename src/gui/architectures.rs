@@ -0,0 +1,56 @@
+use iced::{
+    widget::{row, Space},
+    Element, Length,
+};
+
+use birdnest_core::dpkg;
+
+use crate::gui::disk_usage::toggle_button;
+use crate::gui::theme::Theme as AppTheme;
+use crate::gui::{Message, PackageInfo};
+
+// dpkg records what each installed package is built for. The Installed tab
+// can show that on every row, and narrow the list to packages for another
+// architecture than the system's, such as i386 libraries kept for 32-bit
+// programs.
+
+#[derive(Debug, Default)]
+pub struct Architectures {
+    /// Show each row's architecture and Multi-Arch status
+    pub shown: bool,
+    /// List only packages built for a foreign architecture
+    pub foreign_only: bool,
+    /// The system's own architecture; None until loaded, or without dpkg
+    pub native: Option<String>,
+}
+
+impl Architectures {
+    /// Whether `package` stays listed. Until the native architecture is
+    /// known, nothing counts as foreign.
+    pub fn keeps(&self, package: &PackageInfo) -> bool {
+        if !self.foreign_only {
+            return true;
+        }
+        match (&package.arch, &self.native) {
+            (Some(arch), Some(native)) => arch.is_foreign(native),
+            _ => false,
+        }
+    }
+}
+
+pub async fn load_native() -> Option<String> {
+    tokio::task::spawn_blocking(dpkg::native_architecture).await.ok().flatten()
+}
+
+// Only lists whose packages say what they are built for get the toggles
+pub fn toggles<'a>(state: &Architectures, packages: &[PackageInfo], theme: AppTheme, radius: f32) -> Element<'a, Message> {
+    if !packages.iter().any(|package| package.arch.is_some()) {
+        return Space::with_width(Length::Fixed(0.0)).into();
+    }
+    row![
+        toggle_button("Architecture", state.shown, Message::ToggleInstalledArch, theme, radius),
+        toggle_button("Foreign Only", state.foreign_only, Message::ToggleForeignArch, theme, radius),
+    ]
+    .spacing(8)
+    .into()
+}
@@ -0,0 +1,126 @@
+use iced::{
+    widget::{button, container, text, Column},
+    Color, Element, Length, Padding,
+};
+
+use birdnest_core::appstream::Catalog;
+use birdnest_core::search_index::SearchIndex;
+
+use crate::gui::theme::Theme as AppTheme;
+use crate::gui::styles::{RoundedButtonStyle, RoundedContainerStyle};
+use crate::gui::Message;
+
+// Package names offered under the header field while typing, from what is
+// already in memory: installed packages, the search index built from the apt
+// lists, and the AppStream catalog. Up and Down pick one, Tab puts it in the
+// field, Enter searches for it and Escape closes the list.
+
+/// Suggestions listed at most
+pub const SHOWN: usize = 8;
+// One letter matches too much to be worth listing
+const MIN_PREFIX: usize = 2;
+
+#[derive(Debug, Default)]
+pub struct Autocomplete {
+    pub suggestions: Vec<String>,
+    /// The suggestion Up and Down moved to
+    pub highlighted: Option<usize>,
+}
+
+impl Autocomplete {
+    pub fn is_open(&self) -> bool {
+        !self.suggestions.is_empty()
+    }
+
+    pub fn set(&mut self, suggestions: Vec<String>) {
+        self.suggestions = suggestions;
+        self.highlighted = None;
+    }
+
+    pub fn close(&mut self) {
+        self.set(Vec::new());
+    }
+
+    pub fn up(&mut self) {
+        self.highlighted = match self.highlighted {
+            None | Some(0) => None,
+            Some(index) => Some(index - 1),
+        };
+    }
+
+    pub fn down(&mut self) {
+        let Some(last) = self.suggestions.len().checked_sub(1) else {
+            return;
+        };
+        self.highlighted = Some(self.highlighted.map_or(0, |index| (index + 1).min(last)));
+    }
+
+    pub fn selected(&self) -> Option<&str> {
+        self.highlighted.and_then(|index| self.suggestions.get(index)).map(String::as_str)
+    }
+}
+
+/// Names starting with what was typed, installed ones first since those are
+/// what people look up most, then the shortest; nothing for a single letter,
+/// several words, or a name that is already typed out in full and is the only match
+pub fn suggest<'a>(
+    query: &str,
+    installed: impl Iterator<Item = &'a str>,
+    index: Option<&SearchIndex>,
+    catalog: &Catalog,
+) -> Vec<String> {
+    let query = query.trim();
+    if query.chars().count() < MIN_PREFIX || query.contains(char::is_whitespace) {
+        return Vec::new();
+    }
+    let prefix = query.to_lowercase();
+
+    let mut installed: Vec<&str> = installed.filter(|name| name.to_lowercase().starts_with(&prefix)).collect();
+    installed.sort_by(|a, b| a.len().cmp(&b.len()).then(a.cmp(b)));
+    let mut available: Vec<String> = index.map(|index| index.complete(&prefix, SHOWN * 2)).unwrap_or_default();
+    available.extend(catalog.packages_starting_with(&prefix).into_iter().map(str::to_string));
+    available.sort_by(|a, b| a.len().cmp(&b.len()).then(a.cmp(b)));
+
+    let mut suggestions: Vec<String> = Vec::new();
+    for name in installed.into_iter().map(str::to_string).chain(available) {
+        if suggestions.len() == SHOWN {
+            break;
+        }
+        if !suggestions.contains(&name) {
+            suggestions.push(name);
+        }
+    }
+    if matches!(suggestions.as_slice(), [only] if only.eq_ignore_ascii_case(query)) {
+        suggestions.clear();
+    }
+    suggestions
+}
+
+pub fn view(state: &Autocomplete, theme: AppTheme, radius: f32) -> Element<'_, Message> {
+    let list = state.suggestions.iter().enumerate().fold(Column::new().spacing(2), |list, (index, name)| {
+        let highlighted = state.highlighted == Some(index);
+        list.push(
+            button(text(name).size(14.0))
+                .on_press(Message::SuggestionPicked(name.clone()))
+                .width(Length::Fill)
+                .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                    is_primary: highlighted,
+                    radius,
+                    primary_color: theme.primary(),
+                    text_color: if highlighted { Color::BLACK } else { theme.text() },
+                    background_color: theme.card_background(),
+                })))
+                .padding(Padding::from([6.0, 12.0])),
+        )
+    });
+
+    container(list)
+        .width(Length::Fill)
+        .padding(Padding::new(6.0))
+        .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+            radius,
+            background: Some(theme.card_background()),
+            elevation: 1.0,
+        })))
+        .into()
+}
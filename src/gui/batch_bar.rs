@@ -0,0 +1,63 @@
+use iced::{
+    alignment, Color,
+    widget::{button, container, row, text},
+    Element, Length, Padding,
+};
+
+use birdnest_core::batch::{Group, Origin};
+
+use crate::gui::styles::{RoundedButtonStyle, RoundedContainerStyle};
+use crate::gui::theme::Theme as AppTheme;
+use crate::gui::{Message, PackageSource};
+
+// Ticks in the Search, Installed, Flatpak and Pikman tabs add up. Once they
+// span more than one kind of change, say packages to install and a Flatpak,
+// a bar above every tab offers to apply them all from one dialog.
+
+/// The batch origin of a package picked in the Pikman tab
+pub fn pikman_origin(source: &PackageSource) -> Origin {
+    let distro = match source {
+        PackageSource::Aur => "aur",
+        PackageSource::Fedora => "fedora",
+        PackageSource::Alpine => "alpine",
+        PackageSource::Default | PackageSource::Flatpak => return Origin::System,
+    };
+    Origin::pikman(distro).unwrap_or(Origin::System)
+}
+
+/// Nothing unless there is more than one group to apply
+pub fn view<'a>(groups: &[Group], theme: AppTheme, radius: f32) -> Element<'a, Message> {
+    if groups.len() < 2 {
+        return row![].into();
+    }
+    let labels: Vec<String> = groups.iter().map(Group::label).collect();
+    let action = |label: &str, primary: bool, message: Message| {
+        button(text(label.to_string()).size(13))
+            .on_press(message)
+            .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                is_primary: primary,
+                radius,
+                primary_color: theme.primary(),
+                text_color: if primary { Color::BLACK } else { Color::WHITE },
+                background_color: theme.background(),
+            })))
+            .padding(Padding::new(8.0))
+    };
+    container(
+        row![
+            text(labels.join(" · ")).size(13).style(iced::theme::Text::Color(theme.text())).width(Length::Fill),
+            action("Clear", false, Message::ClearBatch),
+            action("Review and Apply", true, Message::ReviewBatch),
+        ]
+        .spacing(10)
+        .align_items(alignment::Alignment::Center),
+    )
+    .width(Length::Fill)
+    .padding(Padding::new(12.0))
+    .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+        radius,
+        background: Some(theme.card_background()),
+        elevation: 1.0,
+    })))
+    .into()
+}
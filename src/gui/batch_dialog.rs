@@ -0,0 +1,330 @@
+use iced::{
+    alignment, executor, Color,
+    widget::{button, column, container, row, scrollable, text, Column, Space},
+    Application, Command, Element, Length, Subscription, Theme as IcedTheme, Padding,
+    window,
+};
+use std::sync::Arc;
+
+use crate::gui::command_stream::{self, Step, StreamEvent};
+use crate::gui::scaling;
+use crate::gui::theme::Theme as AppTheme;
+use crate::gui::styles::{RoundedButtonStyle, RoundedContainerStyle, CustomScrollableStyle};
+use birdnest_core::batch::{self, Action, Group, Item, Origin};
+use birdnest_core::elevation;
+use birdnest_core::hooks::HookEvent;
+use birdnest_core::immutable::{self, SystemKind};
+use birdnest_core::package_manager::PackageBackend;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    PasswordlessChecked(bool),
+    Apply,
+    StepOutput(StreamEvent),
+    HooksFinished(String),
+    Close,
+}
+
+// Applies everything ticked across the Search, Installed, Flatpak and Pikman
+// tabs at once. Each group of the batch is its own run through the
+// transaction queue, started when the one before it finishes, so a failed
+// group is reported and the rest still go ahead.
+#[derive(Debug)]
+pub struct BatchDialog {
+    pub groups: Vec<Group>,
+    /// How each finished group went, in order
+    pub results: Vec<Result<(), String>>,
+    /// The group running now
+    running: Option<usize>,
+    pub is_complete: bool,
+    output: String,
+    passwordless: bool,
+    // Bumped for every apply, so each group of each run gets a fresh subscription
+    attempt: u32,
+    pub backend: Arc<dyn PackageBackend>,
+    pub system: SystemKind,
+    pub theme: AppTheme,
+    pub border_radius: f32,
+    // The child window it was opened in
+    pub window: window::Id,
+}
+
+impl BatchDialog {
+    pub fn new(items: &[Item]) -> Self {
+        Self {
+            groups: batch::groups(items),
+            results: Vec::new(),
+            running: None,
+            is_complete: false,
+            output: String::new(),
+            passwordless: false,
+            attempt: 0,
+            backend: crate::gui::create_backend(),
+            system: immutable::detect(),
+            theme: AppTheme::Dark,
+            border_radius: 12.0,
+            window: window::Id::MAIN,
+        }
+    }
+
+    pub fn window_settings() -> window::Settings {
+        window::Settings {
+            size: scaling::fit(iced::Size::new(750.0, 700.0)),
+            min_size: Some(scaling::fit(iced::Size::new(600.0, 450.0))),
+            resizable: true,
+            decorations: true,
+            ..window::Settings::default()
+        }
+    }
+
+    fn item_count(&self) -> usize {
+        self.groups.iter().map(|group| group.names.len()).sum()
+    }
+
+    /// What an item in group `index` shows beside its name
+    pub fn status(&self, index: usize) -> String {
+        match self.results.get(index) {
+            Some(Ok(())) => match self.groups[index].action {
+                Action::Install => "Installed".to_string(),
+                Action::Remove => "Removed".to_string(),
+            },
+            Some(Err(e)) => format!("Failed: {}", e),
+            None if self.running == Some(index) => "Running".to_string(),
+            None if self.running.is_some() => "Waiting".to_string(),
+            None => String::new(),
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        let results: Vec<(Group, Result<(), String>)> = self.groups.iter().cloned().zip(self.results.iter().cloned()).collect();
+        batch::summary(&results)
+    }
+
+    fn steps(&self, index: usize) -> Vec<Step> {
+        let group = &self.groups[index];
+        let commands = group.commands(self.backend.as_ref());
+        if commands.is_empty() {
+            let (backend, packages) = (self.backend.clone(), group.names.clone());
+            let run: Arc<dyn Fn() -> anyhow::Result<()> + Send + Sync> = match group.action {
+                Action::Install => Arc::new(move || backend.install(&packages)),
+                Action::Remove => Arc::new(move || backend.remove(&packages)),
+            };
+            return vec![Step::InProcess(run)];
+        }
+        commands.into_iter().map(|command| Step::Command { elevated: !command.as_user, command }).collect()
+    }
+}
+
+impl Application for BatchDialog {
+    type Message = Message;
+    type Theme = IcedTheme;
+    type Executor = executor::Default;
+    type Flags = Self;
+
+    fn new(flags: Self) -> (Self, Command<Message>) {
+        let cmd = if flags.groups.iter().any(|group| group.origin == Origin::System) {
+            Command::perform(
+                async { tokio::task::spawn_blocking(elevation::passwordless).await.unwrap_or(false) },
+                Message::PasswordlessChecked,
+            )
+        } else {
+            Command::none()
+        };
+        (flags, cmd)
+    }
+
+    fn title(&self) -> String {
+        format!("Apply {} Changes - BirdNest", self.item_count())
+    }
+
+    fn update(&mut self, message: Message) -> Command<Message> {
+        match message {
+            Message::PasswordlessChecked(passwordless) => {
+                self.passwordless = passwordless;
+                Command::none()
+            }
+            Message::Apply => {
+                // Each group runs as the subscription below while it is the running one
+                if !self.groups.is_empty() {
+                    self.attempt += 1;
+                    self.results.clear();
+                    self.output.clear();
+                    self.running = Some(0);
+                }
+                Command::none()
+            }
+            Message::StepOutput(StreamEvent::Line(line)) => {
+                self.output.push_str(&line);
+                self.output.push('\n');
+                scrollable::snap_to(output_id(self.window), scrollable::RelativeOffset::END)
+            }
+            Message::StepOutput(StreamEvent::Finished(result)) => {
+                let Some(index) = self.running else {
+                    return Command::none();
+                };
+                let hooks = result.is_ok().then(|| {
+                    let group = &self.groups[index];
+                    let event = match group.action {
+                        Action::Install => HookEvent::Install,
+                        Action::Remove => HookEvent::Remove,
+                    };
+                    Command::perform(
+                        command_stream::run_hooks(event, group.names.clone(), group.origin == Origin::Flatpak),
+                        Message::HooksFinished,
+                    )
+                });
+                if let Err(e) = &result {
+                    self.output.push_str(&format!("{}: {}\n", self.groups[index].label(), e));
+                }
+                self.results.push(result);
+                self.running = Some(index + 1).filter(|next| *next < self.groups.len());
+                if self.running.is_none() {
+                    self.is_complete = true;
+                    birdnest_core::cache::invalidate_installed();
+                }
+                hooks.unwrap_or_else(Command::none)
+            }
+            Message::HooksFinished(output) => {
+                self.output.push_str(&output);
+                scrollable::snap_to(output_id(self.window), scrollable::RelativeOffset::END)
+            }
+            Message::Close => iced::window::close(self.window),
+        }
+    }
+
+    fn view(&self) -> Element<'_, Message> {
+        let theme = self.theme;
+
+        // Every item under its group, with how the group went
+        let mut list = Column::new().spacing(10);
+        for (index, group) in self.groups.iter().enumerate() {
+            let status = self.status(index);
+            let color = match self.results.get(index) {
+                Some(Ok(())) => Color::from_rgb(0.4, 0.8, 0.4),
+                Some(Err(_)) => theme.danger(),
+                None if self.running == Some(index) => theme.primary(),
+                None => theme.secondary_text(),
+            };
+            let mut items = Column::new().spacing(4);
+            for name in &group.names {
+                items = items.push(
+                    row![
+                        text(name).size(14).style(iced::theme::Text::Color(theme.text())).width(Length::Fill),
+                        text(&status).size(13).style(iced::theme::Text::Color(color)),
+                    ]
+                    .spacing(10),
+                );
+            }
+            list = list.push(column![text(group.label()).size(15).style(iced::theme::Text::Color(theme.primary())), items].spacing(6));
+        }
+
+        let mut notices = Column::new().spacing(6);
+        let system = self.groups.iter().any(|group| group.origin == Origin::System);
+        if system && !self.passwordless && !self.is_complete {
+            notices = notices.push(
+                text("Administrator privileges will be requested")
+                    .size(12)
+                    .style(iced::theme::Text::Color(Color::from_rgb(1.0, 0.8, 0.0))),
+            );
+        }
+        if system && self.system.is_immutable() && !self.is_complete {
+            notices = notices.push(
+                text(immutable::REBOOT_NOTICE).size(12).style(iced::theme::Text::Color(Color::from_rgb(1.0, 0.8, 0.0))),
+            );
+        }
+
+        let status: Element<'_, Message> = if self.running.is_some() {
+            text(format!("Applying {} of {}...", self.results.len() + 1, self.groups.len()))
+                .size(14)
+                .style(iced::theme::Text::Color(theme.text()))
+                .into()
+        } else if self.is_complete {
+            let color = if self.results.iter().all(Result::is_ok) { Color::from_rgb(0.0, 1.0, 0.0) } else { theme.danger() };
+            text(self.summary()).size(14).style(iced::theme::Text::Color(color)).into()
+        } else {
+            Space::with_height(Length::Fixed(0.0)).into()
+        };
+
+        let button_style = |is_primary: bool| {
+            iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                is_primary,
+                radius: self.border_radius,
+                primary_color: theme.primary(),
+                text_color: if is_primary { Color::BLACK } else { Color::WHITE },
+                background_color: theme.background(),
+            }))
+        };
+        let buttons = if self.is_complete {
+            row![
+                Space::with_width(Length::Fill),
+                button("Close").on_press(Message::Close).style(button_style(true)).padding(Padding::new(14.0)),
+            ]
+        } else {
+            let running = self.running.is_some();
+            let apply = button(if running { "Applying..." } else { "Apply All" })
+                .on_press_maybe((!running && !self.groups.is_empty()).then_some(Message::Apply))
+                .style(button_style(true))
+                .padding(Padding::new(14.0));
+            let cancel = button("Cancel")
+                .on_press_maybe((!running).then_some(Message::Close))
+                .style(button_style(false))
+                .padding(Padding::new(14.0));
+            row![Space::with_width(Length::Fill), cancel, apply].spacing(10)
+        }
+        .align_items(alignment::Alignment::Center);
+
+        container(
+            column![
+                text(self.title().trim_end_matches(" - BirdNest").to_string())
+                    .size(24)
+                    .style(iced::theme::Text::Color(theme.text())),
+                scrollable(list).height(Length::FillPortion(2)),
+                notices,
+                status,
+                scrollable(
+                    text(&self.output)
+                        .size(12)
+                        .font(iced::Font::MONOSPACE)
+                        .style(iced::theme::Text::Color(theme.text()))
+                )
+                .id(output_id(self.window))
+                .style(iced::theme::Scrollable::Custom(Box::new(CustomScrollableStyle {
+                    background_color: theme.surface(),
+                    border_radius: self.border_radius,
+                })))
+                .height(Length::FillPortion(3)),
+                buttons,
+            ]
+            .spacing(15)
+            .padding(Padding::new(30.0))
+        )
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+            radius: self.border_radius,
+            background: Some(theme.background()),
+            elevation: 0.0,
+        })))
+        .into()
+    }
+
+    fn subscription(&self) -> Subscription<Message> {
+        match self.running {
+            Some(index) => command_stream::subscription(("batch", self.attempt, index), self.steps(index), Message::StepOutput),
+            None => Subscription::none(),
+        }
+    }
+
+    fn theme(&self) -> IcedTheme {
+        match self.theme {
+            AppTheme::Light => IcedTheme::Light,
+            AppTheme::Dark => IcedTheme::Dark,
+        }
+    }
+}
+
+// The output pane, kept scrolled to the newest line; widget operations reach
+// every open window, so the id is per window
+fn output_id(window: window::Id) -> scrollable::Id {
+    scrollable::Id::new(format!("batch-output-{:?}", window))
+}
@@ -0,0 +1,243 @@
+use iced::{
+    alignment, Color,
+    widget::{button, column, container, row, scrollable, text, Column, Row, Space},
+    Element, Length, Padding,
+};
+
+use birdnest_core::appstream::{Catalog, Component};
+
+use crate::gui::row_menu::{RowAction, RowTarget};
+use crate::gui::theme::Theme as AppTheme;
+use crate::gui::styles::{CustomScrollableStyle, RoundedButtonStyle, RoundedContainerStyle};
+use crate::gui::{app_icon, Message};
+
+// Cards are laid out as many to a row as fit this width, up to four
+pub const CARD_WIDTH: f32 = 280.0;
+const MAX_COLUMNS: usize = 4;
+// Cards rendered at first and added by each "Show more"
+pub const PAGE: usize = 60;
+
+/// A freedesktop main category and the AppStream categories it takes in
+#[derive(Debug)]
+pub struct Category {
+    pub label: &'static str,
+    pub appstream: &'static [&'static str],
+}
+
+pub const CATEGORIES: &[Category] = &[
+    Category { label: "Internet", appstream: &["Network"] },
+    Category { label: "Graphics", appstream: &["Graphics"] },
+    Category { label: "Audio & Video", appstream: &["AudioVideo", "Audio", "Video"] },
+    Category { label: "Games", appstream: &["Game"] },
+    Category { label: "Office", appstream: &["Office"] },
+    Category { label: "Development", appstream: &["Development"] },
+    Category { label: "Education", appstream: &["Education"] },
+    Category { label: "Science", appstream: &["Science"] },
+    Category { label: "System", appstream: &["System", "Settings"] },
+    Category { label: "Utilities", appstream: &["Utility"] },
+];
+
+/// The category open on the Browse tab and how many of its cards show
+#[derive(Debug)]
+pub struct Browse {
+    pub category: usize,
+    pub shown: usize,
+}
+
+impl Default for Browse {
+    fn default() -> Self {
+        Self { category: 0, shown: PAGE }
+    }
+}
+
+impl Browse {
+    pub fn open(&mut self, category: usize) {
+        *self = Self { category: category.min(CATEGORIES.len() - 1), ..Self::default() };
+    }
+}
+
+/// An app on the Browse tab and what installing it takes
+#[derive(Debug, Clone, Copy)]
+pub struct BrowseApp<'a> {
+    pub component: &'a Component,
+    pub flatpak: bool,
+}
+
+impl BrowseApp<'_> {
+    /// The package or Flatpak application ID to install
+    pub fn target(&self) -> RowTarget {
+        match (&self.component.package, self.flatpak) {
+            (Some(package), false) => RowTarget::package(package),
+            _ => RowTarget::flatpak(self.component.id.strip_suffix(".desktop").unwrap_or(&self.component.id)),
+        }
+    }
+}
+
+/// Apps in `category` from the system's and the Flatpak remotes' catalogs,
+/// by name, narrowed to those whose name or summary contains `filter`
+pub fn apps<'a>(system: &'a Catalog, flatpak: &'a Catalog, category: &Category, filter: &str) -> Vec<BrowseApp<'a>> {
+    let filter = filter.trim().to_lowercase();
+    let mut apps: Vec<BrowseApp> = system
+        .in_categories(category.appstream)
+        .into_iter()
+        // Without a package name there is nothing apt can install
+        .filter(|component| component.package.is_some())
+        .map(|component| BrowseApp { component, flatpak: false })
+        .chain(flatpak.in_categories(category.appstream).into_iter().map(|component| BrowseApp { component, flatpak: true }))
+        .filter(|app| {
+            filter.is_empty()
+                || app.component.name.to_lowercase().contains(&filter)
+                || app.component.summary.to_lowercase().contains(&filter)
+        })
+        .collect();
+    apps.sort_by_cached_key(|app| app.component.name.to_lowercase());
+    apps
+}
+
+/// How many cards fit across `width`
+pub fn columns(width: f32) -> usize {
+    ((width / CARD_WIDTH) as usize).clamp(1, MAX_COLUMNS)
+}
+
+pub fn view<'a>(state: &Browse, apps: Vec<BrowseApp<'a>>, columns: usize, icons: bool, theme: AppTheme, radius: f32) -> Element<'a, Message> {
+    let chip = |index: usize, category: &Category| {
+        let selected = index == state.category;
+        button(text(category.label).size(14.0))
+            .on_press(Message::BrowseCategory(index))
+            .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                is_primary: selected,
+                radius,
+                primary_color: theme.primary(),
+                text_color: if selected { Color::BLACK } else { Color::WHITE },
+                background_color: theme.background(),
+            })))
+            .padding(Padding::new(10.0))
+    };
+    // Category buttons wrap to as many rows as the window needs
+    let per_row = columns * 3;
+    let chips = CATEGORIES.chunks(per_row).enumerate().fold(Column::new().spacing(8), |chips, (chunk, categories)| {
+        chips.push(
+            categories
+                .iter()
+                .enumerate()
+                .fold(Row::new().spacing(8), |row, (i, category)| row.push(chip(chunk * per_row + i, category))),
+        )
+    });
+    let header = container(chips)
+        .width(Length::Fill)
+        .padding(Padding::new(16.0))
+        .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+            radius,
+            background: Some(theme.card_background()),
+            elevation: 1.5,
+        })));
+
+    if apps.is_empty() {
+        return column![
+            header,
+            text("No apps here. Installing AppStream metadata (appstream, or a Flatpak remote) fills this tab.")
+                .size(16)
+                .style(iced::theme::Text::Color(theme.text())),
+        ]
+        .spacing(16)
+        .into();
+    }
+
+    let total = apps.len();
+    let mut grid = Column::new().spacing(10).padding(10);
+    for cards in apps[..total.min(state.shown)].chunks(columns) {
+        let mut line = Row::new().spacing(10);
+        for app in cards {
+            line = line.push(view_card(*app, icons, theme, radius));
+        }
+        // Keep a short last row's cards the same width as the rest
+        for _ in cards.len()..columns {
+            line = line.push(Space::with_width(Length::FillPortion(1)));
+        }
+        grid = grid.push(line);
+    }
+    if total > state.shown {
+        grid = grid.push(
+            button(text(format!("Show more ({} of {})", state.shown, total)).size(14.0))
+                .on_press(Message::BrowseShowMore)
+                .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                    is_primary: false,
+                    radius,
+                    primary_color: theme.primary(),
+                    text_color: Color::WHITE,
+                    background_color: theme.background(),
+                })))
+                .padding(Padding::new(10.0)),
+        );
+    }
+
+    column![
+        header,
+        scrollable(grid)
+            .height(Length::Fill)
+            .style(iced::theme::Scrollable::Custom(Box::new(CustomScrollableStyle {
+                background_color: theme.background(),
+                border_radius: radius,
+            }))),
+    ]
+    .spacing(16)
+    .into()
+}
+
+fn view_card<'a>(app: BrowseApp<'a>, icons: bool, theme: AppTheme, radius: f32) -> Element<'a, Message> {
+    let component = app.component;
+    let source = match (&component.package, app.flatpak) {
+        (_, true) => "Flatpak".to_string(),
+        (Some(package), false) => package.clone(),
+        (None, false) => String::new(),
+    };
+    let summary = if component.summary.chars().count() > 90 {
+        format!("{}...", component.summary.chars().take(90).collect::<String>())
+    } else {
+        component.summary.clone()
+    };
+    let small_button = |label| {
+        button(text(label).size(13.0))
+            .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                is_primary: false,
+                radius,
+                primary_color: theme.primary(),
+                text_color: Color::WHITE,
+                background_color: theme.background(),
+            })))
+            .padding(Padding::new(8.0))
+    };
+
+    let mut actions = row![Space::with_width(Length::Fill)].spacing(8);
+    // The details pane asks the package backend, which doesn't know Flatpaks
+    if let (Some(package), false) = (&component.package, app.flatpak) {
+        actions = actions.push(small_button("Details").on_press(Message::BrowseDetails(package.clone())));
+    }
+    actions = actions.push(small_button("Install").on_press(Message::RowAction(app.target(), RowAction::Install)));
+
+    container(
+        column![
+            row![
+                app_icon(Some(component), true, icons),
+                column![
+                    text(&component.name).size(18.0).style(iced::theme::Text::Color(theme.text())),
+                    text(source).size(12.0).style(iced::theme::Text::Color(theme.secondary_text())),
+                ]
+                .spacing(2),
+            ]
+            .spacing(10)
+            .align_items(alignment::Alignment::Center),
+            text(summary).size(13.0).style(iced::theme::Text::Color(theme.text())),
+            actions,
+        ]
+        .spacing(8),
+    )
+    .width(Length::FillPortion(1))
+    .padding(Padding::new(12.0))
+    .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+        radius,
+        background: Some(theme.card_background()),
+        elevation: 1.0,
+    })))
+    .into()
+}
@@ -0,0 +1,99 @@
+use iced::{
+    Color,
+    widget::{button, column, row, text, Column},
+    Element, Length, Padding,
+};
+use std::sync::Arc;
+
+use birdnest_core::changelog::{self, ChangelogEntry};
+use birdnest_core::package_manager::PackageBackend;
+
+use crate::gui::theme::Theme as AppTheme;
+use crate::gui::styles::RoundedButtonStyle;
+use crate::gui::Message;
+
+/// Versions the details pane lists; a package's whole history can run to
+/// hundreds
+pub const MAX_ENTRIES: usize = 10;
+
+/// A package's changelog in the details pane or under an update, fetched
+/// only when asked for since apt downloads it
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Changelog {
+    #[default]
+    NotLoaded,
+    Loading,
+    Loaded(Vec<ChangelogEntry>),
+    Failed(String),
+}
+
+impl Changelog {
+    pub fn set(&mut self, result: Result<Vec<ChangelogEntry>, String>) {
+        *self = match result {
+            Ok(entries) => Changelog::Loaded(entries),
+            Err(e) => Changelog::Failed(e),
+        };
+    }
+}
+
+/// The package's changelog from the backend, cut down to what upgrading
+/// from `installed` brings when that is given
+pub async fn load(backend: Arc<dyn PackageBackend>, package: String, installed: Option<String>) -> Result<Vec<ChangelogEntry>, String> {
+    let entries = tokio::task::spawn_blocking(move || backend.changelog(&package))
+        .await
+        .map_err(|e| format!("Task error: {}", e))?
+        .map_err(|e| e.to_string())?;
+    Ok(match installed {
+        Some(installed) => changelog::newer_than(entries, &installed),
+        None => entries,
+    })
+}
+
+/// The newest `limit` versions of `changelog`, or a button that loads it
+pub fn view<'a>(changelog: &'a Changelog, load: Message, limit: usize, theme: AppTheme, radius: f32) -> Element<'a, Message> {
+    let note = |message: String, color: Color| -> Element<'a, Message> { text(message).size(13.0).style(iced::theme::Text::Color(color)).into() };
+    match changelog {
+        Changelog::NotLoaded => button(text("Show changelog").size(13.0))
+            .on_press(load)
+            .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                is_primary: false,
+                radius,
+                primary_color: theme.primary(),
+                text_color: Color::WHITE,
+                background_color: theme.background(),
+            })))
+            .padding(Padding::new(8.0))
+            .into(),
+        Changelog::Loading => note("Fetching the changelog...".to_string(), theme.secondary_text()),
+        Changelog::Failed(e) => note(format!("No changelog: {}", e), theme.danger()),
+        Changelog::Loaded(entries) if entries.is_empty() => note("The changelog lists no changes".to_string(), theme.secondary_text()),
+        Changelog::Loaded(entries) => {
+            let mut list = Column::new().spacing(10).width(Length::Fill);
+            for entry in entries.iter().take(limit) {
+                list = list.push(view_entry(entry, theme));
+            }
+            match entries.len().saturating_sub(limit) {
+                0 => {}
+                1 => list = list.push(note("1 older version not shown".to_string(), theme.secondary_text())),
+                more => list = list.push(note(format!("{} older versions not shown", more), theme.secondary_text())),
+            }
+            list.into()
+        }
+    }
+}
+
+fn view_entry(entry: &ChangelogEntry, theme: AppTheme) -> Element<'_, Message> {
+    let mut heading = row![text(&entry.version).size(14.0).font(iced::Font::MONOSPACE).style(iced::theme::Text::Color(theme.primary()))]
+        .spacing(8);
+    if let Some(urgency) = entry.urgency.as_deref().filter(|u| changelog::is_urgent(u)) {
+        heading = heading.push(text(format!("{} urgency", urgency)).size(12.0).style(iced::theme::Text::Color(theme.danger())));
+    }
+    if let Some(date) = &entry.date {
+        heading = heading.push(text(date).size(12.0).style(iced::theme::Text::Color(theme.secondary_text())));
+    }
+    let mut changes = column![heading].spacing(2);
+    for change in &entry.changes {
+        changes = changes.push(text(format!("• {}", change)).size(13.0).style(iced::theme::Text::Color(theme.text())));
+    }
+    changes.into()
+}
@@ -0,0 +1,205 @@
+use iced::{
+    alignment, executor, Color,
+    widget::{button, column, container, row, scrollable, text, Column, Space},
+    Application, Command, Element, Length, Pixels, Settings, Theme as IcedTheme, Padding,
+    window,
+};
+
+use crate::gui::scaling;
+use crate::gui::theme::Theme as AppTheme;
+use crate::gui::styles::{RoundedButtonStyle, RoundedContainerStyle, CustomScrollableStyle};
+use crate::manpage::{self, CommandHelp};
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    SelectCommand(usize),
+    Close,
+}
+
+// Help > CLI reference: every documented command, generated from the same
+// clap definitions as `birdnest --help` and `birdnest man`
+#[derive(Debug)]
+pub struct CliReferenceDialog {
+    commands: Vec<CommandHelp>,
+    selected: usize,
+    pub theme: AppTheme,
+    pub border_radius: f32,
+    // MAIN when run standalone, otherwise the child window it was opened in
+    pub window: window::Id,
+}
+
+impl CliReferenceDialog {
+    pub fn new() -> Self {
+        Self {
+            commands: manpage::reference(),
+            selected: 0,
+            theme: AppTheme::Dark,
+            border_radius: 12.0,
+            window: window::Id::MAIN,
+        }
+    }
+
+    pub fn window_settings() -> window::Settings {
+        window::Settings {
+            size: scaling::fit(iced::Size::new(960.0, 680.0)),
+            min_size: Some(scaling::fit(iced::Size::new(700.0, 450.0))),
+            resizable: true,
+            decorations: true,
+            ..window::Settings::default()
+        }
+    }
+
+    pub fn run_separate_window() -> Result<(), iced::Error> {
+        let dialog = Self::new();
+
+        <CliReferenceDialog as Application>::run(Settings {
+            window: Self::window_settings(),
+            flags: dialog,
+            default_text_size: Pixels(14.0),
+            antialiasing: true,
+            id: None,
+            fonts: Vec::new(),
+            default_font: iced::Font::DEFAULT,
+        })
+    }
+}
+
+impl Application for CliReferenceDialog {
+    type Message = Message;
+    type Theme = IcedTheme;
+    type Executor = executor::Default;
+    type Flags = Self;
+
+    fn new(flags: Self) -> (Self, Command<Message>) {
+        (flags, Command::none())
+    }
+
+    fn title(&self) -> String {
+        "CLI Reference - BirdNest".to_string()
+    }
+
+    fn update(&mut self, message: Message) -> Command<Message> {
+        match message {
+            Message::SelectCommand(index) => {
+                if index < self.commands.len() {
+                    self.selected = index;
+                }
+                Command::none()
+            }
+            Message::Close => {
+                iced::window::close(self.window)
+            }
+        }
+    }
+
+    fn view(&self) -> Element<'_, Message> {
+        let theme = self.theme;
+
+        let mut command_list = Column::new().spacing(4);
+        for (index, entry) in self.commands.iter().enumerate() {
+            let is_selected = index == self.selected;
+            command_list = command_list.push(
+                button(text(&entry.path).size(13))
+                    .on_press(Message::SelectCommand(index))
+                    .width(Length::Fill)
+                    .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                        is_primary: is_selected,
+                        radius: self.border_radius,
+                        primary_color: theme.primary(),
+                        text_color: if is_selected { Color::BLACK } else { Color::WHITE },
+                        background_color: theme.background(),
+                    })))
+                    .padding(Padding::new(8.0)),
+            );
+        }
+
+        let (heading, about, help) = match self.commands.get(self.selected) {
+            Some(entry) => (entry.path.as_str(), entry.about.as_str(), entry.help.as_str()),
+            None => ("birdnest", "", ""),
+        };
+
+        container(
+            column![
+                text("CLI Reference")
+                    .size(24)
+                    .style(iced::theme::Text::Color(theme.text())),
+                text("Everything in the GUI can also be done from a terminal. Run `birdnest man` to generate man pages.")
+                    .size(13)
+                    .style(iced::theme::Text::Color(theme.secondary_text())),
+                row![
+                    scrollable(command_list.padding(Padding::new(8.0)))
+                        .style(iced::theme::Scrollable::Custom(Box::new(CustomScrollableStyle {
+                            background_color: theme.surface(),
+                            border_radius: self.border_radius,
+                        })))
+                        .width(Length::Fixed(260.0))
+                        .height(Length::Fill),
+                    container(
+                        column![
+                            text(heading)
+                                .size(18)
+                                .font(iced::Font::MONOSPACE)
+                                .style(iced::theme::Text::Color(theme.primary())),
+                            text(about)
+                                .size(14)
+                                .style(iced::theme::Text::Color(theme.secondary_text())),
+                            scrollable(
+                                text(help)
+                                    .size(12)
+                                    .font(iced::Font::MONOSPACE)
+                                    .style(iced::theme::Text::Color(theme.text()))
+                            )
+                            .style(iced::theme::Scrollable::Custom(Box::new(CustomScrollableStyle {
+                                background_color: theme.surface(),
+                                border_radius: self.border_radius,
+                            })))
+                            .height(Length::Fill),
+                        ]
+                        .spacing(8)
+                    )
+                    .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+                        radius: self.border_radius,
+                        background: Some(theme.card_background()),
+                        elevation: 1.0,
+                    })))
+                    .width(Length::Fill)
+                    .height(Length::Fill)
+                    .padding(Padding::new(12.0)),
+                ]
+                .spacing(12)
+                .height(Length::Fill),
+                row![
+                    Space::with_width(Length::Fill),
+                    button("Close")
+                        .on_press(Message::Close)
+                        .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                            is_primary: true,
+                            radius: self.border_radius,
+                            primary_color: theme.primary(),
+                            text_color: Color::WHITE,
+                            background_color: theme.background(),
+                        })))
+                        .padding(Padding::new(14.0)),
+                ]
+                .align_items(alignment::Alignment::Center),
+            ]
+            .spacing(15)
+            .padding(Padding::new(30.0))
+        )
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+            radius: self.border_radius,
+            background: Some(theme.background()),
+            elevation: 0.0,
+        })))
+        .into()
+    }
+
+    fn theme(&self) -> IcedTheme {
+        match self.theme {
+            AppTheme::Light => IcedTheme::Light,
+            AppTheme::Dark => IcedTheme::Dark,
+        }
+    }
+}
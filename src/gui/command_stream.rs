@@ -0,0 +1,236 @@
+use futures::channel::mpsc::UnboundedSender;
+use futures::{Stream, StreamExt};
+use iced::Subscription;
+use std::hash::Hash;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Arc;
+use tokio::process::Command as TokioCommand;
+
+use birdnest_core::elevation;
+use birdnest_core::events::{self, Event};
+use birdnest_core::hooks::{self, HookEvent};
+use birdnest_core::utils::CommandBuilder;
+use birdnest_core::package_manager::BackendCommand;
+
+use crate::gui::transaction_queue;
+
+/// What a running transaction reports to its dialog
+#[derive(Debug, Clone)]
+pub enum StreamEvent {
+    /// A line of stdout or stderr, or the "$ command" header before each step
+    Line(String),
+    /// Every step ran, or the first failing one stopped the run; success is
+    /// decided by exit status alone
+    Finished(Result<(), String>),
+}
+
+/// One step of a transaction
+pub enum Step {
+    /// Run a command, through the user's elevation method when `elevated`
+    Command { command: BackendCommand, elevated: bool },
+    /// Call into a backend without a command line (the mock) in-process
+    InProcess(Arc<dyn Fn() -> anyhow::Result<()> + Send + Sync>),
+}
+
+/// The unelevated command a stream is running, so a dialog can pause it
+/// (SIGSTOP) and resume it (SIGCONT). Elevated commands run as root and
+/// can't be signalled; those steps are never recorded here.
+#[derive(Debug, Clone, Default)]
+pub struct ProcessHandle {
+    // 0 between commands
+    pid: Arc<AtomicU32>,
+    // Kept across steps: the next command starts paused if this one was
+    paused: Arc<AtomicBool>,
+}
+
+impl ProcessHandle {
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::SeqCst);
+        self.signal(libc::SIGSTOP);
+    }
+
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::SeqCst);
+        self.signal(libc::SIGCONT);
+    }
+
+    fn set(&self, pid: Option<u32>) {
+        self.pid.store(pid.unwrap_or(0), Ordering::SeqCst);
+        if pid.is_some() && self.is_paused() {
+            self.signal(libc::SIGSTOP);
+        }
+    }
+
+    fn signal(&self, signal: libc::c_int) {
+        let pid = self.pid.load(Ordering::SeqCst);
+        if pid != 0 {
+            tracing::debug!("command_stream: Sending signal {} to {}", signal, pid);
+            unsafe { libc::kill(pid as libc::pid_t, signal) };
+        }
+    }
+}
+
+/// Run `steps` in order while the subscription is active, streaming their
+/// output. `id` must change for every run, so a retry starts a fresh one.
+pub fn subscription<I, M>(id: I, steps: Vec<Step>, on_event: fn(StreamEvent) -> M) -> Subscription<M>
+where
+    I: Hash + 'static,
+    M: 'static,
+{
+    iced::subscription::run_with_id(id, stream(steps).map(on_event))
+}
+
+/// Like [`subscription`], recording each unelevated command in `process`
+pub fn pausable_subscription<I, M>(id: I, steps: Vec<Step>, process: ProcessHandle, on_event: fn(StreamEvent) -> M) -> Subscription<M>
+where
+    I: Hash + 'static,
+    M: 'static,
+{
+    iced::subscription::run_with_id(id, pausable_stream(steps, process).map(on_event))
+}
+
+pub fn stream(steps: Vec<Step>) -> impl Stream<Item = StreamEvent> {
+    pausable_stream(steps, ProcessHandle::default())
+}
+
+pub fn pausable_stream(steps: Vec<Step>, process: ProcessHandle) -> impl Stream<Item = StreamEvent> {
+    let (tx, rx) = futures::channel::mpsc::unbounded();
+    // Started from inside the executor, where the tokio runtime is available
+    futures::stream::once(async move {
+        tokio::spawn(async move {
+            let ticket = transaction_queue::join(describe(&steps));
+            let ahead = ticket.ahead();
+            if ahead > 0 {
+                let _ = tx.unbounded_send(StreamEvent::Line(format!("Waiting for {} earlier transaction(s) to finish...", ahead)));
+            }
+            let turn = ticket.turn().await;
+            let result = run_steps(steps, ticket.id(), &process, &tx).await;
+            ticket.finish(&result);
+            drop(turn);
+            if let Err(e) = &result {
+                tracing::warn!("command_stream: Step failed: {}", e);
+            }
+            let _ = tx.unbounded_send(StreamEvent::Finished(result));
+        });
+        rx
+    })
+    .flatten()
+}
+
+/// Run the config's hooks after a transaction succeeded; returns their output
+/// and how each went, for the dialog's terminal
+pub async fn run_hooks(event: HookEvent, packages: Vec<String>, flatpak: bool) -> String {
+    let outcomes = tokio::task::spawn_blocking(move || hooks::run_configured(event, &packages, flatpak))
+        .await
+        .unwrap_or_default();
+    outcomes.iter().map(|outcome| format!("{}{}\n", outcome.output, outcome.summary())).collect()
+}
+
+// What the transaction queue lists for a run: its first command
+fn describe(steps: &[Step]) -> String {
+    match steps.first() {
+        Some(Step::Command { command, .. }) => command.display(),
+        Some(Step::InProcess(_)) => "Package transaction".to_string(),
+        None => "Nothing to do".to_string(),
+    }
+}
+
+async fn run_steps(steps: Vec<Step>, id: u64, process: &ProcessHandle, tx: &UnboundedSender<StreamEvent>) -> Result<(), String> {
+    for step in steps {
+        match step {
+            Step::Command { command, elevated } => run_command(&command, elevated, id, process, tx).await?,
+            Step::InProcess(run) => tokio::task::spawn_blocking(move || run())
+                .await
+                .map_err(|e| format!("Task error: {}", e))?
+                .map_err(|e| e.to_string())?,
+        }
+    }
+    Ok(())
+}
+
+async fn run_command(
+    command: &BackendCommand,
+    elevated: bool,
+    id: u64,
+    process: &ProcessHandle,
+    tx: &UnboundedSender<StreamEvent>,
+) -> Result<(), String> {
+    use tokio::io::{AsyncBufReadExt, BufReader};
+
+    // The user already confirmed in the dialog
+    let (std_cmd, method) = CommandBuilder::new(&command.program)
+        .args(&command.args())
+        .elevated(elevated)
+        .noninteractive()
+        .build()
+        .map_err(|e| e.to_string())?;
+    let prompt = match method {
+        Some(method) => format!("$ {} {}", method.command_prefix(), command.display()),
+        None => format!("$ {}", command.display()),
+    };
+    let _ = tx.unbounded_send(StreamEvent::Line(prompt));
+    if let Some(method) = method {
+        if !tokio::task::spawn_blocking(move || elevation::is_passwordless(method)).await.unwrap_or(false) {
+            events::publish(Event::NeedsInput {
+                id,
+                prompt: format!("Authenticate with {} to run {}", method.command_prefix(), command.program),
+            });
+        }
+    }
+    let mut cmd = TokioCommand::from(std_cmd);
+    tracing::debug!("command_stream: Executing command: {}", command.display());
+    cmd.stdin(std::process::Stdio::null());
+    cmd.stdout(std::process::Stdio::piped());
+    cmd.stderr(std::process::Stdio::piped());
+
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("Failed to run {}: {}", command.program, e))?;
+    if !elevated {
+        process.set(child.id());
+    }
+    let stdout = child.stdout.take().ok_or("Failed to capture stdout")?;
+    let stderr = child.stderr.take().ok_or("Failed to capture stderr")?;
+    let mut stdout_reader = BufReader::new(stdout).lines();
+    let mut stderr_reader = BufReader::new(stderr).lines();
+
+    let (mut stdout_done, mut stderr_done) = (false, false);
+    while !(stdout_done && stderr_done) {
+        tokio::select! {
+            line = stdout_reader.next_line(), if !stdout_done => match line {
+                Ok(Some(line)) => send_line(id, line, tx),
+                _ => stdout_done = true,
+            },
+            line = stderr_reader.next_line(), if !stderr_done => match line {
+                Ok(Some(line)) => send_line(id, line, tx),
+                _ => stderr_done = true,
+            },
+        }
+    }
+
+    let status = child.wait().await;
+    process.set(None);
+    let status = status.map_err(|e| e.to_string())?;
+    tracing::debug!("command_stream: {} exited with {:?}", command.program, status.code());
+    if elevated && (status.code() == Some(126) || status.code() == Some(127)) {
+        return Err("Authentication cancelled or failed. Please try again.".to_string());
+    }
+    if !status.success() {
+        return Err(match status.code() {
+            Some(code) => format!("{} exited with code {}", command.program, code),
+            None => format!("{} was terminated by a signal", command.program),
+        });
+    }
+    Ok(())
+}
+
+// Output goes to the dialog running the transaction and, for everything else
+// watching, onto the event bus
+fn send_line(id: u64, line: String, tx: &UnboundedSender<StreamEvent>) {
+    events::publish(Event::Progress { id, line: line.clone() });
+    let _ = tx.unbounded_send(StreamEvent::Line(line));
+}
@@ -1,25 +1,83 @@
 use iced::{
     alignment, executor, Color,
-    widget::{button, column, container, row, scrollable, text, Space},
-    Application, Command, Element, Length, Pixels, Settings, Theme as IcedTheme, Padding,
+    widget::{button, column, container, row, scrollable, text, Column, Space},
+    Application, Command, Element, Length, Pixels, Settings, Subscription, Theme as IcedTheme, Padding,
     window,
 };
+use std::sync::Arc;
 
+use crate::gui::command_stream::{self, Step, StreamEvent};
+use crate::gui::scaling;
 use crate::gui::theme::Theme as AppTheme;
 use crate::gui::styles::{RoundedButtonStyle, RoundedContainerStyle, CustomScrollableStyle};
+use birdnest_core::elevation;
+use birdnest_core::hooks::HookEvent;
+use birdnest_core::package_manager::{self, PackageBackend};
 
 #[derive(Debug, Clone)]
 pub enum Message {
+    ClosureLoaded(Vec<String>),
+    PasswordlessChecked(bool),
+    Resolve(Resolution),
+    ResolutionOutput(StreamEvent),
+    HooksFinished(String),
     Close,
 }
 
+/// A way out of a removal that failed, run from the conflict dialog
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Resolution {
+    /// Remove the packages again along with everything that depends on them
+    RemoveDependents,
+    /// Let apt finish or undo what an earlier transaction left half done
+    FixBroken,
+    /// Give up on the removal and mark the packages as wanted
+    KeepPackage,
+}
+
+impl Resolution {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Resolution::RemoveDependents => "Remove Dependent Packages Too",
+            Resolution::FixBroken => "Run apt --fix-broken install",
+            Resolution::KeepPackage => "Keep Package",
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct ConflictDialog {
-    package_names: Vec<String>,
+    pub package_names: Vec<String>,
     conflict_message: String,
-    terminal_output: String,
-    theme: AppTheme,
-    border_radius: f32,
+    /// The failed removal's output, then each resolution's as it runs
+    pub terminal_output: String,
+    /// The packages and everything depending on them; None while loading
+    pub closure: Option<Vec<String>>,
+    /// The resolutions the backend can run, in the order they are shown
+    pub offered: Vec<Resolution>,
+    pub running: Option<Resolution>,
+    /// The last resolution run and how it went
+    pub result: Option<(Resolution, Result<(), String>)>,
+    passwordless: bool,
+    // Bumped for every resolution, so each gets a fresh subscription
+    attempt: u32,
+    pub backend: Arc<dyn PackageBackend>,
+    pub theme: AppTheme,
+    pub border_radius: f32,
+    // MAIN when run standalone, otherwise the child window it was opened in
+    pub window: window::Id,
+}
+
+async fn load_closure(backend: Arc<dyn PackageBackend>, packages: Vec<String>) -> Vec<String> {
+    let closure = tokio::task::spawn_blocking(move || package_manager::removal_closure(backend.as_ref(), &packages)).await;
+    match closure {
+        Ok(Ok(closure)) => closure,
+        Ok(Err(e)) => {
+            tracing::debug!("load_closure: {}", e);
+            Vec::new()
+        }
+        Err(_) => Vec::new(),
+    }
 }
 
 impl ConflictDialog {
@@ -28,22 +86,81 @@ impl ConflictDialog {
             package_names,
             conflict_message,
             terminal_output,
+            closure: None,
+            offered: Vec::new(),
+            running: None,
+            result: None,
+            passwordless: false,
+            attempt: 0,
+            backend: crate::gui::create_backend(),
             theme: AppTheme::Dark,
             border_radius: 12.0,
+            window: window::Id::MAIN,
+        }
+    }
+
+    /// What depends on the packages, which removing them too would take along
+    pub fn dependents(&self) -> Vec<&str> {
+        self.closure
+            .iter()
+            .flatten()
+            .filter(|name| !self.package_names.contains(*name))
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Whether a resolution has already worked, leaving nothing to resolve
+    pub fn is_resolved(&self) -> bool {
+        matches!(self.result, Some((_, Ok(()))))
+    }
+
+    fn describe(&self, resolution: Resolution) -> String {
+        match resolution {
+            Resolution::RemoveDependents => format!("Also remove {}, which depend on it", self.dependents().join(", ")),
+            Resolution::FixBroken => "Install or remove what the installed packages are missing, as an interrupted earlier transaction can leave them".to_string(),
+            Resolution::KeepPackage => "Leave it installed and mark it as wanted, so it isn't offered for autoremove later".to_string(),
+        }
+    }
+
+    fn outcome(&self) -> Option<String> {
+        let (resolution, result) = self.result.as_ref()?;
+        Some(match (resolution, result) {
+            (Resolution::RemoveDependents, Ok(())) => format!("Removed {} packages", self.closure.as_ref().map_or(0, Vec::len)),
+            (Resolution::FixBroken, Ok(())) => "Broken packages fixed; the removal can be tried again".to_string(),
+            (Resolution::KeepPackage, Ok(())) => format!("Kept {}", self.package_names.join(", ")),
+            (resolution, Err(e)) => format!("{} failed: {}", resolution.label(), e),
+        })
+    }
+
+    fn steps(&self, resolution: Resolution) -> Vec<Step> {
+        let closure = self.closure.clone().unwrap_or_default();
+        let commands = match resolution {
+            Resolution::RemoveDependents => self.backend.remove_commands(&closure),
+            Resolution::FixBroken => self.backend.fix_broken_commands(),
+            Resolution::KeepPackage => self.backend.keep_commands(&self.package_names),
+        };
+        if commands.is_empty() && resolution == Resolution::RemoveDependents {
+            let backend = self.backend.clone();
+            return vec![Step::InProcess(Arc::new(move || backend.remove(&closure)))];
+        }
+        commands.into_iter().map(|command| Step::Command { elevated: !command.as_user, command }).collect()
+    }
+
+    pub fn window_settings() -> window::Settings {
+        window::Settings {
+            size: scaling::fit(iced::Size::new(800.0, 600.0)),
+            min_size: Some(scaling::fit(iced::Size::new(600.0, 400.0))),
+            resizable: true,
+            decorations: true,
+            ..window::Settings::default()
         }
     }
 
     pub fn run_separate_window(package_names: Vec<String>, conflict_message: String, terminal_output: String) -> Result<(), iced::Error> {
         let dialog = Self::new(package_names, conflict_message, terminal_output);
 
-        let mut window_settings = window::Settings::default();
-        window_settings.size = iced::Size::new(800.0, 600.0);
-        window_settings.min_size = Some(iced::Size::new(600.0, 400.0));
-        window_settings.resizable = true;
-        window_settings.decorations = true;
-
         <ConflictDialog as Application>::run(Settings {
-            window: window_settings,
+            window: Self::window_settings(),
             flags: dialog,
             default_text_size: Pixels(14.0),
             antialiasing: true,
@@ -60,8 +177,20 @@ impl Application for ConflictDialog {
     type Executor = executor::Default;
     type Flags = Self;
 
-    fn new(flags: Self) -> (Self, Command<Message>) {
-        (flags, Command::none())
+    fn new(mut flags: Self) -> (Self, Command<Message>) {
+        // Removing the dependents too is offered once they are known
+        if !flags.backend.fix_broken_commands().is_empty() {
+            flags.offered.push(Resolution::FixBroken);
+        }
+        if !flags.backend.keep_commands(&flags.package_names).is_empty() {
+            flags.offered.push(Resolution::KeepPackage);
+        }
+        let load = Command::perform(load_closure(flags.backend.clone(), flags.package_names.clone()), Message::ClosureLoaded);
+        let check_passwordless = Command::perform(
+            async { tokio::task::spawn_blocking(elevation::passwordless).await.unwrap_or(false) },
+            Message::PasswordlessChecked,
+        );
+        (flags, Command::batch([load, check_passwordless]))
     }
 
     fn title(&self) -> String {
@@ -74,8 +203,52 @@ impl Application for ConflictDialog {
 
     fn update(&mut self, message: Message) -> Command<Message> {
         match message {
+            Message::ClosureLoaded(closure) => {
+                self.closure = Some(closure);
+                if !self.dependents().is_empty() {
+                    self.offered.insert(0, Resolution::RemoveDependents);
+                }
+                Command::none()
+            }
+            Message::PasswordlessChecked(passwordless) => {
+                self.passwordless = passwordless;
+                Command::none()
+            }
+            Message::Resolve(resolution) => {
+                // Runs as the subscription below while it is the running one
+                if self.running.is_none() && !self.is_resolved() {
+                    self.attempt += 1;
+                    self.result = None;
+                    self.running = Some(resolution);
+                    self.terminal_output.push_str(&format!("\n--- {} ---\n", resolution.label()));
+                }
+                scrollable::snap_to(output_id(self.window), scrollable::RelativeOffset::END)
+            }
+            Message::ResolutionOutput(StreamEvent::Line(line)) => {
+                self.terminal_output.push_str(&line);
+                self.terminal_output.push('\n');
+                scrollable::snap_to(output_id(self.window), scrollable::RelativeOffset::END)
+            }
+            Message::ResolutionOutput(StreamEvent::Finished(result)) => {
+                let Some(resolution) = self.running.take() else {
+                    return Command::none();
+                };
+                let hooks = (result.is_ok() && resolution == Resolution::RemoveDependents).then(|| {
+                    let removed = self.closure.clone().unwrap_or_default();
+                    Command::perform(command_stream::run_hooks(HookEvent::Remove, removed, false), Message::HooksFinished)
+                });
+                if result.is_ok() {
+                    birdnest_core::cache::invalidate_installed();
+                }
+                self.result = Some((resolution, result));
+                hooks.unwrap_or_else(Command::none)
+            }
+            Message::HooksFinished(output) => {
+                self.terminal_output.push_str(&output);
+                scrollable::snap_to(output_id(self.window), scrollable::RelativeOffset::END)
+            }
             Message::Close => {
-                iced::window::close(iced::window::Id::MAIN)
+                iced::window::close(self.window)
             }
         }
     }
@@ -101,6 +274,51 @@ impl Application for ConflictDialog {
         // Parse the conflict message to extract the main reason
         let (main_reason, details) = parse_conflict_message(&self.conflict_message);
 
+        let button_style = |is_primary: bool| {
+            iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                is_primary,
+                radius: self.border_radius,
+                primary_color: theme.primary(),
+                text_color: if is_primary { Color::BLACK } else { Color::WHITE },
+                background_color: theme.background(),
+            }))
+        };
+
+        // Each resolution with what it does; they stay available until one works
+        let mut resolutions = Column::new().spacing(10);
+        if !self.offered.is_empty() {
+            resolutions = resolutions.push(text("How to resolve it:").size(16).style(iced::theme::Text::Color(theme.text())));
+        }
+        let idle = self.running.is_none() && !self.is_resolved();
+        for resolution in &self.offered {
+            let label = if self.running == Some(*resolution) { "Running..." } else { resolution.label() };
+            resolutions = resolutions.push(
+                row![
+                    text(self.describe(*resolution))
+                        .size(13)
+                        .style(iced::theme::Text::Color(theme.secondary_text()))
+                        .width(Length::Fill),
+                    button(text(label).size(13))
+                        .on_press_maybe(idle.then_some(Message::Resolve(*resolution)))
+                        .style(button_style(false))
+                        .padding(Padding::new(10.0)),
+                ]
+                .spacing(10)
+                .align_items(alignment::Alignment::Center),
+            );
+        }
+        if !self.passwordless && idle && !self.offered.is_empty() {
+            resolutions = resolutions.push(
+                text("Administrator privileges will be requested")
+                    .size(12)
+                    .style(iced::theme::Text::Color(Color::from_rgb(1.0, 0.8, 0.0))),
+            );
+        }
+        if let Some(outcome) = self.outcome() {
+            let color = if self.is_resolved() { Color::from_rgb(0.0, 1.0, 0.0) } else { theme.danger() };
+            resolutions = resolutions.push(text(outcome).size(14).style(iced::theme::Text::Color(color)));
+        }
+
         container(
             column![
                 text(&title_text)
@@ -166,6 +384,7 @@ impl Application for ConflictDialog {
                                 .font(iced::Font::MONOSPACE)
                                 .style(iced::theme::Text::Color(theme.text()))
                         )
+                        .id(output_id(self.window))
                         .style(iced::theme::Scrollable::Custom(Box::new(CustomScrollableStyle {
                             background_color: theme.surface(),
                             border_radius: self.border_radius,
@@ -176,18 +395,13 @@ impl Application for ConflictDialog {
                 } else {
                     column![].spacing(0)
                 },
+                resolutions,
                 Space::with_height(Length::Fill),
                 row![
                     Space::with_width(Length::Fill),
                     button("Close")
-                        .on_press(Message::Close)
-                        .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
-                            is_primary: true,
-                            radius: self.border_radius,
-                            primary_color: theme.primary(),
-                            text_color: Color::WHITE,
-                            background_color: theme.background(),
-                        })))
+                        .on_press_maybe(self.running.is_none().then_some(Message::Close))
+                        .style(button_style(true))
                         .padding(Padding::new(14.0)),
                 ]
                 .spacing(10)
@@ -206,6 +420,13 @@ impl Application for ConflictDialog {
         .into()
     }
 
+    fn subscription(&self) -> Subscription<Message> {
+        match self.running {
+            Some(resolution) => command_stream::subscription(("conflict", self.attempt), self.steps(resolution), Message::ResolutionOutput),
+            None => Subscription::none(),
+        }
+    }
+
     fn theme(&self) -> IcedTheme {
         match self.theme {
             AppTheme::Light => IcedTheme::Light,
@@ -214,6 +435,12 @@ impl Application for ConflictDialog {
     }
 }
 
+// The output pane, kept scrolled to the newest line; widget operations reach
+// every open window, so the id is per window
+fn output_id(window: window::Id) -> scrollable::Id {
+    scrollable::Id::new(format!("conflict-output-{:?}", window))
+}
+
 // Parse conflict message to extract main reason and details
 fn parse_conflict_message(conflict_msg: &str) -> (String, String) {
     // Try to extract the main reason (first line or before "Details:")
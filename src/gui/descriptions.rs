@@ -0,0 +1,63 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use birdnest_core::package_manager::PackageBackend;
+
+use crate::gui::PackageInfo;
+
+// Listing installed packages leaves their descriptions out to keep startup
+// fast. The Installed tab asks the backend for them a page at a time, for
+// the rows around where the list is scrolled, and fills them in as they
+// arrive.
+
+/// Rows described at a time, a couple of screens' worth
+pub const PAGE: usize = 40;
+
+#[derive(Debug, Default)]
+pub struct Descriptions {
+    /// Packages already asked about, answered or not, so scrolling back
+    /// doesn't ask again
+    requested: HashSet<String>,
+    /// How far down the Installed list is scrolled, from 0 to 1
+    pub offset: f32,
+}
+
+impl Descriptions {
+    /// The packages around the scroll position in `rows` that still need a
+    /// description and haven't been asked about
+    pub fn wanted(&self, rows: &[&PackageInfo]) -> Vec<String> {
+        // Rows differ in height, so this is as proportional as list_nav's scrolling
+        let start = (rows.len().saturating_sub(PAGE) as f32 * self.offset.clamp(0.0, 1.0)) as usize;
+        rows.iter()
+            .skip(start)
+            .take(PAGE)
+            .filter(|package| package.description.is_empty() && !self.requested.contains(&package.name))
+            .map(|package| package.name.clone())
+            .collect()
+    }
+
+    pub fn mark_requested(&mut self, names: &[String]) {
+        self.requested.extend(names.iter().cloned());
+    }
+
+    /// Start over, for a freshly loaded list
+    pub fn reset(&mut self) {
+        self.requested.clear();
+    }
+}
+
+pub async fn load(backend: Arc<dyn PackageBackend>, packages: Vec<String>) -> Result<HashMap<String, String>, String> {
+    tokio::task::spawn_blocking(move || backend.descriptions(&packages))
+        .await
+        .map_err(|e| format!("Task error: {}", e))?
+        .map_err(|e| e.to_string())
+}
+
+/// Fills the descriptions in, leaving packages without one as they were
+pub fn fill(packages: &mut [PackageInfo], descriptions: &HashMap<String, String>) {
+    for package in packages.iter_mut().filter(|package| package.description.is_empty()) {
+        if let Some(description) = descriptions.get(&package.name) {
+            package.description = description.clone();
+        }
+    }
+}
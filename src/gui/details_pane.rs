@@ -0,0 +1,262 @@
+use iced::{
+    alignment, Color,
+    widget::{button, column, container, image, row, scrollable, text, Column},
+    Element, Length, Padding,
+};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::process::Command as TokioCommand;
+
+use birdnest_core::appstream::Component;
+use birdnest_core::footprint::{self, Footprint};
+use birdnest_core::package_manager::{PackageBackend, PackageDetails};
+use birdnest_core::plan::format_size;
+use birdnest_core::utils::CommandBuilder;
+
+use crate::gui::changelog_view::{self, Changelog};
+use crate::gui::row_menu::{RowAction, RowTarget};
+use crate::gui::theme::Theme as AppTheme;
+use crate::gui::styles::{CustomScrollableStyle, RoundedButtonStyle, RoundedContainerStyle};
+use crate::gui::Message;
+
+pub const PANE_WIDTH: f32 = 380.0;
+
+/// The package shown in the side panel next to the Search and Installed
+/// lists, what the backend and AppStream said about it, its screenshot if
+/// one exists and, when asked for, its changelog
+#[derive(Debug, Default)]
+pub struct DetailsPane {
+    pub package: Option<String>,
+    pub app: Option<Component>,
+    pub details: Option<Result<PackageDetails, String>>,
+    pub screenshot: Option<PathBuf>,
+    pub changelog: Changelog,
+    pub footprint: Usage,
+}
+
+/// What the running processes of an installed package use
+#[derive(Debug, Default)]
+pub enum Usage {
+    /// Not installed, so nothing of it can run
+    #[default]
+    Unknown,
+    Measuring,
+    Measured(Result<Footprint, String>),
+}
+
+impl DetailsPane {
+    /// Points the pane at `package`; false if it already shows it
+    pub fn show(&mut self, package: String) -> bool {
+        if self.is_showing(&package) {
+            return false;
+        }
+        *self = Self { package: Some(package), ..Self::default() };
+        true
+    }
+
+    pub fn close(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn is_showing(&self, package: &str) -> bool {
+        self.package.as_deref() == Some(package)
+    }
+}
+
+pub async fn load(backend: Arc<dyn PackageBackend>, package: String) -> Result<PackageDetails, String> {
+    tokio::task::spawn_blocking(move || backend.info(&package))
+        .await
+        .map_err(|e| format!("Task error: {}", e))?
+        .map_err(|e| e.to_string())
+}
+
+/// CPU and memory of the package's running processes, over a second
+pub async fn measure(backend: Arc<dyn PackageBackend>, package: String) -> Result<Footprint, String> {
+    tokio::task::spawn_blocking(move || footprint::measure(backend.as_ref(), &package))
+        .await
+        .map_err(|e| format!("Task error: {}", e))?
+        .map_err(|e| e.to_string())
+}
+
+/// The first screenshot in a screenshots.debian.net package listing
+pub fn screenshot_url(listing: &str) -> Option<String> {
+    let listing: serde_json::Value = serde_json::from_str(listing).ok()?;
+    let first = listing.get("screenshots")?.as_array()?.first()?;
+    first
+        .get("small_image_url")
+        .or_else(|| first.get("large_image_url"))?
+        .as_str()
+        .map(str::to_string)
+}
+
+/// Downloads the package's screenshot into ~/.cache/birdnest/screenshots,
+/// once: from `url` when AppStream has one, else from screenshots.debian.net.
+/// None when there is none or curl can't reach the site.
+pub async fn screenshot(package: String, url: Option<String>) -> Option<PathBuf> {
+    let home = std::env::var("HOME").ok()?;
+    let dir = PathBuf::from(home).join(".cache").join("birdnest").join("screenshots");
+    let path = dir.join(format!("{}.png", package));
+    if path.exists() {
+        return Some(path);
+    }
+
+    let url = match url {
+        Some(url) => url,
+        None => {
+            let listing_url = format!("https://screenshots.debian.net/json/package/{}", package);
+            let (command, _) = CommandBuilder::new("curl").args(&["-fsSL", "--max-time", "10", &listing_url]).build().ok()?;
+            let listing = TokioCommand::from(command)
+                .output()
+                .await
+                .ok()?;
+            if !listing.status.success() {
+                tracing::debug!("details_pane: No screenshot listing for {}", package);
+                return None;
+            }
+            screenshot_url(&String::from_utf8_lossy(&listing.stdout))?
+        }
+    };
+
+    std::fs::create_dir_all(&dir).ok()?;
+    let (command, _) = CommandBuilder::new("curl").args(&["-fsSL", "--max-time", "20", &url]).build().ok()?;
+    let download = TokioCommand::from(command)
+        .arg("-o")
+        .arg(&path)
+        .status()
+        .await
+        .ok()?;
+    if !download.success() {
+        // Don't leave a partial file that would be taken for the screenshot next time
+        let _ = std::fs::remove_file(&path);
+        return None;
+    }
+    Some(path)
+}
+
+pub fn view<'a>(pane: &'a DetailsPane, theme: AppTheme, radius: f32, width: Length) -> Element<'a, Message> {
+    let Some(package) = &pane.package else {
+        return column![].into();
+    };
+
+    let close = button(text("Close").size(13.0))
+        .on_press(Message::CloseDetails)
+        .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+            is_primary: false,
+            radius,
+            primary_color: theme.primary(),
+            text_color: Color::WHITE,
+            background_color: theme.background(),
+        })))
+        .padding(Padding::new(8.0));
+    let title = pane.app.as_ref().map_or(package.as_str(), |app| app.name.as_str());
+    let mut content = Column::new().spacing(10).push(
+        row![
+            text(title).size(22.0).style(iced::theme::Text::Color(theme.primary())).width(Length::Fill),
+            close,
+        ]
+        .align_items(alignment::Alignment::Center),
+    );
+    if let Some(app) = &pane.app {
+        content = content.push(field("Package", package, theme));
+        if !app.categories.is_empty() {
+            content = content.push(field("Categories", &app.categories.join(", "), theme));
+        }
+    }
+
+    match &pane.details {
+        None => content = content.push(text("Loading details...").size(14.0).style(iced::theme::Text::Color(theme.text()))),
+        Some(Err(e)) => content = content.push(text(e).size(14.0).style(iced::theme::Text::Color(theme.danger()))),
+        Some(Ok(details)) => {
+            if let Some(path) = &pane.screenshot {
+                content = content.push(image(image::Handle::from_path(path)).width(Length::Fill));
+            }
+            content = content.push(field("Version", &details.version, theme));
+            if let Some(size) = details.installed_size {
+                content = content.push(field("Installed size", &format_size(size), theme));
+            }
+            let usage = match &pane.footprint {
+                Usage::Unknown => None,
+                Usage::Measuring => Some("Measuring...".to_string()),
+                Usage::Measured(Ok(footprint)) => Some(footprint.summary()),
+                Usage::Measured(Err(e)) => Some(e.clone()),
+            };
+            if let Some(usage) = usage {
+                let mut measure = button(text("Measure again").size(13.0))
+                    .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                        is_primary: false,
+                        radius,
+                        primary_color: theme.primary(),
+                        text_color: Color::WHITE,
+                        background_color: theme.background(),
+                    })))
+                    .padding(Padding::new(8.0));
+                if !matches!(pane.footprint, Usage::Measuring) {
+                    measure = measure.on_press(Message::MeasureFootprint);
+                }
+                content = content.push(
+                    row![field("Running now", &usage, theme), measure]
+                        .spacing(8)
+                        .align_items(alignment::Alignment::Center),
+                );
+            }
+            if let Some(arch) = &details.architecture {
+                content = content.push(field("Architecture", &arch.name, theme));
+                content = content.push(field("Multi-Arch", arch.multi_arch.as_deref().unwrap_or("no"), theme));
+            }
+            if let Some(maintainer) = &details.maintainer {
+                content = content.push(field("Maintainer", maintainer, theme));
+            }
+            if let Some(homepage) = &details.homepage {
+                content = content.push(
+                    row![
+                        field("Homepage", homepage, theme),
+                        button(text("Open").size(13.0))
+                            .on_press(Message::RowAction(RowTarget::package(package), RowAction::OpenHomepage))
+                            .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                                is_primary: false,
+                                radius,
+                                primary_color: theme.primary(),
+                                text_color: Color::WHITE,
+                                background_color: theme.background(),
+                            })))
+                            .padding(Padding::new(8.0)),
+                    ]
+                    .spacing(8)
+                    .align_items(alignment::Alignment::Center),
+                );
+            }
+            content = content.push(text(&details.description).size(14.0).style(iced::theme::Text::Color(theme.text())));
+            if !details.dependencies.is_empty() {
+                content = content.push(field("Depends on", &details.dependencies.join(", "), theme));
+            }
+            content = content
+                .push(text("Changelog").size(12.0).style(iced::theme::Text::Color(theme.primary())))
+                .push(changelog_view::view(&pane.changelog, Message::LoadChangelog, changelog_view::MAX_ENTRIES, theme, radius));
+        }
+    }
+
+    container(
+        scrollable(content.padding(Padding::new(16.0))).style(iced::theme::Scrollable::Custom(Box::new(CustomScrollableStyle {
+            background_color: theme.card_background(),
+            border_radius: radius,
+        }))),
+    )
+    .width(width)
+    .height(Length::Fill)
+    .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+        radius,
+        background: Some(theme.card_background()),
+        elevation: 1.5,
+    })))
+    .into()
+}
+
+fn field<'a>(label: &str, value: &str, theme: AppTheme) -> Element<'a, Message> {
+    column![
+        text(label).size(12.0).style(iced::theme::Text::Color(theme.primary())),
+        text(value).size(14.0).style(iced::theme::Text::Color(theme.text())),
+    ]
+    .spacing(2)
+    .width(Length::Fill)
+    .into()
+}
@@ -0,0 +1,211 @@
+use iced::{
+    executor, Color,
+    widget::{button, column, container, row, scrollable, text, Column, Space},
+    Application, Command, Element, Length, Theme as IcedTheme, Padding,
+    window,
+};
+
+use crate::gui::scaling;
+use crate::gui::theme::Theme as AppTheme;
+use crate::gui::styles::{RoundedButtonStyle, RoundedContainerStyle, CustomScrollableStyle};
+use birdnest_core::digest::{self, Change, ChangeKind, Digest};
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Loaded(Digest),
+    SetDays(u32),
+    Close,
+}
+
+// Maintenance > Weekly Digest: what was installed, upgraded and removed
+// lately and by whom, and when the sources changed, for keeping track of a
+// machine several people look after
+#[derive(Debug)]
+pub struct DigestDialog {
+    /// None while the logs are read
+    pub digest: Option<Digest>,
+    pub days: u32,
+    pub theme: AppTheme,
+    pub border_radius: f32,
+    // The child window it was opened in
+    pub window: window::Id,
+}
+
+async fn load(days: u32) -> Digest {
+    tokio::task::spawn_blocking(move || digest::collect(days)).await.unwrap_or_default()
+}
+
+impl DigestDialog {
+    pub fn new() -> Self {
+        Self {
+            digest: None,
+            days: digest::DEFAULT_DAYS,
+            theme: AppTheme::Dark,
+            border_radius: 12.0,
+            window: window::Id::MAIN,
+        }
+    }
+
+    pub fn window_settings() -> window::Settings {
+        window::Settings {
+            size: scaling::fit(iced::Size::new(860.0, 700.0)),
+            min_size: Some(scaling::fit(iced::Size::new(600.0, 450.0))),
+            resizable: true,
+            decorations: true,
+            ..window::Settings::default()
+        }
+    }
+
+    fn view_change(&self, change: &Change) -> Element<'_, Message> {
+        let theme = self.theme;
+        let mut details = column![
+            text(format!("{}  {}", change.package, change.versions())).size(14).style(iced::theme::Text::Color(theme.text())),
+        ]
+        .spacing(2)
+        .width(Length::Fill);
+        if let Some(origin) = change.origin() {
+            details = details.push(text(origin).size(12).style(iced::theme::Text::Color(theme.secondary_text())));
+        }
+        row![
+            details,
+            text(&change.time).size(12).font(iced::Font::MONOSPACE).style(iced::theme::Text::Color(theme.secondary_text())),
+        ]
+        .spacing(10)
+        .into()
+    }
+
+    fn view_section<'a>(&self, title: String, rows: Vec<Element<'a, Message>>) -> Element<'a, Message> {
+        let theme = self.theme;
+        let mut section = Column::new().spacing(8).push(text(title).size(16).style(iced::theme::Text::Color(theme.primary())));
+        for row in rows {
+            section = section.push(row);
+        }
+        container(section.padding(Padding::new(16.0)))
+            .width(Length::Fill)
+            .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+                radius: self.border_radius,
+                background: Some(theme.card_background()),
+                elevation: 1.0,
+            })))
+            .into()
+    }
+}
+
+impl Application for DigestDialog {
+    type Message = Message;
+    type Theme = IcedTheme;
+    type Executor = executor::Default;
+    type Flags = Self;
+
+    fn new(flags: Self) -> (Self, Command<Message>) {
+        let days = flags.days;
+        (flags, Command::perform(load(days), Message::Loaded))
+    }
+
+    fn title(&self) -> String {
+        "Weekly Digest - BirdNest".to_string()
+    }
+
+    fn update(&mut self, message: Message) -> Command<Message> {
+        match message {
+            Message::Loaded(digest) => {
+                self.digest = Some(digest);
+                Command::none()
+            }
+            Message::SetDays(days) => {
+                self.days = days;
+                self.digest = None;
+                Command::perform(load(days), Message::Loaded)
+            }
+            Message::Close => iced::window::close(self.window),
+        }
+    }
+
+    fn view(&self) -> Element<'_, Message> {
+        let theme = self.theme;
+        let button_style = |is_primary: bool| {
+            iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                is_primary,
+                radius: self.border_radius,
+                primary_color: theme.primary(),
+                text_color: if is_primary { Color::BLACK } else { Color::WHITE },
+                background_color: theme.background(),
+            }))
+        };
+
+        let mut periods = row![].spacing(6);
+        for (label, days) in [("Last 7 days", 7), ("Last 30 days", 30)] {
+            periods = periods.push(
+                button(text(label).size(12))
+                    .on_press(Message::SetDays(days))
+                    .style(button_style(days == self.days))
+                    .padding(Padding::new(6.0)),
+            );
+        }
+
+        let mut body = Column::new().spacing(12);
+        let summary = match &self.digest {
+            None => "Reading apt's history and dpkg's log...".to_string(),
+            Some(digest) => {
+                for kind in ChangeKind::ALL {
+                    let rows: Vec<Element<'_, Message>> = digest.of(kind).map(|change| self.view_change(change)).collect();
+                    if !rows.is_empty() {
+                        body = body.push(self.view_section(format!("{} ({})", kind.label(), rows.len()), rows));
+                    }
+                }
+                if !digest.source_changes.is_empty() {
+                    let rows = digest
+                        .source_changes
+                        .iter()
+                        .map(|snapshot| {
+                            text(format!("Changed after {} (backup {})", snapshot.summary(), snapshot.id))
+                                .size(13)
+                                .style(iced::theme::Text::Color(theme.text()))
+                                .into()
+                        })
+                        .collect();
+                    body = body.push(self.view_section(format!("Sources Changed ({})", digest.source_changes.len()), rows));
+                }
+                digest.summary()
+            }
+        };
+
+        container(
+            column![
+                text("Weekly Digest").size(24).style(iced::theme::Text::Color(theme.text())),
+                text("Package changes from apt's history and dpkg's log, with who asked for them, and changes to the apt sources.")
+                    .size(13)
+                    .style(iced::theme::Text::Color(theme.secondary_text())),
+                periods,
+                text(summary).size(15).style(iced::theme::Text::Color(theme.text())),
+                scrollable(body)
+                    .style(iced::theme::Scrollable::Custom(Box::new(CustomScrollableStyle {
+                        background_color: theme.surface(),
+                        border_radius: self.border_radius,
+                    })))
+                    .height(Length::Fill),
+                row![
+                    Space::with_width(Length::Fill),
+                    button("Close").on_press(Message::Close).style(button_style(true)).padding(Padding::new(14.0)),
+                ],
+            ]
+            .spacing(15)
+            .padding(Padding::new(30.0))
+        )
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+            radius: self.border_radius,
+            background: Some(theme.background()),
+            elevation: 0.0,
+        })))
+        .into()
+    }
+
+    fn theme(&self) -> IcedTheme {
+        match self.theme {
+            AppTheme::Light => IcedTheme::Light,
+            AppTheme::Dark => IcedTheme::Dark,
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use iced::{
+    alignment, Color,
+    widget::{button, column, container, row, scrollable, text, Column, Row},
+    Element, Length, Padding,
+};
+use std::collections::HashSet;
+
+use birdnest_core::disk_usage::{self, Usage};
+use birdnest_core::plan;
+
+use crate::gui::theme::Theme as AppTheme;
+use crate::gui::styles::{RoundedButtonStyle, RoundedContainerStyle, CustomScrollableStyle};
+use crate::gui::{Message, PackageInfo};
+
+// Enough boxes to show where the space goes without labels getting unreadable
+const TREEMAP_ENTRIES: usize = 40;
+const TREEMAP_ROWS: usize = 4;
+const TREEMAP_HEIGHT: f32 = 320.0;
+const LARGEST_LISTED: usize = 15;
+
+const FLATPAK_COLOR: Color = Color::from_rgb(0.4, 0.6, 0.9);
+
+/// The Installed tab's disk usage view, in place of the package list while open
+#[derive(Debug, Default)]
+pub struct DiskUsageState {
+    pub open: bool,
+    /// Installed Flatpak apps by size; None until measured
+    pub flatpaks: Option<Vec<Usage>>,
+    pub loading: bool,
+}
+
+pub async fn load_flatpaks() -> Vec<Usage> {
+    tokio::task::spawn_blocking(|| match disk_usage::flatpak_usage() {
+        Ok(usage) => usage,
+        Err(e) => {
+            tracing::warn!("disk_usage: can't list Flatpak sizes: {}", e);
+            Vec::new()
+        }
+    })
+    .await
+    .unwrap_or_default()
+}
+
+/// Installed packages and Flatpak apps together, largest first
+pub fn combined(state: &DiskUsageState, packages: &[PackageInfo]) -> Vec<Usage> {
+    let mut usage: Vec<Usage> = packages
+        .iter()
+        .filter_map(|pkg| Some(Usage { name: pkg.name.clone(), bytes: pkg.installed_bytes?, flatpak: false }))
+        .collect();
+    usage.extend(state.flatpaks.iter().flatten().cloned());
+    disk_usage::sort_largest(&mut usage);
+    usage
+}
+
+// FillPortion takes a u16, so sizes are scaled to thousandths of the whole
+fn portion(bytes: u64, total: u64) -> u16 {
+    ((u128::from(bytes) * 1000 / u128::from(total.max(1))) as u16).max(1)
+}
+
+fn summary_line(state: &DiskUsageState, usage: &[Usage], package_count: usize) -> String {
+    let sized = usage.iter().filter(|u| !u.flatpak).count();
+    let mut line = format!(
+        "System packages: {} in {} packages",
+        plan::format_size(disk_usage::total(usage, false)),
+        sized
+    );
+    if sized < package_count {
+        line.push_str(&format!(" ({} without a known size)", package_count - sized));
+    }
+    match &state.flatpaks {
+        Some(flatpaks) => line.push_str(&format!(
+            " · Flatpak apps: {} in {} apps",
+            plan::format_size(disk_usage::total(usage, true)),
+            flatpaks.len()
+        )),
+        None if state.loading => line.push_str(" · Measuring Flatpak apps..."),
+        None => {}
+    }
+    line
+}
+
+pub fn view<'a>(
+    state: &DiskUsageState,
+    packages: &[PackageInfo],
+    selected: &HashSet<String>,
+    theme: AppTheme,
+    radius: f32,
+) -> Element<'a, Message> {
+    let usage = combined(state, packages);
+    let summary = text(summary_line(state, &usage, packages.len()))
+        .size(14)
+        .style(iced::theme::Text::Color(theme.secondary_text()));
+    if usage.is_empty() {
+        return column![
+            summary,
+            text("No sizes known for the installed packages").size(16).style(iced::theme::Text::Color(theme.text())),
+        ]
+        .spacing(12)
+        .into();
+    }
+
+    // Packages can be picked for removal straight from the map; Flatpaks are
+    // removed from their own tab
+    let cell = |entry: &Usage, total: u64| -> Element<'a, Message> {
+        let is_selected = selected.contains(&entry.name);
+        let background = if entry.flatpak {
+            FLATPAK_COLOR
+        } else if is_selected {
+            theme.danger()
+        } else {
+            theme.primary()
+        };
+        let label = column![
+            text(&entry.name).size(12).style(iced::theme::Text::Color(Color::BLACK)),
+            text(plan::format_size(entry.bytes)).size(11).style(iced::theme::Text::Color(Color::BLACK)),
+        ]
+        .spacing(2);
+        let boxed = container(label)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .padding(Padding::new(4.0))
+            .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+                radius: 4.0,
+                background: Some(background),
+                elevation: 0.0,
+            })));
+        let boxed: Element<'a, Message> = if entry.flatpak {
+            boxed.into()
+        } else {
+            button(boxed)
+                .on_press(Message::ToggleInstalledPackage(entry.name.clone()))
+                .padding(0)
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .style(iced::theme::Button::Text)
+                .into()
+        };
+        container(boxed)
+            .width(Length::FillPortion(portion(entry.bytes, total)))
+            .height(Length::Fill)
+            .into()
+    };
+
+    let shown = &usage[..usage.len().min(TREEMAP_ENTRIES)];
+    let shown_total: u64 = shown.iter().map(|u| u.bytes).sum();
+    let mut treemap = Column::new().spacing(3).height(Length::Fixed(TREEMAP_HEIGHT));
+    for strip in disk_usage::strips(shown, TREEMAP_ROWS) {
+        let strip_total: u64 = strip.iter().map(|u| u.bytes).sum();
+        let mut cells: Row<'a, Message> = Row::new().spacing(3).height(Length::FillPortion(portion(strip_total, shown_total)));
+        for entry in &strip {
+            cells = cells.push(cell(entry, strip_total));
+        }
+        treemap = treemap.push(cells);
+    }
+
+    let mut largest = Column::new().spacing(6);
+    for entry in usage.iter().take(LARGEST_LISTED) {
+        largest = largest.push(
+            row![
+                text(&entry.name).size(14).style(iced::theme::Text::Color(theme.text())).width(Length::Fill),
+                text(if entry.flatpak { "Flatpak" } else { "Package" })
+                    .size(12)
+                    .style(iced::theme::Text::Color(theme.secondary_text())),
+                text(plan::format_size(entry.bytes))
+                    .size(14)
+                    .style(iced::theme::Text::Color(theme.text()))
+                    .width(Length::Fixed(90.0))
+                    .horizontal_alignment(alignment::Horizontal::Right),
+            ]
+            .spacing(12)
+            .align_items(alignment::Alignment::Center),
+        );
+    }
+
+    scrollable(
+        column![
+            summary,
+            treemap,
+            text("Largest").size(18).style(iced::theme::Text::Color(theme.text())),
+            container(largest)
+                .padding(Padding::new(12.0))
+                .width(Length::Fill)
+                .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+                    radius,
+                    background: Some(theme.card_background()),
+                    elevation: 1.0,
+                }))),
+            text("Click a package to select it for removal.")
+                .size(12)
+                .style(iced::theme::Text::Color(theme.secondary_text())),
+        ]
+        .spacing(12)
+        .padding(Padding::new(10.0)),
+    )
+    .style(iced::theme::Scrollable::Custom(Box::new(CustomScrollableStyle {
+        background_color: theme.background(),
+        border_radius: radius,
+    })))
+    .height(Length::Fill)
+    .into()
+}
+
+// Used by the sort buttons above the list and the view toggle
+pub fn toggle_button<'a>(label: &str, active: bool, message: Message, theme: AppTheme, radius: f32) -> Element<'a, Message> {
+    button(text(label).size(13))
+        .on_press(message)
+        .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+            is_primary: active,
+            radius,
+            primary_color: theme.primary(),
+            text_color: if active { Color::BLACK } else { Color::WHITE },
+            background_color: theme.background(),
+        })))
+        .padding(Padding::new(10.0))
+        .into()
+}
@@ -0,0 +1,315 @@
+use iced::{
+    alignment, executor, Color,
+    widget::{button, column, container, row, scrollable, text, Column, Space},
+    Application, Command, Element, Length, Subscription, Theme as IcedTheme, Padding,
+    window,
+};
+
+use birdnest_core::environments::{self, Template};
+
+use crate::gui::advanced_options;
+use crate::gui::command_stream::{self, Step, StreamEvent};
+use crate::gui::scaling;
+use crate::gui::theme::Theme as AppTheme;
+use crate::gui::styles::{RoundedButtonStyle, RoundedContainerStyle, CustomScrollableStyle};
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    TemplatesLoaded(Vec<Template>),
+    Select(usize),
+    ContainerChanged(String),
+    ExportToggled(String, bool),
+    Create,
+    Output(StreamEvent),
+    Close,
+}
+
+// Pikman > Environments: pick a template, name the container and the tools
+// to export, and create it all in one run through the transaction queue
+#[derive(Debug)]
+pub struct EnvironmentDialog {
+    pub templates: Vec<Template>,
+    pub selected: Option<usize>,
+    // The container to create, starting from the template's name for it
+    pub container: String,
+    // The template's exports still ticked
+    pub exports: Vec<String>,
+    pub is_running: bool,
+    pub result: Option<Result<String, String>>,
+    pub terminal_output: String,
+    // Bumped for every run, so creating another gets a fresh subscription
+    pub attempt: u32,
+    pub theme: AppTheme,
+    pub border_radius: f32,
+    // The child window it was opened in
+    pub window: window::Id,
+}
+
+async fn load_templates() -> Vec<Template> {
+    tokio::task::spawn_blocking(environments::load).await.unwrap_or_default()
+}
+
+impl EnvironmentDialog {
+    pub fn new() -> Self {
+        Self {
+            templates: Vec::new(),
+            selected: None,
+            container: String::new(),
+            exports: Vec::new(),
+            is_running: false,
+            result: None,
+            terminal_output: String::new(),
+            attempt: 0,
+            theme: AppTheme::Dark,
+            border_radius: 12.0,
+            window: window::Id::MAIN,
+        }
+    }
+
+    pub fn window_settings() -> window::Settings {
+        window::Settings {
+            size: scaling::fit(iced::Size::new(900.0, 680.0)),
+            min_size: Some(scaling::fit(iced::Size::new(700.0, 450.0))),
+            resizable: true,
+            decorations: true,
+            ..window::Settings::default()
+        }
+    }
+
+    pub fn template(&self) -> Option<&Template> {
+        self.selected.and_then(|index| self.templates.get(index))
+    }
+
+    pub fn steps(&self) -> Vec<Step> {
+        let Some(template) = self.template() else {
+            return Vec::new();
+        };
+        template
+            .steps(self.container.trim(), &self.exports)
+            .into_iter()
+            .map(|command| Step::Command { command, elevated: false })
+            .collect()
+    }
+}
+
+impl Application for EnvironmentDialog {
+    type Message = Message;
+    type Theme = IcedTheme;
+    type Executor = executor::Default;
+    type Flags = Self;
+
+    fn new(flags: Self) -> (Self, Command<Message>) {
+        (flags, Command::perform(load_templates(), Message::TemplatesLoaded))
+    }
+
+    fn title(&self) -> String {
+        "Environments - BirdNest".to_string()
+    }
+
+    fn update(&mut self, message: Message) -> Command<Message> {
+        match message {
+            Message::TemplatesLoaded(templates) => {
+                self.templates = templates;
+                if self.selected.is_none() && !self.templates.is_empty() {
+                    return self.update(Message::Select(0));
+                }
+                Command::none()
+            }
+            Message::Select(index) => {
+                let Some(template) = self.templates.get(index) else {
+                    return Command::none();
+                };
+                if self.is_running {
+                    return Command::none();
+                }
+                self.container = template.container.clone();
+                self.exports = template.export.clone();
+                self.selected = Some(index);
+                self.result = None;
+                Command::none()
+            }
+            Message::ContainerChanged(name) => {
+                self.container = name;
+                self.result = None;
+                Command::none()
+            }
+            Message::ExportToggled(tool, export) => {
+                self.exports.retain(|t| *t != tool);
+                if export {
+                    self.exports.push(tool);
+                }
+                Command::none()
+            }
+            Message::Create => {
+                if self.is_running || self.template().is_none() {
+                    return Command::none();
+                }
+                if let Err(e) = environments::check_container(self.container.trim()) {
+                    self.result = Some(Err(e.to_string()));
+                    return Command::none();
+                }
+                self.is_running = true;
+                self.result = None;
+                self.terminal_output.clear();
+                self.attempt += 1;
+                Command::none()
+            }
+            Message::Output(StreamEvent::Line(line)) => {
+                self.terminal_output.push_str(&line);
+                self.terminal_output.push('\n');
+                scrollable::snap_to(output_id(self.window), scrollable::RelativeOffset::END)
+            }
+            Message::Output(StreamEvent::Finished(result)) => {
+                self.is_running = false;
+                let container = self.container.trim().to_string();
+                self.result = Some(result.map(|()| format!("{} is ready. Open it with Enter Container, or: pikman enter {}", container, container)));
+                Command::none()
+            }
+            Message::Close => iced::window::close(self.window),
+        }
+    }
+
+    fn subscription(&self) -> Subscription<Message> {
+        if self.is_running {
+            command_stream::subscription(("environment", self.attempt), self.steps(), Message::Output)
+        } else {
+            Subscription::none()
+        }
+    }
+
+    fn view(&self) -> Element<'_, Message> {
+        let theme = self.theme;
+        let button_style = |is_primary: bool| {
+            iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                is_primary,
+                radius: self.border_radius,
+                primary_color: theme.primary(),
+                text_color: if is_primary { Color::BLACK } else { Color::WHITE },
+                background_color: theme.background(),
+            }))
+        };
+
+        let mut list = Column::new().spacing(4);
+        for (index, template) in self.templates.iter().enumerate() {
+            list = list.push(
+                button(text(&template.name).size(13))
+                    .on_press(Message::Select(index))
+                    .width(Length::Fill)
+                    .style(button_style(self.selected == Some(index)))
+                    .padding(Padding::new(8.0)),
+            );
+        }
+
+        let details: Element<'_, Message> = match self.template() {
+            Some(template) => {
+                let mut details = column![
+                    text(&template.name).size(18).style(iced::theme::Text::Color(theme.text())),
+                    text(&template.description).size(13).style(iced::theme::Text::Color(theme.secondary_text())),
+                    text(format!("{} container with: {}", template.manager, template.packages.join(", ")))
+                        .size(13)
+                        .style(iced::theme::Text::Color(theme.text())),
+                    advanced_options::field("Container", &template.container, &self.container, Message::ContainerChanged, theme, self.border_radius),
+                ]
+                .spacing(10);
+                if !template.export.is_empty() {
+                    details = details.push(text("Export to the host").size(13).style(iced::theme::Text::Color(theme.secondary_text())));
+                    for tool in &template.export {
+                        let name = tool.clone();
+                        details = details.push(advanced_options::toggle(
+                            tool,
+                            self.exports.contains(tool),
+                            move |export| Message::ExportToggled(name.clone(), export),
+                            theme,
+                        ));
+                    }
+                }
+                details.into()
+            }
+            None => text("No templates").size(13).style(iced::theme::Text::Color(theme.secondary_text())).into(),
+        };
+
+        let status: Element<'_, Message> = match (&self.result, self.is_running) {
+            (_, true) => text("Creating...").size(13).style(iced::theme::Text::Color(theme.secondary_text())).into(),
+            (Some(Ok(message)), _) => text(message).size(13).style(iced::theme::Text::Color(theme.text())).into(),
+            (Some(Err(e)), _) => text(e).size(13).style(iced::theme::Text::Color(theme.danger())).into(),
+            (None, _) => Space::with_width(Length::Fill).into(),
+        };
+        let create = button("Create")
+            .on_press_maybe((!self.is_running && self.template().is_some()).then_some(Message::Create))
+            .style(button_style(true))
+            .padding(Padding::new(14.0));
+
+        container(
+            column![
+                text("Environments").size(24).style(iced::theme::Text::Color(theme.text())),
+                text("A container with a package set and exported tools, from a template. Add your own as TOML files in ~/.config/birdnest/environments.")
+                    .size(13)
+                    .style(iced::theme::Text::Color(theme.secondary_text())),
+                row![
+                    scrollable(list.padding(Padding::new(8.0)))
+                        .style(iced::theme::Scrollable::Custom(Box::new(CustomScrollableStyle {
+                            background_color: theme.surface(),
+                            border_radius: self.border_radius,
+                        })))
+                        .width(Length::Fixed(260.0))
+                        .height(Length::Fill),
+                    column![
+                        container(details)
+                            .width(Length::Fill)
+                            .padding(Padding::new(12.0))
+                            .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+                                radius: self.border_radius,
+                                background: Some(theme.card_background()),
+                                elevation: 1.0,
+                            }))),
+                        scrollable(
+                            text(&self.terminal_output)
+                                .size(12)
+                                .font(iced::Font::MONOSPACE)
+                                .style(iced::theme::Text::Color(theme.text())),
+                        )
+                        .id(output_id(self.window))
+                        .style(iced::theme::Scrollable::Custom(Box::new(CustomScrollableStyle {
+                            background_color: theme.surface(),
+                            border_radius: self.border_radius,
+                        })))
+                        .height(Length::Fill),
+                    ]
+                    .spacing(12)
+                    .width(Length::Fill),
+                ]
+                .spacing(12)
+                .height(Length::Fill),
+                row![
+                    status,
+                    create,
+                    button("Close").on_press(Message::Close).style(button_style(false)).padding(Padding::new(14.0)),
+                ]
+                .spacing(10)
+                .align_items(alignment::Alignment::Center),
+            ]
+            .spacing(15)
+            .padding(Padding::new(30.0)),
+        )
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+            radius: self.border_radius,
+            background: Some(theme.background()),
+            elevation: 0.0,
+        })))
+        .into()
+    }
+
+    fn theme(&self) -> IcedTheme {
+        match self.theme {
+            AppTheme::Light => IcedTheme::Light,
+            AppTheme::Dark => IcedTheme::Dark,
+        }
+    }
+}
+
+// Widget operations reach every open window, so the id is per window
+fn output_id(window: window::Id) -> scrollable::Id {
+    scrollable::Id::new(format!("environment-output-{:?}", window))
+}
@@ -0,0 +1,371 @@
+use iced::{
+    alignment, Color,
+    widget::{button, column, container, row, text, text_input, Column, Space},
+    Element, Length, Padding,
+};
+
+use std::collections::HashMap;
+
+use crate::gui::theme::Theme as AppTheme;
+use crate::gui::styles::{RoundedButtonStyle, RoundedContainerStyle, YellowTextInputStyle};
+use crate::gui::{pikman_search, search_flatpak, Message, Tab};
+
+/// Fewer results than this on the active tab offer searching other sources too
+pub const FEW_RESULTS: usize = 5;
+// Matches listed per extra source
+const SHOWN_PER_SOURCE: usize = FEW_RESULTS * 2;
+
+/// Sources the header search can also look in below a tab's own results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExtraSource {
+    Flatpak,
+    Aur,
+}
+
+impl ExtraSource {
+    pub fn label(self) -> &'static str {
+        match self {
+            ExtraSource::Flatpak => "Flatpak",
+            ExtraSource::Aur => "AUR",
+        }
+    }
+}
+
+/// A match from an extra source; `id` is what installing it takes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtraResult {
+    pub name: String,
+    pub id: String,
+    pub description: String,
+}
+
+/// What searching an extra source found, or why it failed
+pub type ExtraResults = Result<Vec<ExtraResult>, String>;
+
+/// Installing a result from one source
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstallOption {
+    pub source: ExtraSource,
+    pub id: String,
+}
+
+/// The same app found in several places, folded into one row each
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Merged {
+    /// Extra sources offering the tab's own results, by result index
+    pub for_tab: HashMap<usize, Vec<InstallOption>>,
+    /// Extra results still listed, by source and index, with every source
+    /// offering them; the ones missing were folded into another row
+    pub listed: HashMap<(ExtraSource, usize), Vec<InstallOption>>,
+}
+
+#[derive(Clone, Copy)]
+enum Owner {
+    Tab(usize),
+    Extra(ExtraSource, usize),
+}
+
+/// What identifies an app across sources: its AppStream ID without
+/// ".desktop", and its package name (a Flatpak's is the last part of its ID)
+/// in lowercase letters and digits, less an AUR "-bin" or "-git" suffix
+pub fn merge_keys(package: &str, appstream_id: Option<&str>) -> Vec<String> {
+    let mut keys = Vec::new();
+    if let Some(id) = appstream_id {
+        keys.push(format!("id:{}", id.trim_end_matches(".desktop").to_lowercase()));
+    }
+    let name = package.strip_suffix("-bin").or_else(|| package.strip_suffix("-git")).unwrap_or(package);
+    let name: String = name.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect();
+    if !name.is_empty() {
+        keys.push(format!("name:{}", name));
+    }
+    keys
+}
+
+// A Flatpak's application ID is its AppStream ID; AUR results only have a package name
+fn extra_keys(source: ExtraSource, result: &ExtraResult) -> Vec<String> {
+    match source {
+        ExtraSource::Flatpak => merge_keys(result.id.rsplit('.').next().unwrap_or(&result.id), Some(&result.id)),
+        ExtraSource::Aur => merge_keys(&result.id, None),
+    }
+}
+
+/// Folds extra results that are the same app as one of the tab's results
+/// (`tab_keys`, from [`merge_keys`]) or as an earlier extra result into that row
+pub fn merge(tab_keys: &[Vec<String>], extras: &[(ExtraSource, Option<ExtraResults>)]) -> Merged {
+    let mut owners: HashMap<String, Owner> = HashMap::new();
+    for (index, keys) in tab_keys.iter().enumerate() {
+        for key in keys {
+            owners.entry(key.clone()).or_insert(Owner::Tab(index));
+        }
+    }
+
+    let mut merged = Merged::default();
+    for (source, results) in extras {
+        let Some(Ok(results)) = results else {
+            continue;
+        };
+        for (index, result) in results.iter().enumerate().take(SHOWN_PER_SOURCE) {
+            let option = InstallOption { source: *source, id: result.id.clone() };
+            let keys = extra_keys(*source, result);
+            let options = match keys.iter().find_map(|key| owners.get(key)).copied() {
+                Some(Owner::Tab(row)) => merged.for_tab.entry(row).or_default(),
+                Some(Owner::Extra(s, i)) => merged.listed.entry((s, i)).or_default(),
+                None => {
+                    for key in keys {
+                        owners.entry(key).or_insert(Owner::Extra(*source, index));
+                    }
+                    merged.listed.entry((*source, index)).or_default()
+                }
+            };
+            // A source listing the same app twice still installs it one way
+            if !options.iter().any(|o| o.source == option.source) {
+                options.push(option);
+            }
+        }
+    }
+    merged
+}
+
+/// The header's query, shared by every tab, and the extra sources opened
+/// under the active tab's results (None while still searching)
+#[derive(Debug, Default)]
+pub struct GlobalSearch {
+    pub query: String,
+    /// The active tab has results for the query, so their count means something
+    pub searched: bool,
+    pub extras: Vec<(ExtraSource, Option<ExtraResults>)>,
+}
+
+impl GlobalSearch {
+    pub fn is_expanded(&self, source: ExtraSource) -> bool {
+        self.extras.iter().any(|(s, _)| *s == source)
+    }
+
+    /// Marks `source` as being searched; false if it already is open
+    pub fn expand(&mut self, source: ExtraSource) -> bool {
+        if self.is_expanded(source) {
+            return false;
+        }
+        self.extras.push((source, None));
+        true
+    }
+
+    pub fn set_results(&mut self, source: ExtraSource, results: ExtraResults) {
+        if let Some((_, slot)) = self.extras.iter_mut().find(|(s, _)| *s == source) {
+            *slot = Some(results);
+        }
+    }
+}
+
+/// Other sources worth offering on `tab`: the ones it doesn't search itself.
+/// The Installed tab only filters what is already there, so it gets none.
+pub fn offered(tab: Tab, pikman_filter: Option<&str>) -> Vec<ExtraSource> {
+    match tab {
+        Tab::Search => vec![ExtraSource::Flatpak, ExtraSource::Aur],
+        Tab::Flatpak => vec![ExtraSource::Aur],
+        Tab::Pikman if pikman_filter == Some("aur") => vec![ExtraSource::Flatpak],
+        Tab::Pikman => vec![ExtraSource::Flatpak, ExtraSource::Aur],
+        Tab::Installed | Tab::Browse | Tab::AppImage | Tab::Updates | Tab::Sources | Tab::Maintenance => Vec::new(),
+    }
+}
+
+fn placeholder(tab: Tab) -> &'static str {
+    match tab {
+        Tab::Installed => "Filter installed packages...",
+        Tab::Browse => "Filter apps in this category...",
+        Tab::Flatpak => "Search Flatpak apps...",
+        Tab::Pikman => "Search with pikman...",
+        Tab::Search | Tab::AppImage | Tab::Updates | Tab::Sources | Tab::Maintenance => "Search packages...",
+    }
+}
+
+pub async fn search(source: ExtraSource, query: String) -> ExtraResults {
+    match source {
+        ExtraSource::Flatpak => search_flatpak(query)
+            .await
+            .map(|apps| {
+                apps.into_iter()
+                    .map(|app| ExtraResult { name: app.name, id: app.application, description: app.description })
+                    .collect()
+            })
+            .map_err(|e| e.to_string()),
+        ExtraSource::Aur => pikman_search(query, Some("aur".to_string()))
+            .await
+            .map(|packages| {
+                packages
+                    .into_iter()
+                    .map(|pkg| ExtraResult { name: pkg.name.clone(), id: pkg.name, description: pkg.description })
+                    .collect()
+            })
+            .map_err(|e| e.to_string()),
+    }
+}
+
+pub fn view_field<'a>(state: &'a GlobalSearch, tab: Tab, theme: AppTheme, radius: f32) -> Element<'a, Message> {
+    let mut submit = button(text(if matches!(tab, Tab::Installed | Tab::Browse) { "Filter" } else { "Search" }).size(16.0))
+        .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+            is_primary: true,
+            radius,
+            primary_color: theme.primary(),
+            text_color: Color::BLACK,
+            background_color: theme.background(),
+        })))
+        .padding(Padding::new(14.0));
+    if !state.query.trim().is_empty() {
+        submit = submit.on_press(Message::HeaderSearch);
+    }
+    container(
+        row![
+            text_input(placeholder(tab), &state.query)
+                .on_input(Message::HeaderQueryChanged)
+                .on_submit(Message::HeaderSearch)
+                .padding(Padding::new(12.0))
+                .width(Length::Fill)
+                .style(iced::theme::TextInput::Custom(Box::new(YellowTextInputStyle {
+                    radius,
+                    primary_color: theme.primary(),
+                    background_color: theme.background(),
+                    text_color: Color::BLACK,
+                }))),
+            submit,
+        ]
+        .spacing(10)
+        .align_items(alignment::Alignment::Center),
+    )
+    .width(Length::Fill)
+    .padding(Padding::new(12.0))
+    .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+        radius,
+        background: Some(theme.card_background()),
+        elevation: 1.0,
+    })))
+    .into()
+}
+
+/// "Search also in ..." buttons, shown when the active tab found few
+/// results, and whatever the opened sources found
+pub fn view_extras<'a>(
+    state: &'a GlobalSearch,
+    merged: &Merged,
+    offered: Vec<ExtraSource>,
+    tab_results: usize,
+    theme: AppTheme,
+    radius: f32,
+) -> Element<'a, Message> {
+    if !state.searched || state.query.trim().is_empty() || (tab_results >= FEW_RESULTS && state.extras.is_empty()) {
+        return Space::with_height(Length::Fixed(0.0)).into();
+    }
+
+    let mut expanders = row![text(format!("Only {} result(s) here.", tab_results))
+        .size(14.0)
+        .style(iced::theme::Text::Color(theme.text()))]
+    .spacing(10)
+    .align_items(alignment::Alignment::Center);
+    for source in offered.into_iter().filter(|source| !state.is_expanded(*source)) {
+        expanders = expanders.push(
+            button(text(format!("Search also in {}", source.label())).size(14.0))
+                .on_press(Message::SearchAlsoIn(source))
+                .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                    is_primary: false,
+                    radius,
+                    primary_color: theme.primary(),
+                    text_color: Color::WHITE,
+                    background_color: theme.background(),
+                })))
+                .padding(Padding::new(10.0)),
+        );
+    }
+
+    let mut content = Column::new().spacing(10);
+    if tab_results < FEW_RESULTS {
+        content = content.push(expanders);
+    }
+    for (source, results) in &state.extras {
+        content = content.push(
+            text(format!("From {}", source.label()))
+                .size(16.0)
+                .style(iced::theme::Text::Color(theme.primary())),
+        );
+        content = match results {
+            None => content.push(text("Searching...").size(13.0).style(iced::theme::Text::Color(theme.text()))),
+            Some(Err(e)) => content.push(text(e).size(13.0).style(iced::theme::Text::Color(theme.danger()))),
+            Some(Ok(results)) if results.is_empty() => {
+                content.push(text("No matches").size(13.0).style(iced::theme::Text::Color(theme.text())))
+            }
+            Some(Ok(results)) => {
+                let listed: Vec<_> = results
+                    .iter()
+                    .enumerate()
+                    .take(SHOWN_PER_SOURCE)
+                    .filter_map(|(index, result)| Some((result, merged.listed.get(&(*source, index))?)))
+                    .collect();
+                if listed.is_empty() {
+                    content.push(
+                        text("Every match is already listed above")
+                            .size(13.0)
+                            .style(iced::theme::Text::Color(theme.text())),
+                    )
+                } else {
+                    listed.into_iter().fold(content, |content, (result, options)| {
+                        content.push(view_extra_row(result, options, theme, radius))
+                    })
+                }
+            }
+        };
+    }
+
+    container(content)
+        .width(Length::Fill)
+        .padding(Padding::new(12.0))
+        .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+            radius,
+            background: Some(theme.surface()),
+            elevation: 1.0,
+        })))
+        .into()
+}
+
+fn view_extra_row<'a>(result: &'a ExtraResult, options: &[InstallOption], theme: AppTheme, radius: f32) -> Element<'a, Message> {
+    let install = match options {
+        [option] => install_button("Install", option, theme, radius).into(),
+        _ => view_install_options("Install via", options, theme, radius),
+    };
+    row![
+        column![
+            text(&result.name).size(15.0).style(iced::theme::Text::Color(theme.text())),
+            text(&result.description).size(12.0).style(iced::theme::Text::Color(theme.text())),
+        ]
+        .spacing(2)
+        .width(Length::Fill),
+        install,
+    ]
+    .spacing(10)
+    .align_items(alignment::Alignment::Center)
+    .into()
+}
+
+/// `label` and a button per source `options` install from
+pub fn view_install_options<'a>(label: &str, options: &[InstallOption], theme: AppTheme, radius: f32) -> Element<'a, Message> {
+    options
+        .iter()
+        .fold(
+            row![text(label.to_string()).size(13.0).style(iced::theme::Text::Color(theme.text()))]
+                .spacing(8)
+                .align_items(alignment::Alignment::Center),
+            |buttons, option| buttons.push(install_button(option.source.label(), option, theme, radius)),
+        )
+        .into()
+}
+
+fn install_button<'a>(caption: &str, option: &InstallOption, theme: AppTheme, radius: f32) -> iced::widget::Button<'a, Message> {
+    button(text(caption.to_string()).size(13.0))
+        .on_press(Message::InstallExtra(option.source, option.id.clone()))
+        .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+            is_primary: false,
+            radius,
+            primary_color: theme.primary(),
+            text_color: Color::WHITE,
+            background_color: theme.background(),
+        })))
+        .padding(Padding::new(8.0))
+}
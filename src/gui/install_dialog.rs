@@ -1,22 +1,55 @@
 use iced::{
     alignment, executor, Color,
     widget::{button, column, container, row, scrollable, text, Space},
-    Application, Command, Element, Length, Pixels, Settings, Theme as IcedTheme, Padding,
+    Application, Command, Element, Length, Pixels, Settings, Subscription, Theme as IcedTheme, Padding,
     window,
 };
-use tokio::process::Command as TokioCommand;
+use std::collections::HashMap;
+use std::sync::Arc;
 
+use crate::gui::advanced_options;
+use crate::gui::scaling;
 use crate::gui::theme::Theme as AppTheme;
 use crate::gui::styles::{RoundedButtonStyle, RoundedContainerStyle, CustomScrollableStyle};
+use crate::gui::plan_view::{load_install_plan, load_recommends, view_flatpak_size, view_plan, view_recommends};
+use crate::gui::upgrade_dialog::progress_bars;
+use crate::gui::command_stream::{self, ProcessHandle, Step, StreamEvent};
+use birdnest_core::config::Config;
+use birdnest_core::elevation;
+use birdnest_core::flatpak::{FlatpakFile, FlatpakManager, InstallSize};
+use birdnest_core::hooks::HookEvent;
+use birdnest_core::immutable::{self, SystemKind};
+use birdnest_core::install_options::{optional, InstallOptions};
+use birdnest_core::package_manager::{BackendCommand, PackageBackend};
+use birdnest_core::plan::{PlannedPackage, TransactionPlan};
+use birdnest_core::progress::{self, TransactionProgress};
 
 #[derive(Debug, Clone)]
 pub enum Message {
     LoadPackageInfo,
     PackageInfoLoaded(Vec<PackageDetail>),
+    PlanLoaded(Option<TransactionPlan>),
+    RecommendsLoaded(Option<Vec<PlannedPackage>>),
+    SizeLoaded(Option<InstallSize>),
+    NewRemoteChecked(Option<String>),
+    PasswordlessChecked(bool),
     InstallPackages,
-    InstallationProgress(String),
+    InstallationOutput(StreamEvent),
     InstallationComplete,
     InstallationError(String),
+    HooksFinished(String),
+    ToggleDependencies,
+    ToggleAdvanced,
+    NoRecommendsToggled(bool),
+    TargetReleaseChanged(String),
+    FlatpakRemoteChanged(String),
+    FlatpakBranchChanged(String),
+    // Simulates again with the current apt flags
+    Replan,
+    SaveDefaults,
+    DefaultsSaved(Result<Box<Config>, String>),
+    PauseDownload,
+    ResumeDownload,
     Cancel,
 }
 
@@ -37,9 +70,43 @@ pub struct InstallDialog {
     pub is_installing: bool,
     pub is_complete: bool,
     pub installation_progress: String,
+    // Per-package progress, read from apt's status lines and Flatpak's output
+    pub progress: TransactionProgress,
+    pub terminal_output: String,
+    // Bumped for every install attempt, so a retry gets a fresh subscription
+    pub attempt: u32,
     pub theme: AppTheme,
     pub border_radius: f32,
     pub is_flatpak: bool,
+    // Remote to install each Flatpak from, when the search picked one
+    pub flatpak_remotes: HashMap<String, String>,
+    // Simulated apt transaction (None for Flatpak or when simulation fails)
+    pub plan: Option<TransactionPlan>,
+    // Installs that only recommends pull in, whether or not this install skips them
+    pub recommends: Option<Vec<PlannedPackage>>,
+    // The plan's extra dependencies are listed one by one, not just counted
+    pub show_dependencies: bool,
+    // What a Flatpak install fetches, runtimes included (None until remote-info answers)
+    pub flatpak_size: Option<InstallSize>,
+    // Installing a .flatpakref or bundle instead of apps by ID (boxed: rarely set)
+    pub flatpak_file: Option<Box<FlatpakFile>>,
+    // The remote installing the .flatpakref adds, when it isn't configured yet
+    pub new_remote: Option<String>,
+    // Root is available without a password (NOPASSWD sudo, permissive polkit rule, or already root)
+    pub passwordless: bool,
+    pub backend: Arc<dyn PackageBackend>,
+    // ABRoot/OSTree systems stage changes for the next boot instead of applying them
+    pub system: SystemKind,
+    // MAIN when run standalone, otherwise the child window it was opened in
+    pub window: window::Id,
+    // The running flatpak, which can be paused mid-download
+    pub process: ProcessHandle,
+    // Flags for this install, starting from the user's defaults
+    pub options: InstallOptions,
+    // Remote every Flatpak here installs from, over the ones picked per app
+    pub remote_override: Option<String>,
+    pub show_advanced: bool,
+    pub defaults_status: Option<Result<String, String>>,
 }
 
 impl InstallDialog {
@@ -51,9 +118,42 @@ impl InstallDialog {
             is_installing: false,
             is_complete: false,
             installation_progress: String::new(),
+            progress: TransactionProgress::default(),
+            terminal_output: String::new(),
+            attempt: 0,
             theme: AppTheme::Dark,
             border_radius: 12.0,
             is_flatpak,
+            flatpak_remotes: HashMap::new(),
+            plan: None,
+            recommends: None,
+            show_dependencies: false,
+            flatpak_size: None,
+            flatpak_file: None,
+            new_remote: None,
+            passwordless: false,
+            backend: crate::gui::create_backend(),
+            system: immutable::detect(),
+            window: window::Id::MAIN,
+            process: ProcessHandle::default(),
+            options: InstallOptions::default(),
+            remote_override: None,
+            show_advanced: false,
+            defaults_status: None,
+        }
+    }
+
+    pub fn for_file(file: FlatpakFile) -> Self {
+        Self { flatpak_file: Some(Box::new(file.clone())), ..Self::new(vec![file.name()], true) }
+    }
+
+    pub fn window_settings() -> window::Settings {
+        window::Settings {
+            size: scaling::fit(iced::Size::new(750.0, 800.0)),
+            min_size: Some(scaling::fit(iced::Size::new(600.0, 500.0))),
+            resizable: true,
+            decorations: true,
+            ..window::Settings::default()
         }
     }
 
@@ -63,16 +163,17 @@ impl InstallDialog {
     }
 
     pub fn run_separate_window_with_flatpak_flag(package_names: Vec<String>, is_flatpak: bool) -> Result<(), iced::Error> {
-        let dialog = Self::new(package_names, is_flatpak);
+        Self::run_standalone(Self::new(package_names, is_flatpak))
+    }
 
-        let mut window_settings = window::Settings::default();
-        window_settings.size = iced::Size::new(750.0, 800.0);
-        window_settings.min_size = Some(iced::Size::new(600.0, 500.0));
-        window_settings.resizable = true;
-        window_settings.decorations = true;
+    pub fn run_for_file(file: FlatpakFile) -> Result<(), iced::Error> {
+        Self::run_standalone(Self::for_file(file))
+    }
 
+    fn run_standalone(mut dialog: Self) -> Result<(), iced::Error> {
+        dialog.options = Config::load().map(|config| config.install_options).unwrap_or_default();
         <InstallDialog as Application>::run(Settings {
-            window: window_settings,
+            window: Self::window_settings(),
             flags: dialog,
             default_text_size: Pixels(14.0),
             antialiasing: true,
@@ -81,6 +182,15 @@ impl InstallDialog {
             default_font: iced::Font::DEFAULT,
         })
     }
+
+    // Simulate the install with the current flags, and what recommends change
+    fn simulate(&self, packages: Vec<String>) -> Command<Message> {
+        let flags = self.options.apt_args();
+        Command::batch([
+            Command::perform(load_install_plan(packages.clone(), flags.clone()), Message::PlanLoaded),
+            Command::perform(load_recommends(packages, flags), Message::RecommendsLoaded),
+        ])
+    }
 }
 
 impl Application for InstallDialog {
@@ -110,57 +220,190 @@ impl Application for InstallDialog {
     fn update(&mut self, message: Message) -> Command<Message> {
         match message {
             Message::LoadPackageInfo => {
+                if let Some(file) = self.flatpak_file.clone() {
+                    return load_file_info(*file);
+                }
                 self.is_loading = true;
                 let package_names = self.package_names.clone();
                 let is_flatpak = self.is_flatpak;
-                Command::perform(load_package_info(package_names, is_flatpak), |result| {
+                let load_info = Command::perform(load_package_info(self.backend.clone(), package_names.clone(), is_flatpak, self.flatpak_remotes.clone()), |result| {
                     match result {
                         Ok(infos) => Message::PackageInfoLoaded(infos),
                         Err(e) => Message::InstallationError(e),
                     }
-                })
+                });
+                if is_flatpak {
+                    Command::batch([load_info, Command::perform(load_flatpak_size(package_names, self.flatpak_remotes.clone()), Message::SizeLoaded)])
+                } else {
+                    let check_passwordless = Command::perform(
+                        async { tokio::task::spawn_blocking(elevation::passwordless).await.unwrap_or(false) },
+                        Message::PasswordlessChecked,
+                    );
+                    // apt can't simulate changes to an immutable image
+                    let load_plan = if self.system.is_immutable() {
+                        Command::none()
+                    } else {
+                        self.simulate(package_names)
+                    };
+                    Command::batch([load_info, load_plan, check_passwordless])
+                }
             }
             Message::PackageInfoLoaded(infos) => {
                 self.is_loading = false;
                 self.package_info = infos;
                 Command::none()
             }
+            Message::PlanLoaded(plan) => {
+                self.plan = plan;
+                Command::none()
+            }
+            Message::RecommendsLoaded(recommends) => {
+                self.recommends = recommends;
+                Command::none()
+            }
+            Message::ToggleDependencies => {
+                self.show_dependencies = !self.show_dependencies;
+                Command::none()
+            }
+            Message::ToggleAdvanced => {
+                self.show_advanced = !self.show_advanced;
+                Command::none()
+            }
+            Message::NoRecommendsToggled(skip) => {
+                self.options.no_install_recommends = skip;
+                self.defaults_status = None;
+                self.update(Message::Replan)
+            }
+            Message::TargetReleaseChanged(release) => {
+                self.options.target_release = optional(&release);
+                self.defaults_status = None;
+                Command::none()
+            }
+            Message::FlatpakRemoteChanged(remote) => {
+                self.remote_override = optional(&remote);
+                self.defaults_status = None;
+                Command::none()
+            }
+            Message::FlatpakBranchChanged(branch) => {
+                self.options.flatpak_branch = optional(&branch);
+                self.defaults_status = None;
+                Command::none()
+            }
+            Message::Replan => {
+                if self.is_flatpak || self.system.is_immutable() || self.options.check().is_err() {
+                    return Command::none();
+                }
+                self.simulate(self.package_names.clone())
+            }
+            Message::SaveDefaults => {
+                if let Err(e) = self.options.check() {
+                    self.defaults_status = Some(Err(e.to_string()));
+                    return Command::none();
+                }
+                let (is_flatpak, options, remote) = (self.is_flatpak, self.options.clone(), self.remote_override.clone());
+                // Only what this dialog shows is saved; the container is the pikman dialog's
+                Command::perform(
+                    advanced_options::save_defaults(move |config| {
+                        if is_flatpak {
+                            config.install_options.flatpak_branch = options.flatpak_branch;
+                            // A blank remote keeps picking per app, as before
+                            if remote.is_some() {
+                                config.flatpak_default_remote = remote;
+                            }
+                        } else {
+                            config.install_options.no_install_recommends = options.no_install_recommends;
+                            config.install_options.target_release = options.target_release;
+                        }
+                    }),
+                    Message::DefaultsSaved,
+                )
+            }
+            Message::DefaultsSaved(result) => {
+                self.defaults_status = Some(result.map(|_| "Saved; installs start with these from now on".to_string()));
+                Command::none()
+            }
+            Message::SizeLoaded(size) => {
+                self.flatpak_size = size;
+                Command::none()
+            }
+            Message::NewRemoteChecked(remote) => {
+                self.new_remote = remote;
+                Command::none()
+            }
+            Message::PasswordlessChecked(passwordless) => {
+                self.passwordless = passwordless;
+                Command::none()
+            }
             Message::InstallPackages => {
+                if let Err(e) = self.options.check() {
+                    self.show_advanced = true;
+                    self.installation_progress = format!("Error: {}", e);
+                    return Command::none();
+                }
+                // The run itself is the subscription below, active while installing
                 self.is_installing = true;
-                self.installation_progress = "Preparing installation...".to_string();
-                let package_names = self.package_names.clone();
-                let is_flatpak = self.package_info.first().map(|p| p.is_flatpak).unwrap_or(false);
-                Command::perform(install_packages(package_names, is_flatpak), |result| {
-                    match result {
-                        Ok(progress) => Message::InstallationProgress(progress),
-                        Err(e) => Message::InstallationError(e.to_string()),
-                    }
-                })
+                self.attempt += 1;
+                self.process = ProcessHandle::default();
+                self.installation_progress = "Installing...".to_string();
+                self.progress = TransactionProgress::new(&self.package_names);
+                self.terminal_output.clear();
+                Command::none()
             }
-            Message::InstallationProgress(progress) => {
-                let progress_clone = progress.clone();
-                self.installation_progress = progress;
-                if progress_clone.contains("Complete") ||
-                   progress_clone.contains("Installed") ||
-                   progress_clone.contains("complete") ||
-                   progress_clone.to_lowercase().contains("success") {
-                    Command::perform(async {}, |_| Message::InstallationComplete)
-                } else {
-                    Command::none()
+            Message::InstallationOutput(StreamEvent::Line(line)) => {
+                if self.progress.feed(&line) {
+                    if let Some(status) = self.progress.status().filter(|_| !self.process.is_paused()) {
+                        self.installation_progress = status.to_string();
+                    }
+                    return Command::none();
                 }
+                self.terminal_output.push_str(&line);
+                self.terminal_output.push('\n');
+                scrollable::snap_to(output_id(self.window), scrollable::RelativeOffset::END)
             }
+            Message::InstallationOutput(StreamEvent::Finished(result)) => match result {
+                Ok(()) => self.update(Message::InstallationComplete),
+                Err(e) => self.update(Message::InstallationError(e)),
+            },
             Message::InstallationComplete => {
                 self.is_installing = false;
                 self.is_complete = true;
+                self.progress.finish();
                 self.installation_progress = "Installation completed successfully!".to_string();
-                Command::none()
+                // Staged layers aren't installed until the reboot
+                if self.system.is_immutable() && !self.is_flatpak {
+                    return Command::none();
+                }
+                Command::perform(
+                    command_stream::run_hooks(HookEvent::Install, self.package_names.clone(), self.is_flatpak),
+                    Message::HooksFinished,
+                )
+            }
+            Message::HooksFinished(output) => {
+                self.terminal_output.push_str(&output);
+                scrollable::snap_to(output_id(self.window), scrollable::RelativeOffset::END)
             }
-            Message::InstallationError(_msg) => {
+            Message::InstallationError(msg) => {
+                tracing::debug!("InstallationError: {}", msg);
                 self.is_installing = false;
+                self.installation_progress = format!("Error: {}", msg);
+                Command::none()
+            }
+            Message::PauseDownload => {
+                self.process.pause();
+                self.installation_progress = "Paused".to_string();
+                Command::none()
+            }
+            Message::ResumeDownload => {
+                self.process.resume();
+                self.installation_progress = "Installing...".to_string();
                 Command::none()
             }
             Message::Cancel => {
-                iced::window::close(iced::window::Id::MAIN)
+                // A stopped flatpak would otherwise sit there after the window is gone
+                if self.process.is_paused() {
+                    self.process.resume();
+                }
+                iced::window::close(self.window)
             }
         }
     }
@@ -212,6 +455,14 @@ impl Application for InstallDialog {
         }
     }
 
+    fn subscription(&self) -> Subscription<Message> {
+        if self.is_installing {
+            command_stream::pausable_subscription(("install", self.attempt), self.install_steps(), self.process.clone(), Message::InstallationOutput)
+        } else {
+            Subscription::none()
+        }
+    }
+
     fn theme(&self) -> IcedTheme {
         match self.theme {
             AppTheme::Light => IcedTheme::Light,
@@ -220,7 +471,52 @@ impl Application for InstallDialog {
     }
 }
 
+// The output pane, kept scrolled to the newest line; widget operations reach
+// every open window, so the id is per window
+fn output_id(window: window::Id) -> scrollable::Id {
+    scrollable::Id::new(format!("install-output-{:?}", window))
+}
+
 impl InstallDialog {
+    pub fn install_steps(&self) -> Vec<Step> {
+        if let Some(file) = &self.flatpak_file {
+            let [flag, path] = file.source_args();
+            return vec![Step::Command {
+                command: BackendCommand::new("flatpak", &["install", "-y", "--noninteractive", &flag], &[path]),
+                elevated: false,
+            }];
+        }
+        let is_flatpak = self.package_info.first().map(|p| p.is_flatpak).unwrap_or(false);
+        if is_flatpak {
+            return self
+                .package_names
+                .iter()
+                .map(|package| {
+                    let mut args = vec!["install", "-y", "--noninteractive"];
+                    if let Some(remote) = self.remote_override.as_ref().or(self.flatpak_remotes.get(package)) {
+                        args.push(remote);
+                    }
+                    Step::Command {
+                        command: BackendCommand::new("flatpak", &args, &[self.options.flatpak_ref(package)]),
+                        elevated: false,
+                    }
+                })
+                .collect();
+        }
+        // Immutable systems need more than one step (e.g. `abroot pkg add` then `abroot pkg apply`)
+        let commands = self.options.apply_apt(self.backend.install_commands(&self.package_names));
+        if commands.is_empty() {
+            let (backend, packages) = (self.backend.clone(), self.package_names.clone());
+            return vec![Step::InProcess(Arc::new(move || backend.install(&packages)))];
+        }
+        // apt reports per-package progress on its status fd, which the dialog reads back
+        commands
+            .into_iter()
+            .map(progress::with_status_fd)
+            .map(|command| Step::Command { elevated: !command.as_user, command })
+            .collect()
+    }
+
     fn view_package_info(&self) -> Element<Message> {
         let theme = self.theme;
         let needs_sudo = !self.package_info.first().map(|p| p.is_flatpak).unwrap_or(false);
@@ -259,6 +555,28 @@ impl InstallDialog {
                     })))
                     .padding(Padding::new(14.0)),
                 Space::with_width(Length::Fill),
+                {
+                    // Only Flatpak downloads run unelevated, where they can be stopped
+                    if self.is_installing && !needs_sudo {
+                        let (label, message) = if self.process.is_paused() {
+                            ("Resume", Message::ResumeDownload)
+                        } else {
+                            ("Pause", Message::PauseDownload)
+                        };
+                        Element::from(button(label)
+                            .on_press(message)
+                            .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                                is_primary: false,
+                                radius: self.border_radius,
+                                primary_color: theme.primary(),
+                                text_color: theme.text(),
+                                background_color: theme.background(),
+                            })))
+                            .padding(Padding::new(14.0)))
+                    } else {
+                        Element::from(Space::with_width(Length::Fixed(0.0)))
+                    }
+                },
                 {
                     if self.is_installing {
                         button("Installing...")
@@ -366,6 +684,30 @@ impl InstallDialog {
             .spacing(0)
         };
 
+        let terminal_section = if !self.terminal_output.is_empty() {
+            column![
+                text("Output:")
+                    .size(14)
+                    .style(iced::theme::Text::Color(theme.primary())),
+                Space::with_height(Length::Fixed(8.0)),
+                scrollable(
+                    text(&self.terminal_output)
+                        .size(12)
+                        .font(iced::Font::MONOSPACE)
+                        .style(iced::theme::Text::Color(theme.text()))
+                )
+                .id(output_id(self.window))
+                .style(iced::theme::Scrollable::Custom(Box::new(CustomScrollableStyle {
+                    background_color: theme.surface(),
+                    border_radius: self.border_radius,
+                })))
+                .height(Length::Fixed(300.0)),
+            ]
+            .spacing(0)
+        } else {
+            column![].spacing(0)
+        };
+
         let progress_section = if !self.installation_progress.is_empty() {
             column![
                 Space::with_height(Length::Fixed(20.0)),
@@ -389,7 +731,7 @@ impl InstallDialog {
                 scrollable(
                     column![
                         content,
-                        if needs_sudo && !self.is_installing && !self.is_complete {
+                        if needs_sudo && !self.passwordless && !self.is_installing && !self.is_complete {
                             column![
                                 Space::with_height(Length::Fixed(12.0)),
                                 text("Administrator privileges will be requested")
@@ -400,7 +742,56 @@ impl InstallDialog {
                         } else {
                             column![].spacing(0)
                         },
+                        match &self.new_remote {
+                            Some(remote) if !self.is_installing && !self.is_complete => column![
+                                text(format!("Installing this adds the Flatpak remote '{}'", remote))
+                                    .size(12)
+                                    .style(iced::theme::Text::Color(Color::from_rgb(1.0, 0.8, 0.0))),
+                            ]
+                            .spacing(0),
+                            _ => column![].spacing(0),
+                        },
+                        if needs_sudo && self.system.is_immutable() {
+                            column![
+                                text(immutable::REBOOT_NOTICE)
+                                    .size(12)
+                                    .style(iced::theme::Text::Color(Color::from_rgb(1.0, 0.8, 0.0))),
+                            ]
+                            .spacing(0)
+                        } else {
+                            column![].spacing(0)
+                        },
+                        if self.flatpak_file.is_none() && !self.is_installing && !self.is_complete {
+                            self.view_advanced()
+                        } else {
+                            Element::from(column![].spacing(0))
+                        },
+                        match &self.plan {
+                            Some(plan) if !self.is_installing && !self.is_complete => view_plan(
+                                plan,
+                                &self.package_names,
+                                Some((self.show_dependencies, Message::ToggleDependencies)),
+                                theme,
+                            ),
+                            _ => Element::from(column![].spacing(0)),
+                        },
+                        match &self.recommends {
+                            Some(extras) if self.plan.is_some() && !self.is_installing && !self.is_complete => {
+                                view_recommends(extras, self.options.no_install_recommends, theme)
+                            }
+                            _ => Element::from(column![].spacing(0)),
+                        },
+                        match &self.flatpak_size {
+                            Some(size) if !self.is_installing && !self.is_complete => view_flatpak_size(size, theme),
+                            _ => Element::from(column![].spacing(0)),
+                        },
+                        if self.is_installing || self.is_complete {
+                            progress_bars(&self.progress, theme)
+                        } else {
+                            Element::from(column![].spacing(0))
+                        },
                         progress_section,
+                        terminal_section,
                     ]
                     .spacing(15)
                     .padding(Padding::new(20.0))
@@ -428,17 +819,138 @@ impl InstallDialog {
     }
 }
 
-async fn load_package_info(package_names: Vec<String>, is_flatpak: bool) -> Result<Vec<PackageDetail>, String> {
+impl InstallDialog {
+    // apt flags for packages; remote and branch for Flatpaks
+    fn view_advanced(&self) -> Element<'_, Message> {
+        let (theme, radius) = (self.theme, self.border_radius);
+        let fields = if self.is_flatpak {
+            vec![
+                advanced_options::field(
+                    "Remote",
+                    "As picked for each app",
+                    self.remote_override.as_deref().unwrap_or(""),
+                    Message::FlatpakRemoteChanged,
+                    theme,
+                    radius,
+                ),
+                advanced_options::field(
+                    "Branch",
+                    "The app's default, e.g. stable",
+                    self.options.flatpak_branch.as_deref().unwrap_or(""),
+                    Message::FlatpakBranchChanged,
+                    theme,
+                    radius,
+                ),
+            ]
+        } else {
+            vec![
+                advanced_options::toggle(
+                    "Don't install recommended packages (--no-install-recommends)",
+                    self.options.no_install_recommends,
+                    Message::NoRecommendsToggled,
+                    theme,
+                ),
+                row![
+                    advanced_options::field(
+                        "Target release",
+                        "e.g. noble-backports",
+                        self.options.target_release.as_deref().unwrap_or(""),
+                        Message::TargetReleaseChanged,
+                        theme,
+                        radius,
+                    ),
+                    button(text("Simulate").size(13))
+                        .on_press(Message::Replan)
+                        .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                            is_primary: false,
+                            radius,
+                            primary_color: theme.primary(),
+                            text_color: theme.text(),
+                            background_color: theme.background(),
+                        })))
+                        .padding(Padding::from([6.0, 12.0])),
+                ]
+                .spacing(10)
+                .align_items(alignment::Alignment::Center)
+                .into(),
+            ]
+        };
+        advanced_options::view(
+            self.show_advanced,
+            Message::ToggleAdvanced,
+            fields,
+            Message::SaveDefaults,
+            self.defaults_status.as_ref(),
+            theme,
+            radius,
+        )
+    }
+}
+
+// A .flatpakref describes its app without asking a remote, which may not be
+// configured yet; a bundle is only known by its file name
+fn load_file_info(file: FlatpakFile) -> Command<Message> {
+    let detail = match &file {
+        FlatpakFile::Ref { app, .. } => PackageDetail {
+            name: app.name.clone(),
+            version: app.branch.clone().unwrap_or_else(|| "Unknown".to_string()),
+            description: app.title.clone().unwrap_or_else(|| format!("From {}", app.url)),
+            size: "Unknown".to_string(),
+            is_flatpak: true,
+        },
+        FlatpakFile::Bundle(path) => PackageDetail {
+            name: file.name(),
+            version: "Unknown".to_string(),
+            description: format!("Flatpak bundle {}", path.display()),
+            size: "Unknown".to_string(),
+            is_flatpak: true,
+        },
+    };
+    let check_remote = match file {
+        FlatpakFile::Ref { app, .. } => Command::perform(
+            async move {
+                tokio::task::spawn_blocking(move || FlatpakManager::new()?.new_remote(&app))
+                    .await
+                    .ok()
+                    .and_then(Result::ok)
+                    .flatten()
+            },
+            Message::NewRemoteChecked,
+        ),
+        FlatpakFile::Bundle(_) => Command::none(),
+    };
+    Command::batch([Command::perform(async move { vec![detail] }, Message::PackageInfoLoaded), check_remote])
+}
+
+// Sizes from the remote each app installs from; flathub when none was picked,
+// as for the package details
+async fn load_flatpak_size(apps: Vec<String>, remotes: HashMap<String, String>) -> Option<InstallSize> {
+    let apps: Vec<(String, String)> = apps
+        .into_iter()
+        .map(|app| {
+            let remote = remotes.get(&app).cloned().unwrap_or_else(|| "flathub".to_string());
+            (app, remote)
+        })
+        .collect();
+    tokio::task::spawn_blocking(move || FlatpakManager::new()?.install_size(&apps))
+        .await
+        .ok()?
+        .map_err(|e| tracing::debug!("InstallDialog: no Flatpak sizes: {}", e))
+        .ok()
+}
+
+async fn load_package_info(backend: Arc<dyn PackageBackend>, package_names: Vec<String>, is_flatpak: bool, remotes: HashMap<String, String>) -> Result<Vec<PackageDetail>, String> {
     use futures::future;
     
     #[cfg(debug_assertions)]
-    eprintln!("[DEBUG] Loading package info for {} packages (flatpak: {})", package_names.len(), is_flatpak);
+    tracing::debug!("Loading package info for {} packages (flatpak: {})", package_names.len(), is_flatpak);
     
     let futures: Vec<_> = package_names.into_iter()
         .map(|pkg| {
             #[cfg(debug_assertions)]
-            eprintln!("[DEBUG] Loading detail for package: {}", pkg);
-            load_single_package_detail(pkg, is_flatpak)
+            tracing::debug!("Loading detail for package: {}", pkg);
+            let remote = remotes.get(&pkg).cloned();
+            load_single_package_detail(backend.clone(), pkg, is_flatpak, remote)
         })
         .collect();
     
@@ -449,13 +961,13 @@ async fn load_package_info(package_names: Vec<String>, is_flatpak: bool) -> Resu
         match result {
             Ok(detail) => {
                 #[cfg(debug_assertions)]
-                eprintln!("[DEBUG] Successfully loaded package detail: {}", detail.name);
+                tracing::debug!("Successfully loaded package detail: {}", detail.name);
                 details.push(detail)
             },
             Err(e) => {
                 #[cfg(debug_assertions)]
-                eprintln!("[DEBUG] Warning: Failed to load package detail: {}", e);
-                eprintln!("Warning: Failed to load package detail: {}", e);
+                tracing::warn!("Failed to load package detail: {}", e);
+                tracing::warn!("Failed to load package detail: {}", e);
             }
         }
     }
@@ -464,19 +976,19 @@ async fn load_package_info(package_names: Vec<String>, is_flatpak: bool) -> Resu
         Err("Failed to load information for any packages".to_string())
     } else {
         #[cfg(debug_assertions)]
-        eprintln!("[DEBUG] Loaded {} package details", details.len());
+        tracing::debug!("Loaded {} package details", details.len());
         Ok(details)
     }
 }
 
-async fn load_single_package_detail(package: String, is_flatpak: bool) -> Result<PackageDetail, String> {
+async fn load_single_package_detail(backend: Arc<dyn PackageBackend>, package: String, is_flatpak: bool, remote: Option<String>) -> Result<PackageDetail, String> {
     #[cfg(debug_assertions)]
-    eprintln!("[FLATPAK DEBUG] load_single_package_detail() called for package: '{}', is_flatpak: {}", package, is_flatpak);
+    tracing::debug!("load_single_package_detail() called for package: '{}', is_flatpak: {}", package, is_flatpak);
     
     // Validate Flatpak application ID format (must contain at least 2 periods)
     if is_flatpak && !package.contains('.') {
         #[cfg(debug_assertions)]
-        eprintln!("[FLATPAK DEBUG] load_single_package_detail: ERROR - Invalid Flatpak ID format: '{}' (must contain at least one period)", package);
+        tracing::warn!("load_single_package_detail: Invalid Flatpak ID format: '{}' (must contain at least one period)", package);
         return Err(format!("Invalid Flatpak application ID: '{}'. Application IDs must be in the format 'org.example.App' (containing at least one period).", package));
     }
     
@@ -485,78 +997,85 @@ async fn load_single_package_detail(package: String, is_flatpak: bool) -> Result
         let period_count = package.matches('.').count();
         if period_count < 1 {
             #[cfg(debug_assertions)]
-            eprintln!("[FLATPAK DEBUG] load_single_package_detail: ERROR - Invalid Flatpak ID format: '{}' (must contain at least one period, found {})", package, period_count);
+            tracing::warn!("load_single_package_detail: Invalid Flatpak ID format: '{}' (must contain at least one period, found {})", package, period_count);
             return Err(format!("Invalid Flatpak application ID: '{}'. Application IDs must be in the format 'org.example.App' (containing at least one period).", package));
         }
     }
     
     tokio::task::spawn_blocking(move || {
-        use crate::utils::run_command;
+        use birdnest_core::utils::run_command;
         
         if is_flatpak {
             #[cfg(debug_assertions)]
-            eprintln!("[FLATPAK DEBUG] load_single_package_detail: Loading flatpak package info...");
+            tracing::debug!("load_single_package_detail: Loading flatpak package info...");
             // Try flatpak info first (for installed packages)
             #[cfg(debug_assertions)]
-            eprintln!("[FLATPAK DEBUG] load_single_package_detail: Trying 'flatpak info {}'...", package);
+            tracing::debug!("load_single_package_detail: Trying 'flatpak info {}'...", package);
             let info_output = match run_command("flatpak", &["info", &package], false) {
                 Ok(output) => {
                     #[cfg(debug_assertions)]
-                    eprintln!("[FLATPAK DEBUG] load_single_package_detail: 'flatpak info' succeeded, output length: {} bytes", output.len());
+                    tracing::debug!("load_single_package_detail: 'flatpak info' succeeded, output length: {} bytes", output.len());
                     Ok(output)
                 },
                 Err(_e) => {
                     #[cfg(debug_assertions)]
-                    eprintln!("[FLATPAK DEBUG] load_single_package_detail: 'flatpak info' failed: {}, trying remote-info...", _e);
-                    // Package not installed, try remote-info with common remotes
-                    // Try flathub first (most common remote)
-                    #[cfg(debug_assertions)]
-                    eprintln!("[FLATPAK DEBUG] load_single_package_detail: Trying 'flatpak remote-info flathub {}'...", package);
-                    run_command("flatpak", &["remote-info", "flathub", &package], false)
+                    tracing::debug!("load_single_package_detail: 'flatpak info' failed: {}, trying remote-info...", _e);
+                    // Package not installed: ask the remote it will install from, if one was picked,
+                    // otherwise try remote-info with common remotes
+                    remote
+                        .as_deref()
+                        .ok_or_else(|| anyhow::anyhow!("no remote picked"))
+                        .and_then(|remote| run_command("flatpak", &["remote-info", remote, &package], false))
+                        .or_else(|_| {
+                            // Try flathub first (most common remote)
+                            #[cfg(debug_assertions)]
+                            tracing::debug!("load_single_package_detail: Trying 'flatpak remote-info flathub {}'...", package);
+                            run_command("flatpak", &["remote-info", "flathub", &package], false)
+                        })
                         .or_else(|_e| {
                             #[cfg(debug_assertions)]
-                            eprintln!("[FLATPAK DEBUG] load_single_package_detail: flathub failed: {}, trying other remotes...", _e);
+                            tracing::debug!("load_single_package_detail: flathub failed: {}, trying other remotes...", _e);
                             // Try other common remotes
                             for remote in &["fedora", "gnome-nightly", "kdeapps", "elementary"] {
                                 #[cfg(debug_assertions)]
-                                eprintln!("[FLATPAK DEBUG] load_single_package_detail: Trying remote: {}", remote);
+                                tracing::debug!("load_single_package_detail: Trying remote: {}", remote);
                                 if let Ok(output) = run_command("flatpak", &["remote-info", remote, &package], false) {
                                     #[cfg(debug_assertions)]
-                                    eprintln!("[FLATPAK DEBUG] load_single_package_detail: Success with remote: {}", remote);
+                                    tracing::debug!("load_single_package_detail: Success with remote: {}", remote);
                                     return Ok(output);
                                 }
                             }
                             // Last resort: try to find remote by listing all remotes and trying each
                             #[cfg(debug_assertions)]
-                            eprintln!("[FLATPAK DEBUG] load_single_package_detail: All common remotes failed, listing all remotes...");
+                            tracing::debug!("load_single_package_detail: All common remotes failed, listing all remotes...");
                             if let Ok(remotes_output) = run_command("flatpak", &["remotes", "--columns=name"], false) {
                                 #[cfg(debug_assertions)]
-                                eprintln!("[FLATPAK DEBUG] load_single_package_detail: Got remotes list, length: {} bytes", remotes_output.len());
+                                tracing::debug!("load_single_package_detail: Got remotes list, length: {} bytes", remotes_output.len());
                                 for remote in remotes_output.lines() {
                                     let remote = remote.trim();
                                     if !remote.is_empty() {
                                         #[cfg(debug_assertions)]
-                                        eprintln!("[FLATPAK DEBUG] load_single_package_detail: Trying remote: {}", remote);
+                                        tracing::debug!("load_single_package_detail: Trying remote: {}", remote);
                                         if let Ok(output) = run_command("flatpak", &["remote-info", remote, &package], false) {
                                             #[cfg(debug_assertions)]
-                                            eprintln!("[FLATPAK DEBUG] load_single_package_detail: Success with remote: {}", remote);
+                                            tracing::debug!("load_single_package_detail: Success with remote: {}", remote);
                                             return Ok(output);
                                         }
                                     }
                                 }
                             } else {
                                 #[cfg(debug_assertions)]
-                                eprintln!("[FLATPAK DEBUG] load_single_package_detail: Failed to list remotes");
+                                tracing::debug!("load_single_package_detail: Failed to list remotes");
                             }
                             #[cfg(debug_assertions)]
-                            eprintln!("[FLATPAK DEBUG] load_single_package_detail: ERROR - All attempts failed");
+                            tracing::warn!("load_single_package_detail: All attempts failed");
                             Err(format!("Failed to get flatpak info for {} (package may not be available in any remote)", package))
                         })
                 }
             }?;
             
             #[cfg(debug_assertions)]
-            eprintln!("[FLATPAK DEBUG] load_single_package_detail: Got info output, parsing...");
+            tracing::debug!("load_single_package_detail: Got info output, parsing...");
             let mut version = String::new();
             let mut description = String::new();
             let mut size = String::new();
@@ -579,7 +1098,7 @@ async fn load_single_package_detail(package: String, is_flatpak: bool) -> Result
                         description = line.to_string();
                     }
                     #[cfg(debug_assertions)]
-                    eprintln!("[FLATPAK DEBUG] load_single_package_detail: Found description (first line): {}", description);
+                    tracing::debug!("load_single_package_detail: Found description (first line): {}", description);
                     is_first_line = false;
                     continue;
                 }
@@ -589,13 +1108,13 @@ async fn load_single_package_detail(package: String, is_flatpak: bool) -> Result
                 if line.starts_with("Version:") {
                     version = line.replace("Version:", "").trim().to_string();
                     #[cfg(debug_assertions)]
-                    eprintln!("[FLATPAK DEBUG] load_single_package_detail: Found version: {}", version);
+                    tracing::debug!("load_single_package_detail: Found version: {}", version);
                 }
                 // Parse description (if not found on first line)
                 else if line.starts_with("Description:") {
                     description = line.replace("Description:", "").trim().to_string();
                     #[cfg(debug_assertions)]
-                    eprintln!("[FLATPAK DEBUG] load_single_package_detail: Found description (line): {}", description);
+                    tracing::debug!("load_single_package_detail: Found description (line): {}", description);
                 }
                 // Parse size - flatpak remote-info uses "Download:" and "Installed:" format
                 else if line.starts_with("Download:") {
@@ -606,7 +1125,7 @@ async fn load_single_package_detail(package: String, is_flatpak: bool) -> Result
                         size = format!("{} / Download: {}", size, download_size);
                     }
                     #[cfg(debug_assertions)]
-                    eprintln!("[FLATPAK DEBUG] load_single_package_detail: Found download size: {}", download_size);
+                    tracing::debug!("load_single_package_detail: Found download size: {}", download_size);
                 }
                 else if line.starts_with("Installed:") {
                     let installed_size = line.replace("Installed:", "").trim().to_string();
@@ -616,7 +1135,7 @@ async fn load_single_package_detail(package: String, is_flatpak: bool) -> Result
                         size = format!("{} / Installed: {}", size, installed_size);
                     }
                     #[cfg(debug_assertions)]
-                    eprintln!("[FLATPAK DEBUG] load_single_package_detail: Found installed size: {}", installed_size);
+                    tracing::debug!("load_single_package_detail: Found installed size: {}", installed_size);
                 }
                 // Also handle old format for flatpak info (installed packages)
                 else if line.starts_with("Installed size:") || line.starts_with("Download size:") {
@@ -624,7 +1143,7 @@ async fn load_single_package_detail(package: String, is_flatpak: bool) -> Result
                         .replace("Download size:", "")
                         .trim().to_string();
                     #[cfg(debug_assertions)]
-                    eprintln!("[FLATPAK DEBUG] load_single_package_detail: Found size (old format): {}", size_str);
+                    tracing::debug!("load_single_package_detail: Found size (old format): {}", size_str);
                     if size.is_empty() {
                         size = size_str;
                     } else {
@@ -634,7 +1153,7 @@ async fn load_single_package_detail(package: String, is_flatpak: bool) -> Result
             }
             
             #[cfg(debug_assertions)]
-            eprintln!("[FLATPAK DEBUG] load_single_package_detail: Creating PackageDetail - name: {}, version: {}, size: {}", package, version, size);
+            tracing::debug!("load_single_package_detail: Creating PackageDetail - name: {}, version: {}, size: {}", package, version, size);
             Ok(PackageDetail {
                 name: package,
                 version: if version.is_empty() { "Unknown".to_string() } else { version },
@@ -643,47 +1162,13 @@ async fn load_single_package_detail(package: String, is_flatpak: bool) -> Result
                 is_flatpak: true,
             })
         } else {
-            let show_output = run_command("apt", &["show", &package], false)
+            let details = backend.info(&package)
                 .map_err(|e| format!("Failed to get package info: {}", e))?;
-            let mut version = String::new();
-            let mut description = String::new();
-            let mut size = String::new();
-            
-            for line in show_output.lines() {
-                if line.starts_with("Version:") {
-                    version = line.replace("Version:", "").trim().to_string();
-                } else if line.starts_with("Description:") {
-                    description = line.replace("Description:", "").trim().to_string();
-                } else if line.starts_with("Installed-Size:") {
-                    let size_kb = line.replace("Installed-Size:", "").trim().to_string();
-                    if let Ok(kb) = size_kb.parse::<f64>() {
-                        if kb >= 1024.0 {
-                            size = format!("{:.2} MB", kb / 1024.0);
-                        } else {
-                            size = format!("{} KB", size_kb);
-                        }
-                    } else {
-                        size = format!("{} KB", size_kb);
-                    }
-                }
-            }
-            
-            if description.is_empty() {
-                for line in show_output.lines() {
-                    if line.starts_with("Description:") {
-                        description = line.replace("Description:", "").trim().to_string();
-                    } else if !description.is_empty() && line.starts_with(" ") {
-                        description.push_str(" ");
-                        description.push_str(line.trim());
-                    }
-                }
-            }
-            
             Ok(PackageDetail {
                 name: package,
-                version: if version.is_empty() { "Unknown".to_string() } else { version },
-                description: if description.is_empty() { "No description available".to_string() } else { description },
-                size: if size.is_empty() { "Unknown".to_string() } else { size },
+                version: details.version.clone(),
+                description: crate::gui::package_description(&details),
+                size: crate::gui::package_size(&details),
                 is_flatpak: false,
             })
         }
@@ -691,136 +1176,3 @@ async fn load_single_package_detail(package: String, is_flatpak: bool) -> Result
     .await
     .map_err(|e| format!("Task error: {}", e))?
 }
-
-async fn install_packages(package_names: Vec<String>, is_flatpak: bool) -> Result<String, String> {
-    #[cfg(debug_assertions)]
-    eprintln!("[FLATPAK DEBUG] install_packages() called with {} packages (flatpak: {})", package_names.len(), is_flatpak);
-    
-    if is_flatpak {
-        #[cfg(debug_assertions)]
-        eprintln!("[FLATPAK DEBUG] install_packages: Installing flatpak packages...");
-        // Install flatpak packages
-        for (idx, package) in package_names.iter().enumerate() {
-            #[cfg(debug_assertions)]
-            eprintln!("[FLATPAK DEBUG] install_packages: Installing package {}/{}: {}", idx + 1, package_names.len(), package);
-            
-            let mut cmd = TokioCommand::new("flatpak");
-            cmd.arg("install");
-            cmd.arg("-y");
-            cmd.arg(package);
-            
-            #[cfg(debug_assertions)]
-            eprintln!("[FLATPAK DEBUG] install_packages: Executing command: flatpak install -y {}", package);
-            
-            let output = cmd
-                .output()
-                .await
-                .map_err(|e| {
-                    #[cfg(debug_assertions)]
-                    eprintln!("[FLATPAK DEBUG] install_packages: Command execution failed: {}", e);
-                    format!("Failed to execute installation: {}", e)
-                })?;
-            
-            #[cfg(debug_assertions)]
-            {
-                eprintln!("[FLATPAK DEBUG] install_packages: Command exit status: {:?}", output.status);
-                eprintln!("[FLATPAK DEBUG] install_packages: Command success: {}", output.status.success());
-                if !output.stdout.is_empty() {
-                    eprintln!("[FLATPAK DEBUG] install_packages: Command stdout (first 500 chars): {}", 
-                        String::from_utf8_lossy(&output.stdout).chars().take(500).collect::<String>());
-                }
-                if !output.stderr.is_empty() {
-                    eprintln!("[FLATPAK DEBUG] install_packages: Command stderr (first 500 chars): {}", 
-                        String::from_utf8_lossy(&output.stderr).chars().take(500).collect::<String>());
-                }
-            }
-            
-            if !output.status.success() {
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                #[cfg(debug_assertions)]
-                eprintln!("[FLATPAK DEBUG] install_packages: ERROR - Installation failed for package: {}", package);
-                #[cfg(debug_assertions)]
-                eprintln!("[FLATPAK DEBUG] install_packages: Error details: {}", stderr);
-                return Err(format!("Installation failed: {}", stderr));
-            }
-            
-            #[cfg(debug_assertions)]
-            eprintln!("[FLATPAK DEBUG] install_packages: Successfully installed flatpak package: {}", package);
-        }
-        #[cfg(debug_assertions)]
-        eprintln!("[FLATPAK DEBUG] install_packages: All flatpak packages installed successfully");
-        Ok("Installation Complete!".to_string())
-    } else {
-        // Install apt packages using pkexec
-        #[cfg(debug_assertions)]
-        eprintln!("[DEBUG] Installing apt packages: {:?}", package_names);
-        
-        let mut cmd = TokioCommand::new("pkexec");
-        cmd.arg("apt");
-        cmd.arg("install");
-        cmd.arg("-y");
-        for name in &package_names {
-            cmd.arg(name);
-        }
-        
-        #[cfg(debug_assertions)]
-        {
-            let cmd_str = format!("pkexec apt install -y {}", package_names.join(" "));
-            eprintln!("[DEBUG] Executing command: {}", cmd_str);
-        }
-        
-        // Ensure DISPLAY is set for GUI password dialog
-        if let Ok(display) = std::env::var("DISPLAY") {
-            #[cfg(debug_assertions)]
-            eprintln!("[DEBUG] Setting DISPLAY environment variable: {}", display);
-            cmd.env("DISPLAY", display);
-        }
-        if let Ok(xauth) = std::env::var("XAUTHORITY") {
-            #[cfg(debug_assertions)]
-            eprintln!("[DEBUG] Setting XAUTHORITY environment variable");
-            cmd.env("XAUTHORITY", xauth);
-        }
-        if let Ok(wayland) = std::env::var("WAYLAND_DISPLAY") {
-            #[cfg(debug_assertions)]
-            eprintln!("[DEBUG] Setting WAYLAND_DISPLAY environment variable: {}", wayland);
-            cmd.env("WAYLAND_DISPLAY", wayland);
-        }
-        
-        let output = cmd
-            .output()
-            .await
-            .map_err(|e| {
-                #[cfg(debug_assertions)]
-                eprintln!("[DEBUG] Command execution failed: {}", e);
-                format!("Failed to execute installation: {}. Make sure polkit is installed.", e)
-            })?;
-        
-        #[cfg(debug_assertions)]
-        {
-            eprintln!("[DEBUG] Command exit status: {:?}", output.status);
-            if !output.stdout.is_empty() {
-                eprintln!("[DEBUG] Command stdout: {}", String::from_utf8_lossy(&output.stdout));
-            }
-            if !output.stderr.is_empty() {
-                eprintln!("[DEBUG] Command stderr: {}", String::from_utf8_lossy(&output.stderr));
-            }
-        }
-        
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            if output.status.code() == Some(126) || output.status.code() == Some(127) {
-                #[cfg(debug_assertions)]
-                eprintln!("[DEBUG] Authentication cancelled or failed");
-                return Err("Authentication cancelled or failed. Please try again.".to_string());
-            }
-            #[cfg(debug_assertions)]
-            eprintln!("[DEBUG] Installation failed");
-            return Err(format!("Installation failed: {}", stderr));
-        }
-        
-        #[cfg(debug_assertions)]
-        eprintln!("[DEBUG] All apt packages installed successfully");
-        Ok("Installation Complete!".to_string())
-    }
-}
-
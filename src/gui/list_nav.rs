@@ -0,0 +1,115 @@
+use iced::keyboard::{key::Named, Key, Modifiers};
+use iced::widget::scrollable;
+use iced::Command;
+use std::time::{Duration, Instant};
+
+// Keyboard navigation for long lists: type-ahead like a file manager's, plus
+// arrows, PageUp/PageDown and Home/End. The list keeps a ListCursor and
+// scrolls with scroll_to; key presses reach it only when no text field took them.
+
+// Letters typed closer together than this build one prefix
+const TYPE_AHEAD_TIMEOUT: Duration = Duration::from_millis(1000);
+/// Rows PageUp and PageDown move by
+pub const PAGE_ROWS: usize = 10;
+
+/// A navigation key pressed while nothing else had the keyboard
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListKey {
+    Char(char),
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+}
+
+impl ListKey {
+    /// What a key press means to a list; shortcuts with Ctrl, Alt or Super
+    /// are left to whoever handles them
+    pub fn from_key(key: &Key, modifiers: Modifiers) -> Option<Self> {
+        if modifiers.control() || modifiers.alt() || modifiers.logo() {
+            return None;
+        }
+        match key {
+            Key::Character(text) => {
+                let mut chars = text.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) if !c.is_whitespace() && !c.is_control() => Some(ListKey::Char(c)),
+                    _ => None,
+                }
+            }
+            Key::Named(Named::ArrowUp) => Some(ListKey::Up),
+            Key::Named(Named::ArrowDown) => Some(ListKey::Down),
+            Key::Named(Named::PageUp) => Some(ListKey::PageUp),
+            Key::Named(Named::PageDown) => Some(ListKey::PageDown),
+            Key::Named(Named::Home) => Some(ListKey::Home),
+            Key::Named(Named::End) => Some(ListKey::End),
+            _ => None,
+        }
+    }
+}
+
+/// The row the keyboard is on in one list, and the prefix typed so far
+#[derive(Debug, Default)]
+pub struct ListCursor {
+    pub index: Option<usize>,
+    prefix: String,
+    last_typed: Option<Instant>,
+}
+
+impl ListCursor {
+    /// Move for `key` through a list of `names`, returning the row moved to
+    pub fn handle<S: AsRef<str>>(&mut self, key: &ListKey, names: &[S], now: Instant) -> Option<usize> {
+        let last = names.len().checked_sub(1)?;
+        let current = self.index.unwrap_or(0).min(last);
+        let index = match key {
+            ListKey::Char(c) => self.type_ahead(*c, names, now)?,
+            ListKey::Up => current.saturating_sub(1),
+            ListKey::Down if self.index.is_none() => 0,
+            ListKey::Down => (current + 1).min(last),
+            ListKey::PageUp => current.saturating_sub(PAGE_ROWS),
+            ListKey::PageDown => (current + PAGE_ROWS).min(last),
+            ListKey::Home => 0,
+            ListKey::End => last,
+        };
+        if !matches!(key, ListKey::Char(_)) {
+            self.prefix.clear();
+        }
+        self.index = Some(index);
+        Some(index)
+    }
+
+    fn type_ahead<S: AsRef<str>>(&mut self, c: char, names: &[S], now: Instant) -> Option<usize> {
+        if self.last_typed.is_some_and(|last| now.duration_since(last) > TYPE_AHEAD_TIMEOUT) {
+            self.prefix.clear();
+        }
+        self.last_typed = Some(now);
+        self.prefix.extend(c.to_lowercase());
+
+        let starts_with = |name: &str, prefix: &str| name.to_lowercase().starts_with(prefix);
+        // Pressing one letter again steps through the names starting with it
+        let mut letters = self.prefix.chars();
+        let first = letters.next()?;
+        if self.prefix.chars().count() > 1 && letters.all(|l| l == first) {
+            let letter = first.to_string();
+            let from = self.index.map_or(0, |i| i + 1);
+            return (from..names.len())
+                .chain(0..from)
+                .find(|&i| starts_with(names[i].as_ref(), &letter));
+        }
+        names.iter().position(|name| starts_with(name.as_ref(), &self.prefix))
+    }
+
+    /// Forget the position, for when the list is filtered or reordered
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// Scroll so row `index` of `len` comes into view. Rows differ in height, so
+/// the offset is proportional, which lands close enough in a long list.
+pub fn scroll_to<M: 'static>(id: scrollable::Id, index: usize, len: usize) -> Command<M> {
+    let y = if len > 1 { index as f32 / (len - 1) as f32 } else { 0.0 };
+    scrollable::snap_to(id, scrollable::RelativeOffset { x: 0.0, y })
+}
@@ -0,0 +1,292 @@
+use iced::{
+    alignment, executor, Color,
+    widget::{button, column, container, row, scrollable, text, Column, Space},
+    Application, Command, Element, Length, Pixels, Settings, Theme as IcedTheme, Padding,
+    window,
+};
+use std::path::PathBuf;
+use tokio::process::Command as TokioCommand;
+
+use crate::gui::scaling;
+use crate::gui::theme::Theme as AppTheme;
+use crate::gui::styles::{RoundedButtonStyle, RoundedContainerStyle, CustomScrollableStyle};
+use crate::logging::{self, Level};
+
+// A long day of debug output would make the view crawl; older lines stay in the file
+const MAX_LINES: usize = 2000;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    FilesLoaded(Vec<PathBuf>),
+    FileLoaded(String),
+    SelectFile(usize),
+    SetLevel(Level),
+    Refresh,
+    OpenFolder,
+    FolderOpened(Result<(), String>),
+    Close,
+}
+
+// Help > Logs: BirdNest's log files, newest first, filtered by level
+#[derive(Debug)]
+pub struct LogViewerDialog {
+    pub files: Vec<PathBuf>,
+    selected: usize,
+    pub content: String,
+    pub min_level: Level,
+    error: Option<String>,
+    pub theme: AppTheme,
+    pub border_radius: f32,
+    // MAIN when run standalone, otherwise the child window it was opened in
+    pub window: window::Id,
+}
+
+async fn load_files() -> Vec<PathBuf> {
+    tokio::task::spawn_blocking(|| logging::log_dir().map(|dir| logging::log_files(&dir)).unwrap_or_default())
+        .await
+        .unwrap_or_default()
+}
+
+async fn load_file(path: PathBuf) -> String {
+    tokio::fs::read_to_string(&path).await.unwrap_or_else(|e| format!("Can't read {}: {}", path.display(), e))
+}
+
+async fn open_folder() -> Result<(), String> {
+    let dir = logging::log_dir().ok_or("HOME is not set")?;
+    TokioCommand::new("xdg-open")
+        .arg(&dir)
+        .spawn()
+        .map_err(|e| format!("Failed to open {}: {}", dir.display(), e))?;
+    Ok(())
+}
+
+impl LogViewerDialog {
+    pub fn new() -> Self {
+        Self {
+            files: Vec::new(),
+            selected: 0,
+            content: String::new(),
+            min_level: Level::Info,
+            error: None,
+            theme: AppTheme::Dark,
+            border_radius: 12.0,
+            window: window::Id::MAIN,
+        }
+    }
+
+    pub fn window_settings() -> window::Settings {
+        window::Settings {
+            size: scaling::fit(iced::Size::new(960.0, 680.0)),
+            min_size: Some(scaling::fit(iced::Size::new(700.0, 450.0))),
+            resizable: true,
+            decorations: true,
+            ..window::Settings::default()
+        }
+    }
+
+    pub fn run_separate_window() -> Result<(), iced::Error> {
+        let dialog = Self::new();
+
+        <LogViewerDialog as Application>::run(Settings {
+            window: Self::window_settings(),
+            flags: dialog,
+            default_text_size: Pixels(14.0),
+            antialiasing: true,
+            id: None,
+            fonts: Vec::new(),
+            default_font: iced::Font::DEFAULT,
+        })
+    }
+
+    fn load_selected(&self) -> Command<Message> {
+        match self.files.get(self.selected) {
+            Some(path) => Command::perform(load_file(path.clone()), Message::FileLoaded),
+            None => Command::none(),
+        }
+    }
+}
+
+impl Application for LogViewerDialog {
+    type Message = Message;
+    type Theme = IcedTheme;
+    type Executor = executor::Default;
+    type Flags = Self;
+
+    fn new(flags: Self) -> (Self, Command<Message>) {
+        (flags, Command::perform(load_files(), Message::FilesLoaded))
+    }
+
+    fn title(&self) -> String {
+        "Logs - BirdNest".to_string()
+    }
+
+    fn update(&mut self, message: Message) -> Command<Message> {
+        match message {
+            Message::FilesLoaded(files) => {
+                self.files = files;
+                self.selected = 0;
+                self.load_selected()
+            }
+            Message::FileLoaded(content) => {
+                self.content = content;
+                scrollable::snap_to(log_id(self.window), scrollable::RelativeOffset::END)
+            }
+            Message::SelectFile(index) => {
+                if index >= self.files.len() {
+                    return Command::none();
+                }
+                self.selected = index;
+                self.load_selected()
+            }
+            Message::SetLevel(level) => {
+                self.min_level = level;
+                scrollable::snap_to(log_id(self.window), scrollable::RelativeOffset::END)
+            }
+            Message::Refresh => Command::perform(load_files(), Message::FilesLoaded),
+            Message::OpenFolder => Command::perform(open_folder(), Message::FolderOpened),
+            Message::FolderOpened(result) => {
+                self.error = result.err();
+                Command::none()
+            }
+            Message::Close => {
+                iced::window::close(self.window)
+            }
+        }
+    }
+
+    fn view(&self) -> Element<'_, Message> {
+        let theme = self.theme;
+        let button_style = |is_primary: bool| {
+            iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                is_primary,
+                radius: self.border_radius,
+                primary_color: theme.primary(),
+                text_color: if is_primary { Color::BLACK } else { Color::WHITE },
+                background_color: theme.background(),
+            }))
+        };
+
+        let mut file_list = Column::new().spacing(4);
+        for (index, path) in self.files.iter().enumerate() {
+            let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            file_list = file_list.push(
+                button(text(name).size(13))
+                    .on_press(Message::SelectFile(index))
+                    .width(Length::Fill)
+                    .style(button_style(index == self.selected))
+                    .padding(Padding::new(8.0)),
+            );
+        }
+        if self.files.is_empty() {
+            file_list = file_list.push(
+                text("No logs yet")
+                    .size(13)
+                    .style(iced::theme::Text::Color(theme.secondary_text())),
+            );
+        }
+
+        let mut levels = row![text("Show").size(13).style(iced::theme::Text::Color(theme.secondary_text()))]
+            .spacing(6)
+            .align_items(alignment::Alignment::Center);
+        for level in [Level::Debug, Level::Info, Level::Warn, Level::Error] {
+            levels = levels.push(
+                button(text(level.label()).size(12))
+                    .on_press(Message::SetLevel(level))
+                    .style(button_style(level == self.min_level))
+                    .padding(Padding::new(6.0)),
+            );
+        }
+
+        let lines = logging::tail(&self.content, self.min_level, MAX_LINES);
+        let log: Element<'_, Message> = if lines.is_empty() {
+            text("Nothing at this level")
+                .size(13)
+                .style(iced::theme::Text::Color(theme.secondary_text()))
+                .into()
+        } else {
+            text(lines.join("\n"))
+                .size(12)
+                .font(iced::Font::MONOSPACE)
+                .style(iced::theme::Text::Color(theme.text()))
+                .into()
+        };
+
+        let status: Element<'_, Message> = match &self.error {
+            Some(error) => text(error).size(13).style(iced::theme::Text::Color(theme.danger())).into(),
+            None => Space::with_width(Length::Fill).into(),
+        };
+
+        container(
+            column![
+                text("Logs")
+                    .size(24)
+                    .style(iced::theme::Text::Color(theme.text())),
+                text(match logging::log_dir() {
+                    Some(dir) => format!("Kept for a week in {}. Run with --verbose to see debug messages in the terminal too.", dir.display()),
+                    None => "Logs are only written when HOME is set.".to_string(),
+                })
+                .size(13)
+                .style(iced::theme::Text::Color(theme.secondary_text())),
+                levels,
+                row![
+                    scrollable(file_list.padding(Padding::new(8.0)))
+                        .style(iced::theme::Scrollable::Custom(Box::new(CustomScrollableStyle {
+                            background_color: theme.surface(),
+                            border_radius: self.border_radius,
+                        })))
+                        .width(Length::Fixed(260.0))
+                        .height(Length::Fill),
+                    container(
+                        scrollable(container(log).padding(Padding::new(8.0)))
+                            .id(log_id(self.window))
+                            .style(iced::theme::Scrollable::Custom(Box::new(CustomScrollableStyle {
+                                background_color: theme.surface(),
+                                border_radius: self.border_radius,
+                            })))
+                            .height(Length::Fill),
+                    )
+                    .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+                        radius: self.border_radius,
+                        background: Some(theme.card_background()),
+                        elevation: 1.0,
+                    })))
+                    .width(Length::Fill)
+                    .height(Length::Fill)
+                    .padding(Padding::new(12.0)),
+                ]
+                .spacing(12)
+                .height(Length::Fill),
+                row![
+                    status,
+                    button("Open Folder").on_press(Message::OpenFolder).style(button_style(false)).padding(Padding::new(14.0)),
+                    button("Refresh").on_press(Message::Refresh).style(button_style(false)).padding(Padding::new(14.0)),
+                    button("Close").on_press(Message::Close).style(button_style(true)).padding(Padding::new(14.0)),
+                ]
+                .spacing(10)
+                .align_items(alignment::Alignment::Center),
+            ]
+            .spacing(15)
+            .padding(Padding::new(30.0))
+        )
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+            radius: self.border_radius,
+            background: Some(theme.background()),
+            elevation: 0.0,
+        })))
+        .into()
+    }
+
+    fn theme(&self) -> IcedTheme {
+        match self.theme {
+            AppTheme::Light => IcedTheme::Light,
+            AppTheme::Dark => IcedTheme::Dark,
+        }
+    }
+}
+
+// Widget operations reach every open window, so the id is per window
+fn log_id(window: window::Id) -> scrollable::Id {
+    scrollable::Id::new(format!("log-viewer-{:?}", window))
+}
@@ -0,0 +1,492 @@
+use iced::{
+    alignment, Color,
+    widget::{button, checkbox, column, container, row, scrollable, text, Column, Space},
+    Element, Length, Padding,
+};
+use futures::channel::mpsc::UnboundedSender;
+use futures::{Stream, StreamExt};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tokio::process::Command as TokioCommand;
+
+use birdnest_core::deferred_removal::{self, DeferredRemoval};
+use birdnest_core::package_manager::PackageBackend;
+use birdnest_core::utils::{self, CommandBuilder};
+use birdnest_core::flatpak::{self, FlatpakInstallation};
+use birdnest_core::plan;
+use birdnest_core::startup::{self, StartupItem};
+
+use crate::gui::theme::Theme as AppTheme;
+use crate::gui::styles::{RoundedButtonStyle, RoundedContainerStyle, CustomScrollableStyle, YellowCheckboxStyle};
+use crate::gui::{transaction_queue, Message};
+
+// Only the tail of a long repair is kept on screen
+const MAX_OUTPUT_LINES: usize = 500;
+
+/// Progress of a `flatpak repair` run, streamed into the Maintenance tab
+#[derive(Debug, Clone)]
+pub enum RepairEvent {
+    Output(String),
+    Finished {
+        before: Option<u64>,
+        after: Option<u64>,
+        error: Option<String>,
+    },
+}
+
+#[derive(Debug, Default)]
+pub struct MaintenanceState {
+    /// Disk usage per installation; missing until measured
+    pub usage: HashMap<FlatpakInstallation, Option<u64>>,
+    pub usage_loading: bool,
+    pub repairing: Option<FlatpakInstallation>,
+    pub output: Vec<String>,
+    pub last_result: Option<String>,
+    /// Removed apps still hidden, waiting for their days to pass
+    pub deferred: Vec<DeferredRemoval>,
+    pub uninstalling_deferred: bool,
+    /// What starts at login, heaviest first; None until checked
+    pub startup: Option<Vec<StartupItem>>,
+    pub startup_loading: bool,
+    /// Items being turned on or off
+    pub startup_switching: HashSet<String>,
+}
+
+impl MaintenanceState {
+    pub fn apply(&mut self, event: RepairEvent) {
+        match event {
+            RepairEvent::Output(line) => {
+                self.output.push(line);
+                if self.output.len() > MAX_OUTPUT_LINES {
+                    let excess = self.output.len() - MAX_OUTPUT_LINES;
+                    self.output.drain(..excess);
+                }
+            }
+            RepairEvent::Finished { before, after, error } => {
+                let label = self.repairing.map(|i| i.label()).unwrap_or("Flatpak");
+                self.last_result = Some(match error {
+                    Some(e) => format!("{} repair failed: {}", label, e),
+                    None => format!("{} repair finished: {}", label, flatpak::usage_change(before, after)),
+                });
+                if let Some(installation) = self.repairing.take() {
+                    self.usage.insert(installation, after);
+                }
+            }
+        }
+    }
+}
+
+// Walking an installation can take a while on a big system install
+pub async fn load_usage() -> Vec<(FlatpakInstallation, Option<u64>)> {
+    tokio::task::spawn_blocking(|| {
+        FlatpakInstallation::ALL
+            .iter()
+            .map(|installation| (*installation, installation.disk_usage()))
+            .collect()
+    })
+    .await
+    .unwrap_or_default()
+}
+
+pub async fn load_deferred() -> Vec<DeferredRemoval> {
+    tokio::task::spawn_blocking(deferred_removal::load).await.unwrap_or_default()
+}
+
+// Finding owners and sampling CPU takes a second or more
+pub async fn load_startup(backend: Arc<dyn PackageBackend>) -> Vec<StartupItem> {
+    tokio::task::spawn_blocking(move || startup::report(backend.as_ref())).await.unwrap_or_default()
+}
+
+pub async fn set_startup(item: StartupItem, enabled: bool) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || startup::set_enabled(&item, enabled))
+        .await
+        .map_err(|e| format!("Task error: {}", e))?
+        .map_err(|e| e.to_string())
+}
+
+/// Uninstall hidden apps one by one through the transaction queue; returns
+/// how many went, or the first failure
+pub async fn uninstall_deferred(backend: Arc<dyn PackageBackend>, entries: Vec<DeferredRemoval>) -> Result<usize, String> {
+    let mut removed = 0;
+    for entry in entries {
+        let backend = backend.clone();
+        let label = format!("Uninstall {}", entry.name);
+        transaction_queue::serialized(label, async move {
+            tokio::task::spawn_blocking(move || deferred_removal::uninstall(&entry, backend.as_ref()))
+                .await
+                .map_err(|e| format!("Task error: {}", e))?
+                .map(|_| String::new())
+                .map_err(|e| e.to_string())
+        })
+        .await?;
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+/// Run `flatpak repair` for one installation, yielding its output line by line
+/// and finally the disk usage before and after
+pub fn repair_stream(installation: FlatpakInstallation) -> impl Stream<Item = RepairEvent> {
+    let (tx, rx) = futures::channel::mpsc::unbounded();
+    // The repair is started from inside the executor, where the tokio runtime is available
+    futures::stream::once(async move {
+        tokio::spawn(async move {
+            let before = tokio::task::spawn_blocking(move || installation.disk_usage()).await.ok().flatten();
+            let error = run_repair(installation, &tx).await.err().map(|e| e.to_string());
+            let after = tokio::task::spawn_blocking(move || installation.disk_usage()).await.ok().flatten();
+            let _ = tx.unbounded_send(RepairEvent::Finished { before, after, error });
+        });
+        rx
+    })
+    .flatten()
+}
+
+async fn run_repair(installation: FlatpakInstallation, tx: &UnboundedSender<RepairEvent>) -> anyhow::Result<()> {
+    use tokio::io::{AsyncBufReadExt, BufReader};
+
+    let args = ["repair", installation.flag()];
+    let (std_cmd, method) = CommandBuilder::new("flatpak").args(&args).elevated(installation.needs_root()).build()?;
+    let prompt = match method {
+        Some(method) => format!("$ {} flatpak {}", method.command_prefix(), args.join(" ")),
+        None => format!("$ flatpak {}", args.join(" ")),
+    };
+    let _ = tx.unbounded_send(RepairEvent::Output(prompt));
+    let mut cmd = TokioCommand::from(std_cmd);
+    cmd.stdin(std::process::Stdio::null());
+    cmd.stdout(std::process::Stdio::piped());
+    cmd.stderr(std::process::Stdio::piped());
+
+    let mut child = cmd.spawn()?;
+    let stdout = child.stdout.take().ok_or_else(|| anyhow::anyhow!("Failed to capture stdout"))?;
+    let stderr = child.stderr.take().ok_or_else(|| anyhow::anyhow!("Failed to capture stderr"))?;
+    let mut stdout_reader = BufReader::new(stdout).lines();
+    let mut stderr_reader = BufReader::new(stderr).lines();
+
+    let (mut stdout_done, mut stderr_done) = (false, false);
+    while !(stdout_done && stderr_done) {
+        tokio::select! {
+            line = stdout_reader.next_line(), if !stdout_done => match line {
+                Ok(Some(line)) => { let _ = tx.unbounded_send(RepairEvent::Output(line)); }
+                _ => stdout_done = true,
+            },
+            line = stderr_reader.next_line(), if !stderr_done => match line {
+                Ok(Some(line)) => { let _ = tx.unbounded_send(RepairEvent::Output(line)); }
+                _ => stderr_done = true,
+            },
+        }
+    }
+
+    let status = child.wait().await?;
+    if status.code() == Some(126) || status.code() == Some(127) {
+        anyhow::bail!("Authentication failed or cancelled");
+    }
+    if !status.success() {
+        anyhow::bail!("flatpak repair exited with code {}", status.code().unwrap_or(-1));
+    }
+    Ok(())
+}
+
+// Hidden apps with their days left, each with Restore and Uninstall Now
+fn view_deferred(state: &MaintenanceState, theme: AppTheme, radius: f32) -> Element<'_, Message> {
+    let now = utils::now();
+    let small_button = |label, primary: bool| {
+        button(text(label).size(14.0))
+            .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                is_primary: primary,
+                radius,
+                primary_color: theme.primary(),
+                text_color: if primary { Color::BLACK } else { Color::WHITE },
+                background_color: theme.background(),
+            })))
+            .padding(Padding::new(10.0))
+    };
+
+    let mut rows = Column::new().spacing(8);
+    for entry in &state.deferred {
+        let when = match entry.days_left(now) {
+            0 => "Uninstalling...".to_string(),
+            1 => "Uninstalled in 1 day".to_string(),
+            days => format!("Uninstalled in {} days", days),
+        };
+        let uninstall = small_button("Uninstall Now", false);
+        rows = rows.push(
+            row![
+                column![
+                    text(&entry.name).size(16.0).style(iced::theme::Text::Color(theme.text())),
+                    text(when).size(13.0).style(iced::theme::Text::Color(theme.secondary_text())),
+                ]
+                .spacing(2),
+                Space::with_width(Length::Fill),
+                small_button("Restore", true).on_press(Message::RestoreDeferred(entry.name.clone())),
+                if state.uninstalling_deferred { uninstall } else { uninstall.on_press(Message::UninstallDeferred(entry.name.clone())) },
+            ]
+            .spacing(8)
+            .align_items(alignment::Alignment::Center),
+        );
+    }
+
+    container(
+        column![
+            text("Hidden Apps").size(20.0).style(iced::theme::Text::Color(theme.text())),
+            text("Removed apps wait here, hidden from the app menu, before they are uninstalled.")
+                .size(13.0)
+                .style(iced::theme::Text::Color(theme.secondary_text())),
+            rows,
+        ]
+        .spacing(8),
+    )
+    .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+        radius,
+        background: Some(theme.card_background()),
+        elevation: 1.0,
+    })))
+    .width(Length::Fill)
+    .padding(Padding::new(16.0))
+    .into()
+}
+
+// Autostart entries and user services with their package and what it uses
+// now, each with a toggle
+fn view_startup(state: &MaintenanceState, theme: AppTheme, radius: f32) -> Element<'_, Message> {
+    let mut check = button(text(if state.startup_loading { "Checking..." } else { "Check Again" }).size(14.0))
+        .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+            is_primary: false,
+            radius,
+            primary_color: theme.primary(),
+            text_color: Color::WHITE,
+            background_color: theme.background(),
+        })))
+        .padding(Padding::new(10.0));
+    if !state.startup_loading {
+        check = check.on_press(Message::CheckStartup);
+    }
+
+    let mut rows = Column::new().spacing(8);
+    match &state.startup {
+        None => {}
+        Some(items) if items.is_empty() => {
+            rows = rows.push(text("Nothing starts at login.").size(13.0).style(iced::theme::Text::Color(theme.secondary_text())));
+        }
+        Some(items) => {
+            for item in items {
+                let origin = match &item.package {
+                    Some(package) => format!("{} from {} · {}", item.kind.label(), package, item.footprint.summary()),
+                    None => format!("{} not from a package", item.kind.label()),
+                };
+                let toggle = checkbox(item.name.as_str(), item.enabled)
+                    .text_size(16.0)
+                    .style(iced::theme::Checkbox::Custom(Box::new(YellowCheckboxStyle {
+                        radius: 4.0,
+                        primary_color: theme.primary(),
+                    })));
+                let toggle = if state.startup_switching.contains(&item.id) {
+                    toggle
+                } else {
+                    let id = item.id.clone();
+                    toggle.on_toggle(move |enabled| Message::ToggleStartup(id.clone(), enabled))
+                };
+                rows = rows.push(
+                    column![toggle, text(origin).size(13.0).style(iced::theme::Text::Color(theme.secondary_text()))].spacing(2),
+                );
+            }
+        }
+    }
+
+    container(
+        column![
+            row![
+                column![
+                    text("Starts at Login").size(20.0).style(iced::theme::Text::Color(theme.text())),
+                    text("Autostart entries and user services, with the package each came from and what it uses now. Changes apply from the next login.")
+                        .size(13.0)
+                        .style(iced::theme::Text::Color(theme.secondary_text())),
+                ]
+                .spacing(4)
+                .width(Length::Fill),
+                check,
+            ]
+            .spacing(8)
+            .align_items(alignment::Alignment::Center),
+            rows,
+        ]
+        .spacing(8),
+    )
+    .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+        radius,
+        background: Some(theme.card_background()),
+        elevation: 1.0,
+    })))
+    .width(Length::Fill)
+    .padding(Padding::new(16.0))
+    .into()
+}
+
+// A card with a button opening one of the Maintenance windows: the digest
+// of the last week's changes, or trimming preinstalled apps
+fn view_launcher(title: &'static str, description: &'static str, label: &'static str, message: Message, theme: AppTheme, radius: f32) -> Element<'static, Message> {
+    container(
+        row![
+            column![
+                text(title).size(20.0).style(iced::theme::Text::Color(theme.text())),
+                text(description)
+                    .size(13.0)
+                    .style(iced::theme::Text::Color(theme.secondary_text())),
+            ]
+            .spacing(4),
+            Space::with_width(Length::Fill),
+            button(text(label).size(14.0))
+                .on_press(message)
+                .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                    is_primary: false,
+                    radius,
+                    primary_color: theme.primary(),
+                    text_color: Color::WHITE,
+                    background_color: theme.background(),
+                })))
+                .padding(Padding::new(10.0)),
+        ]
+        .align_items(alignment::Alignment::Center),
+    )
+    .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+        radius,
+        background: Some(theme.card_background()),
+        elevation: 1.0,
+    })))
+    .width(Length::Fill)
+    .padding(Padding::new(16.0))
+    .into()
+}
+
+// The digest, trimming preinstalled apps, hidden apps when there are any,
+// what starts at login, then one card per installation with its size and a Repair button, then the
+// live output
+pub fn view(state: &MaintenanceState, theme: AppTheme, radius: f32) -> Element<'_, Message> {
+    let mut cards = row![].spacing(12);
+    for installation in FlatpakInstallation::ALL {
+        let usage = match state.usage.get(&installation) {
+            Some(Some(bytes)) => plan::format_size(*bytes),
+            Some(None) => "Not present".to_string(),
+            None if state.usage_loading => "Measuring...".to_string(),
+            None => "Unknown".to_string(),
+        };
+        let is_running = state.repairing == Some(installation);
+        let mut repair = button(text(if is_running { "Repairing..." } else { "Repair" }).size(16.0))
+            .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                is_primary: true,
+                radius,
+                primary_color: theme.primary(),
+                text_color: Color::BLACK,
+                background_color: theme.background(),
+            })))
+            .padding(Padding::new(12.0));
+        if state.repairing.is_none() {
+            repair = repair.on_press(Message::RepairFlatpak(installation));
+        }
+
+        cards = cards.push(
+            container(
+                row![
+                    column![
+                        text(format!("{} installation", installation.label()))
+                            .size(18.0)
+                            .style(iced::theme::Text::Color(theme.text())),
+                        text(installation.path().display().to_string())
+                            .size(13.0)
+                            .font(iced::Font::MONOSPACE)
+                            .style(iced::theme::Text::Color(theme.secondary_text())),
+                        text(usage)
+                            .size(14.0)
+                            .style(iced::theme::Text::Color(theme.primary())),
+                    ]
+                    .spacing(4),
+                    Space::with_width(Length::Fill),
+                    repair,
+                ]
+                .align_items(alignment::Alignment::Center),
+            )
+            .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+                radius,
+                background: Some(theme.card_background()),
+                elevation: 1.0,
+            })))
+            .width(Length::Fill)
+            .padding(Padding::new(16.0)),
+        );
+    }
+
+    let mut log = Column::new().spacing(2);
+    if state.output.is_empty() {
+        log = log.push(
+            text("Repair verifies every installed object and re-downloads anything missing or corrupted.")
+                .size(13.0)
+                .style(iced::theme::Text::Color(theme.secondary_text())),
+        );
+    }
+    for line in &state.output {
+        log = log.push(
+            text(line)
+                .size(12.0)
+                .font(iced::Font::MONOSPACE)
+                .style(iced::theme::Text::Color(theme.text())),
+        );
+    }
+
+    let status: Element<'_, Message> = match &state.last_result {
+        Some(result) => text(result)
+            .size(14.0)
+            .style(iced::theme::Text::Color(theme.text()))
+            .into(),
+        None => Space::with_height(Length::Fixed(0.0)).into(),
+    };
+
+    let hidden: Element<'_, Message> = if state.deferred.is_empty() {
+        Space::with_height(Length::Fixed(0.0)).into()
+    } else {
+        view_deferred(state, theme, radius)
+    };
+
+    column![
+        view_launcher(
+            "Weekly Digest",
+            "What was installed, upgraded and removed lately, by whom, and when the sources changed.",
+            "Show Digest",
+            Message::ShowDigest,
+            theme,
+            radius,
+        ),
+        view_launcher(
+            "Trim Preinstalled Software",
+            "Remove the optional apps PikaOS came with, by category, keeping what your desktop depends on.",
+            "Choose Apps...",
+            Message::ShowTrim,
+            theme,
+            radius,
+        ),
+        hidden,
+        view_startup(state, theme, radius),
+        text("Flatpak Repair")
+            .size(20.0)
+            .style(iced::theme::Text::Color(theme.text())),
+        cards,
+        status,
+        container(
+            scrollable(log.padding(Padding::new(12.0)))
+                .style(iced::theme::Scrollable::Custom(Box::new(CustomScrollableStyle {
+                    background_color: theme.surface(),
+                    border_radius: radius,
+                })))
+                .width(Length::Fill)
+                .height(Length::Fill),
+        )
+        .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+            radius,
+            background: Some(theme.card_background()),
+            elevation: 1.0,
+        })))
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .padding(Padding::new(8.0)),
+    ]
+    .spacing(12)
+    .into()
+}
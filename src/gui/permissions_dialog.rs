@@ -0,0 +1,274 @@
+use iced::{
+    alignment, executor, Color,
+    widget::{button, checkbox, column, container, row, scrollable, text, Column, Row, Space},
+    Application, Command, Element, Length, Pixels, Settings, Theme as IcedTheme, Padding,
+    window,
+};
+
+use birdnest_core::flatpak::{AppPermissions, FlatpakManager, Permission, PermissionKind};
+
+use crate::gui::scaling;
+use crate::gui::theme::Theme as AppTheme;
+use crate::gui::styles::{RoundedButtonStyle, RoundedContainerStyle, CustomScrollableStyle, YellowCheckboxStyle};
+
+// Toggles per row in each permission group
+const TOGGLES_PER_ROW: usize = 3;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Loaded(Result<AppPermissions, String>),
+    Toggle(Permission, bool),
+    Reset,
+    Changed(Result<(), String>),
+    Close,
+}
+
+// Flatpak tab > "..." > Permissions: Flatseal-style toggles for one installed
+// app, written as per-user `flatpak override`s
+#[derive(Debug)]
+pub struct PermissionsDialog {
+    pub app: String,
+    pub permissions: Option<AppPermissions>,
+    pub error: Option<String>,
+    // A change is being written; toggles wait for it
+    pub busy: bool,
+    pub theme: AppTheme,
+    pub border_radius: f32,
+    // MAIN when run standalone, otherwise the child window it was opened in
+    pub window: window::Id,
+}
+
+impl PermissionsDialog {
+    pub fn new(app: String) -> Self {
+        Self {
+            app,
+            permissions: None,
+            error: None,
+            busy: true,
+            theme: AppTheme::Dark,
+            border_radius: 12.0,
+            window: window::Id::MAIN,
+        }
+    }
+
+    pub fn window_settings() -> window::Settings {
+        window::Settings {
+            size: scaling::fit(iced::Size::new(760.0, 640.0)),
+            min_size: Some(scaling::fit(iced::Size::new(520.0, 400.0))),
+            resizable: true,
+            decorations: true,
+            ..window::Settings::default()
+        }
+    }
+
+    pub fn run_separate_window(app: String) -> Result<(), iced::Error> {
+        let dialog = Self::new(app);
+
+        <PermissionsDialog as Application>::run(Settings {
+            window: Self::window_settings(),
+            flags: dialog,
+            default_text_size: Pixels(14.0),
+            antialiasing: true,
+            id: None,
+            fonts: Vec::new(),
+            default_font: iced::Font::DEFAULT,
+        })
+    }
+
+    fn load(&self) -> Command<Message> {
+        let app = self.app.clone();
+        Command::perform(
+            async move {
+                tokio::task::spawn_blocking(move || FlatpakManager::new()?.permissions(&app))
+                    .await
+                    .map_err(|e| format!("Task error: {}", e))?
+                    .map_err(|e| e.to_string())
+            },
+            Message::Loaded,
+        )
+    }
+
+    fn change(&mut self, write: impl FnOnce(&FlatpakManager, &str) -> anyhow::Result<()> + Send + 'static) -> Command<Message> {
+        self.busy = true;
+        let app = self.app.clone();
+        Command::perform(
+            async move {
+                tokio::task::spawn_blocking(move || write(&FlatpakManager::new()?, &app))
+                    .await
+                    .map_err(|e| format!("Task error: {}", e))?
+                    .map_err(|e| e.to_string())
+            },
+            Message::Changed,
+        )
+    }
+}
+
+impl Application for PermissionsDialog {
+    type Message = Message;
+    type Theme = IcedTheme;
+    type Executor = executor::Default;
+    type Flags = Self;
+
+    fn new(flags: Self) -> (Self, Command<Message>) {
+        let command = flags.load();
+        (flags, command)
+    }
+
+    fn title(&self) -> String {
+        format!("Permissions: {} - BirdNest", self.app)
+    }
+
+    fn update(&mut self, message: Message) -> Command<Message> {
+        match message {
+            Message::Loaded(result) => {
+                self.busy = false;
+                match result {
+                    Ok(permissions) => {
+                        self.permissions = Some(permissions);
+                        self.error = None;
+                    }
+                    Err(e) => self.error = Some(e),
+                }
+                Command::none()
+            }
+            Message::Toggle(permission, enabled) => {
+                if self.busy {
+                    return Command::none();
+                }
+                self.change(move |flatpak, app| flatpak.set_permission(app, permission, enabled))
+            }
+            Message::Reset => {
+                if self.busy {
+                    return Command::none();
+                }
+                self.change(|flatpak, app| flatpak.reset_permissions(app))
+            }
+            Message::Changed(result) => {
+                if let Err(e) = result {
+                    tracing::warn!("PermissionsDialog: override failed: {}", e);
+                    self.error = Some(e);
+                }
+                // Read back what flatpak stored rather than trusting the toggle
+                self.load()
+            }
+            Message::Close => iced::window::close(self.window),
+        }
+    }
+
+    fn view(&self) -> Element<'_, Message> {
+        let theme = self.theme;
+        let radius = self.border_radius;
+
+        let mut groups = Column::new().spacing(12);
+        match &self.permissions {
+            Some(permissions) => {
+                for kind in PermissionKind::ALL {
+                    groups = groups.push(view_group(kind, permissions, self.busy, theme, radius));
+                }
+            }
+            None if self.error.is_none() => {
+                groups = groups.push(text("Loading permissions...").size(14).style(iced::theme::Text::Color(theme.text())));
+            }
+            None => {}
+        }
+
+        let mut content = column![
+            text(format!("Permissions for {}", self.app))
+                .size(24)
+                .style(iced::theme::Text::Color(theme.text())),
+            text("Changes are stored as overrides for your user and apply the next time the app starts. Entries marked * differ from what the app ships with.")
+                .size(13)
+                .style(iced::theme::Text::Color(theme.secondary_text())),
+        ]
+        .spacing(15)
+        .padding(Padding::new(30.0));
+        if let Some(error) = &self.error {
+            content = content.push(text(error).size(13).style(iced::theme::Text::Color(theme.danger())));
+        }
+
+        let footer_button = |label, primary: bool| {
+            button(text(label).size(14))
+                .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                    is_primary: primary,
+                    radius,
+                    primary_color: theme.primary(),
+                    text_color: Color::WHITE,
+                    background_color: theme.background(),
+                })))
+                .padding(Padding::new(14.0))
+        };
+        let reset = footer_button("Reset to defaults", false);
+        content = content
+            .push(
+                scrollable(groups)
+                    .style(iced::theme::Scrollable::Custom(Box::new(CustomScrollableStyle {
+                        background_color: theme.background(),
+                        border_radius: radius,
+                    })))
+                    .height(Length::Fill),
+            )
+            .push(
+                row![
+                    if self.busy { reset } else { reset.on_press(Message::Reset) },
+                    Space::with_width(Length::Fill),
+                    footer_button("Close", true).on_press(Message::Close),
+                ]
+                .align_items(alignment::Alignment::Center),
+            );
+
+        container(content)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+                radius,
+                background: Some(theme.background()),
+                elevation: 0.0,
+            })))
+            .into()
+    }
+
+    fn theme(&self) -> IcedTheme {
+        match self.theme {
+            AppTheme::Light => IcedTheme::Light,
+            AppTheme::Dark => IcedTheme::Dark,
+        }
+    }
+}
+
+fn view_group<'a>(kind: PermissionKind, permissions: &AppPermissions, busy: bool, theme: AppTheme, radius: f32) -> Element<'a, Message> {
+    let mut rows = Column::new().spacing(8);
+    for values in kind.values().chunks(TOGGLES_PER_ROW) {
+        let mut line = values.iter().fold(Row::new().spacing(12), |line, value| {
+            let permission = Permission { kind, value };
+            let label = if permissions.is_overridden(permission) { format!("{} *", value) } else { value.to_string() };
+            let toggle = checkbox(label, permissions.is_enabled(permission))
+                .text_size(13.0)
+                .style(iced::theme::Checkbox::Custom(Box::new(YellowCheckboxStyle {
+                    radius: 4.0,
+                    primary_color: theme.primary(),
+                })));
+            let toggle = if busy { toggle } else { toggle.on_toggle(move |enabled| Message::Toggle(permission, enabled)) };
+            line.push(container(toggle).width(Length::FillPortion(1)))
+        });
+        for _ in values.len()..TOGGLES_PER_ROW {
+            line = line.push(Space::with_width(Length::FillPortion(1)));
+        }
+        rows = rows.push(line);
+    }
+
+    container(
+        column![
+            text(kind.label()).size(16).style(iced::theme::Text::Color(theme.primary())),
+            rows,
+        ]
+        .spacing(10),
+    )
+    .width(Length::Fill)
+    .padding(Padding::new(12.0))
+    .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+        radius,
+        background: Some(theme.card_background()),
+        elevation: 1.0,
+    })))
+    .into()
+}
@@ -0,0 +1,145 @@
+use iced::{
+    alignment, Color,
+    widget::{button, column, container, pick_list, row, text, Column},
+    Element, Length, Padding,
+};
+use std::path::PathBuf;
+
+use birdnest_core::manifest::PIKMAN_DISTROS;
+use birdnest_core::pikman_exports::{self, ExportKind, ExportedApp};
+
+use crate::gui::advanced_options;
+use crate::gui::styles::{RoundedButtonStyle, RoundedContainerStyle};
+use crate::gui::theme::Theme as AppTheme;
+use crate::gui::Message;
+
+// The Pikman tab's list of what containers have exported to the host, as
+// menu entries and programs on the PATH, with Unexport on each and a form
+// to export another package. The list is read again after every change, so
+// it shows what is really on disk.
+
+#[derive(Debug)]
+pub struct PikmanExports {
+    /// None until listed
+    pub exports: Option<Vec<ExportedApp>>,
+    /// The package typed in to export
+    pub package: String,
+    /// The container to export it from, one of [`PIKMAN_DISTROS`]
+    pub distro: &'static str,
+    /// An export running; the buttons wait for it
+    pub busy: bool,
+    /// The desktop entry or wrapper being unexported
+    pub removing: Option<PathBuf>,
+}
+
+impl Default for PikmanExports {
+    fn default() -> Self {
+        Self { exports: None, package: String::new(), distro: PIKMAN_DISTROS[0], busy: false, removing: None }
+    }
+}
+
+pub async fn load() -> Vec<ExportedApp> {
+    tokio::task::spawn_blocking(pikman_exports::list).await.unwrap_or_default()
+}
+
+pub async fn export(package: String, distro: &'static str) -> Result<String, String> {
+    tokio::task::spawn_blocking(move || {
+        pikman_exports::export(&package, distro).map(|()| format!("Exported {} from the {} container", package, label(distro)))
+    })
+    .await
+    .map_err(|e| format!("Task error: {}", e))?
+    .map_err(|e| e.to_string())
+}
+
+pub async fn unexport(app: ExportedApp) -> Result<String, String> {
+    tokio::task::spawn_blocking(move || pikman_exports::unexport(&app).map(|()| format!("Unexported {}", app.name)))
+        .await
+        .map_err(|e| format!("Task error: {}", e))?
+        .map_err(|e| e.to_string())
+}
+
+fn label(distro: &str) -> &str {
+    match distro {
+        "aur" => "AUR",
+        "fedora" => "Fedora",
+        "alpine" => "Alpine",
+        other => other,
+    }
+}
+
+fn action<'a>(label: &str, message: Option<Message>, danger: bool, theme: AppTheme, radius: f32) -> Element<'a, Message> {
+    button(text(label.to_string()).size(13))
+        .on_press_maybe(message)
+        .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+            is_primary: danger,
+            radius,
+            primary_color: theme.danger(),
+            text_color: Color::WHITE,
+            background_color: theme.background(),
+        })))
+        .padding(Padding::new(10.0))
+        .into()
+}
+
+fn view_export<'a>(app: &ExportedApp, state: &PikmanExports, theme: AppTheme, radius: f32) -> Element<'a, Message> {
+    let kind = match app.kind {
+        ExportKind::Application => "App",
+        ExportKind::Binary => "Program",
+    };
+    let idle = !state.busy && state.removing.is_none();
+    let unexport = match state.removing.as_ref() == Some(&app.path) {
+        true => action("Unexporting...", None, true, theme, radius),
+        false => action("Unexport", idle.then(|| Message::PikmanUnexport(app.clone())), true, theme, radius),
+    };
+    row![
+        column![
+            text(format!("{} ({})", app.name, kind)).size(14).style(iced::theme::Text::Color(theme.text())),
+            text(format!("{} in {}", app.command, app.container)).size(12).style(iced::theme::Text::Color(theme.secondary_text())),
+        ]
+        .spacing(2)
+        .width(Length::Fill),
+        unexport,
+    ]
+    .spacing(10)
+    .align_items(alignment::Alignment::Center)
+    .into()
+}
+
+pub fn view<'a>(state: &PikmanExports, theme: AppTheme, radius: f32) -> Element<'a, Message> {
+    let mut list = Column::new().spacing(8);
+    list = match &state.exports {
+        None => list.push(text("Looking for exported apps...").size(13).style(iced::theme::Text::Color(theme.secondary_text()))),
+        Some(exports) if exports.is_empty() => {
+            list.push(text("Nothing has been exported from a container yet").size(13).style(iced::theme::Text::Color(theme.secondary_text())))
+        }
+        Some(exports) => exports.iter().fold(list, |list, app| list.push(view_export(app, state, theme, radius))),
+    };
+
+    let package = state.package.trim();
+    let export = (!state.busy && state.removing.is_none() && !package.is_empty())
+        .then(|| Message::PikmanExport { package: package.to_string(), distro: state.distro });
+    let form = row![
+        advanced_options::field("Export package", "e.g. gimp", &state.package, Message::PikmanExportPackageChanged, theme, radius),
+        pick_list(&PIKMAN_DISTROS[..], Some(state.distro), Message::PikmanExportDistroChanged).text_size(13.0),
+        action(if state.busy { "Exporting..." } else { "Export" }, export, false, theme, radius),
+    ]
+    .spacing(10)
+    .align_items(alignment::Alignment::Center);
+
+    container(
+        column![
+            text("Exported Apps").size(16).style(iced::theme::Text::Color(theme.text())),
+            list,
+            form,
+        ]
+        .spacing(10),
+    )
+    .padding(Padding::new(16.0))
+    .width(Length::Fill)
+    .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+        radius,
+        background: Some(theme.background()),
+        elevation: 1.5,
+    })))
+    .into()
+}
@@ -1,14 +1,22 @@
 use iced::{
     alignment, executor, Color,
     widget::{button, column, container, row, scrollable, text, Space},
-    Application, Command, Element, Length, Pixels, Settings, Theme as IcedTheme, Padding,
+    Application, Command, Element, Length, Theme as IcedTheme, Padding,
     window,
 };
 use tokio::process::Command as TokioCommand;
 use std::fmt;
 
+use crate::gui::advanced_options;
+use crate::gui::command_stream;
+use crate::gui::scaling;
+use crate::gui::transaction_queue;
 use crate::gui::theme::Theme as AppTheme;
 use crate::gui::styles::{RoundedButtonStyle, RoundedContainerStyle, CustomScrollableStyle};
+use birdnest_core::config::Config;
+use birdnest_core::hooks::HookEvent;
+use birdnest_core::install_options::{optional, InstallOptions};
+use birdnest_core::utils::CommandBuilder;
 
 #[derive(Debug, Clone)]
 pub enum Message {
@@ -20,9 +28,14 @@ pub enum Message {
     InstallationProgress(String),
     TerminalOutput(String),
     InstallationComplete,
+    HooksFinished(String),
     InstallationError(String),
     ConflictDetected(String),
     DistroChanged(Option<DistroType>),
+    ToggleAdvanced,
+    ContainerChanged(String),
+    SaveDefaults,
+    DefaultsSaved(Result<Box<Config>, String>),
     Cancel,
 }
 
@@ -91,8 +104,14 @@ pub struct PikmanInstallDialog {
     pub terminal_output: String,
     pub conflict_message: Option<String>,
     pub selected_distro: Option<DistroType>,
+    // The container to install into, starting from the user's default
+    pub options: InstallOptions,
+    pub show_advanced: bool,
+    pub defaults_status: Option<Result<String, String>>,
     pub theme: AppTheme,
     pub border_radius: f32,
+    // The child window it was opened in
+    pub window: window::Id,
 }
 
 impl PikmanInstallDialog {
@@ -108,29 +127,23 @@ impl PikmanInstallDialog {
             terminal_output: String::new(),
             conflict_message: None,
             selected_distro: Some(DistroType::Default),
+            options: InstallOptions::default(),
+            show_advanced: false,
+            defaults_status: None,
             theme: AppTheme::Dark,
             border_radius: 12.0,
+            window: window::Id::MAIN,
         }
     }
 
-    pub fn run_separate_window(package_names: Vec<String>) -> Result<(), iced::Error> {
-        let dialog = Self::new(package_names);
-
-        let mut window_settings = window::Settings::default();
-        window_settings.size = iced::Size::new(800.0, 900.0);
-        window_settings.min_size = Some(iced::Size::new(600.0, 500.0));
-        window_settings.resizable = true;
-        window_settings.decorations = true;
-
-        <PikmanInstallDialog as Application>::run(Settings {
-            window: window_settings,
-            flags: dialog,
-            default_text_size: Pixels(14.0),
-            antialiasing: true,
-            id: None,
-            fonts: Vec::new(),
-            default_font: iced::Font::DEFAULT,
-        })
+    pub fn window_settings() -> window::Settings {
+        window::Settings {
+            size: scaling::fit(iced::Size::new(800.0, 900.0)),
+            min_size: Some(scaling::fit(iced::Size::new(600.0, 500.0))),
+            resizable: true,
+            decorations: true,
+            ..window::Settings::default()
+        }
     }
 }
 
@@ -179,7 +192,36 @@ impl Application for PikmanInstallDialog {
                 self.selected_distro = distro.clone();
                 Command::none()
             }
+            Message::ToggleAdvanced => {
+                self.show_advanced = !self.show_advanced;
+                Command::none()
+            }
+            Message::ContainerChanged(container) => {
+                self.options.pikman_container = optional(&container);
+                self.defaults_status = None;
+                Command::none()
+            }
+            Message::SaveDefaults => {
+                if let Err(e) = self.options.check() {
+                    self.defaults_status = Some(Err(e.to_string()));
+                    return Command::none();
+                }
+                let container = self.options.pikman_container.clone();
+                Command::perform(
+                    advanced_options::save_defaults(move |config| config.install_options.pikman_container = container),
+                    Message::DefaultsSaved,
+                )
+            }
+            Message::DefaultsSaved(result) => {
+                self.defaults_status = Some(result.map(|_| "Saved; installs start with these from now on".to_string()));
+                Command::none()
+            }
             Message::InstallPackages => {
+                if let Err(e) = self.options.check() {
+                    self.show_advanced = true;
+                    self.defaults_status = Some(Err(e.to_string()));
+                    return Command::none();
+                }
                 self.show_confirmation = true;
                 Command::none()
             }
@@ -190,7 +232,7 @@ impl Application for PikmanInstallDialog {
                 self.terminal_output.clear();
                 let package_names = self.package_names.clone();
                 let distro = self.selected_distro.clone();
-                Command::perform(install_packages(package_names, distro), |result| {
+                Command::perform(install_packages(package_names, distro, self.options.pikman_args()), |result| {
                     match result {
                         Ok((progress, output)) => {
                             if progress.contains("conflict") || progress.contains("error") || progress.contains("failed") {
@@ -223,9 +265,19 @@ impl Application for PikmanInstallDialog {
                 Command::none()
             }
             Message::InstallationComplete => {
+                if self.is_complete {
+                    return Command::none();
+                }
                 self.is_installing = false;
                 self.is_complete = true;
                 self.installation_progress = "Installation completed successfully!".to_string();
+                Command::perform(
+                    command_stream::run_hooks(HookEvent::Install, self.package_names.clone(), false),
+                    Message::HooksFinished,
+                )
+            }
+            Message::HooksFinished(output) => {
+                self.terminal_output.push_str(&output);
                 Command::none()
             }
             Message::InstallationError(msg) => {
@@ -239,7 +291,7 @@ impl Application for PikmanInstallDialog {
                 Command::none()
             }
             Message::Cancel => {
-                window::close::<Message>(window::Id::MAIN)
+                window::close::<Message>(self.window)
             }
         }
     }
@@ -404,6 +456,23 @@ fn view_package_info(dialog: &PikmanInstallDialog, theme: AppTheme) -> Element<M
         })))
     );
 
+    content = content.push(advanced_options::view(
+        dialog.show_advanced,
+        Message::ToggleAdvanced,
+        vec![advanced_options::field(
+            "Container",
+            "pikman's default",
+            dialog.options.pikman_container.as_deref().unwrap_or(""),
+            Message::ContainerChanged,
+            theme,
+            dialog.border_radius,
+        )],
+        Message::SaveDefaults,
+        dialog.defaults_status.as_ref(),
+        theme,
+        dialog.border_radius,
+    ));
+
     // Conflict message if any
     if let Some(ref conflict) = dialog.conflict_message {
         content = content.push(
@@ -632,7 +701,7 @@ fn view_complete(dialog: &PikmanInstallDialog, theme: AppTheme) -> Element<Messa
 
 async fn load_package_info(package_names: Vec<String>) -> Result<Vec<PackageDetail>, String> {
     tokio::task::spawn_blocking(move || {
-        use crate::utils::run_command;
+        use birdnest_core::utils::run_command;
         let mut details = Vec::new();
         
         for name in package_names {
@@ -677,42 +746,38 @@ async fn load_package_info(package_names: Vec<String>) -> Result<Vec<PackageDeta
     .map_err(|e| format!("Failed to load package info: {}", e))?
 }
 
+// Waits its turn behind other transactions, like the other dialogs
 async fn install_packages(
     package_names: Vec<String>,
     distro: Option<DistroType>,
+    flags: Vec<String>,
+) -> Result<(String, String), anyhow::Error> {
+    let ticket = transaction_queue::join(format!("pikman install {}", package_names.join(" ")));
+    let turn = ticket.turn().await;
+    let result = run_install(package_names, distro, flags).await;
+    ticket.finish(&result.as_ref().map(|_| ()).map_err(|e| e.to_string()));
+    drop(turn);
+    result
+}
+
+async fn run_install(
+    package_names: Vec<String>,
+    distro: Option<DistroType>,
+    flags: Vec<String>,
 ) -> Result<(String, String), anyhow::Error> {
     use tokio::io::{AsyncBufReadExt, BufReader};
     
-    let mut cmd = TokioCommand::new("pkexec");
-    cmd.arg("pikman");
-    cmd.arg("install");
-    cmd.arg("-y");
-    
+    let mut args = vec!["install", "-y"];
     if let Some(ref d) = distro {
         if let Some(flag) = d.to_pikman_flag() {
-            cmd.arg(flag);
+            args.push(flag);
         }
     }
+    args.extend(flags.iter().map(|s| s.as_str()));
+    args.extend(package_names.iter().map(|s| s.as_str()));
     
-    for pkg in &package_names {
-        cmd.arg(pkg);
-    }
-    
-    // Preserve environment variables for GUI password dialog
-    if let Ok(display) = std::env::var("DISPLAY") {
-        cmd.env("DISPLAY", display);
-    }
-    if let Ok(xauth) = std::env::var("XAUTHORITY") {
-        cmd.env("XAUTHORITY", xauth);
-    }
-    if let Ok(wayland) = std::env::var("WAYLAND_DISPLAY") {
-        cmd.env("WAYLAND_DISPLAY", wayland);
-    }
-    if let Ok(path) = std::env::var("PATH") {
-        cmd.env("PATH", path);
-    }
-    
-    cmd.env("DEBIAN_FRONTEND", "noninteractive");
+    let (std_cmd, _) = CommandBuilder::new("pikman").args(&args).elevated(true).noninteractive().build()?;
+    let mut cmd = TokioCommand::from(std_cmd);
     
     cmd.stdout(std::process::Stdio::piped());
     cmd.stderr(std::process::Stdio::piped());
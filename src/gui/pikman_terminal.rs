@@ -0,0 +1,83 @@
+use iced::{
+    alignment, Color,
+    widget::{button, column, row, text},
+    Element, Padding,
+};
+
+use birdnest_core::terminal;
+
+use crate::gui::advanced_options;
+use crate::gui::styles::RoundedButtonStyle;
+use crate::gui::theme::Theme as AppTheme;
+use crate::gui::Message;
+
+// Entering a pikman container, or running a command in one, needs a
+// terminal the GUI doesn't have, so both open in the user's terminal
+// emulator. A command's window stays open after it ends, to read its output.
+
+#[derive(Debug, Default)]
+pub struct PikmanTerminal {
+    /// The container to enter or run in
+    pub container: String,
+    /// A shell command line to run inside it
+    pub command: String,
+}
+
+pub fn enter_command(container: &str) -> Vec<String> {
+    vec!["pikman".to_string(), "enter".to_string(), container.to_string()]
+}
+
+pub fn run_command(container: &str, command: &[String]) -> Vec<String> {
+    let mut args = vec!["pikman".to_string(), "run".to_string(), container.to_string()];
+    args.extend(command.iter().cloned());
+    terminal::held(&args)
+}
+
+/// Opens `command` in the configured or first installed terminal, saying
+/// which one it used
+pub async fn open(preferred: Option<String>, command: Vec<String>) -> Result<String, String> {
+    tokio::task::spawn_blocking(move || terminal::open(preferred.as_deref(), &command))
+        .await
+        .map_err(|e| format!("Task error: {}", e))?
+        .map_err(|e| e.to_string())
+}
+
+fn action<'a>(label: &str, message: Option<Message>, theme: AppTheme, radius: f32) -> Element<'a, Message> {
+    button(text(label.to_string()).size(13))
+        .on_press_maybe(message)
+        .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+            is_primary: false,
+            radius,
+            primary_color: theme.primary(),
+            text_color: Color::WHITE,
+            background_color: theme.background(),
+        })))
+        .padding(Padding::new(10.0))
+        .into()
+}
+
+// Buttons stay disabled until there is a container, and a command to run
+pub fn view<'a>(state: &PikmanTerminal, theme: AppTheme, radius: f32) -> Element<'a, Message> {
+    let container = state.container.trim();
+    let enter = (!container.is_empty()).then(|| Message::PikmanEnter(container.to_string()));
+    let run = (!container.is_empty() && !state.command.trim().is_empty()).then(|| Message::PikmanRun {
+        name: container.to_string(),
+        command: vec!["sh".to_string(), "-c".to_string(), state.command.clone()],
+    });
+    column![
+        row![
+            advanced_options::field("Container", "e.g. arch", &state.container, Message::PikmanContainerNameChanged, theme, radius),
+            action("Enter Container", enter, theme, radius),
+        ]
+        .spacing(10)
+        .align_items(alignment::Alignment::Center),
+        row![
+            advanced_options::field("Command", "e.g. neofetch", &state.command, Message::PikmanCommandChanged, theme, radius),
+            action("Run in Terminal", run, theme, radius),
+        ]
+        .spacing(10)
+        .align_items(alignment::Alignment::Center),
+    ]
+    .spacing(10)
+    .into()
+}
@@ -0,0 +1,159 @@
+use iced::{
+    widget::{button, column, text, Column, Space},
+    Color, Element, Length, Padding,
+};
+
+use birdnest_core::flatpak::InstallSize;
+use birdnest_core::plan::{self, format_size, PlannedPackage, TransactionPlan};
+
+use crate::gui::styles::RoundedButtonStyle;
+use crate::gui::theme::Theme as AppTheme;
+
+// Simulate the transaction off the UI thread; None when apt can't simulate it
+pub async fn load_install_plan(packages: Vec<String>, flags: Vec<String>) -> Option<TransactionPlan> {
+    tokio::task::spawn_blocking(move || plan::plan_install_with(&packages, &flags).ok())
+        .await
+        .ok()
+        .flatten()
+}
+
+// What recommended packages add to the install; None when apt can't simulate it
+pub async fn load_recommends(packages: Vec<String>, flags: Vec<String>) -> Option<Vec<PlannedPackage>> {
+    tokio::task::spawn_blocking(move || plan::plan_recommends(&packages, &flags).ok())
+        .await
+        .ok()
+        .flatten()
+}
+
+pub async fn load_remove_plan(packages: Vec<String>) -> Option<TransactionPlan> {
+    tokio::task::spawn_blocking(move || plan::plan_remove(&packages, false).ok())
+        .await
+        .ok()
+        .flatten()
+}
+
+// What an autoremove after the removal would take; None when apt can't simulate it
+pub async fn load_follow_up(packages: Vec<String>) -> Option<Vec<PlannedPackage>> {
+    tokio::task::spawn_blocking(move || plan::plan_follow_up(&packages).ok())
+        .await
+        .ok()
+        .flatten()
+}
+
+fn names(packages: &[PlannedPackage], skip: &[String]) -> String {
+    packages
+        .iter()
+        .filter(|p| !skip.contains(&p.name))
+        .map(|p| p.name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// One dependency per line: name, version and what it adds on disk
+fn dependency_line(package: &PlannedPackage) -> String {
+    let mut line = format!("  {} {}", package.name, package.new_version.as_deref().unwrap_or(""));
+    if let Some(installed) = package.installed_bytes {
+        line.push_str(&format!(" - {}", format_size(installed)));
+    }
+    line
+}
+
+// What the transaction changes beyond the packages the user picked, plus
+// apt-style counts and sizes - the same summary the CLI prints before prompting.
+// With `dependencies` set, extra installs are a count and combined size that
+// expands into one line per package instead of a comma-separated list.
+pub fn view_plan<'a, M: Clone + 'a>(
+    plan: &TransactionPlan,
+    requested: &[String],
+    dependencies: Option<(bool, M)>,
+    theme: AppTheme,
+) -> Element<'a, M> {
+    let mut section: Column<'a, M> = column![Space::with_height(Length::Fixed(12.0))].spacing(4);
+
+    let extra_installs = names(&plan.install, requested);
+    if let (Some((expanded, on_toggle)), Some(line)) = (dependencies, plan.dependencies_line(requested)) {
+        section = section.push(
+            button(text(format!("{} {}", if expanded { "▾" } else { "▸" }, line)).size(13))
+                .on_press(on_toggle)
+                .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                    is_primary: false,
+                    radius: 8.0,
+                    primary_color: theme.primary(),
+                    text_color: theme.secondary_text(),
+                    background_color: theme.background(),
+                })))
+                .padding(Padding::new(6.0)),
+        );
+        if expanded {
+            for package in plan.dependencies(requested) {
+                section = section.push(
+                    text(dependency_line(package))
+                        .size(12)
+                        .style(iced::theme::Text::Color(theme.secondary_text())),
+                );
+            }
+        }
+    } else if !extra_installs.is_empty() {
+        section = section.push(
+            text(format!("Also installs: {}", extra_installs))
+                .size(13)
+                .style(iced::theme::Text::Color(theme.secondary_text())),
+        );
+    }
+    let upgrades = names(&plan.upgrade, &[]);
+    if !upgrades.is_empty() {
+        section = section.push(
+            text(format!("Upgrades: {}", upgrades))
+                .size(13)
+                .style(iced::theme::Text::Color(theme.secondary_text())),
+        );
+    }
+    let extra_removals = names(&plan.remove, requested);
+    if !extra_removals.is_empty() {
+        section = section.push(
+            text(format!("Also removes: {}", extra_removals))
+                .size(13)
+                .style(iced::theme::Text::Color(Color::from_rgb(1.0, 0.4, 0.4))),
+        );
+    }
+
+    section = section.push(
+        text(plan.counts_line())
+            .size(13)
+            .style(iced::theme::Text::Color(theme.text())),
+    );
+    if let Some(sizes) = plan.sizes_line() {
+        section = section.push(
+            text(sizes)
+                .size(13)
+                .style(iced::theme::Text::Color(theme.text())),
+        );
+    }
+
+    section.into()
+}
+
+// How many packages recommends add, or leave out when `skipped`; nothing when
+// they make no difference
+pub fn view_recommends<'a, M: 'a>(extras: &[PlannedPackage], skipped: bool, theme: AppTheme) -> Element<'a, M> {
+    match plan::recommends_line(extras, skipped) {
+        Some(line) => text(line).size(13).style(iced::theme::Text::Color(theme.secondary_text())).into(),
+        None => column![].into(),
+    }
+}
+
+// The Flatpak counterpart: runtimes the install pulls in and the totals, since
+// a runtime can be many times the app's own size
+pub fn view_flatpak_size<'a, M: 'a>(size: &InstallSize, theme: AppTheme) -> Element<'a, M> {
+    let mut section: Column<'a, M> = column![Space::with_height(Length::Fixed(12.0))].spacing(4);
+    for runtime in size.runtimes() {
+        section = section.push(
+            text(format!("Also installs runtime {}", runtime.sizes_line()))
+                .size(13)
+                .style(iced::theme::Text::Color(theme.secondary_text())),
+        );
+    }
+    section
+        .push(text(size.totals_line()).size(13).style(iced::theme::Text::Color(theme.text())))
+        .into()
+}
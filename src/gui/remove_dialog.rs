@@ -1,26 +1,48 @@
 use iced::{
     alignment, executor, Color,
     widget::{button, column, container, row, scrollable, text, Space},
-    Application, Command, Element, Length, Pixels, Settings, Theme as IcedTheme, Padding,
+    Application, Command, Element, Length, Pixels, Settings, Subscription, Theme as IcedTheme, Padding,
     window,
 };
-use tokio::process::Command as TokioCommand;
+use std::sync::Arc;
 
+use crate::gui::scaling;
 use crate::gui::theme::Theme as AppTheme;
 use crate::gui::styles::{RoundedButtonStyle, RoundedContainerStyle, CustomScrollableStyle};
+use crate::gui::advanced_options;
+use crate::gui::plan_view::{load_follow_up, load_remove_plan, view_plan};
+use crate::gui::upgrade_dialog::progress_bars;
+use crate::gui::command_stream::{self, Step, StreamEvent};
+use birdnest_core::{deferred_removal, elevation};
+use birdnest_core::flatpak::FlatpakManager;
+use birdnest_core::hooks::HookEvent;
+use birdnest_core::immutable::{self, SystemKind};
+use birdnest_core::package_manager::{BackendCommand, PackageBackend, ReverseDependency};
+use birdnest_core::plan::{self, PlannedPackage, TransactionPlan};
+use birdnest_core::progress::{self, TransactionProgress};
 
 #[derive(Debug, Clone)]
 pub enum Message {
     LoadPackageInfo,
     PackageInfoLoaded(Vec<PackageDetail>),
+    PlanLoaded(Option<TransactionPlan>),
+    FollowUpLoaded(Option<Vec<PlannedPackage>>),
+    // Whether the packages the removal leaves unused go in the same transaction
+    RemoveFollowUpToggled(bool),
+    DependentsLoaded(Vec<(String, Vec<ReverseDependency>)>),
+    DesktopFilesLoaded(Vec<(String, Vec<String>)>),
+    PasswordlessChecked(bool),
     RemovePackages,
     ConfirmRemove,
     #[allow(dead_code)]
     RemovalProgress(String),
-    TerminalOutput(String),
+    RemovalOutput(StreamEvent),
     RemovalComplete,
     RemovalError(String),
+    HooksFinished(String),
     ConflictDetected(String),
+    // Add the Flatpak apps that run on a runtime being removed to the removal
+    IncludeDependents,
     Cancel,
 }
 
@@ -42,11 +64,38 @@ pub struct RemoveDialog {
     pub is_complete: bool,
     pub show_confirmation: bool,
     pub removal_progress: String,
+    // Per-package progress from apt's status lines; empty for Flatpaks,
+    // whose uninstall reports none
+    pub progress: TransactionProgress,
     pub terminal_output: String,
+    // Bumped for every removal attempt, so a retry gets a fresh subscription
+    pub attempt: u32,
     pub conflict_message: Option<String>,
     pub theme: AppTheme,
     pub border_radius: f32,
     pub is_flatpak: bool,
+    // Simulated apt transaction (None for Flatpak or when simulation fails)
+    pub plan: Option<TransactionPlan>,
+    // Packages only installed for the selected ones, which an autoremove
+    // afterwards would take
+    pub follow_up: Vec<PlannedPackage>,
+    // Remove the follow-up packages along with the selected ones
+    pub remove_follow_up: bool,
+    // Installed packages depending on each selected one, checked before
+    // removing; for Flatpaks, the apps running on each selected runtime
+    pub dependents: Vec<(String, Vec<ReverseDependency>)>,
+    // Root is available without a password (NOPASSWD sudo, permissive polkit rule, or already root)
+    pub passwordless: bool,
+    pub backend: Arc<dyn PackageBackend>,
+    // ABRoot/OSTree systems stage changes for the next boot instead of applying them
+    pub system: SystemKind,
+    // Days GUI apps stay hidden before they are uninstalled (config's
+    // deferred_removal_days); None removes straight away
+    pub defer_days: Option<u32>,
+    // Each package's desktop files, looked up when removals are deferred
+    pub desktop_files: Vec<(String, Vec<String>)>,
+    // MAIN when run standalone, otherwise the child window it was opened in
+    pub window: window::Id,
 }
 
 impl RemoveDialog {
@@ -59,11 +108,33 @@ impl RemoveDialog {
             is_complete: false,
             show_confirmation: false,
             removal_progress: String::new(),
+            progress: TransactionProgress::default(),
             terminal_output: String::new(),
+            attempt: 0,
             conflict_message: None,
             theme: AppTheme::Dark,
             border_radius: 12.0,
             is_flatpak,
+            plan: None,
+            follow_up: Vec::new(),
+            remove_follow_up: false,
+            dependents: Vec::new(),
+            passwordless: false,
+            backend: crate::gui::create_backend(),
+            system: immutable::detect(),
+            defer_days: None,
+            desktop_files: Vec::new(),
+            window: window::Id::MAIN,
+        }
+    }
+
+    pub fn window_settings() -> window::Settings {
+        window::Settings {
+            size: scaling::fit(iced::Size::new(750.0, 800.0)),
+            min_size: Some(scaling::fit(iced::Size::new(600.0, 500.0))),
+            resizable: true,
+            decorations: true,
+            ..window::Settings::default()
         }
     }
 
@@ -73,16 +144,11 @@ impl RemoveDialog {
     }
 
     pub fn run_separate_window_with_flatpak_flag(package_names: Vec<String>, is_flatpak: bool) -> Result<(), iced::Error> {
-        let dialog = Self::new(package_names, is_flatpak);
-
-        let mut window_settings = window::Settings::default();
-        window_settings.size = iced::Size::new(750.0, 800.0);
-        window_settings.min_size = Some(iced::Size::new(600.0, 500.0));
-        window_settings.resizable = true;
-        window_settings.decorations = true;
+        let mut dialog = Self::new(package_names, is_flatpak);
+        dialog.defer_days = birdnest_core::config::Config::load().ok().and_then(|config| config.deferred_removal_days);
 
         <RemoveDialog as Application>::run(Settings {
-            window: window_settings,
+            window: Self::window_settings(),
             flags: dialog,
             default_text_size: Pixels(14.0),
             antialiasing: true,
@@ -123,131 +189,142 @@ impl Application for RemoveDialog {
                 self.is_loading = true;
                 let package_names = self.package_names.clone();
                 let is_flatpak = self.is_flatpak;
-                Command::perform(load_package_info(package_names, is_flatpak), |result| {
+                let load_info = Command::perform(load_package_info(self.backend.clone(), package_names.clone(), is_flatpak), |result| {
                     match result {
                         Ok(infos) => Message::PackageInfoLoaded(infos),
                         Err(e) => Message::RemovalError(e),
                     }
-                })
+                });
+                let load_info = if self.defer_days.is_some() {
+                    let load_desktop_files = Command::perform(
+                        load_desktop_files(self.backend.clone(), package_names.clone(), is_flatpak),
+                        Message::DesktopFilesLoaded,
+                    );
+                    Command::batch([load_info, load_desktop_files])
+                } else {
+                    load_info
+                };
+                if is_flatpak {
+                    let load_dependents = Command::perform(load_flatpak_dependents(package_names), Message::DependentsLoaded);
+                    Command::batch([load_info, load_dependents])
+                } else {
+                    let check_passwordless = Command::perform(
+                        async { tokio::task::spawn_blocking(elevation::passwordless).await.unwrap_or(false) },
+                        Message::PasswordlessChecked,
+                    );
+                    // apt can't simulate changes to an immutable image
+                    let load_plan = if self.system.is_immutable() {
+                        Command::none()
+                    } else {
+                        Command::batch([
+                            Command::perform(load_remove_plan(package_names.clone()), Message::PlanLoaded),
+                            Command::perform(load_follow_up(package_names.clone()), Message::FollowUpLoaded),
+                        ])
+                    };
+                    let load_dependents = Command::perform(load_dependents(self.backend.clone(), package_names), Message::DependentsLoaded);
+                    Command::batch([load_info, load_plan, load_dependents, check_passwordless])
+                }
             }
             Message::PackageInfoLoaded(infos) => {
                 self.is_loading = false;
                 self.package_info = infos;
                 Command::none()
             }
+            Message::PlanLoaded(plan) => {
+                self.plan = plan;
+                Command::none()
+            }
+            Message::FollowUpLoaded(follow_up) => {
+                self.follow_up = follow_up.unwrap_or_default();
+                Command::none()
+            }
+            Message::RemoveFollowUpToggled(remove) => {
+                self.remove_follow_up = remove;
+                Command::none()
+            }
+            Message::DependentsLoaded(dependents) => {
+                self.dependents = dependents;
+                Command::none()
+            }
+            Message::IncludeDependents => {
+                // Apps go first, so each runtime is unused by the time its turn comes
+                let mut package_names: Vec<String> = self.cascading().into_iter().map(str::to_string).collect();
+                package_names.append(&mut self.package_names);
+                self.package_names = package_names;
+                self.dependents.clear();
+                self.update(Message::LoadPackageInfo)
+            }
+            Message::DesktopFilesLoaded(desktop_files) => {
+                self.desktop_files = desktop_files;
+                Command::none()
+            }
+            Message::PasswordlessChecked(passwordless) => {
+                self.passwordless = passwordless;
+                Command::none()
+            }
             Message::RemovePackages => {
+                if self.blocked() {
+                    return Command::none();
+                }
                 // Show confirmation dialog first
                 self.show_confirmation = true;
                 Command::none()
             }
             Message::ConfirmRemove => {
-                // User confirmed, proceed with removal
-                eprintln!("[DEBUG] ConfirmRemove: User confirmed removal");
+                // User confirmed; the removal itself is the subscription below, active while removing
+                tracing::debug!("ConfirmRemove: User confirmed removal");
                 self.show_confirmation = false;
                 self.is_removing = true;
+                self.attempt += 1;
                 self.removal_progress = "Preparing removal...".to_string();
-                let package_names = self.package_names.clone();
-                let is_flatpak = self.package_info.first().map(|p| p.is_flatpak).unwrap_or(false);
-                // Store is_flatpak in self for use in the result handler
-                self.is_flatpak = is_flatpak;
-                
-                eprintln!("[DEBUG] ConfirmRemove: Packages to remove: {:?}", package_names);
-                eprintln!("[DEBUG] ConfirmRemove: Is flatpak: {}", is_flatpak);
-                
-                // Show the command that will be executed (without -y flag)
-                let cmd_preview = if is_flatpak {
-                    format!("flatpak uninstall {}\n", package_names.join(" "))
+                self.terminal_output.clear();
+                // Store is_flatpak in self for the removal steps
+                self.is_flatpak = self.package_info.first().map(|p| p.is_flatpak).unwrap_or(false);
+                self.progress = if self.is_flatpak || self.deferring().is_some() {
+                    TransactionProgress::default()
                 } else {
-                    format!("pkexec apt-get remove {}\n", package_names.join(" "))
+                    TransactionProgress::new(&self.removing())
                 };
-                self.terminal_output = format!("$ {}\n", cmd_preview.trim());
-                eprintln!("[DEBUG] ConfirmRemove: Command preview: {}", cmd_preview.trim());
-                
-                Command::perform(remove_packages(package_names, is_flatpak), move |result| {
-                    eprintln!("[DEBUG] ConfirmRemove: Removal command completed");
-                    match result {
-                        Ok((_progress, output)) => {
-                            eprintln!("[DEBUG] ConfirmRemove: Removal succeeded, output length: {}", output.len());
-                            // Always show terminal output first, completion detection will handle the rest
-                            Message::TerminalOutput(output)
-                        },
-                        Err(e) => {
-                            eprintln!("[DEBUG] ConfirmRemove: Removal failed with error: {}", e);
-                            // Check if this is a conflict error
-                            if e.starts_with("CONFLICT_DETECTED:") {
-                                let conflict_msg = e.strip_prefix("CONFLICT_DETECTED:").unwrap_or(&e).to_string();
-                                Message::ConflictDetected(conflict_msg)
-                            } else {
-                                Message::RemovalError(e.to_string())
-                            }
-                        },
-                    }
-                })
+                tracing::debug!("ConfirmRemove: Packages to remove: {:?}", self.package_names);
+                tracing::debug!("ConfirmRemove: Is flatpak: {}", self.is_flatpak);
+                Command::none()
             }
-            Message::TerminalOutput(output) => {
-                eprintln!("[DEBUG] TerminalOutput: Received output, length: {}", output.len());
-                eprintln!("[DEBUG] TerminalOutput: Output (first 500 chars): {}", &output.chars().take(500).collect::<String>());
-                
-                // Append terminal output first
-                if !self.terminal_output.is_empty() && !self.terminal_output.ends_with('\n') {
-                    self.terminal_output.push('\n');
-                }
-                self.terminal_output.push_str(&output);
-                
-                // Check for conflicts in the output (after appending so we have full context)
-                let full_output = self.terminal_output.clone();
-                let conflict = detect_conflicts(&full_output);
-                if let Some(conflict_msg) = conflict {
-                    eprintln!("[DEBUG] TerminalOutput: Conflict detected: {}", conflict_msg);
-                    return Command::perform(async {}, move |_| Message::ConflictDetected(conflict_msg));
-                }
-                
-                // Update progress text based on output
-                let output_lower = output.to_lowercase();
-                if output_lower.contains("removing") || output_lower.contains("purging") {
-                    self.removal_progress = "Removing packages...".to_string();
-                    eprintln!("[DEBUG] TerminalOutput: Detected 'removing' or 'purging'");
-                } else if output_lower.contains("reading") {
-                    self.removal_progress = "Reading package lists...".to_string();
-                    eprintln!("[DEBUG] TerminalOutput: Detected 'reading'");
-                } else if output_lower.contains("building") {
-                    self.removal_progress = "Building dependency tree...".to_string();
-                    eprintln!("[DEBUG] TerminalOutput: Detected 'building'");
-                } else if output_lower.contains("complete") || output_lower.contains("done") {
-                    self.removal_progress = "Removal complete!".to_string();
-                    eprintln!("[DEBUG] TerminalOutput: Detected 'complete' or 'done'");
-                }
-                
-                // Check if removal is complete
-                let is_complete = output_lower.contains("complete") ||
-                   output_lower.contains("removed") ||
-                   output_lower.contains("uninstalling") || // Flatpak uninstall output
-                   output_lower.contains("done") ||
-                   output_lower.contains("success") ||
-                   output_lower.contains("finished") ||
-                   output_lower.contains("0 upgraded, 0 newly installed");
-                
-                eprintln!("[DEBUG] TerminalOutput: Is complete check: {}", is_complete);
-                
-                if is_complete {
-                    eprintln!("[DEBUG] TerminalOutput: Marking removal as complete");
-                    Command::perform(async {}, |_| Message::RemovalComplete)
-                } else {
-                    Command::none()
+            Message::RemovalOutput(StreamEvent::Line(line)) => {
+                // Progress text follows apt's status lines, which stay out of the log
+                if self.progress.feed(&line) {
+                    if let Some(status) = self.progress.status() {
+                        self.removal_progress = status.to_string();
+                    }
+                    return Command::none();
                 }
+                self.terminal_output.push_str(&line);
+                self.terminal_output.push('\n');
+                scrollable::snap_to(output_id(self.window), scrollable::RelativeOffset::END)
             }
+            Message::RemovalOutput(StreamEvent::Finished(result)) => match result {
+                Ok(()) => self.update(Message::RemovalComplete),
+                // A failed apt run explains itself in the output; show held or broken packages in the conflict dialog
+                Err(e) => match detect_conflicts(&self.terminal_output) {
+                    Some(conflict_msg) if !self.is_flatpak => self.update(Message::ConflictDetected(conflict_msg)),
+                    _ => self.update(Message::RemovalError(e)),
+                },
+            },
             Message::ConflictDetected(conflict_msg) => {
-                eprintln!("[DEBUG] ConflictDetected: {}", conflict_msg);
+                tracing::debug!("ConflictDetected: {}", conflict_msg);
                 self.is_removing = false;
                 self.conflict_message = Some(conflict_msg.clone());
+                if self.window != window::Id::MAIN {
+                    // Opened from the main window, which shows the conflict in a window of its own
+                    return Command::none();
+                }
                 
                 // Launch conflict dialog as separate window
                 let package_names = self.package_names.clone();
                 let terminal_output = self.terminal_output.clone();
                 let conflict_msg_clone = conflict_msg.clone();
                 
-                eprintln!("[DEBUG] ConflictDetected: Launching conflict dialog with {} packages", package_names.len());
-                eprintln!("[DEBUG] ConflictDetected: Message length: {}, Output length: {}", conflict_msg_clone.len(), terminal_output.len());
+                tracing::debug!("ConflictDetected: Launching conflict dialog with {} packages", package_names.len());
+                tracing::debug!("ConflictDetected: Message length: {}, Output length: {}", conflict_msg_clone.len(), terminal_output.len());
                 
                 Command::perform(
                     async move {
@@ -267,13 +344,13 @@ impl Application for RemoveDialog {
                         cmd.arg("--output");
                         cmd.arg(&terminal_output);
                         
-                        eprintln!("[DEBUG] ConflictDetected: Spawning conflict dialog process");
+                        tracing::debug!("ConflictDetected: Spawning conflict dialog process");
                         match cmd.spawn() {
                             Ok(_) => {
-                                eprintln!("[DEBUG] ConflictDetected: Conflict dialog spawned successfully");
+                                tracing::debug!("ConflictDetected: Conflict dialog spawned successfully");
                             }
                             Err(e) => {
-                                eprintln!("[DEBUG] ConflictDetected: Failed to spawn conflict dialog: {}", e);
+                                tracing::warn!("ConflictDetected: Failed to spawn conflict dialog: {}", e);
                             }
                         }
                     },
@@ -287,17 +364,32 @@ impl Application for RemoveDialog {
             Message::RemovalComplete => {
                 self.is_removing = false;
                 self.is_complete = true;
+                self.progress.finish();
+                if let Some(days) = self.deferring() {
+                    self.removal_progress = format!("Hidden; uninstalled in {} day(s)", days);
+                    self.terminal_output.push_str("✓ Hidden from the app menu. Restore from the Maintenance tab to keep it.");
+                    return Command::none();
+                }
                 self.removal_progress = "Removal completed successfully!".to_string();
-                if !self.terminal_output.contains("completed successfully") && !self.terminal_output.contains("Removal completed") {
-                    if !self.terminal_output.is_empty() && !self.terminal_output.ends_with('\n') {
-                        self.terminal_output.push('\n');
-                    }
-                    self.terminal_output.push_str("✓ Removal completed successfully!");
+                self.terminal_output.push_str("✓ Removal completed successfully!");
+                // Staged layers aren't removed until the reboot
+                if self.system.is_immutable() && !self.is_flatpak {
+                    return Command::none();
                 }
-                Command::none()
+                Command::perform(
+                    command_stream::run_hooks(HookEvent::Remove, self.removing(), self.is_flatpak),
+                    Message::HooksFinished,
+                )
+            }
+            Message::HooksFinished(output) => {
+                if !output.is_empty() {
+                    self.terminal_output.push('\n');
+                    self.terminal_output.push_str(&output);
+                }
+                scrollable::snap_to(output_id(self.window), scrollable::RelativeOffset::END)
             }
             Message::RemovalError(msg) => {
-                eprintln!("[DEBUG] RemovalError: Error received: {}", msg);
+                tracing::warn!("RemovalError: Error received: {}", msg);
                 self.is_removing = false;
                 if !self.terminal_output.is_empty() && !self.terminal_output.ends_with('\n') {
                     self.terminal_output.push('\n');
@@ -311,7 +403,7 @@ impl Application for RemoveDialog {
                     self.show_confirmation = false;
                     Command::none()
                 } else {
-                    iced::window::close(iced::window::Id::MAIN)
+                    iced::window::close(self.window)
                 }
             }
         }
@@ -364,6 +456,14 @@ impl Application for RemoveDialog {
         }
     }
 
+    fn subscription(&self) -> Subscription<Message> {
+        if self.is_removing {
+            command_stream::subscription(("remove", self.attempt), self.remove_steps(), Message::RemovalOutput)
+        } else {
+            Subscription::none()
+        }
+    }
+
     fn theme(&self) -> IcedTheme {
         match self.theme {
             AppTheme::Light => IcedTheme::Light,
@@ -372,7 +472,194 @@ impl Application for RemoveDialog {
     }
 }
 
+// The output pane, kept scrolled to the newest line; widget operations reach
+// every open window, so the id is per window
+fn output_id(window: window::Id) -> scrollable::Id {
+    scrollable::Id::new(format!("remove-output-{:?}", window))
+}
+
 impl RemoveDialog {
+    fn remove_steps(&self) -> Vec<Step> {
+        if let Some(days) = self.deferring() {
+            let (desktop_files, is_flatpak) = (self.desktop_files.clone(), self.is_flatpak);
+            return vec![Step::InProcess(Arc::new(move || {
+                for (package, files) in &desktop_files {
+                    deferred_removal::defer(package, is_flatpak, files, days)?;
+                }
+                Ok(())
+            }))];
+        }
+        if self.is_flatpak {
+            // --noninteractive skips flatpak's own confirmation; the user already confirmed in the GUI
+            return self
+                .package_names
+                .iter()
+                .map(|package| Step::Command {
+                    command: BackendCommand::new("flatpak", &["uninstall", "--noninteractive", "-y"], std::slice::from_ref(package)),
+                    elevated: false,
+                })
+                .collect();
+        }
+        // Immutable systems need more than one step (e.g. `abroot pkg remove` then `abroot pkg apply`)
+        let packages = self.removing();
+        let commands = self.backend.remove_commands(&packages);
+        if commands.is_empty() {
+            let backend = self.backend.clone();
+            return vec![Step::InProcess(Arc::new(move || backend.remove(&packages)))];
+        }
+        // apt reports per-package progress on its status fd, which the dialog reads back
+        commands
+            .into_iter()
+            .map(progress::with_status_fd)
+            .map(|command| Step::Command { elevated: !command.as_user, command })
+            .collect()
+    }
+
+    /// Every package the removal takes: the selected ones, then the ones they
+    /// leave unused when those are ticked to go too. They are named outright
+    /// rather than with `--autoremove`, which would also take packages that
+    /// were unused before.
+    pub fn removing(&self) -> Vec<String> {
+        let mut packages = self.package_names.clone();
+        if self.remove_follow_up && self.deferring().is_none() {
+            packages.extend(self.follow_up.iter().map(|p| p.name.clone()));
+        }
+        packages
+    }
+
+    fn view_follow_up(&self) -> Element<'_, Message> {
+        let theme = self.theme;
+        let line = match plan::follow_up_line(&self.package_names, &self.follow_up) {
+            Some(line) if !self.is_removing && !self.is_complete && self.deferring().is_none() => line,
+            _ => return column![].spacing(0).into(),
+        };
+        let count = self.follow_up.len();
+        let label = match count {
+            1 => "Remove it too, in the same transaction".to_string(),
+            count => format!("Remove these {} too, in the same transaction", count),
+        };
+        column![
+            text(line).size(13).style(iced::theme::Text::Color(theme.secondary_text())),
+            advanced_options::toggle(&label, self.remove_follow_up, Message::RemoveFollowUpToggled, theme),
+        ]
+        .spacing(8)
+        .into()
+    }
+
+    /// Days the removal is put off for: only when every package is a GUI app,
+    /// since hiding does nothing for the rest
+    pub fn deferring(&self) -> Option<u32> {
+        let all_apps = self.desktop_files.len() == self.package_names.len() && self.desktop_files.iter().all(|(_, files)| !files.is_empty());
+        self.defer_days.filter(|_| all_apps)
+    }
+
+    /// Dependents the removal takes along: every one that isn't merely using
+    /// the package as one of several alternatives
+    pub fn cascading(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self
+            .dependents
+            .iter()
+            .flat_map(|(_, dependents)| dependents.iter().filter(|d| !d.alternative).map(|d| d.name.as_str()))
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
+
+    /// Flatpak won't uninstall a runtime that installed apps still use, so
+    /// removing is blocked until those apps are removed too
+    pub fn blocked(&self) -> bool {
+        self.is_flatpak && !self.dependents.is_empty()
+    }
+
+    fn view_dependents(&self) -> Element<'_, Message> {
+        let theme = self.theme;
+        if self.dependents.is_empty() || self.is_removing || self.is_complete {
+            return column![].spacing(0).into();
+        }
+
+        let cascading = self.cascading();
+        if self.is_flatpak {
+            return self.view_flatpak_dependents(&cascading);
+        }
+        let heading = if cascading.is_empty() {
+            "Other installed packages can use these".to_string()
+        } else {
+            format!("⚠️ This removal also removes {} package(s) that depend on it", cascading.len())
+        };
+        let mut section = column![text(heading)
+            .size(16)
+            .style(iced::theme::Text::Color(if cascading.is_empty() { theme.text() } else { theme.danger() }))]
+        .spacing(6);
+        for (package, dependents) in &self.dependents {
+            let (alternatives, required): (Vec<_>, Vec<_>) = dependents.iter().partition(|d| d.alternative);
+            if !required.is_empty() {
+                let names: Vec<&str> = required.iter().map(|d| d.name.as_str()).collect();
+                section = section.push(
+                    text(format!("{} is needed by: {}", package, names.join(", ")))
+                        .size(13)
+                        .style(iced::theme::Text::Color(theme.text())),
+                );
+            }
+            if !alternatives.is_empty() {
+                let names: Vec<&str> = alternatives.iter().map(|d| d.name.as_str()).collect();
+                section = section.push(
+                    text(format!("{} is one choice for: {} (they stay if an alternative is installed)", package, names.join(", ")))
+                        .size(13)
+                        .style(iced::theme::Text::Color(theme.secondary_text())),
+                );
+            }
+        }
+
+        container(section.padding(Padding::new(16.0)))
+            .width(Length::Fill)
+            .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+                radius: self.border_radius,
+                background: Some(if cascading.is_empty() { theme.card_background() } else { Color::from_rgb(0.3, 0.1, 0.1) }),
+                elevation: 1.0,
+            })))
+            .into()
+    }
+
+    fn view_flatpak_dependents(&self, apps: &[&str]) -> Element<'_, Message> {
+        let theme = self.theme;
+        let mut section = column![
+            text(format!("⚠️ {} installed app(s) run on this, so Flatpak won't remove it", apps.len()))
+                .size(16)
+                .style(iced::theme::Text::Color(theme.danger())),
+        ]
+        .spacing(6);
+        for (runtime, dependents) in &self.dependents {
+            let names: Vec<&str> = dependents.iter().map(|d| d.name.as_str()).collect();
+            section = section.push(
+                text(format!("{} is the runtime of: {}", runtime, names.join(", ")))
+                    .size(13)
+                    .style(iced::theme::Text::Color(theme.text())),
+            );
+        }
+        section = section.push(
+            button(text(if apps.len() == 1 { "Remove This App Too".to_string() } else { format!("Remove These {} Apps Too", apps.len()) }))
+                .on_press(Message::IncludeDependents)
+                .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                    is_primary: false,
+                    radius: self.border_radius,
+                    primary_color: theme.danger(),
+                    text_color: Color::WHITE,
+                    background_color: theme.background(),
+                })))
+                .padding(Padding::new(10.0)),
+        );
+
+        container(section.padding(Padding::new(16.0)))
+            .width(Length::Fill)
+            .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+                radius: self.border_radius,
+                background: Some(Color::from_rgb(0.3, 0.1, 0.1)),
+                elevation: 1.0,
+            })))
+            .into()
+    }
+
     fn view_package_info(&self) -> Element<Message> {
         let theme = self.theme;
         let needs_sudo = !self.package_info.first().map(|p| p.is_flatpak).unwrap_or(false);
@@ -385,11 +672,25 @@ impl RemoveDialog {
 
         // Show confirmation dialog if needed
         if self.show_confirmation {
-            let confirmation_text = if self.package_info.len() == 1 {
+            let mut confirmation_text = if self.package_info.len() == 1 {
                 format!("Are you sure you want to remove {}?", self.package_info[0].name)
             } else {
                 format!("Are you sure you want to remove these {} packages?", self.package_info.len())
             };
+            let cascading = self.cascading();
+            if let Some(days) = self.deferring() {
+                confirmation_text.push_str(&format!(
+                    "\n\nIt is hidden from your app menu for {} day(s) first, then uninstalled the next time BirdNest opens. Restore it from the Maintenance tab before then to keep it.",
+                    days
+                ));
+            } else if !cascading.is_empty() {
+                confirmation_text.push_str(&format!("\n\nThis also removes {} package(s) that depend on it: {}", cascading.len(), cascading.join(", ")));
+            }
+            let removing = self.removing();
+            let follow_up = &removing[self.package_names.len()..];
+            if !follow_up.is_empty() {
+                confirmation_text.push_str(&format!("\n\nAlong with the packages it leaves unused: {}", follow_up.join(", ")));
+            }
             
             return container(
                 column![
@@ -483,7 +784,7 @@ impl RemoveDialog {
                             .padding(Padding::new(14.0))
                     } else {
                         button("Remove")
-                            .on_press(Message::RemovePackages)
+                            .on_press_maybe((!self.blocked()).then_some(Message::RemovePackages))
                             .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
                                 is_primary: true,
                                 radius: self.border_radius,
@@ -614,7 +915,7 @@ impl RemoveDialog {
         };
 
         // Terminal output section (shown during removal or after completion)
-        let terminal_section = if self.is_removing || (self.is_complete && !self.terminal_output.is_empty()) {
+        let terminal_section = if !self.terminal_output.is_empty() {
             column![
                 Space::with_height(Length::Fixed(20.0)),
                 text("Output:")
@@ -627,6 +928,7 @@ impl RemoveDialog {
                         .font(iced::Font::MONOSPACE)
                         .style(iced::theme::Text::Color(theme.text()))
                 )
+                .id(output_id(self.window))
                 .style(iced::theme::Scrollable::Custom(Box::new(CustomScrollableStyle {
                     background_color: theme.surface(),
                     border_radius: self.border_radius,
@@ -638,7 +940,7 @@ impl RemoveDialog {
             column![].spacing(0)
         };
 
-        let progress_section = if !self.removal_progress.is_empty() && !self.is_removing && !self.is_complete {
+        let progress_section = if !self.removal_progress.is_empty() && !self.is_complete {
             column![
                 Space::with_height(Length::Fixed(20.0)),
                 text(&self.removal_progress)
@@ -661,7 +963,7 @@ impl RemoveDialog {
                 scrollable(
                     column![
                         content,
-                        if needs_sudo && !self.is_removing && !self.is_complete {
+                        if needs_sudo && !self.passwordless && !self.is_removing && !self.is_complete {
                             column![
                                 Space::with_height(Length::Fixed(12.0)),
                                 text("Administrator privileges will be requested")
@@ -672,6 +974,27 @@ impl RemoveDialog {
                         } else {
                             column![].spacing(0)
                         },
+                        if needs_sudo && self.system.is_immutable() {
+                            column![
+                                text(immutable::REBOOT_NOTICE)
+                                    .size(12)
+                                    .style(iced::theme::Text::Color(Color::from_rgb(1.0, 0.8, 0.0))),
+                            ]
+                            .spacing(0)
+                        } else {
+                            column![].spacing(0)
+                        },
+                        match &self.plan {
+                            Some(plan) if !self.is_removing && !self.is_complete => view_plan(plan, &self.package_names, None, theme),
+                            _ => Element::from(column![].spacing(0)),
+                        },
+                        self.view_follow_up(),
+                        self.view_dependents(),
+                        if self.is_removing || self.is_complete {
+                            progress_bars(&self.progress, theme)
+                        } else {
+                            Element::from(column![].spacing(0))
+                        },
                         progress_section,
                         conflict_section,
                         terminal_section,
@@ -702,11 +1025,65 @@ impl RemoveDialog {
     }
 }
 
-async fn load_package_info(package_names: Vec<String>, is_flatpak: bool) -> Result<Vec<PackageDetail>, String> {
+/// Installed packages depending on each of `packages`, leaving out ones being
+/// removed with it; backends that can't tell report none
+// Every package's desktop files, which decide whether the removal can be put off
+async fn load_desktop_files(backend: Arc<dyn PackageBackend>, packages: Vec<String>, is_flatpak: bool) -> Vec<(String, Vec<String>)> {
+    tokio::task::spawn_blocking(move || {
+        packages
+            .into_iter()
+            .map(|package| {
+                let files = deferred_removal::app_desktop_files(backend.as_ref(), &package, is_flatpak);
+                (package, files)
+            })
+            .collect()
+    })
+    .await
+    .unwrap_or_default()
+}
+
+async fn load_flatpak_dependents(packages: Vec<String>) -> Vec<(String, Vec<ReverseDependency>)> {
+    let dependents = tokio::task::spawn_blocking(move || FlatpakManager::new()?.dependents(&packages)).await;
+    match dependents {
+        Ok(Ok(dependents)) => dependents
+            .into_iter()
+            .map(|(runtime, apps)| {
+                (runtime, apps.into_iter().map(|name| ReverseDependency { name, alternative: false }).collect())
+            })
+            .collect(),
+        Ok(Err(e)) => {
+            tracing::debug!("load_flatpak_dependents: {}", e);
+            Vec::new()
+        }
+        Err(_) => Vec::new(),
+    }
+}
+
+pub async fn load_dependents(backend: Arc<dyn PackageBackend>, packages: Vec<String>) -> Vec<(String, Vec<ReverseDependency>)> {
+    tokio::task::spawn_blocking(move || {
+        packages
+            .iter()
+            .filter_map(|package| {
+                let dependents: Vec<ReverseDependency> = match backend.reverse_dependencies(package) {
+                    Ok(dependents) => dependents.into_iter().filter(|d| !packages.contains(&d.name)).collect(),
+                    Err(e) => {
+                        tracing::debug!("load_dependents: {}", e);
+                        return None;
+                    }
+                };
+                (!dependents.is_empty()).then(|| (package.clone(), dependents))
+            })
+            .collect()
+    })
+    .await
+    .unwrap_or_default()
+}
+
+async fn load_package_info(backend: Arc<dyn PackageBackend>, package_names: Vec<String>, is_flatpak: bool) -> Result<Vec<PackageDetail>, String> {
     use futures::future;
     
     let futures: Vec<_> = package_names.into_iter()
-        .map(|pkg| load_single_package_detail(pkg, is_flatpak))
+        .map(|pkg| load_single_package_detail(backend.clone(), pkg, is_flatpak))
         .collect();
     
     let results: Vec<Result<PackageDetail, String>> = future::join_all(futures).await;
@@ -716,7 +1093,7 @@ async fn load_package_info(package_names: Vec<String>, is_flatpak: bool) -> Resu
         match result {
             Ok(detail) => details.push(detail),
             Err(e) => {
-                eprintln!("Warning: Failed to load package detail: {}", e);
+                tracing::warn!("Failed to load package detail: {}", e);
             }
         }
     }
@@ -728,9 +1105,9 @@ async fn load_package_info(package_names: Vec<String>, is_flatpak: bool) -> Resu
     }
 }
 
-async fn load_single_package_detail(package: String, is_flatpak: bool) -> Result<PackageDetail, String> {
+async fn load_single_package_detail(backend: Arc<dyn PackageBackend>, package: String, is_flatpak: bool) -> Result<PackageDetail, String> {
     tokio::task::spawn_blocking(move || {
-        use crate::utils::run_command;
+        use birdnest_core::utils::run_command;
         
         if is_flatpak {
             let info_output = run_command("flatpak", &["info", &package], false)
@@ -785,47 +1162,13 @@ async fn load_single_package_detail(package: String, is_flatpak: bool) -> Result
                 is_flatpak: true,
             })
         } else {
-            let show_output = run_command("apt", &["show", &package], false)
+            let details = backend.info(&package)
                 .map_err(|e| format!("Failed to get package info: {}", e))?;
-            let mut version = String::new();
-            let mut description = String::new();
-            let mut size = String::new();
-            
-            for line in show_output.lines() {
-                if line.starts_with("Version:") {
-                    version = line.replace("Version:", "").trim().to_string();
-                } else if line.starts_with("Description:") {
-                    description = line.replace("Description:", "").trim().to_string();
-                } else if line.starts_with("Installed-Size:") {
-                    let size_kb = line.replace("Installed-Size:", "").trim().to_string();
-                    if let Ok(kb) = size_kb.parse::<f64>() {
-                        if kb >= 1024.0 {
-                            size = format!("{:.2} MB", kb / 1024.0);
-                        } else {
-                            size = format!("{} KB", size_kb);
-                        }
-                    } else {
-                        size = format!("{} KB", size_kb);
-                    }
-                }
-            }
-            
-            if description.is_empty() {
-                for line in show_output.lines() {
-                    if line.starts_with("Description:") {
-                        description = line.replace("Description:", "").trim().to_string();
-                    } else if !description.is_empty() && line.starts_with(" ") {
-                        description.push_str(" ");
-                        description.push_str(line.trim());
-                    }
-                }
-            }
-            
             Ok(PackageDetail {
                 name: package,
-                version: if version.is_empty() { "Unknown".to_string() } else { version },
-                description: if description.is_empty() { "No description available".to_string() } else { description },
-                size: if size.is_empty() { "Unknown".to_string() } else { size },
+                version: details.version.clone(),
+                description: crate::gui::package_description(&details),
+                size: crate::gui::package_size(&details),
                 is_flatpak: false,
             })
         }
@@ -834,183 +1177,6 @@ async fn load_single_package_detail(package: String, is_flatpak: bool) -> Result
     .map_err(|e| format!("Task error: {}", e))?
 }
 
-async fn remove_packages(package_names: Vec<String>, is_flatpak: bool) -> Result<(String, String), String> {
-    eprintln!("[DEBUG] remove_packages: Starting removal, packages: {:?}, is_flatpak: {}", package_names, is_flatpak);
-    
-    if is_flatpak {
-        // Remove flatpak packages
-        eprintln!("[DEBUG] remove_packages: Using flatpak uninstall");
-        let mut all_output = String::new();
-        for package in &package_names {
-            eprintln!("[DEBUG] remove_packages: Removing flatpak package: {}", package);
-            let mut cmd = TokioCommand::new("flatpak");
-            cmd.arg("uninstall");
-            // Add --noninteractive flag to skip confirmation (user already confirmed in GUI)
-            cmd.arg("--noninteractive");
-            cmd.arg("-y"); // Also add -y flag for yes to all prompts
-            cmd.arg(package);
-            
-            eprintln!("[DEBUG] remove_packages: Executing: flatpak uninstall {}", package);
-            let output = cmd
-                .output()
-                .await
-                .map_err(|e| {
-                    eprintln!("[DEBUG] remove_packages: Command execution error: {}", e);
-                    format!("Failed to execute removal: {}", e)
-                })?;
-            
-            eprintln!("[DEBUG] remove_packages: Command exit code: {:?}", output.status.code());
-            eprintln!("[DEBUG] remove_packages: Command success: {}", output.status.success());
-            
-            // Capture stdout and stderr
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            
-            eprintln!("[DEBUG] remove_packages: stdout length: {}, stderr length: {}", stdout.len(), stderr.len());
-            if !stdout.is_empty() {
-                eprintln!("[DEBUG] remove_packages: stdout: {}", stdout);
-            }
-            if !stderr.is_empty() {
-                eprintln!("[DEBUG] remove_packages: stderr: {}", stderr);
-            }
-            
-            if !all_output.is_empty() {
-                all_output.push('\n');
-            }
-            if !stdout.is_empty() {
-                all_output.push_str(&stdout);
-            }
-            if !stderr.is_empty() {
-                if !all_output.is_empty() && !all_output.ends_with('\n') {
-                    all_output.push('\n');
-                }
-                all_output.push_str(&stderr);
-            }
-            
-            if !output.status.success() {
-                eprintln!("[DEBUG] remove_packages: Removal failed for package: {}", package);
-                return Err(format!("Removal failed: {}", stderr));
-            }
-            eprintln!("[DEBUG] remove_packages: Successfully removed package: {}", package);
-        }
-        eprintln!("[DEBUG] remove_packages: All flatpak packages removed successfully");
-        Ok(("Removal Complete!".to_string(), all_output))
-    } else {
-        // Remove apt packages using pkexec
-        eprintln!("[DEBUG] remove_packages: Using apt-get remove via pkexec");
-        // Use apt-get instead of apt for more reliable output
-        let mut cmd = TokioCommand::new("pkexec");
-        cmd.arg("apt-get");
-        cmd.arg("remove");
-        // Add -y flag since user already confirmed in GUI
-        cmd.arg("-y");
-        for name in &package_names {
-            cmd.arg(name);
-            eprintln!("[DEBUG] remove_packages: Adding package to remove: {}", name);
-        }
-        
-        // Set DEBIAN_FRONTEND=noninteractive to avoid prompts (user already confirmed in GUI)
-        cmd.env("DEBIAN_FRONTEND", "noninteractive");
-        eprintln!("[DEBUG] remove_packages: Set DEBIAN_FRONTEND=noninteractive");
-        
-        // Ensure DISPLAY is set for GUI password dialog
-        if let Ok(display) = std::env::var("DISPLAY") {
-            let display_clone = display.clone();
-            cmd.env("DISPLAY", display);
-            eprintln!("[DEBUG] remove_packages: Set DISPLAY={}", display_clone);
-        } else {
-            eprintln!("[DEBUG] remove_packages: DISPLAY not set");
-        }
-        if let Ok(xauth) = std::env::var("XAUTHORITY") {
-            cmd.env("XAUTHORITY", xauth);
-            eprintln!("[DEBUG] remove_packages: Set XAUTHORITY");
-        }
-        if let Ok(wayland) = std::env::var("WAYLAND_DISPLAY") {
-            let wayland_clone = wayland.clone();
-            cmd.env("WAYLAND_DISPLAY", wayland);
-            eprintln!("[DEBUG] remove_packages: Set WAYLAND_DISPLAY={}", wayland_clone);
-        }
-        
-        // Also preserve PATH and other important env vars
-        if let Ok(path) = std::env::var("PATH") {
-            cmd.env("PATH", path);
-        }
-        
-        let cmd_str = format!("pkexec apt-get remove -y {}", package_names.join(" "));
-        eprintln!("[DEBUG] remove_packages: Executing command: {}", cmd_str);
-        
-        let output = cmd
-            .output()
-            .await
-            .map_err(|e| {
-                eprintln!("[DEBUG] remove_packages: Command execution error: {}", e);
-                format!("Failed to execute removal: {}. Make sure polkit is installed.", e)
-            })?;
-        
-        let exit_code = output.status.code();
-        eprintln!("[DEBUG] remove_packages: Command exit code: {:?}", exit_code);
-        eprintln!("[DEBUG] remove_packages: Command success: {}", output.status.success());
-        
-        // Capture stdout and stderr
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        
-        eprintln!("[DEBUG] remove_packages: stdout length: {}, stderr length: {}", stdout.len(), stderr.len());
-        if !stdout.is_empty() {
-            eprintln!("[DEBUG] remove_packages: stdout (first 500 chars): {}", &stdout.chars().take(500).collect::<String>());
-        }
-        if !stderr.is_empty() {
-            eprintln!("[DEBUG] remove_packages: stderr (first 500 chars): {}", &stderr.chars().take(500).collect::<String>());
-        }
-        
-        let mut all_output = String::new();
-        if !stdout.is_empty() {
-            all_output.push_str(&stdout);
-        }
-        if !stderr.is_empty() {
-            if !all_output.is_empty() && !all_output.ends_with('\n') {
-                all_output.push('\n');
-            }
-            all_output.push_str(&stderr);
-        }
-        
-        // If output is empty but command succeeded, apt might have run silently
-        // This can happen when packages are already removed or don't exist
-        if all_output.is_empty() && output.status.success() {
-            eprintln!("[DEBUG] remove_packages: Command succeeded but output is empty");
-            all_output = format!("Packages removed successfully.\nExit code: {:?}", exit_code);
-        }
-        
-        if !output.status.success() {
-            eprintln!("[DEBUG] remove_packages: Command failed");
-            if exit_code == Some(126) || exit_code == Some(127) {
-                eprintln!("[DEBUG] remove_packages: Authentication error (exit code {:?})", exit_code);
-                return Err("Authentication cancelled or failed. Please try again.".to_string());
-            }
-            
-            // Check for conflicts in the error output
-            let combined_error = format!("{}\n{}", stdout, stderr);
-            if let Some(conflict_msg) = detect_conflicts(&combined_error) {
-                eprintln!("[DEBUG] remove_packages: Conflict detected in error output");
-                // Return a special error that will trigger conflict dialog
-                return Err(format!("CONFLICT_DETECTED:{}", conflict_msg));
-            }
-            
-            // Include exit code in error for debugging
-            let error_msg = if all_output.is_empty() {
-                format!("Removal failed with exit code: {:?}", exit_code)
-            } else {
-                format!("Removal failed: {}\nExit code: {:?}", stderr, exit_code)
-            };
-            eprintln!("[DEBUG] remove_packages: Error message: {}", error_msg);
-            return Err(error_msg);
-        }
-        
-        eprintln!("[DEBUG] remove_packages: Removal completed successfully");
-        Ok(("Removal Complete!".to_string(), all_output))
-    }
-}
-
 // Function to detect conflicts in apt-get output and extract user-friendly messages
 fn detect_conflicts(output: &str) -> Option<String> {
     let output_lower = output.to_lowercase();
@@ -0,0 +1,173 @@
+use iced::{
+    alignment, Color,
+    widget::{button, checkbox, column, container, row, text, Column, Space},
+    Element, Length, Padding,
+};
+use std::collections::HashSet;
+use tokio::process::Command as TokioCommand;
+
+use birdnest_core::utils::CommandBuilder;
+use birdnest_core::restart::{self, RestartStatus};
+
+use crate::gui::theme::Theme as AppTheme;
+use crate::gui::styles::{RoundedButtonStyle, RoundedContainerStyle, YellowCheckboxStyle};
+
+/// The last restart check plus which of its services are ticked for restarting
+#[derive(Debug, Default)]
+pub struct RestartState {
+    pub status: RestartStatus,
+    pub selected: HashSet<String>,
+}
+
+impl RestartState {
+    pub fn set_status(&mut self, status: RestartStatus) {
+        // Everything is ticked at first; unticks survive a re-check
+        let unticked: HashSet<String> = self
+            .status
+            .services
+            .iter()
+            .filter(|service| !self.selected.contains(*service))
+            .cloned()
+            .collect();
+        self.selected = status.services.iter().filter(|s| !unticked.contains(*s)).cloned().collect();
+        self.status = status;
+    }
+
+    pub fn toggle(&mut self, service: String) {
+        if !self.selected.remove(&service) {
+            self.selected.insert(service);
+        }
+    }
+
+    /// Ticked services, in the order they were reported
+    pub fn selection(&self) -> Vec<String> {
+        self.status.services.iter().filter(|s| self.selected.contains(*s)).cloned().collect()
+    }
+}
+
+pub async fn check() -> RestartStatus {
+    tokio::task::spawn_blocking(restart::check).await.unwrap_or_default()
+}
+
+pub async fn restart_services(services: Vec<String>) -> Result<(), String> {
+    let command = restart::restart_services_command(&services);
+    let (std_cmd, _) = CommandBuilder::backend(&command).build().map_err(|e| e.to_string())?;
+    tracing::debug!("restart_services: Executing command: {}", command.display());
+    let output = TokioCommand::from(std_cmd)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to restart services: {}", e))?;
+    if output.status.code() == Some(126) || output.status.code() == Some(127) {
+        return Err("Authentication cancelled or failed. Please try again.".to_string());
+    }
+    if !output.status.success() {
+        return Err(format!("Failed to restart services: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(())
+}
+
+pub async fn reboot() -> Result<(), String> {
+    let command = restart::reboot_command();
+    tracing::debug!("reboot: Executing command: {}", command.display());
+    let status = TokioCommand::new(&command.program)
+        .args(command.args())
+        .status()
+        .await
+        .map_err(|e| format!("Failed to reboot: {}", e))?;
+    if !status.success() {
+        return Err("Reboot was refused or cancelled".to_string());
+    }
+    Ok(())
+}
+
+// Shared by the main window and the upgrade dialog, hence generic over the message type
+pub fn view<'a, M: Clone + 'a>(
+    state: &'a RestartState,
+    theme: AppTheme,
+    radius: f32,
+    on_toggle: fn(String) -> M,
+    on_restart_services: M,
+    on_reboot: M,
+) -> Element<'a, M> {
+    let status = &state.status;
+    if status.is_empty() {
+        return Space::with_height(Length::Fixed(0.0)).into();
+    }
+
+    // Services get a checkbox each rather than the summary line
+    let findings = RestartStatus { services: Vec::new(), ..status.clone() };
+    let mut lines = Column::new().spacing(4);
+    for line in findings.summary() {
+        lines = lines.push(text(line).size(13.0).style(iced::theme::Text::Color(theme.text())));
+    }
+    if !status.services.is_empty() {
+        lines = lines.push(
+            text("Services using outdated libraries:")
+                .size(13.0)
+                .style(iced::theme::Text::Color(theme.text())),
+        );
+        for service in &status.services {
+            lines = lines.push(
+                checkbox(service.as_str(), state.selected.contains(service))
+                    .on_toggle(move |_| on_toggle(service.clone()))
+                    .text_size(13.0)
+                    .style(iced::theme::Checkbox::Custom(Box::new(YellowCheckboxStyle {
+                        radius: 4.0,
+                        primary_color: theme.primary(),
+                    }))),
+            );
+        }
+    }
+
+    let button_style = |is_primary: bool| {
+        iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+            is_primary,
+            radius,
+            primary_color: theme.primary(),
+            text_color: if is_primary { Color::BLACK } else { Color::WHITE },
+            background_color: theme.background(),
+        }))
+    };
+    let mut buttons = row![].spacing(10);
+    if !status.services.is_empty() {
+        let mut restart = button(text(format!("Restart {} Selected", state.selected.len())).size(14.0))
+            .style(button_style(!status.needs_reboot()))
+            .padding(Padding::new(10.0));
+        if !state.selected.is_empty() {
+            restart = restart.on_press(on_restart_services);
+        }
+        buttons = buttons.push(restart);
+    }
+    if status.needs_reboot() {
+        buttons = buttons.push(
+            button(text("Reboot Now").size(14.0))
+                .on_press(on_reboot)
+                .style(button_style(true))
+                .padding(Padding::new(10.0)),
+        );
+    }
+
+    container(
+        row![
+            column![
+                text(if status.needs_reboot() { "Restart required" } else { "Services need a restart" })
+                    .size(16.0)
+                    .style(iced::theme::Text::Color(Color::from_rgb(1.0, 0.8, 0.0))),
+                lines,
+            ]
+            .spacing(6)
+            .width(Length::Fill),
+            buttons,
+        ]
+        .spacing(12)
+        .align_items(alignment::Alignment::Center),
+    )
+    .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+        radius,
+        background: Some(theme.card_background()),
+        elevation: 1.0,
+    })))
+    .width(Length::Fill)
+    .padding(Padding::new(12.0))
+    .into()
+}
@@ -0,0 +1,181 @@
+use iced::{
+    alignment,
+    widget::{button, row, text, Row, Space},
+    Color, Element, Length, Padding,
+};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::ops::Range;
+
+use birdnest_core::dpkg;
+
+use crate::gui::disk_usage::toggle_button;
+use crate::gui::styles::RoundedButtonStyle;
+use crate::gui::theme::Theme as AppTheme;
+use crate::gui::{Message, PackageInfo};
+
+// "lib" matches thousands of packages, and a system can have as many
+// installed. The Search and Installed tabs list them a page of
+// `search_result_limit` rows at a time, in the order picked above the list,
+// so only one page of rows is ever built. Sizes and change times are only
+// known for installed packages; search results that aren't installed sort
+// after those that are.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortOrder {
+    /// Best match first, as the search ranked it
+    #[default]
+    Relevance,
+    Name,
+    /// Largest first
+    Size,
+    /// Installed or upgraded most recently first
+    Updated,
+}
+
+impl SortOrder {
+    pub const SEARCH: [SortOrder; 4] = [SortOrder::Relevance, SortOrder::Name, SortOrder::Size, SortOrder::Updated];
+    // Installed packages have no ranking to go by
+    pub const INSTALLED: [SortOrder; 3] = [SortOrder::Name, SortOrder::Size, SortOrder::Updated];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SortOrder::Relevance => "Relevance",
+            SortOrder::Name => "By Name",
+            SortOrder::Size => "By Size",
+            SortOrder::Updated => "Recently Updated",
+        }
+    }
+}
+
+/// Sizes and change times of installed packages, by name, for sorting rows
+/// that don't carry them
+#[derive(Debug, Default)]
+pub struct InstalledFacts {
+    pub sizes: HashMap<String, u64>,
+    /// When dpkg last installed or upgraded each package
+    pub changed: HashMap<String, i64>,
+}
+
+impl InstalledFacts {
+    pub fn set_sizes(&mut self, packages: &[PackageInfo]) {
+        self.sizes = packages.iter().filter_map(|package| Some((package.name.clone(), package.installed_bytes?))).collect();
+    }
+
+    fn size(&self, package: &PackageInfo) -> Option<u64> {
+        package.installed_bytes.or_else(|| self.sizes.get(&package.name).copied())
+    }
+
+    // Unknown values sort last, whichever way the rest go
+    fn compare(&self, a: &PackageInfo, b: &PackageInfo, order: SortOrder) -> Ordering {
+        fn descending<T: Ord>(a: Option<T>, b: Option<T>) -> Ordering {
+            match (a, b) {
+                (Some(a), Some(b)) => b.cmp(&a),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            }
+        }
+        match order {
+            SortOrder::Relevance => Ordering::Equal,
+            SortOrder::Name => a.name.cmp(&b.name),
+            SortOrder::Size => descending(self.size(a), self.size(b)),
+            SortOrder::Updated => descending(self.changed.get(&a.name), self.changed.get(&b.name)),
+        }
+    }
+
+    /// `packages` in `order`. Stable, so ties keep the order they came in.
+    pub fn sort(&self, packages: &mut [&PackageInfo], order: SortOrder) {
+        packages.sort_by(|a, b| self.compare(a, b, order));
+    }
+
+    /// The positions of `packages` in `order`, leaving the list itself as
+    /// the search ranked it
+    pub fn order(&self, packages: &[PackageInfo], order: SortOrder) -> Vec<usize> {
+        let mut positions: Vec<usize> = (0..packages.len()).collect();
+        positions.sort_by(|a, b| self.compare(&packages[*a], &packages[*b], order));
+        positions
+    }
+}
+
+pub async fn load_change_times() -> HashMap<String, i64> {
+    tokio::task::spawn_blocking(dpkg::read_change_times).await.unwrap_or_default()
+}
+
+/// Which page of a long list is showing
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Pager {
+    pub page: usize,
+}
+
+impl Pager {
+    /// How many pages `len` rows take, `size` to a page; one for everything
+    /// when there is no size
+    pub fn pages(len: usize, size: Option<usize>) -> usize {
+        match size {
+            Some(size) if size > 0 => len.div_ceil(size).max(1),
+            _ => 1,
+        }
+    }
+
+    /// The rows of `len` the page shows; the last page once the list has
+    /// shrunk below this one
+    pub fn range(&self, len: usize, size: Option<usize>) -> Range<usize> {
+        let Some(size) = size.filter(|size| *size > 0) else {
+            return 0..len;
+        };
+        let page = self.page.min(Self::pages(len, Some(size)) - 1);
+        page * size..len.min((page + 1) * size)
+    }
+
+    pub fn reset(&mut self) {
+        self.page = 0;
+    }
+}
+
+/// The orders a list can be sorted in, as toggle buttons
+pub fn sort_choices<'a>(
+    choices: &[SortOrder],
+    selected: Option<SortOrder>,
+    on_select: fn(SortOrder) -> Message,
+    theme: AppTheme,
+    radius: f32,
+) -> Row<'a, Message> {
+    choices.iter().fold(row![].spacing(8), |line, order| {
+        line.push(toggle_button(order.label(), selected == Some(*order), on_select(*order), theme, radius))
+    })
+}
+
+/// "Page 2 of 63" between Previous and Next; nothing for a list that fits
+/// on one page
+pub fn view<'a>(pager: Pager, len: usize, size: Option<usize>, on_page: fn(usize) -> Message, theme: AppTheme, radius: f32) -> Element<'a, Message> {
+    let pages = Pager::pages(len, size);
+    if pages < 2 {
+        return Space::with_height(Length::Fixed(0.0)).into();
+    }
+    let range = pager.range(len, size);
+    let page = range.start / size.unwrap_or(len).max(1);
+    let step = |label: &str, target: Option<usize>| {
+        button(text(label.to_string()).size(13))
+            .on_press_maybe(target.map(on_page))
+            .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                is_primary: false,
+                radius,
+                primary_color: theme.primary(),
+                text_color: Color::WHITE,
+                background_color: theme.background(),
+            })))
+            .padding(Padding::new(8.0))
+    };
+    row![
+        step("‹ Previous", page.checked_sub(1)),
+        text(format!("Page {} of {} ({}–{} of {})", page + 1, pages, range.start + 1, range.end, len))
+            .size(13)
+            .style(iced::theme::Text::Color(theme.text())),
+        step("Next ›", (page + 1 < pages).then_some(page + 1)),
+        Space::with_width(Length::Fill),
+    ]
+    .spacing(12)
+    .align_items(alignment::Alignment::Center)
+    .into()
+}
@@ -0,0 +1,291 @@
+use iced::{
+    alignment, Color,
+    widget::{button, column, container, mouse_area, row, scrollable, text, Column, Row},
+    Element, Length, Padding,
+};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::process::Command as TokioCommand;
+
+use birdnest_core::flatpak::{self, FlatpakManager};
+use birdnest_core::utils::{run_command, CommandBuilder};
+use birdnest_core::package_manager::{BackendCommand, PackageBackend};
+
+use crate::gui::theme::Theme as AppTheme;
+use crate::gui::styles::{CustomScrollableStyle, RoundedButtonStyle, RoundedContainerStyle};
+use crate::gui::transaction_queue;
+use crate::gui::Message;
+
+/// The list row a quick-actions menu belongs to
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RowTarget {
+    pub name: String,
+    pub flatpak: bool,
+}
+
+impl RowTarget {
+    pub fn package(name: &str) -> Self {
+        Self { name: name.to_string(), flatpak: false }
+    }
+
+    pub fn flatpak(application: &str) -> Self {
+        Self { name: application.to_string(), flatpak: true }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RowAction {
+    Install,
+    Remove,
+    Reinstall,
+    Hold,
+    CopyName,
+    OpenHomepage,
+    ShowFiles,
+    Permissions,
+    ExportBundle,
+}
+
+impl RowAction {
+    pub fn label(self) -> &'static str {
+        match self {
+            RowAction::Install => "Install",
+            RowAction::Remove => "Remove",
+            RowAction::Reinstall => "Reinstall",
+            RowAction::Hold => "Hold",
+            RowAction::CopyName => "Copy name",
+            RowAction::OpenHomepage => "Open homepage",
+            RowAction::ShowFiles => "Show files",
+            RowAction::Permissions => "Permissions",
+            RowAction::ExportBundle => "Export bundle",
+        }
+    }
+}
+
+/// Which row has its menu open, and the file list it is showing
+#[derive(Debug, Default)]
+pub struct RowMenu {
+    pub open: Option<RowTarget>,
+    pub files: Option<Result<Vec<String>, String>>,
+}
+
+impl RowMenu {
+    /// Opens the menu on `target`, or closes it if it was already open there
+    pub fn toggle(&mut self, target: RowTarget) {
+        self.files = None;
+        self.open = if self.open.as_ref() == Some(&target) { None } else { Some(target) };
+    }
+
+    pub fn close(&mut self) {
+        self.open = None;
+        self.files = None;
+    }
+
+    pub fn is_open(&self, target: &RowTarget) -> bool {
+        self.open.as_ref() == Some(target)
+    }
+}
+
+/// What the menu offers for a row; actions the backend has no command for
+/// are left out
+pub fn actions(target: &RowTarget, installed: bool, backend: &dyn PackageBackend) -> Vec<RowAction> {
+    let names = std::slice::from_ref(&target.name);
+    let mut actions = Vec::new();
+    if installed {
+        actions.push(RowAction::Remove);
+        if target.flatpak || !backend.reinstall_commands(names).is_empty() {
+            actions.push(RowAction::Reinstall);
+        }
+        if !target.flatpak && !backend.hold_commands(names).is_empty() {
+            actions.push(RowAction::Hold);
+        }
+    } else {
+        actions.push(RowAction::Install);
+    }
+    actions.extend([RowAction::CopyName, RowAction::OpenHomepage]);
+    if installed && !target.flatpak {
+        actions.push(RowAction::ShowFiles);
+    }
+    if installed && target.flatpak {
+        actions.extend([RowAction::Permissions, RowAction::ExportBundle]);
+    }
+    actions
+}
+
+/// The commands a reinstall runs; Flatpaks reinstall per user, unelevated
+pub fn commands(action: RowAction, target: &RowTarget, backend: &dyn PackageBackend) -> Vec<BackendCommand> {
+    let names = std::slice::from_ref(&target.name);
+    match (action, target.flatpak) {
+        (RowAction::Reinstall, true) => vec![BackendCommand::as_user(
+            "flatpak",
+            &["install", "--reinstall", "-y", "--noninteractive"],
+            names,
+        )],
+        (RowAction::Reinstall, false) => backend.reinstall_commands(names),
+        _ => Vec::new(),
+    }
+}
+
+/// Runs `commands` in order, elevated unless marked as user commands, once
+/// earlier transactions have finished
+pub async fn run(commands: Vec<BackendCommand>, done: String) -> Result<String, String> {
+    let label = commands.first().map(BackendCommand::display).unwrap_or_else(|| done.clone());
+    transaction_queue::serialized(label, run_now(commands, done)).await
+}
+
+async fn run_now(commands: Vec<BackendCommand>, done: String) -> Result<String, String> {
+    for command in commands {
+        let (std_cmd, method) = CommandBuilder::backend(&command).noninteractive().build().map_err(|e| e.to_string())?;
+        if let Some(method) = method {
+            tracing::debug!("row_menu::run: Executing command: {} {}", method.command_prefix(), command.display());
+        }
+        let mut cmd = TokioCommand::from(std_cmd);
+        let output = cmd
+            .output()
+            .await
+            .map_err(|e| format!("Failed to run {}: {}", command.display(), e))?;
+        if output.status.code() == Some(126) || output.status.code() == Some(127) {
+            return Err("Authentication cancelled or failed. Please try again.".to_string());
+        }
+        if !output.status.success() {
+            return Err(format!("{} failed: {}", command.display(), String::from_utf8_lossy(&output.stderr).trim()));
+        }
+    }
+    Ok(done)
+}
+
+pub async fn files(backend: Arc<dyn PackageBackend>, package: String) -> Result<Vec<String>, String> {
+    tokio::task::spawn_blocking(move || backend.files(&package))
+        .await
+        .map_err(|e| format!("Task error: {}", e))?
+        .map_err(|e| e.to_string())
+}
+
+/// Looks up the row's homepage (Flathub's page for Flatpaks) and opens it
+/// in the default browser
+pub async fn open_homepage(backend: Arc<dyn PackageBackend>, target: RowTarget) -> Result<String, String> {
+    let url = if target.flatpak {
+        format!("https://flathub.org/apps/{}", target.name)
+    } else {
+        let name = target.name.clone();
+        tokio::task::spawn_blocking(move || backend.info(&name))
+            .await
+            .map_err(|e| format!("Task error: {}", e))?
+            .map_err(|e| e.to_string())?
+            .homepage
+            .ok_or_else(|| format!("{} doesn't list a homepage", target.name))?
+    };
+    TokioCommand::new("xdg-open")
+        .arg(&url)
+        .spawn()
+        .map_err(|e| format!("Failed to open {}: {}", url, e))?;
+    Ok(format!("Opened {}", url))
+}
+
+/// Writes an installed Flatpak to `<app ID>.flatpak` in the Downloads
+/// folder, to carry to a machine without its remote
+pub async fn export_bundle(app: String) -> Result<String, String> {
+    tokio::task::spawn_blocking(move || {
+        let installed = FlatpakManager::new()?.installed_ref(&app)?;
+        let file = flatpak::bundle_file(&downloads_dir(), &app);
+        let args = installed.bundle_args(&file);
+        run_command("flatpak", &args.iter().map(String::as_str).collect::<Vec<_>>(), false)?;
+        Ok::<_, anyhow::Error>(format!("Exported {} to {}", app, file.display()))
+    })
+    .await
+    .map_err(|e| format!("Task error: {}", e))?
+    .map_err(|e| format!("Couldn't export a bundle: {}", e))
+}
+
+// The desktop's Downloads folder, or the home folder without one
+fn downloads_dir() -> PathBuf {
+    run_command("xdg-user-dir", &["DOWNLOAD"], false)
+        .ok()
+        .map(|dir| PathBuf::from(dir.trim()))
+        .filter(|dir| dir.is_dir())
+        .unwrap_or_else(|| PathBuf::from(std::env::var("HOME").unwrap_or_default()))
+}
+
+/// A list row with its quick actions: right-clicking it or pressing "..."
+/// opens them underneath
+pub fn wrap<'a>(
+    row_content: Element<'a, Message>,
+    target: RowTarget,
+    menu: &'a RowMenu,
+    actions: Vec<RowAction>,
+    theme: AppTheme,
+    radius: f32,
+) -> Element<'a, Message> {
+    let is_open = menu.is_open(&target);
+    let overflow = button(text("...").size(16.0))
+        .on_press(Message::RowMenuToggled(target.clone()))
+        .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+            is_primary: is_open,
+            radius,
+            primary_color: theme.primary(),
+            text_color: if is_open { Color::BLACK } else { Color::WHITE },
+            background_color: theme.background(),
+        })))
+        .padding(Padding::new(10.0));
+    let line = row![
+        mouse_area(container(row_content).width(Length::Fill)).on_right_press(Message::RowMenuToggled(target.clone())),
+        overflow,
+    ]
+    .spacing(6)
+    .align_items(alignment::Alignment::Center);
+    if !is_open {
+        return line.into();
+    }
+    column![line, view(&target, menu, actions, theme, radius)].spacing(4).into()
+}
+
+fn view<'a>(target: &RowTarget, menu: &'a RowMenu, actions: Vec<RowAction>, theme: AppTheme, radius: f32) -> Element<'a, Message> {
+    let buttons = actions.into_iter().fold(Row::new().spacing(8), |buttons, action| {
+        let danger = action == RowAction::Remove;
+        buttons.push(
+            button(text(action.label()).size(13.0))
+                .on_press(Message::RowAction(target.clone(), action))
+                .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                    is_primary: false,
+                    radius,
+                    primary_color: if danger { theme.danger() } else { theme.primary() },
+                    text_color: if danger { theme.danger() } else { Color::WHITE },
+                    background_color: theme.background(),
+                })))
+                .padding(Padding::new(8.0)),
+        )
+    });
+
+    let mut content = Column::new().spacing(8).push(buttons);
+    match &menu.files {
+        Some(Ok(files)) => {
+            let list = files.iter().fold(Column::new().spacing(2), |list, file| {
+                list.push(text(file).size(12.0).style(iced::theme::Text::Color(theme.text())))
+            });
+            content = content
+                .push(text(format!("{} files", files.len())).size(13.0).style(iced::theme::Text::Color(theme.text())))
+                .push(
+                    scrollable(list)
+                        .height(Length::Fixed(160.0))
+                        .style(iced::theme::Scrollable::Custom(Box::new(CustomScrollableStyle {
+                            background_color: theme.background(),
+                            border_radius: radius,
+                        }))),
+                );
+        }
+        Some(Err(e)) => {
+            content = content.push(text(e).size(13.0).style(iced::theme::Text::Color(theme.danger())));
+        }
+        None => {}
+    }
+
+    container(content)
+        .width(Length::Fill)
+        .padding(Padding::new(10.0))
+        .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+            radius,
+            background: Some(theme.surface()),
+            elevation: 1.0,
+        })))
+        .into()
+}
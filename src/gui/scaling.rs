@@ -0,0 +1,41 @@
+use iced::Size;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+// The smallest screen windows have to fit: 1080p at 150% leaves 1280x720
+// logical pixels and a 1366x768 laptop about as much, less room for a panel.
+// 1080p at 125% (1536x864) has more.
+pub const SMALLEST_SCREEN: Size = Size::new(1280.0, 680.0);
+
+// Beyond these the layout breaks down rather than getting easier to read
+const MIN_SCALE: f32 = 0.75;
+const MAX_SCALE: f32 = 2.0;
+
+// The user's ui_scale, as f32 bits, on top of the desktop's own scaling,
+// which winit already applies
+static UI_SCALE: AtomicU32 = AtomicU32::new(0x3f80_0000); // 1.0
+
+/// The config's ui_scale, 1.0 when unset, kept within what the layout survives
+pub fn clamp(scale: Option<f32>) -> f32 {
+    scale.filter(|s| s.is_finite()).unwrap_or(1.0).clamp(MIN_SCALE, MAX_SCALE)
+}
+
+pub fn set_ui_scale(scale: Option<f32>) {
+    UI_SCALE.store(clamp(scale).to_bits(), Ordering::Relaxed);
+}
+
+pub fn ui_scale() -> f32 {
+    f32::from_bits(UI_SCALE.load(Ordering::Relaxed))
+}
+
+/// A window size laid out at 100%, grown by the UI scale and capped to
+/// [`SMALLEST_SCREEN`] so windows open fully on screen
+pub fn fit(size: Size) -> Size {
+    fit_scaled(size, ui_scale())
+}
+
+pub fn fit_scaled(size: Size, scale: f32) -> Size {
+    Size::new(
+        (size.width * scale).min(SMALLEST_SCREEN.width),
+        (size.height * scale).min(SMALLEST_SCREEN.height),
+    )
+}
@@ -0,0 +1,100 @@
+use iced::{
+    alignment,
+    widget::{column, pick_list, row, text, Space},
+    Element, Length,
+};
+
+use birdnest_core::appstream::Catalog;
+
+use crate::gui::advanced_options;
+use crate::gui::browse::CATEGORIES;
+use crate::gui::theme::Theme as AppTheme;
+use crate::gui::{Message, PackageInfo};
+
+// The Search tab lists `search_result_limit` results a page at a time. A
+// search that finds more than a page says how many it found, with filters
+// to narrow it down, rather than leaving thousands of rows to page through.
+
+const ANY_CATEGORY: &str = "Any category";
+
+#[derive(Debug, Default)]
+pub struct SearchFilters {
+    /// Match the query against package names only, not descriptions
+    pub names_only: bool,
+    /// Only packages with an app in this Browse category, by index into
+    /// [`CATEGORIES`]
+    pub category: Option<usize>,
+    /// How many packages matched, when that is more than a page
+    pub total: Option<usize>,
+}
+
+impl SearchFilters {
+    pub fn is_active(&self) -> bool {
+        self.names_only || self.category.is_some()
+    }
+
+    /// Narrows what a backend's own search found to the filters, noting the
+    /// total when it runs past a page of `limit` rows
+    pub fn apply(&mut self, results: &mut Vec<PackageInfo>, query: &str, catalog: &Catalog, limit: Option<usize>) {
+        let query = query.to_lowercase();
+        let terms: Vec<&str> = query.split_whitespace().collect();
+        results.retain(|package| {
+            let name = package.name.to_lowercase();
+            (!self.names_only || terms.iter().all(|term| name.contains(term))) && in_category(catalog, &package.name, self.category)
+        });
+        self.set_total(results.len(), limit);
+    }
+
+    /// Notes that `total` packages matched, if that is more than `limit`
+    pub fn set_total(&mut self, total: usize, limit: Option<usize>) {
+        self.total = limit.filter(|limit| total > *limit).map(|_| total);
+    }
+}
+
+/// Whether the package called `name` has an app in `category`; any
+/// package will do when there is no category
+pub fn in_category(catalog: &Catalog, name: &str, category: Option<usize>) -> bool {
+    let Some(category) = category.and_then(|index| CATEGORIES.get(index)) else {
+        return true;
+    };
+    catalog
+        .for_package(name)
+        .is_some_and(|app| app.categories.iter().any(|c| category.appstream.contains(&c.as_str())))
+}
+
+// While the results run past a page, how many there were; while either that
+// or a filter is on, the filters. `listed` is below the total when low-memory
+// mode kept only the first results.
+pub fn view<'a>(filters: &SearchFilters, listed: usize, theme: AppTheme) -> Element<'a, Message> {
+    if filters.total.is_none() && !filters.is_active() {
+        return Space::with_height(Length::Fixed(0.0)).into();
+    }
+
+    let mut choices = vec![ANY_CATEGORY];
+    choices.extend(CATEGORIES.iter().map(|category| category.label));
+    let selected = filters.category.and_then(|index| CATEGORIES.get(index)).map_or(ANY_CATEGORY, |category| category.label);
+    let controls = row![
+        advanced_options::toggle("Names only", filters.names_only, Message::SearchNamesOnly, theme),
+        pick_list(choices, Some(selected), |label| {
+            Message::SearchCategoryChanged(CATEGORIES.iter().position(|category| category.label == label))
+        })
+        .text_size(13.0),
+    ]
+    .spacing(16)
+    .align_items(alignment::Alignment::Center);
+
+    let mut banner = column![].spacing(8);
+    if let Some(total) = filters.total {
+        let found = if listed < total {
+            format!("Listing the first {} of {} matches.", listed, total)
+        } else {
+            format!("{} matches.", total)
+        };
+        banner = banner.push(
+            text(format!("{} Add words to the search, or narrow it down:", found))
+            .size(14.0)
+            .style(iced::theme::Text::Color(theme.primary())),
+        );
+    }
+    banner.push(controls).into()
+}
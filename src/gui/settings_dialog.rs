@@ -0,0 +1,705 @@
+use iced::{
+    alignment, executor, Color,
+    widget::{button, checkbox, column, container, row, scrollable, slider, text, text_input, Row, Space},
+    Application, Command, Element, Length, Pixels, Settings, Subscription, Theme as IcedTheme, Padding,
+    window,
+};
+use std::path::PathBuf;
+
+use birdnest_core::config::{Config, ThemePreference};
+use birdnest_core::manifest::Manifest;
+use birdnest_core::package_manager::BackendCommand;
+use birdnest_core::proxy::{self, SystemProxy};
+
+use crate::gui::command_stream::{self, Step, StreamEvent};
+use crate::gui::scaling;
+use crate::gui::theme::Theme as AppTheme;
+use crate::gui::styles::{RoundedButtonStyle, RoundedContainerStyle, CustomScrollableStyle, YellowCheckboxStyle, YellowTextInputStyle};
+use crate::gui::TABS;
+
+// The package_manager values BackendKind::detect understands
+const BACKENDS: [&str; 5] = ["auto", "pikman", "apt", "dnf", "pacman"];
+const MAX_BORDER_RADIUS: f32 = 32.0;
+const TABS_PER_ROW: usize = 4;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Loaded(Result<Box<Config>, String>),
+    ThemeChanged(ThemePreference),
+    BorderRadiusChanged(f32),
+    DefaultTabChanged(Option<String>),
+    AutoConfirmToggled(bool),
+    NoRecommendsToggled(bool),
+    CacheTtlChanged(String),
+    DeferredDaysChanged(String),
+    UpdateHoursChanged(String),
+    UpdateNotifyToggled(bool),
+    FinishNotifyToggled(bool),
+    SearchLimitChanged(String),
+    PreloadFlatpakToggled(bool),
+    PreloadPikmanToggled(bool),
+    ProxyHttpChanged(String),
+    ProxyHttpsChanged(String),
+    SystemProxyLoaded(SystemProxy),
+    BackendChanged(&'static str),
+    ManifestPathChanged(String),
+    Backup,
+    BackedUp(Result<String, String>),
+    Restore,
+    RestorePlanned(Result<Vec<BackendCommand>, String>),
+    ConfirmRestore,
+    RestoreOutput(StreamEvent),
+    Save,
+    Saved(Result<(), String>),
+    Close,
+}
+
+// Header > Settings: the GUI-facing parts of config.toml. Saving writes the
+// whole config back, so settings only the file has are kept
+#[derive(Debug)]
+pub struct SettingsDialog {
+    pub config: Option<Config>,
+    // The cache lifetime as typed, in hours; empty for none
+    pub cache_ttl: String,
+    // Days removed apps stay hidden, as typed; empty to remove straight away
+    pub deferred_days: String,
+    // Hours between update checks, as typed; empty or 0 for none
+    pub update_hours: String,
+    // Search results listed at most, as typed; empty or 0 for all
+    pub search_limit: String,
+    // The proxy URLs as typed; empty for none
+    pub proxy_http: String,
+    pub proxy_https: String,
+    // apt's and the environment's proxies, None while they are read
+    pub system_proxy: Option<SystemProxy>,
+    pub status: Option<Result<String, String>>,
+    // Backup and restore: the manifest file, what restoring it would run
+    // once reviewed, and that run's output
+    pub manifest_path: String,
+    pub restore_steps: Vec<BackendCommand>,
+    pub restoring: bool,
+    pub restore_output: String,
+    pub restore_attempt: u32,
+    pub backup_status: Option<Result<String, String>>,
+    pub theme: AppTheme,
+    pub border_radius: f32,
+    // MAIN when run standalone, otherwise the child window it was opened in
+    pub window: window::Id,
+}
+
+impl SettingsDialog {
+    pub fn new() -> Self {
+        Self {
+            config: None,
+            cache_ttl: String::new(),
+            deferred_days: String::new(),
+            update_hours: String::new(),
+            search_limit: String::new(),
+            proxy_http: String::new(),
+            proxy_https: String::new(),
+            system_proxy: None,
+            status: None,
+            manifest_path: default_manifest_path(),
+            restore_steps: Vec::new(),
+            restoring: false,
+            restore_output: String::new(),
+            restore_attempt: 0,
+            backup_status: None,
+            theme: AppTheme::Dark,
+            border_radius: 12.0,
+            window: window::Id::MAIN,
+        }
+    }
+
+    pub fn window_settings() -> window::Settings {
+        window::Settings {
+            size: scaling::fit(iced::Size::new(720.0, 640.0)),
+            min_size: Some(scaling::fit(iced::Size::new(520.0, 420.0))),
+            resizable: true,
+            decorations: true,
+            ..window::Settings::default()
+        }
+    }
+
+    pub fn run_separate_window() -> Result<(), iced::Error> {
+        <SettingsDialog as Application>::run(Settings {
+            window: Self::window_settings(),
+            flags: Self::new(),
+            default_text_size: Pixels(14.0),
+            antialiasing: true,
+            id: None,
+            fonts: Vec::new(),
+            default_font: iced::Font::DEFAULT,
+        })
+    }
+
+    fn edit(&mut self, change: impl FnOnce(&mut Config)) -> Command<Message> {
+        if let Some(config) = &mut self.config {
+            change(config);
+            self.status = None;
+        }
+        Command::none()
+    }
+
+    /// The config as it would be saved; Err while one of the number fields
+    /// isn't a number or a proxy isn't a URL
+    pub fn edited(&self) -> Option<Result<Config, String>> {
+        let mut config = self.config.clone()?;
+        Some(self.read_numbers(&mut config).and_then(|()| self.read_proxy(&mut config)).map(|()| config))
+    }
+
+    // Backup writes the manifest; Restore reviews what it would install
+    // first, and Install runs that
+    fn view_backup(&self, theme: AppTheme, radius: f32) -> Element<'_, Message> {
+        let small_button = |label, primary: bool, message: Option<Message>| {
+            button(text(label).size(13))
+                .on_press_maybe(message)
+                .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                    is_primary: primary,
+                    radius,
+                    primary_color: theme.primary(),
+                    text_color: if primary { Color::BLACK } else { Color::WHITE },
+                    background_color: theme.background(),
+                })))
+                .padding(Padding::new(8.0))
+        };
+        let idle = !self.restoring && !self.manifest_path.trim().is_empty();
+        let mut section = column![
+            text("Backup and restore").size(16).style(iced::theme::Text::Color(theme.primary())),
+            row![
+                text_input("Manifest file", &self.manifest_path)
+                    .on_input(Message::ManifestPathChanged)
+                    .padding(Padding::new(10.0))
+                    .width(Length::Fill)
+                    .style(iced::theme::TextInput::Custom(Box::new(YellowTextInputStyle {
+                        radius,
+                        primary_color: theme.primary(),
+                        background_color: theme.background(),
+                        text_color: Color::BLACK,
+                    }))),
+                small_button("Back Up", false, idle.then_some(Message::Backup)),
+                small_button("Restore", false, idle.then_some(Message::Restore)),
+            ]
+            .spacing(8)
+            .align_items(alignment::Alignment::Center),
+            text("Manually installed apt packages, Flatpak apps with their remotes, and pikman packages, as with birdnest export")
+                .size(12)
+                .style(iced::theme::Text::Color(theme.secondary_text())),
+        ]
+        .spacing(8);
+        match &self.backup_status {
+            Some(Ok(message)) => section = section.push(text(message).size(13).style(iced::theme::Text::Color(theme.text()))),
+            Some(Err(e)) => section = section.push(text(e).size(13).style(iced::theme::Text::Color(theme.danger()))),
+            None => {}
+        }
+        for step in &self.restore_steps {
+            section = section.push(text(step.display()).size(12).font(iced::Font::MONOSPACE).style(iced::theme::Text::Color(theme.text())));
+        }
+        if !self.restore_steps.is_empty() && !self.restoring {
+            section = section.push(small_button("Install", true, Some(Message::ConfirmRestore)));
+        }
+        if !self.restore_output.is_empty() {
+            section = section.push(
+                text(&self.restore_output)
+                    .size(12)
+                    .font(iced::Font::MONOSPACE)
+                    .style(iced::theme::Text::Color(theme.secondary_text())),
+            );
+        }
+        section.into()
+    }
+
+    fn read_numbers(&self, config: &mut Config) -> Result<(), String> {
+        config.cache_ttl_hours = parse_count(&self.cache_ttl, "Cache lifetime", "hours")?;
+        config.deferred_removal_days = parse_count(&self.deferred_days, "Removal delay", "days")?;
+        config.update_check.every_hours = parse_count(&self.update_hours, "Update check interval", "hours")?.unwrap_or(0);
+        config.search_result_limit = parse_count(&self.search_limit, "Rows per page", "rows")?.unwrap_or(0);
+        Ok(())
+    }
+
+    fn read_proxy(&self, config: &mut Config) -> Result<(), String> {
+        config.proxy.http = proxy::parse(&self.proxy_http).map_err(|e| format!("HTTP proxy: {}", e))?;
+        config.proxy.https = proxy::parse(&self.proxy_https).map_err(|e| format!("HTTPS proxy: {}", e))?;
+        Ok(())
+    }
+
+    // What apt and the desktop session use, so the fields can be left
+    // empty when that is already right
+    fn system_proxy_notes(&self) -> Vec<String> {
+        let Some(system) = &self.system_proxy else {
+            return vec!["Reading apt's proxy settings...".to_string()];
+        };
+        vec![
+            format!("apt's own setting: {}", system.apt.describe()),
+            format!("BirdNest was started with: {}", system.environment.describe()),
+        ]
+    }
+}
+
+// Backups go in the home directory unless another file is typed in
+fn default_manifest_path() -> String {
+    let home = std::env::var_os("HOME").map(PathBuf::from).unwrap_or_default();
+    home.join("birdnest-manifest.toml").display().to_string()
+}
+
+async fn backup(path: PathBuf) -> Result<String, String> {
+    tokio::task::spawn_blocking(move || {
+        let manifest = Manifest::collect()?;
+        std::fs::write(&path, manifest.to_toml())?;
+        Ok::<_, anyhow::Error>(format!("Backed up {} packages and apps to {}", manifest.len(), path.display()))
+    })
+    .await
+    .map_err(|e| format!("Task error: {}", e))?
+    .map_err(|e| e.to_string())
+}
+
+// What restoring the manifest at `path` runs: only what isn't installed yet
+async fn plan_restore(path: PathBuf) -> Result<Vec<BackendCommand>, String> {
+    tokio::task::spawn_blocking(move || {
+        let manifest = Manifest::load(&path)?;
+        Ok::<_, anyhow::Error>(manifest.missing(&Manifest::installed()?).steps())
+    })
+    .await
+    .map_err(|e| format!("Task error: {}", e))?
+    .map_err(|e| format!("{:#}", e))
+}
+
+// An optional whole number typed into a field; empty means unset
+fn parse_count(value: &str, label: &str, unit: &str) -> Result<Option<u32>, String> {
+    match value.trim() {
+        "" => Ok(None),
+        count => count.parse().map(Some).map_err(|_| format!("{} must be a number of {}, not \"{}\"", label, unit, count)),
+    }
+}
+
+impl Application for SettingsDialog {
+    type Message = Message;
+    type Theme = IcedTheme;
+    type Executor = executor::Default;
+    type Flags = Self;
+
+    fn new(flags: Self) -> (Self, Command<Message>) {
+        let load = Command::perform(
+            async {
+                tokio::task::spawn_blocking(Config::load)
+                    .await
+                    .map_err(|e| format!("Task error: {}", e))?
+                    .map(Box::new)
+                    .map_err(|e| e.to_string())
+            },
+            Message::Loaded,
+        );
+        let detect = Command::perform(
+            async { tokio::task::spawn_blocking(proxy::detect).await.unwrap_or_default() },
+            Message::SystemProxyLoaded,
+        );
+        (flags, Command::batch([load, detect]))
+    }
+
+    fn title(&self) -> String {
+        "Settings - BirdNest".to_string()
+    }
+
+    fn update(&mut self, message: Message) -> Command<Message> {
+        match message {
+            Message::Loaded(Ok(config)) => {
+                self.cache_ttl = config.cache_ttl_hours.map(|hours| hours.to_string()).unwrap_or_default();
+                self.deferred_days = config.deferred_removal_days.map(|days| days.to_string()).unwrap_or_default();
+                self.update_hours = match config.update_check.every_hours {
+                    0 => String::new(),
+                    hours => hours.to_string(),
+                };
+                self.search_limit = match config.search_result_limit {
+                    0 => String::new(),
+                    limit => limit.to_string(),
+                };
+                self.proxy_http = config.proxy.http.clone().unwrap_or_default();
+                self.proxy_https = config.proxy.https.clone().unwrap_or_default();
+                self.config = Some(*config);
+                Command::none()
+            }
+            Message::Loaded(Err(e)) => {
+                self.status = Some(Err(format!("Couldn't read config.toml: {}", e)));
+                Command::none()
+            }
+            Message::ThemeChanged(theme) => self.edit(|config| config.theme = theme),
+            Message::BorderRadiusChanged(radius) => self.edit(|config| config.border_radius = radius),
+            Message::DefaultTabChanged(tab) => self.edit(|config| config.default_tab = tab),
+            Message::AutoConfirmToggled(auto) => self.edit(|config| config.auto_confirm = auto),
+            Message::NoRecommendsToggled(skip) => self.edit(|config| config.install_options.no_install_recommends = skip),
+            Message::BackendChanged(backend) => self.edit(|config| config.package_manager = backend.to_string()),
+            Message::CacheTtlChanged(hours) => {
+                self.cache_ttl = hours;
+                self.status = None;
+                Command::none()
+            }
+            Message::DeferredDaysChanged(days) => {
+                self.deferred_days = days;
+                self.status = None;
+                Command::none()
+            }
+            Message::UpdateHoursChanged(hours) => {
+                self.update_hours = hours;
+                self.status = None;
+                Command::none()
+            }
+            Message::SearchLimitChanged(limit) => {
+                self.search_limit = limit;
+                self.status = None;
+                Command::none()
+            }
+            Message::ProxyHttpChanged(url) => {
+                self.proxy_http = url;
+                self.status = None;
+                Command::none()
+            }
+            Message::ProxyHttpsChanged(url) => {
+                self.proxy_https = url;
+                self.status = None;
+                Command::none()
+            }
+            Message::SystemProxyLoaded(system) => {
+                self.system_proxy = Some(system);
+                Command::none()
+            }
+            Message::UpdateNotifyToggled(notify) => self.edit(|config| config.update_check.notify = notify),
+            Message::FinishNotifyToggled(notify) => self.edit(|config| config.notifications.on_finish = notify),
+            Message::PreloadFlatpakToggled(preload) => self.edit(|config| config.preload.flatpak = preload),
+            Message::PreloadPikmanToggled(preload) => self.edit(|config| config.preload.pikman = preload),
+            Message::Save => match self.edited() {
+                Some(Ok(config)) => {
+                    self.config = Some(config.clone());
+                    Command::perform(
+                        async move {
+                            tokio::task::spawn_blocking(move || config.save())
+                                .await
+                                .map_err(|e| format!("Task error: {}", e))?
+                                .map_err(|e| e.to_string())
+                        },
+                        Message::Saved,
+                    )
+                }
+                Some(Err(e)) => {
+                    self.status = Some(Err(e));
+                    Command::none()
+                }
+                None => Command::none(),
+            },
+            Message::Saved(result) => {
+                self.status = Some(result.map(|_| "Saved. A new package backend is used the next time BirdNest starts.".to_string()));
+                Command::none()
+            }
+            Message::ManifestPathChanged(path) => {
+                self.manifest_path = path;
+                self.restore_steps.clear();
+                self.backup_status = None;
+                Command::none()
+            }
+            Message::Backup => {
+                self.backup_status = Some(Ok("Backing up...".to_string()));
+                Command::perform(backup(PathBuf::from(self.manifest_path.trim())), Message::BackedUp)
+            }
+            Message::BackedUp(result) => {
+                self.backup_status = Some(result);
+                Command::none()
+            }
+            Message::Restore => {
+                self.restore_steps.clear();
+                self.backup_status = Some(Ok("Comparing the manifest with what is installed...".to_string()));
+                Command::perform(plan_restore(PathBuf::from(self.manifest_path.trim())), Message::RestorePlanned)
+            }
+            Message::RestorePlanned(Ok(steps)) => {
+                self.backup_status = Some(Ok(if steps.is_empty() {
+                    "Everything in the manifest is already installed".to_string()
+                } else {
+                    "Restoring runs these commands:".to_string()
+                }));
+                self.restore_steps = steps;
+                Command::none()
+            }
+            Message::RestorePlanned(Err(e)) => {
+                self.backup_status = Some(Err(e));
+                Command::none()
+            }
+            Message::ConfirmRestore => {
+                if self.restoring || self.restore_steps.is_empty() {
+                    return Command::none();
+                }
+                self.restoring = true;
+                self.restore_output.clear();
+                self.restore_attempt += 1;
+                self.backup_status = Some(Ok("Restoring...".to_string()));
+                Command::none()
+            }
+            Message::RestoreOutput(StreamEvent::Line(line)) => {
+                self.restore_output.push_str(&line);
+                self.restore_output.push('\n');
+                Command::none()
+            }
+            Message::RestoreOutput(StreamEvent::Finished(result)) => {
+                self.restoring = false;
+                self.restore_steps.clear();
+                self.backup_status = Some(result.map(|()| "Restored everything in the manifest".to_string()));
+                Command::none()
+            }
+            Message::Close => iced::window::close(self.window),
+        }
+    }
+
+    fn subscription(&self) -> Subscription<Message> {
+        if self.restoring {
+            let steps = self.restore_steps.iter().map(|command| Step::Command { elevated: !command.as_user, command: command.clone() }).collect();
+            command_stream::subscription(("restore", self.restore_attempt), steps, Message::RestoreOutput)
+        } else {
+            Subscription::none()
+        }
+    }
+
+    fn view(&self) -> Element<'_, Message> {
+        let theme = self.theme;
+        let radius = self.border_radius;
+
+        let choice = |label: String, selected: bool, message: Message| {
+            button(text(label).size(13))
+                .on_press(message)
+                .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                    is_primary: selected,
+                    radius,
+                    primary_color: theme.primary(),
+                    text_color: if selected { Color::BLACK } else { Color::WHITE },
+                    background_color: theme.background(),
+                })))
+                .padding(Padding::new(8.0))
+        };
+        let heading = |label| text(label).size(16).style(iced::theme::Text::Color(theme.primary()));
+        let number_input = |placeholder, value, on_input: fn(String) -> Message| {
+            text_input(placeholder, value)
+                .on_input(on_input)
+                .padding(Padding::new(10.0))
+                .width(Length::Fixed(220.0))
+                .style(iced::theme::TextInput::Custom(Box::new(YellowTextInputStyle {
+                    radius,
+                    primary_color: theme.primary(),
+                    background_color: theme.background(),
+                    text_color: Color::BLACK,
+                })))
+        };
+        let url_input = |placeholder, value, on_input: fn(String) -> Message| {
+            text_input(placeholder, value)
+                .on_input(on_input)
+                .padding(Padding::new(10.0))
+                .width(Length::Fill)
+                .style(iced::theme::TextInput::Custom(Box::new(YellowTextInputStyle {
+                    radius,
+                    primary_color: theme.primary(),
+                    background_color: theme.background(),
+                    text_color: Color::BLACK,
+                })))
+        };
+        let note = |label| text(label).size(12).style(iced::theme::Text::Color(theme.secondary_text()));
+
+        let mut content = column![text("Settings").size(24).style(iced::theme::Text::Color(theme.text()))]
+            .spacing(15)
+            .padding(Padding::new(30.0));
+
+        if let Some(config) = &self.config {
+            let themes = [("Dark", ThemePreference::Dark), ("Light", ThemePreference::Light)]
+                .into_iter()
+                .fold(Row::new().spacing(8), |line, (label, preference)| {
+                    line.push(choice(label.to_string(), config.theme == preference, Message::ThemeChanged(preference)))
+                });
+
+            let mut tab_choices = vec![choice("Automatic".to_string(), config.default_tab.is_none(), Message::DefaultTabChanged(None))];
+            for (label, _) in TABS {
+                let name = label.to_lowercase();
+                let selected = config.default_tab.as_deref() == Some(name.as_str());
+                tab_choices.push(choice(label.to_string(), selected, Message::DefaultTabChanged(Some(name))));
+            }
+            // Two rows, so the chips fit the window's minimum width
+            let mut tabs = column![].spacing(8);
+            let mut tab_choices = tab_choices.into_iter().peekable();
+            while tab_choices.peek().is_some() {
+                tabs = tabs.push(tab_choices.by_ref().take(TABS_PER_ROW).fold(Row::new().spacing(8), Row::push));
+            }
+
+            let backends = BACKENDS.into_iter().fold(Row::new().spacing(8), |line, backend| {
+                line.push(choice(backend.to_string(), config.package_manager == backend, Message::BackendChanged(backend)))
+            });
+
+            let mut proxy = column![
+                heading("Proxy"),
+                url_input("HTTP proxy, e.g. http://proxy.example.com:3128", &self.proxy_http, Message::ProxyHttpChanged),
+                url_input("HTTPS proxy; the HTTP one when empty", &self.proxy_https, Message::ProxyHttpsChanged),
+                note("Used by apt, Flatpak, pikman and BirdNest's own downloads; empty keeps the system's settings"),
+            ]
+            .spacing(8);
+            for line in self.system_proxy_notes() {
+                proxy = proxy.push(text(line).size(12).style(iced::theme::Text::Color(theme.secondary_text())));
+            }
+
+            content = content
+                .push(column![heading("Theme"), themes].spacing(8))
+                .push(
+                    column![
+                        heading("Corner radius"),
+                        row![
+                            slider(0.0..=MAX_BORDER_RADIUS, config.border_radius, Message::BorderRadiusChanged).step(1.0),
+                            text(format!("{:.0}", config.border_radius)).size(13).style(iced::theme::Text::Color(theme.text())),
+                        ]
+                        .spacing(12)
+                        .align_items(alignment::Alignment::Center),
+                    ]
+                    .spacing(8),
+                )
+                .push(column![heading("Open on tab"), tabs].spacing(8))
+                .push(
+                    column![
+                        heading("Command line"),
+                        checkbox("Answer yes to confirmation prompts", config.auto_confirm)
+                            .on_toggle(Message::AutoConfirmToggled)
+                            .text_size(13.0)
+                            .style(iced::theme::Checkbox::Custom(Box::new(YellowCheckboxStyle {
+                                radius: 4.0,
+                                primary_color: theme.primary(),
+                            }))),
+                    ]
+                    .spacing(8),
+                )
+                .push(
+                    column![
+                        heading("Installing"),
+                        checkbox("Leave out recommended packages", config.install_options.no_install_recommends)
+                            .on_toggle(Message::NoRecommendsToggled)
+                            .text_size(13.0)
+                            .style(iced::theme::Checkbox::Custom(Box::new(YellowCheckboxStyle {
+                                radius: 4.0,
+                                primary_color: theme.primary(),
+                            }))),
+                    ]
+                    .spacing(8),
+                )
+                .push(
+                    column![
+                        heading("Rows per page"),
+                        number_input("All on one page", &self.search_limit, Message::SearchLimitChanged),
+                        note("Search results and installed packages list this many at a time"),
+                    ]
+                    .spacing(8),
+                )
+                .push(
+                    column![
+                        heading("Installed-package cache lifetime (hours)"),
+                        number_input("Until packages change", &self.cache_ttl, Message::CacheTtlChanged),
+                    ]
+                    .spacing(8),
+                )
+                .push(
+                    column![
+                        heading("Check for updates every (hours)"),
+                        number_input("Never", &self.update_hours, Message::UpdateHoursChanged),
+                        checkbox("Notify me when new updates are found", config.update_check.notify)
+                            .on_toggle(Message::UpdateNotifyToggled)
+                            .text_size(13.0)
+                            .style(iced::theme::Checkbox::Custom(Box::new(YellowCheckboxStyle {
+                                radius: 4.0,
+                                primary_color: theme.primary(),
+                            }))),
+                        checkbox("Notify me when changes finish while I'm in another app", config.notifications.on_finish)
+                            .on_toggle(Message::FinishNotifyToggled)
+                            .text_size(13.0)
+                            .style(iced::theme::Checkbox::Custom(Box::new(YellowCheckboxStyle {
+                                radius: 4.0,
+                                primary_color: theme.primary(),
+                            }))),
+                    ]
+                    .spacing(8),
+                )
+                .push(
+                    column![
+                        heading("After startup"),
+                        checkbox("Load installed Flatpak apps in the background", config.preload.flatpak)
+                            .on_toggle(Message::PreloadFlatpakToggled)
+                            .text_size(13.0)
+                            .style(iced::theme::Checkbox::Custom(Box::new(YellowCheckboxStyle {
+                                radius: 4.0,
+                                primary_color: theme.primary(),
+                            }))),
+                        checkbox("Start pikman containers in the background", config.preload.pikman)
+                            .on_toggle(Message::PreloadPikmanToggled)
+                            .text_size(13.0)
+                            .style(iced::theme::Checkbox::Custom(Box::new(YellowCheckboxStyle {
+                                radius: 4.0,
+                                primary_color: theme.primary(),
+                            }))),
+                        note("Makes the Flatpak and Pikman tabs open instantly, at the cost of some work a few seconds after BirdNest starts"),
+                    ]
+                    .spacing(8),
+                )
+                .push(
+                    column![
+                        heading("Keep removed apps hidden for (days)"),
+                        number_input("Remove straight away", &self.deferred_days, Message::DeferredDaysChanged),
+                        note("Removed GUI apps leave the app menu first and are uninstalled once the days pass; restore them from the Maintenance tab"),
+                    ]
+                    .spacing(8),
+                )
+                .push(column![heading("Package backend"), backends, note("auto picks pikman/apt, dnf or pacman, whichever is installed")].spacing(8))
+                .push(proxy)
+                .push(self.view_backup(theme, radius));
+        } else if self.status.is_none() {
+            content = content.push(note("Loading settings..."));
+        }
+
+        let status: Element<Message> = match &self.status {
+            Some(Ok(message)) => text(message).size(13).style(iced::theme::Text::Color(theme.secondary_text())).into(),
+            Some(Err(e)) => text(e).size(13).style(iced::theme::Text::Color(theme.danger())).into(),
+            None => Space::with_width(Length::Fill).into(),
+        };
+        let footer_button = |label, primary: bool| {
+            button(text(label).size(14))
+                .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                    is_primary: primary,
+                    radius,
+                    primary_color: theme.primary(),
+                    text_color: if primary { Color::BLACK } else { Color::WHITE },
+                    background_color: theme.background(),
+                })))
+                .padding(Padding::new(14.0))
+        };
+        let save = footer_button("Save", true);
+
+        container(
+            column![
+                scrollable(content)
+                    .height(Length::Fill)
+                    .style(iced::theme::Scrollable::Custom(Box::new(CustomScrollableStyle {
+                        background_color: theme.background(),
+                        border_radius: radius,
+                    }))),
+                row![
+                    container(status).width(Length::Fill),
+                    footer_button("Close", false).on_press(Message::Close),
+                    if self.config.is_some() { save.on_press(Message::Save) } else { save },
+                ]
+                .spacing(12)
+                .padding(Padding::new(20.0))
+                .align_items(alignment::Alignment::Center),
+            ],
+        )
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+            radius,
+            background: Some(theme.background()),
+            elevation: 0.0,
+        })))
+        .into()
+    }
+
+    fn theme(&self) -> IcedTheme {
+        match self.theme {
+            AppTheme::Light => IcedTheme::Light,
+            AppTheme::Dark => IcedTheme::Dark,
+        }
+    }
+}
@@ -0,0 +1,324 @@
+use iced::{
+    alignment, Color,
+    widget::{button, column, container, row, scrollable, text, text_input, Column, Row, Space},
+    Element, Length, Padding,
+};
+use std::path::PathBuf;
+
+use birdnest_core::ppa::Ppa;
+use birdnest_core::repos::{self, Edit, NewSource, Source};
+use birdnest_core::sources_backup::{self, Snapshot};
+
+use crate::gui::theme::Theme as AppTheme;
+use crate::gui::styles::{RoundedButtonStyle, RoundedContainerStyle, CustomScrollableStyle, YellowTextInputStyle};
+use crate::gui::Message;
+
+// The Sources tab: apt's sources files, one card per entry, with a form to
+// add a repository. Every change goes through `birdnest sources` as root,
+// which backs the files up first; the backups are listed below the sources,
+// each with a Restore.
+
+#[derive(Debug, Default)]
+pub struct SourcesState {
+    pub sources: Vec<Source>,
+    pub loading: bool,
+    pub error: Option<String>,
+    /// A URL, or a whole `deb ...` line pasted from a project's instructions
+    pub new_uri: String,
+    pub new_suite: String,
+    pub new_components: String,
+    /// Whether this system is Ubuntu-based, so PPAs can be added
+    pub ppas_supported: bool,
+    /// This release's codename, which new sources default to
+    pub codename: Option<String>,
+    /// The entry whose Remove was pressed, waiting for confirmation
+    pub confirm_remove: Option<(PathBuf, usize)>,
+    /// An edit is running; the buttons wait for it
+    pub busy: bool,
+    /// Backups of the sources, newest first
+    pub backups: Vec<Snapshot>,
+    /// The backup whose Restore was pressed, waiting for confirmation
+    pub confirm_restore: Option<String>,
+}
+
+impl SourcesState {
+    /// The repository the Add fields describe
+    pub fn new_source(&self) -> Result<NewSource, String> {
+        let uri = self.new_uri.trim();
+        if uri.starts_with("deb") {
+            return NewSource::from_line(uri).map_err(|e| e.to_string());
+        }
+        let suite = match self.new_suite.trim() {
+            "" => self.codename.clone().ok_or("Enter the release to use, e.g. stable")?,
+            suite => suite.to_string(),
+        };
+        let mut components: Vec<String> = self.new_components.split_whitespace().map(str::to_string).collect();
+        if components.is_empty() {
+            components.push("main".to_string());
+        }
+        let source = NewSource { uri: uri.to_string(), suite, components, signed_by: None };
+        source.validate().map_err(|e| e.to_string())?;
+        Ok(source)
+    }
+
+    pub fn clear_form(&mut self) {
+        self.new_uri.clear();
+        self.new_suite.clear();
+        self.new_components.clear();
+    }
+}
+
+/// The sources, and the codename for the add form
+pub async fn load() -> Result<(Vec<Source>, Option<String>), String> {
+    tokio::task::spawn_blocking(|| Ok((repos::load()?, repos::codename())))
+        .await
+        .map_err(|e| format!("Task error: {}", e))?
+        .map_err(|e: anyhow::Error| e.to_string())
+}
+
+pub async fn load_backups() -> Vec<Snapshot> {
+    tokio::task::spawn_blocking(sources_backup::list).await.unwrap_or_default()
+}
+
+/// What the output log says once `edit` is done
+pub fn done_message(edit: &Edit) -> String {
+    match edit {
+        Edit::SetEnabled { file, enabled: true, .. } => format!("Enabled a source in {}", file.display()),
+        Edit::SetEnabled { file, enabled: false, .. } => format!("Disabled a source in {}", file.display()),
+        Edit::SetComponent { component, enabled: true, .. } => format!("Enabled the {} component", component),
+        Edit::SetComponent { component, enabled: false, .. } => format!("Disabled the {} component", component),
+        Edit::Add(source) => format!("Added {}", source.uri),
+        Edit::Remove { file, .. } => format!("Removed a source from {}", file.display()),
+        Edit::Restore { backup } => format!("Restored the sources from backup {}", backup),
+    }
+}
+
+fn view_source<'a>(state: &SourcesState, source: &Source, theme: AppTheme, radius: f32) -> Element<'a, Message> {
+    let small_button = |label: String, primary: bool, message: Option<Message>| {
+        button(text(label).size(13.0))
+            .on_press_maybe(if state.busy { None } else { message })
+            .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                is_primary: primary,
+                radius,
+                primary_color: theme.primary(),
+                text_color: if primary { Color::BLACK } else { Color::WHITE },
+                background_color: theme.background(),
+            })))
+            .padding(Padding::new(8.0))
+    };
+
+    // PPAs go by their ppa:owner/name, and removing one takes its key too
+    let ppa = source.uris.iter().find_map(|uri| Ppa::from_uri(uri));
+    let mut flags = Vec::new();
+    if !source.enabled {
+        flags.push("Disabled");
+    }
+    if source.is_third_party() {
+        flags.push("Third-party");
+    }
+    let title = match &ppa {
+        Some(ppa) => format!("{}  {}", ppa.spec(), source.suites.join(" ")),
+        None => source.summary(),
+    };
+    let mut heading = row![text(title)
+        .size(14.0)
+        .font(iced::Font::MONOSPACE)
+        .style(iced::theme::Text::Color(if source.enabled { theme.text() } else { theme.secondary_text() }))
+        .width(Length::Fill)]
+    .spacing(8)
+    .align_items(alignment::Alignment::Center);
+    if !flags.is_empty() {
+        heading = heading.push(text(flags.join(" · ")).size(12.0).style(iced::theme::Text::Color(theme.secondary_text())));
+    }
+
+    let mut components: Row<'a, Message> = row![text("Components").size(12.0).style(iced::theme::Text::Color(theme.secondary_text()))]
+        .spacing(6)
+        .align_items(alignment::Alignment::Center);
+    for component in source.offered_components() {
+        let enabled = source.components.contains(&component);
+        let edit = Edit::SetComponent { file: source.file.clone(), entry: source.entry, component: component.clone(), enabled: !enabled };
+        components = components.push(small_button(component, enabled, Some(Message::EditSource(edit))));
+    }
+
+    let toggle = Edit::SetEnabled { file: source.file.clone(), entry: source.entry, enabled: !source.enabled };
+    let mut actions = row![
+        components,
+        Space::with_width(Length::Fill),
+        small_button(if source.enabled { "Disable" } else { "Enable" }.to_string(), false, Some(Message::EditSource(toggle))),
+    ]
+    .spacing(8)
+    .align_items(alignment::Alignment::Center);
+    if source.is_third_party() {
+        let key = (source.file.clone(), source.entry);
+        actions = if state.confirm_remove.as_ref() == Some(&key) {
+            let remove = match ppa {
+                Some(ppa) => Message::EditPpa(ppa, false),
+                None => Message::EditSource(Edit::Remove { file: source.file.clone(), entry: source.entry }),
+            };
+            actions
+                .push(small_button("Remove for Good".to_string(), true, Some(remove)))
+                .push(small_button("Keep".to_string(), false, Some(Message::ConfirmRemoveSource(None))))
+        } else {
+            actions.push(small_button("Remove".to_string(), false, Some(Message::ConfirmRemoveSource(Some(key)))))
+        };
+    }
+
+    container(column![heading, actions].spacing(8))
+        .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+            radius,
+            background: Some(theme.card_background()),
+            elevation: 1.0,
+        })))
+        .width(Length::Fill)
+        .padding(Padding::new(12.0))
+        .into()
+}
+
+fn view_add_form<'a>(state: &SourcesState, theme: AppTheme, radius: f32) -> Element<'a, Message> {
+    let input = |placeholder: &str, value: &str, on_input: fn(String) -> Message| {
+        text_input(placeholder, value)
+            .on_input(on_input)
+            .padding(Padding::new(10.0))
+            .style(iced::theme::TextInput::Custom(Box::new(YellowTextInputStyle {
+                radius,
+                primary_color: theme.primary(),
+                background_color: theme.background(),
+                text_color: Color::BLACK,
+            })))
+    };
+    let codename = state.codename.as_deref().unwrap_or("Release");
+    let can_add = !state.busy && !state.new_uri.trim().is_empty();
+
+    container(
+        column![
+            text("Add a Repository").size(18.0).style(iced::theme::Text::Color(theme.text())),
+            text(if state.ppas_supported {
+                "Paste a repository URL, a whole \"deb ...\" line from a project's install instructions, or a ppa:owner/name."
+            } else {
+                "Paste a repository URL, or a whole \"deb ...\" line from a project's install instructions."
+            })
+                .size(13.0)
+                .style(iced::theme::Text::Color(theme.secondary_text())),
+            row![
+                input("https://example.com/apt", &state.new_uri, Message::SourceUriChanged).width(Length::FillPortion(3)),
+                input(codename, &state.new_suite, Message::SourceSuiteChanged).width(Length::FillPortion(1)),
+                input("main", &state.new_components, Message::SourceComponentsChanged).width(Length::FillPortion(1)),
+                button(text("Add").size(14.0))
+                    .on_press_maybe(can_add.then_some(Message::AddSource))
+                    .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                        is_primary: true,
+                        radius,
+                        primary_color: theme.primary(),
+                        text_color: Color::BLACK,
+                        background_color: theme.background(),
+                    })))
+                    .padding(Padding::new(10.0)),
+            ]
+            .spacing(8)
+            .align_items(alignment::Alignment::Center),
+        ]
+        .spacing(8),
+    )
+    .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+        radius,
+        background: Some(theme.card_background()),
+        elevation: 1.0,
+    })))
+    .width(Length::Fill)
+    .padding(Padding::new(16.0))
+    .into()
+}
+
+// The newest backup matches the sources as they are, unless something else
+// changed them since, so it is still offered
+fn view_backups<'a>(state: &SourcesState, theme: AppTheme, radius: f32) -> Element<'a, Message> {
+    let small_button = |label: &str, primary: bool, message: Message| {
+        button(text(label.to_string()).size(13.0))
+            .on_press_maybe((!state.busy).then_some(message))
+            .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                is_primary: primary,
+                radius,
+                primary_color: theme.primary(),
+                text_color: if primary { Color::BLACK } else { Color::WHITE },
+                background_color: theme.background(),
+            })))
+            .padding(Padding::new(8.0))
+    };
+
+    let mut list = Column::new().spacing(6);
+    for backup in &state.backups {
+        let mut line = row![text(backup.summary()).size(13.0).style(iced::theme::Text::Color(theme.text())).width(Length::Fill)]
+            .spacing(8)
+            .align_items(alignment::Alignment::Center);
+        line = if state.confirm_restore.as_ref() == Some(&backup.id) {
+            let restore = Edit::Restore { backup: backup.id.clone() };
+            line.push(small_button("Restore These", true, Message::EditSource(restore)))
+                .push(small_button("Cancel", false, Message::ConfirmRestoreSources(None)))
+        } else {
+            line.push(small_button("Restore", false, Message::ConfirmRestoreSources(Some(backup.id.clone()))))
+        };
+        list = list.push(line);
+    }
+    if state.backups.is_empty() {
+        list = list.push(
+            text("The sources are backed up before each change made here")
+                .size(13.0)
+                .style(iced::theme::Text::Color(theme.secondary_text())),
+        );
+    }
+
+    container(column![text("Backups").size(16.0).style(iced::theme::Text::Color(theme.text())), list].spacing(8))
+        .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+            radius,
+            background: Some(theme.card_background()),
+            elevation: 1.0,
+        })))
+        .width(Length::Fill)
+        .padding(Padding::new(12.0))
+        .into()
+}
+
+// The add form, then each sources file with its entries, then the backups
+pub fn view(state: &SourcesState, theme: AppTheme, radius: f32) -> Element<'_, Message> {
+    let mut list = Column::new().spacing(8);
+    let mut current_file = None;
+    for source in &state.sources {
+        if current_file != Some(&source.file) {
+            current_file = Some(&source.file);
+            list = list.push(
+                text(source.file.display().to_string())
+                    .size(13.0)
+                    .font(iced::Font::MONOSPACE)
+                    .style(iced::theme::Text::Color(theme.secondary_text())),
+            );
+        }
+        list = list.push(view_source(state, source, theme, radius));
+    }
+    if state.sources.is_empty() {
+        list = list.push(
+            text(if state.loading { "Loading sources..." } else { "No APT sources on this system" })
+                .size(16.0)
+                .style(iced::theme::Text::Color(theme.text())),
+        );
+    }
+    list = list.push(view_backups(state, theme, radius));
+
+    let status: Element<'_, Message> = match &state.error {
+        Some(error) => text(error).size(13.0).style(iced::theme::Text::Color(theme.danger())).into(),
+        None if state.busy => text("Applying...").size(13.0).style(iced::theme::Text::Color(theme.secondary_text())).into(),
+        None => Space::with_height(Length::Fixed(0.0)).into(),
+    };
+
+    column![
+        view_add_form(state, theme, radius),
+        status,
+        scrollable(list.padding(Padding::new(4.0)))
+            .style(iced::theme::Scrollable::Custom(Box::new(CustomScrollableStyle {
+                background_color: theme.background(),
+                border_radius: radius,
+            })))
+            .height(Length::Fill),
+    ]
+    .spacing(12)
+    .into()
+}
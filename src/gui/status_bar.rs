@@ -0,0 +1,161 @@
+use iced::{
+    alignment, Color,
+    widget::{button, container, row, text, tooltip, Row, Space},
+    Element, Length, Padding,
+};
+use std::sync::Arc;
+
+use birdnest_core::package_manager::PackageBackend;
+use birdnest_core::update_check::UpdateCount;
+use birdnest_core::utils;
+
+use crate::gui::theme::Theme as AppTheme;
+use crate::gui::styles::{RoundedButtonStyle, RoundedContainerStyle};
+use crate::gui::{Message, Tab};
+use crate::renderer::Choice;
+
+// The bar along the bottom of the main window: what runs in the background
+// right now, whether each package source can be used, and the pending updates
+
+/// Whether a package source's tool is installed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceHealth {
+    pub label: String,
+    pub available: bool,
+}
+
+// What the tool behind `backend` is called; backends that work in-process
+// (the mock) have nothing to find
+fn backend_health(backend: &dyn PackageBackend) -> SourceHealth {
+    let program = backend.install_commands(&[]).into_iter().next().map(|command| command.program);
+    SourceHealth {
+        label: backend.name().to_string(),
+        available: program.is_none_or(|program| utils::find_in_path(&program).is_some()),
+    }
+}
+
+fn tool_health(label: &str, program: &str) -> SourceHealth {
+    SourceHealth { label: label.to_string(), available: utils::find_in_path(program).is_some() }
+}
+
+pub async fn check_health(backend: Arc<dyn PackageBackend>) -> Vec<SourceHealth> {
+    tokio::task::spawn_blocking(move || {
+        vec![backend_health(backend.as_ref()), tool_health("Flatpak", "flatpak"), tool_health("pikman", "pikman")]
+    })
+    .await
+    .unwrap_or_default()
+}
+
+/// Everything the bar shows, gathered by the main window
+pub struct Status<'a> {
+    /// Background work in progress, e.g. "Loading Flatpak apps"
+    pub tasks: Vec<String>,
+    /// Empty until checked
+    pub health: &'a [SourceHealth],
+    pub updates: Option<UpdateCount>,
+    /// The renderer picked at startup, with the reason on hover
+    pub renderer: Option<&'static Choice>,
+    /// Whether the Activity drawer is showing
+    pub activity_open: bool,
+    /// Errors the Activity drawer has that haven't been looked at
+    pub unseen_errors: usize,
+}
+
+/// "Loading installed packages, Searching (+1 more)"; None when idle
+pub fn tasks_line(tasks: &[String]) -> Option<String> {
+    match tasks {
+        [] => None,
+        [task] => Some(format!("{}...", task)),
+        [first, second] => Some(format!("{}, {}...", first, second)),
+        [first, second, rest @ ..] => Some(format!("{}, {} (+{} more)...", first, second, rest.len())),
+    }
+}
+
+pub fn view<'a>(status: Status<'_>, theme: AppTheme, radius: f32) -> Element<'a, Message> {
+    let activity = text(tasks_line(&status.tasks).unwrap_or_else(|| "Idle".to_string()))
+        .size(13.0)
+        .style(iced::theme::Text::Color(if status.tasks.is_empty() { theme.secondary_text() } else { theme.text() }));
+
+    let mut health: Row<'a, Message> = row![].spacing(12).align_items(alignment::Alignment::Center);
+    for source in status.health {
+        let (mark, color) = if source.available {
+            ("●", Color::from_rgb(0.3, 0.8, 0.4))
+        } else {
+            ("○", theme.danger())
+        };
+        health = health.push(
+            row![
+                text(mark).size(12.0).style(iced::theme::Text::Color(color)),
+                text(if source.available { source.label.clone() } else { format!("{} unavailable", source.label) })
+                    .size(13.0)
+                    .style(iced::theme::Text::Color(theme.secondary_text())),
+            ]
+            .spacing(4)
+            .align_items(alignment::Alignment::Center),
+        );
+    }
+
+    let renderer: Element<'a, Message> = match status.renderer {
+        Some(choice) => tooltip(
+            text(choice.label()).size(13.0).style(iced::theme::Text::Color(theme.secondary_text())),
+            text(choice.describe()).size(13.0),
+            tooltip::Position::Top,
+        )
+        .style(iced::theme::Container::Box)
+        .padding(6)
+        .into(),
+        None => Space::with_width(Length::Fixed(0.0)).into(),
+    };
+
+    let updates: Element<'a, Message> = match status.updates {
+        Some(count) if count.total() > 0 => button(text(count.summary()).size(13.0))
+            .on_press(Message::TabChanged(Tab::Updates))
+            .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                is_primary: false,
+                radius,
+                primary_color: theme.primary(),
+                text_color: theme.primary(),
+                background_color: theme.background(),
+            })))
+            .padding(Padding::from([4.0, 10.0]))
+            .into(),
+        Some(count) => text(count.summary()).size(13.0).style(iced::theme::Text::Color(theme.secondary_text())).into(),
+        None => Space::with_width(Length::Fixed(0.0)).into(),
+    };
+
+    let log_label = match status.unseen_errors {
+        0 => "Activity".to_string(),
+        1 => "Activity (1 error)".to_string(),
+        errors => format!("Activity ({} errors)", errors),
+    };
+    let log_button = button(text(log_label).size(13.0))
+        .on_press(Message::ToggleActivity)
+        .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+            is_primary: status.activity_open,
+            radius,
+            primary_color: theme.primary(),
+            text_color: if status.activity_open {
+                Color::BLACK
+            } else if status.unseen_errors > 0 {
+                theme.danger()
+            } else {
+                theme.secondary_text()
+            },
+            background_color: theme.background(),
+        })))
+        .padding(Padding::from([4.0, 10.0]));
+
+    container(
+        row![log_button, activity, Space::with_width(Length::Fill), renderer, health, updates]
+            .spacing(16)
+            .align_items(alignment::Alignment::Center),
+    )
+    .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+        radius,
+        background: Some(theme.surface()),
+        elevation: 0.5,
+    })))
+    .width(Length::Fill)
+    .padding(Padding::from([6.0, 14.0]))
+    .into()
+}
@@ -4,6 +4,23 @@ use iced::widget::container::{Appearance as ContainerAppearance, StyleSheet as C
 use iced::widget::scrollable::{Appearance as ScrollableAppearance, StyleSheet as ScrollableStyleSheet};
 use iced::widget::text_input::{Appearance as TextInputAppearance, StyleSheet as TextInputStyleSheet};
 use iced::widget::checkbox::{Appearance as CheckboxAppearance, StyleSheet as CheckboxStyleSheet};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// Set once at startup in low-memory mode - large blurred shadows are the most
+// expensive thing we draw, so they are dropped entirely
+static REDUCED_EFFECTS: AtomicBool = AtomicBool::new(false);
+
+pub fn set_reduced_effects(enabled: bool) {
+    REDUCED_EFFECTS.store(enabled, Ordering::Relaxed);
+}
+
+fn effective_shadow(shadow: iced::Shadow) -> iced::Shadow {
+    if REDUCED_EFFECTS.load(Ordering::Relaxed) {
+        iced::Shadow::default()
+    } else {
+        shadow
+    }
+}
 
 pub struct RoundedButtonStyle {
     pub is_primary: bool,
@@ -46,12 +63,12 @@ impl ButtonStyleSheet for RoundedButtonStyle {
                 },
             },
             text_color: self.text_color,
-            shadow: iced::Shadow {
+            shadow: effective_shadow(iced::Shadow {
                 // MAXIMUM shadows for EXTREME 3D bubble effect
                 color: Color::from_rgba(0.0, 0.0, 0.0, 1.0), // Always maximum opacity
                 offset: iced::Vector::new(0.0, if self.is_primary { 20.0 } else { 15.0 }),
                 blur_radius: if self.is_primary { 50.0 } else { 35.0 },
-            },
+            }),
             shadow_offset: iced::Vector::default(),
         }
     }
@@ -85,6 +102,7 @@ impl ButtonStyleSheet for RoundedButtonStyle {
                 blur_radius: 40.0,
             };
         }
+        appearance.shadow = effective_shadow(appearance.shadow);
         appearance
     }
 
@@ -113,6 +131,7 @@ impl ButtonStyleSheet for RoundedButtonStyle {
                 blur_radius: 4.0,
             };
         }
+        appearance.shadow = effective_shadow(appearance.shadow);
         appearance
     }
 
@@ -208,11 +227,11 @@ impl ContainerStyleSheet for RoundedContainerStyle {
                 width: if self.elevation == 0.0 { 0.0 } else { 2.0 },
                 color: Color::from_rgba(0.95, 0.9, 0.45, border_opacity.min(0.5)), // Visible yellow border
             },
-            shadow: iced::Shadow {
+            shadow: effective_shadow(iced::Shadow {
                 color: Color::from_rgba(0.0, 0.0, 0.0, shadow_opacity),
                 offset: iced::Vector::new(0.0, shadow_offset),
                 blur_radius: shadow_blur,
-            },
+            }),
             text_color: None,
         }
     }
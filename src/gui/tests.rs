@@ -0,0 +1,2376 @@
+// GUI update/view tests against birdnest-core's fixture-driven MockBackend
+use super::*;
+use birdnest_core::flatpak::{FlatpakFile, InstallSize, PulledRef, RefFile};
+use birdnest_core::mock::MockBackend;
+use birdnest_core::package_manager::BackendCommand;
+
+fn mock_gui(config: Config) -> (BirdNestGUI, Arc<MockBackend>) {
+    let backend = Arc::new(MockBackend::with_default_fixture());
+    let (gui, _) = BirdNestGUI::with_backend(config, backend.clone(), SystemKind::Traditional);
+    (gui, backend)
+}
+
+fn names(packages: &[PackageInfo]) -> Vec<&str> {
+    packages.iter().map(|p| p.name.as_str()).collect()
+}
+
+#[tokio::test]
+async fn installed_list_loads_from_backend() {
+    let (mut gui, backend) = mock_gui(Config::default());
+    let _ = gui.update(Message::InitializeBackends);
+    assert!(gui.installed_loading);
+
+    let packages = load_installed_packages(backend).await;
+    let _ = gui.update(Message::InstalledPackagesLoaded(packages));
+
+    assert!(gui.installed_loaded);
+    assert!(!gui.installed_loading);
+    assert_eq!(
+        names(&gui.installed_packages),
+        ["bash", "coreutils", "firefox", "git", "htop", "vim"]
+    );
+}
+
+#[tokio::test]
+async fn search_ranks_name_prefix_matches_first() {
+    let (mut gui, backend) = mock_gui(Config::default());
+    let _ = gui.update(Message::SearchQueryChanged("vim".to_string()));
+
+    let results = search_packages(backend, gui.search_query.clone()).await;
+    let _ = gui.update(Message::SearchResults(results));
+
+    assert_eq!(names(&gui.search_results), ["vim", "neovim"]);
+}
+
+#[tokio::test]
+async fn installed_list_reflects_backend_transactions() {
+    let (mut gui, backend) = mock_gui(Config::default());
+    backend.install(&["btop".to_string()]).unwrap();
+    backend.remove(&["htop".to_string()]).unwrap();
+
+    let packages = load_installed_packages(backend.clone()).await;
+    let _ = gui.update(Message::InstalledPackagesLoaded(packages));
+
+    let installed = names(&gui.installed_packages);
+    assert!(installed.contains(&"btop"));
+    assert!(!installed.contains(&"htop"));
+}
+
+#[test]
+fn low_memory_mode_caps_search_results() {
+    let config = Config {
+        low_memory_mode: true,
+        ..Config::default()
+    };
+    let (mut gui, _) = mock_gui(config);
+    let results: Vec<PackageInfo> = (0..Config::LOW_MEMORY_RESULT_LIMIT + 50)
+        .map(|i| PackageInfo {
+            name: format!("pkg{}", i),
+            description: String::new(),
+            version: String::new(),
+            size: String::new(),
+            installed_bytes: None,
+            arch: None,
+            provenance: None,
+            source: PackageSource::Default,
+        })
+        .collect();
+
+    let _ = gui.update(Message::SearchResults(results));
+
+    assert_eq!(gui.search_results.len(), Config::LOW_MEMORY_RESULT_LIMIT);
+
+    let apps: Vec<FlatpakInfo> = (0..Config::LOW_MEMORY_RESULT_LIMIT + 50)
+        .map(|i| FlatpakInfo {
+            name: format!("App {}", i),
+            description: String::new(),
+            version: String::new(),
+            application: format!("org.example.App{}", i),
+            remotes: Vec::new(),
+        })
+        .collect();
+    let _ = gui.update(Message::FlatpakAppsLoaded(apps));
+    // Only drawing is capped; the count, filter and row menu see every app
+    assert_eq!(gui.flatpak_apps.len(), Config::LOW_MEMORY_RESULT_LIMIT + 50);
+    let _ = gui.update(Message::FlatpakInstalledFilterChanged(format!("App{}", Config::LOW_MEMORY_RESULT_LIMIT + 49)));
+    assert_eq!(filter_flatpak_apps(&gui.flatpak_apps, &gui.flatpak_installed_filter).len(), 1);
+    let _ = gui.update(Message::TabChanged(Tab::Flatpak));
+    let _ = gui.view(window::Id::MAIN);
+    let _ = gui.update(Message::TabChanged(Tab::Browse));
+    let _ = gui.view(window::Id::MAIN);
+}
+
+#[test]
+fn selections_feed_script_export() {
+    let (mut gui, _) = mock_gui(Config::default());
+    let _ = gui.update(Message::TogglePackage("neovim".to_string()));
+    let _ = gui.update(Message::ToggleInstalledPackage("git".to_string()));
+    let _ = gui.update(Message::TogglePackage("btop".to_string()));
+    let _ = gui.update(Message::TogglePackage("btop".to_string()));
+
+    let selection = gui.script_selection();
+    assert_eq!(selection.apt_packages, ["git", "neovim"]);
+    assert!(selection.flatpaks.is_empty());
+}
+
+#[test]
+fn every_tab_renders() {
+    let (mut gui, _) = mock_gui(Config::default());
+    for tab in [Tab::Search, Tab::Browse, Tab::Installed, Tab::Flatpak, Tab::Pikman, Tab::AppImage, Tab::Updates, Tab::Maintenance] {
+        let _ = gui.update(Message::TabChanged(tab));
+        let _ = gui.view(window::Id::MAIN);
+    }
+}
+
+#[test]
+fn completions_cover_public_commands_and_installed_packages() {
+    use clap_complete::Shell;
+
+    let bash = crate::completions::script(Shell::Bash);
+    assert!(bash.contains(" remove "));
+    assert!(!bash.contains(" install-dialog "));
+    assert!(bash.contains("birdnest complete-installed $flatpak"));
+    assert!(bash.trim_end().ends_with("complete -F _birdnest_installed -o bashdefault -o default birdnest"));
+
+    // The generated function is renamed so the wrapper can take its place
+    let zsh = crate::completions::script(Shell::Zsh);
+    assert!(zsh.contains("\n_birdnest_static() {"));
+    assert_eq!(zsh.matches("\n_birdnest() {").count(), 1);
+    assert!(zsh.trim_end().ends_with("_birdnest \"$@\""));
+    assert!(!zsh.contains("(internal use)"));
+
+    let fish = crate::completions::script(Shell::Fish);
+    assert!(fish.contains("-a \"(birdnest complete-installed --flatpak 2>/dev/null)\""));
+    assert!(!fish.contains("(internal use)"));
+}
+
+#[test]
+fn log_viewer_filters_by_level_and_flags_set_verbosity() {
+    use crate::logging::{self, Level, Verbosity};
+    use clap::Parser;
+    use iced::Application as _;
+    use log_viewer_dialog::Message as LogMessage;
+
+    let content = "\
+2026-10-16T09:12:03.100000Z DEBUG birdnest_core::cache: Loaded 1523 packages from cache
+2026-10-16T09:12:04.200000Z  WARN birdnest::gui: Update check failed: no network
+    caused by: timed out
+2026-10-16T09:12:05.300000Z  INFO birdnest::gui: Tab changed to: Updates
+";
+    assert_eq!(logging::tail(content, Level::Debug, 10).len(), 4);
+    assert_eq!(
+        logging::tail(content, Level::Warn, 10),
+        ["2026-10-16T09:12:04.200000Z  WARN birdnest::gui: Update check failed: no network", "    caused by: timed out"]
+    );
+    assert_eq!(logging::tail(content, Level::Debug, 1), ["2026-10-16T09:12:05.300000Z  INFO birdnest::gui: Tab changed to: Updates"]);
+
+    let mut dialog = LogViewerDialog::new();
+    let _ = dialog.update(LogMessage::FileLoaded(content.to_string()));
+    let _ = dialog.update(LogMessage::SetLevel(Level::Error));
+    assert_eq!(dialog.min_level, Level::Error);
+    let _ = dialog.view();
+
+    let quiet = crate::cli::Cli::try_parse_from(["birdnest", "list", "-q"]).unwrap();
+    assert_eq!(Verbosity::from_flags(quiet.verbose, quiet.quiet), Verbosity::Quiet);
+    assert!(crate::cli::Cli::try_parse_from(["birdnest", "-v", "-q", "list"]).is_err());
+}
+
+#[test]
+fn cli_reference_lists_public_commands_only() {
+    let reference = crate::manpage::reference();
+    let paths: Vec<&str> = reference.iter().map(|entry| entry.path.as_str()).collect();
+
+    assert_eq!(paths[0], "birdnest");
+    assert!(paths.contains(&"birdnest install"));
+    assert!(paths.contains(&"birdnest flatpak install"));
+    assert!(!paths.iter().any(|path| path.ends_with("-dialog")));
+    assert!(!paths.contains(&"birdnest help"));
+
+    let install = reference.iter().find(|entry| entry.path == "birdnest install").unwrap();
+    assert!(install.help.contains("--yes"));
+
+    // The man page lists the same commands
+    let page = crate::manpage::main_page().unwrap();
+    assert!(page.contains("birdnest\\-install(1)"));
+    assert!(!page.contains("dialog(1)") && !page.contains("birdnest\\-help(1)"));
+}
+
+#[test]
+fn repair_events_stream_into_maintenance_tab() {
+    let (mut gui, _) = mock_gui(Config::default());
+    gui.maintenance.repairing = Some(FlatpakInstallation::User);
+
+    let _ = gui.update(Message::FlatpakRepairEvent(maintenance::RepairEvent::Output("[1/3] Verifying".to_string())));
+    let _ = gui.update(Message::FlatpakRepairEvent(maintenance::RepairEvent::Finished {
+        before: Some(2_000_000),
+        after: Some(1_000_000),
+        error: None,
+    }));
+
+    assert_eq!(gui.maintenance.output, vec!["[1/3] Verifying".to_string()]);
+    assert!(gui.maintenance.repairing.is_none());
+    assert_eq!(gui.maintenance.usage.get(&FlatpakInstallation::User), Some(&Some(1_000_000)));
+    assert!(gui.maintenance.last_result.as_deref().unwrap().contains("freed"));
+}
+
+#[test]
+fn startup_items_list_on_the_maintenance_tab_and_switch_off() {
+    use birdnest_core::footprint::Footprint;
+    use birdnest_core::startup::{Kind, StartupItem};
+
+    let (mut gui, _) = mock_gui(Config::default());
+    let _ = gui.update(Message::TabChanged(Tab::Maintenance));
+    assert!(gui.maintenance.startup_loading);
+
+    let item = |kind, id: &str, name: &str, package: Option<&str>| StartupItem {
+        kind,
+        id: id.to_string(),
+        name: name.to_string(),
+        path: format!("/etc/xdg/autostart/{}", id).into(),
+        program: None,
+        enabled: true,
+        package: package.map(str::to_string),
+        footprint: Footprint::default(),
+    };
+    let _ = gui.update(Message::StartupLoaded(vec![
+        item(Kind::Service, "syncthing.service", "Syncthing", Some("syncthing")),
+        item(Kind::Autostart, "notes.desktop", "Notes", None),
+    ]));
+    assert!(!gui.maintenance.startup_loading);
+    let _ = gui.view(window::Id::MAIN);
+
+    let _ = gui.update(Message::ToggleStartup("syncthing.service".to_string(), false));
+    assert!(gui.maintenance.startup_switching.contains("syncthing.service"));
+    let _ = gui.view(window::Id::MAIN);
+    let _ = gui.update(Message::StartupToggled("syncthing.service".to_string(), false, Ok(())));
+    let _ = gui.update(Message::StartupToggled("notes.desktop".to_string(), false, Err("read-only".to_string())));
+    let enabled: Vec<bool> = gui.maintenance.startup.iter().flatten().map(|item| item.enabled).collect();
+    assert_eq!(enabled, [false, true]);
+    assert!(gui.maintenance.startup_switching.is_empty());
+}
+
+#[test]
+fn immutable_systems_open_on_flatpak() {
+    let backend = Arc::new(MockBackend::with_default_fixture());
+    let (gui, _) = BirdNestGUI::with_backend(Config::default(), backend.clone(), SystemKind::Abroot);
+    assert_eq!(gui.current_tab, Tab::Flatpak);
+
+    let (gui, _) = BirdNestGUI::with_backend(Config::default(), backend, SystemKind::Traditional);
+    assert_eq!(gui.current_tab, Tab::Search);
+}
+
+#[tokio::test]
+async fn updates_tab_selects_a_subset_to_upgrade() {
+    let (mut gui, backend) = mock_gui(Config::default());
+    let _ = gui.update(Message::LoadUpdates);
+    assert!(gui.updates.loading);
+
+    let mut entries = updates::load_updates(backend).await.unwrap();
+    entries.retain(|entry| !entry.is_flatpak);
+    entries.push(updates::UpdateInfo {
+        name: "org.mozilla.firefox".to_string(),
+        current_version: "128.0".to_string(),
+        new_version: "129.0".to_string(),
+        is_flatpak: true,
+    });
+    let _ = gui.update(Message::UpdatesLoaded(Ok(entries)));
+    assert!(!gui.updates.loading);
+    assert_eq!(gui.updates.entries.len(), 3);
+
+    let _ = gui.update(Message::SelectAllUpdates);
+    let _ = gui.update(Message::ToggleUpdate((false, "firefox".to_string())));
+    let (packages, flatpaks) = gui.updates.selection();
+    assert_eq!(packages, ["htop"]);
+    assert_eq!(flatpaks, ["org.mozilla.firefox"]);
+    let _ = gui.view(window::Id::MAIN);
+}
+
+#[tokio::test]
+async fn updates_show_what_changed_since_the_installed_version() {
+    use birdnest_core::appstream::{Catalog, Component, Release};
+    use changelog_view::Changelog;
+
+    let (mut gui, backend) = mock_gui(Config::default());
+    gui.current_tab = Tab::Updates;
+    let htop = updates::UpdateInfo { name: "htop".to_string(), current_version: "3.3.0-4".to_string(), new_version: "3.3.0-5".to_string(), is_flatpak: false };
+    gui.updates.set_entries(vec![
+        htop.clone(),
+        updates::UpdateInfo { name: "vim".to_string(), current_version: "2:9.1.0016-1".to_string(), new_version: "2:9.1.0016-2".to_string(), is_flatpak: false },
+        updates::UpdateInfo { name: "org.gimp.GIMP".to_string(), current_version: "2.10.36".to_string(), new_version: "2.10.38".to_string(), is_flatpak: true },
+    ]);
+    let release = |version: &str, urgency: &str| Release { version: version.to_string(), urgency: Some(urgency.to_string()), notes: vec!["Fixes".to_string()], ..Default::default() };
+    gui.flatpak_catalog = Arc::new(Catalog::new(vec![Component {
+        id: "org.gimp.GIMP".to_string(),
+        releases: vec![release("2.10.38", "high"), release("2.10.36", "low")],
+        ..Default::default()
+    }]));
+
+    // Expanding everything fills Flatpak notes from the catalog straight
+    // away and fetches apt changelogs one at a time, in list order
+    let _ = gui.update(Message::ExpandAllUpdates);
+    assert_eq!(gui.updates.expanded.len(), 3);
+    let key = |flatpak: bool, name: &str| (flatpak, name.to_string());
+    assert_eq!(gui.updates.changelogs.get(&key(false, "htop")), Some(&Changelog::Loading));
+    assert_eq!(gui.updates.changelogs.get(&key(false, "vim")), None);
+    let gimp = gui.updates.changelogs.get(&key(true, "org.gimp.GIMP")).unwrap();
+    assert!(matches!(gimp, Changelog::Loaded(entries) if entries.len() == 1));
+    let gimp_entry = gui.updates.entries[2].clone();
+    assert_eq!(updates::summary(&gimp_entry, Some(gimp)), "2.10.36 → 2.10.38, 1 new version, high urgency");
+    let _ = gui.view(window::Id::MAIN);
+
+    let result = changelog_view::load(backend, "htop".to_string(), Some("3.3.0-4".to_string())).await;
+    let _ = gui.update(Message::UpdateChangelogLoaded(key(false, "htop"), result));
+    let Some(Changelog::Loaded(entries)) = gui.updates.changelogs.get(&key(false, "htop")) else {
+        panic!("no changelog: {:?}", gui.updates.changelogs);
+    };
+    assert_eq!(entries[0].changes[0], "Fix the CPU meter on systems with offline cores.");
+    assert_eq!(gui.updates.changelogs.get(&key(false, "vim")), Some(&Changelog::Loading));
+
+    // A refresh listing a newer version drops the changelog cut for the old one
+    let _ = gui.update(Message::ToggleUpdateChangelog(key(false, "vim")));
+    gui.updates.set_entries(vec![updates::UpdateInfo { new_version: "3.3.0-6".to_string(), ..htop }]);
+    assert!(gui.updates.changelogs.is_empty());
+    assert_eq!(gui.updates.expanded.len(), 1);
+    let _ = gui.update(Message::UpdateChangelogLoaded(key(false, "vim"), Err("late".to_string())));
+    assert_eq!(gui.updates.changelogs.get(&key(false, "vim")), None);
+
+    let _ = gui.update(Message::ExpandAllUpdates);
+    assert!(gui.updates.expanded.is_empty());
+}
+
+#[test]
+fn pending_restarts_show_on_the_updates_tab() {
+    let (mut gui, _) = mock_gui(Config::default());
+    let _ = gui.update(Message::RestartStatusLoaded(RestartStatus {
+        services: vec!["cron.service".to_string()],
+        ..RestartStatus::default()
+    }));
+    assert_eq!(gui.updates.restart.status.services, ["cron.service"]);
+    assert!(!gui.updates.restart.status.needs_reboot());
+    gui.current_tab = Tab::Updates;
+    let _ = gui.view(window::Id::MAIN);
+}
+
+#[test]
+fn unticked_services_stay_unticked_after_a_recheck() {
+    let (mut gui, _) = mock_gui(Config::default());
+    let status = RestartStatus {
+        services: vec!["cron.service".to_string(), "ssh.service".to_string()],
+        ..RestartStatus::default()
+    };
+    let _ = gui.update(Message::RestartStatusLoaded(status.clone()));
+    let _ = gui.update(Message::ToggleRestartService("ssh.service".to_string()));
+    assert_eq!(gui.updates.restart.selection(), ["cron.service"]);
+
+    let _ = gui.update(Message::RestartStatusLoaded(status));
+    assert_eq!(gui.updates.restart.selection(), ["cron.service"]);
+}
+
+#[tokio::test]
+async fn command_output_streams_and_exit_status_decides_success() {
+    use command_stream::{Step, StreamEvent};
+    use futures::StreamExt;
+
+    let failing = Step::Command {
+        command: BackendCommand::new("sh", &["-c", "echo done; echo complete >&2; exit 3"], &[]),
+        elevated: false,
+    };
+    let events: Vec<StreamEvent> = command_stream::stream(vec![failing]).collect().await;
+    let lines: Vec<&str> = events
+        .iter()
+        .filter_map(|event| match event {
+            StreamEvent::Line(line) => Some(line.as_str()),
+            StreamEvent::Finished(_) => None,
+        })
+        .collect();
+    assert!(lines.contains(&"done") && lines.contains(&"complete"));
+    // Output that merely looks successful doesn't count
+    assert!(matches!(events.last(), Some(StreamEvent::Finished(Err(e))) if e.contains("code 3")));
+
+    let backend = Arc::new(MockBackend::with_default_fixture());
+    let installer = backend.clone();
+    let in_process = Step::InProcess(Arc::new(move || installer.install(&["btop".to_string()])));
+    let events: Vec<StreamEvent> = command_stream::stream(vec![in_process]).collect().await;
+    assert!(matches!(events.last(), Some(StreamEvent::Finished(Ok(())))));
+    assert_eq!(backend.transactions(), ["install btop"]);
+}
+
+#[test]
+fn remove_dialog_reports_conflicts_from_failed_runs() {
+    use remove_dialog::Message as RemoveMessage;
+    use command_stream::StreamEvent;
+    use iced::Application as _;
+
+    let mut dialog = RemoveDialog::new(vec!["libc6".to_string()], false);
+    let _ = dialog.update(RemoveMessage::ConfirmRemove);
+    assert!(dialog.is_removing);
+    let _ = dialog.update(RemoveMessage::RemovalOutput(StreamEvent::Line(
+        "The following packages have unmet dependencies:".to_string(),
+    )));
+    let _ = dialog.update(RemoveMessage::RemovalOutput(StreamEvent::Finished(Err(
+        "apt-get exited with code 100".to_string(),
+    ))));
+    assert!(!dialog.is_removing);
+    assert!(!dialog.is_complete);
+    assert!(dialog.conflict_message.is_some());
+}
+
+#[test]
+fn remove_dialog_progress_follows_apt_status_lines() {
+    use remove_dialog::Message as RemoveMessage;
+    use command_stream::StreamEvent;
+    use iced::Application as _;
+
+    let mut dialog = RemoveDialog::new(vec!["vlc".to_string()], false);
+    let _ = dialog.update(RemoveMessage::ConfirmRemove);
+    for line in ["pmstatus:vlc:40:Removing vlc (amd64)", "Removing vlc (3.0.20-3) ..."] {
+        let _ = dialog.update(RemoveMessage::RemovalOutput(StreamEvent::Line(line.to_string())));
+    }
+    assert_eq!(dialog.progress.packages()[0].fraction, 0.6);
+    assert_eq!(dialog.removal_progress, "Removing vlc (amd64)");
+    // Status lines drive the bars and stay out of the output
+    assert_eq!(dialog.terminal_output, "Removing vlc (3.0.20-3) ...\n");
+
+    let _ = dialog.update(RemoveMessage::RemovalOutput(StreamEvent::Finished(Ok(()))));
+    assert_eq!(dialog.progress.overall(), 1.0);
+    let _ = dialog.view();
+}
+
+#[test]
+fn upgrade_dialog_tracks_progress_per_package() {
+    use command_stream::StreamEvent;
+    use iced::Application as _;
+    use upgrade_dialog::{Message as UpgradeMessage, UpgradeDialog};
+
+    let mut dialog = UpgradeDialog::new(vec!["hello".to_string()], vec!["org.gnome.Calculator".to_string()]);
+    let _ = dialog.update(UpgradeMessage::Upgrade);
+    for line in ["pmstatus:hello:50:Unpacking hello (amd64)", "Setting up hello (2.10-3) ...", "Updating app/org.gnome.Calculator/x86_64/stable"] {
+        let _ = dialog.update(UpgradeMessage::UpgradeOutput(StreamEvent::Line(line.to_string())));
+    }
+    let fractions: Vec<f32> = dialog.progress.packages().iter().map(|p| p.fraction).collect();
+    assert_eq!(fractions, [0.5, 0.0]);
+    let _ = dialog.view();
+
+    let _ = dialog.update(UpgradeMessage::UpgradeOutput(StreamEvent::Finished(Ok(()))));
+    assert!(dialog.is_complete);
+    assert_eq!(dialog.progress.overall(), 1.0);
+}
+
+#[test]
+fn flatpak_filter_matches_name_and_app_id() {
+    let (mut gui, _) = mock_gui(Config::default());
+    let app = |name: &str, application: &str| FlatpakInfo {
+        name: name.to_string(),
+        description: String::new(),
+        version: String::new(),
+        application: application.to_string(),
+        remotes: Vec::new(),
+    };
+    gui.flatpak_apps = vec![app("Firefox", "org.mozilla.firefox"), app("Builder", "org.gnome.Builder")];
+
+    let _ = gui.update(Message::FlatpakInstalledFilterChanged("FIRE".to_string()));
+    let names: Vec<&str> = filter_flatpak_apps(&gui.flatpak_apps, &gui.flatpak_installed_filter)
+        .iter()
+        .map(|app| app.name.as_str())
+        .collect();
+    assert_eq!(names, ["Firefox"]);
+    assert_eq!(filter_flatpak_apps(&gui.flatpak_apps, "org.gnome").len(), 1);
+    assert_eq!(filter_flatpak_apps(&gui.flatpak_apps, "").len(), 2);
+
+    gui.current_tab = Tab::Flatpak;
+    let _ = gui.view(window::Id::MAIN);
+}
+
+#[test]
+fn dialogs_open_as_child_windows_and_refresh_lists_when_done() {
+    use command_stream::StreamEvent;
+    use install_dialog::Message as InstallMessage;
+    use remove_dialog::Message as RemoveMessage;
+
+    let (mut gui, _) = mock_gui(Config::default());
+    gui.selected_packages.insert("btop".to_string());
+    let _ = gui.update(Message::InstallSelected);
+    let install = gui.windows.ids()[0];
+    assert!(matches!(gui.windows.get(install), Some(Dialog::Install(d)) if d.window == install));
+    let _ = gui.view(install);
+
+    let install_message = |message| Message::Dialog(install, DialogMessage::Install(message));
+    let _ = gui.update(install_message(InstallMessage::InstallPackages));
+    assert!(!gui.updates.loading);
+    let _ = gui.update(install_message(InstallMessage::InstallationOutput(StreamEvent::Finished(Ok(())))));
+    // The transaction's ticket announces the end on the event bus
+    let _ = gui.update(Message::Bus(BusEvent::Finished { id: 1, result: Ok(()) }));
+    assert!(gui.installed_loading && gui.updates.loading);
+
+    // A failed removal swaps its window for the conflict dialog
+    gui.selected_installed.insert("libc6".to_string());
+    let _ = gui.update(Message::RemoveSelectedPackages);
+    let remove = gui.windows.ids().into_iter().find(|id| *id != install).unwrap();
+    let _ = gui.update(Message::Dialog(
+        remove,
+        DialogMessage::Remove(RemoveMessage::ConflictDetected("libc6 is essential".to_string())),
+    ));
+    let _ = gui.update(Message::WindowClosed(remove));
+    let conflicts = gui.windows.ids().into_iter().filter(|id| matches!(gui.windows.get(*id), Some(Dialog::Conflict(_))));
+    assert_eq!(conflicts.count(), 1);
+    assert!(gui.windows.get(remove).is_none());
+}
+
+#[test]
+fn trimmed_apps_go_to_the_remove_dialog_unless_they_take_a_meta_package() {
+    use birdnest_core::dpkg::InstalledPackage;
+    use birdnest_core::trim;
+    use trim_dialog::Message as TrimMessage;
+
+    let (mut gui, _) = mock_gui(Config::default());
+    let _ = gui.update(Message::TabChanged(Tab::Maintenance));
+    let _ = gui.view(window::Id::MAIN);
+    let _ = gui.update(Message::ShowTrim);
+    let id = gui.windows.ids()[0];
+    assert!(matches!(gui.windows.get(id), Some(Dialog::Trim(_))));
+
+    let installed: Vec<InstalledPackage> = ["thunderbird", "gnome-mines", "gnome-sudoku", "vim"]
+        .iter()
+        .map(|name| InstalledPackage { name: name.to_string(), installed_bytes: Some(5_000_000), ..InstalledPackage::default() })
+        .collect();
+    let mut offers = trim::installed_offers(&installed);
+    offers[0].protected_by = vec!["pika-gnome-desktop".to_string()];
+    let trim_message = |message| Message::Dialog(id, DialogMessage::Trim(message));
+    let _ = gui.update(trim_message(TrimMessage::Loaded(Ok(offers))));
+    let _ = gui.view(id);
+
+    for game in ["gnome-mines", "gnome-sudoku"] {
+        let _ = gui.update(trim_message(TrimMessage::Toggle(game.to_string(), true)));
+    }
+    let _ = gui.update(trim_message(TrimMessage::Remove));
+    assert!(matches!(gui.windows.get(id), Some(Dialog::Trim(d)) if d.checking));
+    // Together the picks would take the desktop's meta-package
+    let _ = gui.update(trim_message(TrimMessage::Checked(Ok(vec!["pika-games".to_string()]))));
+    assert!(matches!(gui.windows.get(id), Some(Dialog::Trim(d)) if d.blocked == ["pika-games"]));
+    let _ = gui.view(id);
+    assert_eq!(gui.windows.ids().len(), 1);
+
+    let _ = gui.update(trim_message(TrimMessage::Toggle("gnome-sudoku".to_string(), false)));
+    let _ = gui.update(trim_message(TrimMessage::Remove));
+    let _ = gui.update(trim_message(TrimMessage::Checked(Ok(Vec::new()))));
+    let _ = gui.update(Message::WindowClosed(id));
+    let removals: Vec<Vec<String>> = gui
+        .windows
+        .ids()
+        .into_iter()
+        .filter_map(|id| match gui.windows.get(id) {
+            Some(Dialog::Remove(d)) => Some(d.package_names.clone()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(removals, [vec!["gnome-mines".to_string()]]);
+}
+
+#[test]
+fn conflict_dialog_can_remove_the_dependents_too() {
+    use command_stream::StreamEvent;
+    use conflict_dialog::{ConflictDialog, Message as ConflictMessage, Resolution};
+    use iced::Application as _;
+
+    let backend = Arc::new(MockBackend::with_default_fixture());
+    let mut dialog = ConflictDialog::new(vec!["coreutils".to_string()], "git depends on coreutils".to_string(), String::new());
+    dialog.backend = backend.clone();
+    let (mut dialog, _) = <ConflictDialog as iced::Application>::new(dialog);
+    // The mock can't repair or mark packages, so only removing is offered, once the dependents are known
+    assert!(dialog.offered.is_empty());
+    let closure = birdnest_core::package_manager::removal_closure(backend.as_ref(), &dialog.package_names).unwrap();
+    let _ = dialog.update(ConflictMessage::ClosureLoaded(closure));
+    assert_eq!(dialog.offered, [Resolution::RemoveDependents]);
+    assert_eq!(dialog.dependents(), ["git"]);
+    let _ = dialog.view();
+
+    let _ = dialog.update(ConflictMessage::Resolve(Resolution::RemoveDependents));
+    assert_eq!(dialog.running, Some(Resolution::RemoveDependents));
+    let _ = dialog.update(ConflictMessage::ResolutionOutput(StreamEvent::Line("Removing git".to_string())));
+    let _ = dialog.update(ConflictMessage::ResolutionOutput(StreamEvent::Finished(Ok(()))));
+    assert!(dialog.is_resolved());
+    assert!(dialog.terminal_output.contains("Removing git"));
+
+    // Nothing is left to resolve after that
+    let _ = dialog.update(ConflictMessage::Resolve(Resolution::RemoveDependents));
+    assert!(dialog.running.is_none());
+    let _ = dialog.view();
+}
+
+#[test]
+fn picks_from_every_tab_apply_as_one_batch() {
+    use batch_dialog::Message as BatchMessage;
+    use command_stream::StreamEvent;
+    use iced::Application as _;
+
+    let (mut gui, _) = mock_gui(Config::default());
+    gui.selected_packages.insert("btop".to_string());
+    gui.selected_installed.insert("vim".to_string());
+    gui.selected_flatpak.insert("org.gimp.GIMP".to_string());
+    gui.pikman_search_results = vec![PackageInfo {
+        name: "yay-bin".to_string(),
+        description: String::new(),
+        version: String::new(),
+        size: String::new(),
+        installed_bytes: None,
+        arch: None,
+        provenance: None,
+        source: PackageSource::Aur,
+    }];
+    let _ = gui.update(Message::TogglePikmanPackage("yay-bin".to_string()));
+    let _ = gui.view(window::Id::MAIN);
+
+    // The Search tab's own button leaves the Flatpak for the Flatpak tab
+    let _ = gui.update(Message::InstallSelected);
+    assert!(matches!(gui.windows.get(gui.windows.ids()[0]), Some(Dialog::Install(d)) if d.package_names == ["btop"]));
+    assert_eq!(gui.selected_flatpak.len(), 1);
+
+    gui.selected_packages.insert("btop".to_string());
+    let _ = gui.update(Message::ReviewBatch);
+    assert!(gui.selected_flatpak.is_empty() && gui.selected_pikman.is_empty());
+    let id = gui.windows.ids().into_iter().find(|id| matches!(gui.windows.get(*id), Some(Dialog::Batch(_)))).unwrap();
+    let Some(Dialog::Batch(dialog)) = gui.windows.get(id) else { unreachable!() };
+    let labels: Vec<String> = dialog.groups.iter().map(batch::Group::label).collect();
+    assert_eq!(labels, ["Remove 1 system package", "Install 1 system package", "Install 1 Flatpak app", "Install 1 AUR package"]);
+    let _ = gui.view(id);
+
+    let mut dialog = BatchDialog::new(&gui.batch_items());
+    assert!(dialog.groups.is_empty());
+    dialog.groups = batch::groups(&[
+        batch::Item::new("btop", batch::Origin::System, batch::Action::Install),
+        batch::Item::new("yay-bin", batch::Origin::Pikman("aur"), batch::Action::Install),
+    ]);
+    let _ = dialog.update(BatchMessage::Apply);
+    assert_eq!((dialog.status(0).as_str(), dialog.status(1).as_str()), ("Running", "Waiting"));
+    // A failed group doesn't stop the next one
+    let _ = dialog.update(BatchMessage::StepOutput(StreamEvent::Finished(Err("apt exited with code 100".to_string()))));
+    assert_eq!(dialog.status(1), "Running");
+    let _ = dialog.update(BatchMessage::StepOutput(StreamEvent::Finished(Ok(()))));
+    assert!(dialog.is_complete);
+    assert_eq!(dialog.status(1), "Installed");
+    assert_eq!(dialog.summary(), "1 of 2 changes applied; Install 1 system package failed: apt exited with code 100");
+    let _ = dialog.view();
+}
+
+#[test]
+fn install_dialog_expands_the_dependencies_a_package_pulls_in() {
+    use install_dialog::Message as InstallMessage;
+
+    let (mut gui, _) = mock_gui(Config::default());
+    gui.selected_packages.insert("vlc".to_string());
+    let _ = gui.update(Message::InstallSelected);
+    let install = gui.windows.ids()[0];
+    let plan = birdnest_core::plan::parse_simulation(
+        "Inst libvlc5 (3.0.20-3 Debian:12/stable [amd64])\nInst vlc (3.0.20-3 Debian:12/stable [amd64])\n",
+    );
+    let install_message = |message| Message::Dialog(install, DialogMessage::Install(message));
+    let _ = gui.update(install_message(InstallMessage::PlanLoaded(Some(plan))));
+    let _ = gui.view(install);
+
+    let _ = gui.update(install_message(InstallMessage::ToggleDependencies));
+    let Some(Dialog::Install(dialog)) = gui.windows.get(install) else {
+        panic!("install dialog closed");
+    };
+    assert!(dialog.show_dependencies);
+    let plan = dialog.plan.as_ref().unwrap();
+    assert_eq!(plan.dependencies_line(&dialog.package_names).as_deref(), Some("1 additional dependency"));
+    let _ = gui.view(install);
+}
+
+#[test]
+fn install_dialogs_start_from_saved_options_and_pass_them_on() {
+    use install_dialog::Message as InstallMessage;
+    use command_stream::Step;
+
+    let mut config = Config::default();
+    config.install_options.flatpak_branch = Some("beta".to_string());
+    config.install_options.pikman_container = Some("arch".to_string());
+    let (mut gui, _) = mock_gui(config);
+    assert_eq!(gui.pikman_install_dialog(vec!["btop".to_string()]).options.pikman_args(), ["--name", "arch"]);
+
+    let _ = gui.open_dialog(Dialog::Install(Box::new(gui.install_dialog(vec!["org.gimp.GIMP".to_string()], true))));
+    let id = gui.windows.ids()[0];
+    let install_message = |message| Message::Dialog(id, DialogMessage::Install(message));
+    let detail = install_dialog::PackageDetail {
+        name: "org.gimp.GIMP".to_string(),
+        version: "2.10.38".to_string(),
+        description: "GNU Image Manipulation Program".to_string(),
+        size: "Unknown".to_string(),
+        is_flatpak: true,
+    };
+    let _ = gui.update(install_message(InstallMessage::PackageInfoLoaded(vec![detail])));
+    let _ = gui.update(install_message(InstallMessage::ToggleAdvanced));
+    let _ = gui.update(install_message(InstallMessage::FlatpakRemoteChanged(" flathub-beta ".to_string())));
+    let _ = gui.view(id);
+    let Some(Dialog::Install(dialog)) = gui.windows.get(id) else {
+        panic!("install dialog closed");
+    };
+    let commands: Vec<String> = dialog
+        .install_steps()
+        .iter()
+        .filter_map(|step| match step {
+            Step::Command { command, .. } => Some(command.display()),
+            Step::InProcess(_) => None,
+        })
+        .collect();
+    assert_eq!(commands, ["flatpak install -y --noninteractive flathub-beta org.gimp.GIMP//beta"]);
+
+    // A branch that would read as a flag stops the install before it starts
+    let _ = gui.update(install_message(InstallMessage::FlatpakBranchChanged("--user".to_string())));
+    let _ = gui.update(install_message(InstallMessage::InstallPackages));
+    assert!(matches!(gui.windows.get(id), Some(Dialog::Install(d)) if !d.is_installing && d.installation_progress.starts_with("Error")));
+
+    // Defaults saved from a dialog reach the main window like Settings do
+    let mut saved = Config::default();
+    saved.install_options.no_install_recommends = true;
+    let _ = gui.update(install_message(InstallMessage::DefaultsSaved(Ok(Box::new(saved)))));
+    assert!(gui.install_options.no_install_recommends);
+    assert!(gui.install_dialog(vec!["htop".to_string()], false).options.no_install_recommends);
+}
+
+#[test]
+fn install_dialog_shows_what_recommends_add_and_can_leave_them_out() {
+    use install_dialog::Message as InstallMessage;
+    use birdnest_core::plan::{PlannedPackage, TransactionPlan};
+
+    let mut config = Config::default();
+    config.install_options.no_install_recommends = true;
+    let (mut gui, _) = mock_gui(config);
+    let _ = gui.open_dialog(Dialog::Install(Box::new(gui.install_dialog(vec!["git".to_string()], false))));
+    let id = gui.windows.ids()[0];
+    let install_message = |message| Message::Dialog(id, DialogMessage::Install(message));
+    let package = |name: &str| PlannedPackage {
+        name: name.to_string(),
+        old_version: None,
+        new_version: Some("1.0".to_string()),
+        download_bytes: None,
+        installed_bytes: Some(2_000_000),
+    };
+    let plan = TransactionPlan { install: vec![package("git")], ..TransactionPlan::default() };
+    let _ = gui.update(install_message(InstallMessage::PlanLoaded(Some(plan))));
+    let _ = gui.update(install_message(InstallMessage::RecommendsLoaded(Some(vec![package("git-man"), package("less")]))));
+    let _ = gui.view(id);
+    let Some(Dialog::Install(dialog)) = gui.windows.get(id) else {
+        panic!("install dialog closed");
+    };
+    assert!(dialog.options.apt_args().contains(&"--no-install-recommends".to_string()));
+    assert_eq!(dialog.recommends.as_ref().map(Vec::len), Some(2));
+
+    // Turning recommends back on is just for this install
+    let _ = gui.update(install_message(InstallMessage::NoRecommendsToggled(false)));
+    let _ = gui.view(id);
+    assert!(matches!(gui.windows.get(id), Some(Dialog::Install(d)) if d.options.apt_args().is_empty()));
+    assert!(gui.install_options.no_install_recommends);
+}
+
+#[test]
+fn environment_dialog_creates_the_picked_template_with_the_chosen_exports() {
+    use birdnest_core::environments;
+    use command_stream::Step;
+    use environment_dialog::Message as EnvMessage;
+
+    let (mut gui, _) = mock_gui(Config::default());
+    let _ = gui.update(Message::ShowEnvironments);
+    let id = gui.windows.ids()[0];
+    let env_message = |message| Message::Dialog(id, DialogMessage::Environment(message));
+    let templates = environments::builtin();
+    let rust = templates.iter().position(|t| t.name == "Rust dev on Arch").unwrap();
+    let _ = gui.update(env_message(EnvMessage::TemplatesLoaded(templates)));
+    let _ = gui.update(env_message(EnvMessage::Select(rust)));
+    let _ = gui.update(env_message(EnvMessage::ContainerChanged("my rust".to_string())));
+    let _ = gui.update(env_message(EnvMessage::Create));
+    let _ = gui.view(id);
+    let Some(Dialog::Environment(dialog)) = gui.windows.get(id) else {
+        panic!("environment dialog closed");
+    };
+    assert!(!dialog.is_running);
+    assert!(matches!(&dialog.result, Some(Err(e)) if e.contains("one word")));
+
+    let _ = gui.update(env_message(EnvMessage::ContainerChanged("my-rust".to_string())));
+    let _ = gui.update(env_message(EnvMessage::ExportToggled("gdb".to_string(), false)));
+    let _ = gui.update(env_message(EnvMessage::Create));
+    let Some(Dialog::Environment(dialog)) = gui.windows.get(id) else {
+        panic!("environment dialog closed");
+    };
+    assert!(dialog.is_running);
+    let commands: Vec<String> = dialog
+        .steps()
+        .iter()
+        .filter_map(|step| match step {
+            Step::Command { command, elevated: false } => Some(command.display()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(commands.len(), 2);
+    assert_eq!(commands[0], "pikman init my-rust --manager arch");
+}
+
+#[test]
+fn flatpak_results_group_by_remote_and_install_from_the_picked_one() {
+    let (mut gui, _) = mock_gui(Config::default());
+    let output = "Kdenlive\tVideo editor\torg.kde.kdenlive\t24.08\tstable\tkdeapps\n\
+                  Firefox\tWeb browser\torg.mozilla.firefox\t129.0\tstable\tflathub,flathub-beta\n\
+                  Builder\tIDE\torg.gnome.Builder\t46.2\tstable\tflathub\n";
+    let _ = gui.update(Message::FlatpakSearchResults(parse_flatpak_search_output(output)));
+    let order: Vec<&str> = gui.flatpak_search_results.iter().map(|r| r.application.as_str()).collect();
+    assert_eq!(order, ["org.mozilla.firefox", "org.gnome.Builder", "org.kde.kdenlive"]);
+    let _ = gui.view(window::Id::MAIN);
+
+    let _ = gui.update(Message::FlatpakRemoteChosen {
+        application: "org.mozilla.firefox".to_string(),
+        remote: "flathub-beta".to_string(),
+    });
+    let dialog = gui.install_dialog(vec!["org.mozilla.firefox".to_string(), "org.kde.kdenlive".to_string()], true);
+    assert_eq!(dialog.flatpak_remotes["org.mozilla.firefox"], "flathub-beta");
+    assert_eq!(dialog.flatpak_remotes["org.kde.kdenlive"], "kdeapps");
+
+    // Runtimes the install pulls show with the totals before confirming
+    let _ = gui.open_dialog(Dialog::Install(Box::new(dialog)));
+    let id = gui.windows.ids()[0];
+    let runtime = |download| PulledRef { name: "org.kde.Platform/x86_64/6.7".to_string(), runtime: true, download, installed: download };
+    let size = InstallSize { refs: vec![runtime(Some(400_000_000)), runtime(None)] };
+    let _ = gui.update(Message::Dialog(id, DialogMessage::Install(install_dialog::Message::SizeLoaded(Some(size)))));
+    assert!(matches!(gui.windows.get(id), Some(Dialog::Install(d)) if d.flatpak_size.as_ref().unwrap().runtimes().count() == 2));
+    let _ = gui.view(id);
+}
+
+#[test]
+fn queued_runtime_downloads_report_and_never_overlap() {
+    let (mut gui, _) = mock_gui(Config::default());
+    let _ = gui.update(Message::PredownloadQueued(Ok(2)));
+    assert_eq!(gui.activity.last_info().unwrap(), "2 runtime(s) queued to download during 01:00-06:00");
+
+    // A tick while runtimes are still downloading doesn't start them again
+    gui.predownloading = true;
+    let _ = gui.update(Message::PredownloadTick);
+    let _ = gui.update(Message::PredownloadsFinished(Ok(1)));
+    assert!(!gui.predownloading);
+    assert_eq!(gui.activity.last_info().unwrap(), "Downloaded 1 runtime(s) ahead of their apps");
+
+    gui.selected_flatpak.insert("org.gnome.Builder".to_string());
+    gui.current_tab = Tab::Flatpak;
+    let _ = gui.view(window::Id::MAIN);
+}
+
+#[test]
+fn flatpakref_files_open_in_the_install_dialog_and_name_a_new_remote() {
+    let (mut gui, _) = mock_gui(Config::default());
+    let app = RefFile {
+        name: "org.gnome.Builder".to_string(),
+        url: "https://dl.flathub.org/repo/".to_string(),
+        suggested_remote: Some("flathub".to_string()),
+        ..Default::default()
+    };
+    let file = FlatpakFile::Ref { path: "/tmp/builder.flatpakref".into(), app };
+    let _ = gui.open_dialog(Dialog::Install(Box::new(InstallDialog::for_file(file))));
+    let id = gui.windows.ids()[0];
+    assert!(matches!(gui.windows.get(id), Some(Dialog::Install(d)) if d.package_names == ["org.gnome.Builder"] && d.is_flatpak));
+
+    let message = install_dialog::Message::NewRemoteChecked(Some("flathub".to_string()));
+    let _ = gui.update(Message::Dialog(id, DialogMessage::Install(message)));
+    assert!(matches!(gui.windows.get(id), Some(Dialog::Install(d)) if d.new_remote.as_deref() == Some("flathub")));
+    let _ = gui.view(id);
+}
+
+#[test]
+fn flatpak_bundles_install_by_drag_and_drop_and_export_from_the_row_menu() {
+    use clap::Parser;
+
+    let (mut gui, backend) = mock_gui(Config::default());
+    let dir = std::env::temp_dir().join(format!("birdnest-dropped-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let bundle = dir.join("org.gimp.GIMP.flatpak");
+    std::fs::write(&bundle, b"").unwrap();
+
+    let _ = gui.update(Message::FileDropped(bundle));
+    let id = gui.windows.ids()[0];
+    assert!(matches!(gui.windows.get(id), Some(Dialog::Install(d)) if d.package_names == ["org.gimp.GIMP.flatpak"] && d.is_flatpak));
+    // Anything else is only reported
+    let errors = gui.activity.error_count();
+    let _ = gui.update(Message::FileDropped(dir.join("notes.txt")));
+    assert_eq!(gui.activity.error_count(), errors + 1);
+    assert_eq!(gui.windows.ids().len(), 1);
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let app = RowTarget::flatpak("org.gimp.GIMP");
+    assert!(row_menu::actions(&app, true, backend.as_ref()).contains(&RowAction::ExportBundle));
+    assert!(!row_menu::actions(&app, false, backend.as_ref()).contains(&RowAction::ExportBundle));
+    let _ = gui.update(Message::RowAction(app, RowAction::ExportBundle));
+    assert_eq!(gui.activity.last_info().unwrap(), "Exporting org.gimp.GIMP to a bundle");
+
+    let cli = crate::cli::Cli::try_parse_from(["birdnest", "flatpak", "export", "org.gimp.GIMP", "gimp.flatpak"]).unwrap();
+    let Some(crate::cli::Commands::Flatpak { subcommand: crate::cli::FlatpakSubcommand::Export { app, file } }) = cli.command else {
+        unreachable!()
+    };
+    assert_eq!(app, "org.gimp.GIMP");
+    assert_eq!(file, Some(std::path::PathBuf::from("gimp.flatpak")));
+}
+
+#[tokio::test]
+async fn row_menu_offers_actions_for_the_row_and_lists_its_files() {
+    let (mut gui, backend) = mock_gui(Config::default());
+    let packages = load_installed_packages(backend.clone()).await;
+    let _ = gui.update(Message::InstalledPackagesLoaded(packages));
+
+    let htop = RowTarget::package("htop");
+    let _ = gui.update(Message::RowMenuToggled(htop.clone()));
+    assert!(gui.row_menu.is_open(&htop));
+    let actions = row_menu::actions(&htop, true, backend.as_ref());
+    assert!(actions.contains(&RowAction::Remove) && actions.contains(&RowAction::ShowFiles));
+    // The mock has no reinstall command, so it isn't offered
+    assert!(!actions.contains(&RowAction::Reinstall));
+    assert_eq!(row_menu::actions(&RowTarget::package("btop"), false, backend.as_ref())[0], RowAction::Install);
+    let _ = gui.view(window::Id::MAIN);
+
+    let _ = gui.update(Message::RowAction(htop.clone(), RowAction::ShowFiles));
+    let files = row_menu::files(backend.clone(), "htop".to_string()).await;
+    let _ = gui.update(Message::RowFilesLoaded(htop.clone(), files));
+    assert_eq!(gui.row_menu.files.as_ref().unwrap().as_ref().unwrap()[0], "/usr/bin/htop");
+
+    let _ = gui.update(Message::RowAction(htop, RowAction::CopyName));
+    assert!(gui.row_menu.open.is_none());
+    assert_eq!(gui.activity.last_info().unwrap(), "Copied htop to clipboard");
+}
+
+#[tokio::test]
+async fn typing_searches_the_index_and_drops_stale_results() {
+    let (mut gui, backend) = mock_gui(Config::default());
+    let index = build_search_index(backend).await.expect("mock backend lists its packages");
+    let _ = gui.update(Message::SearchIndexBuilt(Some(index.clone())));
+
+    let _ = gui.update(Message::SearchQueryChanged("gi".to_string()));
+    let stale = gui.search_generation;
+    let _ = gui.update(Message::SearchQueryChanged("git".to_string()));
+
+    let results = |generation, query: &str| {
+        let matches = index.search_filtered(query, 200, false, |_| true);
+        Message::InstantSearchResults(generation, matches.packages.into_iter().map(PackageInfo::from).collect(), matches.total)
+    };
+    let _ = gui.update(results(stale, "gi"));
+    assert!(gui.search_results.is_empty());
+    let _ = gui.update(results(gui.search_generation, "git"));
+    assert_eq!(names(&gui.search_results)[..3], ["git", "gitk", "git-lfs"]);
+
+    // Clearing the box clears the results
+    let _ = gui.update(Message::SearchQueryChanged(String::new()));
+    assert!(gui.search_results.is_empty());
+}
+
+#[tokio::test]
+async fn header_field_suggests_package_names_to_pick_with_the_keyboard() {
+    use list_nav::ListKey;
+
+    let (mut gui, backend) = mock_gui(Config::default());
+    let index = build_search_index(backend.clone()).await.expect("mock backend lists its packages");
+    let _ = gui.update(Message::SearchIndexBuilt(Some(index)));
+    let _ = gui.update(Message::InstalledPackagesLoaded(load_installed_packages(backend).await));
+
+    // Installed names come first, then the shortest available ones
+    let _ = gui.update(Message::HeaderQueryChanged("Gi".to_string()));
+    assert_eq!(gui.autocomplete.suggestions, ["git", "gitk", "git-lfs"]);
+    let _ = gui.update(Message::ListKey(ListKey::Down));
+    let _ = gui.update(Message::ListKey(ListKey::Down));
+    let _ = gui.view(window::Id::MAIN);
+    let _ = gui.update(Message::HeaderSearch);
+    assert_eq!(gui.global_search.query, "gitk");
+    assert!(!gui.autocomplete.is_open());
+
+    let _ = gui.update(Message::HeaderQueryChanged("fi".to_string()));
+    let _ = gui.update(Message::AcceptSuggestion);
+    assert_eq!(gui.global_search.query, "firefox");
+    assert_eq!(gui.autocomplete.suggestions, ["firefox", "firefox-esr"]);
+    let _ = gui.update(Message::DismissSuggestions);
+    assert!(!gui.autocomplete.is_open());
+
+    // The Installed tab only offers what is installed; one letter or a
+    // name typed out in full offers nothing
+    gui.current_tab = Tab::Installed;
+    let _ = gui.update(Message::HeaderQueryChanged("fi".to_string()));
+    assert_eq!(gui.autocomplete.suggestions, ["firefox"]);
+    let _ = gui.update(Message::HeaderQueryChanged("v".to_string()));
+    assert!(gui.autocomplete.suggestions.is_empty());
+    let _ = gui.update(Message::HeaderQueryChanged("firefox".to_string()));
+    assert!(gui.autocomplete.suggestions.is_empty());
+}
+
+#[test]
+fn header_search_drives_the_active_tab_and_offers_other_sources() {
+    let (mut gui, _backend) = mock_gui(Config::default());
+    gui.current_tab = Tab::Installed;
+    let _ = gui.update(Message::HeaderQueryChanged("vim".to_string()));
+    assert_eq!(gui.installed_search_query, "vim");
+    assert!(global_search::offered(Tab::Installed, None).is_empty());
+
+    // The query follows to the next tab, whose few results offer the rest
+    let _ = gui.update(Message::TabChanged(Tab::Flatpak));
+    assert_eq!(gui.flatpak_search_query, "vim");
+    let _ = gui.update(Message::FlatpakSearchResults(Vec::new()));
+    assert!(gui.global_search.searched);
+    assert_eq!(global_search::offered(Tab::Flatpak, None), [ExtraSource::Aur]);
+
+    let _ = gui.update(Message::SearchAlsoIn(ExtraSource::Aur));
+    assert!(gui.global_search.is_expanded(ExtraSource::Aur));
+    let found = vec![global_search::ExtraResult { name: "vim-git".to_string(), id: "vim-git".to_string(), description: String::new() }];
+    // Results for a query the user has since changed are dropped
+    let _ = gui.update(Message::ExtraResultsLoaded(ExtraSource::Aur, "vi".to_string(), Ok(found.clone())));
+    assert_eq!(gui.global_search.extras, [(ExtraSource::Aur, None)]);
+    let _ = gui.update(Message::ExtraResultsLoaded(ExtraSource::Aur, "vim".to_string(), Ok(found.clone())));
+    assert_eq!(gui.global_search.extras, [(ExtraSource::Aur, Some(Ok(found)))]);
+
+    // Typing again closes them
+    let _ = gui.update(Message::HeaderQueryChanged("vim-".to_string()));
+    assert!(gui.global_search.extras.is_empty());
+}
+
+#[tokio::test]
+async fn clicking_a_row_shows_its_details_beside_the_list() {
+    let (mut gui, backend) = mock_gui(Config::default());
+    let _ = gui.update(Message::ToggleInstalledPackage("htop".to_string()));
+    assert!(gui.details.is_showing("htop"));
+
+    // A slow answer for a row clicked earlier doesn't replace the current one
+    let _ = gui.update(Message::DetailsLoaded("vim".to_string(), Err("late".to_string())));
+    assert_eq!(gui.details.details, None);
+    let details = details_pane::load(backend.clone(), "htop".to_string()).await;
+    let _ = gui.update(Message::DetailsLoaded("htop".to_string(), details));
+    let details = gui.details.details.clone().unwrap().unwrap();
+    assert_eq!(details.homepage.as_deref(), Some("https://htop.dev/"));
+
+    // The changelog only downloads when asked for
+    assert_eq!(gui.details.changelog, changelog_view::Changelog::NotLoaded);
+    let _ = gui.update(Message::LoadChangelog);
+    assert_eq!(gui.details.changelog, changelog_view::Changelog::Loading);
+    let changelog = changelog_view::load(backend, "htop".to_string(), None).await;
+    let _ = gui.update(Message::ChangelogLoaded("htop".to_string(), changelog));
+    assert!(matches!(&gui.details.changelog, changelog_view::Changelog::Loaded(entries) if entries.len() == 2));
+
+    let _ = gui.update(Message::CloseDetails);
+    assert_eq!(gui.details.package, None);
+
+    let listing = r#"{"packagename": "htop", "screenshots": [{"small_image_url": "https://screenshots.debian.net/htop_small.png", "large_image_url": "https://screenshots.debian.net/htop_large.png"}]}"#;
+    assert_eq!(details_pane::screenshot_url(listing).as_deref(), Some("https://screenshots.debian.net/htop_small.png"));
+    assert_eq!(details_pane::screenshot_url(r#"{"screenshots": []}"#), None);
+}
+
+#[tokio::test]
+async fn details_of_an_installed_package_say_what_its_processes_use() {
+    use birdnest_core::footprint::Footprint;
+
+    let (mut gui, backend) = mock_gui(Config::default());
+    // Nothing to measure for a package that isn't installed
+    let _ = gui.update(Message::ToggleInstalledPackage("htop".to_string()));
+    assert!(matches!(gui.details.footprint, details_pane::Usage::Unknown));
+    let _ = gui.update(Message::CloseDetails);
+
+    let _ = gui.update(Message::InstalledPackagesLoaded(load_installed_packages(backend.clone()).await));
+    let _ = gui.update(Message::ToggleInstalledPackage("htop".to_string()));
+    assert!(matches!(gui.details.footprint, details_pane::Usage::Measuring));
+    let details = details_pane::load(backend.clone(), "htop".to_string()).await;
+    let _ = gui.update(Message::DetailsLoaded("htop".to_string(), details));
+    let _ = gui.view(window::Id::MAIN);
+
+    // A measurement for a package no longer shown is dropped
+    let _ = gui.update(Message::FootprintLoaded("vim".to_string(), Ok(Footprint::default())));
+    assert!(matches!(gui.details.footprint, details_pane::Usage::Measuring));
+    let running = Footprint { processes: 1, memory: 4_200_000, cpu: 2.0 };
+    let _ = gui.update(Message::FootprintLoaded("htop".to_string(), Ok(running)));
+    assert!(matches!(&gui.details.footprint, details_pane::Usage::Measured(Ok(footprint)) if footprint.summary() == "1 process using 4.2 MB of memory and 2% CPU"));
+    let _ = gui.view(window::Id::MAIN);
+
+    let _ = gui.update(Message::MeasureFootprint);
+    assert!(matches!(gui.details.footprint, details_pane::Usage::Measuring));
+    // Without an installed package there are no files to match processes on
+    assert!(details_pane::measure(backend, "emacs".to_string()).await.is_err());
+}
+
+#[tokio::test]
+async fn installs_by_other_tools_reload_the_installed_list() {
+    use birdnest_core::db_watch::{Change, Database};
+    use std::time::{Duration, SystemTime};
+    use transaction_queue::{ItemStatus, QueueItem};
+
+    let (mut gui, backend) = mock_gui(Config::default());
+    let _ = gui.update(Message::InstalledPackagesLoaded(load_installed_packages(backend.clone()).await));
+    assert!(!gui.installed_loading);
+
+    let _ = gui.update(Message::DatabaseChanged(Change { database: Database::Packages, at: SystemTime::now() }));
+    assert!(gui.installed_loading);
+    let _ = gui.update(Message::InstalledPackagesLoaded(load_installed_packages(backend.clone()).await));
+
+    // Already in lists reloaded since
+    let before = SystemTime::now() - Duration::from_secs(5);
+    let _ = gui.update(Message::RefreshLists);
+    let _ = gui.update(Message::InstalledPackagesLoaded(load_installed_packages(backend.clone()).await));
+    let _ = gui.update(Message::DatabaseChanged(Change { database: Database::Packages, at: before }));
+    assert!(!gui.installed_loading);
+
+    // BirdNest's own transaction reloads everything when it finishes
+    let running = QueueItem { id: 1, label: "Installing htop".to_string(), status: ItemStatus::Running };
+    let _ = gui.update(Message::QueueChanged(vec![running]));
+    let later = SystemTime::now() + Duration::from_secs(1);
+    let _ = gui.update(Message::DatabaseChanged(Change { database: Database::Packages, at: later }));
+    assert!(!gui.installed_loading);
+    let done = QueueItem { id: 1, label: "Installing htop".to_string(), status: ItemStatus::Done };
+    let _ = gui.update(Message::QueueChanged(vec![done]));
+    let _ = gui.update(Message::DatabaseChanged(Change { database: Database::Packages, at: later }));
+    assert!(gui.installed_loading);
+}
+
+#[tokio::test]
+async fn paused_downloads_stop_until_resumed() {
+    use command_stream::{ProcessHandle, Step, StreamEvent};
+    use futures::StreamExt;
+
+    let process = ProcessHandle::default();
+    let step = Step::Command {
+        command: BackendCommand::new("sh", &["-c", "echo $$; sleep 0.5"], &[]),
+        elevated: false,
+    };
+    let mut events = Box::pin(command_stream::pausable_stream(vec![step], process.clone()));
+    let pid = loop {
+        match events.next().await {
+            Some(StreamEvent::Line(line)) if !line.starts_with('$') => break line,
+            Some(_) => continue,
+            None => panic!("stream ended before the command printed its pid"),
+        }
+    };
+    let state = || std::fs::read_to_string(format!("/proc/{}/stat", pid)).unwrap().split_whitespace().nth(2).unwrap().to_string();
+
+    process.pause();
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    assert!(process.is_paused());
+    assert_eq!(state(), "T");
+
+    process.resume();
+    let rest: Vec<StreamEvent> = events.collect().await;
+    assert!(matches!(rest.last(), Some(StreamEvent::Finished(Ok(())))));
+}
+
+#[test]
+fn remove_dialog_can_take_what_the_removal_leaves_unused() {
+    use remove_dialog::Message as RemoveMessage;
+    use iced::Application as _;
+
+    let mut dialog = RemoveDialog::new(vec!["vlc".to_string()], false);
+    let follow_up = birdnest_core::plan::parse_simulation("Remv vlc-data [3.0.20-1]\nRemv libvlc5 [3.0.20-1]\n").remove;
+    let _ = dialog.update(RemoveMessage::FollowUpLoaded(Some(follow_up)));
+    assert_eq!(dialog.removing(), ["vlc"]);
+
+    let _ = dialog.update(RemoveMessage::RemoveFollowUpToggled(true));
+    assert_eq!(dialog.removing(), ["vlc", "vlc-data", "libvlc5"]);
+    let _ = dialog.update(RemoveMessage::RemoveFollowUpToggled(false));
+    assert_eq!(dialog.removing(), ["vlc"]);
+}
+
+#[tokio::test]
+async fn remove_dialog_warns_about_dependents_before_removing() {
+    use remove_dialog::Message as RemoveMessage;
+    use iced::Application as _;
+
+    let backend: Arc<dyn PackageBackend> = Arc::new(MockBackend::with_default_fixture());
+    let mut dialog = RemoveDialog::new(vec!["coreutils".to_string()], false);
+    let dependents = remove_dialog::load_dependents(backend.clone(), dialog.package_names.clone()).await;
+    let _ = dialog.update(RemoveMessage::DependentsLoaded(dependents));
+    assert_eq!(dialog.cascading(), ["git"]);
+
+    // Removing the dependent along with it leaves nothing to warn about
+    let both = vec!["coreutils".to_string(), "git".to_string()];
+    assert!(remove_dialog::load_dependents(backend, both).await.is_empty());
+}
+
+#[tokio::test]
+async fn transactions_wait_for_earlier_ones_in_order() {
+    use command_stream::{Step, StreamEvent};
+    use futures::StreamExt;
+    use transaction_queue::ItemStatus;
+
+    let step = |script: &str| Step::Command { command: BackendCommand::new("sh", &["-c", script], &[]), elevated: false };
+    let mut first = Box::pin(command_stream::stream(vec![step("sleep 0.3; echo first-done")]));
+    // The "$ command" header means it has its turn
+    assert!(matches!(first.next().await, Some(StreamEvent::Line(line)) if line.starts_with('$')));
+
+    let second: Vec<StreamEvent> = command_stream::stream(vec![step("echo second-done")]).collect().await;
+    let lines: Vec<&str> = second
+        .iter()
+        .filter_map(|event| match event {
+            StreamEvent::Line(line) => Some(line.as_str()),
+            StreamEvent::Finished(_) => None,
+        })
+        .collect();
+    assert!(lines[0].starts_with("Waiting for"));
+    assert!(lines.contains(&"second-done"));
+
+    // By the time the second one ran, the first had finished
+    let status = |label: &str| transaction_queue::items().into_iter().find(|item| item.label.contains(label)).map(|item| item.status);
+    assert_eq!(status("first-done"), Some(ItemStatus::Done));
+    assert_eq!(status("second-done"), Some(ItemStatus::Done));
+    let rest: Vec<StreamEvent> = first.collect().await;
+    assert!(matches!(rest.last(), Some(StreamEvent::Finished(Ok(())))));
+}
+
+#[test]
+fn appstream_metadata_names_and_illustrates_results() {
+    use birdnest_core::appstream::{Catalog, Component};
+
+    let (mut gui, _) = mock_gui(Config::default());
+    let htop = Component {
+        id: "htop.desktop".to_string(),
+        package: Some("htop".to_string()),
+        name: "Htop".to_string(),
+        screenshots: vec!["https://example.org/htop.png".to_string()],
+        categories: vec!["System".to_string(), "Monitor".to_string()],
+        ..Default::default()
+    };
+    let _ = gui.update(Message::AppCatalogsLoaded(Arc::new(Catalog::new(vec![htop])), Arc::default()));
+    let _ = gui.update(Message::SearchResults(vec![PackageInfo {
+        name: "htop".to_string(),
+        description: String::new(),
+        version: String::new(),
+        size: String::new(),
+        installed_bytes: None,
+        arch: None,
+        provenance: None,
+        source: PackageSource::Default,
+    }]));
+    let _ = gui.view(window::Id::MAIN);
+
+    // The details pane takes the app's name and categories from the catalog
+    let _ = gui.update(Message::TogglePackage("htop".to_string()));
+    let app = gui.details.app.clone().unwrap();
+    assert_eq!(app.name, "Htop");
+    assert_eq!(app.categories, ["System", "Monitor"]);
+    let _ = gui.view(window::Id::MAIN);
+
+    // Packages the catalog doesn't know keep their plain name
+    let _ = gui.update(Message::TogglePackage("vim".to_string()));
+    assert_eq!(gui.details.app, None);
+}
+
+#[test]
+fn windows_fit_scaled_small_screens() {
+    use iced::multi_window::Application as _;
+
+    // The install dialog's 750x800 is too tall for 1080p at 150%
+    let size = scaling::fit_scaled(iced::Size::new(750.0, 800.0), 1.0);
+    assert_eq!(size, iced::Size::new(750.0, scaling::SMALLEST_SCREEN.height));
+    let size = scaling::fit_scaled(iced::Size::new(600.0, 450.0), 1.25);
+    assert_eq!(size, iced::Size::new(750.0, 562.5));
+    assert!(InstallDialog::window_settings().min_size.unwrap().height <= scaling::SMALLEST_SCREEN.height);
+
+    let (gui, _) = mock_gui(Config { ui_scale: Some(1.25), ..Config::default() });
+    assert_eq!(gui.scale_factor(window::Id::MAIN), 1.25);
+    // Settings the layout can't take are held to what it can
+    let (gui, _) = mock_gui(Config { ui_scale: Some(10.0), ..Config::default() });
+    assert_eq!(gui.scale_factor(window::Id::MAIN), 2.0);
+}
+
+#[test]
+fn narrow_windows_collapse_the_tab_bar_into_a_menu() {
+    let (mut gui, _) = mock_gui(Config::default());
+    assert!(!gui.is_narrow());
+
+    // Half of a 1366px laptop screen
+    let _ = gui.update(Message::WindowResized(window::Id::MAIN, 683.0));
+    assert!(gui.is_narrow());
+    let _ = gui.update(Message::ToggleNavMenu);
+    assert!(gui.nav_open);
+    let _ = gui.update(Message::ToggleInstalledPackage("htop".to_string()));
+    for (_, tab) in TABS {
+        gui.current_tab = tab;
+        let _ = gui.view(window::Id::MAIN);
+    }
+
+    // Picking a tab closes the menu; dialogs resizing don't count
+    let _ = gui.update(Message::TabChanged(Tab::Flatpak));
+    assert!(!gui.nav_open);
+    let _ = gui.update(Message::WindowResized(window::Id::unique(), 1200.0));
+    assert!(gui.is_narrow());
+    let _ = gui.update(Message::ToggleNavMenu);
+    let _ = gui.update(Message::WindowResized(window::Id::MAIN, 1200.0));
+    assert!(!gui.is_narrow() && !gui.nav_open);
+}
+
+#[test]
+fn browse_tab_lists_category_apps_from_both_catalogs() {
+    use birdnest_core::appstream::{Catalog, Component};
+
+    let app = |id: &str, package: Option<&str>, name: &str, category: &str| Component {
+        id: id.to_string(),
+        package: package.map(str::to_string),
+        name: name.to_string(),
+        categories: vec![category.to_string()],
+        ..Default::default()
+    };
+    let system = Catalog::new(vec![
+        app("org.gimp.GIMP", Some("gimp"), "GIMP", "Graphics"),
+        app("htop.desktop", Some("htop"), "Htop", "System"),
+        // Nothing to install it with
+        app("org.example.Orphan", None, "Orphan", "Graphics"),
+    ]);
+    let flatpak = Catalog::new(vec![app("org.kde.krita", None, "Krita", "Graphics")]);
+
+    let (mut gui, _) = mock_gui(Config::default());
+    let _ = gui.update(Message::AppCatalogsLoaded(Arc::new(system), Arc::new(flatpak)));
+    let _ = gui.update(Message::TabChanged(Tab::Browse));
+    let graphics = browse::CATEGORIES.iter().position(|c| c.label == "Graphics").unwrap();
+    let _ = gui.update(Message::BrowseCategory(graphics));
+
+    let apps = browse::apps(&gui.app_catalog, &gui.flatpak_catalog, &browse::CATEGORIES[graphics], "");
+    let targets: Vec<_> = apps.iter().map(|app| app.target()).collect();
+    assert_eq!(targets, [RowTarget::package("gimp"), RowTarget::flatpak("org.kde.krita")]);
+    assert_eq!(browse::apps(&gui.app_catalog, &gui.flatpak_catalog, &browse::CATEGORIES[graphics], "kri").len(), 1);
+
+    let _ = gui.update(Message::BrowseDetails("gimp".to_string()));
+    assert!(gui.details.is_showing("gimp"));
+    let _ = gui.view(window::Id::MAIN);
+    // Switching category starts the new one from the top
+    let _ = gui.update(Message::BrowseShowMore);
+    let _ = gui.update(Message::BrowseCategory(0));
+    assert_eq!(gui.browse.shown, browse::PAGE);
+    assert_eq!(gui.details.package, None);
+}
+
+#[test]
+fn installed_flatpaks_open_a_permission_editor() {
+    use birdnest_core::flatpak::{AppPermissions, PermissionSet};
+    use permissions_dialog::Message as PermissionsMessage;
+
+    let (mut gui, backend) = mock_gui(Config::default());
+    let app = RowTarget::flatpak("org.mozilla.firefox");
+    assert!(row_menu::actions(&app, true, backend.as_ref()).contains(&RowAction::Permissions));
+    assert!(!row_menu::actions(&app, false, backend.as_ref()).contains(&RowAction::Permissions));
+    assert!(!row_menu::actions(&RowTarget::package("htop"), true, backend.as_ref()).contains(&RowAction::Permissions));
+
+    let _ = gui.update(Message::RowAction(app, RowAction::Permissions));
+    let id = gui.windows.ids()[0];
+    assert!(matches!(gui.windows.get(id), Some(Dialog::Permissions(d)) if d.app == "org.mozilla.firefox" && d.busy));
+
+    let permissions = AppPermissions {
+        granted: PermissionSet::parse("[Context]\nsockets=x11;wayland;\n"),
+        overrides: PermissionSet::parse("[Context]\nsockets=!x11;\n"),
+    };
+    let _ = gui.update(Message::Dialog(id, DialogMessage::Permissions(PermissionsMessage::Loaded(Ok(permissions)))));
+    assert!(matches!(gui.windows.get(id), Some(Dialog::Permissions(d)) if !d.busy && d.permissions.is_some()));
+    let _ = gui.view(id);
+}
+
+#[tokio::test]
+async fn transactions_report_on_the_event_bus() {
+    use command_stream::Step;
+    use futures::StreamExt;
+
+    let mut events = bus::subscribe();
+    let step = Step::Command { command: BackendCommand::new("sh", &["-c", "echo on-the-bus"], &[]), elevated: false };
+    let _: Vec<_> = command_stream::stream(vec![step]).collect().await;
+
+    // Other tests run transactions too, so follow this one by its ID
+    let received: Vec<BusEvent> = std::iter::from_fn(|| events.try_recv().ok()).collect();
+    let id = received
+        .iter()
+        .find_map(|event| match event {
+            BusEvent::TransactionStarted { id, label } if label.contains("on-the-bus") => Some(*id),
+            _ => None,
+        })
+        .unwrap();
+    assert!(received.contains(&BusEvent::Progress { id, line: "on-the-bus".to_string() }));
+    assert!(received.contains(&BusEvent::Finished { id, result: Ok(()) }));
+
+    let (mut gui, _) = mock_gui(Config::default());
+    gui.installed_loaded = true;
+    let _ = gui.update(Message::Bus(BusEvent::CacheInvalidated));
+    assert!(!gui.installed_loaded);
+}
+
+#[test]
+fn search_folds_the_same_app_from_other_sources_into_one_row() {
+    use global_search::{ExtraResult, InstallOption};
+
+    let (mut gui, _) = mock_gui(Config::default());
+    gui.search_results = ["firefox", "gnome-calculator", "htop"]
+        .into_iter()
+        .map(|name| PackageInfo {
+            name: name.to_string(),
+            description: String::new(),
+            version: "1.0".to_string(),
+            size: String::new(),
+            installed_bytes: None,
+            arch: None,
+            provenance: None,
+            source: PackageSource::Default,
+        })
+        .collect();
+    gui.app_catalog = Arc::new(birdnest_core::appstream::Catalog::new(vec![birdnest_core::appstream::Component {
+        id: "org.gnome.Calculator.desktop".to_string(),
+        package: Some("gnome-calculator".to_string()),
+        name: "Calculator".to_string(),
+        ..Default::default()
+    }]));
+    let result = |name: &str, id: &str| ExtraResult { name: name.to_string(), id: id.to_string(), description: String::new() };
+    gui.global_search.extras = vec![
+        (
+            ExtraSource::Flatpak,
+            Some(Ok(vec![
+                result("Firefox", "org.mozilla.firefox"),
+                result("Calculator", "org.gnome.Calculator"),
+                result("Shotwell", "org.gnome.Shotwell"),
+            ])),
+        ),
+        (ExtraSource::Aur, Some(Ok(vec![result("firefox-bin", "firefox-bin"), result("shotwell-git", "shotwell-git")]))),
+    ];
+
+    let merged = gui.merged_results();
+    let option = |source, id: &str| InstallOption { source, id: id.to_string() };
+    // By package name, and for the calculator by AppStream ID
+    assert_eq!(merged.for_tab[&0], [option(ExtraSource::Flatpak, "org.mozilla.firefox"), option(ExtraSource::Aur, "firefox-bin")]);
+    assert_eq!(merged.for_tab[&1], [option(ExtraSource::Flatpak, "org.gnome.Calculator")]);
+    assert!(!merged.for_tab.contains_key(&2));
+    // Shotwell isn't a Search result, so its Flatpak row offers the AUR too
+    assert_eq!(merged.listed.len(), 1);
+    assert_eq!(
+        merged.listed[&(ExtraSource::Flatpak, 2)],
+        [option(ExtraSource::Flatpak, "org.gnome.Shotwell"), option(ExtraSource::Aur, "shotwell-git")]
+    );
+    let _ = gui.view(window::Id::MAIN);
+}
+
+#[test]
+fn settings_from_config_toml_apply_at_startup_and_on_save() {
+    use birdnest_core::config::ThemePreference;
+    use settings_dialog::Message as SettingsMessage;
+
+    let config = Config { theme: ThemePreference::Light, border_radius: 8.0, default_tab: Some("updates".to_string()), ..Config::default() };
+    let (mut gui, _) = mock_gui(config.clone());
+    assert_eq!(gui.current_tab, Tab::Updates);
+    assert_eq!(gui.theme, AppTheme::Light);
+    assert_eq!(gui.border_radius, 8.0);
+    // A tab name config.toml doesn't know falls back to the usual one
+    let (gui_unknown, _) = mock_gui(Config { default_tab: Some("nowhere".to_string()), ..Config::default() });
+    assert_eq!(gui_unknown.current_tab, Tab::Search);
+
+    let _ = gui.update(Message::ShowSettings);
+    let id = gui.windows.ids()[0];
+    let settings = |message| Message::Dialog(id, DialogMessage::Settings(message));
+    let _ = gui.update(settings(SettingsMessage::Loaded(Ok(Box::new(config)))));
+    let _ = gui.update(settings(SettingsMessage::ThemeChanged(ThemePreference::Dark)));
+    let _ = gui.update(settings(SettingsMessage::BorderRadiusChanged(16.0)));
+    let _ = gui.view(id);
+
+    // The cache lifetime has to be a number before anything is written
+    let _ = gui.update(settings(SettingsMessage::CacheTtlChanged("a day".to_string())));
+    assert!(matches!(gui.windows.get(id), Some(Dialog::Settings(d)) if matches!(d.edited(), Some(Err(_)))));
+    let _ = gui.update(settings(SettingsMessage::CacheTtlChanged(String::new())));
+
+    // Once saved, the main window picks the new look up
+    assert_eq!(gui.theme, AppTheme::Light);
+    let _ = gui.update(settings(SettingsMessage::Saved(Ok(()))));
+    assert_eq!(gui.theme, AppTheme::Dark);
+    assert_eq!(gui.border_radius, 16.0);
+}
+
+#[test]
+fn settings_show_the_system_proxy_and_check_the_typed_one() {
+    use birdnest_core::proxy::{ProxyConfig, SystemProxy};
+    use settings_dialog::Message as SettingsMessage;
+
+    let (mut gui, _) = mock_gui(Config::default());
+    let _ = gui.update(Message::ShowSettings);
+    let id = gui.windows.ids()[0];
+    let settings = |message| Message::Dialog(id, DialogMessage::Settings(message));
+    let _ = gui.update(settings(SettingsMessage::Loaded(Ok(Box::default()))));
+    let apt = ProxyConfig { http: Some("http://apt-cache:3142".to_string()), https: None };
+    let _ = gui.update(settings(SettingsMessage::SystemProxyLoaded(SystemProxy { apt, environment: ProxyConfig::default() })));
+    let _ = gui.view(id);
+
+    // A host without a scheme isn't saved
+    let _ = gui.update(settings(SettingsMessage::ProxyHttpChanged("proxy.example.com:3128".to_string())));
+    assert!(matches!(gui.windows.get(id), Some(Dialog::Settings(d)) if matches!(d.edited(), Some(Err(_)))));
+
+    let _ = gui.update(settings(SettingsMessage::ProxyHttpChanged("http://proxy.example.com:3128".to_string())));
+    let Some(Dialog::Settings(dialog)) = gui.windows.get(id) else { unreachable!() };
+    let config = dialog.edited().unwrap().unwrap();
+    assert_eq!(config.proxy.http.as_deref(), Some("http://proxy.example.com:3128"));
+    assert_eq!(config.proxy.https, None);
+}
+
+#[test]
+fn deferred_removal_hides_gui_apps_and_lists_them_on_maintenance() {
+    use birdnest_core::deferred_removal::{self, DeferredRemoval};
+    use remove_dialog::Message as RemoveMessage;
+
+    let (mut gui, backend) = mock_gui(Config { deferred_removal_days: Some(7), ..Config::default() });
+    let _ = gui.update(Message::RowAction(RowTarget::package("htop"), RowAction::Remove));
+    let id = gui.windows.ids()[0];
+    assert!(matches!(gui.windows.get(id), Some(Dialog::Remove(d)) if d.defer_days == Some(7) && d.deferring().is_none()));
+
+    // htop ships a desktop file, so its removal waits; vim has none and goes straight away
+    let files = vec![("htop".to_string(), deferred_removal::app_desktop_files(backend.as_ref(), "htop", false))];
+    let _ = gui.update(Message::Dialog(id, DialogMessage::Remove(RemoveMessage::DesktopFilesLoaded(files))));
+    assert!(matches!(gui.windows.get(id), Some(Dialog::Remove(d)) if d.deferring() == Some(7)));
+    assert!(deferred_removal::app_desktop_files(backend.as_ref(), "vim", false).is_empty());
+
+    // Apps whose days are up are uninstalled once loaded; the rest wait on the Maintenance tab
+    let now = birdnest_core::utils::now();
+    let hidden = |name: &str, remove_after| DeferredRemoval { name: name.to_string(), flatpak: false, remove_after, hidden: Vec::new() };
+    let _ = gui.update(Message::DeferredLoaded(vec![hidden("htop", now + 3600)]));
+    assert!(!gui.maintenance.uninstalling_deferred);
+    assert_eq!(gui.maintenance.deferred.len(), 1);
+    let _ = gui.update(Message::TabChanged(Tab::Maintenance));
+    let _ = gui.view(window::Id::MAIN);
+
+    let _ = gui.update(Message::DeferredLoaded(vec![hidden("htop", now + 3600), hidden("vim", now - 60)]));
+    assert!(gui.maintenance.uninstalling_deferred);
+}
+
+#[test]
+fn updates_can_be_staged_for_the_next_shutdown() {
+    use birdnest_core::shutdown_updates::StagedUpdates;
+
+    // The mock can't download ahead, so there's no toggle to offer
+    let (mut gui, _) = mock_gui(Config::default());
+    assert!(!gui.updates.shutdown_supported);
+
+    gui.updates.shutdown_supported = true;
+    gui.updates.set_entries(vec![
+        updates::UpdateInfo { name: "htop".to_string(), current_version: "3.2".to_string(), new_version: "3.3".to_string(), is_flatpak: false },
+        updates::UpdateInfo { name: "org.gnome.Builder".to_string(), current_version: String::new(), new_version: String::new(), is_flatpak: true },
+    ]);
+    assert_eq!(gui.updates.pending(), (vec!["htop".to_string()], vec!["org.gnome.Builder".to_string()]));
+
+    let staged = StagedUpdates { packages: vec!["htop".to_string()], flatpaks: Vec::new() };
+    let _ = gui.update(Message::ShutdownUpdatesLoaded(Some(staged)));
+    let _ = gui.update(Message::TabChanged(Tab::Updates));
+    let _ = gui.view(window::Id::MAIN);
+
+    // Staging with a backend that can't download reports why instead of running anything
+    let errors = gui.activity.error_count();
+    let _ = gui.update(Message::ShutdownUpdatesToggled(true));
+    assert!(!gui.updates.shutdown_busy);
+    assert_eq!(gui.activity.error_count(), errors + 1);
+}
+
+#[test]
+fn status_bar_gathers_background_work_from_every_tab() {
+    use birdnest_core::update_check::UpdateCount;
+
+    let (mut gui, _) = mock_gui(Config::default());
+    assert_eq!(gui.status().tasks, ["Loading installed packages"]);
+    let _ = gui.update(Message::InstalledPackagesLoaded(Vec::new()));
+    assert_eq!(status_bar::tasks_line(&gui.status().tasks), None);
+
+    let _ = gui.update(Message::UpdateCheckTick);
+    let _ = gui.update(Message::BackgroundRefresh);
+    gui.transactions.push(transaction_queue::QueueItem {
+        id: 7,
+        label: "apt-get install -y btop".to_string(),
+        status: transaction_queue::ItemStatus::Running,
+    });
+    let tasks = gui.status().tasks;
+    assert_eq!(tasks, ["apt-get install -y btop", "Refreshing package lists", "Checking for updates"]);
+    assert_eq!(
+        status_bar::tasks_line(&tasks).as_deref(),
+        Some("apt-get install -y btop, Refreshing package lists (+1 more)...")
+    );
+
+    let _ = gui.update(Message::SourceHealthChecked(vec![
+        status_bar::SourceHealth { label: "mock".to_string(), available: true },
+        status_bar::SourceHealth { label: "pikman".to_string(), available: false },
+    ]));
+    let _ = gui.update(Message::UpdateCheckFinished(Ok(Some(UpdateCount { packages: 1, flatpaks: 0 }))));
+    assert_eq!(gui.status().health.len(), 2);
+    let _ = gui.view(window::Id::MAIN);
+}
+
+#[test]
+fn update_checks_badge_the_updates_tab() {
+    use birdnest_core::update_check::UpdateCount;
+
+    let (mut gui, _) = mock_gui(Config::default());
+    let _ = gui.update(Message::UpdateCheckTick);
+    assert!(gui.checking_updates);
+    // A second tick while one check runs waits for it
+    let _ = gui.update(Message::UpdateCheckTick);
+
+    let _ = gui.update(Message::UpdateCheckFinished(Ok(Some(UpdateCount { packages: 2, flatpaks: 1 }))));
+    assert!(!gui.checking_updates);
+    assert_eq!(gui.update_count.map(|c| c.total()), Some(3));
+    assert_eq!(gui.notified_updates, 3);
+    let _ = gui.view(window::Id::MAIN);
+
+    // Skipped by the schedule: the badge keeps the last count
+    let _ = gui.update(Message::UpdateCheckTick);
+    let _ = gui.update(Message::UpdateCheckFinished(Ok(None)));
+    assert_eq!(gui.update_count.map(|c| c.total()), Some(3));
+
+    // Loading the Updates tab recounts from its list
+    let _ = gui.update(Message::UpdatesLoaded(Ok(vec![updates::UpdateInfo {
+        name: "htop".to_string(),
+        current_version: "3.2".to_string(),
+        new_version: "3.3".to_string(),
+        is_flatpak: false,
+    }])));
+    assert_eq!(gui.update_count, Some(UpdateCount { packages: 1, flatpaks: 0 }));
+
+    let (mut off, _) = mock_gui(Config { update_check: birdnest_core::update_check::UpdateCheck { every_hours: 0, notify: false }, ..Config::default() });
+    let _ = off.update(Message::UpdateCheckTick);
+    assert!(!off.checking_updates);
+}
+
+#[test]
+fn hook_output_follows_the_transaction_output() {
+    use command_stream::StreamEvent;
+    use install_dialog::Message as InstallMessage;
+
+    let (mut gui, _) = mock_gui(Config::default());
+    gui.selected_packages.insert("btop".to_string());
+    let _ = gui.update(Message::InstallSelected);
+    let install = gui.windows.ids()[0];
+    let install_message = |message| Message::Dialog(install, DialogMessage::Install(message));
+    let _ = gui.update(install_message(InstallMessage::InstallPackages));
+    let _ = gui.update(install_message(InstallMessage::InstallationOutput(StreamEvent::Line("Setting up btop".to_string()))));
+    let _ = gui.update(install_message(InstallMessage::InstallationOutput(StreamEvent::Finished(Ok(())))));
+    let _ = gui.update(install_message(InstallMessage::HooksFinished("stowed\nHook `stow -R shell` finished\n".to_string())));
+
+    let Some(Dialog::Install(dialog)) = gui.windows.get(install) else { panic!("install window closed") };
+    assert!(dialog.is_complete);
+    assert_eq!(dialog.terminal_output, "Setting up btop\nstowed\nHook `stow -R shell` finished\n");
+}
+
+#[tokio::test]
+async fn installed_tab_sorts_by_size_and_maps_disk_usage() {
+    use birdnest_core::disk_usage::Usage;
+
+    let (mut gui, backend) = mock_gui(Config::default());
+    let packages = load_installed_packages(backend).await;
+    let _ = gui.update(Message::InstalledPackagesLoaded(packages));
+    let _ = gui.update(Message::InstalledSortChanged(SortOrder::Size));
+    let largest: Vec<&str> = gui.filtered_installed().iter().take(3).map(|p| p.name.as_str()).collect();
+    assert_eq!(largest, ["firefox", "git", "coreutils"]);
+    assert_eq!(gui.installed_packages[2].size, "262.1 MB");
+
+    let _ = gui.update(Message::ToggleDiskUsage);
+    assert!(gui.disk_usage.open);
+    assert_eq!(gui.status().tasks, ["Measuring Flatpak apps"]);
+    let gimp = Usage { name: "org.gimp.GIMP".to_string(), bytes: 400_000_000, flatpak: true };
+    let _ = gui.update(Message::FlatpakUsageLoaded(vec![gimp]));
+    let combined = disk_usage::combined(&gui.disk_usage, &gui.installed_packages);
+    assert_eq!(combined[0].name, "org.gimp.GIMP");
+    assert_eq!(combined.len(), 7);
+    gui.current_tab = Tab::Installed;
+    let _ = gui.view(window::Id::MAIN);
+
+    let _ = gui.update(Message::ToggleDiskUsage);
+    assert!(!gui.disk_usage.open);
+}
+
+#[test]
+fn remove_dialog_blocks_a_runtime_until_its_apps_go_too() {
+    use birdnest_core::package_manager::ReverseDependency;
+    use remove_dialog::Message as RemoveMessage;
+    use iced::Application as _;
+
+    let mut dialog = RemoveDialog::new(vec!["org.gnome.Platform".to_string()], true);
+    let app = |name: &str| ReverseDependency { name: name.to_string(), alternative: false };
+    let _ = dialog.update(RemoveMessage::DependentsLoaded(vec![(
+        "org.gnome.Platform".to_string(),
+        vec![app("org.gimp.GIMP"), app("org.gnome.Maps")],
+    )]));
+    assert!(dialog.blocked());
+    let _ = dialog.update(RemoveMessage::RemovePackages);
+    assert!(!dialog.show_confirmation);
+    let _ = dialog.view();
+
+    let _ = dialog.update(RemoveMessage::IncludeDependents);
+    assert_eq!(dialog.package_names, ["org.gimp.GIMP", "org.gnome.Maps", "org.gnome.Platform"]);
+    assert!(!dialog.blocked());
+    let _ = dialog.update(RemoveMessage::RemovePackages);
+    assert!(dialog.show_confirmation);
+}
+
+#[tokio::test]
+async fn held_packages_show_locked_and_are_left_out_of_upgrades() {
+    use clap::Parser;
+
+    let (mut gui, backend) = mock_gui(Config::default());
+    let held = updates::load_held(backend.clone()).await;
+    let _ = gui.update(Message::HeldLoaded(held));
+    assert!(gui.updates.held.contains("firefox"));
+
+    let mut entries = updates::load_updates(backend.clone()).await.unwrap();
+    entries.retain(|entry| !entry.is_flatpak);
+    let _ = gui.update(Message::UpdatesLoaded(Ok(entries)));
+    let _ = gui.update(Message::SelectAllUpdates);
+    assert_eq!(gui.updates.selection().0, ["htop"]);
+    assert_eq!(gui.updates.pending().0, ["htop"]);
+    gui.current_tab = Tab::Updates;
+    let _ = gui.view(window::Id::MAIN);
+
+    // The mock can't hold anything, so the lock only shows what's held
+    assert!(!gui.can_hold);
+    assert!(gui.update(Message::ToggleHold("vim".to_string())).actions().is_empty());
+    let _ = gui.update(Message::InstalledPackagesLoaded(load_installed_packages(backend).await));
+    gui.current_tab = Tab::Installed;
+    let _ = gui.view(window::Id::MAIN);
+
+    let cli = crate::cli::Cli::try_parse_from(["birdnest", "hold", "firefox", "vim"]).unwrap();
+    assert!(matches!(cli.command, Some(crate::cli::Commands::Hold { packages }) if packages.len() == 2));
+    assert!(crate::cli::Cli::try_parse_from(["birdnest", "unhold"]).is_err());
+}
+
+#[tokio::test]
+async fn typing_in_the_installed_list_jumps_to_matching_packages() {
+    use list_nav::{ListCursor, ListKey};
+    use std::time::{Duration, Instant};
+
+    let (mut gui, backend) = mock_gui(Config::default());
+    let packages = load_installed_packages(backend).await;
+    let _ = gui.update(Message::InstalledPackagesLoaded(packages));
+    gui.current_tab = Tab::Installed;
+
+    let _ = gui.update(Message::ListKey(ListKey::Char('H')));
+    assert_eq!(gui.installed_cursor.index, Some(4));
+    let _ = gui.update(Message::ListKey(ListKey::End));
+    assert_eq!(gui.installed_cursor.index, Some(5));
+    let _ = gui.update(Message::ListKey(ListKey::PageUp));
+    assert_eq!(gui.installed_cursor.index, Some(0));
+    let _ = gui.view(window::Id::MAIN);
+
+    // Keys typed while a dialog has focus are left alone
+    let _ = gui.update(Message::WindowFocused(window::Id::MAIN, false));
+    let _ = gui.update(Message::ListKey(ListKey::Char('v')));
+    assert_eq!(gui.installed_cursor.index, Some(0));
+    let _ = gui.update(Message::WindowFocused(window::Id::MAIN, true));
+    let _ = gui.update(Message::InstalledSearchQueryChanged("i".to_string()));
+    assert_eq!(gui.installed_cursor.index, None);
+
+    let names = ["gcc", "gdb", "git", "gzip", "vim"];
+    let start = Instant::now();
+    let mut cursor = ListCursor::default();
+    assert_eq!(cursor.handle(&ListKey::Char('g'), &names, start), Some(0));
+    assert_eq!(cursor.handle(&ListKey::Char('i'), &names, start), Some(2));
+    assert_eq!(cursor.handle(&ListKey::Char('x'), &names, start), None);
+    // A pause starts a new prefix, and the same letter again steps through its names
+    let later = start + Duration::from_secs(2);
+    assert_eq!(cursor.handle(&ListKey::Char('g'), &names, later), Some(0));
+    assert_eq!(cursor.handle(&ListKey::Char('g'), &names, later), Some(1));
+    assert_eq!(cursor.handle(&ListKey::Char('g'), &names, later), Some(2));
+
+    let ctrl_a = ListKey::from_key(&keyboard::Key::Character("a".into()), keyboard::Modifiers::CTRL);
+    assert_eq!(ctrl_a, None);
+}
+
+#[test]
+fn sources_tab_lists_entries_and_adds_a_pasted_line() {
+    use birdnest_core::repos::{self, Edit};
+    use clap::Parser;
+
+    let (mut gui, _) = mock_gui(Config::default());
+    let file = std::path::PathBuf::from("/etc/apt/sources.list.d/example.list");
+    let parsed = repos::parse(&file, "deb https://repo.example.com/apt stable main\n");
+    let _ = gui.update(Message::SourcesLoaded(Ok((parsed, Some("nest".to_string())))));
+    gui.current_tab = Tab::Sources;
+    let _ = gui.update(Message::ConfirmRemoveSource(Some((file.clone(), 0))));
+    let _ = gui.view(window::Id::MAIN);
+
+    // An empty suite falls back to the release's codename
+    let _ = gui.update(Message::SourceUriChanged("https://apt.example.org".to_string()));
+    assert_eq!(gui.sources.new_source().unwrap().suite, "nest");
+    let _ = gui.update(Message::SourceUriChanged("deb https://apt.example.org bookworm main contrib".to_string()));
+    let _ = gui.update(Message::AddSource);
+    assert!(gui.sources.busy && gui.sources.error.is_none());
+    assert_eq!(gui.sources.confirm_remove, None);
+    let _ = gui.update(Message::SourceEdited(Ok("Added https://apt.example.org".to_string())));
+    assert!(!gui.sources.busy && gui.sources.new_uri.is_empty());
+
+    let _ = gui.update(Message::SourceUriChanged("not a url".to_string()));
+    let _ = gui.update(Message::AddSource);
+    assert!(!gui.sources.busy && gui.sources.error.is_some());
+
+    let cli = crate::cli::Cli::try_parse_from(["birdnest", "sources", "disable", "/etc/apt/sources.list", "--component", "universe"]).unwrap();
+    let Some(crate::cli::Commands::Sources { subcommand }) = cli.command else { panic!("not a sources command") };
+    let edit = Edit::SetComponent { file: "/etc/apt/sources.list".into(), entry: 0, component: "universe".to_string(), enabled: false };
+    assert_eq!(crate::cli::sources_edit(subcommand).unwrap(), Some(edit));
+}
+
+#[test]
+fn sources_backups_restore_after_confirmation() {
+    use birdnest_core::repos::Edit;
+    use birdnest_core::sources_backup::Snapshot;
+    use clap::Parser;
+
+    let (mut gui, _) = mock_gui(Config::default());
+    let backup = Snapshot { id: "1714999500".to_string(), created: 1714999500, files: vec!["sources.list".into()] };
+    let _ = gui.update(Message::SourceBackupsLoaded(vec![backup]));
+    gui.current_tab = Tab::Sources;
+    let _ = gui.update(Message::ConfirmRestoreSources(Some("1714999500".to_string())));
+    let _ = gui.view(window::Id::MAIN);
+
+    let _ = gui.update(Message::EditSource(Edit::Restore { backup: "1714999500".to_string() }));
+    assert!(gui.sources.busy && gui.sources.confirm_restore.is_none());
+    let _ = gui.update(Message::SourceEdited(Ok("Restored the sources from backup 1714999500".to_string())));
+    assert!(!gui.sources.busy && gui.sources.loading);
+
+    let cli = crate::cli::Cli::try_parse_from(["birdnest", "sources", "restore", "1714999500"]).unwrap();
+    let Some(crate::cli::Commands::Sources { subcommand }) = cli.command else { panic!("not a sources command") };
+    assert_eq!(crate::cli::sources_edit(subcommand).unwrap(), Some(Edit::Restore { backup: "1714999500".to_string() }));
+}
+
+#[test]
+fn the_activity_drawer_keeps_the_session_with_times() {
+    let (mut gui, _) = mock_gui(Config::default());
+    let _ = gui.update(Message::OutputReceived("Installed htop".to_string()));
+    let _ = gui.update(Message::ErrorReceived("Failed to remove vim".to_string()));
+    assert_eq!(gui.activity.unseen_errors, 1);
+    let entry = &gui.activity.entries[0];
+    assert_eq!((entry.message.as_str(), entry.error, entry.time.len()), ("Installed htop", false, 8));
+
+    let _ = gui.update(Message::ToggleActivity);
+    assert!(gui.activity.open && gui.activity.unseen_errors == 0);
+    let _ = gui.view(window::Id::MAIN);
+    // Errors seen in the open drawer don't count as unseen
+    let _ = gui.update(Message::ErrorReceived("Failed again".to_string()));
+    assert_eq!((gui.activity.unseen_errors, gui.activity.error_count()), (0, 2));
+    let _ = gui.update(Message::ClearOutput);
+    assert!(gui.activity.entries.is_empty() && gui.activity.last_info().is_none());
+}
+
+#[test]
+fn ppas_are_added_and_removed_with_their_keys() {
+    use birdnest_core::ppa::Ppa;
+    use clap::Parser;
+
+    let (mut gui, _) = mock_gui(Config::default());
+    let file = std::path::PathBuf::from("/etc/apt/sources.list.d/mozillateam-ubuntu-ppa.sources");
+    let parsed = birdnest_core::repos::parse(&file, "Types: deb\nURIs: https://ppa.launchpadcontent.net/mozillateam/ppa/ubuntu\nSuites: noble\nComponents: main\n");
+    let _ = gui.update(Message::SourcesLoaded(Ok((parsed, Some("noble".to_string())))));
+    gui.current_tab = Tab::Sources;
+    let _ = gui.update(Message::ConfirmRemoveSource(Some((file, 0))));
+    let _ = gui.view(window::Id::MAIN);
+
+    let _ = gui.update(Message::SourceUriChanged("ppa:bad/".to_string()));
+    let _ = gui.update(Message::AddSource);
+    assert!(!gui.sources.busy && gui.sources.error.is_some());
+    let _ = gui.update(Message::SourceUriChanged("ppa:kisak/kisak-mesa".to_string()));
+    let _ = gui.update(Message::AddSource);
+    assert!(gui.sources.busy && gui.sources.error.is_none());
+
+    let cli = crate::cli::Cli::try_parse_from(["birdnest", "ppa", "add", "ppa:kisak/kisak-mesa", "--suite", "noble"]).unwrap();
+    let Some(crate::cli::Commands::Ppa { subcommand: crate::cli::PpaSubcommand::Add { ppa, suite } }) = cli.command else {
+        panic!("not a ppa add command")
+    };
+    assert_eq!((Ppa::parse(&ppa).unwrap().name.as_str(), suite.as_deref()), ("kisak-mesa", Some("noble")));
+}
+
+#[test]
+fn software_rendering_is_picked_for_drivers_that_draw_blank_windows() {
+    use crate::renderer::{choose, Gpu, Reason, Renderer};
+
+    let gpu = |drivers: &[&str]| Gpu { drivers: drivers.iter().map(|d| d.to_string()).collect(), render_node: true };
+    assert_eq!(choose(false, false, None, &gpu(&["amdgpu"])).renderer, Renderer::Gpu);
+    assert_eq!(choose(false, false, None, &gpu(&["nouveau"])).reason, Reason::Driver("nouveau".to_string()));
+    // Hybrid graphics render on the good card
+    assert_eq!(choose(false, false, None, &gpu(&["i915", "nouveau"])).renderer, Renderer::Gpu);
+    assert_eq!(choose(false, false, None, &Gpu::default()).reason, Reason::NoGpu);
+
+    assert_eq!(choose(true, false, None, &gpu(&["amdgpu"])).reason, Reason::Flag);
+    assert_eq!(choose(false, true, None, &gpu(&["amdgpu"])).reason, Reason::Config);
+    let from_env = choose(false, false, Some("wgpu"), &gpu(&["nouveau"]));
+    assert_eq!((from_env.renderer, from_env.reason.clone()), (Renderer::Gpu, Reason::Environment));
+    assert!(choose(true, false, None, &gpu(&[])).describe().contains("--software-rendering"));
+
+    let cli = <crate::cli::Cli as clap::Parser>::try_parse_from(["birdnest", "--software-rendering"]).unwrap();
+    assert!(cli.software_rendering && cli.command.is_none());
+}
+
+#[test]
+fn appimages_are_checked_and_removed_one_action_at_a_time() {
+    use birdnest_core::appimage::{AppImage, UpdateInfo, UpdateStatus};
+    use clap::Parser;
+
+    let config = Config { appimage_dirs: vec!["/opt/appimages".into()], ..Config::default() };
+    let (mut gui, _) = mock_gui(config);
+    assert_eq!(gui.appimages.directories.last(), Some(&std::path::PathBuf::from("/opt/appimages")));
+
+    let krita = std::path::PathBuf::from("/home/me/Applications/krita-5.2.2-x86_64.AppImage");
+    let appimage = |path: &std::path::PathBuf, update_info| AppImage {
+        path: path.clone(),
+        name: "krita".to_string(),
+        version: Some("5.2.2".to_string()),
+        size: 210_000_000,
+        update_info,
+        desktop_entry: None,
+    };
+    let obsidian = std::path::PathBuf::from("/opt/appimages/Obsidian-1.5.3.AppImage");
+    let info = UpdateInfo::parse("zsync|https://example.com/krita.zsync");
+    let _ = gui.update(Message::AppImagesLoaded(vec![appimage(&krita, info), appimage(&obsidian, None)]));
+    gui.current_tab = Tab::AppImage;
+    let _ = gui.view(window::Id::MAIN);
+
+    // Only AppImages that say where updates come from are checked
+    let _ = gui.update(Message::CheckAppImageUpdates);
+    assert!(gui.appimages.busy.len() == 1 && gui.appimages.busy.contains(&krita));
+    let _ = gui.update(Message::AppImageAction(krita.clone(), appimage::Action::Update));
+    assert_eq!(gui.appimages.busy.len(), 1);
+    let _ = gui.update(Message::AppImageDone(krita.clone(), appimage::Action::CheckUpdate, Ok(Some(UpdateStatus::Available(Some("5.2.3".to_string()))))));
+    assert!(gui.appimages.busy.is_empty() && !gui.appimages.loading);
+    assert_eq!(gui.activity.last_info(), Some("krita 5.2.3 is available"));
+    let _ = gui.view(window::Id::MAIN);
+
+    // Removing asks first, then rescans
+    let _ = gui.update(Message::ConfirmRemoveAppImage(Some(obsidian.clone())));
+    let _ = gui.view(window::Id::MAIN);
+    let _ = gui.update(Message::AppImageAction(obsidian.clone(), appimage::Action::Remove));
+    assert_eq!(gui.appimages.confirm_remove, None);
+    let _ = gui.update(Message::AppImageDone(obsidian, appimage::Action::Remove, Err("Permission denied".to_string())));
+    assert!(gui.appimages.error.as_deref().is_some_and(|e| e.contains("Permission denied")));
+    assert!(gui.appimages.loading);
+    let _ = gui.update(Message::AppImagesLoaded(vec![appimage(&krita, None)]));
+    assert_eq!(gui.appimages.updates.len(), 1);
+
+    let cli = crate::cli::Cli::try_parse_from(["birdnest", "appimage", "update", "krita"]).unwrap();
+    assert!(matches!(cli.command, Some(crate::cli::Commands::AppImage { subcommand: crate::cli::AppImageSubcommand::Update { .. } })));
+}
+
+#[test]
+fn all_sources_merges_matches_and_installs_from_the_picked_source() {
+    use all_sources::Hit;
+
+    let (mut gui, _) = mock_gui(Config::default());
+    let hit = |title: &str, name: &str, app_id: Option<&str>, source: PackageSource| Hit {
+        title: title.to_string(),
+        app_id: app_id.map(str::to_string),
+        package: PackageInfo {
+            name: name.to_string(),
+            description: format!("{} from {}", title, source.as_str()),
+            version: String::new(),
+            size: String::new(),
+            installed_bytes: None,
+            arch: None,
+            provenance: None,
+            source,
+        },
+    };
+
+    let _ = gui.update(Message::SearchQueryChanged("vim".to_string()));
+    let _ = gui.update(Message::ToggleAllSources(true));
+    assert_eq!(gui.all_sources.query, "vim");
+    assert_eq!(gui.all_sources.results.len(), all_sources::SOURCES.len());
+    assert!(gui.status().tasks.iter().any(|task| task == "Searching every source"));
+    gui.current_tab = Tab::Search;
+    let _ = gui.view(window::Id::MAIN);
+
+    // Results for an older query are dropped
+    let _ = gui.update(Message::AllSourcesLoaded(PackageSource::Aur, "vi".to_string(), Ok(vec![hit("vi", "vi", None, PackageSource::Aur)])));
+    assert_eq!(gui.all_sources.results[2], (PackageSource::Aur, None));
+
+    let _ = gui.update(Message::AllSourcesLoaded(
+        PackageSource::Aur,
+        "vim".to_string(),
+        Ok(vec![hit("vim-git", "vim-git", None, PackageSource::Aur), hit("neovim", "neovim", None, PackageSource::Aur)]),
+    ));
+    let _ = gui.update(Message::AllSourcesLoaded(
+        PackageSource::Flatpak,
+        "vim".to_string(),
+        Ok(vec![hit("Vim", "org.vim.Vim", Some("org.vim.Vim"), PackageSource::Flatpak)]),
+    ));
+    let _ = gui.update(Message::AllSourcesLoaded(PackageSource::Default, "vim".to_string(), Ok(vec![hit("vim", "vim", None, PackageSource::Default)])));
+    let _ = gui.update(Message::AllSourcesLoaded(PackageSource::Fedora, "vim".to_string(), Err("pikman is not installed".to_string())));
+    let _ = gui.update(Message::AllSourcesLoaded(PackageSource::Alpine, "vim".to_string(), Ok(Vec::new())));
+    assert!(!gui.all_sources.is_searching());
+
+    // vim, org.vim.Vim and vim-git are one app, listed in source order
+    let rows = gui.all_sources.rows();
+    assert_eq!(rows.len(), 2);
+    let sources: Vec<_> = rows[0].options.iter().map(|option| option.source.clone()).collect();
+    assert_eq!(sources, [PackageSource::Default, PackageSource::Flatpak, PackageSource::Aur]);
+    assert_eq!(rows[1].title, "neovim");
+    let _ = gui.view(window::Id::MAIN);
+
+    let _ = gui.update(Message::InstallFrom(rows[0].options[2].clone()));
+    let dialog = gui.windows.ids()[0];
+    assert!(matches!(
+        gui.windows.get(dialog),
+        Some(Dialog::PikmanInstall(d)) if d.package_names == ["vim-git"] && d.selected_distro == Some(pikman_install_dialog::DistroType::Aur)
+    ));
+}
+
+#[test]
+fn settings_restore_a_manifest_only_after_reviewing_it() {
+    use birdnest_core::package_manager::BackendCommand;
+    use clap::Parser;
+    use command_stream::StreamEvent;
+    use settings_dialog::Message as SettingsMessage;
+
+    let (mut gui, _) = mock_gui(Config::default());
+    let _ = gui.update(Message::ShowSettings);
+    let id = gui.windows.ids()[0];
+    let settings = |message| Message::Dialog(id, DialogMessage::Settings(message));
+    let _ = gui.update(settings(SettingsMessage::Loaded(Ok(Box::default()))));
+    let dialog = |gui: &BirdNestGUI| match gui.windows.get(id) {
+        Some(Dialog::Settings(d)) => (d.restore_steps.len(), d.restoring, d.backup_status.clone()),
+        _ => panic!("settings closed"),
+    };
+    assert!(matches!(gui.windows.get(id), Some(Dialog::Settings(d)) if d.manifest_path.ends_with("birdnest-manifest.toml")));
+
+    // Nothing runs until the planned commands are confirmed
+    let steps = vec![
+        BackendCommand::new("apt-get", &["install", "-y"], &["htop".to_string()]),
+        BackendCommand::as_user("pikman", &["install", "--aur", "-y"], &["yay-bin".to_string()]),
+    ];
+    let _ = gui.update(settings(SettingsMessage::RestorePlanned(Ok(steps))));
+    assert_eq!(dialog(&gui).0, 2);
+    assert!(!dialog(&gui).1);
+    let _ = gui.view(id);
+    let _ = gui.update(settings(SettingsMessage::ConfirmRestore));
+    assert!(dialog(&gui).1);
+    let _ = gui.update(settings(SettingsMessage::RestoreOutput(StreamEvent::Line("Setting up htop".to_string()))));
+    let _ = gui.update(settings(SettingsMessage::RestoreOutput(StreamEvent::Finished(Err("pikman exited with 1".to_string())))));
+    assert_eq!(dialog(&gui), (0, false, Some(Err("pikman exited with 1".to_string()))));
+
+    // A manifest with nothing missing has nothing to confirm
+    let _ = gui.update(settings(SettingsMessage::RestorePlanned(Ok(Vec::new()))));
+    let _ = gui.update(settings(SettingsMessage::ConfirmRestore));
+    assert!(!dialog(&gui).1);
+
+    let cli = crate::cli::Cli::try_parse_from(["birdnest", "import", "manifest.toml", "-y"]).unwrap();
+    assert!(matches!(cli.command, Some(crate::cli::Commands::Import { yes: true, .. })));
+    assert!(crate::cli::Cli::try_parse_from(["birdnest", "export", "-o", "manifest.toml"]).is_ok());
+}
+
+#[test]
+fn offline_bundles_export_packages_and_flatpak_apps() {
+    use crate::cli::{BundleSubcommand, Cli, Commands};
+    use clap::Parser;
+
+    let cli = Cli::try_parse_from(["birdnest", "bundle", "export", "/media/usb", "vlc", "--flatpak-app", "org.kde.krita"]).unwrap();
+    let Some(Commands::Bundle { subcommand: BundleSubcommand::Export { dir, packages, flatpak_apps } }) = cli.command else {
+        unreachable!()
+    };
+    assert_eq!(dir, std::path::PathBuf::from("/media/usb"));
+    assert_eq!(packages, ["vlc"]);
+    assert_eq!(flatpak_apps, ["org.kde.krita"]);
+    assert!(Cli::try_parse_from(["birdnest", "bundle", "import", "/media/usb", "-y"]).is_ok());
+    assert!(Cli::try_parse_from(["birdnest", "bundle", "import"]).is_err());
+}
+
+#[test]
+fn preloading_warms_the_flatpak_and_pikman_tabs() {
+    let (mut gui, _) = mock_gui(Config::default());
+    let _ = gui.update(Message::Preload);
+    assert!(gui.flatpak_loading);
+    assert!(gui.status().tasks.iter().any(|task| task == "Loading Flatpak apps"));
+    let _ = gui.update(Message::FlatpakAppsLoaded(vec![FlatpakInfo {
+        name: "Firefox".to_string(),
+        description: String::new(),
+        version: "128.0".to_string(),
+        application: "org.mozilla.firefox".to_string(),
+        remotes: Vec::new(),
+    }]));
+    assert!(gui.flatpak_loaded);
+
+    // Opening the tab refreshes the list but keeps showing the preloaded one
+    let _ = gui.update(Message::TabChanged(Tab::Flatpak));
+    assert!(gui.flatpak_loading && gui.flatpak_loaded);
+    let _ = gui.view(window::Id::MAIN);
+
+    // Each container's packages show under its source on the Pikman tab
+    gui.pikman_installed.insert("aur".to_string(), None);
+    assert!(gui.status().tasks.iter().any(|task| task == "Starting pikman containers"));
+    let _ = gui.update(Message::PikmanContainerLoaded("aur".to_string(), Ok(vec!["yay-bin".to_string()])));
+    let _ = gui.update(Message::PikmanContainerLoaded("fedora".to_string(), Err("no container".to_string())));
+    assert!(!gui.status().tasks.iter().any(|task| task == "Starting pikman containers"));
+    let _ = gui.update(Message::PikmanFilterChanged(Some("aur".to_string())));
+    let _ = gui.update(Message::TabChanged(Tab::Pikman));
+    let _ = gui.view(window::Id::MAIN);
+
+    // A failed preload goes quietly, and turning it off skips it
+    let (mut gui, _) = mock_gui(Config::default());
+    let _ = gui.update(Message::Preload);
+    let _ = gui.update(Message::FlatpakPreloadFailed("flatpak not found".to_string()));
+    assert!(!gui.flatpak_loading);
+    let preload = birdnest_core::config::Preload { flatpak: false, pikman: false, delay_secs: 0 };
+    let (mut off, _) = mock_gui(Config { preload, ..Config::default() });
+    let _ = off.update(Message::Preload);
+    assert!(!off.flatpak_loading && off.pikman_installed.is_empty());
+}
+
+#[test]
+fn searches_past_the_limit_say_how_many_matched_and_offer_filters() {
+    use settings_dialog::Message as SettingsMessage;
+
+    let package = |name: &str| PackageInfo {
+        name: name.to_string(),
+        description: "a library".to_string(),
+        version: String::new(),
+        size: String::new(),
+        installed_bytes: None,
+        arch: None,
+        provenance: None,
+        source: PackageSource::Default,
+    };
+    let (mut gui, _) = mock_gui(Config { search_result_limit: 3, ..Config::default() });
+    gui.search_query = "lib".to_string();
+    let found: Vec<PackageInfo> = ["libfoo", "libbar", "libbaz", "gimp", "libqux"].into_iter().map(package).collect();
+    let _ = gui.update(Message::SearchResults(found.clone()));
+    assert_eq!(search_page(&gui), ["libfoo", "libbar", "libbaz"]);
+    assert_eq!(gui.search_results.len(), 5);
+    assert_eq!(gui.search_filters.total, Some(5));
+    let _ = gui.view(window::Id::MAIN);
+
+    // Names only drops the package that matched on its description
+    let _ = gui.update(Message::SearchNamesOnly(true));
+    let _ = gui.update(Message::SearchResults(found.clone()));
+    assert_eq!(gui.search_filters.total, Some(4));
+    // Few enough results need no banner, though the filter stays on offer
+    let _ = gui.update(Message::SearchResults(found[..2].to_vec()));
+    assert_eq!(gui.search_filters.total, None);
+    assert!(gui.search_filters.is_active());
+    // Packages without an app in the category are left out
+    let _ = gui.update(Message::SearchCategoryChanged(Some(0)));
+    let _ = gui.update(Message::SearchResults(found));
+    assert!(gui.search_results.is_empty());
+    let _ = gui.view(window::Id::MAIN);
+
+    // 0, or an empty field in Settings, lists every result
+    let (mut unlimited, _) = mock_gui(Config { search_result_limit: 0, ..Config::default() });
+    assert_eq!(unlimited.search_limit, None);
+    let _ = unlimited.update(Message::ShowSettings);
+    let id = unlimited.windows.ids()[0];
+    let _ = unlimited.update(Message::Dialog(id, DialogMessage::Settings(SettingsMessage::Loaded(Ok(Box::default())))));
+    let _ = unlimited.update(Message::Dialog(id, DialogMessage::Settings(SettingsMessage::SearchLimitChanged(String::new()))));
+    assert!(matches!(unlimited.windows.get(id), Some(Dialog::Settings(d)) if matches!(d.edited(), Some(Ok(ref c)) if c.search_result_limit == 0)));
+}
+
+fn search_page(gui: &BirdNestGUI) -> Vec<&str> {
+    gui.search_page().iter().map(|&index| gui.search_results[index].name.as_str()).collect()
+}
+
+#[tokio::test]
+async fn search_and_installed_results_sort_and_page() {
+    let (mut gui, backend) = mock_gui(Config { search_result_limit: 2, ..Config::default() });
+    let _ = gui.update(Message::InstalledPackagesLoaded(load_installed_packages(backend).await));
+    let changed = [("vim", 300), ("git", 200), ("bash", 100)];
+    let _ = gui.update(Message::ChangeTimesLoaded(changed.into_iter().map(|(name, time)| (name.to_string(), time)).collect()));
+    let installed_page = |gui: &BirdNestGUI| gui.installed_page().iter().map(|pkg| pkg.name.clone()).collect::<Vec<_>>();
+
+    // Packages dpkg has no time for come last, by name
+    let _ = gui.update(Message::InstalledSortChanged(SortOrder::Updated));
+    assert_eq!(installed_page(&gui), ["vim", "git"]);
+    let _ = gui.update(Message::InstalledPage(1));
+    assert_eq!(installed_page(&gui), ["bash", "coreutils"]);
+    // Past the end shows the last page
+    let _ = gui.update(Message::InstalledPage(5));
+    assert_eq!(installed_page(&gui), ["firefox", "htop"]);
+    let _ = gui.view(window::Id::MAIN);
+    let _ = gui.update(Message::InstalledSortChanged(SortOrder::Name));
+    assert_eq!(installed_page(&gui), ["bash", "coreutils"]);
+
+    // Search results that aren't installed have no size to sort by
+    let found: Vec<PackageInfo> = ["zsh", "vim", "git"]
+        .into_iter()
+        .map(|name| PackageInfo {
+            name: name.to_string(),
+            description: String::new(),
+            version: String::new(),
+            size: String::new(),
+            installed_bytes: None,
+            arch: None,
+            provenance: None,
+            source: PackageSource::Default,
+        })
+        .collect();
+    let _ = gui.update(Message::TabChanged(Tab::Search));
+    let _ = gui.update(Message::SearchResults(found.clone()));
+    assert_eq!(search_page(&gui), ["zsh", "vim"]);
+    let _ = gui.update(Message::SearchSortChanged(SortOrder::Size));
+    assert_eq!(search_page(&gui), ["git", "vim"]);
+    let _ = gui.update(Message::SearchPage(1));
+    assert_eq!(search_page(&gui), ["zsh"]);
+    let _ = gui.view(window::Id::MAIN);
+    // A new search starts over on the first page, still by size
+    let _ = gui.update(Message::SearchResults(found));
+    assert_eq!(search_page(&gui), ["git", "vim"]);
+    assert_eq!(names(&gui.search_results), ["zsh", "vim", "git"]);
+}
+
+#[tokio::test]
+async fn installed_rows_get_descriptions_as_they_scroll_into_view() {
+    let installed: Vec<String> = (0..100)
+        .map(|i| format!(r#"{{ "name": "pkg{:03}", "version": "1.0", "description": "package number {}" }}"#, i, i))
+        .collect();
+    let backend = Arc::new(MockBackend::from_json(&format!(r#"{{ "installed": [{}] }}"#, installed.join(","))).unwrap());
+    let (mut gui, _) = BirdNestGUI::with_backend(Config::default(), backend.clone(), SystemKind::Traditional);
+    let _ = gui.update(Message::InstalledPackagesLoaded(load_installed_packages(backend.clone()).await));
+    assert!(gui.installed_packages.iter().all(|pkg| pkg.description.is_empty()));
+
+    // The first page was asked for on load, so only scrolling asks for more
+    assert!(gui.installed_descriptions.wanted(&gui.filtered_installed()).is_empty());
+    let first_page: Vec<String> = (0..descriptions::PAGE).map(|i| format!("pkg{:03}", i)).collect();
+    let _ = gui.update(Message::DescriptionsLoaded(descriptions::load(backend.clone(), first_page).await));
+    assert_eq!(gui.installed_packages[0].description, "package number 0");
+    assert!(gui.installed_packages[99].description.is_empty());
+
+    gui.installed_descriptions.offset = 1.0;
+    let last_page = gui.installed_descriptions.wanted(&gui.filtered_installed());
+    assert_eq!(last_page.len(), descriptions::PAGE);
+    assert_eq!(last_page.last().map(String::as_str), Some("pkg099"));
+    let _ = gui.update(Message::InstalledScrolled(1.0));
+    assert!(gui.installed_descriptions.wanted(&gui.filtered_installed()).is_empty());
+    let _ = gui.update(Message::DescriptionsLoaded(descriptions::load(backend, last_page).await));
+    assert_eq!(gui.installed_packages[99].description, "package number 99");
+    let _ = gui.view(window::Id::MAIN);
+}
+
+#[test]
+fn foreign_architecture_packages_can_be_listed_alone() {
+    let (mut gui, _) = mock_gui(Config::default());
+    let package = |name: &str, arch: &str, multi_arch: Option<&str>| {
+        PackageInfo::from(dpkg::InstalledPackage {
+            name: name.to_string(),
+            version: "1.0".to_string(),
+            arch: Some(dpkg::Arch { name: arch.to_string(), multi_arch: multi_arch.map(str::to_string) }),
+            ..dpkg::InstalledPackage::default()
+        })
+    };
+    let _ = gui.update(Message::InstalledPackagesLoaded(vec![
+        package("bash", "amd64", None),
+        package("libc6", "i386", Some("same")),
+        package("tzdata", "all", Some("foreign")),
+    ]));
+
+    let _ = gui.update(Message::ToggleForeignArch);
+    // Nothing counts as foreign until dpkg names the system's architecture
+    assert!(gui.filtered_installed().is_empty());
+    let _ = gui.update(Message::NativeArchLoaded(Some("amd64".to_string())));
+    let foreign: Vec<&str> = gui.filtered_installed().iter().map(|pkg| pkg.name.as_str()).collect();
+    assert_eq!(foreign, ["libc6"]);
+
+    let _ = gui.update(Message::ToggleInstalledArch);
+    assert!(gui.architectures.shown);
+    let _ = gui.view(window::Id::MAIN);
+}
+
+#[test]
+fn installed_rows_show_where_packages_came_from_and_filter_by_it() {
+    let (mut gui, _) = mock_gui(Config::default());
+    let package = |name: &str, description: &str, provenance: dpkg::Provenance| {
+        PackageInfo::from(dpkg::InstalledPackage {
+            name: name.to_string(),
+            version: "1.0".to_string(),
+            description: description.to_string(),
+            provenance,
+            ..dpkg::InstalledPackage::default()
+        })
+    };
+    let debian = Some("deb.debian.org/debian bookworm/main".to_string());
+    let _ = gui.update(Message::InstalledPackagesLoaded(vec![
+        package("bash", "GNU Bourne Again SHell", dpkg::Provenance { essential: true, auto_installed: false, origin: debian.clone() }),
+        package("libfoo1", "foo runtime library", dpkg::Provenance { essential: false, auto_installed: true, origin: debian }),
+        package("discord", "Chat for communities", dpkg::Provenance {
+            origin: Some(dpkg::LOCAL_ORIGIN.to_string()),
+            ..dpkg::Provenance::default()
+        }),
+    ]));
+    assert_eq!(gui.installed_packages[0].description, "GNU Bourne Again SHell");
+
+    let filtered = |gui: &BirdNestGUI| -> Vec<String> { gui.filtered_installed().iter().map(|pkg| pkg.name.clone()).collect() };
+    let _ = gui.update(Message::InstalledSearchQueryChanged("bookworm".to_string()));
+    assert_eq!(filtered(&gui), ["bash", "libfoo1"]);
+    let _ = gui.update(Message::InstalledSearchQueryChanged("essential".to_string()));
+    assert_eq!(filtered(&gui), ["bash"]);
+    let _ = gui.update(Message::InstalledSearchQueryChanged("automatically".to_string()));
+    assert_eq!(filtered(&gui), ["libfoo1"]);
+    let _ = gui.update(Message::InstalledSearchQueryChanged("local".to_string()));
+    assert_eq!(filtered(&gui), ["discord"]);
+    gui.current_tab = Tab::Installed;
+    let _ = gui.view(window::Id::MAIN);
+}
+
+#[test]
+fn pikman_commands_open_in_a_terminal_that_stays_open() {
+    let (mut gui, _) = mock_gui(Config { terminal: Some("konsole".to_string()), ..Config::default() });
+    assert_eq!(gui.terminal.as_deref(), Some("konsole"));
+    let _ = gui.update(Message::PikmanContainerNameChanged("arch".to_string()));
+    let _ = gui.update(Message::PikmanCommandChanged("uname -a".to_string()));
+    assert_eq!(gui.pikman_terminal.container, "arch");
+
+    assert_eq!(pikman_terminal::enter_command("arch"), ["pikman", "enter", "arch"]);
+    let run = pikman_terminal::run_command("arch", &["sh".to_string(), "-c".to_string(), "uname -a".to_string()]);
+    // The window waits for Enter once the command ends
+    assert_eq!(run[..2], ["sh", "-c"]);
+    assert_eq!(run[3..], ["sh", "pikman", "run", "arch", "sh", "-c", "uname -a"]);
+    let _ = gui.view(window::Id::MAIN);
+}
+
+#[test]
+fn exported_apps_wait_for_their_unexport_and_are_listed_again() {
+    use birdnest_core::pikman_exports::{ExportKind, ExportedApp};
+    let (mut gui, _) = mock_gui(Config::default());
+    let gdb = ExportedApp {
+        name: "gdb".to_string(),
+        container: "rust-dev".to_string(),
+        command: "/usr/bin/gdb".to_string(),
+        kind: ExportKind::Binary,
+        path: std::path::PathBuf::from("/home/me/.local/bin/gdb"),
+    };
+    let _ = gui.update(Message::PikmanExportsLoaded(vec![gdb.clone()]));
+
+    let _ = gui.update(Message::PikmanUnexport(gdb.clone()));
+    assert_eq!(gui.pikman_exports.removing.as_ref(), Some(&gdb.path));
+    let _ = gui.view(window::Id::MAIN);
+    let _ = gui.update(Message::PikmanExportFinished(Err("pikman didn't remove /home/me/.local/bin/gdb".to_string())));
+    assert_eq!(gui.pikman_exports.removing, None);
+    assert_eq!(gui.activity.error_count(), 1);
+
+    let _ = gui.update(Message::PikmanExportPackageChanged("gimp".to_string()));
+    let _ = gui.update(Message::PikmanExportDistroChanged("fedora"));
+    let _ = gui.update(Message::PikmanExport { package: "gimp".to_string(), distro: "fedora" });
+    assert!(gui.pikman_exports.busy);
+    let _ = gui.update(Message::PikmanExportFinished(Ok("Exported gimp from the Fedora container".to_string())));
+    assert!(!gui.pikman_exports.busy);
+    assert!(gui.pikman_exports.package.is_empty());
+}
+
+#[test]
+fn digest_dialog_reloads_for_another_period() {
+    use digest_dialog::Message as DigestMessage;
+    use iced::Application as _;
+
+    let mut dialog = DigestDialog::new();
+    let history = "Start-Date: 2024-05-06  12:45:00\nCommandline: apt install vlc\nInstall: vlc:amd64 (3.0.20-3)\n";
+    let digest = birdnest_core::digest::build(history, "", Vec::new(), "2024-05-01", 0);
+    let _ = dialog.update(DigestMessage::Loaded(digest));
+    assert_eq!(dialog.digest.as_ref().map(|d| d.changes.len()), Some(1));
+    let _ = dialog.view();
+
+    let _ = dialog.update(DigestMessage::SetDays(30));
+    assert_eq!(dialog.days, 30);
+    assert!(dialog.digest.is_none());
+    let _ = dialog.view();
+}
+
+#[test]
+fn finished_transactions_are_announced_only_while_birdnest_is_in_the_background() {
+    let (mut gui, _) = mock_gui(Config::default());
+    gui.notifiers = Notifiers::new(Vec::new());
+    let _ = gui.update(Message::Bus(BusEvent::Finished { id: 1, result: Ok(()) }));
+    assert!(!gui.announced);
+
+    // Focus moving between BirdNest's own windows isn't leaving
+    let _ = gui.update(Message::WindowFocused(window::Id::MAIN, false));
+    let _ = gui.update(Message::WindowFocused(window::Id::unique(), true));
+    let _ = gui.update(Message::Bus(BusEvent::Finished { id: 2, result: Ok(()) }));
+    assert!(!gui.announced);
+
+    gui.focused_window = None;
+    let _ = gui.update(Message::Bus(BusEvent::Finished { id: 3, result: Err("Stopped".to_string()) }));
+    assert!(gui.announced);
+    let _ = gui.update(Message::WindowFocused(window::Id::MAIN, true));
+    assert!(!gui.announced);
+
+    let mut config = Config::default();
+    config.notifications.on_finish = false;
+    gui.apply_settings(&config);
+    gui.focused_window = None;
+    let _ = gui.update(Message::Bus(BusEvent::Finished { id: 4, result: Ok(()) }));
+    assert!(!gui.announced);
+}
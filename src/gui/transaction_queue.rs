@@ -0,0 +1,206 @@
+use iced::{
+    alignment, Color,
+    widget::{button, column, container, row, text, Column},
+    Element, Length, Padding, Subscription,
+};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{LazyLock, Mutex};
+use tokio::sync::watch;
+
+use birdnest_core::events::{self, Event};
+
+use crate::gui::theme::Theme as AppTheme;
+use crate::gui::styles::{RoundedButtonStyle, RoundedContainerStyle};
+use crate::gui::Message;
+
+// apt and dpkg take a lock for the whole transaction, so two dialogs running
+// at once would fail on it; every transaction in this process waits its
+// turn here. tokio's Mutex wakes waiters in the order they arrived.
+static TURN: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+static ITEMS: Mutex<Vec<QueueItem>> = Mutex::new(Vec::new());
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+static CHANGES: LazyLock<watch::Sender<()>> = LazyLock::new(|| watch::Sender::new(()));
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ItemStatus {
+    Queued,
+    Running,
+    Done,
+    Failed(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueueItem {
+    pub id: u64,
+    pub label: String,
+    pub status: ItemStatus,
+}
+
+impl QueueItem {
+    pub fn is_finished(&self) -> bool {
+        matches!(self.status, ItemStatus::Done | ItemStatus::Failed(_))
+    }
+}
+
+/// A transaction's place in the queue. Dropping it before [`finish`](Self::finish)
+/// takes it off the queue if it never ran, or marks it stopped.
+#[derive(Debug)]
+pub struct Ticket {
+    id: u64,
+    label: String,
+}
+
+/// Queues a transaction described by `label`
+pub fn join(label: String) -> Ticket {
+    let id = NEXT_ID.fetch_add(1, Ordering::SeqCst);
+    tracing::debug!("transaction_queue: Queued #{} {}", id, label);
+    ITEMS.lock().unwrap().push(QueueItem { id, label: label.clone(), status: ItemStatus::Queued });
+    changed();
+    Ticket { id, label }
+}
+
+impl Ticket {
+    /// The transaction's ID in the queue and on the event bus
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Unfinished transactions queued before this one
+    pub fn ahead(&self) -> usize {
+        ITEMS
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|item| item.id < self.id && !item.is_finished())
+            .count()
+    }
+
+    /// Waits for every earlier transaction to finish; the turn lasts as long
+    /// as the returned guard
+    pub async fn turn(&self) -> tokio::sync::MutexGuard<'static, ()> {
+        let guard = TURN.lock().await;
+        self.set_status(ItemStatus::Running);
+        events::publish(Event::TransactionStarted { id: self.id, label: self.label.clone() });
+        guard
+    }
+
+    pub fn finish(self, result: &Result<(), String>) {
+        self.set_status(match result {
+            Ok(()) => ItemStatus::Done,
+            Err(e) => ItemStatus::Failed(e.clone()),
+        });
+        events::publish(Event::Finished { id: self.id, result: result.clone() });
+    }
+
+    fn set_status(&self, status: ItemStatus) {
+        if let Some(item) = ITEMS.lock().unwrap().iter_mut().find(|item| item.id == self.id) {
+            item.status = status;
+        }
+        changed();
+    }
+}
+
+impl Drop for Ticket {
+    fn drop(&mut self) {
+        let mut items = ITEMS.lock().unwrap();
+        match items.iter().position(|item| item.id == self.id) {
+            Some(index) if items[index].status == ItemStatus::Queued => {
+                items.remove(index);
+            }
+            Some(index) if items[index].status == ItemStatus::Running => {
+                items[index].status = ItemStatus::Failed("Stopped".to_string());
+                events::publish(Event::Finished { id: self.id, result: Err("Stopped".to_string()) });
+            }
+            _ => return,
+        }
+        drop(items);
+        changed();
+    }
+}
+
+/// What transaction `id` was queued as, while the queue still lists it
+pub fn label(id: u64) -> Option<String> {
+    ITEMS.lock().unwrap().iter().find(|item| item.id == id).map(|item| item.label.clone())
+}
+
+/// Runs `work` once every transaction queued before it has finished
+pub async fn serialized(label: String, work: impl std::future::Future<Output = Result<String, String>>) -> Result<String, String> {
+    let ticket = join(label);
+    let turn = ticket.turn().await;
+    let result = work.await;
+    ticket.finish(&result.as_ref().map(|_| ()).map_err(Clone::clone));
+    drop(turn);
+    result
+}
+
+pub fn items() -> Vec<QueueItem> {
+    ITEMS.lock().unwrap().clone()
+}
+
+pub fn clear_finished() {
+    ITEMS.lock().unwrap().retain(|item| !item.is_finished());
+    changed();
+}
+
+fn changed() {
+    CHANGES.send_replace(());
+}
+
+/// The queue's items every time they change
+pub fn subscription() -> Subscription<Vec<QueueItem>> {
+    iced::subscription::unfold("transaction-queue", CHANGES.subscribe(), |mut changes| async move {
+        // The sender is a static, so this never ends
+        let _ = changes.changed().await;
+        (items(), changes)
+    })
+}
+
+pub fn view<'a>(items: &'a [QueueItem], theme: AppTheme, radius: f32) -> Element<'a, Message> {
+    if items.is_empty() {
+        return column![].into();
+    }
+
+    let mut list = Column::new().spacing(4);
+    for item in items {
+        let (status, color) = match &item.status {
+            ItemStatus::Queued => ("Queued".to_string(), theme.secondary_text()),
+            ItemStatus::Running => ("Running".to_string(), theme.primary()),
+            ItemStatus::Done => ("Done".to_string(), Color::from_rgb(0.4, 0.8, 0.4)),
+            ItemStatus::Failed(e) => (format!("Failed: {}", e), theme.danger()),
+        };
+        list = list.push(
+            row![
+                text(&item.label).size(13).style(iced::theme::Text::Color(theme.text())).width(Length::Fill),
+                text(status).size(13).style(iced::theme::Text::Color(color)),
+            ]
+            .spacing(10),
+        );
+    }
+
+    let mut header = row![text("Transactions").size(15).style(iced::theme::Text::Color(theme.primary())).width(Length::Fill)]
+        .align_items(alignment::Alignment::Center);
+    if items.iter().any(QueueItem::is_finished) {
+        header = header.push(
+            button(text("Clear finished").size(12))
+                .on_press(Message::ClearFinishedTransactions)
+                .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                    is_primary: false,
+                    radius,
+                    primary_color: theme.primary(),
+                    text_color: Color::WHITE,
+                    background_color: theme.background(),
+                })))
+                .padding(Padding::new(6.0)),
+        );
+    }
+
+    container(column![header, list].spacing(6))
+        .width(Length::Fill)
+        .padding(Padding::new(12.0))
+        .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+            radius,
+            background: Some(theme.card_background()),
+            elevation: 1.0,
+        })))
+        .into()
+}
@@ -0,0 +1,289 @@
+use iced::{
+    alignment, executor, Color,
+    widget::{button, checkbox, column, container, row, scrollable, text, Column, Space},
+    Application, Command, Element, Length, Theme as IcedTheme, Padding,
+    window,
+};
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use crate::gui::scaling;
+use crate::gui::theme::Theme as AppTheme;
+use crate::gui::styles::{RoundedButtonStyle, RoundedContainerStyle, CustomScrollableStyle, YellowCheckboxStyle};
+use birdnest_core::package_manager::PackageBackend;
+use birdnest_core::plan;
+use birdnest_core::trim::{self, Category, Offer};
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Loaded(Result<Vec<Offer>, String>),
+    Toggle(String, bool),
+    Remove,
+    /// The meta-packages the picks would take together
+    Checked(Result<Vec<String>, String>),
+    Close,
+}
+
+// Maintenance > Trim Preinstalled Software: the optional apps PikaOS comes
+// with, by category, each saying what goes with it. The picks are handed to
+// the remove dialog, which shows the whole plan before anything is removed.
+#[derive(Debug)]
+pub struct TrimDialog {
+    /// None while the installed apps are checked against the meta-packages
+    pub offers: Option<Result<Vec<Offer>, String>>,
+    pub picks: BTreeSet<String>,
+    pub checking: bool,
+    /// Meta-packages the picks would take together; the removal waits until
+    /// one is unticked
+    pub blocked: Vec<String>,
+    /// Why the picks couldn't be checked
+    pub error: Option<String>,
+    /// Picks cleared to remove; the main window opens the remove dialog
+    /// for them and closes this one
+    pub confirmed: Option<Vec<String>>,
+    pub backend: Arc<dyn PackageBackend>,
+    pub theme: AppTheme,
+    pub border_radius: f32,
+    // The child window it was opened in
+    pub window: window::Id,
+}
+
+async fn load(backend: Arc<dyn PackageBackend>) -> Result<Vec<Offer>, String> {
+    tokio::task::spawn_blocking(move || trim::offers(backend.as_ref()))
+        .await
+        .map_err(|e| format!("Task error: {}", e))?
+        .map_err(|e| e.to_string())
+}
+
+async fn check(backend: Arc<dyn PackageBackend>, packages: Vec<String>) -> Result<Vec<String>, String> {
+    tokio::task::spawn_blocking(move || trim::check_picks(backend.as_ref(), &packages))
+        .await
+        .map_err(|e| format!("Task error: {}", e))?
+        .map_err(|e| e.to_string())
+}
+
+impl TrimDialog {
+    pub fn new() -> Self {
+        Self {
+            offers: None,
+            picks: BTreeSet::new(),
+            checking: false,
+            blocked: Vec::new(),
+            error: None,
+            confirmed: None,
+            backend: crate::gui::create_backend(),
+            theme: AppTheme::Dark,
+            border_radius: 12.0,
+            window: window::Id::MAIN,
+        }
+    }
+
+    pub fn window_settings() -> window::Settings {
+        window::Settings {
+            size: scaling::fit(iced::Size::new(820.0, 720.0)),
+            min_size: Some(scaling::fit(iced::Size::new(600.0, 450.0))),
+            resizable: true,
+            decorations: true,
+            ..window::Settings::default()
+        }
+    }
+
+    // Name and size, what goes with it, and why it's kept when a
+    // meta-package needs it
+    fn view_offer(&self, offer: &Offer) -> Element<'_, Message> {
+        let theme = self.theme;
+        let package = offer.candidate.package;
+        let label = match offer.installed_bytes {
+            Some(bytes) => format!("{} ({})", offer.candidate.name, plan::format_size(bytes)),
+            None => offer.candidate.name.to_string(),
+        };
+        let toggle = checkbox(label, self.picks.contains(package))
+            .text_size(15.0)
+            .style(iced::theme::Checkbox::Custom(Box::new(YellowCheckboxStyle {
+                radius: 4.0,
+                primary_color: theme.primary(),
+            })));
+        let toggle = if offer.is_protected() || self.checking {
+            toggle
+        } else {
+            toggle.on_toggle(move |picked| Message::Toggle(package.to_string(), picked))
+        };
+
+        let mut details = column![toggle, text(offer.candidate.consequence).size(12).style(iced::theme::Text::Color(theme.secondary_text()))].spacing(2);
+        if offer.is_protected() {
+            details = details.push(
+                text(format!("Kept: removing it would also remove {}", offer.protected_by.join(", ")))
+                    .size(12)
+                    .style(iced::theme::Text::Color(theme.primary())),
+            );
+        }
+        details.into()
+    }
+
+    fn view_category<'a>(&'a self, category: Category, offers: &'a [Offer]) -> Option<Element<'a, Message>> {
+        let theme = self.theme;
+        let mut section = Column::new().spacing(10).push(text(category.label()).size(16).style(iced::theme::Text::Color(theme.primary())));
+        let mut any = false;
+        for offer in offers.iter().filter(|offer| offer.candidate.category == category) {
+            section = section.push(self.view_offer(offer));
+            any = true;
+        }
+        any.then(|| {
+            container(section.padding(Padding::new(16.0)))
+                .width(Length::Fill)
+                .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+                    radius: self.border_radius,
+                    background: Some(theme.card_background()),
+                    elevation: 1.0,
+                })))
+                .into()
+        })
+    }
+}
+
+impl Application for TrimDialog {
+    type Message = Message;
+    type Theme = IcedTheme;
+    type Executor = executor::Default;
+    type Flags = Self;
+
+    fn new(flags: Self) -> (Self, Command<Message>) {
+        let backend = flags.backend.clone();
+        (flags, Command::perform(load(backend), Message::Loaded))
+    }
+
+    fn title(&self) -> String {
+        "Trim Preinstalled Software - BirdNest".to_string()
+    }
+
+    fn update(&mut self, message: Message) -> Command<Message> {
+        match message {
+            Message::Loaded(offers) => {
+                self.offers = Some(offers);
+                Command::none()
+            }
+            Message::Toggle(package, picked) => {
+                if picked {
+                    self.picks.insert(package);
+                } else {
+                    self.picks.remove(&package);
+                }
+                self.blocked.clear();
+                self.error = None;
+                Command::none()
+            }
+            Message::Remove => {
+                if self.picks.is_empty() || self.checking {
+                    return Command::none();
+                }
+                self.checking = true;
+                Command::perform(check(self.backend.clone(), self.picks.iter().cloned().collect()), Message::Checked)
+            }
+            Message::Checked(result) => {
+                self.checking = false;
+                match result {
+                    Ok(blocked) if blocked.is_empty() => self.confirmed = Some(self.picks.iter().cloned().collect()),
+                    Ok(blocked) => self.blocked = blocked,
+                    Err(e) => self.error = Some(e),
+                }
+                Command::none()
+            }
+            Message::Close => iced::window::close(self.window),
+        }
+    }
+
+    fn view(&self) -> Element<'_, Message> {
+        let theme = self.theme;
+        let button_style = |is_primary: bool| {
+            iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                is_primary,
+                radius: self.border_radius,
+                primary_color: theme.primary(),
+                text_color: if is_primary { Color::BLACK } else { Color::WHITE },
+                background_color: theme.background(),
+            }))
+        };
+
+        let mut body = Column::new().spacing(12);
+        let summary = match &self.offers {
+            None => "Checking which apps are installed and what depends on them...".to_string(),
+            Some(Err(e)) => format!("Couldn't list the installed apps: {}", e),
+            Some(Ok(offers)) if offers.is_empty() => "None of the optional apps are installed.".to_string(),
+            Some(Ok(offers)) => {
+                for category in Category::ALL {
+                    if let Some(section) = self.view_category(category, offers) {
+                        body = body.push(section);
+                    }
+                }
+                match self.picks.len() {
+                    0 => "Tick the apps you don't use.".to_string(),
+                    1 => "1 app picked".to_string(),
+                    picked => format!("{} apps picked", picked),
+                }
+            }
+        };
+
+        let warning: Element<'_, Message> = match (&self.error, self.blocked.is_empty()) {
+            (Some(e), _) => text(format!("Couldn't check what the removal takes with it: {}", e))
+                .size(13)
+                .style(iced::theme::Text::Color(theme.primary()))
+                .into(),
+            (None, false) => text(format!(
+                "Together these would also remove {}. Untick one of them to keep your desktop's defaults and updates.",
+                self.blocked.join(", ")
+            ))
+            .size(13)
+            .style(iced::theme::Text::Color(theme.primary()))
+            .into(),
+            (None, true) => Space::with_height(Length::Fixed(0.0)).into(),
+        };
+
+        let mut remove = button(text(if self.checking { "Checking..." } else { "Remove Selected..." }))
+            .style(button_style(true))
+            .padding(Padding::new(14.0));
+        if !self.picks.is_empty() && !self.checking {
+            remove = remove.on_press(Message::Remove);
+        }
+
+        container(
+            column![
+                text("Trim Preinstalled Software").size(24).style(iced::theme::Text::Color(theme.text())),
+                text("Optional apps PikaOS installs by default. Apps your desktop's meta-package depends on are kept; the remove dialog shows everything that goes before anything does.")
+                    .size(13)
+                    .style(iced::theme::Text::Color(theme.secondary_text())),
+                text(summary).size(15).style(iced::theme::Text::Color(theme.text())),
+                scrollable(body)
+                    .style(iced::theme::Scrollable::Custom(Box::new(CustomScrollableStyle {
+                        background_color: theme.surface(),
+                        border_radius: self.border_radius,
+                    })))
+                    .height(Length::Fill),
+                warning,
+                row![
+                    Space::with_width(Length::Fill),
+                    button("Close").on_press(Message::Close).style(button_style(false)).padding(Padding::new(14.0)),
+                    remove,
+                ]
+                .spacing(10)
+                .align_items(alignment::Alignment::Center),
+            ]
+            .spacing(15)
+            .padding(Padding::new(30.0))
+        )
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+            radius: self.border_radius,
+            background: Some(theme.background()),
+            elevation: 0.0,
+        })))
+        .into()
+    }
+
+    fn theme(&self) -> IcedTheme {
+        match self.theme {
+            AppTheme::Light => IcedTheme::Light,
+            AppTheme::Dark => IcedTheme::Dark,
+        }
+    }
+}
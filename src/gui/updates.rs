@@ -0,0 +1,395 @@
+use iced::{
+    alignment, Color,
+    widget::{button, checkbox, column, container, row, scrollable, text, Column, Space},
+    Element, Length, Padding,
+};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use birdnest_core::changelog;
+use birdnest_core::flatpak::FlatpakManager;
+use birdnest_core::package_manager::{PackageBackend, PackageUpdate};
+use birdnest_core::shutdown_updates::{self, StagedUpdates};
+
+use crate::gui::changelog_view::{self, Changelog};
+use crate::gui::restart_banner;
+use crate::gui::theme::Theme as AppTheme;
+use crate::gui::styles::{RoundedButtonStyle, RoundedContainerStyle, CustomScrollableStyle, YellowCheckboxStyle};
+use crate::gui::Message;
+
+/// One row of the Updates tab
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpdateInfo {
+    pub name: String,
+    pub current_version: String,
+    pub new_version: String,
+    pub is_flatpak: bool,
+}
+
+impl UpdateInfo {
+    fn from_update(update: PackageUpdate, is_flatpak: bool) -> Self {
+        Self {
+            name: update.name,
+            current_version: update.current_version,
+            new_version: update.new_version,
+            is_flatpak,
+        }
+    }
+
+    // apt names and Flatpak IDs live in separate namespaces
+    pub fn key(&self) -> (bool, String) {
+        (self.is_flatpak, self.name.clone())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct UpdatesState {
+    pub entries: Vec<UpdateInfo>,
+    pub selected: HashSet<(bool, String)>,
+    pub loading: bool,
+    pub error: Option<String>,
+    /// Reboot and service restarts still pending from earlier upgrades
+    pub restart: restart_banner::RestartState,
+    /// Updates waiting to install at the next shutdown
+    pub shutdown: Option<StagedUpdates>,
+    /// The backend can download updates ahead, so the shutdown toggle shows
+    pub shutdown_supported: bool,
+    pub shutdown_busy: bool,
+    /// System packages held at their version (shown on the Installed tab
+    /// too); their updates are listed but can't be picked
+    pub held: HashSet<String>,
+    /// Rows opened to show what their update changes
+    pub expanded: HashSet<(bool, String)>,
+    /// Changelogs fetched for opened rows, cut down to the new versions
+    pub changelogs: HashMap<(bool, String), Changelog>,
+}
+
+impl UpdatesState {
+    pub fn set_entries(&mut self, entries: Vec<UpdateInfo>) {
+        // Keep ticks on packages that are still upgradable after a refresh
+        let keys: HashSet<(bool, String)> = entries.iter().map(UpdateInfo::key).collect();
+        self.selected.retain(|key| keys.contains(key));
+        self.expanded.retain(|key| keys.contains(key));
+        // A changelog is only good for the version it was cut down to
+        let unchanged: HashSet<(bool, String)> = entries.iter().filter(|e| self.entries.contains(e)).map(UpdateInfo::key).collect();
+        self.changelogs.retain(|key, _| unchanged.contains(key));
+        self.entries = entries;
+    }
+
+    pub fn toggle(&mut self, key: (bool, String)) {
+        if !self.selected.remove(&key) {
+            self.selected.insert(key);
+        }
+    }
+
+    pub fn toggle_expanded(&mut self, key: (bool, String)) {
+        if !self.expanded.remove(&key) {
+            self.expanded.insert(key);
+        }
+    }
+
+    /// Opens every row, or closes them all when they already are
+    pub fn expand_all(&mut self) {
+        if self.expanded.len() == self.entries.len() {
+            self.expanded.clear();
+        } else {
+            self.expanded = self.entries.iter().map(UpdateInfo::key).collect();
+        }
+    }
+
+    /// The next opened system package whose changelog isn't fetched, in list
+    /// order; None while one is being fetched, since apt downloads them one
+    /// at a time
+    pub fn next_changelog(&self) -> Option<&UpdateInfo> {
+        if self.changelogs.values().any(|changelog| *changelog == Changelog::Loading) {
+            return None;
+        }
+        self.entries
+            .iter()
+            .find(|e| !e.is_flatpak && self.expanded.contains(&e.key()) && !self.changelogs.contains_key(&e.key()))
+    }
+
+    pub fn is_held(&self, entry: &UpdateInfo) -> bool {
+        !entry.is_flatpak && self.held.contains(&entry.name)
+    }
+
+    // Held packages would make apt refuse the whole upgrade
+    fn upgradable(&self) -> impl Iterator<Item = &UpdateInfo> {
+        self.entries.iter().filter(|e| !self.is_held(e))
+    }
+
+    pub fn select_all(&mut self) {
+        if self.selected.len() == self.upgradable().count() {
+            self.selected.clear();
+        } else {
+            self.selected = self.upgradable().map(UpdateInfo::key).collect();
+        }
+    }
+
+    /// Every pending apt package and Flatpak ID that isn't held, for staging
+    /// at shutdown
+    pub fn pending(&self) -> (Vec<String>, Vec<String>) {
+        let (flatpaks, packages): (Vec<&UpdateInfo>, Vec<&UpdateInfo>) = self.upgradable().partition(|e| e.is_flatpak);
+        let names = |entries: Vec<&UpdateInfo>| entries.into_iter().map(|e| e.name.clone()).collect();
+        (names(packages), names(flatpaks))
+    }
+
+    /// Selected apt packages and Flatpak IDs, in list order
+    pub fn selection(&self) -> (Vec<String>, Vec<String>) {
+        let mut packages = Vec::new();
+        let mut flatpaks = Vec::new();
+        for entry in self.entries.iter().filter(|e| self.selected.contains(&e.key())) {
+            if entry.is_flatpak {
+                flatpaks.push(entry.name.clone());
+            } else {
+                packages.push(entry.name.clone());
+            }
+        }
+        (packages, flatpaks)
+    }
+}
+
+// A Flatpak failure (not installed, no network) shouldn't hide the apt updates,
+// so only the backend's error is returned
+pub async fn load_updates(backend: Arc<dyn PackageBackend>) -> Result<Vec<UpdateInfo>, String> {
+    tokio::task::spawn_blocking(move || {
+        let mut entries: Vec<UpdateInfo> = backend
+            .list_upgradable()
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .map(|update| UpdateInfo::from_update(update, false))
+            .collect();
+        match FlatpakManager::new().and_then(|fm| fm.list_updates()) {
+            Ok(updates) => entries.extend(updates.into_iter().map(|update| UpdateInfo::from_update(update, true))),
+            Err(e) => tracing::warn!("load_updates: Flatpak updates unavailable: {}", e),
+        }
+        tracing::debug!("load_updates: {} updates", entries.len());
+        Ok(entries)
+    })
+    .await
+    .map_err(|e| format!("Task error: {}", e))?
+}
+
+pub async fn load_held(backend: Arc<dyn PackageBackend>) -> HashSet<String> {
+    tokio::task::spawn_blocking(move || match backend.held() {
+        Ok(held) => held.into_iter().collect(),
+        Err(e) => {
+            tracing::warn!("load_held: {}", e);
+            HashSet::new()
+        }
+    })
+    .await
+    .unwrap_or_default()
+}
+
+pub async fn load_staged() -> Option<StagedUpdates> {
+    tokio::task::spawn_blocking(shutdown_updates::load).await.ok().flatten()
+}
+
+// Versions an opened row lists
+const HIGHLIGHTS: usize = 3;
+
+fn version_change(entry: &UpdateInfo) -> String {
+    match (entry.current_version.is_empty(), entry.new_version.is_empty()) {
+        (false, false) => format!("{} → {}", entry.current_version, entry.new_version),
+        (true, false) => format!("→ {}", entry.new_version),
+        _ => "update available".to_string(),
+    }
+}
+
+/// What an opened row sums up: the version change, how many versions it
+/// spans and the most pressing urgency among them
+pub fn summary(entry: &UpdateInfo, changelog: Option<&Changelog>) -> String {
+    let mut summary = version_change(entry);
+    if let Some(Changelog::Loaded(entries)) = changelog {
+        match entries.len() {
+            0 => {}
+            1 => summary.push_str(", 1 new version"),
+            count => summary.push_str(&format!(", {} new versions", count)),
+        }
+        if let Some(urgency) = changelog::urgency(entries) {
+            summary.push_str(&format!(", {} urgency", urgency));
+        }
+    }
+    summary
+}
+
+// The newest versions under an opened row; the details pane has the rest
+fn view_changes<'a>(entry: &'a UpdateInfo, changelog: Option<&'a Changelog>, theme: AppTheme, radius: f32) -> Element<'a, Message> {
+    let changes: Element<'a, Message> = match changelog {
+        Some(changelog) => changelog_view::view(changelog, Message::ToggleUpdateChangelog(entry.key()), HIGHLIGHTS, theme, radius),
+        None => text("Waiting for the changelogs above").size(13.0).style(iced::theme::Text::Color(theme.secondary_text())).into(),
+    };
+    column![
+        text(summary(entry, changelog)).size(13.0).font(iced::Font::MONOSPACE).style(iced::theme::Text::Color(theme.secondary_text())),
+        changes,
+    ]
+    .spacing(8)
+    .padding(Padding::from([0.0, 0.0, 0.0, 36.0]))
+    .into()
+}
+
+pub fn view(state: &UpdatesState, theme: AppTheme, radius: f32) -> Element<'_, Message> {
+    let secondary_button = |label: &'static str, message: Message| {
+        button(label)
+            .on_press(message)
+            .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                is_primary: false,
+                radius,
+                primary_color: theme.primary(),
+                text_color: Color::WHITE,
+                background_color: theme.background(),
+            })))
+            .padding(Padding::new(14.0))
+    };
+
+    let mut upgrade = button(text(if state.selected.is_empty() {
+        "Select updates to install".to_string()
+    } else {
+        format!("Upgrade {} Selected", state.selected.len())
+    }).size(16.0))
+    .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+        is_primary: !state.selected.is_empty(),
+        radius,
+        primary_color: theme.primary(),
+        text_color: if state.selected.is_empty() { Color::WHITE } else { Color::BLACK },
+        background_color: theme.background(),
+    })))
+    .padding(Padding::new(14.0));
+    if !state.selected.is_empty() {
+        upgrade = upgrade.on_press(Message::UpgradeSelected);
+    }
+
+    let mut toolbar = row![
+        secondary_button("Refresh", Message::LoadUpdates),
+        secondary_button("Select All", Message::SelectAllUpdates),
+        secondary_button(
+            if !state.entries.is_empty() && state.expanded.len() == state.entries.len() { "Collapse All" } else { "Expand All" },
+            Message::ExpandAllUpdates,
+        ),
+    ]
+    .spacing(10)
+    .align_items(alignment::Alignment::Center);
+    if state.shutdown_supported {
+        let staged = state.shutdown.is_some();
+        let toggle = checkbox("Install on next shutdown", staged)
+            .text_size(14.0)
+            .style(iced::theme::Checkbox::Custom(Box::new(YellowCheckboxStyle {
+                radius: 4.0,
+                primary_color: theme.primary(),
+            })));
+        // Nothing to stage until updates are listed; unstaging always works
+        let can_toggle = !state.shutdown_busy && (staged || !state.entries.is_empty());
+        toolbar = toolbar.push(if can_toggle { toggle.on_toggle(Message::ShutdownUpdatesToggled) } else { toggle });
+    }
+    let toolbar = toolbar.push(Space::with_width(Length::Fill)).push(upgrade);
+
+    let status = if state.loading {
+        "Checking for updates...".to_string()
+    } else if let Some(error) = &state.error {
+        format!("Could not check for updates: {}", error)
+    } else if let Some(staged) = &state.shutdown {
+        format!("{} updates available; {} install when you next shut down", state.entries.len(), staged.len())
+    } else if state.entries.is_empty() {
+        "Everything is up to date".to_string()
+    } else {
+        format!("{} updates available", state.entries.len())
+    };
+    let held = state.entries.iter().filter(|e| state.is_held(e)).count();
+    let status = match held {
+        0 => status,
+        1 => format!("{}; 1 is held at its installed version and won't upgrade until you release it on the Installed tab", status),
+        _ => format!("{}; {} are held at their installed version and won't upgrade until you release them on the Installed tab", status, held),
+    };
+
+    let mut list = Column::new().spacing(6);
+    for entry in &state.entries {
+        let key = entry.key();
+        let selected = state.selected.contains(&key);
+        let held = state.is_held(entry);
+        let tick = checkbox("", selected).style(iced::theme::Checkbox::Custom(Box::new(YellowCheckboxStyle {
+            radius: 4.0,
+            primary_color: theme.primary(),
+        })));
+        let tick = if held {
+            tick
+        } else {
+            let key = key.clone();
+            tick.on_toggle(move |_| Message::ToggleUpdate(key.clone()))
+        };
+        let expanded = state.expanded.contains(&key);
+        let changelog = state.changelogs.get(&key);
+        let urgency = match changelog {
+            Some(Changelog::Loaded(entries)) => changelog::urgency(entries).filter(|u| changelog::is_urgent(u)),
+            _ => None,
+        };
+        let mut card = Column::new().spacing(10).push(
+            row![
+                tick,
+                text(&entry.name)
+                    .size(16.0)
+                    .style(iced::theme::Text::Color(theme.text()))
+                    .width(Length::FillPortion(3)),
+                text(version_change(entry))
+                    .size(14.0)
+                    .font(iced::Font::MONOSPACE)
+                    .style(iced::theme::Text::Color(theme.secondary_text()))
+                    .width(Length::FillPortion(3)),
+                text(urgency.map(|u| format!("{} urgency", u)).unwrap_or_default())
+                    .size(13.0)
+                    .style(iced::theme::Text::Color(theme.danger())),
+                text(if held { "🔒 Held" } else if entry.is_flatpak { "Flatpak" } else { "System" })
+                    .size(13.0)
+                    .style(iced::theme::Text::Color(if held { theme.secondary_text() } else { theme.primary() })),
+                button(text(if expanded { "▾" } else { "▸" }).size(13.0))
+                    .on_press(Message::ToggleUpdateChangelog(key.clone()))
+                    .style(iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                        is_primary: false,
+                        radius,
+                        primary_color: theme.primary(),
+                        text_color: Color::WHITE,
+                        background_color: theme.background(),
+                    })))
+                    .padding(Padding::new(8.0)),
+            ]
+            .spacing(12)
+            .align_items(alignment::Alignment::Center),
+        );
+        if expanded {
+            card = card.push(view_changes(entry, changelog, theme, radius));
+        }
+        list = list.push(
+            container(card)
+                .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+                    radius,
+                    background: Some(theme.card_background()),
+                    elevation: 1.0,
+                })))
+                .width(Length::Fill)
+                .padding(Padding::new(12.0)),
+        );
+    }
+
+    column![
+        restart_banner::view(
+            &state.restart,
+            theme,
+            radius,
+            Message::ToggleRestartService,
+            Message::RestartServices,
+            Message::Reboot,
+        ),
+        toolbar,
+        text(status)
+            .size(14.0)
+            .style(iced::theme::Text::Color(theme.secondary_text())),
+        scrollable(list.padding(Padding::new(4.0)))
+            .style(iced::theme::Scrollable::Custom(Box::new(CustomScrollableStyle {
+                background_color: theme.surface(),
+                border_radius: radius,
+            })))
+            .height(Length::Fill),
+    ]
+    .spacing(12)
+    .into()
+}
@@ -0,0 +1,404 @@
+use iced::{
+    alignment, executor, Color,
+    widget::{button, column, container, progress_bar, row, scrollable, text, Column, Space},
+    Application, Command, Element, Length, Pixels, Settings, Subscription, Theme as IcedTheme, Padding,
+    window,
+};
+use std::sync::Arc;
+
+use crate::gui::command_stream::{self, Step, StreamEvent};
+use crate::gui::restart_banner;
+use crate::gui::scaling;
+use crate::gui::theme::Theme as AppTheme;
+use crate::gui::styles::{RoundedButtonStyle, RoundedContainerStyle, CustomScrollableStyle};
+use birdnest_core::elevation;
+use birdnest_core::hooks::HookEvent;
+use birdnest_core::immutable::{self, SystemKind};
+use birdnest_core::package_manager::{BackendCommand, PackageBackend};
+use birdnest_core::progress::{self, TransactionProgress};
+use birdnest_core::restart::RestartStatus;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    PasswordlessChecked(bool),
+    Upgrade,
+    UpgradeOutput(StreamEvent),
+    RestartChecked(RestartStatus),
+    ToggleRestartService(String),
+    RestartServices,
+    Reboot,
+    RestartActionFinished(Result<(), String>),
+    HooksFinished(String),
+    Close,
+}
+
+// Upgrades the packages and Flatpaks picked in the Updates tab
+#[derive(Debug)]
+pub struct UpgradeDialog {
+    packages: Vec<String>,
+    flatpaks: Vec<String>,
+    is_upgrading: bool,
+    pub is_complete: bool,
+    output: String,
+    // Read back out of the output, which then leaves out apt's status lines
+    pub progress: TransactionProgress,
+    error: Option<String>,
+    passwordless: bool,
+    // Bumped for every upgrade attempt, so a retry gets a fresh subscription
+    attempt: u32,
+    restart: restart_banner::RestartState,
+    pub backend: Arc<dyn PackageBackend>,
+    pub system: SystemKind,
+    pub theme: AppTheme,
+    pub border_radius: f32,
+    // MAIN when run standalone, otherwise the child window it was opened in
+    pub window: window::Id,
+}
+
+impl UpgradeDialog {
+    pub fn new(packages: Vec<String>, flatpaks: Vec<String>) -> Self {
+        Self {
+            packages,
+            flatpaks,
+            is_upgrading: false,
+            is_complete: false,
+            output: String::new(),
+            progress: TransactionProgress::default(),
+            error: None,
+            passwordless: false,
+            attempt: 0,
+            restart: restart_banner::RestartState::default(),
+            backend: crate::gui::create_backend(),
+            system: immutable::detect(),
+            theme: AppTheme::Dark,
+            border_radius: 12.0,
+            window: window::Id::MAIN,
+        }
+    }
+
+    pub fn window_settings() -> window::Settings {
+        window::Settings {
+            size: scaling::fit(iced::Size::new(750.0, 700.0)),
+            min_size: Some(scaling::fit(iced::Size::new(600.0, 450.0))),
+            resizable: true,
+            decorations: true,
+            ..window::Settings::default()
+        }
+    }
+
+    pub fn run_separate_window(packages: Vec<String>, flatpaks: Vec<String>) -> Result<(), iced::Error> {
+        let dialog = Self::new(packages, flatpaks);
+
+        <UpgradeDialog as Application>::run(Settings {
+            window: Self::window_settings(),
+            flags: dialog,
+            default_text_size: Pixels(14.0),
+            antialiasing: true,
+            id: None,
+            fonts: Vec::new(),
+            default_font: iced::Font::DEFAULT,
+        })
+    }
+}
+
+impl Application for UpgradeDialog {
+    type Message = Message;
+    type Theme = IcedTheme;
+    type Executor = executor::Default;
+    type Flags = Self;
+
+    fn new(flags: Self) -> (Self, Command<Message>) {
+        let cmd = if flags.packages.is_empty() {
+            Command::none()
+        } else {
+            Command::perform(
+                async { tokio::task::spawn_blocking(elevation::passwordless).await.unwrap_or(false) },
+                Message::PasswordlessChecked,
+            )
+        };
+        (flags, cmd)
+    }
+
+    fn title(&self) -> String {
+        format!("Upgrade {} Packages - BirdNest", self.packages.len() + self.flatpaks.len())
+    }
+
+    fn update(&mut self, message: Message) -> Command<Message> {
+        match message {
+            Message::PasswordlessChecked(passwordless) => {
+                self.passwordless = passwordless;
+                Command::none()
+            }
+            Message::Upgrade => {
+                // The run itself is the subscription below, active while upgrading
+                self.is_upgrading = true;
+                self.attempt += 1;
+                self.error = None;
+                self.output.clear();
+                let names: Vec<String> = self.packages.iter().chain(&self.flatpaks).cloned().collect();
+                self.progress = TransactionProgress::new(&names);
+                Command::none()
+            }
+            Message::UpgradeOutput(StreamEvent::Line(line)) => {
+                if self.progress.feed(&line) {
+                    return Command::none();
+                }
+                self.output.push_str(&line);
+                self.output.push('\n');
+                scrollable::snap_to(output_id(self.window), scrollable::RelativeOffset::END)
+            }
+            Message::UpgradeOutput(StreamEvent::Finished(result)) => {
+                self.is_upgrading = false;
+                match result {
+                    Ok(()) => {
+                        self.is_complete = true;
+                        self.progress.finish();
+                        birdnest_core::cache::invalidate_installed();
+                        let mut commands = vec![Command::perform(restart_banner::check(), Message::RestartChecked)];
+                        // A staged image isn't upgraded until the reboot
+                        if !self.packages.is_empty() && !self.system.is_immutable() {
+                            commands.push(Command::perform(
+                                command_stream::run_hooks(HookEvent::Upgrade, self.packages.clone(), false),
+                                Message::HooksFinished,
+                            ));
+                        }
+                        if !self.flatpaks.is_empty() {
+                            commands.push(Command::perform(
+                                command_stream::run_hooks(HookEvent::Upgrade, self.flatpaks.clone(), true),
+                                Message::HooksFinished,
+                            ));
+                        }
+                        return Command::batch(commands);
+                    }
+                    Err(e) => self.error = Some(e),
+                }
+                Command::none()
+            }
+            Message::RestartChecked(mut status) => {
+                // A staged image only takes effect after a reboot
+                if self.system.is_immutable() && !self.packages.is_empty() {
+                    status.reboot_required = true;
+                }
+                self.restart.set_status(status);
+                Command::none()
+            }
+            Message::HooksFinished(output) => {
+                self.output.push_str(&output);
+                scrollable::snap_to(output_id(self.window), scrollable::RelativeOffset::END)
+            }
+            Message::ToggleRestartService(service) => {
+                self.restart.toggle(service);
+                Command::none()
+            }
+            Message::RestartServices => {
+                Command::perform(restart_banner::restart_services(self.restart.selection()), Message::RestartActionFinished)
+            }
+            Message::Reboot => {
+                Command::perform(restart_banner::reboot(), Message::RestartActionFinished)
+            }
+            Message::RestartActionFinished(result) => match result {
+                // Check again so restarted services drop off the banner
+                Ok(()) => Command::perform(restart_banner::check(), Message::RestartChecked),
+                Err(e) => {
+                    self.error = Some(e);
+                    Command::none()
+                }
+            },
+            Message::Close => {
+                iced::window::close(self.window)
+            }
+        }
+    }
+
+    fn view(&self) -> Element<'_, Message> {
+        let theme = self.theme;
+
+        let mut list = Column::new().spacing(4);
+        if self.progress.packages().is_empty() {
+            for name in &self.packages {
+                list = list.push(text(format!("{}  (system)", name)).size(14).style(iced::theme::Text::Color(theme.text())));
+            }
+            for id in &self.flatpaks {
+                list = list.push(text(format!("{}  (Flatpak)", id)).size(14).style(iced::theme::Text::Color(theme.text())));
+            }
+        } else {
+            list = list.push(progress_bars(&self.progress, theme));
+        }
+
+        let mut notices = Column::new().spacing(6);
+        if !self.packages.is_empty() && !self.is_complete {
+            if !self.passwordless {
+                notices = notices.push(
+                    text("Administrator privileges will be requested")
+                        .size(12)
+                        .style(iced::theme::Text::Color(Color::from_rgb(1.0, 0.8, 0.0))),
+                );
+            }
+            if self.system.is_immutable() {
+                notices = notices.push(
+                    text(format!("{} The whole image is upgraded, not just the selected packages.", immutable::REBOOT_NOTICE))
+                        .size(12)
+                        .style(iced::theme::Text::Color(Color::from_rgb(1.0, 0.8, 0.0))),
+                );
+            }
+        }
+
+        let status: Element<'_, Message> = if self.is_upgrading {
+            text("Upgrading...").size(14).style(iced::theme::Text::Color(theme.text())).into()
+        } else if let Some(error) = &self.error {
+            text(error).size(14).style(iced::theme::Text::Color(theme.danger())).into()
+        } else if self.is_complete {
+            text("Upgrade Complete!").size(14).style(iced::theme::Text::Color(Color::from_rgb(0.0, 1.0, 0.0))).into()
+        } else {
+            Space::with_height(Length::Fixed(0.0)).into()
+        };
+
+        let button_style = |is_primary: bool| {
+            iced::theme::Button::Custom(Box::new(RoundedButtonStyle {
+                is_primary,
+                radius: self.border_radius,
+                primary_color: theme.primary(),
+                text_color: if is_primary { Color::BLACK } else { Color::WHITE },
+                background_color: theme.background(),
+            }))
+        };
+        let buttons = if self.is_complete {
+            row![
+                Space::with_width(Length::Fill),
+                button("Close").on_press(Message::Close).style(button_style(true)).padding(Padding::new(14.0)),
+            ]
+        } else {
+            let mut upgrade = button(if self.is_upgrading { "Upgrading..." } else { "Upgrade" })
+                .style(button_style(true))
+                .padding(Padding::new(14.0));
+            let mut cancel = button("Cancel").style(button_style(false)).padding(Padding::new(14.0));
+            if !self.is_upgrading {
+                upgrade = upgrade.on_press(Message::Upgrade);
+                cancel = cancel.on_press(Message::Close);
+            }
+            row![Space::with_width(Length::Fill), cancel, upgrade].spacing(10)
+        }
+        .align_items(alignment::Alignment::Center);
+
+        container(
+            column![
+                text(self.title().trim_end_matches(" - BirdNest").to_string())
+                    .size(24)
+                    .style(iced::theme::Text::Color(theme.text())),
+                list,
+                notices,
+                status,
+                restart_banner::view(
+                    &self.restart,
+                    theme,
+                    self.border_radius,
+                    Message::ToggleRestartService,
+                    Message::RestartServices,
+                    Message::Reboot,
+                ),
+                scrollable(
+                    text(&self.output)
+                        .size(12)
+                        .font(iced::Font::MONOSPACE)
+                        .style(iced::theme::Text::Color(theme.text()))
+                )
+                .id(output_id(self.window))
+                .style(iced::theme::Scrollable::Custom(Box::new(CustomScrollableStyle {
+                    background_color: theme.surface(),
+                    border_radius: self.border_radius,
+                })))
+                .height(Length::Fill),
+                buttons,
+            ]
+            .spacing(15)
+            .padding(Padding::new(30.0))
+        )
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .style(iced::theme::Container::Custom(Box::new(RoundedContainerStyle {
+            radius: self.border_radius,
+            background: Some(theme.background()),
+            elevation: 0.0,
+        })))
+        .into()
+    }
+
+    fn subscription(&self) -> Subscription<Message> {
+        if self.is_upgrading {
+            command_stream::subscription(("upgrade", self.attempt), self.upgrade_steps(), Message::UpgradeOutput)
+        } else {
+            Subscription::none()
+        }
+    }
+
+    fn theme(&self) -> IcedTheme {
+        match self.theme {
+            AppTheme::Light => IcedTheme::Light,
+            AppTheme::Dark => IcedTheme::Dark,
+        }
+    }
+}
+
+/// Once a transaction has started, a bar per package (dependencies too, as
+/// they turn up) under the overall one; the install and remove dialogs show
+/// the same. Nothing while there are no packages to follow.
+pub fn progress_bars<'a, M: 'a>(progress: &TransactionProgress, theme: AppTheme) -> Element<'a, M> {
+    if progress.packages().is_empty() {
+        return Column::new().into();
+    }
+    let mut bars = Column::new().spacing(4).push(progress_row("Overall", progress.overall(), 14, theme));
+    for package in progress.packages() {
+        bars = bars.push(progress_row(&package.name, package.fraction, 13, theme));
+    }
+    bars.into()
+}
+
+fn progress_row<'a, M: 'a>(label: &str, fraction: f32, size: u16, theme: AppTheme) -> Element<'a, M> {
+    row![
+        text(label).size(size).width(Length::FillPortion(2)).style(iced::theme::Text::Color(theme.text())),
+        progress_bar(0.0..=1.0, fraction).height(Length::Fixed(8.0)).width(Length::FillPortion(3)),
+        text(format!("{:.0}%", fraction * 100.0))
+            .size(size)
+            .width(Length::Fixed(44.0))
+            .horizontal_alignment(alignment::Horizontal::Right)
+            .style(iced::theme::Text::Color(theme.secondary_text())),
+    ]
+    .spacing(10)
+    .align_items(alignment::Alignment::Center)
+    .into()
+}
+
+// The output pane, kept scrolled to the newest line; widget operations reach
+// every open window, so the id is per window
+fn output_id(window: window::Id) -> scrollable::Id {
+    scrollable::Id::new(format!("upgrade-output-{:?}", window))
+}
+
+impl UpgradeDialog {
+    // System packages first (through the backend's commands, elevated), then
+    // Flatpaks, which handle their own authorization
+    fn upgrade_steps(&self) -> Vec<Step> {
+        let mut steps = Vec::new();
+        if !self.packages.is_empty() {
+            let commands = self.backend.upgrade_commands(&self.packages);
+            if commands.is_empty() {
+                let (backend, packages) = (self.backend.clone(), self.packages.clone());
+                steps.push(Step::InProcess(Arc::new(move || backend.upgrade(&packages))));
+            }
+            // apt reports per-package progress on its status fd, which the dialog reads back
+            steps.extend(
+                commands
+                    .into_iter()
+                    .map(progress::with_status_fd)
+                    .map(|command| Step::Command { elevated: !command.as_user, command }),
+            );
+        }
+        if !self.flatpaks.is_empty() {
+            steps.push(Step::Command {
+                command: BackendCommand::new("flatpak", &["update", "-y", "--noninteractive"], &self.flatpaks),
+                elevated: false,
+            });
+        }
+        steps
+    }
+}
@@ -0,0 +1,363 @@
+use iced::{window, Application, Command, Element, Subscription};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use birdnest_core::config::Config;
+use birdnest_core::immutable::SystemKind;
+use birdnest_core::package_manager::PackageBackend;
+
+use crate::gui::batch_dialog::{self, BatchDialog};
+use crate::gui::cli_reference_dialog::{self, CliReferenceDialog};
+use crate::gui::conflict_dialog::{self, ConflictDialog};
+use crate::gui::digest_dialog::{self, DigestDialog};
+use crate::gui::environment_dialog::{self, EnvironmentDialog};
+use crate::gui::install_dialog::{self, InstallDialog};
+use crate::gui::log_viewer_dialog::{self, LogViewerDialog};
+use crate::gui::permissions_dialog::{self, PermissionsDialog};
+use crate::gui::pikman_install_dialog::{self, PikmanInstallDialog};
+use crate::gui::remove_dialog::{self, RemoveDialog};
+use crate::gui::settings_dialog::{self, SettingsDialog};
+use crate::gui::theme::Theme as AppTheme;
+use crate::gui::trim_dialog::{self, TrimDialog};
+use crate::gui::upgrade_dialog::{self, UpgradeDialog};
+use crate::gui::Message;
+
+// Dialogs open as child windows of the main application. Each one is still an
+// iced Application of its own, so the internal `*-dialog` subcommands can run
+// it standalone; here its new/update/view/subscription are driven directly.
+#[derive(Debug)]
+pub enum Dialog {
+    Install(Box<InstallDialog>),
+    Remove(RemoveDialog),
+    Upgrade(UpgradeDialog),
+    Batch(BatchDialog),
+    Conflict(ConflictDialog),
+    PikmanInstall(PikmanInstallDialog),
+    Environment(EnvironmentDialog),
+    CliReference(CliReferenceDialog),
+    LogViewer(LogViewerDialog),
+    Digest(DigestDialog),
+    Trim(TrimDialog),
+    Permissions(PermissionsDialog),
+    Settings(Box<SettingsDialog>),
+}
+
+#[derive(Debug, Clone)]
+pub enum DialogMessage {
+    Install(install_dialog::Message),
+    Remove(remove_dialog::Message),
+    Upgrade(upgrade_dialog::Message),
+    Batch(batch_dialog::Message),
+    Conflict(conflict_dialog::Message),
+    PikmanInstall(pikman_install_dialog::Message),
+    Environment(environment_dialog::Message),
+    CliReference(cli_reference_dialog::Message),
+    LogViewer(log_viewer_dialog::Message),
+    Digest(digest_dialog::Message),
+    Trim(trim_dialog::Message),
+    Permissions(permissions_dialog::Message),
+    Settings(settings_dialog::Message),
+}
+
+/// What a dialog has to tell the main window
+#[derive(Debug)]
+pub enum Notice {
+    /// A removal hit a dependency conflict; the remove window is closed and
+    /// this dialog should open in its place
+    Conflict(ConflictDialog),
+    /// Settings were saved; the main window applies the ones it shows
+    SettingsSaved(Box<Config>),
+    /// Apps picked to trim; the trim window is closed and the remove dialog
+    /// should open for them
+    Trim(Vec<String>),
+}
+
+// Forwards a message to whichever dialog variant is open
+macro_rules! dispatch {
+    ($dialog:expr, $d:ident => $body:expr) => {
+        match $dialog {
+            Dialog::Install($d) => $body,
+            Dialog::Remove($d) => $body,
+            Dialog::Upgrade($d) => $body,
+            Dialog::Batch($d) => $body,
+            Dialog::Conflict($d) => $body,
+            Dialog::PikmanInstall($d) => $body,
+            Dialog::Environment($d) => $body,
+            Dialog::CliReference($d) => $body,
+            Dialog::LogViewer($d) => $body,
+            Dialog::Digest($d) => $body,
+            Dialog::Trim($d) => $body,
+            Dialog::Permissions($d) => $body,
+            Dialog::Settings($d) => $body,
+        }
+    };
+}
+
+impl Dialog {
+    fn window_settings(&self) -> window::Settings {
+        match self {
+            Dialog::Install(_) => InstallDialog::window_settings(),
+            Dialog::Remove(_) => RemoveDialog::window_settings(),
+            Dialog::Upgrade(_) => UpgradeDialog::window_settings(),
+            Dialog::Batch(_) => BatchDialog::window_settings(),
+            Dialog::Conflict(_) => ConflictDialog::window_settings(),
+            Dialog::PikmanInstall(_) => PikmanInstallDialog::window_settings(),
+            Dialog::Environment(_) => EnvironmentDialog::window_settings(),
+            Dialog::CliReference(_) => CliReferenceDialog::window_settings(),
+            Dialog::LogViewer(_) => LogViewerDialog::window_settings(),
+            Dialog::Digest(_) => DigestDialog::window_settings(),
+            Dialog::Trim(_) => TrimDialog::window_settings(),
+            Dialog::Permissions(_) => PermissionsDialog::window_settings(),
+            Dialog::Settings(_) => SettingsDialog::window_settings(),
+        }
+    }
+
+    // Shares the main window's backend, system and look instead of detecting them again
+    fn attach(&mut self, window: window::Id, backend: &Arc<dyn PackageBackend>, system: SystemKind, theme: AppTheme, radius: f32) {
+        match self {
+            Dialog::Install(d) => {
+                d.backend = backend.clone();
+                d.system = system;
+            }
+            Dialog::Remove(d) => {
+                d.backend = backend.clone();
+                d.system = system;
+            }
+            Dialog::Upgrade(d) => {
+                d.backend = backend.clone();
+                d.system = system;
+            }
+            Dialog::Batch(d) => {
+                d.backend = backend.clone();
+                d.system = system;
+            }
+            Dialog::Conflict(d) => {
+                d.backend = backend.clone();
+            }
+            Dialog::Trim(d) => {
+                d.backend = backend.clone();
+            }
+            Dialog::PikmanInstall(_)
+            | Dialog::Environment(_)
+            | Dialog::CliReference(_)
+            | Dialog::LogViewer(_)
+            | Dialog::Digest(_)
+            | Dialog::Permissions(_)
+            | Dialog::Settings(_) => {}
+        }
+        dispatch!(self, d => {
+            d.window = window;
+            d.theme = theme;
+            d.border_radius = radius;
+        })
+    }
+
+    fn start(self) -> (Self, Command<DialogMessage>) {
+        match self {
+            Dialog::Install(d) => {
+                let (d, command) = <InstallDialog as Application>::new(*d);
+                (Dialog::Install(Box::new(d)), command.map(DialogMessage::Install))
+            }
+            Dialog::Remove(d) => {
+                let (d, command) = <RemoveDialog as Application>::new(d);
+                (Dialog::Remove(d), command.map(DialogMessage::Remove))
+            }
+            Dialog::Upgrade(d) => {
+                let (d, command) = <UpgradeDialog as Application>::new(d);
+                (Dialog::Upgrade(d), command.map(DialogMessage::Upgrade))
+            }
+            Dialog::Batch(d) => {
+                let (d, command) = <BatchDialog as Application>::new(d);
+                (Dialog::Batch(d), command.map(DialogMessage::Batch))
+            }
+            Dialog::Conflict(d) => {
+                let (d, command) = <ConflictDialog as Application>::new(d);
+                (Dialog::Conflict(d), command.map(DialogMessage::Conflict))
+            }
+            Dialog::PikmanInstall(d) => {
+                let (d, command) = <PikmanInstallDialog as Application>::new(d);
+                (Dialog::PikmanInstall(d), command.map(DialogMessage::PikmanInstall))
+            }
+            Dialog::Environment(d) => {
+                let (d, command) = <EnvironmentDialog as Application>::new(d);
+                (Dialog::Environment(d), command.map(DialogMessage::Environment))
+            }
+            Dialog::CliReference(d) => {
+                let (d, command) = <CliReferenceDialog as Application>::new(d);
+                (Dialog::CliReference(d), command.map(DialogMessage::CliReference))
+            }
+            Dialog::LogViewer(d) => {
+                let (d, command) = <LogViewerDialog as Application>::new(d);
+                (Dialog::LogViewer(d), command.map(DialogMessage::LogViewer))
+            }
+            Dialog::Digest(d) => {
+                let (d, command) = <DigestDialog as Application>::new(d);
+                (Dialog::Digest(d), command.map(DialogMessage::Digest))
+            }
+            Dialog::Trim(d) => {
+                let (d, command) = <TrimDialog as Application>::new(d);
+                (Dialog::Trim(d), command.map(DialogMessage::Trim))
+            }
+            Dialog::Permissions(d) => {
+                let (d, command) = <PermissionsDialog as Application>::new(d);
+                (Dialog::Permissions(d), command.map(DialogMessage::Permissions))
+            }
+            Dialog::Settings(d) => {
+                let (d, command) = <SettingsDialog as Application>::new(*d);
+                (Dialog::Settings(Box::new(d)), command.map(DialogMessage::Settings))
+            }
+        }
+    }
+
+    fn update(&mut self, message: DialogMessage) -> Command<DialogMessage> {
+        match (self, message) {
+            (Dialog::Install(d), DialogMessage::Install(m)) => d.update(m).map(DialogMessage::Install),
+            (Dialog::Remove(d), DialogMessage::Remove(m)) => d.update(m).map(DialogMessage::Remove),
+            (Dialog::Upgrade(d), DialogMessage::Upgrade(m)) => d.update(m).map(DialogMessage::Upgrade),
+            (Dialog::Batch(d), DialogMessage::Batch(m)) => d.update(m).map(DialogMessage::Batch),
+            (Dialog::Conflict(d), DialogMessage::Conflict(m)) => d.update(m).map(DialogMessage::Conflict),
+            (Dialog::PikmanInstall(d), DialogMessage::PikmanInstall(m)) => d.update(m).map(DialogMessage::PikmanInstall),
+            (Dialog::Environment(d), DialogMessage::Environment(m)) => d.update(m).map(DialogMessage::Environment),
+            (Dialog::CliReference(d), DialogMessage::CliReference(m)) => d.update(m).map(DialogMessage::CliReference),
+            (Dialog::LogViewer(d), DialogMessage::LogViewer(m)) => d.update(m).map(DialogMessage::LogViewer),
+            (Dialog::Digest(d), DialogMessage::Digest(m)) => d.update(m).map(DialogMessage::Digest),
+            (Dialog::Trim(d), DialogMessage::Trim(m)) => d.update(m).map(DialogMessage::Trim),
+            (Dialog::Permissions(d), DialogMessage::Permissions(m)) => d.update(m).map(DialogMessage::Permissions),
+            (Dialog::Settings(d), DialogMessage::Settings(m)) => d.update(m).map(DialogMessage::Settings),
+            (_, message) => {
+                tracing::debug!("Dialog: dropping message for another dialog kind: {:?}", message);
+                Command::none()
+            }
+        }
+    }
+
+    fn view(&self) -> Element<'_, DialogMessage> {
+        match self {
+            Dialog::Install(d) => d.view().map(DialogMessage::Install),
+            Dialog::Remove(d) => d.view().map(DialogMessage::Remove),
+            Dialog::Upgrade(d) => d.view().map(DialogMessage::Upgrade),
+            Dialog::Batch(d) => d.view().map(DialogMessage::Batch),
+            Dialog::Conflict(d) => d.view().map(DialogMessage::Conflict),
+            Dialog::PikmanInstall(d) => d.view().map(DialogMessage::PikmanInstall),
+            Dialog::Environment(d) => d.view().map(DialogMessage::Environment),
+            Dialog::CliReference(d) => d.view().map(DialogMessage::CliReference),
+            Dialog::LogViewer(d) => d.view().map(DialogMessage::LogViewer),
+            Dialog::Digest(d) => d.view().map(DialogMessage::Digest),
+            Dialog::Trim(d) => d.view().map(DialogMessage::Trim),
+            Dialog::Permissions(d) => d.view().map(DialogMessage::Permissions),
+            Dialog::Settings(d) => d.view().map(DialogMessage::Settings),
+        }
+    }
+
+    fn subscription(&self) -> Subscription<DialogMessage> {
+        match self {
+            Dialog::Install(d) => d.subscription().map(DialogMessage::Install),
+            Dialog::Remove(d) => d.subscription().map(DialogMessage::Remove),
+            Dialog::Upgrade(d) => d.subscription().map(DialogMessage::Upgrade),
+            Dialog::Batch(d) => d.subscription().map(DialogMessage::Batch),
+            Dialog::Conflict(d) => d.subscription().map(DialogMessage::Conflict),
+            Dialog::PikmanInstall(d) => d.subscription().map(DialogMessage::PikmanInstall),
+            Dialog::Environment(d) => d.subscription().map(DialogMessage::Environment),
+            Dialog::CliReference(d) => d.subscription().map(DialogMessage::CliReference),
+            Dialog::LogViewer(d) => d.subscription().map(DialogMessage::LogViewer),
+            Dialog::Digest(d) => d.subscription().map(DialogMessage::Digest),
+            Dialog::Trim(d) => d.subscription().map(DialogMessage::Trim),
+            Dialog::Permissions(d) => d.subscription().map(DialogMessage::Permissions),
+            Dialog::Settings(d) => d.subscription().map(DialogMessage::Settings),
+        }
+    }
+
+    fn title(&self) -> String {
+        dispatch!(self, d => d.title())
+    }
+}
+
+/// The dialogs open next to the main window, by window id
+#[derive(Debug, Default)]
+pub struct Windows {
+    dialogs: HashMap<window::Id, Dialog>,
+}
+
+impl Windows {
+    pub fn open(
+        &mut self,
+        mut dialog: Dialog,
+        backend: &Arc<dyn PackageBackend>,
+        system: SystemKind,
+        theme: AppTheme,
+        radius: f32,
+    ) -> (window::Id, Command<Message>) {
+        let (id, spawn) = window::spawn(dialog.window_settings());
+        dialog.attach(id, backend, system, theme, radius);
+        let (dialog, start) = dialog.start();
+        tracing::debug!("Windows: opened {:?} as {}", id, dialog.title());
+        self.dialogs.insert(id, dialog);
+        (id, Command::batch([spawn, start.map(move |message| Message::Dialog(id, message))]))
+    }
+
+    pub fn update(&mut self, id: window::Id, message: DialogMessage) -> (Command<Message>, Option<Notice>) {
+        let Some(dialog) = self.dialogs.get_mut(&id) else {
+            // Output still arriving for a window that was just closed
+            return (Command::none(), None);
+        };
+        let conflict = matches!(message, DialogMessage::Remove(remove_dialog::Message::ConflictDetected(_)));
+        let saved = matches!(message, DialogMessage::Settings(settings_dialog::Message::Saved(Ok(()))));
+        let defaults = match &message {
+            DialogMessage::Install(install_dialog::Message::DefaultsSaved(Ok(config)))
+            | DialogMessage::PikmanInstall(pikman_install_dialog::Message::DefaultsSaved(Ok(config))) => Some(config.clone()),
+            _ => None,
+        };
+        let command = dialog.update(message).map(move |message| Message::Dialog(id, message));
+
+        let notice = match dialog {
+            Dialog::Remove(d) if conflict => Some(Notice::Conflict(ConflictDialog::new(
+                d.package_names.clone(),
+                d.conflict_message.clone().unwrap_or_default(),
+                d.terminal_output.clone(),
+            ))),
+            Dialog::Settings(d) if saved => d.config.clone().map(|config| Notice::SettingsSaved(Box::new(config))),
+            Dialog::Trim(d) => d.confirmed.take().map(Notice::Trim),
+            _ => defaults.map(Notice::SettingsSaved),
+        };
+        if matches!(notice, Some(Notice::Conflict(_) | Notice::Trim(_))) {
+            return (Command::batch([command, window::close(id)]), notice);
+        }
+        (command, notice)
+    }
+
+    pub fn view(&self, id: window::Id) -> Option<Element<'_, Message>> {
+        let dialog = self.dialogs.get(&id)?;
+        Some(dialog.view().map(move |message| Message::Dialog(id, message)))
+    }
+
+    pub fn title(&self, id: window::Id) -> Option<String> {
+        self.dialogs.get(&id).map(Dialog::title)
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        // `with` keeps each window's runs apart and carries its id, since `map`
+        // only takes non-capturing closures
+        Subscription::batch(self.dialogs.iter().map(|(id, dialog)| {
+            dialog
+                .subscription()
+                .with(*id)
+                .map(|(id, message)| Message::Dialog(id, message))
+        }))
+    }
+
+    pub fn closed(&mut self, id: window::Id) {
+        if self.dialogs.remove(&id).is_some() {
+            tracing::debug!("Windows: {:?} closed", id);
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn get(&self, id: window::Id) -> Option<&Dialog> {
+        self.dialogs.get(&id)
+    }
+
+    #[allow(dead_code)]
+    pub fn ids(&self) -> Vec<window::Id> {
+        self.dialogs.keys().copied().collect()
+    }
+}
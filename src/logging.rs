@@ -0,0 +1,144 @@
+use std::fs;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::{fmt, prelude::*, EnvFilter};
+
+// Diagnostics go through `tracing`. Everything BirdNest logs from debug up is
+// written to a daily file under ~/.local/share/birdnest/logs, of which the
+// last week is kept; the terminal only shows warnings and errors unless
+// --verbose or --quiet say otherwise. BIRDNEST_LOG takes an env-filter
+// directive (e.g. "debug" or "birdnest_core::cache=trace") for the terminal
+// and overrides both flags.
+
+pub const FILTER_ENV: &str = "BIRDNEST_LOG";
+const KEEP_FILES: usize = 7;
+const FILE_PREFIX: &str = "birdnest";
+const FILE_SUFFIX: &str = "log";
+// Other crates (iced, wgpu) only make it into the file when something goes wrong
+const FILE_FILTER: &str = "warn,birdnest=debug,birdnest_core=debug";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verbosity {
+    Quiet,
+    Normal,
+    Verbose,
+}
+
+impl Verbosity {
+    pub fn from_flags(verbose: bool, quiet: bool) -> Self {
+        match (verbose, quiet) {
+            (true, _) => Verbosity::Verbose,
+            (false, true) => Verbosity::Quiet,
+            (false, false) => Verbosity::Normal,
+        }
+    }
+
+    fn terminal_filter(self) -> &'static str {
+        match self {
+            Verbosity::Quiet => "error",
+            Verbosity::Normal => "error,birdnest=warn,birdnest_core=warn",
+            Verbosity::Verbose => "warn,birdnest=debug,birdnest_core=debug",
+        }
+    }
+}
+
+pub fn log_dir() -> Option<PathBuf> {
+    let data = match std::env::var("XDG_DATA_HOME") {
+        Ok(data) if !data.is_empty() => PathBuf::from(data),
+        _ => PathBuf::from(std::env::var("HOME").ok()?).join(".local").join("share"),
+    };
+    Some(data.join("birdnest").join("logs"))
+}
+
+// Only stderr when the log directory can't be created (no HOME, read-only home)
+pub fn init(verbosity: Verbosity) {
+    let terminal_filter = EnvFilter::try_from_env(FILTER_ENV).unwrap_or_else(|_| EnvFilter::new(verbosity.terminal_filter()));
+    let terminal = fmt::layer()
+        .with_writer(std::io::stderr)
+        .with_ansi(std::io::stderr().is_terminal())
+        .with_target(false)
+        .without_time()
+        .with_filter(terminal_filter);
+    let file = log_dir()
+        .and_then(|dir| {
+            fs::create_dir_all(&dir).ok()?;
+            RollingFileAppender::builder()
+                .rotation(Rotation::DAILY)
+                .filename_prefix(FILE_PREFIX)
+                .filename_suffix(FILE_SUFFIX)
+                .max_log_files(KEEP_FILES)
+                .build(dir)
+                .ok()
+        })
+        .map(|appender| fmt::layer().with_writer(appender).with_ansi(false).with_filter(EnvFilter::new(FILE_FILTER)));
+    let _ = tracing_subscriber::registry().with(terminal).with(file).try_init();
+}
+
+/// The log files in `dir`, newest first
+pub fn log_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(FILE_PREFIX) && name.ends_with(FILE_SUFFIX))
+        })
+        .collect();
+    // Dated names sort by day
+    files.sort();
+    files.reverse();
+    files
+}
+
+/// The last `count` lines of `content` at `min_level` or above ("WARN" keeps
+/// warnings and errors); lines without a level, like continuations, stay
+/// with the line they belong to
+pub fn tail(content: &str, min_level: Level, count: usize) -> Vec<&str> {
+    let mut keep = true;
+    let lines: Vec<&str> = content
+        .lines()
+        .filter(|line| {
+            if let Some(level) = Level::of(line) {
+                keep = level >= min_level;
+            }
+            keep
+        })
+        .collect();
+    lines[lines.len().saturating_sub(count)..].to_vec()
+}
+
+/// Levels as the log file spells them, least severe first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl Level {
+    pub const ALL: [Level; 5] = [Level::Trace, Level::Debug, Level::Info, Level::Warn, Level::Error];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Level::Trace => "TRACE",
+            Level::Debug => "DEBUG",
+            Level::Info => "INFO",
+            Level::Warn => "WARN",
+            Level::Error => "ERROR",
+        }
+    }
+
+    // "2026-10-16T09:12:03.123456Z DEBUG birdnest_core::cache: ..."
+    fn of(line: &str) -> Option<Level> {
+        let mut words = line.split_whitespace();
+        let _timestamp = words.next()?;
+        let word = words.next()?;
+        Level::ALL.into_iter().find(|level| level.label() == word)
+    }
+}
@@ -2,47 +2,74 @@ use anyhow::Result;
 use clap::Parser;
 
 mod cli;
-mod config;
-mod package_manager;
-mod flatpak;
-mod utils;
+mod completions;
+mod logging;
+mod manpage;
+mod profiling;
+mod renderer;
 mod gui;
+mod tui;
 
 use cli::Cli;
 
 fn main() -> Result<()> {
-    eprintln!("[DEBUG] BirdNest starting...");
-    eprintln!("[DEBUG] Arguments: {:?}", std::env::args().collect::<Vec<String>>());
-    
-    let args: Vec<String> = std::env::args().collect();
+    let cli = Cli::parse();
+    logging::init(logging::Verbosity::from_flags(cli.verbose, cli.quiet));
+    tracing::debug!("BirdNest starting...");
+    tracing::debug!("Arguments: {:?}", std::env::args().collect::<Vec<String>>());
+    if cli.profile_startup {
+        profiling::enable();
+    }
+    if let Some(method) = &cli.elevation {
+        let method = birdnest_core::elevation::ElevationMethod::parse(method)?;
+        birdnest_core::elevation::set_session_preference(method);
+    }
+    birdnest_core::output::init();
+    birdnest_core::progress::set_plain(cli.plain);
+    let config = birdnest_core::config::Config::load().unwrap_or_default();
+    birdnest_core::utils::set_auto_confirm(config.auto_confirm);
+    birdnest_core::cache::set_ttl(config.cache_ttl());
+    birdnest_core::proxy::apply(&config.proxy);
+    renderer::init(cli.software_rendering, config.software_rendering);
+    profiling::mark("arguments parsed");
     
-    // If no arguments provided, launch GUI
-    if args.len() == 1 {
-        eprintln!("[DEBUG] No CLI arguments, launching GUI...");
+    // A .flatpakref or bundle from the file manager opens straight in the install dialog
+    if let (None, Some(file)) = (&cli.command, &cli.file) {
+        let file = birdnest_core::flatpak::FlatpakFile::open(file)?;
+        tracing::debug!("Opening {} in the install dialog", file.path().display());
+        gui::install_dialog::InstallDialog::run_for_file(file)?;
+        return Ok(());
+    }
+
+    // If no command provided, launch GUI
+    if cli.command.is_none() {
+        tracing::debug!("No CLI command, launching GUI...");
         match gui::run() {
             Ok(_) => {
-                eprintln!("[DEBUG] GUI exited successfully");
-        Ok(())
+                tracing::debug!("GUI exited successfully");
+                profiling::report();
+                Ok(())
             }
             Err(e) => {
-                eprintln!("[ERROR] GUI failed: {:?}", e);
+                tracing::error!("GUI failed: {:?}", e);
                 Err(e.into())
             }
         }
     } else {
-        eprintln!("[DEBUG] CLI arguments provided, using CLI mode...");
-        let cli = Cli::parse();
-        eprintln!("[DEBUG] CLI parsed successfully, running command...");
-        match cli.run() {
+        tracing::debug!("CLI arguments provided, using CLI mode...");
+        tracing::debug!("CLI parsed successfully, running command...");
+        let result = cli.run();
+        profiling::mark("command finished");
+        profiling::report();
+        match result {
             Ok(_) => {
-                eprintln!("[DEBUG] CLI command completed successfully");
+                tracing::debug!("CLI command completed successfully");
                 Ok(())
             }
             Err(e) => {
-                eprintln!("[ERROR] CLI command failed: {:?}", e);
+                tracing::error!("CLI command failed: {:?}", e);
                 Err(e)
             }
         }
     }
 }
-
@@ -0,0 +1,100 @@
+use anyhow::Result;
+use clap::{Command, CommandFactory};
+use clap_mangen::Man;
+use std::path::{Path, PathBuf};
+
+use crate::cli::Cli;
+
+// Man pages and the GUI's CLI reference are both generated from the clap
+// definitions in cli.rs, so neither can drift from the real command line
+
+/// One command's entry in the CLI reference
+#[derive(Debug, Clone)]
+pub struct CommandHelp {
+    /// Full invocation, e.g. "birdnest flatpak install"
+    pub path: String,
+    pub about: String,
+    /// clap's long help text for the command
+    pub help: String,
+}
+
+fn built_command() -> Command<'static> {
+    let mut cmd = Cli::command();
+    cmd.build();
+    cmd
+}
+
+/// Dialog launchers and the like, which the GUI and systemd run for themselves
+pub fn is_internal(cmd: &Command) -> bool {
+    cmd.get_about().unwrap_or_default().ends_with("(internal use)")
+}
+
+// Internal commands and clap's generated `help` aren't part of the user-facing surface
+fn is_documented(cmd: &Command) -> bool {
+    cmd.get_name() != "help" && !cmd.is_hide_set() && !is_internal(cmd)
+}
+
+/// Every documented command with its help text, top-level first
+pub fn reference() -> Vec<CommandHelp> {
+    let mut entries = Vec::new();
+    collect_reference(&built_command(), "birdnest", &mut entries);
+    entries
+}
+
+fn collect_reference(cmd: &Command, path: &str, entries: &mut Vec<CommandHelp>) {
+    let mut help = Vec::new();
+    let _ = cmd.clone().write_long_help(&mut help);
+    entries.push(CommandHelp {
+        path: path.to_string(),
+        about: cmd.get_about().unwrap_or_default().to_string(),
+        help: String::from_utf8_lossy(&help).trim_end().to_string(),
+    });
+    for sub in cmd.get_subcommands().filter(|sub| is_documented(sub)) {
+        collect_reference(sub, &format!("{} {}", path, sub.get_name()), entries);
+    }
+}
+
+/// The top-level `birdnest(1)` page
+pub fn main_page() -> Result<String> {
+    Ok(String::from_utf8(render(documented(Cli::command()))?)?)
+}
+
+/// Write birdnest.1 plus one page per subcommand (birdnest-install.1,
+/// birdnest-flatpak-install.1, ...) into `dir`
+pub fn write_all(dir: &Path) -> Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(dir)?;
+    let mut written = Vec::new();
+    write_pages(documented(Cli::command()), dir, &mut written)?;
+    Ok(written)
+}
+
+// Each page is named for its command's path, so its COMMANDS section points
+// at birdnest-flatpak-install(1) and so on
+fn write_pages(cmd: Command<'static>, dir: &Path, written: &mut Vec<PathBuf>) -> Result<()> {
+    let name = cmd.get_name().to_string();
+    let file = dir.join(format!("{}.1", name));
+    std::fs::write(&file, render(cmd.clone())?)?;
+    written.push(file);
+    for sub in cmd.get_subcommands().filter(|sub| !sub.is_hide_set()) {
+        write_pages(sub.clone().name(format!("{}-{}", name, sub.get_name())), dir, written)?;
+    }
+    Ok(())
+}
+
+fn render(cmd: Command<'static>) -> Result<Vec<u8>> {
+    let mut page = Vec::new();
+    Man::new(cmd).source(format!("birdnest {}", env!("CARGO_PKG_VERSION"))).manual("User Commands").render(&mut page)?;
+    Ok(page)
+}
+
+// The command line as the man pages show it: internal commands and clap's
+// generated `help` hidden, at every level
+fn documented(cmd: Command<'static>) -> Command<'static> {
+    let names: Vec<String> = cmd.get_subcommands().map(|sub| sub.get_name().to_string()).collect();
+    names.iter().fold(cmd.disable_help_subcommand(true), |cmd, name| {
+        cmd.mut_subcommand(name.as_str(), |sub| {
+            let hide = sub.is_hide_set() || is_internal(&sub);
+            documented(sub).hide(hide)
+        })
+    })
+}
@@ -0,0 +1,68 @@
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+// Startup timing breakdown, only collected when --profile-startup is passed
+struct StartupProfile {
+    start: Instant,
+    marks: Vec<(String, Duration)>,
+    reported: bool,
+}
+
+static PROFILE: OnceLock<Mutex<StartupProfile>> = OnceLock::new();
+
+pub fn enable() {
+    PROFILE.get_or_init(|| {
+        Mutex::new(StartupProfile {
+            start: Instant::now(),
+            marks: Vec::new(),
+            reported: false,
+        })
+    });
+}
+
+pub fn mark(label: &str) {
+    if let Some(profile) = PROFILE.get() {
+        if let Ok(mut profile) = profile.lock() {
+            let elapsed = profile.start.elapsed();
+            profile.marks.push((label.to_string(), elapsed));
+        }
+    }
+}
+
+// Record a mark only the first time it is seen (for hot paths like view())
+pub fn mark_once(label: &str) {
+    if let Some(profile) = PROFILE.get() {
+        if let Ok(mut profile) = profile.lock() {
+            if !profile.marks.iter().any(|(l, _)| l == label) {
+                let elapsed = profile.start.elapsed();
+                profile.marks.push((label.to_string(), elapsed));
+            }
+        }
+    }
+}
+
+// Print the breakdown to stderr; later calls are ignored so the report appears once
+pub fn report() {
+    let Some(profile) = PROFILE.get() else {
+        return;
+    };
+    let Ok(mut profile) = profile.lock() else {
+        return;
+    };
+    if profile.reported {
+        return;
+    }
+    profile.reported = true;
+
+    eprintln!("Startup profile:");
+    let mut previous = Duration::ZERO;
+    for (label, at) in &profile.marks {
+        eprintln!(
+            "  {:>9.1} ms  (+{:>7.1} ms)  {}",
+            at.as_secs_f64() * 1000.0,
+            at.saturating_sub(previous).as_secs_f64() * 1000.0,
+            label
+        );
+        previous = *at;
+    }
+}
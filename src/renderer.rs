@@ -0,0 +1,167 @@
+use std::path::Path;
+use std::sync::OnceLock;
+
+// Which of iced's renderers draws the GUI. iced tries wgpu first and only
+// falls back to the tiny-skia software renderer when no GPU adapter is found.
+// Some drivers do offer an adapter and then draw blank windows, so BirdNest
+// picks software rendering itself for those, and when asked to.
+
+/// Read by iced when it creates its compositor, and inherited by dialog processes
+pub const BACKEND_ENV: &str = "ICED_BACKEND";
+
+// Kernel DRM drivers wgpu is known to draw blank or garbled windows on:
+// nouveau, emulated VM adapters, and bare framebuffers with no GPU behind them
+const SOFTWARE_DRIVERS: &[&str] = &[
+    "nouveau",
+    "qxl",
+    "cirrus",
+    "cirrus-qemu",
+    "bochs-drm",
+    "bochs",
+    "vboxvideo",
+    "simpledrm",
+    "simple-framebuffer",
+    "efifb",
+    "vesafb",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Renderer {
+    Gpu,
+    Software,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reason {
+    /// `--software-rendering`
+    Flag,
+    /// `software_rendering` in the config
+    Config,
+    /// `ICED_BACKEND` was already set
+    Environment,
+    /// Only drivers from SOFTWARE_DRIVERS are loaded
+    Driver(String),
+    /// No `/dev/dri/renderD*` node, so no GPU to render with
+    NoGpu,
+    Default,
+}
+
+/// The renderer picked at startup and why
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Choice {
+    pub renderer: Renderer,
+    pub reason: Reason,
+}
+
+impl Choice {
+    /// "Software (tiny-skia), because of the nouveau driver", for diagnostics
+    pub fn describe(&self) -> String {
+        let renderer = match self.renderer {
+            Renderer::Gpu => "GPU (wgpu)",
+            Renderer::Software => "Software (tiny-skia)",
+        };
+        match &self.reason {
+            Reason::Flag => format!("{}, forced with --software-rendering", renderer),
+            Reason::Config => format!("{}, set by software_rendering in the config", renderer),
+            Reason::Environment => format!("{}, set by {}", renderer, BACKEND_ENV),
+            Reason::Driver(driver) => format!("{}, because wgpu draws blank windows with the {} driver", renderer, driver),
+            Reason::NoGpu => format!("{}, no GPU render node found", renderer),
+            Reason::Default => format!("{}, falling back to software if no GPU adapter works", renderer),
+        }
+    }
+
+    /// "GPU rendering" or "Software rendering", for the status bar
+    pub fn label(&self) -> &'static str {
+        match self.renderer {
+            Renderer::Gpu => "GPU rendering",
+            Renderer::Software => "Software rendering",
+        }
+    }
+}
+
+/// The graphics hardware as the kernel sees it
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Gpu {
+    /// DRM driver of each card, e.g. "amdgpu", "i915", "nouveau"
+    pub drivers: Vec<String>,
+    pub render_node: bool,
+}
+
+pub fn probe() -> Gpu {
+    let mut drivers = Vec::new();
+    if let Ok(entries) = std::fs::read_dir("/sys/class/drm") {
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            // card0, not card0-HDMI-A-1
+            if !name.starts_with("card") || name.contains('-') {
+                continue;
+            }
+            if let Ok(driver) = std::fs::read_link(entry.path().join("device/driver")) {
+                if let Some(driver) = driver.file_name() {
+                    drivers.push(driver.to_string_lossy().to_string());
+                }
+            }
+        }
+    }
+    drivers.sort();
+    drivers.dedup();
+    let render_node = std::fs::read_dir("/dev/dri")
+        .map(|entries| entries.flatten().any(|entry| entry.file_name().to_string_lossy().starts_with("renderD")))
+        .unwrap_or(false);
+    Gpu { drivers, render_node }
+}
+
+/// Pick the renderer: an explicit request wins, then `ICED_BACKEND`, then
+/// what the drivers are known to manage
+pub fn choose(flag: bool, config: bool, env: Option<&str>, gpu: &Gpu) -> Choice {
+    let software = |reason| Choice { renderer: Renderer::Software, reason };
+    if flag {
+        return software(Reason::Flag);
+    }
+    if config {
+        return software(Reason::Config);
+    }
+    if let Some(backends) = env {
+        let renderer = match backends.split(',').next().map(str::trim) {
+            Some("tiny-skia") => Renderer::Software,
+            _ => Renderer::Gpu,
+        };
+        return Choice { renderer, reason: Reason::Environment };
+    }
+    if !gpu.render_node {
+        return software(Reason::NoGpu);
+    }
+    // A hybrid laptop with one good driver renders on that one
+    if !gpu.drivers.is_empty() && gpu.drivers.iter().all(|driver| SOFTWARE_DRIVERS.contains(&driver.as_str())) {
+        return software(Reason::Driver(gpu.drivers.join(", ")));
+    }
+    Choice { renderer: Renderer::Gpu, reason: Reason::Default }
+}
+
+static ACTIVE: OnceLock<Choice> = OnceLock::new();
+
+/// Decide once at startup and tell iced through `ICED_BACKEND`
+pub fn init(flag: bool, config: bool) -> &'static Choice {
+    ACTIVE.get_or_init(|| {
+        let env = std::env::var(BACKEND_ENV).ok().filter(|value| !value.trim().is_empty());
+        let choice = choose(flag, config, env.as_deref(), &probe());
+        if choice.renderer == Renderer::Software && choice.reason != Reason::Environment {
+            std::env::set_var(BACKEND_ENV, "tiny-skia");
+        }
+        tracing::debug!("Renderer: {}", choice.describe());
+        choice
+    })
+}
+
+/// The renderer picked by [`init`], if it ran
+pub fn active() -> Option<&'static Choice> {
+    ACTIVE.get()
+}
+
+// Only for the hint in `birdnest status`: whether Vulkan drivers are
+// installed, since wgpu falls back to OpenGL without them
+pub fn vulkan_installed() -> bool {
+    ["/usr/share/vulkan/icd.d", "/etc/vulkan/icd.d"]
+        .iter()
+        .any(|dir| std::fs::read_dir(Path::new(dir)).is_ok_and(|mut entries| entries.next().is_some()))
+}
@@ -0,0 +1,484 @@
+use anyhow::Result;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::DefaultTerminal;
+use std::collections::{BTreeSet, HashSet, VecDeque};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, Write};
+use std::os::fd::AsRawFd;
+use std::path::PathBuf;
+
+use birdnest_core::flatpak::FlatpakManager;
+use birdnest_core::hooks::{self, HookEvent};
+use birdnest_core::output::PackageRow;
+use birdnest_core::package_manager::{host_backend, PackageBackend};
+use birdnest_core::utils::{self, CommandBuilder};
+use birdnest_core::cache;
+
+mod view;
+
+#[cfg(test)]
+mod tests;
+
+// `birdnest tui`: the GUI's Search, Installed and Flatpak tabs for terminals
+// without a display. Transactions leave the screen and run in the terminal
+// itself, so apt's output and password prompts work as they do in the CLI
+
+const PAGE: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tab {
+    Search,
+    Installed,
+    Flatpak,
+}
+
+pub const TABS: [(&str, Tab); 3] = [("Search", Tab::Search), ("Installed", Tab::Installed), ("Flatpak", Tab::Flatpak)];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Install,
+    Remove,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transaction {
+    pub action: Action,
+    pub packages: Vec<String>,
+    pub flatpak: bool,
+}
+
+impl Transaction {
+    /// "Install 2 packages (htop, vim)?"
+    pub fn question(&self) -> String {
+        let verb = match self.action {
+            Action::Install => "Install",
+            Action::Remove => "Remove",
+        };
+        let kind = if self.flatpak { "Flatpak app" } else { "package" };
+        let plural = if self.packages.len() == 1 { "" } else { "s" };
+        format!("{} {} {}{} ({})? [y/n]", verb, self.packages.len(), kind, plural, self.packages.join(", "))
+    }
+}
+
+/// What the run loop does for a key press
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Effect {
+    Quit,
+    /// Fill a tab's list: search for its query, or list what is installed
+    Load(Tab),
+    /// Leave the screen and run a transaction in the terminal
+    Run(Transaction),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Row {
+    pub name: String,
+    pub version: String,
+    pub detail: String,
+    pub installed: bool,
+}
+
+impl From<PackageRow> for Row {
+    fn from(row: PackageRow) -> Self {
+        Self { name: row.name, version: row.version, detail: row.detail, installed: row.installed }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct TabState {
+    pub rows: Vec<Row>,
+    pub loaded: bool,
+    pub cursor: usize,
+    pub selected: BTreeSet<String>,
+    // Searched for on Search and Flatpak, a filter on Installed
+    pub query: String,
+}
+
+#[derive(Debug)]
+pub struct App {
+    pub tab: Tab,
+    pub tabs: [TabState; 3],
+    /// Typing goes into the current tab's query
+    pub editing: bool,
+    pub confirm: Option<Transaction>,
+    pub status: String,
+    // Marks search results that are already installed
+    installed: HashSet<String>,
+    installed_flatpaks: HashSet<String>,
+}
+
+impl Default for App {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl App {
+    pub fn new() -> Self {
+        Self {
+            tab: Tab::Search,
+            tabs: Default::default(),
+            editing: true,
+            confirm: None,
+            status: "Type to search, Enter to run it; Tab switches tabs, ? shows keys".to_string(),
+            installed: HashSet::new(),
+            installed_flatpaks: HashSet::new(),
+        }
+    }
+
+    fn index(tab: Tab) -> usize {
+        TABS.iter().position(|(_, t)| *t == tab).unwrap_or(0)
+    }
+
+    pub fn state(&self, tab: Tab) -> &TabState {
+        &self.tabs[Self::index(tab)]
+    }
+
+    fn state_mut(&mut self, tab: Tab) -> &mut TabState {
+        &mut self.tabs[Self::index(tab)]
+    }
+
+    fn current(&self) -> &TabState {
+        self.state(self.tab)
+    }
+
+    /// The rows the current tab shows; Installed filters by its query
+    pub fn visible(&self) -> Vec<&Row> {
+        let state = self.current();
+        let filter = state.query.to_lowercase();
+        state
+            .rows
+            .iter()
+            .filter(|row| self.tab != Tab::Installed || filter.is_empty() || row.name.to_lowercase().contains(&filter))
+            .collect()
+    }
+
+    /// Replace a tab's rows after a load, marking what is installed
+    pub fn set_rows(&mut self, tab: Tab, rows: Vec<Row>) {
+        match tab {
+            Tab::Installed => self.installed = rows.iter().map(|row| row.name.clone()).collect(),
+            Tab::Flatpak if self.state(tab).query.is_empty() => {
+                self.installed_flatpaks = rows.iter().map(|row| row.name.clone()).collect()
+            }
+            _ => {}
+        }
+        self.status = format!("{} result(s)", rows.len());
+        let state = self.state_mut(tab);
+        state.rows = rows;
+        state.loaded = true;
+        state.cursor = 0;
+        self.mark_installed();
+    }
+
+    // Search results show what is installed by now
+    fn mark_installed(&mut self) {
+        let [search, _, flatpak] = &mut self.tabs;
+        for row in &mut search.rows {
+            row.installed = self.installed.contains(&row.name);
+        }
+        if !flatpak.query.is_empty() {
+            for row in &mut flatpak.rows {
+                row.installed = self.installed_flatpaks.contains(&row.name);
+            }
+        }
+    }
+
+    pub fn set_error(&mut self, tab: Tab, error: String) {
+        self.state_mut(tab).loaded = true;
+        self.status = format!("Error: {}", error);
+    }
+
+    fn switch(&mut self, tab: Tab) -> Option<Effect> {
+        self.tab = tab;
+        self.editing = false;
+        // Search has nothing to show before a query; the others list what is installed
+        (tab != Tab::Search && !self.current().loaded).then_some(Effect::Load(tab))
+    }
+
+    fn step_tab(&mut self, forward: bool) -> Option<Effect> {
+        let index = Self::index(self.tab);
+        let next = if forward { (index + 1) % TABS.len() } else { (index + TABS.len() - 1) % TABS.len() };
+        self.switch(TABS[next].1)
+    }
+
+    fn move_cursor(&mut self, to: impl FnOnce(usize, usize) -> usize) {
+        let len = self.visible().len();
+        let state = self.state_mut(self.tab);
+        state.cursor = if len == 0 { 0 } else { to(state.cursor, len - 1).min(len - 1) };
+    }
+
+    fn toggle_selection(&mut self) {
+        let Some(name) = self.visible().get(self.current().cursor).map(|row| row.name.clone()) else {
+            return;
+        };
+        let selected = &mut self.state_mut(self.tab).selected;
+        if !selected.remove(&name) {
+            selected.insert(name);
+        }
+    }
+
+    // The selected rows, or the one under the cursor when none are, that
+    // `action` applies to
+    fn targets(&self, action: Action) -> Vec<String> {
+        let state = self.current();
+        let rows = self.visible();
+        let picked: Vec<&Row> = if state.selected.is_empty() {
+            rows.get(state.cursor).copied().into_iter().collect()
+        } else {
+            rows.into_iter().filter(|row| state.selected.contains(&row.name)).collect()
+        };
+        picked.into_iter().filter(|row| row.installed == (action == Action::Remove)).map(|row| row.name.clone()).collect()
+    }
+
+    fn ask(&mut self, action: Action) {
+        let packages = self.targets(action);
+        if packages.is_empty() {
+            self.status = match action {
+                Action::Install => "Nothing to install: pick packages that aren't installed yet".to_string(),
+                Action::Remove => "Nothing to remove: pick installed packages".to_string(),
+            };
+            return;
+        }
+        let transaction = Transaction { action, packages, flatpak: self.tab == Tab::Flatpak };
+        self.status = transaction.question();
+        self.confirm = Some(transaction);
+    }
+
+    /// A transaction ran; returns the tabs to reload
+    pub fn finished(&mut self, transaction: &Transaction, result: Result<(), String>) -> Vec<Tab> {
+        if let Err(e) = result {
+            self.status = format!("Error: {}", e);
+            return Vec::new();
+        }
+        self.status = format!("{} finished", transaction.packages.join(", "));
+        self.state_mut(self.tab).selected.clear();
+        let installed = if transaction.flatpak { &mut self.installed_flatpaks } else { &mut self.installed };
+        for package in &transaction.packages {
+            match transaction.action {
+                Action::Install => installed.insert(package.clone()),
+                Action::Remove => installed.remove(package),
+            };
+        }
+        self.mark_installed();
+        // The lists of what is installed, where they were loaded
+        if transaction.flatpak {
+            let flatpak = self.state(Tab::Flatpak);
+            if flatpak.query.is_empty() && flatpak.loaded { vec![Tab::Flatpak] } else { Vec::new() }
+        } else {
+            vec![Tab::Installed]
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<Effect> {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return Some(Effect::Quit);
+        }
+        if let Some(transaction) = self.confirm.take() {
+            return match key.code {
+                KeyCode::Char('y') | KeyCode::Enter => Some(Effect::Run(transaction)),
+                _ => {
+                    self.status = "Cancelled".to_string();
+                    None
+                }
+            };
+        }
+        if self.editing {
+            return self.edit_query(key);
+        }
+        match key.code {
+            KeyCode::Char('q') => return Some(Effect::Quit),
+            KeyCode::Tab | KeyCode::Right => return self.step_tab(true),
+            KeyCode::BackTab | KeyCode::Left => return self.step_tab(false),
+            KeyCode::Char(digit @ '1'..='3') => return self.switch(TABS[digit as usize - '1' as usize].1),
+            KeyCode::Char('/') => self.editing = true,
+            KeyCode::Up | KeyCode::Char('k') => self.move_cursor(|cursor, _| cursor.saturating_sub(1)),
+            KeyCode::Down | KeyCode::Char('j') => self.move_cursor(|cursor, _| cursor + 1),
+            KeyCode::PageUp => self.move_cursor(|cursor, _| cursor.saturating_sub(PAGE)),
+            KeyCode::PageDown => self.move_cursor(|cursor, _| cursor + PAGE),
+            KeyCode::Home | KeyCode::Char('g') => self.move_cursor(|_, _| 0),
+            KeyCode::End | KeyCode::Char('G') => self.move_cursor(|_, last| last),
+            KeyCode::Char(' ') => self.toggle_selection(),
+            KeyCode::Char('i') => self.ask(Action::Install),
+            KeyCode::Char('r') => self.ask(Action::Remove),
+            KeyCode::Char('R') | KeyCode::F(5) => return Some(Effect::Load(self.tab)),
+            KeyCode::Char('?') => {
+                self.status = "/ search  space select  i install  r remove  R reload  1-3 or Tab switch tabs  q quit".to_string()
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn edit_query(&mut self, key: KeyEvent) -> Option<Effect> {
+        let tab = self.tab;
+        match key.code {
+            KeyCode::Char(c) => self.state_mut(tab).query.push(c),
+            KeyCode::Backspace => {
+                self.state_mut(tab).query.pop();
+            }
+            KeyCode::Esc => self.editing = false,
+            KeyCode::Enter => {
+                self.editing = false;
+                // Installed filters as you type; the others search on Enter
+                if tab != Tab::Installed {
+                    return Some(Effect::Load(tab));
+                }
+            }
+            _ => {}
+        }
+        if tab == Tab::Installed {
+            self.state_mut(tab).cursor = 0;
+        }
+        None
+    }
+}
+
+pub fn run() -> Result<()> {
+    let backend = host_backend();
+    // The backends log to stderr, which would draw over the screen
+    let log = StderrLog::open(log_path())?;
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, &mut App::new(), backend.as_ref(), &log);
+    ratatui::restore();
+    drop(log);
+    result
+}
+
+fn log_path() -> PathBuf {
+    std::env::temp_dir().join("birdnest-tui.log")
+}
+
+fn event_loop(terminal: &mut DefaultTerminal, app: &mut App, backend: &dyn PackageBackend, log: &StderrLog) -> Result<()> {
+    let mut effects = VecDeque::from([Effect::Load(Tab::Installed)]);
+    loop {
+        while let Some(effect) = effects.pop_front() {
+            match effect {
+                Effect::Quit => return Ok(()),
+                Effect::Load(tab) => {
+                    app.status = "Loading...".to_string();
+                    terminal.draw(|frame| view::draw(frame, app))?;
+                    match load(backend, tab, &app.state(tab).query) {
+                        Ok(rows) => app.set_rows(tab, rows),
+                        Err(e) => app.set_error(tab, e.to_string()),
+                    }
+                }
+                Effect::Run(transaction) => {
+                    ratatui::restore();
+                    log.pause();
+                    let result = run_in_terminal(backend, &transaction);
+                    log.resume();
+                    *terminal = ratatui::init();
+                    effects.extend(app.finished(&transaction, result).into_iter().map(Effect::Load));
+                }
+            }
+        }
+        terminal.draw(|frame| view::draw(frame, app))?;
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press {
+                effects.extend(app.handle_key(key));
+            }
+        }
+    }
+}
+
+fn load(backend: &dyn PackageBackend, tab: Tab, query: &str) -> Result<Vec<Row>> {
+    Ok(match tab {
+        Tab::Search if query.is_empty() => Vec::new(),
+        Tab::Search => backend
+            .search(query)?
+            .into_iter()
+            .map(|p| Row { name: p.name, version: p.version, detail: p.description, installed: false })
+            .collect(),
+        Tab::Installed => backend
+            .list_installed()?
+            .into_iter()
+            .map(|p| Row { name: p.name, version: p.version, detail: String::new(), installed: true })
+            .collect(),
+        Tab::Flatpak if query.is_empty() => FlatpakManager::new()?.app_rows()?.into_iter().map(Row::from).collect(),
+        Tab::Flatpak => FlatpakManager::new()?.search_rows(query)?.into_iter().map(Row::from).collect(),
+    })
+}
+
+// Runs the transaction with the terminal's own output and password prompts,
+// then waits for Enter so the output can be read before the screen returns
+fn run_in_terminal(backend: &dyn PackageBackend, transaction: &Transaction) -> Result<(), String> {
+    println!();
+    let result = execute(backend, transaction).map_err(|e| e.to_string());
+    match &result {
+        Ok(()) => utils::print_success("Done"),
+        Err(e) => utils::print_error(e),
+    }
+    print!("Press Enter to return to BirdNest...");
+    let _ = std::io::stdout().flush();
+    let _ = std::io::stdin().lock().read_line(&mut String::new());
+    result
+}
+
+fn execute(backend: &dyn PackageBackend, transaction: &Transaction) -> Result<()> {
+    let packages = &transaction.packages;
+    if transaction.flatpak {
+        // FlatpakManager runs the hooks itself
+        let flatpak = FlatpakManager::new()?;
+        return match transaction.action {
+            Action::Install => flatpak.install(packages, true),
+            Action::Remove => flatpak.remove(packages, true),
+        };
+    }
+
+    let (commands, event) = match transaction.action {
+        Action::Install => (backend.install_commands(packages), HookEvent::Install),
+        Action::Remove => (backend.remove_commands(packages), HookEvent::Remove),
+    };
+    if commands.is_empty() {
+        match transaction.action {
+            Action::Install => backend.install(packages)?,
+            Action::Remove => backend.remove(packages)?,
+        }
+    }
+    for command in commands {
+        println!("$ {}", command.display());
+        let (mut std_cmd, _) = CommandBuilder::backend(&command).build()?;
+        let status = std_cmd.status()?;
+        if !status.success() {
+            anyhow::bail!("{} exited with code {}", command.display(), status.code().unwrap_or(-1));
+        }
+    }
+    cache::invalidate_installed();
+    hooks::run_and_print(event, packages, false);
+    Ok(())
+}
+
+// stderr pointed at a log file while the screen is up, and back at the
+// terminal while a transaction runs
+struct StderrLog {
+    file: File,
+    terminal: i32,
+}
+
+impl StderrLog {
+    fn open(path: PathBuf) -> Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let terminal = unsafe { libc::dup(libc::STDERR_FILENO) };
+        if terminal < 0 {
+            anyhow::bail!("Couldn't duplicate stderr: {}", std::io::Error::last_os_error());
+        }
+        let log = Self { file, terminal };
+        log.resume();
+        Ok(log)
+    }
+
+    fn pause(&self) {
+        unsafe { libc::dup2(self.terminal, libc::STDERR_FILENO) };
+    }
+
+    fn resume(&self) {
+        unsafe { libc::dup2(self.file.as_raw_fd(), libc::STDERR_FILENO) };
+    }
+}
+
+impl Drop for StderrLog {
+    fn drop(&mut self) {
+        self.pause();
+        unsafe { libc::close(self.terminal) };
+    }
+}
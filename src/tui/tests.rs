@@ -0,0 +1,89 @@
+// TUI key handling and drawing tests against birdnest-core's MockBackend
+use super::*;
+use birdnest_core::mock::MockBackend;
+use ratatui::backend::TestBackend;
+use ratatui::Terminal;
+
+fn press(app: &mut App, code: KeyCode) -> Option<Effect> {
+    app.handle_key(KeyEvent::from(code))
+}
+
+fn type_text(app: &mut App, text: &str) {
+    for c in text.chars() {
+        assert_eq!(press(app, KeyCode::Char(c)), None);
+    }
+}
+
+fn loaded(backend: &MockBackend, app: &mut App, tab: Tab) {
+    let rows = load(backend, tab, &app.state(tab).query).unwrap();
+    app.set_rows(tab, rows);
+}
+
+fn screen(app: &App) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(100, 20)).unwrap();
+    terminal.draw(|frame| view::draw(frame, app)).unwrap();
+    let buffer = terminal.backend().buffer();
+    buffer.content().chunks(buffer.area.width as usize).map(|line| line.iter().map(|cell| cell.symbol()).collect::<String>() + "\n").collect()
+}
+
+#[test]
+fn search_select_and_install_from_the_keyboard() {
+    let backend = MockBackend::with_default_fixture();
+    let mut app = App::new();
+    loaded(&backend, &mut app, Tab::Installed);
+
+    type_text(&mut app, "b");
+    assert_eq!(press(&mut app, KeyCode::Enter), Some(Effect::Load(Tab::Search)));
+    loaded(&backend, &mut app, Tab::Search);
+    let rows = app.visible();
+    assert!(!rows.is_empty());
+    // Installed packages are marked, and installing skips them
+    let installed: Vec<String> = rows.iter().filter(|row| row.installed).map(|row| row.name.clone()).collect();
+    let available: Vec<String> = rows.iter().filter(|row| !row.installed).map(|row| row.name.clone()).collect();
+    assert!(!available.is_empty());
+
+    for _ in 0..app.visible().len() {
+        press(&mut app, KeyCode::Char(' '));
+        press(&mut app, KeyCode::Down);
+    }
+    assert_eq!(press(&mut app, KeyCode::Char('i')), None);
+    let transaction = app.confirm.clone().unwrap();
+    assert_eq!(transaction.action, Action::Install);
+    assert!(!transaction.flatpak);
+    assert_eq!(transaction.packages.len(), available.len());
+    assert!(!transaction.packages.iter().any(|name| installed.contains(name)));
+    assert!(screen(&app).contains("? [y/n]"));
+
+    assert_eq!(press(&mut app, KeyCode::Char('y')), Some(Effect::Run(transaction.clone())));
+    assert_eq!(app.finished(&transaction, Ok(())), vec![Tab::Installed]);
+    assert!(app.visible().iter().all(|row| row.installed));
+    assert!(app.state(Tab::Search).selected.is_empty());
+}
+
+#[test]
+fn installed_tab_filters_as_you_type_and_removes_under_the_cursor() {
+    let backend = MockBackend::with_default_fixture();
+    let mut app = App::new();
+    press(&mut app, KeyCode::Esc);
+    assert_eq!(press(&mut app, KeyCode::Char('2')), Some(Effect::Load(Tab::Installed)));
+    loaded(&backend, &mut app, Tab::Installed);
+    let first = app.visible()[0].name.clone();
+    // Loaded once; switching back doesn't reload
+    assert_eq!(press(&mut app, KeyCode::Tab), Some(Effect::Load(Tab::Flatpak)));
+    assert_eq!(press(&mut app, KeyCode::BackTab), None);
+
+    press(&mut app, KeyCode::Char('/'));
+    type_text(&mut app, &first);
+    assert_eq!(press(&mut app, KeyCode::Enter), None);
+    assert!(app.visible().iter().all(|row| row.name.contains(&first)));
+    assert!(screen(&app).contains(&first));
+
+    press(&mut app, KeyCode::Char('r'));
+    assert_eq!(app.confirm.as_ref().map(|t| t.packages[0].clone()), Some(first));
+    // Anything but y cancels
+    assert_eq!(press(&mut app, KeyCode::Char('n')), None);
+    assert!(app.confirm.is_none());
+    assert_eq!(press(&mut app, KeyCode::Char('i')), None);
+    assert!(app.confirm.is_none());
+    assert_eq!(press(&mut app, KeyCode::Char('q')), Some(Effect::Quit));
+}
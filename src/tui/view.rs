@@ -0,0 +1,63 @@
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Cell, Paragraph, Row as TableRow, Table, TableState, Tabs};
+use ratatui::Frame;
+
+use super::{App, Tab, TABS};
+
+// The GUI's yellow, for the current tab and the cursor
+const ACCENT: Color = Color::Yellow;
+
+pub fn draw(frame: &mut Frame, app: &App) {
+    let [tabs_area, query_area, list_area, status_area] =
+        Layout::vertical([Constraint::Length(1), Constraint::Length(3), Constraint::Min(3), Constraint::Length(1)]).areas(frame.area());
+
+    let titles = TABS.iter().enumerate().map(|(i, (label, _))| format!(" {} {} ", i + 1, label));
+    let current = TABS.iter().position(|(_, tab)| *tab == app.tab).unwrap_or(0);
+    frame.render_widget(
+        Tabs::new(titles).select(current).highlight_style(Style::new().fg(Color::Black).bg(ACCENT).add_modifier(Modifier::BOLD)).divider("|"),
+        tabs_area,
+    );
+
+    let state = app.state(app.tab);
+    let label = match app.tab {
+        Tab::Installed => "Filter",
+        Tab::Search => "Search packages",
+        Tab::Flatpak => "Search Flatpak remotes (empty lists installed apps)",
+    };
+    let cursor = if app.editing { "_" } else { "" };
+    let border = if app.editing { Style::new().fg(ACCENT) } else { Style::new() };
+    frame.render_widget(
+        Paragraph::new(format!("{}{}", state.query, cursor)).block(Block::new().borders(Borders::ALL).border_style(border).title(label)),
+        query_area,
+    );
+
+    let rows = app.visible().into_iter().map(|row| {
+        let mark = if state.selected.contains(&row.name) { "[x]" } else { "[ ]" };
+        let installed = if row.installed && app.tab != Tab::Installed { "installed" } else { "" };
+        TableRow::new(vec![
+            Cell::from(mark),
+            Cell::from(row.name.as_str()),
+            Cell::from(row.version.as_str()),
+            Cell::from(installed).green(),
+            Cell::from(row.detail.as_str()),
+        ])
+    });
+    let table = Table::new(
+        rows,
+        [Constraint::Length(3), Constraint::Percentage(30), Constraint::Length(16), Constraint::Length(9), Constraint::Fill(1)],
+    )
+    .header(TableRow::new(vec!["", "Name", "Version", "", "Description"]).bold())
+    .block(Block::new().borders(Borders::ALL))
+    .row_highlight_style(Style::new().fg(Color::Black).bg(ACCENT));
+    let mut table_state = TableState::default().with_selected((!app.visible().is_empty()).then_some(state.cursor));
+    frame.render_stateful_widget(table, list_area, &mut table_state);
+
+    let status = match &app.confirm {
+        Some(transaction) => Line::from(transaction.question()).fg(ACCENT).bold(),
+        None if app.status.starts_with("Error") => Line::from(app.status.as_str()).red(),
+        None => Line::from(app.status.as_str()),
+    };
+    frame.render_widget(Paragraph::new(status), status_area);
+}
@@ -0,0 +1,147 @@
+// End-to-end tests that run the real CLI against apt inside a throwaway
+// podman container. They need podman and network access, so they are ignored
+// by default:
+//
+//   cargo test --test e2e_container -- --ignored
+//
+// BIRDNEST_E2E_IMAGE picks the image (default debian:trixie); it must have a
+// glibc at least as new as the host that built the binary.
+
+use std::process::{Command, Output};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+const DEFAULT_IMAGE: &str = "debian:trixie";
+// Small package with no recommends, quick to install and remove
+const TEST_PACKAGE: &str = "hello";
+
+static CONTAINER_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+struct Container {
+    name: String,
+}
+
+impl Container {
+    fn start() -> Self {
+        let image = std::env::var("BIRDNEST_E2E_IMAGE").unwrap_or_else(|_| DEFAULT_IMAGE.to_string());
+        let name = format!(
+            "birdnest-e2e-{}-{}",
+            std::process::id(),
+            CONTAINER_COUNTER.fetch_add(1, Ordering::SeqCst)
+        );
+        let binary = env!("CARGO_BIN_EXE_birdnest");
+
+        let output = Command::new("podman")
+            .args(["run", "-d", "--rm", "--name", &name])
+            .arg("-v")
+            .arg(format!("{}:/usr/local/bin/birdnest:ro,Z", binary))
+            .args([image.as_str(), "sleep", "infinity"])
+            .output()
+            .expect("podman is required for end-to-end tests");
+        assert!(
+            output.status.success(),
+            "failed to start {}: {}",
+            image,
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let container = Container { name };
+        // The GUI libraries are linked dynamically, so the binary needs them even for CLI use
+        container.sh("apt-get update -qq && apt-get install -y -qq libxkbcommon0 libwayland-client0 libfontconfig1 >/dev/null");
+        container
+    }
+
+    fn exec(&self, args: &[&str]) -> Output {
+        Command::new("podman")
+            .args(["exec", &self.name])
+            .args(args)
+            .output()
+            .expect("failed to run podman exec")
+    }
+
+    fn sh(&self, script: &str) -> String {
+        let output = self.exec(&["sh", "-c", script]);
+        assert!(
+            output.status.success(),
+            "`{}` failed: {}",
+            script,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).to_string()
+    }
+
+    // Run birdnest with a closed stdin so a stray confirmation prompt fails instead of hanging
+    fn birdnest(&self, args: &[&str]) -> String {
+        let mut full = vec!["birdnest"];
+        full.extend_from_slice(args);
+        let output = self.exec(&full);
+        assert!(
+            output.status.success(),
+            "birdnest {} failed:\nstdout: {}\nstderr: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).to_string()
+    }
+
+    fn is_installed(&self, package: &str) -> bool {
+        self.exec(&["dpkg", "-s", package]).status.success()
+    }
+}
+
+impl Drop for Container {
+    fn drop(&mut self) {
+        let _ = Command::new("podman").args(["rm", "-f", "-t", "0", &self.name]).output();
+    }
+}
+
+#[test]
+#[ignore]
+fn search_finds_packages_in_apt_sources() {
+    let container = Container::start();
+    container.birdnest(&["update"]);
+
+    let output = container.birdnest(&["search", TEST_PACKAGE]);
+    assert!(
+        output.lines().any(|line| line.split_whitespace().any(|field| field == TEST_PACKAGE)),
+        "search output did not list {}:\n{}",
+        TEST_PACKAGE,
+        output
+    );
+}
+
+#[test]
+#[ignore]
+fn install_and_remove_round_trip() {
+    let container = Container::start();
+    container.birdnest(&["update"]);
+    assert!(!container.is_installed(TEST_PACKAGE));
+
+    container.birdnest(&["install", "-y", TEST_PACKAGE]);
+    assert!(container.is_installed(TEST_PACKAGE));
+    assert!(container.sh(&format!("command -v {}", TEST_PACKAGE)).contains(TEST_PACKAGE));
+
+    let listed = container.birdnest(&["list"]);
+    assert!(listed.contains(TEST_PACKAGE), "list output is missing {}", TEST_PACKAGE);
+
+    container.birdnest(&["remove", "-y", TEST_PACKAGE]);
+    assert!(!container.is_installed(TEST_PACKAGE));
+}
+
+#[test]
+#[ignore]
+fn show_reports_package_metadata() {
+    let container = Container::start();
+    container.birdnest(&["update"]);
+
+    let output = container.birdnest(&["show", TEST_PACKAGE]);
+    assert!(output.contains(&format!("Package: {}", TEST_PACKAGE)), "unexpected show output:\n{}", output);
+}
+
+#[test]
+#[ignore]
+fn removing_a_missing_package_fails() {
+    let container = Container::start();
+    let output = container.exec(&["birdnest", "remove", "-y", "birdnest-no-such-package"]);
+    assert!(!output.status.success());
+}